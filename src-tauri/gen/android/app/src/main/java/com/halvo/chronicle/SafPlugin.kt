@@ -0,0 +1,162 @@
+package com.halvo.chronicle
+
+import android.app.Activity
+import android.content.Intent
+import android.net.Uri
+import android.util.Base64
+import androidx.activity.result.ActivityResult
+import androidx.documentfile.provider.DocumentFile
+import app.tauri.annotation.ActivityCallback
+import app.tauri.annotation.Command
+import app.tauri.annotation.InvokeArg
+import app.tauri.annotation.TauriPlugin
+import app.tauri.plugin.Invoke
+import app.tauri.plugin.JSArray
+import app.tauri.plugin.JSObject
+import app.tauri.plugin.Plugin
+
+// Storage Access Framework access for vaults in folders picked with the
+// system picker. Paths are vault-relative and use `/`; contents travel as
+// base64. Needs `androidx.documentfile:documentfile` in the app's
+// dependencies.
+
+@InvokeArg
+class TreeArgs {
+    lateinit var treeUri: String
+}
+
+@InvokeArg
+class FileArgs {
+    lateinit var treeUri: String
+    lateinit var path: String
+}
+
+@InvokeArg
+class WriteArgs {
+    lateinit var treeUri: String
+    lateinit var path: String
+    lateinit var contents: String
+}
+
+@InvokeArg
+class RenameArgs {
+    lateinit var treeUri: String
+    lateinit var from: String
+    lateinit var to: String
+}
+
+@TauriPlugin
+class SafPlugin(private val activity: Activity) : Plugin(activity) {
+    @Command
+    fun pickFolder(invoke: Invoke) {
+        val intent = Intent(Intent.ACTION_OPEN_DOCUMENT_TREE)
+        startActivityForResult(invoke, intent, "folderPicked")
+    }
+
+    @ActivityCallback
+    private fun folderPicked(invoke: Invoke, result: ActivityResult) {
+        val uri = result.data?.data
+        if (result.resultCode != Activity.RESULT_OK || uri == null) {
+            invoke.reject("No folder was picked")
+            return
+        }
+        // Keep access across restarts
+        activity.contentResolver.takePersistableUriPermission(
+            uri,
+            Intent.FLAG_GRANT_READ_URI_PERMISSION or Intent.FLAG_GRANT_WRITE_URI_PERMISSION
+        )
+        invoke.resolve(JSObject().put("uri", uri.toString()))
+    }
+
+    @Command
+    fun listFiles(invoke: Invoke) {
+        val args = invoke.parseArgs(TreeArgs::class.java)
+        val files = JSArray()
+        collectFiles(tree(args.treeUri), "", files)
+        invoke.resolve(JSObject().put("files", files))
+    }
+
+    @Command
+    fun readFile(invoke: Invoke) {
+        val args = invoke.parseArgs(FileArgs::class.java)
+        val file = find(tree(args.treeUri), args.path)
+        if (file == null || !file.isFile) {
+            invoke.reject("File not found: ${args.path}")
+            return
+        }
+        val bytes = activity.contentResolver.openInputStream(file.uri)!!.use { it.readBytes() }
+        invoke.resolve(JSObject().put("contents", Base64.encodeToString(bytes, Base64.NO_WRAP)))
+    }
+
+    @Command
+    fun writeFile(invoke: Invoke) {
+        val args = invoke.parseArgs(WriteArgs::class.java)
+        val file = findOrCreate(tree(args.treeUri), args.path)
+        val bytes = Base64.decode(args.contents, Base64.NO_WRAP)
+        // "wt" truncates, so a shorter file doesn't keep the old tail
+        activity.contentResolver.openOutputStream(file.uri, "wt")!!.use { it.write(bytes) }
+        invoke.resolve()
+    }
+
+    @Command
+    fun deleteFile(invoke: Invoke) {
+        val args = invoke.parseArgs(FileArgs::class.java)
+        find(tree(args.treeUri), args.path)?.delete()
+        invoke.resolve()
+    }
+
+    @Command
+    fun renameFile(invoke: Invoke) {
+        val args = invoke.parseArgs(RenameArgs::class.java)
+        val root = tree(args.treeUri)
+        val source = find(root, args.from)
+        if (source == null) {
+            invoke.reject("File not found: ${args.from}")
+            return
+        }
+        val sameFolder = args.from.substringBeforeLast('/', "") == args.to.substringBeforeLast('/', "")
+        if (sameFolder) {
+            source.renameTo(args.to.substringAfterLast('/'))
+        } else {
+            // Documents can't be moved between folders on every provider,
+            // so copy and delete
+            val target = findOrCreate(root, args.to)
+            activity.contentResolver.openInputStream(source.uri)!!.use { input ->
+                activity.contentResolver.openOutputStream(target.uri, "wt")!!.use { input.copyTo(it) }
+            }
+            source.delete()
+        }
+        invoke.resolve()
+    }
+
+    private fun tree(treeUri: String): DocumentFile =
+        DocumentFile.fromTreeUri(activity, Uri.parse(treeUri))
+            ?: throw IllegalArgumentException("Not a folder URI: $treeUri")
+
+    private fun collectFiles(dir: DocumentFile, prefix: String, files: JSArray) {
+        for (child in dir.listFiles()) {
+            val name = child.name ?: continue
+            if (name.startsWith(".")) continue
+            if (child.isDirectory) {
+                collectFiles(child, "$prefix$name/", files)
+            } else {
+                files.put("$prefix$name")
+            }
+        }
+    }
+
+    private fun find(root: DocumentFile, path: String): DocumentFile? =
+        path.split('/').fold(root as DocumentFile?) { dir, name -> dir?.findFile(name) }
+
+    private fun findOrCreate(root: DocumentFile, path: String): DocumentFile {
+        val parts = path.split('/')
+        var dir = root
+        for (name in parts.dropLast(1)) {
+            dir = dir.findFile(name) ?: dir.createDirectory(name)
+                ?: throw IllegalStateException("Can't create folder $name")
+        }
+        val name = parts.last()
+        return dir.findFile(name) ?: dir.createFile("application/octet-stream", name)
+            ?: throw IllegalStateException("Can't create file $path")
+    }
+}
@@ -0,0 +1,5 @@
+//! Headless Chronicle for scripts and servers; see `chronicle_lib::cli`
+
+fn main() {
+    std::process::exit(chronicle_lib::cli::run(std::env::args().skip(1)))
+}
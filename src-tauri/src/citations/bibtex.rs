@@ -0,0 +1,239 @@
+//! Minimal BibTeX reader
+//!
+//! Reads `@type{key, field = {value}, ...}` entries with braced, quoted or
+//! bare values. `@string`, `@preamble` and `@comment` blocks are skipped;
+//! string macros are not expanded.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// One bibliography entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BibEntry {
+    pub key: String,
+    /// Lowercase entry type, e.g. `article`
+    pub entry_type: String,
+    /// Fields by lowercase name, with outer braces or quotes removed
+    pub fields: BTreeMap<String, String>,
+}
+
+impl BibEntry {
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(String::as_str)
+    }
+
+    /// Title with BibTeX case-protecting braces removed
+    pub fn title(&self) -> Option<String> {
+        self.field("title").map(strip_braces)
+    }
+
+    pub fn author(&self) -> Option<String> {
+        self.field("author").map(strip_braces)
+    }
+
+    pub fn year(&self) -> Option<String> {
+        self.field("year")
+            .map(String::from)
+            .or_else(|| self.field("date").map(|d| d.chars().take(4).collect()))
+    }
+
+    /// Whether every word of `query` appears in the key, title or author
+    pub fn matches(&self, query: &str) -> bool {
+        let haystack = format!(
+            "{} {} {} {}",
+            self.key,
+            self.title().unwrap_or_default(),
+            self.author().unwrap_or_default(),
+            self.year().unwrap_or_default()
+        )
+        .to_lowercase();
+        query
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| haystack.contains(word))
+    }
+}
+
+/// Parse every entry in a `.bib` file, skipping malformed ones
+pub fn parse_bibtex(content: &str) -> Vec<BibEntry> {
+    let mut entries = Vec::new();
+    let mut rest = content;
+
+    while let Some(at) = rest.find('@') {
+        rest = &rest[at + 1..];
+        let Some(open) = rest.find(['{', '(']) else {
+            break;
+        };
+        let entry_type = rest[..open].trim().to_lowercase();
+        let close = if rest.as_bytes()[open] == b'{' { '}' } else { ')' };
+        let body_start = open + 1;
+        let Some(body_len) = matching_close(&rest[body_start..], close) else {
+            break;
+        };
+        let body = &rest[body_start..body_start + body_len];
+        rest = &rest[body_start + body_len + 1..];
+
+        let is_entry = !entry_type.is_empty()
+            && entry_type.chars().all(|c| c.is_ascii_alphanumeric())
+            && !matches!(entry_type.as_str(), "string" | "preamble" | "comment");
+        if is_entry {
+            if let Some(entry) = parse_entry(&entry_type, body) {
+                entries.push(entry);
+            }
+        }
+    }
+
+    entries
+}
+
+/// Offset of the delimiter closing a block, accounting for nested braces
+fn matching_close(s: &str, close: char) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth > 0 => depth -= 1,
+            c if c == close && depth == 0 => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_entry(entry_type: &str, body: &str) -> Option<BibEntry> {
+    let (key, mut rest) = body.split_once(',').unwrap_or((body, ""));
+    let key = key.trim();
+    if key.is_empty() {
+        return None;
+    }
+
+    let mut fields = BTreeMap::new();
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        let Some(eq) = rest.find('=') else {
+            break;
+        };
+        let name = rest[..eq].trim().to_lowercase();
+        rest = rest[eq + 1..].trim_start();
+
+        let (value, remaining) = read_value(rest)?;
+        if !name.is_empty() {
+            fields.insert(name, value);
+        }
+        rest = remaining;
+    }
+
+    Some(BibEntry {
+        key: key.to_string(),
+        entry_type: entry_type.to_string(),
+        fields,
+    })
+}
+
+/// Read one field value (`{..}`, `".."` or bare, joined with `#`), returning
+/// it and the text after it
+fn read_value(s: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut rest = s;
+    loop {
+        rest = rest.trim_start();
+        if let Some(inner) = rest.strip_prefix('{') {
+            let end = matching_close(inner, '}')?;
+            value.push_str(&inner[..end]);
+            rest = &inner[end + 1..];
+        } else if let Some(inner) = rest.strip_prefix('"') {
+            let end = quoted_end(inner)?;
+            value.push_str(&inner[..end]);
+            rest = &inner[end + 1..];
+        } else {
+            let end = rest.find([',', '#']).unwrap_or(rest.len());
+            value.push_str(rest[..end].trim());
+            rest = &rest[end..];
+        }
+
+        rest = rest.trim_start();
+        match rest.strip_prefix('#') {
+            Some(after) => rest = after,
+            None => break,
+        }
+    }
+    Some((collapse_whitespace(&value), rest))
+}
+
+/// End of a quoted value; quotes inside braces don't count
+fn quoted_end(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth > 0 => depth -= 1,
+            '"' if depth == 0 => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn collapse_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn strip_braces(s: &str) -> String {
+    s.replace(['{', '}'], "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BIB: &str = r#"
+@string{jml = "Journal of Machine Learning"}
+
+@Article{smith2020,
+  author  = {Smith, Jane and Doe, John},
+  title   = {A {Bayesian} Approach to
+             Note Taking},
+  journal = jml,
+  year    = 2020,
+}
+
+@comment{ignored, title = {no}}
+
+@book{ doe:2019 ,
+  title = "Zettelkasten {"Method"}",
+  date = {2019-05-01},
+  note = "Part " # {one}
+}
+
+@misc{broken
+"#;
+
+    #[test]
+    fn test_parse_bibtex() {
+        let entries = parse_bibtex(BIB);
+        assert_eq!(entries.len(), 2);
+
+        let smith = &entries[0];
+        assert_eq!(smith.key, "smith2020");
+        assert_eq!(smith.entry_type, "article");
+        assert_eq!(smith.title().as_deref(), Some("A Bayesian Approach to Note Taking"));
+        assert_eq!(smith.author().as_deref(), Some("Smith, Jane and Doe, John"));
+        assert_eq!(smith.year().as_deref(), Some("2020"));
+        assert_eq!(smith.field("journal"), Some("jml"));
+
+        let doe = &entries[1];
+        assert_eq!(doe.key, "doe:2019");
+        assert_eq!(doe.title().as_deref(), Some("Zettelkasten \"Method\""));
+        assert_eq!(doe.year().as_deref(), Some("2019"));
+        assert_eq!(doe.field("note"), Some("Part one"));
+    }
+
+    #[test]
+    fn test_entry_matches() {
+        let entries = parse_bibtex(BIB);
+        assert!(entries[0].matches("bayesian smith"));
+        assert!(entries[0].matches("2020"));
+        assert!(!entries[0].matches("zettelkasten"));
+        assert!(entries[1].matches("DOE"));
+    }
+}
@@ -0,0 +1,94 @@
+//! Citations and BibTeX
+//!
+//! Entries come from the `.bib` file named in the `citations` config
+//! section. A note cites an entry with `[[@smith2020]]` or pandoc-style
+//! `[@smith2020, p. 4]`; both link to the entry's literature note,
+//! `<literature_folder>/<key>.md`.
+
+mod bibtex;
+
+pub use bibtex::*;
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Bracketed span holding at least one `@`; a `[` right before it means
+/// it's the inside of a `[[@key]]` wiki link, handled by the link parser
+static PANDOC_SPAN_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(\[?)\[([^\[\]]*@[^\[\]]*)\]").expect("Invalid citation span regex")
+});
+
+/// Cite key after `@`, which must start the span or follow a separator so
+/// email addresses don't count
+static CITE_KEY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|[\s;\-])@(\w[\w:.#$%&+?<>~/-]*)").expect("Invalid cite key regex")
+});
+
+/// Keys cited pandoc-style (`[@a; see @b, p. 3]`) on each line, as
+/// (key, 1-indexed line) pairs
+pub fn extract_pandoc_citations(content: &str) -> Vec<(String, usize)> {
+    let mut citations = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        for span in PANDOC_SPAN_RE.captures_iter(line) {
+            if !span[1].is_empty() {
+                continue;
+            }
+            for key in CITE_KEY_RE.captures_iter(&span[2]) {
+                // Sentence punctuation after a key isn't part of it
+                let key = key[1].trim_end_matches(['.', ':', ',']);
+                if !key.is_empty() {
+                    citations.push((key.to_string(), i + 1));
+                }
+            }
+        }
+    }
+    citations
+}
+
+/// Link target for a wiki link: `@key` becomes the literature note's path
+/// (without `.md`, like other link targets); anything else is unchanged
+pub fn resolve_citation_target(target: &str, literature_folder: &str) -> String {
+    match target.strip_prefix('@') {
+        Some(key) if !key.is_empty() => literature_link(key, literature_folder),
+        _ => target.to_string(),
+    }
+}
+
+/// Literature note link target for a cite key
+pub fn literature_link(key: &str, literature_folder: &str) -> String {
+    let folder = literature_folder.trim_matches('/');
+    if folder.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}/{}", folder, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_pandoc_citations() {
+        let content = "As shown [@smith2020, p. 4; see @doe:2019].\n\
+                       Mail [me@example.com] or see [[@jones2018]].\n\
+                       Also [-@lee2021].";
+        let keys = extract_pandoc_citations(content);
+        assert_eq!(
+            keys,
+            vec![
+                ("smith2020".to_string(), 1),
+                ("doe:2019".to_string(), 1),
+                ("lee2021".to_string(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_citation_target() {
+        assert_eq!(resolve_citation_target("@smith2020", "literature/"), "literature/smith2020");
+        assert_eq!(resolve_citation_target("@smith2020", ""), "smith2020");
+        assert_eq!(resolve_citation_target("Other Note", "literature"), "Other Note");
+        assert_eq!(resolve_citation_target("@", "literature"), "@");
+    }
+}
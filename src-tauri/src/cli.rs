@@ -0,0 +1,270 @@
+//! Headless command-line interface (`chronicle-cli`)
+//!
+//! Works on a vault without the app running, for scripts and for servers
+//! that host a vault. The vault is taken from `--vault`, then the
+//! `CHRONICLE_VAULT` environment variable, then the vault in the config.
+//! An encrypted index is unlocked with `CHRONICLE_DB_KEY`. Commands that
+//! write to the vault refuse to run while the app has it open.
+
+use std::path::{Path, PathBuf};
+
+use crate::commands::{new_note, new_notes, open_index, NewNoteSpec, PublishConfig, SiteGenerator};
+use crate::db::{search::search_notes, Database};
+use crate::error::ChronicleError;
+use crate::models::AppConfig;
+use crate::vault::{Indexer, InstanceLock};
+
+const USAGE: &str = "\
+Usage: chronicle-cli [--vault <path>] <command>
+
+Commands:
+  index [<vault>]                 Index the vault and print the note count
+  search <query> [--limit <n>]    Full-text search; prints path and title
+  new <title> [--content <text>]  Create a note and print its path
+  new-batch <file>                Create the notes in a JSON list (- for stdin)
+                                  and print their paths
+  export <dir>                    Export the vault as a static website";
+
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliCommand {
+    Index,
+    Search { query: String, limit: usize },
+    New { title: String, content: Option<String> },
+    /// Notes given as a JSON list of `{title, folder, content, tags}`
+    NewBatch { input: PathBuf },
+    Export { output: PathBuf },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CliArgs {
+    pub vault: Option<PathBuf>,
+    pub command: CliCommand,
+}
+
+/// Parse arguments (without the program name)
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<CliArgs, String> {
+    let mut vault = None;
+    let mut positional = Vec::new();
+    let mut limit = None;
+    let mut content = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or_else(|| format!("{} needs a value", flag));
+        match arg.as_str() {
+            "--vault" => vault = Some(PathBuf::from(value("--vault")?)),
+            "--limit" => {
+                let n = value("--limit")?;
+                limit = Some(n.parse().map_err(|_| format!("Invalid limit: {}", n))?);
+            }
+            "--content" => content = Some(value("--content")?),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            flag if flag.starts_with("--") => return Err(format!("Unknown option: {}", flag)),
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let name = positional.next().ok_or_else(|| USAGE.to_string())?;
+    let rest: Vec<String> = positional.collect();
+    let joined = || Some(rest.join(" ")).filter(|s| !s.trim().is_empty());
+
+    let command = match name.as_str() {
+        "index" => {
+            if let Some(path) = rest.first() {
+                vault = Some(PathBuf::from(path));
+            }
+            CliCommand::Index
+        }
+        "search" => CliCommand::Search {
+            query: joined().ok_or("search needs a query")?,
+            limit: limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        },
+        "new" => CliCommand::New {
+            title: joined().ok_or("new needs a title")?,
+            content,
+        },
+        "new-batch" => CliCommand::NewBatch {
+            input: rest.first().map(PathBuf::from).ok_or("new-batch needs a file or -")?,
+        },
+        "export" => CliCommand::Export {
+            output: rest.first().map(PathBuf::from).ok_or("export needs an output folder")?,
+        },
+        other => return Err(format!("Unknown command: {}\n\n{}", other, USAGE)),
+    };
+
+    Ok(CliArgs { vault, command })
+}
+
+/// Run the CLI and return the process exit code
+pub fn run<I: IntoIterator<Item = String>>(args: I) -> i32 {
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            return 2;
+        }
+    };
+
+    match execute(&args) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
+
+fn execute(args: &CliArgs) -> Result<(), ChronicleError> {
+    let root = vault_root(args.vault.as_deref())?;
+
+    match &args.command {
+        CliCommand::Index => {
+            let _lock = lock_for_writing(&root)?;
+            let db = open(&root, true)?;
+            let count = Indexer::new(root)?.full_index(&db)?;
+            println!("Indexed {} notes", count);
+        }
+        CliCommand::Search { query, limit } => {
+            let db = open(&root, false)?;
+            let conn = db.conn();
+            for result in search_notes(&conn, query, *limit)? {
+                println!("{}\t{}", result.path, result.title);
+            }
+        }
+        CliCommand::New { title, content } => {
+            let _lock = lock_for_writing(&root)?;
+            let db = open(&root, true)?;
+            let meta = new_note(&root, &db, title, content.clone(), None)?;
+            println!("{}", meta.path);
+        }
+        CliCommand::NewBatch { input } => {
+            let json = if input.as_os_str() == "-" {
+                std::io::read_to_string(std::io::stdin())?
+            } else {
+                std::fs::read_to_string(input)?
+            };
+            let notes: Vec<NewNoteSpec> = serde_json::from_str(&json)?;
+            let _lock = lock_for_writing(&root)?;
+            let db = open(&root, true)?;
+            for meta in new_notes(&root, &db, &notes)? {
+                println!("{}", meta.path);
+            }
+        }
+        CliCommand::Export { output } => {
+            let config = PublishConfig {
+                output_dir: output.clone(),
+                ..Default::default()
+            };
+            SiteGenerator::new(config, root).generate()?;
+            println!("Exported to {}", output.display());
+        }
+    }
+
+    Ok(())
+}
+
+fn vault_root(flag: Option<&Path>) -> Result<PathBuf, ChronicleError> {
+    let root = flag
+        .map(Path::to_path_buf)
+        .or_else(|| std::env::var_os("CHRONICLE_VAULT").map(PathBuf::from))
+        .or_else(|| AppConfig::load().vault.path.map(PathBuf::from))
+        .ok_or(ChronicleError::NoVaultOpen)?;
+
+    if !root.is_dir() {
+        return Err(ChronicleError::VaultNotFound(root.to_string_lossy().to_string()));
+    }
+    Ok(root)
+}
+
+/// Take the vault's instance lock for a command that writes to it, so it
+/// doesn't index into or save over a vault the app has open
+fn lock_for_writing(root: &Path) -> Result<InstanceLock, ChronicleError> {
+    InstanceLock::acquire(root)?
+        .ok_or_else(|| ChronicleError::VaultReadOnly(root.to_string_lossy().to_string()))
+}
+
+/// Open the vault's index; only a `writable` one, under the instance lock,
+/// may be recreated
+fn open(root: &Path, writable: bool) -> Result<Database, ChronicleError> {
+    let key = std::env::var("CHRONICLE_DB_KEY").ok();
+    open_index(root, key.as_deref(), writable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse(&["index", "/notes"]).unwrap();
+        assert_eq!(args.vault, Some(PathBuf::from("/notes")));
+        assert_eq!(args.command, CliCommand::Index);
+
+        let args = parse(&["--vault", "/notes", "search", "rust", "async", "--limit", "5"]).unwrap();
+        assert_eq!(args.vault, Some(PathBuf::from("/notes")));
+        assert_eq!(
+            args.command,
+            CliCommand::Search {
+                query: "rust async".to_string(),
+                limit: 5
+            }
+        );
+
+        let args = parse(&["new", "Meeting", "notes", "--content", "# Hi"]).unwrap();
+        assert_eq!(
+            args.command,
+            CliCommand::New {
+                title: "Meeting notes".to_string(),
+                content: Some("# Hi".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_args_errors() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["search"]).is_err());
+        assert!(parse(&["export"]).is_err());
+        assert!(parse(&["new-batch"]).is_err());
+        assert!(parse(&["search", "x", "--limit", "many"]).is_err());
+        assert!(parse(&["search", "x", "--verbose"]).is_err());
+        assert!(parse(&["frobnicate"]).is_err());
+    }
+
+    #[test]
+    fn test_index_then_search() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("rust.md"), "# Rust\n\nOwnership and borrowing").unwrap();
+
+        let db = open_index(temp.path(), None, true).unwrap();
+        Indexer::new(temp.path().to_path_buf()).unwrap().full_index(&db).unwrap();
+
+        let conn = db.conn();
+        let results = search_notes(&conn, "borrowing", DEFAULT_SEARCH_LIMIT).unwrap();
+        assert_eq!(results[0].path, "rust.md");
+    }
+
+    #[test]
+    fn test_writes_refused_while_vault_is_open() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let args = |command| CliArgs { vault: Some(temp.path().to_path_buf()), command };
+        let app = InstanceLock::acquire(temp.path()).unwrap().unwrap();
+
+        let new = CliCommand::New { title: "Note".to_string(), content: None };
+        for command in [CliCommand::Index, new] {
+            let err = execute(&args(command)).unwrap_err();
+            assert!(matches!(err, ChronicleError::VaultReadOnly(_)));
+        }
+        assert!(!temp.path().join("Note.md").exists());
+
+        drop(app);
+        execute(&args(CliCommand::Index)).unwrap();
+    }
+}
@@ -0,0 +1,138 @@
+//! Attachment commands
+
+use chrono::Local;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::error::ChronicleError;
+use crate::models::AppConfig;
+use crate::vault::{
+    attachment_dir, attachment_embed, attachment_file_name, extract_pdf_text,
+    index_attachment_text, index_pdf_text, recognize_text, resolve_vault_path, unique_file_name,
+    OcrReport, PdfTextReport, VaultFs, PDF_TEXT_AVAILABLE,
+};
+
+/// An attachment stored in the vault
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedAttachment {
+    /// Vault-relative path of the stored file
+    pub path: String,
+    /// Link text to insert into the note
+    pub embed: String,
+}
+
+/// Store a file as an attachment of `note_path`
+///
+/// The content comes from `bytes` (e.g. a pasted image) or is copied from
+/// `source_path`. `file_name` names pasted data; for copies it defaults to
+/// the source's name. The file lands in the configured attachments folder
+/// under a name that doesn't clash with existing files.
+#[tauri::command]
+pub async fn import_attachment(
+    note_path: String,
+    file_name: Option<String>,
+    bytes: Option<Vec<u8>>,
+    source_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ImportedAttachment, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let storage = state.storage();
+
+    blocking(move || {
+        resolve_vault_path(&vault_path, &note_path)?;
+
+        let original_name = file_name
+            .or_else(|| {
+                source_path.as_deref().and_then(|p| {
+                    Path::new(p)
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                })
+            })
+            .unwrap_or_else(|| "attachment".to_string());
+
+        let data = match (bytes, &source_path) {
+            (Some(bytes), _) => bytes,
+            (None, Some(source)) => fs::read(source)?,
+            (None, None) => {
+                return Err(ChronicleError::InvalidAttachment(
+                    "Either bytes or a source path is required".to_string(),
+                ))
+            }
+        };
+
+        store_attachment(&vault_path, storage.as_deref(), &note_path, &original_name, &data)
+    })
+    .await
+}
+
+/// Write `data` as an attachment of `note_path`, in the configured folder
+/// and under a name that doesn't clash with existing files
+pub(crate) fn store_attachment(
+    vault_path: &Path,
+    storage: Option<&dyn VaultFs>,
+    note_path: &str,
+    original_name: &str,
+    data: &[u8],
+) -> Result<ImportedAttachment, ChronicleError> {
+    let config = AppConfig::load().attachments;
+    let dir = attachment_dir(note_path, &config);
+    let full_dir = if dir.is_empty() {
+        vault_path.to_path_buf()
+    } else {
+        resolve_vault_path(vault_path, &dir)?
+    };
+    fs::create_dir_all(&full_dir)?;
+
+    let name = attachment_file_name(original_name, note_path, &config, Local::now().naive_local());
+    let name = unique_file_name(&full_dir, &name);
+    fs::write(full_dir.join(&name), data)?;
+
+    let path = if dir.is_empty() {
+        name
+    } else {
+        format!("{}/{}", dir, name)
+    };
+    // The watcher only reports notes, so attachments are written back to
+    // the vault's storage here
+    if let Some(storage) = storage {
+        storage.write(&path, data)?;
+    }
+    let embed = attachment_embed(note_path, &path, config.embed_style);
+
+    Ok(ImportedAttachment { path, embed })
+}
+
+/// Run OCR over the vault's image attachments, recognizing images that are
+/// new or changed since the last pass
+#[tauri::command]
+pub async fn recognize_attachments(state: State<'_, AppState>) -> Result<OcrReport, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+    let config = AppConfig::load().ocr;
+    if !config.enabled {
+        return Err(ChronicleError::OcrDisabled);
+    }
+
+    blocking(move || index_attachment_text(&vault_path, &db, |image| recognize_text(image, &config)))
+        .await
+}
+
+/// Extract the text of the vault's PDF attachments that are new or changed
+/// since the last pass
+#[tauri::command]
+pub async fn extract_pdf_attachments(
+    state: State<'_, AppState>,
+) -> Result<PdfTextReport, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+    if !PDF_TEXT_AVAILABLE {
+        return Err(ChronicleError::PdfTextUnavailable);
+    }
+
+    blocking(move || index_pdf_text(&vault_path, &db, extract_pdf_text)).await
+}
@@ -0,0 +1,155 @@
+//! Backup commands and the scheduler that takes backups in the background
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::blocking;
+use crate::commands::search::restore_saved_searches;
+use crate::commands::smart_folders::restore_smart_folders;
+use crate::commands::tags::restore_tag_meta;
+use crate::commands::vault::AppState;
+use crate::commands::views::restore_views;
+use crate::commands::workspaces::restore_workspaces;
+use crate::error::ChronicleError;
+use crate::models::{AppConfig, BackupConfig};
+use crate::vault::{self, BackupInfo, Indexer};
+
+/// How often the scheduler checks whether a backup is due
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Outcome of restoring a backup
+#[derive(Debug, Serialize)]
+pub struct RestoreResult {
+    /// Files written from the archive
+    pub restored: usize,
+    /// Backup of the vault as it was just before the restore
+    pub safety_backup: BackupInfo,
+    pub note_count: usize,
+}
+
+/// Back up the vault and drop archives beyond the configured number
+fn take_backup(
+    vault_path: &Path,
+    config: &BackupConfig,
+    include_attachments: bool,
+) -> Result<BackupInfo, ChronicleError> {
+    let dir = config.dir();
+    let info = vault::create_backup(vault_path, &dir, include_attachments)?;
+    let removed = vault::prune_backups(vault_path, &dir, config.keep)?;
+    tracing::info!(backup = %info.name, pruned = removed, "Vault backed up");
+    Ok(info)
+}
+
+/// Back up the open vault now. Attachments are included as configured
+/// unless `include_attachments` says otherwise.
+#[tauri::command]
+pub async fn create_backup(
+    include_attachments: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<BackupInfo, ChronicleError> {
+    let vault_path = state.vault_path()?;
+
+    blocking(move || {
+        let config = AppConfig::load().backup;
+        let include_attachments = include_attachments.unwrap_or(config.include_attachments);
+        take_backup(&vault_path, &config, include_attachments)
+    })
+    .await
+}
+
+/// Backups of the open vault, newest first
+#[tauri::command]
+pub async fn list_backups(state: State<'_, AppState>) -> Result<Vec<BackupInfo>, ChronicleError> {
+    let vault_path = state.vault_path()?;
+
+    blocking(move || Ok(vault::list_backups(&vault_path, &AppConfig::load().backup.dir())?)).await
+}
+
+/// Restore the backup called `name` over the open vault and reindex
+///
+/// The vault is backed up first, so a restore can itself be undone. Notes
+/// created since the backup are kept.
+#[tauri::command]
+pub async fn restore_backup(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<RestoreResult, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let config = AppConfig::load().backup;
+        let archive = vault::list_backups(&vault_path, &config.dir())?
+            .into_iter()
+            .find(|b| b.name == name)
+            .map(|b| PathBuf::from(b.path))
+            .ok_or(ChronicleError::BackupNotFound(name))?;
+
+        let safety_backup = take_backup(&vault_path, &config, true)?;
+        let restored = vault::restore_backup(&vault_path, &archive)?;
+
+        let note_count = Indexer::new(vault_path.clone())?.full_index(&db)?;
+        restore_workspaces(&vault_path, &db)?;
+        restore_smart_folders(&vault_path, &db)?;
+        restore_views(&vault_path, &db)?;
+        restore_saved_searches(&vault_path, &db)?;
+        restore_tag_meta(&vault_path, &db)?;
+
+        Ok(RestoreResult {
+            restored,
+            safety_backup,
+            note_count,
+        })
+    })
+    .await
+}
+
+/// Back up the open vault whenever the newest backup is older than the
+/// configured interval, for as long as the app runs. Each backup is also
+/// emitted as a `backup` event for the frontend.
+pub fn start_backup_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_scheduled_backup(&app).await {
+                tracing::warn!(error = %e, "Scheduled backup failed");
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+async fn run_scheduled_backup(app: &AppHandle) -> Result<(), ChronicleError> {
+    let config = AppConfig::load().backup;
+    if !config.enabled {
+        return Ok(());
+    }
+    let Some(state) = app.try_state::<AppState>() else {
+        return Ok(());
+    };
+    // No vault open (or it's unavailable): nothing to back up. A read-only
+    // instance leaves backups to the one that has the vault.
+    let Some((vault_path, _)) = state.background_vault() else {
+        return Ok(());
+    };
+
+    let taken = blocking(move || {
+        let latest = vault::list_backups(&vault_path, &config.dir())?.into_iter().next();
+        let interval = chrono::Duration::hours(i64::from(config.interval_hours));
+        let due = latest
+            .and_then(|b| DateTime::parse_from_rfc3339(&b.created_at).ok())
+            .is_none_or(|taken| Local::now().signed_duration_since(taken) >= interval);
+        if !due {
+            return Ok(None);
+        }
+        take_backup(&vault_path, &config, config.include_attachments).map(Some)
+    })
+    .await?;
+
+    if let Some(info) = taken {
+        let _ = app.emit("backup", info);
+    }
+    Ok(())
+}
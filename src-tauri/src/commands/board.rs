@@ -0,0 +1,255 @@
+//! Kanban board commands
+//!
+//! A board groups notes into columns by a frontmatter property, so project
+//! boards stay plain Markdown.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::{notes as db_notes, tags::get_notes_by_tag};
+use crate::error::ChronicleError;
+use crate::vault::{
+    get_frontmatter_property, remove_draft, resolve_vault_path, set_frontmatter_property, Indexer,
+};
+
+/// Which notes a board shows and how they're grouped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoardConfig {
+    /// Frontmatter property holding the column
+    #[serde(default = "default_board_property")]
+    pub property: String,
+    /// Column order. Values not listed get columns after these, sorted.
+    #[serde(default)]
+    pub columns: Vec<String>,
+    /// Only notes in this folder (including subfolders)
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Only notes with this tag
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+fn default_board_property() -> String {
+    "status".to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoardCard {
+    pub path: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoardColumn {
+    pub name: String,
+    pub cards: Vec<BoardCard>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Board {
+    pub property: String,
+    pub columns: Vec<BoardColumn>,
+    /// Notes in scope that don't have the property yet
+    pub unassigned: Vec<BoardCard>,
+}
+
+/// Group notes into columns by a frontmatter property
+#[tauri::command]
+pub async fn get_board(
+    board_config: BoardConfig,
+    state: State<'_, AppState>,
+) -> Result<Board, ChronicleError> {
+    validate_property(&board_config.property)?;
+    let vault_path = state.vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let notes = {
+            let conn = db.conn();
+            let tagged: Option<HashSet<i64>> = match &board_config.tag {
+                Some(tag) => Some(get_notes_by_tag(&conn, tag)?.into_iter().collect()),
+                None => None,
+            };
+            let folder = board_config
+                .folder
+                .as_deref()
+                .map(|f| format!("{}/", f.trim_matches('/')))
+                .filter(|f| f != "/");
+
+            db_notes::list_notes(&conn)?
+                .into_iter()
+                .filter(|n| folder.as_ref().is_none_or(|f| n.path.starts_with(f)))
+                .filter(|n| tagged.as_ref().is_none_or(|ids| ids.contains(&n.id)))
+                .collect::<Vec<_>>()
+        };
+
+        // The property isn't indexed, so it's read from each note
+        let cards = notes
+            .into_iter()
+            .map(|note| {
+                let value = resolve_vault_path(&vault_path, &note.path)
+                    .ok()
+                    .and_then(|p| fs::read_to_string(p).ok())
+                    .and_then(|content| get_frontmatter_property(&content, &board_config.property));
+                let card = BoardCard {
+                    path: note.path,
+                    title: note.title,
+                };
+                (card, value)
+            })
+            .collect();
+
+        Ok(build_board(&board_config, cards))
+    })
+    .await
+}
+
+/// Move a note to another column by rewriting its frontmatter property
+/// (`status` unless given)
+#[tauri::command]
+pub async fn move_card(
+    path: String,
+    new_status: String,
+    property: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<db_notes::NoteMeta, ChronicleError> {
+    let property = property.unwrap_or_else(default_board_property);
+    validate_property(&property)?;
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let full_path = resolve_vault_path(&vault_path, &path)?;
+        if !full_path.exists() {
+            return Err(ChronicleError::NoteNotFound(path));
+        }
+
+        let content = fs::read_to_string(&full_path)?;
+        fs::write(&full_path, set_frontmatter_property(&content, &property, &new_status))?;
+
+        // A draft predating the move would undo it when restored
+        if let Err(e) = remove_draft(&vault_path, &path) {
+            tracing::warn!(path = %path, error = %e, "Failed to discard draft");
+        }
+
+        let indexer = Indexer::new(vault_path.clone())?;
+        indexer.index_file(&db, &full_path)?;
+
+        let conn = db.conn();
+        let meta = db_notes::get_note_by_path(&conn, &path)?
+            .ok_or(ChronicleError::NoteNotFound(path))?;
+
+        Ok(meta)
+    })
+    .await
+}
+
+/// Property names are written as bare YAML keys
+fn validate_property(property: &str) -> Result<(), ChronicleError> {
+    let valid = !property.is_empty()
+        && property
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(ChronicleError::InvalidProperty(property.to_string()))
+    }
+}
+
+fn build_board(config: &BoardConfig, cards: Vec<(BoardCard, Option<String>)>) -> Board {
+    let mut grouped: BTreeMap<String, Vec<BoardCard>> = BTreeMap::new();
+    let mut unassigned = Vec::new();
+    for (card, value) in cards {
+        match value.filter(|v| !v.trim().is_empty()) {
+            Some(value) => grouped.entry(value).or_default().push(card),
+            None => unassigned.push(card),
+        }
+    }
+
+    let mut columns: Vec<BoardColumn> = config
+        .columns
+        .iter()
+        .map(|name| BoardColumn {
+            name: name.clone(),
+            cards: grouped.remove(name).unwrap_or_default(),
+        })
+        .collect();
+    columns.extend(
+        grouped
+            .into_iter()
+            .map(|(name, cards)| BoardColumn { name, cards }),
+    );
+
+    for column in &mut columns {
+        column.cards.sort_by_key(|c| c.title.to_lowercase());
+    }
+    unassigned.sort_by_key(|c| c.title.to_lowercase());
+
+    Board {
+        property: config.property.clone(),
+        columns,
+        unassigned,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(title: &str, status: Option<&str>) -> (BoardCard, Option<String>) {
+        let card = BoardCard {
+            path: format!("projects/{}.md", title),
+            title: title.to_string(),
+        };
+        (card, status.map(String::from))
+    }
+
+    fn titles(cards: &[BoardCard]) -> Vec<&str> {
+        cards.iter().map(|c| c.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_build_board() {
+        let config = BoardConfig {
+            property: "status".to_string(),
+            columns: vec!["todo".to_string(), "doing".to_string(), "done".to_string()],
+            folder: None,
+            tag: None,
+        };
+        let cards = vec![
+            card("b", Some("todo")),
+            card("a", Some("todo")),
+            card("c", Some("blocked")),
+            card("d", Some("done")),
+            card("e", None),
+            card("f", Some(" ")),
+        ];
+
+        let board = build_board(&config, cards);
+        let names: Vec<&str> = board.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["todo", "doing", "done", "blocked"]);
+        assert_eq!(titles(&board.columns[0].cards), vec!["a", "b"]);
+        assert!(board.columns[1].cards.is_empty());
+        assert_eq!(titles(&board.unassigned), vec!["e", "f"]);
+    }
+
+    #[test]
+    fn test_board_config_defaults() {
+        let config: BoardConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.property, "status");
+        assert!(config.columns.is_empty());
+    }
+
+    #[test]
+    fn test_validate_property() {
+        assert!(validate_property("status").is_ok());
+        assert!(validate_property("due-date").is_ok());
+        assert!(validate_property("").is_err());
+        assert!(validate_property("a: b").is_err());
+    }
+}
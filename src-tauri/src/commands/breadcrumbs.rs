@@ -0,0 +1,196 @@
+//! Breadcrumbs: where a note sits, by folder and by the parent notes its
+//! `up` property links to
+
+use rusqlite::Connection;
+use serde::Serialize;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::{
+    links::resolve_link_target, notes::get_note_by_path, properties::get_property_values,
+};
+use crate::error::ChronicleError;
+use crate::models::AppConfig;
+use crate::vault::extract_links;
+
+/// Parent chains longer than this are cut off
+const MAX_PARENT_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FolderCrumb {
+    pub name: String,
+    /// Vault-relative folder path
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NoteCrumb {
+    pub path: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Breadcrumbs {
+    /// Folders from the vault root down to the note's folder
+    pub folders: Vec<FolderCrumb>,
+    /// Parent notes from the topmost down to the note's own parent,
+    /// following the first parent that resolves at each step
+    pub parents: Vec<NoteCrumb>,
+    /// Every note the note itself names as a parent
+    pub direct_parents: Vec<NoteCrumb>,
+}
+
+/// The note names in a parent property value: `[[Projects]]`,
+/// `[[projects/index|Projects]]` or a bare `Projects`
+fn parent_targets(value: &str) -> Vec<String> {
+    let links = extract_links(value);
+    let targets: Vec<String> = if links.is_empty() {
+        vec![value.trim().to_string()]
+    } else {
+        links.into_iter().map(|link| link.target).collect()
+    };
+    targets
+        .into_iter()
+        .map(|target| target.split('#').next().unwrap_or_default().trim().to_string())
+        .filter(|target| !target.is_empty())
+        .collect()
+}
+
+/// Parent notes of the note at `path` that exist in the index
+fn parents_of(
+    conn: &Connection,
+    path: &str,
+    property: &str,
+) -> Result<Vec<NoteCrumb>, ChronicleError> {
+    let mut parents: Vec<NoteCrumb> = Vec::new();
+    for value in get_property_values(conn, path, property)? {
+        for target in parent_targets(&value) {
+            let Some(parent_path) = resolve_link_target(conn, &target)? else {
+                continue;
+            };
+            if parent_path == path || parents.iter().any(|p| p.path == parent_path) {
+                continue;
+            }
+            if let Some(note) = get_note_by_path(conn, &parent_path)? {
+                parents.push(NoteCrumb {
+                    path: note.path,
+                    title: note.title,
+                });
+            }
+        }
+    }
+    Ok(parents)
+}
+
+/// Breadcrumbs for the note at `path`, with parents named by `property`
+pub fn get_note_breadcrumbs(
+    conn: &Connection,
+    path: &str,
+    property: &str,
+) -> Result<Breadcrumbs, ChronicleError> {
+    let mut folders = Vec::new();
+    let mut folder_path = String::new();
+    let components: Vec<&str> = path.split('/').collect();
+    for name in &components[..components.len() - 1] {
+        if !folder_path.is_empty() {
+            folder_path.push('/');
+        }
+        folder_path.push_str(name);
+        folders.push(FolderCrumb {
+            name: name.to_string(),
+            path: folder_path.clone(),
+        });
+    }
+
+    let direct_parents = parents_of(conn, path, property)?;
+    let mut parents: Vec<NoteCrumb> = Vec::new();
+    let mut next = direct_parents.first().cloned();
+    while let Some(parent) = next.take() {
+        // Stop at a cycle back to the note or one already in the chain
+        if parent.path == path || parents.iter().any(|p| p.path == parent.path) {
+            break;
+        }
+        if parents.len() == MAX_PARENT_DEPTH {
+            break;
+        }
+        next = parents_of(conn, &parent.path, property)?.into_iter().next();
+        parents.push(parent);
+    }
+    parents.reverse();
+
+    Ok(Breadcrumbs {
+        folders,
+        parents,
+        direct_parents,
+    })
+}
+
+/// Folder ancestry and parent notes of a note, for hierarchical navigation.
+/// Parents come from the `breadcrumbs.parent_property` frontmatter property.
+#[tauri::command]
+pub async fn get_breadcrumbs(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<Breadcrumbs, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let property = AppConfig::load().breadcrumbs.parent_property;
+        let conn = db.conn();
+        if get_note_by_path(&conn, &path)?.is_none() {
+            return Err(ChronicleError::NoteNotFound(path));
+        }
+        get_note_breadcrumbs(&conn, &path, &property)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{notes::upsert_note, properties::replace_note_properties, Database};
+
+    fn note(conn: &Connection, path: &str, title: &str, up: &[&str]) {
+        let id = upsert_note(conn, path, title, None, None, "h", 0).unwrap();
+        let props: Vec<(String, String)> =
+            up.iter().map(|v| ("up".to_string(), v.to_string())).collect();
+        replace_note_properties(conn, id, &props).unwrap();
+    }
+
+    fn paths(crumbs: &[NoteCrumb]) -> Vec<&str> {
+        crumbs.iter().map(|c| c.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_breadcrumbs() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        note(&conn, "home.md", "Home", &[]);
+        note(&conn, "work/projects.md", "Projects", &["[[home|Home]]"]);
+        note(&conn, "areas.md", "Areas", &["home"]);
+        note(&conn, "work/alpha/plan.md", "Plan", &["[[work/projects#Active]]", "[[areas]]"]);
+
+        let crumbs = get_note_breadcrumbs(&conn, "work/alpha/plan.md", "up").unwrap();
+        let folders: Vec<&str> = crumbs.folders.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(folders, vec!["work", "work/alpha"]);
+        assert_eq!(crumbs.folders[1].name, "alpha");
+        assert_eq!(paths(&crumbs.parents), vec!["home.md", "work/projects.md"]);
+        assert_eq!(paths(&crumbs.direct_parents), vec!["work/projects.md", "areas.md"]);
+
+        let root = get_note_breadcrumbs(&conn, "home.md", "up").unwrap();
+        assert!(root.folders.is_empty() && root.parents.is_empty());
+    }
+
+    #[test]
+    fn test_breadcrumbs_stop_at_cycles() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        note(&conn, "a.md", "A", &["[[b]]"]);
+        note(&conn, "b.md", "B", &["[[c]]"]);
+        note(&conn, "c.md", "C", &["[[a]]"]);
+
+        let crumbs = get_note_breadcrumbs(&conn, "a.md", "up").unwrap();
+        assert_eq!(paths(&crumbs.parents), vec!["c.md", "b.md"]);
+    }
+}
@@ -0,0 +1,172 @@
+//! Broken link commands: finding links to missing notes and fixing them
+//! everywhere at once
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use rusqlite::Connection;
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::{links::get_unresolved_links, notes as db_notes, UnresolvedLink};
+use crate::error::ChronicleError;
+use crate::vault::{
+    count_unresolved_links, group_broken_links, resolve_vault_path, rewrite_links,
+    rewrite_markdown_links, BrokenLink, Indexer, LinkEdit, UnresolvedTarget,
+};
+
+/// What to do with every link to a missing note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum BrokenLinkFix {
+    /// Create the missing note so the links resolve
+    Create,
+    /// Point the links at an existing note
+    Retarget { path: String },
+    /// Turn the links into plain text
+    Remove,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrokenLinkFixResult {
+    /// Notes whose links were rewritten
+    pub changed_notes: Vec<String>,
+    pub links_changed: usize,
+    /// The note made by `create`
+    pub created: Option<db_notes::NoteMeta>,
+}
+
+/// Links to notes that don't exist, grouped by target, with similarly
+/// named notes as suggestions. Links to attachments and other files that
+/// exist don't count.
+#[tauri::command]
+pub async fn get_broken_link_report(
+    state: State<'_, AppState>,
+) -> Result<Vec<BrokenLink>, ChronicleError> {
+    let vault_path = state.vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        let links = missing_note_links(&vault_path, &conn)?;
+        let note_paths: Vec<String> =
+            db_notes::list_notes(&conn)?.into_iter().map(|n| n.path).collect();
+        Ok(group_broken_links(links, &note_paths))
+    })
+    .await
+}
+
+/// Each missing note that links point at, with how many notes link to it
+/// and how many times. Cheaper than the full report, for finding typos and
+/// notes worth creating.
+#[tauri::command]
+pub async fn list_unresolved_links(
+    state: State<'_, AppState>,
+) -> Result<Vec<UnresolvedTarget>, ChronicleError> {
+    let vault_path = state.vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let links = missing_note_links(&vault_path, &db.conn())?;
+        Ok(count_unresolved_links(&links))
+    })
+    .await
+}
+
+/// Unresolved links, less those to attachments and other files that exist
+fn missing_note_links(
+    vault_path: &Path,
+    conn: &Connection,
+) -> Result<Vec<UnresolvedLink>, ChronicleError> {
+    Ok(get_unresolved_links(conn)?
+        .into_iter()
+        .filter(|link| {
+            !resolve_vault_path(vault_path, &link.target).is_ok_and(|path| path.is_file())
+        })
+        .collect())
+}
+
+/// Fix every link to `target` the same way
+#[tauri::command]
+pub async fn fix_broken_link(
+    target: String,
+    fix: BrokenLinkFix,
+    state: State<'_, AppState>,
+) -> Result<BrokenLinkFixResult, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let indexer = Indexer::new(vault_path.clone())?;
+        let mut result = BrokenLinkFixResult {
+            changed_notes: Vec::new(),
+            links_changed: 0,
+            created: None,
+        };
+
+        let new_target = match &fix {
+            BrokenLinkFix::Create => {
+                let path = if target.rsplit('/').next().is_some_and(|name| name.contains('.')) {
+                    target.clone()
+                } else {
+                    format!("{}.md", target)
+                };
+                let full_path = resolve_vault_path(&vault_path, &path)?;
+                if full_path.exists() {
+                    return Err(ChronicleError::NoteExists(path));
+                }
+                if let Some(parent) = full_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let title = target.rsplit('/').next().unwrap_or(&target);
+                fs::write(&full_path, format!("# {}\n\n", title))?;
+                indexer.index_file(&db, &full_path)?;
+                result.created = db_notes::get_note_by_path(&db.conn(), &path)?;
+                return Ok(result);
+            }
+            BrokenLinkFix::Retarget { path } => {
+                if db_notes::get_note_by_path(&db.conn(), path)?.is_none() {
+                    return Err(ChronicleError::NoteNotFound(path.clone()));
+                }
+                Some(path.strip_suffix(".md").unwrap_or(path).to_string())
+            }
+            BrokenLinkFix::Remove => None,
+        };
+        let edit = match &new_target {
+            Some(new_target) => LinkEdit::Retarget(new_target),
+            None => LinkEdit::Unlink,
+        };
+        // Markdown links are stored by the vault path they lead to, and
+        // point at the note by its path relative to theirs
+        let markdown_edit = match &fix {
+            BrokenLinkFix::Retarget { path } => LinkEdit::Retarget(path),
+            _ => LinkEdit::Unlink,
+        };
+
+        let sources: BTreeSet<String> = get_unresolved_links(&db.conn())?
+            .into_iter()
+            .filter(|link| link.target.eq_ignore_ascii_case(&target))
+            .map(|link| link.source_path)
+            .collect();
+        for source in sources {
+            let full_path = resolve_vault_path(&vault_path, &source)?;
+            let content = fs::read_to_string(&full_path)?;
+            let (rewritten, wiki) = rewrite_links(&content, &target, &edit);
+            let (rewritten, markdown) =
+                rewrite_markdown_links(&rewritten, &source, &target, &markdown_edit);
+            let changed = wiki + markdown;
+            if changed == 0 {
+                continue;
+            }
+            fs::write(&full_path, rewritten)?;
+            indexer.index_file(&db, &full_path)?;
+            result.links_changed += changed;
+            result.changed_notes.push(source);
+        }
+
+        Ok(result)
+    })
+    .await
+}
@@ -0,0 +1,92 @@
+//! Bulk frontmatter editing across the notes matching a filter
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::smart_folders::notes_matching;
+use crate::error::ChronicleError;
+use crate::vault::{
+    apply_property_changes, frontmatter_text, resolve_vault_path, write_all_or_restore, Indexer,
+    PropertyChange, SmartFilter,
+};
+
+/// A note's frontmatter before and after a bulk edit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyEdit {
+    pub path: String,
+    pub title: String,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkEditResult {
+    /// Notes the changes alter; matching notes they leave as-is are omitted
+    pub edits: Vec<PropertyEdit>,
+    /// False for a preview
+    pub applied: bool,
+}
+
+/// Apply frontmatter changes to every Markdown note matching `filter`, or
+/// with `preview` just report what would change. Nothing is written unless
+/// every note can be; a failed write restores the notes already written.
+#[tauri::command]
+pub async fn bulk_edit_properties(
+    filter: SmartFilter,
+    changes: Vec<PropertyChange>,
+    preview: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<BulkEditResult, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    filter.validate().map_err(ChronicleError::InvalidFilter)?;
+    for change in &changes {
+        change.validate().map_err(ChronicleError::InvalidProperty)?;
+    }
+
+    blocking(move || {
+        let notes = notes_matching(&db.conn(), &filter)?;
+
+        let mut pending = Vec::new();
+        for note in notes.into_iter().filter(|n| n.path.ends_with(".md")) {
+            let full_path = resolve_vault_path(&vault_path, &note.path)?;
+            let original = fs::read_to_string(&full_path)?;
+            let updated = apply_property_changes(&original, &changes);
+            if updated != original {
+                pending.push((note, full_path, original, updated));
+            }
+        }
+
+        let edits = pending
+            .iter()
+            .map(|(note, _, original, updated)| PropertyEdit {
+                path: note.path.clone(),
+                title: note.title.clone(),
+                before: frontmatter_text(original),
+                after: frontmatter_text(updated),
+            })
+            .collect();
+        if preview.unwrap_or(false) {
+            return Ok(BulkEditResult { edits, applied: false });
+        }
+
+        let writes: Vec<_> = pending
+            .iter()
+            .map(|(_, full_path, original, updated)| {
+                (full_path.as_path(), original.as_str(), updated.as_str())
+            })
+            .collect();
+        write_all_or_restore(&writes)?;
+
+        let indexer = Indexer::new(vault_path)?;
+        for (_, full_path, _, _) in &pending {
+            indexer.index_file(&db, full_path)?;
+        }
+        Ok(BulkEditResult { edits, applied: true })
+    })
+    .await
+}
@@ -0,0 +1,199 @@
+//! Calendar panel commands
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::daily::daily_note_date;
+use crate::commands::vault::AppState;
+use crate::db::notes::{self as db_notes, NoteMeta};
+use crate::db::tasks::{self as db_tasks, TaskFilter, TaskRecord};
+use crate::error::ChronicleError;
+use crate::models::{AppConfig, DailyNotesConfig, LocaleConfig};
+
+/// Note reference shown on a calendar day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarNote {
+    pub path: String,
+    pub title: String,
+}
+
+/// Everything that happened on one day
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CalendarDay {
+    /// YYYY-MM-DD
+    pub date: String,
+    /// Path of the daily note for this day
+    pub daily_note: Option<String>,
+    pub created: Vec<CalendarNote>,
+    pub modified: Vec<CalendarNote>,
+    pub tasks_due: Vec<TaskRecord>,
+}
+
+/// Calendar data for every day of a month (YYYY-MM)
+#[tauri::command]
+pub async fn get_calendar_data(
+    month: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<CalendarDay>, ChronicleError> {
+    let first = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map_err(|_| ChronicleError::InvalidDate(month.clone()))?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let config = AppConfig::load();
+        let conn = db.conn();
+
+        let notes = db_notes::list_notes(&conn)?;
+        let tasks = db_tasks::query_tasks(
+            &conn,
+            &TaskFilter {
+                due_after: Some(first.format("%Y-%m-%d").to_string()),
+                due_before: Some(last_day_of_month(first).format("%Y-%m-%d").to_string()),
+                ..Default::default()
+            },
+        )?;
+
+        Ok(build_calendar(first, notes, tasks, &config.daily_notes, &config.locale))
+    })
+    .await
+}
+
+fn last_day_of_month(first: NaiveDate) -> NaiveDate {
+    let (year, month) = if first.month() == 12 {
+        (first.year() + 1, 1)
+    } else {
+        (first.year(), first.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(first) - Duration::days(1)
+}
+
+/// Local calendar date of a stored RFC 3339 timestamp
+fn local_date(timestamp: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|t| t.with_timezone(&Local).date_naive())
+}
+
+fn build_calendar(
+    first: NaiveDate,
+    notes: Vec<NoteMeta>,
+    tasks: Vec<TaskRecord>,
+    daily_config: &DailyNotesConfig,
+    locale: &LocaleConfig,
+) -> Vec<CalendarDay> {
+    let last = last_day_of_month(first);
+    let mut days: BTreeMap<NaiveDate, CalendarDay> = first
+        .iter_days()
+        .take_while(|d| *d <= last)
+        .map(|d| {
+            let day = CalendarDay {
+                date: d.format("%Y-%m-%d").to_string(),
+                ..Default::default()
+            };
+            (d, day)
+        })
+        .collect();
+
+    for note in notes {
+        let date = daily_note_date(&note.path, daily_config, locale);
+        if let Some(day) = date.and_then(|d| days.get_mut(&d)) {
+            day.daily_note = Some(note.path.clone());
+        }
+
+        let entry = CalendarNote {
+            path: note.path,
+            title: note.title,
+        };
+        if let Some(day) = note.created_at.as_deref().and_then(local_date).and_then(|d| days.get_mut(&d)) {
+            day.created.push(entry.clone());
+        }
+        if let Some(day) = note.modified_at.as_deref().and_then(local_date).and_then(|d| days.get_mut(&d)) {
+            day.modified.push(entry);
+        }
+    }
+
+    for task in tasks {
+        let due = task
+            .due
+            .as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+        if let Some(day) = due.and_then(|d| days.get_mut(&d)) {
+            day.tasks_due.push(task);
+        }
+    }
+
+    days.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(path: &str, created: Option<&str>, modified: Option<&str>) -> NoteMeta {
+        NoteMeta {
+            id: 0,
+            path: path.to_string(),
+            title: path.trim_end_matches(".md").to_string(),
+            created_at: created.map(String::from),
+            modified_at: modified.map(String::from),
+            word_count: 0,
+        }
+    }
+
+    fn task(due: &str) -> TaskRecord {
+        TaskRecord {
+            id: 0,
+            note_path: "todo.md".to_string(),
+            note_title: "Todo".to_string(),
+            line_number: 1,
+            text: format!("Thing due:{}", due),
+            completed: false,
+            due: Some(due.to_string()),
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_last_day_of_month() {
+        let d = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(last_day_of_month(d(2024, 2, 1)), d(2024, 2, 29));
+        assert_eq!(last_day_of_month(d(2026, 12, 1)), d(2026, 12, 31));
+    }
+
+    #[test]
+    fn test_build_calendar() {
+        let first = NaiveDate::from_ymd_opt(2026, 2, 1).unwrap();
+        // Midday UTC falls on the same date in every timezone
+        let notes = vec![
+            note("daily/2026-02-10.md", None, None),
+            note("daily/2026-03-01.md", None, None),
+            note("idea.md", Some("2026-02-03T12:00:00Z"), Some("2026-02-05T12:00:00Z")),
+            note("old.md", Some("2025-01-01T12:00:00Z"), Some("not a date")),
+        ];
+        let tasks = vec![task("2026-02-28")];
+
+        let days = build_calendar(
+            first,
+            notes,
+            tasks,
+            &DailyNotesConfig::default(),
+            &LocaleConfig::default(),
+        );
+        assert_eq!(days.len(), 28);
+        assert_eq!(days[0].date, "2026-02-01");
+
+        assert_eq!(days[9].daily_note.as_deref(), Some("daily/2026-02-10.md"));
+        assert_eq!(days[2].created[0].path, "idea.md");
+        assert_eq!(days[4].modified[0].path, "idea.md");
+        assert_eq!(days[27].tasks_due.len(), 1);
+
+        let busy: usize = days
+            .iter()
+            .map(|d| d.created.len() + d.modified.len() + d.daily_note.iter().count())
+            .sum();
+        assert_eq!(busy, 3);
+    }
+}
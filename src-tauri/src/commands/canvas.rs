@@ -0,0 +1,65 @@
+//! Canvas commands
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::error::ChronicleError;
+use crate::vault::{is_canvas_file, resolve_vault_path, Canvas, Indexer};
+
+/// Read and validate a canvas
+#[tauri::command]
+pub async fn get_canvas(path: String, state: State<'_, AppState>) -> Result<Canvas, ChronicleError> {
+    let vault_path = state.vault_path()?;
+
+    blocking(move || {
+        let full_path = canvas_path(&vault_path, &path)?;
+        if !full_path.exists() {
+            return Err(ChronicleError::NoteNotFound(path));
+        }
+        let content = fs::read_to_string(&full_path)?;
+        Canvas::parse(&content).map_err(ChronicleError::InvalidCanvas)
+    })
+    .await
+}
+
+/// Validate and write a canvas, creating it if needed, and re-index it
+#[tauri::command]
+pub async fn save_canvas(
+    path: String,
+    canvas: Canvas,
+    state: State<'_, AppState>,
+) -> Result<(), ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        canvas.validate().map_err(ChronicleError::InvalidCanvas)?;
+        let full_path = canvas_path(&vault_path, &path)?;
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Tab-indented like Obsidian writes it, to keep diffs small
+        let mut content = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(b"\t");
+        let mut serializer = serde_json::Serializer::with_formatter(&mut content, formatter);
+        serde::Serialize::serialize(&canvas, &mut serializer)?;
+        fs::write(&full_path, content)?;
+
+        let indexer = Indexer::new(vault_path)?;
+        indexer.index_file(&db, &full_path)?;
+        Ok(())
+    })
+    .await
+}
+
+/// Resolve a vault-relative path that must name a `.canvas` file
+fn canvas_path(vault_path: &Path, path: &str) -> Result<PathBuf, ChronicleError> {
+    let full_path = resolve_vault_path(vault_path, path)?;
+    if !is_canvas_file(&full_path) {
+        return Err(ChronicleError::InvalidPath(path.to_string()));
+    }
+    Ok(full_path)
+}
@@ -0,0 +1,328 @@
+//! Quick capture: turn a URL, title and/or Markdown snippet into a new note,
+//! or add it to an existing one
+
+use chrono::{Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::notes::new_note_path;
+use crate::commands::vault::AppState;
+use crate::commands::zettel::new_note_id;
+use crate::db::{notes as db_notes, Database};
+use crate::error::ChronicleError;
+use crate::models::AppConfig;
+use crate::vault::{
+    add_frontmatter_tag, resolve_vault_path, set_frontmatter_property, unique_file_name, Indexer,
+};
+use crate::web::{fetch_page, page_title, readable_markdown};
+
+/// Tag for pages captured by URL, to read later
+const TOREAD_TAG: &str = "toread";
+
+/// Something to capture. Any field may be missing, but at least one is
+/// needed for a useful note.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Capture {
+    /// Source page, stored as `source` in the frontmatter
+    pub url: Option<String>,
+    pub title: Option<String>,
+    /// Note body, e.g. a selection converted to Markdown
+    pub markdown: Option<String>,
+    /// Frontmatter tags for the note
+    pub tags: Vec<String>,
+}
+
+/// Create a note from a capture in the new-note location
+#[tauri::command]
+pub async fn quick_capture(
+    capture: Capture,
+    state: State<'_, AppState>,
+) -> Result<db_notes::NoteMeta, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || capture_note(&vault_path, &db, &capture)).await
+}
+
+/// Capture a web page by URL, tagged `#toread`. The page title is fetched,
+/// and with `include_body` the readable part of the page as Markdown too.
+/// With `note`, the page is added to that note (created if missing) rather
+/// than getting a note of its own.
+#[tauri::command]
+pub async fn capture_url(
+    url: String,
+    note: Option<String>,
+    include_body: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<db_notes::NoteMeta, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let html = fetch_page(&url)?;
+        let markdown = Some(readable_markdown(&html))
+            .filter(|m| include_body.unwrap_or(false) && !m.is_empty());
+        let capture = Capture {
+            url: Some(url),
+            title: page_title(&html),
+            markdown,
+            tags: vec![TOREAD_TAG.to_string()],
+        };
+
+        match note {
+            Some(path) => append_capture(&vault_path, &db, &path, &capture),
+            None => capture_note(&vault_path, &db, &capture),
+        }
+    })
+    .await
+}
+
+/// The capture pipeline shared by the command and the clipper endpoint
+pub(crate) fn capture_note(
+    vault_path: &Path,
+    db: &Database,
+    capture: &Capture,
+) -> Result<db_notes::NoteMeta, ChronicleError> {
+    let config = AppConfig::load();
+    let now = Local::now();
+
+    let title = capture_title(capture, now.naive_local());
+    let id = new_note_id(&db.conn(), &config, now.naive_local())?;
+    let path = new_note_path(&title, None, &config.new_notes, now.naive_local(), id.as_deref());
+    let full_path = resolve_vault_path(vault_path, &path)?;
+
+    // Clipping the same page twice gets a second note rather than an error
+    let dir = full_path.parent().unwrap_or(vault_path);
+    fs::create_dir_all(dir)?;
+    let file_name = path.rsplit('/').next().unwrap_or(&path);
+    let file_name = unique_file_name(dir, file_name);
+    let path = match path.rsplit_once('/') {
+        Some((folder, _)) => format!("{}/{}", folder, file_name),
+        None => file_name,
+    };
+    let full_path = resolve_vault_path(vault_path, &path)?;
+
+    fs::write(&full_path, capture_content(capture, &title, &now.to_rfc3339()))?;
+
+    let indexer = Indexer::new(vault_path.to_path_buf())?;
+    indexer.index_file(db, &full_path)?;
+
+    let conn = db.conn();
+    let meta = db_notes::get_note_by_path(&conn, &path)?
+        .ok_or(ChronicleError::NoteNotFound(path))?;
+
+    Ok(meta)
+}
+
+/// Add a capture to the end of a note, creating the note if needed
+pub(crate) fn append_capture(
+    vault_path: &Path,
+    db: &Database,
+    path: &str,
+    capture: &Capture,
+) -> Result<db_notes::NoteMeta, ChronicleError> {
+    let path = if path.ends_with(".md") {
+        path.to_string()
+    } else {
+        format!("{}.md", path)
+    };
+    let full_path = resolve_vault_path(vault_path, &path)?;
+
+    let existing = if full_path.exists() {
+        fs::read_to_string(&full_path)?
+    } else {
+        let stem = full_path.file_stem().map(|s| s.to_string_lossy().to_string());
+        format!("# {}\n", stem.unwrap_or_default())
+    };
+    let title = capture_title(capture, Local::now().naive_local());
+    let content = capture.tags.iter().fold(
+        append_entry(&existing, &capture_entry(capture, &title)),
+        |content, tag| add_frontmatter_tag(&content, tag),
+    );
+
+    if let Some(parent) = full_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&full_path, content)?;
+
+    let indexer = Indexer::new(vault_path.to_path_buf())?;
+    indexer.index_file(db, &full_path)?;
+
+    let conn = db.conn();
+    let meta = db_notes::get_note_by_path(&conn, &path)?
+        .ok_or(ChronicleError::NoteNotFound(path))?;
+
+    Ok(meta)
+}
+
+/// A list item linking the page, or a section when there's a body
+fn capture_entry(capture: &Capture, title: &str) -> String {
+    let url = capture.url.as_deref().map(str::trim).filter(|u| !u.is_empty());
+    let link = match url {
+        Some(url) => format!("[{}]({})", title.replace('[', "\\[").replace(']', "\\]"), url),
+        None => title.to_string(),
+    };
+    match capture.markdown.as_deref().map(str::trim).filter(|m| !m.is_empty()) {
+        Some(markdown) => format!("## {}\n\n{}\n", link, markdown),
+        None => format!("- {}\n", link),
+    }
+}
+
+/// List items join a list the note ends with; anything else gets a blank
+/// line before it
+fn append_entry(content: &str, entry: &str) -> String {
+    let trimmed = content.trim_end();
+    let last_line = trimmed.lines().last().unwrap_or_default();
+    let separator = if entry.starts_with("- ") && last_line.starts_with("- ") {
+        "\n"
+    } else {
+        "\n\n"
+    };
+    format!("{}{}{}", trimmed, separator, entry)
+}
+
+/// The given title, else the URL without its scheme, else a timestamp
+fn capture_title(capture: &Capture, now: NaiveDateTime) -> String {
+    let non_empty = |s: &Option<String>| {
+        s.as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+    };
+
+    if let Some(title) = non_empty(&capture.title) {
+        return title;
+    }
+    if let Some(url) = non_empty(&capture.url) {
+        let without_scheme = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+        let without_query = without_scheme.split(['?', '#']).next().unwrap_or(without_scheme);
+        let title = without_query.trim_matches('/');
+        if !title.is_empty() {
+            return title.to_string();
+        }
+    }
+    format!("Capture {}", now.format("%Y-%m-%d %H%M%S"))
+}
+
+fn capture_content(capture: &Capture, title: &str, captured_at: &str) -> String {
+    let mut content = format!("# {}\n", title);
+    for tag in &capture.tags {
+        content = add_frontmatter_tag(&content, tag);
+    }
+    if let Some(markdown) = capture.markdown.as_deref().map(str::trim).filter(|m| !m.is_empty()) {
+        content.push('\n');
+        content.push_str(markdown);
+        content.push('\n');
+    }
+
+    if let Some(url) = capture.url.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+        content = set_frontmatter_property(&content, "source", url);
+    }
+    set_frontmatter_property(&content, "captured", captured_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::{get_frontmatter_property, parse_note};
+    use chrono::NaiveDate;
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, 9)
+            .unwrap()
+            .and_hms_opt(14, 30, 5)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_capture_title() {
+        let url = Some("https://example.com/posts/rust/?utm=x#top".to_string());
+        let with_title = Capture {
+            url: url.clone(),
+            title: Some("  Rust Post ".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(capture_title(&with_title, now()), "Rust Post");
+
+        let url_only = Capture {
+            url,
+            ..Default::default()
+        };
+        assert_eq!(capture_title(&url_only, now()), "example.com/posts/rust");
+
+        assert_eq!(
+            capture_title(&Capture::default(), now()),
+            "Capture 2026-03-09 143005"
+        );
+    }
+
+    #[test]
+    fn test_capture_content() {
+        let capture = Capture {
+            url: Some("https://example.com/a".to_string()),
+            title: Some("A".to_string()),
+            markdown: Some("> quoted selection\n".to_string()),
+            tags: vec!["toread".to_string()],
+        };
+        let content = capture_content(&capture, "A", "2026-03-09T14:30:05+00:00");
+
+        let (frontmatter, _) = crate::vault::parse_frontmatter(&content);
+        assert_eq!(frontmatter.unwrap().tags, vec!["toread"]);
+
+        assert_eq!(
+            get_frontmatter_property(&content, "source").as_deref(),
+            Some("https://example.com/a")
+        );
+        assert_eq!(
+            get_frontmatter_property(&content, "captured").as_deref(),
+            Some("2026-03-09T14:30:05+00:00")
+        );
+        assert_eq!(parse_note(&content, "a.md").title, "A");
+        assert!(content.ends_with("# A\n\n> quoted selection\n"));
+    }
+
+    #[test]
+    fn test_capture_note_avoids_overwriting() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let db = Database::open_memory().unwrap();
+        let capture = Capture {
+            title: Some("Same Page".to_string()),
+            ..Default::default()
+        };
+
+        let first = capture_note(temp.path(), &db, &capture).unwrap();
+        let second = capture_note(temp.path(), &db, &capture).unwrap();
+        assert_ne!(first.path, second.path);
+        assert!(second.path.ends_with("same-page-1.md"));
+    }
+
+    #[test]
+    fn test_append_capture() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let db = Database::open_memory().unwrap();
+        let page = |title: &str, markdown: Option<&str>| Capture {
+            url: Some(format!("https://example.com/{}", title.to_lowercase())),
+            title: Some(title.to_string()),
+            markdown: markdown.map(String::from),
+            tags: vec!["toread".to_string()],
+        };
+
+        append_capture(temp.path(), &db, "inbox/reading", &page("Rust [2024]", None)).unwrap();
+        append_capture(temp.path(), &db, "inbox/reading.md", &page("Go", None)).unwrap();
+        let meta = append_capture(temp.path(), &db, "inbox/reading.md", &page("Zig", Some("Body"))).unwrap();
+
+        assert_eq!(meta.path, "inbox/reading.md");
+        let content = fs::read_to_string(temp.path().join("inbox/reading.md")).unwrap();
+        assert_eq!(
+            content,
+            "---\ntags: [toread]\n---\n# reading\n\n\
+             - [Rust \\[2024\\]](https://example.com/rust [2024])\n\
+             - [Go](https://example.com/go)\n\n\
+             ## [Zig](https://example.com/zig)\n\nBody\n"
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Citation commands
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::citations::{literature_link, parse_bibtex, BibEntry};
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::{notes as db_notes, Database};
+use crate::error::ChronicleError;
+use crate::models::{AppConfig, CitationsConfig};
+use crate::vault::{resolve_vault_path, set_frontmatter_property, Indexer};
+
+/// A bibliography entry with its literature note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Citation {
+    pub key: String,
+    pub entry_type: String,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub year: Option<String>,
+    /// Vault-relative path of the literature note
+    pub note_path: String,
+    pub note_exists: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InsertedCitation {
+    /// Text to insert at the cursor
+    pub text: String,
+    pub citation: Citation,
+    /// The literature note, if this call created it
+    pub created: Option<db_notes::NoteMeta>,
+}
+
+/// Bibliography entries matching every word of `query` (all entries for an
+/// empty query)
+#[tauri::command]
+pub async fn search_citations(
+    query: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<Citation>, ChronicleError> {
+    let vault_path = state.vault_path()?;
+    let config = AppConfig::load().citations;
+
+    blocking(move || {
+        let citations = load_bibliography(&vault_path, &config)?
+            .iter()
+            .filter(|entry| entry.matches(&query))
+            .take(limit.unwrap_or(20))
+            .map(|entry| to_citation(&vault_path, &config, entry))
+            .collect();
+        Ok(citations)
+    })
+    .await
+}
+
+/// Citation text for a cite key, optionally creating its literature note
+#[tauri::command]
+pub async fn insert_citation(
+    key: String,
+    create_note: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<InsertedCitation, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+    let config = AppConfig::load().citations;
+
+    blocking(move || {
+        let entry = load_bibliography(&vault_path, &config)?
+            .into_iter()
+            .find(|entry| entry.key == key)
+            .ok_or_else(|| ChronicleError::CitationNotFound(key.clone()))?;
+
+        let mut citation = to_citation(&vault_path, &config, &entry);
+        let created = if create_note.unwrap_or(false) && !citation.note_exists {
+            let meta = create_literature_note(&vault_path, &db, &citation)?;
+            citation.note_exists = true;
+            Some(meta)
+        } else {
+            None
+        };
+
+        Ok(InsertedCitation {
+            text: format!("[[@{}]]", entry.key),
+            citation,
+            created,
+        })
+    })
+    .await
+}
+
+fn load_bibliography(vault_path: &Path, config: &CitationsConfig) -> Result<Vec<BibEntry>, ChronicleError> {
+    let bib_file = config
+        .bib_file
+        .as_deref()
+        .filter(|f| !f.trim().is_empty())
+        .ok_or_else(|| ChronicleError::InvalidConfig("No bibliography file configured".to_string()))?;
+
+    // The bibliography often lives outside the vault (e.g. a Zotero export)
+    let path = PathBuf::from(bib_file);
+    let path = if path.is_absolute() {
+        path
+    } else {
+        resolve_vault_path(vault_path, bib_file)?
+    };
+
+    Ok(parse_bibtex(&fs::read_to_string(path)?))
+}
+
+fn to_citation(vault_path: &Path, config: &CitationsConfig, entry: &BibEntry) -> Citation {
+    let note_path = format!("{}.md", literature_link(&entry.key, &config.literature_folder));
+    let note_exists = resolve_vault_path(vault_path, &note_path).is_ok_and(|p| p.exists());
+    Citation {
+        key: entry.key.clone(),
+        entry_type: entry.entry_type.clone(),
+        title: entry.title(),
+        author: entry.author(),
+        year: entry.year(),
+        note_path,
+        note_exists,
+    }
+}
+
+fn create_literature_note(
+    vault_path: &Path,
+    db: &Database,
+    citation: &Citation,
+) -> Result<db_notes::NoteMeta, ChronicleError> {
+    let full_path = resolve_vault_path(vault_path, &citation.note_path)?;
+    if let Some(parent) = full_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let heading = citation.title.as_deref().unwrap_or(&citation.key);
+    let mut content = format!("# {}\n\n", heading);
+    let fields = [
+        ("year", citation.year.as_deref()),
+        ("author", citation.author.as_deref()),
+        ("citekey", Some(citation.key.as_str())),
+    ];
+    for (key, value) in fields {
+        if let Some(value) = value {
+            content = set_frontmatter_property(&content, key, value);
+        }
+    }
+    fs::write(&full_path, content)?;
+
+    let indexer = Indexer::new(vault_path.to_path_buf())?;
+    indexer.index_file(db, &full_path)?;
+
+    let conn = db.conn();
+    db_notes::get_note_by_path(&conn, &citation.note_path)?
+        .ok_or_else(|| ChronicleError::NoteNotFound(citation.note_path.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::get_frontmatter_property;
+
+    #[test]
+    fn test_literature_note() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let db = Database::open_memory().unwrap();
+        fs::write(
+            temp.path().join("refs.bib"),
+            "@article{smith2020, title = {Notes}, author = {Smith, Jane}, year = 2020}",
+        )
+        .unwrap();
+        let config = CitationsConfig {
+            bib_file: Some("refs.bib".to_string()),
+            ..Default::default()
+        };
+
+        let entries = load_bibliography(temp.path(), &config).unwrap();
+        let citation = to_citation(temp.path(), &config, &entries[0]);
+        assert_eq!(citation.note_path, "literature/smith2020.md");
+        assert!(!citation.note_exists);
+
+        let meta = create_literature_note(temp.path(), &db, &citation).unwrap();
+        assert_eq!(meta.title, "Notes");
+        let content = fs::read_to_string(temp.path().join("literature/smith2020.md")).unwrap();
+        assert_eq!(get_frontmatter_property(&content, "citekey").as_deref(), Some("smith2020"));
+        assert_eq!(get_frontmatter_property(&content, "author").as_deref(), Some("Smith, Jane"));
+        assert!(to_citation(temp.path(), &config, &entries[0]).note_exists);
+    }
+
+    #[test]
+    fn test_missing_bibliography_config() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let result = load_bibliography(temp.path(), &CitationsConfig::default());
+        assert!(matches!(result, Err(ChronicleError::InvalidConfig(_))));
+    }
+}
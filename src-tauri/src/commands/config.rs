@@ -1,5 +1,11 @@
 //! Configuration commands
 
+use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
 use crate::error::ChronicleError;
 use crate::models::AppConfig;
 
@@ -12,5 +18,112 @@ pub async fn get_config() -> Result<AppConfig, ChronicleError> {
 /// Save config
 #[tauri::command]
 pub async fn save_config(config: AppConfig) -> Result<(), ChronicleError> {
+    config.validate().map_err(ChronicleError::InvalidConfig)?;
     config.save().map_err(|e| ChronicleError::Io(e.to_string()))
 }
+
+/// Get the contents of the user stylesheet configured in the theme section
+#[tauri::command]
+pub async fn get_custom_css() -> Result<Option<String>, ChronicleError> {
+    let config = AppConfig::load();
+    config.theme.validate().map_err(ChronicleError::InvalidConfig)?;
+
+    match config.theme.custom_css_path() {
+        Some(path) => Ok(Some(fs::read_to_string(path)?)),
+        None => Ok(None),
+    }
+}
+
+/// Watches the config file and emits `config-changed` with the new
+/// `AppConfig` when it is edited outside the app (dotfile managers,
+/// synced settings folders).
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl ConfigWatcher {
+    /// Start watching the config directory
+    pub fn start(app: AppHandle) -> Result<Self, notify::Error> {
+        let config_path = AppConfig::config_path();
+        let config_dir = config_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."));
+
+        // Editors often replace the file instead of writing in place, so
+        // watch the directory rather than the file itself.
+        fs::create_dir_all(&config_dir).map_err(notify::Error::io)?;
+
+        let last = Mutex::new(AppConfig::load());
+
+        let mut watcher = RecommendedWatcher::new(
+            move |res: Result<Event, notify::Error>| {
+                let Ok(event) = res else {
+                    return;
+                };
+                if !is_config_event(&event, &config_path) {
+                    return;
+                }
+
+                match AppConfig::try_load() {
+                    Ok(config) => {
+                        let Ok(mut last) = last.lock() else {
+                            return;
+                        };
+                        if *last != config {
+                            if last.server != config.server {
+                                crate::server::apply_config(&app, &config.server);
+                            }
+                            *last = config.clone();
+                            let _ = app.emit("config-changed", config);
+                        }
+                    }
+                    // Half-written or invalid files are ignored until the next change
+                    Err(e) => tracing::warn!(error = %e, "Failed to reload config"),
+                }
+            },
+            Config::default(),
+        )?;
+
+        watcher.watch(&config_dir, RecursiveMode::NonRecursive)?;
+
+        Ok(Self { _watcher: watcher })
+    }
+}
+
+/// Check whether a raw notify event touches the config file
+fn is_config_event(event: &Event, config_path: &Path) -> bool {
+    let relevant_kind = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_));
+    relevant_kind
+        && event
+            .paths
+            .iter()
+            .any(|p| p.file_name() == config_path.file_name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, ModifyKind, RemoveKind};
+
+    #[test]
+    fn test_is_config_event() {
+        let config_path = Path::new("/home/user/.config/chronicle/config.toml");
+
+        let modified = Event::new(EventKind::Modify(ModifyKind::Any))
+            .add_path(PathBuf::from("/home/user/.config/chronicle/config.toml"));
+        assert!(is_config_event(&modified, config_path));
+
+        let created = Event::new(EventKind::Create(CreateKind::File))
+            .add_path(PathBuf::from("/home/user/.config/chronicle/config.toml"));
+        assert!(is_config_event(&created, config_path));
+
+        let other_file = Event::new(EventKind::Modify(ModifyKind::Any))
+            .add_path(PathBuf::from("/home/user/.config/chronicle/config.toml.swp"));
+        assert!(!is_config_event(&other_file, config_path));
+
+        let removed = Event::new(EventKind::Remove(RemoveKind::File))
+            .add_path(PathBuf::from("/home/user/.config/chronicle/config.toml"));
+        assert!(!is_config_event(&removed, config_path));
+    }
+}
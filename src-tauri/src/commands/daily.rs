@@ -1,20 +1,23 @@
 //! Daily notes commands
 
 use chrono::{Datelike, Duration, Local, NaiveDate};
+use std::collections::HashMap;
 use std::fs;
-use std::sync::Mutex;
 use tauri::State;
 
+use crate::commands::{blocking, ensure_downloaded};
 use crate::commands::vault::AppState;
 use crate::db::notes as db_notes;
 use crate::error::ChronicleError;
-use crate::models::{AppConfig, DailyNotesConfig};
-use crate::vault::Indexer;
+use crate::models::{AppConfig, DailyNotesConfig, LocaleConfig};
+use crate::vault::{
+    is_placeholder, render_template, resolve_vault_path, Indexer, TemplateContext,
+};
 
 /// Get or create today's daily note
 #[tauri::command]
 pub async fn get_or_create_today(
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
 ) -> Result<db_notes::NoteMeta, ChronicleError> {
     let today = Local::now().date_naive();
     get_or_create_daily_note_for_date(today, state).await
@@ -24,7 +27,7 @@ pub async fn get_or_create_today(
 #[tauri::command]
 pub async fn get_or_create_daily_note(
     date: String,
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
 ) -> Result<db_notes::NoteMeta, ChronicleError> {
     let parsed_date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
         .map_err(|_| ChronicleError::InvalidDate(date.clone()))?;
@@ -36,7 +39,7 @@ pub async fn get_or_create_daily_note(
 pub async fn navigate_daily_note(
     current_date: String,
     direction: String,
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
 ) -> Result<db_notes::NoteMeta, ChronicleError> {
     let parsed_date = NaiveDate::parse_from_str(&current_date, "%Y-%m-%d")
         .map_err(|_| ChronicleError::InvalidDate(current_date.clone()))?;
@@ -53,34 +56,34 @@ pub async fn navigate_daily_note(
 /// List all daily notes
 #[tauri::command]
 pub async fn list_daily_notes(
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
 ) -> Result<Vec<DailyNoteInfo>, ChronicleError> {
-    let app_state = state.lock().expect("Failed to lock state");
-    let db = app_state.db.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-    let conn = db.conn();
-    
-    let config = AppConfig::load();
-    let daily_folder = &config.daily_notes.folder;
-    
-    // Get all notes in the daily folder
-    let all_notes = db_notes::list_notes(&conn)?;
-    let daily_notes: Vec<DailyNoteInfo> = all_notes
-        .into_iter()
-        .filter(|n| n.path.starts_with(daily_folder))
-        .filter_map(|n| {
-            // Try to extract date from filename
-            let filename = n.path.rsplit('/').next()?.trim_end_matches(".md");
-            let date = NaiveDate::parse_from_str(filename, "%Y-%m-%d").ok()?;
-            Some(DailyNoteInfo {
-                path: n.path,
-                title: n.title,
-                date: date.format("%Y-%m-%d").to_string(),
-                word_count: n.word_count,
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+
+        let config = AppConfig::load();
+
+        // Get all notes in the daily folder
+        let all_notes = db_notes::list_notes(&conn)?;
+        let daily_notes: Vec<DailyNoteInfo> = all_notes
+            .into_iter()
+            .filter_map(|n| {
+                let date = daily_note_date(&n.path, &config.daily_notes, &config.locale)?;
+                Some(DailyNoteInfo {
+                    path: n.path,
+                    title: n.title,
+                    date: date.format("%Y-%m-%d").to_string(),
+                    display_date: config.locale.display_date(&date),
+                    word_count: n.word_count,
+                })
             })
-        })
-        .collect();
-    
-    Ok(daily_notes)
+            .collect();
+
+        Ok(daily_notes)
+    })
+    .await
 }
 
 /// Get the daily note path for a date
@@ -89,29 +92,28 @@ pub async fn get_daily_note_path(date: String) -> Result<String, ChronicleError>
     let config = AppConfig::load();
     let parsed_date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
         .map_err(|_| ChronicleError::InvalidDate(date))?;
-    Ok(format_daily_note_path(&parsed_date, &config.daily_notes))
+    Ok(format_daily_note_path(&parsed_date, &config.daily_notes, &config.locale))
 }
 
 /// Check if a daily note exists for a date
 #[tauri::command]
 pub async fn daily_note_exists(
     date: String,
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
 ) -> Result<bool, ChronicleError> {
-    let app_state = state.lock().expect("Failed to lock state");
-    let vault_path = app_state
-        .vault_path
-        .as_ref()
-        .ok_or(ChronicleError::NoVaultOpen)?;
-    
-    let config = AppConfig::load();
-    let parsed_date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
-        .map_err(|_| ChronicleError::InvalidDate(date))?;
-    
-    let path = format_daily_note_path(&parsed_date, &config.daily_notes);
-    let full_path = vault_path.join(&path);
-    
-    Ok(full_path.exists())
+    let vault_path = state.vault_path()?;
+
+    blocking(move || {
+        let config = AppConfig::load();
+        let parsed_date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|_| ChronicleError::InvalidDate(date))?;
+
+        let path = format_daily_note_path(&parsed_date, &config.daily_notes, &config.locale);
+        let full_path = resolve_vault_path(&vault_path, &path)?;
+
+        Ok(full_path.exists() || is_placeholder(&full_path))
+    })
+    .await
 }
 
 /// Daily note summary info
@@ -119,7 +121,10 @@ pub async fn daily_note_exists(
 pub struct DailyNoteInfo {
     pub path: String,
     pub title: String,
+    /// Canonical date (YYYY-MM-DD)
     pub date: String,
+    /// Date formatted with the locale settings
+    pub display_date: String,
     pub word_count: i32,
 }
 
@@ -127,63 +132,98 @@ pub struct DailyNoteInfo {
 
 async fn get_or_create_daily_note_for_date(
     date: NaiveDate,
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
 ) -> Result<db_notes::NoteMeta, ChronicleError> {
     let config = AppConfig::load();
-    let daily_config = &config.daily_notes;
+    let path = format_daily_note_path(&date, &config.daily_notes, &config.locale);
     
-    let path = format_daily_note_path(&date, daily_config);
-    
-    let app_state = state.lock().expect("Failed to lock state");
-    let vault_path = app_state
-        .vault_path
-        .as_ref()
-        .ok_or(ChronicleError::NoVaultOpen)?;
-    let db = app_state.db.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-    
-    let full_path = vault_path.join(&path);
-    
-    // Check if note exists
-    if !full_path.exists() {
-        // Create the daily notes folder if needed
-        if let Some(parent) = full_path.parent() {
-            fs::create_dir_all(parent)?;
+    let vault_path = state.vault_path()?;
+    let db = state.db()?;
+    let writable = state.writable_vault_path();
+
+    blocking(move || {
+        let full_path = resolve_vault_path(&vault_path, &path)?;
+        // A daily note made on another device may not be downloaded yet
+        ensure_downloaded(&full_path, &path)?;
+
+        // Check if note exists
+        if !full_path.exists() {
+            writable?;
+            // Create the daily notes folder if needed
+            if let Some(parent) = full_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            // Generate content from template
+            let content = render_daily_template(&date, &config.daily_notes, &config.locale);
+            fs::write(&full_path, &content)?;
+
+            // Index the new note
+            let indexer = Indexer::new(vault_path.clone())?;
+            indexer.index_file(&db, &full_path)?;
         }
-        
-        // Generate content from template
-        let content = render_daily_template(&date, daily_config);
-        fs::write(&full_path, &content)?;
-        
-        // Index the new note
-        let indexer = Indexer::new(vault_path.clone())?;
-        indexer.index_file(db, &full_path)?;
+
+        let conn = db.conn();
+        let meta = db_notes::get_note_by_path(&conn, &path)?
+            .ok_or_else(|| ChronicleError::NoteNotFound(path))?;
+
+        Ok(meta)
+    })
+    .await
+}
+
+/// Date of a daily note, if `path` is one: it must be in the daily folder
+/// with a filename in the configured date format, as written for `locale`
+pub(crate) fn daily_note_date(
+    path: &str,
+    config: &DailyNotesConfig,
+    locale: &LocaleConfig,
+) -> Option<NaiveDate> {
+    if !path.starts_with(&config.folder) {
+        return None;
     }
-    
-    let conn = db.conn();
-    let meta = db_notes::get_note_by_path(&conn, &path)?
-        .ok_or_else(|| ChronicleError::NoteNotFound(path))?;
-    
-    Ok(meta)
+    let filename = path.rsplit('/').next()?.trim_end_matches(".md");
+    if let Ok(date) = NaiveDate::parse_from_str(filename, &config.date_format) {
+        return Some(date);
+    }
+
+    // chrono only parses English month and weekday names, so match the
+    // filename against every day of each year it may name instead
+    filename
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|digits| digits.len() == 4)
+        .filter_map(|digits| NaiveDate::from_ymd_opt(digits.parse().ok()?, 1, 1))
+        .flat_map(|first| first.iter_days().take_while(move |d| d.year() == first.year()))
+        .find(|date| locale.format_date(date, &config.date_format) == filename)
 }
 
-fn format_daily_note_path(date: &NaiveDate, config: &DailyNotesConfig) -> String {
-    let date_str = date.format(&config.date_format).to_string();
+pub(crate) fn format_daily_note_path(
+    date: &NaiveDate,
+    config: &DailyNotesConfig,
+    locale: &LocaleConfig,
+) -> String {
+    let date_str = locale.format_date(date, &config.date_format);
     format!("{}/{}.md", config.folder, date_str)
 }
 
-fn render_daily_template(date: &NaiveDate, config: &DailyNotesConfig) -> String {
-    let date_str = date.format(&config.date_format).to_string();
-    let prev_date = (*date - Duration::days(1)).format(&config.date_format).to_string();
-    let next_date = (*date + Duration::days(1)).format(&config.date_format).to_string();
+fn render_daily_template(
+    date: &NaiveDate,
+    config: &DailyNotesConfig,
+    locale: &LocaleConfig,
+) -> String {
+    let date_str = locale.format_date(date, &config.date_format);
+    let prev_date = locale.format_date(&(*date - Duration::days(1)), &config.date_format);
+    let next_date = locale.format_date(&(*date + Duration::days(1)), &config.date_format);
     
     let mut content = config.template.clone();
     
     // Replace template variables
     content = content.replace("{{date}}", &date_str);
+    content = content.replace("{{display_date}}", &locale.display_date(date));
     content = content.replace("{{year}}", &date.year().to_string());
     content = content.replace("{{month}}", &format!("{:02}", date.month()));
     content = content.replace("{{day}}", &format!("{:02}", date.day()));
-    content = content.replace("{{weekday}}", &date.weekday().to_string());
+    content = content.replace("{{weekday}}", &locale.format_date(date, "%A"));
     content = content.replace("{{previous_date}}", &prev_date);
     content = content.replace("{{next_date}}", &next_date);
     
@@ -205,8 +245,18 @@ fn render_daily_template(date: &NaiveDate, config: &DailyNotesConfig) -> String
             .collect::<Vec<_>>()
             .join("\n");
     }
-    
-    content
+
+    // Date arithmetic, times and filters; prompts stay empty since daily
+    // notes are created without asking
+    let ctx = TemplateContext {
+        date: *date,
+        now: Local::now().naive_local(),
+        date_format: &config.date_format,
+        locale,
+        title: Some(&date_str),
+        prompts: &HashMap::new(),
+    };
+    render_template(&content, &ctx)
 }
 
 #[cfg(test)]
@@ -217,7 +267,7 @@ mod tests {
     fn test_format_daily_note_path() {
         let config = DailyNotesConfig::default();
         let date = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
-        let path = format_daily_note_path(&date, &config);
+        let path = format_daily_note_path(&date, &config, &LocaleConfig::default());
         assert_eq!(path, "daily/2026-03-09.md");
     }
     
@@ -228,7 +278,7 @@ mod tests {
             ..Default::default()
         };
         let date = NaiveDate::from_ymd_opt(2026, 1, 15).unwrap();
-        let path = format_daily_note_path(&date, &config);
+        let path = format_daily_note_path(&date, &config, &LocaleConfig::default());
         assert_eq!(path, "journal/2026-01-15.md");
     }
     
@@ -236,7 +286,7 @@ mod tests {
     fn test_render_daily_template() {
         let config = DailyNotesConfig::default();
         let date = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
-        let content = render_daily_template(&date, &config);
+        let content = render_daily_template(&date, &config, &LocaleConfig::default());
         
         assert!(content.contains("# 2026-03-09"));
         assert!(content.contains("2026-03-08")); // Previous day link
@@ -252,7 +302,7 @@ mod tests {
             ..Default::default()
         };
         let date = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
-        let content = render_daily_template(&date, &config);
+        let content = render_daily_template(&date, &config, &LocaleConfig::default());
         
         // chrono weekday format is "Sun" 
         assert!(content.contains("2026-03-09"));
@@ -261,6 +311,38 @@ mod tests {
         assert!(!content.contains("{{weekday}}"));
     }
     
+    #[test]
+    fn test_render_daily_template_date_arithmetic() {
+        let config = DailyNotesConfig {
+            template: "Review on {{date:+7d:YYYY-MM-DD}} ({{date:+1m:%B}})".to_string(),
+            ..Default::default()
+        };
+        let date = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
+        let content = render_daily_template(&date, &config, &LocaleConfig::default());
+        assert_eq!(content, "Review on 2026-03-16 (April)");
+    }
+
+    #[test]
+    fn test_localized_daily_note_path_and_weekday() {
+        let config = DailyNotesConfig {
+            date_format: "%Y-%m-%d %A".to_string(),
+            template: "{{weekday}} ({{display_date}})".to_string(),
+            ..Default::default()
+        };
+        let locale = LocaleConfig {
+            locale: "fr_FR".to_string(),
+            date_format: "%d %B %Y".to_string(),
+            ..Default::default()
+        };
+        let date = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
+
+        let path = format_daily_note_path(&date, &config, &locale);
+        assert_eq!(path, "daily/2026-03-09 lundi.md");
+
+        let content = render_daily_template(&date, &config, &locale);
+        assert_eq!(content, "lundi (09 mars 2026)");
+    }
+    
     #[test]
     fn test_render_template_variables() {
         let config = DailyNotesConfig {
@@ -268,13 +350,42 @@ mod tests {
             ..Default::default()
         };
         let date = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
-        let content = render_daily_template(&date, &config);
+        let content = render_daily_template(&date, &config, &LocaleConfig::default());
         
         assert!(content.contains("Year: 2026"));
         assert!(content.contains("Month: 03"));
         assert!(content.contains("Day: 09"));
     }
     
+    #[test]
+    fn test_daily_note_date() {
+        let config = DailyNotesConfig::default();
+        let locale = LocaleConfig::default();
+        assert_eq!(
+            daily_note_date("daily/2026-03-09.md", &config, &locale),
+            NaiveDate::from_ymd_opt(2026, 3, 9)
+        );
+        assert_eq!(daily_note_date("notes/2026-03-09.md", &config, &locale), None);
+        assert_eq!(daily_note_date("daily/ideas.md", &config, &locale), None);
+    }
+
+    #[test]
+    fn test_daily_note_date_localized() {
+        let config = DailyNotesConfig {
+            date_format: "%Y-%m-%d %A".to_string(),
+            ..Default::default()
+        };
+        let locale = LocaleConfig {
+            locale: "fr_FR".to_string(),
+            ..Default::default()
+        };
+        let date = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
+        let path = format_daily_note_path(&date, &config, &locale);
+        assert_eq!(path, "daily/2026-03-09 lundi.md");
+        assert_eq!(daily_note_date(&path, &config, &locale), Some(date));
+        assert_eq!(daily_note_date("daily/2026-03-09 mardi.md", &config, &locale), None);
+    }
+
     #[test]
     fn test_default_daily_config() {
         let config = DailyNotesConfig::default();
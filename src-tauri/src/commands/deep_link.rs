@@ -0,0 +1,229 @@
+//! `chronicle://` links from other apps (calendars, task managers, email)
+//!
+//! - `chronicle://open?vault=work&note=projects/alpha.md`
+//! - `chronicle://new?vault=work&title=Call%20notes&content=...`
+//!
+//! `vault` is optional and names the vault by folder name or absolute
+//! path. Links are queued and announced with a `deep-link` event; the
+//! frontend takes them with `take_deep_links`, which also picks up a link
+//! that launched the app before the frontend was listening.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use url::Url;
+
+use crate::commands::vault::AppState;
+use crate::error::ChronicleError;
+use crate::models::AppConfig;
+
+pub const DEEP_LINK_SCHEME: &str = "chronicle";
+
+/// What a link asks for
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum DeepLink {
+    Open {
+        vault: Option<String>,
+        /// Vault-relative note path; without one the vault is just opened
+        note: Option<String>,
+    },
+    New {
+        vault: Option<String>,
+        title: String,
+        content: Option<String>,
+    },
+}
+
+/// A link ready for the frontend to act on
+#[derive(Debug, Clone, Serialize)]
+pub struct DeepLinkRequest {
+    #[serde(flatten)]
+    pub link: DeepLink,
+    /// Vault to open first, when the link names one that isn't open
+    pub vault_path: Option<String>,
+}
+
+/// Links received but not yet taken by the frontend
+#[derive(Default)]
+pub struct DeepLinkQueue(Mutex<Vec<DeepLinkRequest>>);
+
+/// Parse a `chronicle://` URL
+pub fn parse_deep_link(url: &str) -> Result<DeepLink, ChronicleError> {
+    let invalid = |reason: &str| ChronicleError::InvalidPath(format!("{}: {}", reason, url));
+    let parsed = Url::parse(url).map_err(|_| invalid("Not a valid link"))?;
+    if parsed.scheme() != DEEP_LINK_SCHEME {
+        return Err(invalid("Not a chronicle:// link"));
+    }
+
+    let param = |name: &str| {
+        parsed
+            .query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let vault = param("vault");
+
+    // `chronicle://open` has the action as host; `chronicle:open` as path
+    let action = parsed.host_str().unwrap_or_else(|| parsed.path().trim_matches('/'));
+    match action {
+        "open" => Ok(DeepLink::Open {
+            vault,
+            note: param("note").map(|note| note_path(&note)),
+        }),
+        "new" => Ok(DeepLink::New {
+            vault,
+            title: param("title").ok_or_else(|| invalid("Link has no title"))?,
+            content: parsed
+                .query_pairs()
+                .find(|(key, _)| key == "content")
+                .map(|(_, value)| value.to_string()),
+        }),
+        _ => Err(invalid("Unknown link action")),
+    }
+}
+
+/// Notes can be linked without their `.md` extension
+fn note_path(note: &str) -> String {
+    let note = note.trim_start_matches('/');
+    if Path::new(note).extension().is_some() {
+        note.to_string()
+    } else {
+        format!("{}.md", note)
+    }
+}
+
+/// The vault a link names, or `None` if it is the open one. A name matches
+/// the folder name of the open vault or the configured one, ignoring case.
+pub fn resolve_link_vault(
+    name: &str,
+    open_vault: Option<&Path>,
+    configured_vault: Option<&Path>,
+) -> Result<Option<PathBuf>, ChronicleError> {
+    let matches = |path: &Path| {
+        let path_matches = Path::new(name).is_absolute() && path == Path::new(name);
+        let name_matches = path
+            .file_name()
+            .is_some_and(|folder| folder.to_string_lossy().eq_ignore_ascii_case(name));
+        path_matches || name_matches
+    };
+
+    if open_vault.is_some_and(matches) {
+        return Ok(None);
+    }
+    if let Some(configured) = configured_vault.filter(|path| matches(path)) {
+        return Ok(Some(configured.to_path_buf()));
+    }
+    if Path::new(name).is_absolute() && Path::new(name).is_dir() {
+        return Ok(Some(PathBuf::from(name)));
+    }
+    Err(ChronicleError::VaultNotFound(name.to_string()))
+}
+
+/// Queue links the app was opened with and tell the frontend. Links that
+/// can't be followed are logged and dropped.
+pub fn queue_deep_links(app: &AppHandle, urls: impl IntoIterator<Item = String>) {
+    let open_vault = app.state::<AppState>().root();
+    let configured_vault = AppConfig::load().vault.path.map(PathBuf::from);
+
+    let mut queued = false;
+    for url in urls {
+        let request = parse_deep_link(&url).and_then(|link| {
+            let vault = match &link {
+                DeepLink::Open { vault, .. } | DeepLink::New { vault, .. } => vault.as_deref(),
+            };
+            let vault_path = match vault {
+                Some(name) => {
+                    resolve_link_vault(name, open_vault.as_deref(), configured_vault.as_deref())?
+                }
+                None => None,
+            };
+            Ok(DeepLinkRequest {
+                link,
+                vault_path: vault_path.map(|path| path.to_string_lossy().to_string()),
+            })
+        });
+        match request {
+            Ok(request) => {
+                tracing::info!(url = %url, "Received deep link");
+                lock_queue(&app.state::<DeepLinkQueue>()).push(request);
+                queued = true;
+            }
+            Err(e) => tracing::warn!(url = %url, error = %e, "Ignoring deep link"),
+        }
+    }
+
+    if queued {
+        let _ = app.emit("deep-link", ());
+    }
+}
+
+fn lock_queue(queue: &DeepLinkQueue) -> std::sync::MutexGuard<'_, Vec<DeepLinkRequest>> {
+    queue.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Take the links received since the last call, oldest first
+#[tauri::command]
+pub async fn take_deep_links(
+    queue: State<'_, DeepLinkQueue>,
+) -> Result<Vec<DeepLinkRequest>, ChronicleError> {
+    Ok(std::mem::take(&mut *lock_queue(&queue)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_deep_link() {
+        assert_eq!(
+            parse_deep_link("chronicle://open?vault=work&note=projects/alpha.md").unwrap(),
+            DeepLink::Open {
+                vault: Some("work".to_string()),
+                note: Some("projects/alpha.md".to_string()),
+            }
+        );
+        assert_eq!(
+            parse_deep_link("chronicle://open?note=Inbox").unwrap(),
+            DeepLink::Open {
+                vault: None,
+                note: Some("Inbox.md".to_string()),
+            }
+        );
+        assert_eq!(
+            parse_deep_link("chronicle://new?title=Call%20notes&content=-+item%0A").unwrap(),
+            DeepLink::New {
+                vault: None,
+                title: "Call notes".to_string(),
+                content: Some("- item\n".to_string()),
+            }
+        );
+        assert!(parse_deep_link("chronicle://new?content=x").is_err());
+        assert!(parse_deep_link("chronicle://delete?note=a.md").is_err());
+        assert!(parse_deep_link("https://open?note=a.md").is_err());
+    }
+
+    #[test]
+    fn test_resolve_link_vault() {
+        let temp = TempDir::new().unwrap();
+        let work = temp.path().join("Work");
+        let home = temp.path().join("home");
+
+        assert_eq!(resolve_link_vault("work", Some(&work), Some(&home)).unwrap(), None);
+        assert_eq!(
+            resolve_link_vault("home", Some(&work), Some(&home)).unwrap(),
+            Some(home.clone())
+        );
+        assert!(matches!(
+            resolve_link_vault("other", Some(&work), Some(&home)),
+            Err(ChronicleError::VaultNotFound(_))
+        ));
+
+        std::fs::create_dir_all(&work).unwrap();
+        let path = work.to_string_lossy().to_string();
+        assert_eq!(resolve_link_vault(&path, None, None).unwrap(), Some(work));
+    }
+}
@@ -0,0 +1,67 @@
+//! Draft autosave commands
+
+use chrono::Local;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::error::ChronicleError;
+use crate::vault::{all_drafts, read_draft, remove_draft, resolve_vault_path, write_draft, Draft};
+
+/// Flush the editor's unsaved content for a note
+#[tauri::command]
+pub async fn save_draft(
+    path: String,
+    content: String,
+    state: State<'_, AppState>,
+) -> Result<(), ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+
+    blocking(move || {
+        resolve_vault_path(&vault_path, &path)?;
+        let draft = Draft {
+            path,
+            content,
+            saved_at: Local::now().to_rfc3339(),
+        };
+        write_draft(&vault_path, &draft)?;
+        Ok(())
+    })
+    .await
+}
+
+/// Get the unsaved draft for a note, if any
+#[tauri::command]
+pub async fn get_draft(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<Option<Draft>, ChronicleError> {
+    let vault_path = state.vault_path()?;
+
+    blocking(move || {
+        resolve_vault_path(&vault_path, &path)?;
+        Ok(read_draft(&vault_path, &path)?)
+    })
+    .await
+}
+
+/// Throw away the draft for a note
+#[tauri::command]
+pub async fn discard_draft(path: String, state: State<'_, AppState>) -> Result<(), ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+
+    blocking(move || {
+        resolve_vault_path(&vault_path, &path)?;
+        remove_draft(&vault_path, &path)?;
+        Ok(())
+    })
+    .await
+}
+
+/// List every draft in the vault, e.g. to offer recovery after a crash
+#[tauri::command]
+pub async fn list_drafts(state: State<'_, AppState>) -> Result<Vec<Draft>, ChronicleError> {
+    let vault_path = state.vault_path()?;
+
+    blocking(move || Ok(all_drafts(&vault_path)?)).await
+}
@@ -0,0 +1,125 @@
+//! Import of saved emails (`.eml` files) as notes
+
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::attachments::store_attachment;
+use crate::commands::blocking;
+use crate::commands::notes::sanitize_filename;
+use crate::commands::vault::AppState;
+use crate::db::{notes as db_notes, Database};
+use crate::error::ChronicleError;
+use crate::vault::{
+    email_note, parse_email, resolve_vault_path, unique_file_name, Email, Indexer, VaultFs,
+};
+
+/// Outcome of an email import
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EmailImportReport {
+    /// Notes created, one per email
+    pub imported: Vec<db_notes::NoteMeta>,
+    /// Files that couldn't be read as emails
+    pub failed: Vec<String>,
+}
+
+/// Turn saved emails into notes in `folder`: headers go to the frontmatter,
+/// the body becomes the note and attachments are stored next to it
+#[tauri::command]
+pub async fn import_eml(
+    files: Vec<String>,
+    folder: String,
+    state: State<'_, AppState>,
+) -> Result<EmailImportReport, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+    let storage = state.storage();
+
+    blocking(move || {
+        let mut report = EmailImportReport::default();
+        for file in files {
+            let Some(email) = fs::read(&file).ok().and_then(|raw| parse_email(&raw)) else {
+                tracing::warn!(path = %file, "Not a readable email");
+                report.failed.push(file);
+                continue;
+            };
+            let meta = import_email(&vault_path, &db, storage.as_deref(), &folder, &email)?;
+            report.imported.push(meta);
+        }
+        Ok(report)
+    })
+    .await
+}
+
+fn import_email(
+    vault_path: &Path,
+    db: &Database,
+    storage: Option<&dyn VaultFs>,
+    folder: &str,
+    email: &Email,
+) -> Result<db_notes::NoteMeta, ChronicleError> {
+    let folder = folder.trim_matches('/');
+    let dir = if folder.is_empty() {
+        vault_path.to_path_buf()
+    } else {
+        resolve_vault_path(vault_path, folder)?
+    };
+    fs::create_dir_all(&dir)?;
+
+    let stem = Some(sanitize_filename(&email.title()))
+        .filter(|s| !s.trim_matches('-').is_empty())
+        .unwrap_or_else(|| "email".to_string());
+    let file_name = unique_file_name(&dir, &format!("{}.md", stem));
+    let path = if folder.is_empty() {
+        file_name
+    } else {
+        format!("{}/{}", folder, file_name)
+    };
+
+    let mut embeds = Vec::new();
+    for (name, data) in &email.attachments {
+        embeds.push(store_attachment(vault_path, storage, &path, name, data)?.embed);
+    }
+    let full_path = resolve_vault_path(vault_path, &path)?;
+    fs::write(&full_path, email_note(email, &embeds))?;
+
+    let indexer = Indexer::new(vault_path.to_path_buf())?;
+    indexer.index_file(db, &full_path)?;
+
+    let conn = db.conn();
+    let meta = db_notes::get_note_by_path(&conn, &path)?
+        .ok_or(ChronicleError::NoteNotFound(path))?;
+
+    Ok(meta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_email() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        let db = Database::open_memory().unwrap();
+        let email = Email {
+            subject: Some("Re: Q3 plan?".to_string()),
+            from: Some("ada@example.com".to_string()),
+            body: "Looks good.".to_string(),
+            attachments: vec![("plan.txt".to_string(), b"plan".to_vec())],
+            ..Default::default()
+        };
+
+        let first = import_email(root, &db, None, "/mail/", &email).unwrap();
+        let second = import_email(root, &db, None, "mail", &email).unwrap();
+        assert_eq!(first.path, "mail/re--q3-plan-.md");
+        assert_eq!(second.path, "mail/re--q3-plan--1.md");
+        assert_eq!(first.title, "Re: Q3 plan?");
+
+        let content = fs::read_to_string(root.join(&first.path)).unwrap();
+        assert!(content.contains("from: ada@example.com"));
+        assert!(content.contains("Looks good."));
+        assert!(content.contains("plan.txt"));
+    }
+}
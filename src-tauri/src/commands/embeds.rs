@@ -0,0 +1,64 @@
+//! Transclusion commands
+
+use rusqlite::Connection;
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::links::resolve_link_target;
+use crate::error::ChronicleError;
+use crate::vault::{resolve_embeds_in, resolve_vault_path, EmbedSource, ResolvedEmbed};
+
+pub(crate) const DEFAULT_EMBED_DEPTH: usize = 3;
+const MAX_EMBED_DEPTH: usize = 10;
+
+/// The content of every `![[embed]]` in a note, following embeds inside
+/// embedded content up to `depth` levels
+#[tauri::command]
+pub async fn resolve_embeds(
+    path: String,
+    depth: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ResolvedEmbed>, ChronicleError> {
+    let vault_path = state.vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let full_path = resolve_vault_path(&vault_path, &path)?;
+        if !full_path.exists() {
+            return Err(ChronicleError::NoteNotFound(path));
+        }
+        let content = fs::read_to_string(&full_path)?;
+
+        let conn = db.conn();
+        let depth = depth.unwrap_or(DEFAULT_EMBED_DEPTH).min(MAX_EMBED_DEPTH);
+        let load = |name: &str| load_embed(&vault_path, &conn, name);
+        Ok(resolve_embeds_in(&path, &content, depth, &load))
+    })
+    .await
+}
+
+/// Look up an embed's link name: notes resolve like links do, other files
+/// by their vault-relative path
+pub(crate) fn load_embed(
+    vault_path: &Path,
+    conn: &Connection,
+    name: &str,
+) -> Option<EmbedSource> {
+    if let Ok(Some(path)) = resolve_link_target(conn, name) {
+        let full_path = resolve_vault_path(vault_path, &path).ok()?;
+        let content = fs::read_to_string(full_path).ok()?;
+        return Some(EmbedSource::Note { path, content });
+    }
+
+    let is_note = Path::new(name).extension().is_none_or(|ext| ext == "md");
+    if is_note {
+        return None;
+    }
+    let full_path = resolve_vault_path(vault_path, name).ok()?;
+    full_path.is_file().then(|| EmbedSource::Attachment {
+        path: name.to_string(),
+    })
+}
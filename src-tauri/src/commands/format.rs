@@ -0,0 +1,59 @@
+//! Markdown formatting commands
+
+use std::fs;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::{links::resolve_link_target, Database};
+use crate::error::ChronicleError;
+use crate::models::{AppConfig, FormattingConfig};
+use crate::vault::{format_markdown, resolve_vault_path, Indexer};
+
+/// Normalize Markdown by the `formatting` config rules. Given `content`,
+/// returns it formatted; given only `path`, formats that note in place.
+/// Either way the formatted text is returned.
+#[tauri::command]
+pub async fn format_note(
+    path: Option<String>,
+    content: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, ChronicleError> {
+    let vault_path = state.vault_path()?;
+    let db = state.db()?;
+    let writable = state.writable_vault_path();
+
+    blocking(move || {
+        let rules = AppConfig::load().formatting;
+        if let Some(content) = content {
+            return Ok(format_with_index(&content, &rules, &db));
+        }
+        writable?;
+
+        let path = path.ok_or_else(|| ChronicleError::InvalidPath(String::new()))?;
+        let full_path = resolve_vault_path(&vault_path, &path)?;
+        if !full_path.exists() {
+            return Err(ChronicleError::NoteNotFound(path));
+        }
+        let original = fs::read_to_string(&full_path)?;
+        let formatted = format_with_index(&original, &rules, &db);
+        if formatted != original {
+            fs::write(&full_path, &formatted)?;
+            Indexer::new(vault_path)?.index_file(&db, &full_path)?;
+        }
+        Ok(formatted)
+    })
+    .await
+}
+
+/// Format Markdown, fixing link casing against the notes in the index
+pub(crate) fn format_with_index(content: &str, rules: &FormattingConfig, db: &Database) -> String {
+    let conn = db.conn();
+    let link_name = |name: &str| {
+        resolve_link_target(&conn, name)
+            .ok()
+            .flatten()
+            .map(|path| path.strip_suffix(".md").map(str::to_string).unwrap_or(path))
+    };
+    format_markdown(content, rules, &link_name)
+}
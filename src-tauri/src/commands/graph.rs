@@ -1,50 +1,458 @@
 //! Graph data commands
 
-use std::sync::Mutex;
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use tauri::State;
 
+use crate::commands::blocking;
 use crate::commands::vault::AppState;
-use crate::db::{links::get_outlinks, notes::list_notes};
+use crate::db::{
+    links::{get_outlinks, link_neighborhood, shortest_link_path},
+    note_ids::{list_note_uids, uid_paths},
+    notes::{get_note_by_id, get_note_by_path, list_notes, NoteMeta},
+    smart_folders::{links_matching, notes_matching},
+    tags::note_tag_pairs,
+    zettel::zettel_paths,
+};
 use crate::error::ChronicleError;
-use crate::models::{GraphData, GraphEdge, GraphNode};
+use crate::models::{GraphData, GraphEdge, GraphNode, GraphNodeKind};
+use crate::sync::{GitRepo, NotesAtRevision};
+use crate::vault::{
+    add_centrality, diff_graphs, graph_from_notes, graph_overview, GraphDiff, GraphOverview,
+    SmartFilter,
+};
 
-/// Get graph data for visualization
+/// A commit a graph comparison was made at
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphRevision {
+    pub commit: String,
+    /// Commit time, RFC 3339
+    pub time: String,
+}
+
+impl From<&NotesAtRevision> for GraphRevision {
+    fn from(notes: &NotesAtRevision) -> Self {
+        Self {
+            commit: notes.commit.clone(),
+            time: DateTime::from_timestamp(notes.time, 0)
+                .map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphComparison {
+    pub from: GraphRevision,
+    pub to: GraphRevision,
+    #[serde(flatten)]
+    pub diff: GraphDiff,
+}
+
+/// Get graph data for visualization. With `include_tags`, tags are added
+/// as nodes linked to their notes, so notes cluster by topic. With
+/// `half_life_days`, edges are weighted by recency (see `weight_by_recency`);
+/// the other graph commands take the same options. Nodes carry their degree
+/// and PageRank, worked out over the returned graph.
 #[tauri::command]
 pub async fn get_graph_data(
-    state: State<'_, Mutex<AppState>>,
+    include_tags: Option<bool>,
+    half_life_days: Option<f64>,
+    state: State<'_, AppState>,
 ) -> Result<GraphData, ChronicleError> {
-    let app_state = state.lock().expect("Failed to lock state");
-    let db = app_state.db.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-    let conn = db.conn();
+    let db = state.db()?;
+    check_half_life(half_life_days)?;
 
+    blocking(move || {
+        let conn = db.conn();
+        let mut graph = vault_graph(&conn)?;
+        if include_tags.unwrap_or(false) {
+            add_tag_nodes(&conn, &mut graph)?;
+        }
+        if let Some(half_life) = half_life_days {
+            weight_by_recency(&conn, &mut graph, half_life, Utc::now())?;
+        }
+        add_centrality(&mut graph);
+        Ok(graph)
+    })
+    .await
+}
+
+/// An overview of the graph for vaults too large to draw whole: the
+/// `max_nodes` (500 unless given) best-connected notes of the largest
+/// connected group and the links among them. The notes left out are
+/// summarized in clusters around the nearest shown note, which the graph
+/// view can expand with `get_local_graph`. Degrees and PageRank are those
+/// of the whole graph.
+#[tauri::command]
+pub async fn get_graph_overview(
+    max_nodes: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<GraphOverview, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let mut graph = vault_graph(&db.conn())?;
+        add_centrality(&mut graph);
+        Ok(graph_overview(&graph, max_nodes.unwrap_or(500)))
+    })
+    .await
+}
+
+/// Every note and the links among them
+fn vault_graph(conn: &Connection) -> Result<GraphData, ChronicleError> {
     // Get all notes as nodes
-    let notes = list_notes(&conn)?;
+    let notes = list_notes(conn)?;
+    let uids = list_note_uids(conn)?;
     let nodes: Vec<GraphNode> = notes
         .iter()
         .map(|n| GraphNode {
             id: n.path.clone(),
+            kind: GraphNodeKind::Note,
+            uid: uids.get(&n.path).cloned(),
             title: n.title.clone(),
             word_count: n.word_count,
+            degree: 0,
+            pagerank: 0.0,
         })
         .collect();
 
     // Get all edges
+    let paths: HashSet<&str> = notes.iter().map(|n| n.path.as_str()).collect();
+    let ids = zettel_paths(conn)?;
+    let uid_paths = uid_paths(conn)?;
     let mut edges = Vec::new();
     for note in &notes {
-        let links = get_outlinks(&conn, note.id)?;
+        let links = get_outlinks(conn, note.id)?;
         for link in links {
             // Only add edge if target exists
-            if notes
-                .iter()
-                .any(|n| n.path == link.target_path || n.path == format!("{}.md", link.target_path))
-            {
+            let target = link.target_path.as_str();
+            if paths.contains(target) || paths.contains(format!("{}.md", target).as_str()) {
                 edges.push(GraphEdge {
                     source: note.path.clone(),
                     target: link.target_path,
+                    weight: None,
+                });
+            } else if let Some(path) = ids
+                .get(&link.target_path.to_lowercase())
+                .or_else(|| uid_paths.get(&link.target_path.to_lowercase()))
+            {
+                // ID links point at the note's path
+                edges.push(GraphEdge {
+                    source: note.path.clone(),
+                    target: path.clone(),
+                    weight: None,
                 });
             }
         }
     }
-
     Ok(GraphData { nodes, edges })
 }
+
+/// The graph of the notes matching a filter (tags, path globs such as
+/// `projects/*`, date ranges, ...) and the links among them. Filtering runs
+/// in the index, so only the matching part of the vault is loaded.
+#[tauri::command]
+pub async fn get_filtered_graph(
+    filter: SmartFilter,
+    include_tags: Option<bool>,
+    half_life_days: Option<f64>,
+    state: State<'_, AppState>,
+) -> Result<GraphData, ChronicleError> {
+    let db = state.db()?;
+    check_half_life(half_life_days)?;
+    filter.validate().map_err(ChronicleError::InvalidFilter)?;
+
+    blocking(move || {
+        let conn = db.conn();
+        let uids = list_note_uids(&conn)?;
+        let nodes = notes_matching(&conn, &filter)?
+            .into_iter()
+            .map(|n| GraphNode {
+                uid: uids.get(&n.path).cloned(),
+                id: n.path,
+                kind: GraphNodeKind::Note,
+                title: n.title,
+                word_count: n.word_count,
+                degree: 0,
+                pagerank: 0.0,
+            })
+            .collect();
+        let edges = links_matching(&conn, &filter)?
+            .into_iter()
+            .map(|(source, target)| GraphEdge {
+                source,
+                target,
+                weight: None,
+            })
+            .collect();
+
+        let mut graph = GraphData { nodes, edges };
+        if include_tags.unwrap_or(false) {
+            add_tag_nodes(&conn, &mut graph)?;
+        }
+        if let Some(half_life) = half_life_days {
+            weight_by_recency(&conn, &mut graph, half_life, Utc::now())?;
+        }
+        add_centrality(&mut graph);
+        Ok(graph)
+    })
+    .await
+}
+
+/// The graph around one note: notes up to `depth` links away (1 unless
+/// given), following links either way, and the links among them
+#[tauri::command]
+pub async fn get_local_graph(
+    path: String,
+    depth: Option<usize>,
+    include_tags: Option<bool>,
+    half_life_days: Option<f64>,
+    state: State<'_, AppState>,
+) -> Result<GraphData, ChronicleError> {
+    let db = state.db()?;
+    check_half_life(half_life_days)?;
+
+    blocking(move || {
+        let conn = db.conn();
+        let note = get_note_by_path(&conn, &path)?.ok_or(ChronicleError::NoteNotFound(path))?;
+        let neighborhood = link_neighborhood(&conn, note.id, depth.unwrap_or(1))?;
+
+        let uids = list_note_uids(&conn)?;
+        let mut paths = HashMap::new();
+        let mut nodes = Vec::new();
+        for id in neighborhood.note_ids {
+            let Some(meta) = get_note_by_id(&conn, id)? else {
+                continue;
+            };
+            paths.insert(id, meta.path.clone());
+            nodes.push(GraphNode {
+                uid: uids.get(&meta.path).cloned(),
+                id: meta.path,
+                kind: GraphNodeKind::Note,
+                title: meta.title,
+                word_count: meta.word_count,
+                degree: 0,
+                pagerank: 0.0,
+            });
+        }
+        let edges = neighborhood
+            .links
+            .into_iter()
+            .filter_map(|(source, target)| {
+                Some(GraphEdge {
+                    source: paths.get(&source)?.clone(),
+                    target: paths.get(&target)?.clone(),
+                    weight: None,
+                })
+            })
+            .collect();
+
+        let mut graph = GraphData { nodes, edges };
+        if include_tags.unwrap_or(false) {
+            add_tag_nodes(&conn, &mut graph)?;
+        }
+        if let Some(half_life) = half_life_days {
+            weight_by_recency(&conn, &mut graph, half_life, Utc::now())?;
+        }
+        add_centrality(&mut graph);
+        Ok(graph)
+    })
+    .await
+}
+
+/// The shortest chain of links from one note to another, both included, to
+/// see how two ideas relate; `None` if nothing connects them. Links are
+/// followed either way unless `directed` is set.
+#[tauri::command]
+pub async fn find_path(
+    from: String,
+    to: String,
+    directed: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Option<Vec<NoteMeta>>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        let from = get_note_by_path(&conn, &from)?.ok_or(ChronicleError::NoteNotFound(from))?;
+        let to = get_note_by_path(&conn, &to)?.ok_or(ChronicleError::NoteNotFound(to))?;
+
+        let either_way = !directed.unwrap_or(false);
+        let Some(ids) = shortest_link_path(&conn, from.id, to.id, either_way)? else {
+            return Ok(None);
+        };
+        let mut notes = Vec::with_capacity(ids.len());
+        for id in ids {
+            let note = get_note_by_id(&conn, id)?;
+            notes.push(note.ok_or_else(|| ChronicleError::NoteNotFound(id.to_string()))?);
+        }
+        Ok(Some(notes))
+    })
+    .await
+}
+
+/// Add a node for each tag carried by the graph's notes, with an edge from
+/// every note carrying it. Tag IDs are `tag:` and the lowercased name, as
+/// tags ignore case.
+fn add_tag_nodes(conn: &Connection, graph: &mut GraphData) -> Result<(), ChronicleError> {
+    let notes: HashSet<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+    let mut tags: BTreeMap<String, String> = BTreeMap::new();
+    let mut edges = Vec::new();
+    for (path, tag) in note_tag_pairs(conn)? {
+        if !notes.contains(path.as_str()) {
+            continue;
+        }
+        let id = format!("tag:{}", tag.to_lowercase());
+        tags.entry(id.clone()).or_insert(tag);
+        edges.push(GraphEdge {
+            source: path,
+            target: id,
+            weight: None,
+        });
+    }
+
+    graph.nodes.extend(tags.into_iter().map(|(id, name)| GraphNode {
+        id,
+        kind: GraphNodeKind::Tag,
+        uid: None,
+        title: format!("#{}", name),
+        word_count: 0,
+        degree: 0,
+        pagerank: 0.0,
+    }));
+    graph.edges.extend(edges);
+    Ok(())
+}
+
+fn check_half_life(half_life_days: Option<f64>) -> Result<(), ChronicleError> {
+    match half_life_days {
+        Some(days) if days.is_nan() || days <= 0.0 => Err(ChronicleError::InvalidFilter(format!(
+            "Half-life must be a positive number of days, not {}",
+            days
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Weight each edge by how recently the note it starts from was changed:
+/// 1 for a note changed now, halving every `half_life_days`, so stale
+/// structure fades and active areas stand out. Links aren't timestamped,
+/// so the note's last modification stands in for when they were made.
+fn weight_by_recency(
+    conn: &Connection,
+    graph: &mut GraphData,
+    half_life_days: f64,
+    now: DateTime<Utc>,
+) -> Result<(), ChronicleError> {
+    let modified: HashMap<String, Option<String>> =
+        list_notes(conn)?.into_iter().map(|n| (n.path, n.modified_at)).collect();
+    for edge in &mut graph.edges {
+        let modified_at = modified.get(&edge.source).and_then(|m| m.as_deref());
+        edge.weight = Some(recency_weight(modified_at, now, half_life_days));
+    }
+    Ok(())
+}
+
+/// 0.5 to the power of the age in half-lives; 0 for unknown times
+fn recency_weight(modified_at: Option<&str>, now: DateTime<Utc>, half_life_days: f64) -> f64 {
+    let Some(modified) = modified_at.and_then(|m| DateTime::parse_from_rfc3339(m).ok()) else {
+        return 0.0;
+    };
+    let age_days = (now - modified.with_timezone(&Utc)).num_seconds().max(0) as f64 / 86_400.0;
+    0.5f64.powf(age_days / half_life_days)
+}
+
+/// How the graph changed between two revisions of a git-synced vault.
+/// Revisions are commits, branches, tags, `HEAD~n` or dates (`YYYY-MM-DD`,
+/// meaning the last commit that day). With `folder`, only notes in it and
+/// their links are compared.
+#[tauri::command]
+pub async fn compare_graph(
+    rev_a: String,
+    rev_b: String,
+    folder: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<GraphComparison, ChronicleError> {
+    let vault_path = state.vault_path()?;
+
+    blocking(move || {
+        if !GitRepo::is_repo(&vault_path) {
+            return Err(ChronicleError::SyncError(
+                "Comparing graphs needs a vault under git".to_string(),
+            ));
+        }
+        let repo = GitRepo::open(&vault_path)?;
+        let before = repo.notes_at(&rev_a)?;
+        let after = repo.notes_at(&rev_b)?;
+
+        let diff = diff_graphs(
+            &graph_from_notes(&before.notes),
+            &graph_from_notes(&after.notes),
+            folder.as_deref(),
+        );
+        Ok(GraphComparison {
+            from: GraphRevision::from(&before),
+            to: GraphRevision::from(&after),
+            diff,
+        })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{notes::upsert_note, tags::set_note_tags, Database};
+
+    #[test]
+    fn test_add_tag_nodes() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let notes = [("a.md", vec!["Rust", "web"]), ("b.md", vec!["rust"]), ("c.md", vec!["go"])];
+        for (path, tags) in notes {
+            let id = upsert_note(&conn, path, path, None, None, "h", 0).unwrap();
+            let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+            set_note_tags(&conn, id, &tags).unwrap();
+        }
+        let note = |path: &str| GraphNode {
+            id: path.to_string(),
+            kind: GraphNodeKind::Note,
+            uid: None,
+            title: path.to_string(),
+            word_count: 0,
+            degree: 0,
+            pagerank: 0.0,
+        };
+        let mut graph = GraphData {
+            nodes: vec![note("a.md"), note("b.md")],
+            edges: Vec::new(),
+        };
+        add_tag_nodes(&conn, &mut graph).unwrap();
+
+        let tags: Vec<(&str, &str)> = graph.nodes[2..]
+            .iter()
+            .filter(|n| n.kind == GraphNodeKind::Tag)
+            .map(|n| (n.id.as_str(), n.title.as_str()))
+            .collect();
+        assert_eq!(tags, vec![("tag:rust", "#Rust"), ("tag:web", "#web")]);
+        let edges: Vec<(&str, &str)> =
+            graph.edges.iter().map(|e| (e.source.as_str(), e.target.as_str())).collect();
+        assert_eq!(edges, vec![("a.md", "tag:rust"), ("a.md", "tag:web"), ("b.md", "tag:rust")]);
+    }
+
+    #[test]
+    fn test_recency_weight() {
+        let now = DateTime::parse_from_rfc3339("2026-03-31T00:00:00Z").unwrap().to_utc();
+        let weight = |modified| recency_weight(modified, now, 30.0);
+        assert_eq!(weight(Some("2026-03-31T00:00:00Z")), 1.0);
+        assert_eq!(weight(Some("2026-03-01T00:00:00Z")), 0.5);
+        assert_eq!(weight(Some("2026-01-30T00:00:00Z")), 0.25);
+        assert_eq!(weight(Some("2026-04-02T00:00:00Z")), 1.0);
+        assert_eq!(weight(None), 0.0);
+        assert!(check_half_life(Some(0.0)).is_err());
+        assert!(check_half_life(Some(f64::NAN)).is_err());
+    }
+}
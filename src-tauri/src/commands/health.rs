@@ -0,0 +1,49 @@
+//! Vault health check command
+
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::{
+    links::get_unresolved_links, notes as db_notes, properties::list_type_warnings,
+};
+use crate::error::ChronicleError;
+use crate::vault::{
+    broken_link_issues, check_vault_files, duplicate_title_issues, group_broken_links,
+    resolve_vault_path, type_schema_issues, HealthReport,
+};
+
+/// Check the open vault for empty notes, unreadable frontmatter, duplicate
+/// titles, broken links, oversized files, files that aren't UTF-8 and notes
+/// missing properties their type requires
+#[tauri::command]
+pub async fn check_vault_health(
+    state: State<'_, AppState>,
+) -> Result<HealthReport, ChronicleError> {
+    let vault_path = state.vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let (files_checked, mut issues) = check_vault_files(&vault_path)?;
+
+        let conn = db.conn();
+        let notes = db_notes::list_notes(&conn)?;
+        let titles: Vec<(String, String)> =
+            notes.iter().map(|n| (n.path.clone(), n.title.clone())).collect();
+        issues.extend(duplicate_title_issues(&titles));
+
+        // Links to attachments and other files that exist aren't broken
+        let links: Vec<_> = get_unresolved_links(&conn)?
+            .into_iter()
+            .filter(|link| {
+                !resolve_vault_path(&vault_path, &link.target).is_ok_and(|path| path.is_file())
+            })
+            .collect();
+        let note_paths: Vec<String> = notes.into_iter().map(|n| n.path).collect();
+        issues.extend(broken_link_issues(&group_broken_links(links, &note_paths)));
+        issues.extend(type_schema_issues(&list_type_warnings(&conn)?));
+
+        Ok(HealthReport::new(files_checked, issues))
+    })
+    .await
+}
@@ -0,0 +1,131 @@
+//! Import from other note apps: Roam Research, Logseq and TiddlyWiki
+
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::daily::format_daily_note_path;
+use crate::commands::vault::AppState;
+use crate::db::Database;
+use crate::error::ChronicleError;
+use crate::models::AppConfig;
+use crate::vault::{
+    convert_roam_export, convert_tiddlywiki, resolve_vault_path, ConvertedPage, Indexer,
+};
+
+/// Outcome of an import
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    /// Notes written, by vault path
+    pub imported: Vec<String>,
+    /// Pages left out because a note already exists at their path
+    pub skipped: Vec<String>,
+}
+
+/// Import the JSON export at `json_export`: each page becomes a note in
+/// `folder`, daily pages become daily notes. Existing notes are never
+/// overwritten.
+#[tauri::command]
+pub async fn import_roam(
+    json_export: String,
+    folder: String,
+    state: State<'_, AppState>,
+) -> Result<ImportReport, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let json = fs::read_to_string(&json_export)?;
+        import_roam_json(&vault_path, &db, &json, &folder)
+    })
+    .await
+}
+
+/// Import a TiddlyWiki JSON export or saved wiki (`.html`) at
+/// `html_or_json`: each tiddler becomes a note in `folder`, with its tags
+/// in the frontmatter. Existing notes are never overwritten.
+#[tauri::command]
+pub async fn import_tiddlywiki(
+    html_or_json: String,
+    folder: String,
+    state: State<'_, AppState>,
+) -> Result<ImportReport, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let data = fs::read_to_string(&html_or_json)?;
+        let pages = convert_tiddlywiki(&data, &folder)
+            .ok_or_else(|| ChronicleError::InvalidImport(html_or_json.clone()))?;
+        write_pages(&vault_path, &db, pages)
+    })
+    .await
+}
+
+fn import_roam_json(
+    vault_path: &Path,
+    db: &Database,
+    json: &str,
+    folder: &str,
+) -> Result<ImportReport, ChronicleError> {
+    let config = AppConfig::load();
+    let pages = convert_roam_export(json, folder, |date| {
+        format_daily_note_path(&date, &config.daily_notes, &config.locale)
+    })?;
+    write_pages(vault_path, db, pages)
+}
+
+/// Write and index imported pages, skipping those whose note exists
+fn write_pages(
+    vault_path: &Path,
+    db: &Database,
+    pages: Vec<ConvertedPage>,
+) -> Result<ImportReport, ChronicleError> {
+    let indexer = Indexer::new(vault_path.to_path_buf())?;
+    let mut report = ImportReport::default();
+    for page in pages {
+        let full_path = resolve_vault_path(vault_path, &page.path)?;
+        if full_path.exists() {
+            report.skipped.push(page.path);
+            continue;
+        }
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&full_path, &page.content)?;
+        indexer.index_file(db, &full_path)?;
+        report.imported.push(page.path);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::links::get_backlinks;
+
+    #[test]
+    fn test_import_roam_json() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir(root.join("roam")).unwrap();
+        fs::write(root.join("roam/Existing.md"), "# Mine\n").unwrap();
+        let db = Database::open_memory().unwrap();
+        let json = r#"[
+            {"title": "Alpha", "children": [{"string": "Key point", "uid": "k1"}]},
+            {"title": "Beta", "children": [{"string": "See [[Alpha]] and ((k1))", "uid": "b1"}]},
+            {"title": "Existing", "children": [{"string": "Theirs", "uid": "e1"}]}
+        ]"#;
+
+        let report = import_roam_json(root, &db, json, "roam").unwrap();
+        assert_eq!(report.imported, vec!["roam/Alpha.md", "roam/Beta.md"]);
+        assert_eq!(report.skipped, vec!["roam/Existing.md"]);
+        assert_eq!(fs::read_to_string(root.join("roam/Existing.md")).unwrap(), "# Mine\n");
+
+        let backlinks = get_backlinks(&db.conn(), "roam/Alpha.md").unwrap();
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].source_path, "roam/Beta.md");
+    }
+}
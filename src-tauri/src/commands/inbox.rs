@@ -0,0 +1,272 @@
+//! Inbox commands: notes captured into the inbox folder are worked through
+//! one at a time and filed, tagged, merged into another note or deleted
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::notes::{discard_note, move_note, remove_note};
+use crate::commands::snapshots::snapshot_current;
+use crate::commands::vault::AppState;
+use crate::commands::{blocking, ensure_downloaded};
+use crate::db::{notes as db_notes, notes::NoteMeta, Database};
+use crate::error::ChronicleError;
+use crate::models::AppConfig;
+use crate::vault::{
+    add_frontmatter_tag, parse_frontmatter, resolve_vault_path, Indexer, UndoJournal,
+};
+
+/// What to do with an inbox note
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum InboxAction {
+    /// Move the note into a vault folder, keeping its filename
+    File { folder: String },
+    /// Add tags; the note stays in the inbox until it is filed
+    Tag { tags: Vec<String> },
+    /// Append the note's body and tags to another note, then delete it
+    Merge { into: String },
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InboxResult {
+    /// The note as it is now: filed, tagged or merged into. `None` once
+    /// deleted.
+    pub note: Option<NoteMeta>,
+    /// Notes left in the inbox
+    pub remaining: usize,
+}
+
+/// Notes in the inbox, oldest first
+fn inbox_notes(db: &Database, config: &AppConfig) -> Result<Vec<NoteMeta>, ChronicleError> {
+    Ok(db_notes::list_notes_in_folder(&db.conn(), &config.inbox.folder)?)
+}
+
+/// The next inbox note to process: the oldest, or the one after `after`
+/// so notes left in the inbox (e.g. only tagged) can be skipped
+pub fn next_inbox_item(
+    db: &Database,
+    config: &AppConfig,
+    after: Option<&str>,
+) -> Result<Option<NoteMeta>, ChronicleError> {
+    let mut notes = inbox_notes(db, config)?.into_iter();
+    if let Some(after) = after {
+        let mut rest = notes.clone().skip_while(|n| n.path != after);
+        if rest.next().is_some() {
+            return Ok(rest.next());
+        }
+    }
+    Ok(notes.next())
+}
+
+/// Apply `action` to the inbox note at `path`
+pub fn apply_inbox_action(
+    vault_path: &Path,
+    db: &Database,
+    config: &AppConfig,
+    path: &str,
+    action: InboxAction,
+) -> Result<InboxResult, ChronicleError> {
+    let inbox = config.inbox.folder.trim_matches('/');
+    if !path.strip_prefix(inbox).is_some_and(|rest| rest.starts_with('/')) {
+        return Err(ChronicleError::InvalidPath(format!("Not in the inbox: {}", path)));
+    }
+    let full_path = resolve_vault_path(vault_path, path)?;
+    ensure_downloaded(&full_path, path)?;
+    if !full_path.exists() {
+        return Err(ChronicleError::NoteNotFound(path.to_string()));
+    }
+
+    let note = match action {
+        InboxAction::File { folder } => {
+            let filename = full_path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let folder = folder.trim_matches('/');
+            let new_path = if folder.is_empty() {
+                filename
+            } else {
+                format!("{}/{}", folder, filename)
+            };
+            if let Some(parent) = resolve_vault_path(vault_path, &new_path)?.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            Some(move_note(vault_path, db, path, &new_path)?.note)
+        }
+        InboxAction::Tag { tags } => {
+            let content = fs::read_to_string(&full_path)?;
+            let tagged = tags.iter().fold(content, |content, tag| {
+                add_frontmatter_tag(&content, tag.trim().trim_start_matches('#'))
+            });
+            fs::write(&full_path, tagged)?;
+            Some(reindex(vault_path, db, path)?)
+        }
+        InboxAction::Merge { into } => {
+            if into == path {
+                return Err(ChronicleError::InvalidPath(format!(
+                    "Can't merge a note into itself: {}",
+                    path
+                )));
+            }
+            let target_path = resolve_vault_path(vault_path, &into)?;
+            ensure_downloaded(&target_path, &into)?;
+            if !target_path.exists() {
+                return Err(ChronicleError::NoteNotFound(into));
+            }
+
+            let content = fs::read_to_string(&full_path)?;
+            let (frontmatter, body_start) = parse_frontmatter(&content);
+            let target = fs::read_to_string(&target_path)?;
+            let mut merged = format!("{}\n\n{}\n", target.trim_end(), content[body_start..].trim());
+            for tag in frontmatter.map(|fm| fm.tags).unwrap_or_default() {
+                merged = add_frontmatter_tag(&merged, &tag);
+            }
+
+            if let Err(e) = snapshot_current(vault_path, &into, &config.snapshots, false) {
+                tracing::warn!(path = %into, error = %e, "Failed to snapshot note");
+            }
+            // One entry for the whole merge, so undoing it takes the text
+            // back out of the target as well as restoring the note
+            UndoJournal::new(vault_path).record_merge(path, &content, &into, &target)?;
+            fs::write(&target_path, merged)?;
+            let note = reindex(vault_path, db, &into)?;
+            discard_note(vault_path, db, path)?;
+            Some(note)
+        }
+        InboxAction::Delete => {
+            remove_note(vault_path, db, path)?;
+            None
+        }
+    };
+
+    Ok(InboxResult {
+        note,
+        remaining: inbox_notes(db, config)?.len(),
+    })
+}
+
+fn reindex(vault_path: &Path, db: &Database, path: &str) -> Result<NoteMeta, ChronicleError> {
+    let indexer = Indexer::new(vault_path.to_path_buf())?;
+    indexer.index_file(db, &resolve_vault_path(vault_path, path)?)?;
+    db_notes::get_note_by_path(&db.conn(), path)?
+        .ok_or_else(|| ChronicleError::NoteNotFound(path.to_string()))
+}
+
+/// Number of notes waiting in the inbox
+#[tauri::command]
+pub async fn get_inbox_count(state: State<'_, AppState>) -> Result<usize, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || Ok(inbox_notes(&db, &AppConfig::load())?.len())).await
+}
+
+/// The next inbox note to process, oldest first. Pass the note just
+/// processed as `after` to move past notes that stay in the inbox.
+#[tauri::command]
+pub async fn get_next_inbox_item(
+    after: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Option<NoteMeta>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || next_inbox_item(&db, &AppConfig::load(), after.as_deref())).await
+}
+
+/// File, tag, merge or delete an inbox note
+#[tauri::command]
+pub async fn process_inbox_item(
+    path: String,
+    action: InboxAction,
+    state: State<'_, AppState>,
+) -> Result<InboxResult, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || apply_inbox_action(&vault_path, &db, &AppConfig::load(), &path, action))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, Database) {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("inbox")).unwrap();
+        let idea = "---\ntags: [idea]\n---\n# Idea\n\nMore cats";
+        fs::write(root.join("inbox/idea.md"), idea).unwrap();
+        fs::write(root.join("inbox/link.md"), "# Link\n\nhttps://example.com").unwrap();
+        fs::write(root.join("inbox/junk.md"), "# Junk\n").unwrap();
+        fs::write(root.join("pets.md"), "# Pets\n\nDogs\n").unwrap();
+
+        let db = Database::open_memory().unwrap();
+        Indexer::new(root.to_path_buf()).unwrap().full_index(&db).unwrap();
+        (temp, db)
+    }
+
+    fn paths(db: &Database, config: &AppConfig) -> Vec<String> {
+        let mut paths: Vec<String> =
+            inbox_notes(db, config).unwrap().into_iter().map(|n| n.path).collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_process_inbox() {
+        let (temp, db) = setup();
+        let root = temp.path();
+        let config = AppConfig::default();
+        assert_eq!(paths(&db, &config), vec!["inbox/idea.md", "inbox/junk.md", "inbox/link.md"]);
+
+        let tag = InboxAction::Tag { tags: vec!["#toread".to_string()] };
+        let result = apply_inbox_action(root, &db, &config, "inbox/link.md", tag).unwrap();
+        assert_eq!(result.remaining, 3);
+        let content = fs::read_to_string(root.join("inbox/link.md")).unwrap();
+        assert!(content.starts_with("---\ntags: [toread]\n---\n"));
+
+        let file = InboxAction::File { folder: "reading/web".to_string() };
+        let result = apply_inbox_action(root, &db, &config, "inbox/link.md", file).unwrap();
+        assert_eq!(result.note.unwrap().path, "reading/web/link.md");
+        assert!(root.join("reading/web/link.md").exists());
+
+        let merge = InboxAction::Merge { into: "pets.md".to_string() };
+        let result = apply_inbox_action(root, &db, &config, "inbox/idea.md", merge).unwrap();
+        assert_eq!(result.note.unwrap().path, "pets.md");
+        assert_eq!(
+            fs::read_to_string(root.join("pets.md")).unwrap(),
+            "---\ntags: [idea]\n---\n# Pets\n\nDogs\n\n# Idea\n\nMore cats\n"
+        );
+        assert!(!root.join("inbox/idea.md").exists());
+
+        let result =
+            apply_inbox_action(root, &db, &config, "inbox/junk.md", InboxAction::Delete).unwrap();
+        assert!(result.note.is_none());
+        assert_eq!(result.remaining, 0);
+
+        // Only inbox notes can be processed
+        let err = apply_inbox_action(root, &db, &config, "pets.md", InboxAction::Delete);
+        assert!(matches!(err, Err(ChronicleError::InvalidPath(_))));
+    }
+
+    #[test]
+    fn test_next_inbox_item_skips_past() {
+        let (_temp, db) = setup();
+        let config = AppConfig::default();
+        let order: Vec<String> =
+            inbox_notes(&db, &config).unwrap().into_iter().map(|n| n.path).collect();
+
+        let first = next_inbox_item(&db, &config, None).unwrap().unwrap();
+        assert_eq!(first.path, order[0]);
+        let second = next_inbox_item(&db, &config, Some(&order[0])).unwrap().unwrap();
+        assert_eq!(second.path, order[1]);
+        assert!(next_inbox_item(&db, &config, Some(&order[2])).unwrap().is_none());
+        // A note no longer in the inbox starts over
+        let restart = next_inbox_item(&db, &config, Some("pets.md")).unwrap().unwrap();
+        assert_eq!(restart.path, order[0]);
+    }
+}
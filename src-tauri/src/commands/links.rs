@@ -0,0 +1,88 @@
+//! Auto-link commands
+
+use std::fs;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::notes as db_notes;
+use crate::error::ChronicleError;
+use crate::vault::{
+    find_auto_links, insert_auto_links, parse_note, resolve_vault_path, AutoLinkSuggestion,
+    Indexer, LinkTarget,
+};
+
+/// Suggest mentions of other notes' titles or aliases in a note that could
+/// become wiki links
+#[tauri::command]
+pub async fn suggest_auto_links(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<AutoLinkSuggestion>, ChronicleError> {
+    let vault_path = state.vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let full_path = resolve_vault_path(&vault_path, &path)?;
+        let content = fs::read_to_string(&full_path)?;
+
+        let notes = db_notes::list_notes(&db.conn())?;
+
+        // Aliases only live in frontmatter; unreadable notes still match by title
+        let targets: Vec<LinkTarget> = notes
+            .into_iter()
+            .filter(|note| note.path != path)
+            .map(|note| {
+                let aliases = resolve_vault_path(&vault_path, &note.path)
+                    .ok()
+                    .and_then(|p| fs::read_to_string(p).ok())
+                    .and_then(|content| parse_note(&content, &note.path).frontmatter)
+                    .map(|fm| fm.aliases)
+                    .unwrap_or_default();
+
+                let mut names = vec![note.title];
+                names.extend(aliases);
+                LinkTarget {
+                    path: note.path,
+                    names,
+                }
+            })
+            .collect();
+
+        Ok(find_auto_links(&content, &targets))
+    })
+    .await
+}
+
+/// Turn accepted suggestions into wiki links. Suggestions that no longer
+/// match the note on disk are skipped; returns how many were applied.
+#[tauri::command]
+pub async fn apply_auto_links(
+    path: String,
+    suggestions: Vec<AutoLinkSuggestion>,
+    state: State<'_, AppState>,
+) -> Result<usize, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let full_path = resolve_vault_path(&vault_path, &path)?;
+        if !full_path.exists() {
+            return Err(ChronicleError::NoteNotFound(path));
+        }
+
+        let content = fs::read_to_string(&full_path)?;
+        let (new_content, applied) = insert_auto_links(&content, &suggestions);
+
+        if applied > 0 {
+            fs::write(&full_path, &new_content)?;
+
+            // Re-index so the new links show up as backlinks
+            let indexer = Indexer::new(vault_path.clone())?;
+            indexer.index_file(&db, &full_path)?;
+        }
+
+        Ok(applied)
+    })
+    .await
+}
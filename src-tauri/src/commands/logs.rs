@@ -0,0 +1,19 @@
+//! Log access commands
+
+use crate::error::ChronicleError;
+use crate::logging;
+
+const DEFAULT_LOG_LINES: usize = 500;
+
+/// Get the most recent log lines, oldest first
+#[tauri::command]
+pub async fn get_recent_logs(limit: Option<usize>) -> Result<Vec<String>, ChronicleError> {
+    let lines = logging::recent_lines(&logging::log_dir(), limit.unwrap_or(DEFAULT_LOG_LINES))?;
+    Ok(lines)
+}
+
+/// Change the log level for the running app
+#[tauri::command]
+pub async fn set_log_level(level: String) -> Result<(), ChronicleError> {
+    logging::set_level(&level)
+}
@@ -0,0 +1,173 @@
+//! Map of content commands
+
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::snapshots::snapshot_current;
+use crate::commands::vault::AppState;
+use crate::commands::{blocking, ensure_downloaded};
+use crate::db::{
+    notes::{self as db_notes, NoteMeta},
+    tags::get_notes_in_tag_tree,
+    Database,
+};
+use crate::error::ChronicleError;
+use crate::models::AppConfig;
+use crate::vault::{render_moc, resolve_vault_path, update_moc, Indexer, MocEntry};
+
+/// Which notes a map of content lists
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MocSource {
+    /// Notes with the tag, grouped by nested tag (`project/alpha`)
+    Tag(String),
+    /// Notes in the folder, grouped by subfolder
+    Folder(String),
+}
+
+/// The notes `source` lists, leaving out the map itself
+pub fn moc_entries(
+    db: &Database,
+    source: &MocSource,
+    target: &str,
+) -> Result<Vec<MocEntry>, ChronicleError> {
+    let conn = db.conn();
+    let mut entries = Vec::new();
+
+    match source {
+        MocSource::Tag(tag) => {
+            let tag = tag.trim().trim_start_matches('#').trim_end_matches('/');
+            let rows = get_notes_in_tag_tree(&conn, tag)?;
+            for (path, title, name) in &rows {
+                let group = name.get(tag.len() + 1..).map(str::to_string);
+                // Notes with a nested tag are only listed under it
+                let nested = rows.iter().any(|(p, _, n)| p == path && n.len() > tag.len());
+                if group.is_none() && nested {
+                    continue;
+                }
+                entries.push(MocEntry {
+                    path: path.clone(),
+                    title: title.clone(),
+                    group,
+                });
+            }
+        }
+        MocSource::Folder(folder) => {
+            let folder = folder.trim_matches('/');
+            for note in db_notes::list_notes_in_folder(&conn, folder)? {
+                let group = Path::new(&note.path[folder.len() + 1..])
+                    .parent()
+                    .map(|dir| dir.to_string_lossy().replace('\\', "/"))
+                    .filter(|dir| !dir.is_empty());
+                entries.push(MocEntry {
+                    path: note.path,
+                    title: note.title,
+                    group,
+                });
+            }
+        }
+    }
+
+    entries.retain(|entry| entry.path != target);
+    Ok(entries)
+}
+
+/// Write or refresh the list in `target`, creating the note if needed
+pub fn write_moc(
+    vault_path: &Path,
+    db: &Database,
+    source: &MocSource,
+    target: &str,
+) -> Result<NoteMeta, ChronicleError> {
+    let full_path = resolve_vault_path(vault_path, target)?;
+    ensure_downloaded(&full_path, target)?;
+
+    let block = render_moc(&moc_entries(db, source, target)?);
+    let content = if full_path.exists() {
+        if let Err(e) = snapshot_current(vault_path, target, &AppConfig::load().snapshots, true) {
+            tracing::warn!(path = %target, error = %e, "Failed to snapshot note");
+        }
+        update_moc(&fs::read_to_string(&full_path)?, &block)
+    } else {
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let title = full_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        update_moc(&format!("# {}\n", title), &block)
+    };
+    fs::write(&full_path, content)?;
+
+    let indexer = Indexer::new(vault_path.to_path_buf())?;
+    indexer.index_file(db, &full_path)?;
+    db_notes::get_note_by_path(&db.conn(), target)?
+        .ok_or_else(|| ChronicleError::NoteNotFound(target.to_string()))
+}
+
+/// Write or refresh a map of content in `target` listing the notes with a
+/// tag or in a folder. Only the marked list is replaced; anything written
+/// around it is kept.
+#[tauri::command]
+pub async fn generate_moc(
+    source: MocSource,
+    target: String,
+    state: State<'_, AppState>,
+) -> Result<NoteMeta, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || write_moc(&vault_path, &db, &source, &target)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_generate_moc() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("projects/alpha")).unwrap();
+        fs::write(root.join("projects/plan.md"), "---\ntags: [project]\n---\n# Plan\n").unwrap();
+        fs::write(
+            root.join("projects/alpha/spec.md"),
+            "---\ntags: [project, project/alpha]\n---\n# Spec\n",
+        )
+        .unwrap();
+        fs::write(root.join("other.md"), "---\ntags: [projects]\n---\n# Other\n").unwrap();
+        let db = Database::open_memory().unwrap();
+        Indexer::new(root.to_path_buf()).unwrap().full_index(&db).unwrap();
+
+        let by_tag = moc_entries(&db, &MocSource::Tag("#project".to_string()), "x.md").unwrap();
+        let groups: Vec<(&str, Option<&str>)> =
+            by_tag.iter().map(|e| (e.path.as_str(), e.group.as_deref())).collect();
+        assert_eq!(
+            groups,
+            vec![("projects/alpha/spec.md", Some("alpha")), ("projects/plan.md", None)]
+        );
+
+        let source = MocSource::Folder("projects".to_string());
+        write_moc(root, &db, &source, "projects/index.md").unwrap();
+        let content = fs::read_to_string(root.join("projects/index.md")).unwrap();
+        assert_eq!(
+            content,
+            "# index\n\n%% moc:start %%\n- [[projects/plan|Plan]]\n\n\
+             ## alpha\n\n- [[projects/alpha/spec|Spec]]\n%% moc:end %%\n"
+        );
+
+        // Refreshing keeps edits and doesn't list the map itself
+        fs::write(root.join("projects/index.md"), content.replace("# index", "# Projects"))
+            .unwrap();
+        fs::write(root.join("projects/new.md"), "# New\n").unwrap();
+        Indexer::new(root.to_path_buf()).unwrap().full_index(&db).unwrap();
+        write_moc(root, &db, &source, "projects/index.md").unwrap();
+        let content = fs::read_to_string(root.join("projects/index.md")).unwrap();
+        assert!(content.starts_with("# Projects\n\n%% moc:start %%\n- [[projects/new|New]]\n"));
+        assert!(!content.contains("[[projects/index"));
+    }
+}
@@ -1,21 +1,156 @@
 //! Tauri commands for Chronicle
 
+mod attachments;
+mod backup;
+mod board;
+mod breadcrumbs;
+mod broken_links;
+mod bulk_edit;
+mod calendar;
+mod canvas;
+mod capture;
+mod citations;
 mod config;
 mod daily;
+mod deep_link;
+mod drafts;
+mod email;
+mod embeds;
+mod format;
 mod graph;
+mod health;
+mod import;
+mod inbox;
+mod links;
+mod logs;
+mod moc;
+mod note_types;
 mod notes;
+mod opml;
+mod plugins;
+mod preview;
 mod publish;
+mod reading_list;
+mod reminders;
+mod review;
 mod search;
+mod share;
+mod smart_folders;
+mod snapshots;
+mod srs;
 mod sync;
 mod tags;
+mod tasks;
+mod templates;
+mod undo;
 pub mod vault;
+mod views;
+mod workspaces;
+mod writing;
+mod zettel;
 
+pub use attachments::*;
+pub use backup::*;
+pub use board::*;
+pub use breadcrumbs::*;
+pub use broken_links::*;
+pub use bulk_edit::*;
+pub use calendar::*;
+pub use canvas::*;
+pub use capture::*;
+pub use citations::*;
 pub use config::*;
 pub use daily::*;
+pub use deep_link::*;
+pub use drafts::*;
+pub use email::*;
+pub use embeds::*;
+pub use format::*;
 pub use graph::*;
+pub use health::*;
+pub use import::*;
+pub use inbox::*;
+pub use links::*;
+pub use logs::*;
+pub use moc::*;
+pub use note_types::*;
 pub use notes::*;
+pub use opml::*;
+pub use plugins::*;
+pub use preview::*;
 pub use publish::*;
+pub use reading_list::*;
+pub use reminders::*;
+pub use review::*;
 pub use search::*;
+pub use share::*;
+pub use smart_folders::*;
+pub use snapshots::*;
+pub use srs::*;
 pub use sync::*;
 pub use tags::*;
+pub use tasks::*;
+pub use templates::*;
+pub use undo::*;
 pub use vault::*;
+pub use views::*;
+pub use workspaces::*;
+pub use writing::*;
+pub use zettel::*;
+
+use std::path::Path;
+
+use crate::error::ChronicleError;
+use crate::vault::{wait_until_downloaded, DOWNLOAD_TIMEOUT};
+
+/// Run blocking filesystem/database work on the blocking thread pool so it
+/// doesn't stall the async runtime
+pub(crate) async fn blocking<T, F>(f: F) -> Result<T, ChronicleError>
+where
+    F: FnOnce() -> Result<T, ChronicleError> + Send + 'static,
+    T: Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| ChronicleError::Task(e.to_string()))?
+}
+
+/// Wait for a note in a cloud-synced folder to download before reading or
+/// writing it, so a save can't clobber the version still in the cloud
+pub(crate) fn ensure_downloaded(full_path: &Path, path: &str) -> Result<(), ChronicleError> {
+    wait_until_downloaded(full_path, DOWNLOAD_TIMEOUT)
+        .map_err(|_| ChronicleError::NotDownloaded(path.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    /// A command left out of `generate_handler!` still builds, but fails
+    /// with "command not found" when the frontend invokes it
+    #[test]
+    fn test_every_command_is_registered() {
+        let dir = Path::new(file!()).parent().expect("commands folder");
+        let lib = fs::read_to_string(dir.join("../lib.rs")).unwrap();
+        let mut missing = Vec::new();
+        for entry in fs::read_dir(dir).unwrap() {
+            let source = fs::read_to_string(entry.unwrap().path()).unwrap();
+            let mut lines = source.lines().map(str::trim);
+            while let Some(line) = lines.next() {
+                if line != "#[tauri::command]" {
+                    continue;
+                }
+                let Some(signature) = lines.find(|line| !line.starts_with("#[")) else {
+                    break;
+                };
+                let name = signature.split("fn ").nth(1).unwrap_or_default();
+                let name = name.split(['(', '<']).next().unwrap_or_default();
+                if !lib.contains(&format!("commands::{},", name)) {
+                    missing.push(name.to_string());
+                }
+            }
+        }
+        assert!(missing.is_empty(), "Not in generate_handler!: {:?}", missing);
+    }
+}
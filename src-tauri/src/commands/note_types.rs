@@ -0,0 +1,23 @@
+//! Note type commands
+
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::{notes::NoteMeta, properties::list_notes_by_type as db_list_notes_by_type};
+use crate::error::ChronicleError;
+
+/// Notes whose `type` property is `note_type`, ignoring case, by title
+#[tauri::command]
+pub async fn list_notes_by_type(
+    note_type: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<NoteMeta>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        Ok(db_list_notes_by_type(&conn, &note_type)?)
+    })
+    .await
+}
@@ -1,92 +1,193 @@
 //! Note CRUD commands
 
+use chrono::{Local, NaiveDateTime};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::sync::Mutex;
-use tauri::State;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Runtime, State};
 
-use crate::commands::vault::AppState;
-use crate::db::{notes as db_notes, tags::get_note_tags};
+use crate::commands::sync::open_repo;
+use crate::commands::{blocking, ensure_downloaded};
+use crate::commands::format::format_with_index;
+use crate::commands::snapshots::snapshot_current;
+use crate::commands::vault::{AppState, VaultEventPayload};
+use crate::commands::zettel::{new_note_id, new_note_id_besides};
+use crate::db::{
+    links::{
+        get_backlinks, get_markdown_link_sources, get_path_link_sources, resolve_link_target,
+        Backlink,
+    },
+    notes as db_notes,
+    properties::property_numbers,
+    review::record_note_opened,
+    smart_folders::notes_matching,
+    tags::get_note_tags,
+    tasks::{self as db_tasks, TaskRecord},
+    Database,
+};
 use crate::error::ChronicleError;
-use crate::models::Note;
-use crate::vault::Indexer;
+use crate::models::{AppConfig, NewNoteConfig, NewNoteLocation, Note, QueryResult};
+use crate::plugins::transform_on_save;
+use crate::sync::GitRepo;
+use crate::vault::{
+    chrono_from_systemtime, find_query_blocks, frontmatter_properties, get_frontmatter_property,
+    is_placeholder, parse_note, parse_query, rebase_markdown_links, remove_draft, render_template,
+    resolve_vault_path, rewrite_links, rewrite_markdown_links, set_frontmatter_value, Indexer,
+    LinkEdit, Rollup, RollupValue, SnapshotStore, TemplateContext, UndoJournal,
+};
 
 /// List all notes
 #[tauri::command]
 pub async fn list_notes(
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
 ) -> Result<Vec<db_notes::NoteMeta>, ChronicleError> {
-    let app_state = state.lock().expect("Failed to lock state");
+    let db = state.db()?;
 
-    let db = app_state.db.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-    let conn = db.conn();
+    blocking(move || {
+        let conn = db.conn();
 
-    let notes = db_notes::list_notes(&conn)?;
-    Ok(notes)
+        let notes = db_notes::list_notes(&conn)?;
+        Ok(notes)
+    })
+    .await
 }
 
-/// Get a single note with content
+/// Get a single note with content. With `resolve_queries`, the notes each
+/// `chronicle-query` block matches come back with it.
 #[tauri::command]
 pub async fn get_note(
     path: String,
-    state: State<'_, Mutex<AppState>>,
+    resolve_queries: Option<bool>,
+    state: State<'_, AppState>,
 ) -> Result<Note, ChronicleError> {
-    let app_state = state.lock().expect("Failed to lock state");
+    let vault_path = state.vault_path()?;
+    let db = state.db()?;
 
-    let vault_path = app_state
-        .vault_path
-        .as_ref()
-        .ok_or(ChronicleError::NoVaultOpen)?;
-    let db = app_state.db.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
+    blocking(move || {
+        let full_path = resolve_vault_path(&vault_path, &path)?;
+        ensure_downloaded(&full_path, &path)?;
+        if !full_path.exists() {
+            return Err(ChronicleError::NoteNotFound(path));
+        }
+        let content = fs::read_to_string(&full_path)?;
 
-    let full_path = vault_path.join(&path);
-    let content = fs::read_to_string(&full_path)?;
+        let conn = db.conn();
+        let meta = db_notes::get_note_by_path(&conn, &path)?
+            .ok_or_else(|| ChronicleError::NoteNotFound(path.clone()))?;
 
-    let conn = db.conn();
-    let meta = db_notes::get_note_by_path(&conn, &path)?
-        .ok_or_else(|| ChronicleError::NoteNotFound(path.clone()))?;
-
-    let tags = get_note_tags(&conn, meta.id)?;
-
-    Ok(Note {
-        path: meta.path,
-        title: meta.title,
-        content,
-        word_count: meta.word_count,
-        created_at: meta.created_at,
-        modified_at: meta.modified_at,
-        tags,
+        let tags = get_note_tags(&conn, meta.id)?;
+        let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        record_note_opened(&conn, meta.id, &now)?;
+        let locale = AppConfig::load().locale;
+        let queries = if resolve_queries.unwrap_or(false) {
+            run_query_blocks(&conn, &content, &meta.path)?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Note {
+            path: meta.path,
+            title: meta.title,
+            content,
+            word_count: meta.word_count,
+            created_display: meta.created_at.as_deref().and_then(|t| locale.display_timestamp(t)),
+            modified_display: meta.modified_at.as_deref().and_then(|t| locale.display_timestamp(t)),
+            created_at: meta.created_at,
+            modified_at: meta.modified_at,
+            tags,
+            queries,
+        })
     })
+    .await
+}
+
+/// Run each query block in a note, leaving the note itself out of the
+/// results and rollups. A block that can't be parsed gets an error instead.
+pub(crate) fn run_query_blocks(
+    conn: &Connection,
+    content: &str,
+    path: &str,
+) -> Result<Vec<QueryResult>, ChronicleError> {
+    let mut results = Vec::new();
+    for block in find_query_blocks(content) {
+        let (notes, rollups, error) = match parse_query(&block.source) {
+            Ok(spec) => {
+                let mut notes = notes_matching(conn, &spec.filter)?;
+                notes.retain(|note| note.path != path);
+
+                let matched: HashSet<i64> = notes.iter().map(|note| note.id).collect();
+                let mut rollups = Vec::new();
+                for Rollup { key, op } in spec.rollups {
+                    let mut values = property_numbers(conn, &key)?;
+                    values.retain(|(id, _)| matched.contains(id));
+                    let value = op.apply(&values);
+                    rollups.push(RollupValue { key, op, value });
+                }
+
+                notes.truncate(spec.limit.unwrap_or(usize::MAX));
+                (notes, rollups, None)
+            }
+            Err(e) => (Vec::new(), Vec::new(), Some(e)),
+        };
+        results.push(QueryResult {
+            start_line: block.start_line,
+            end_line: block.end_line,
+            notes,
+            rollups,
+            error,
+        });
+    }
+    Ok(results)
 }
 
 /// Create a new note
+///
+/// The location and filename come from the `new_notes` config section;
+/// `current_path` is the open note, used by the `current_folder` location.
 #[tauri::command]
 pub async fn create_note(
     title: String,
     content: Option<String>,
-    state: State<'_, Mutex<AppState>>,
+    current_path: Option<String>,
+    state: State<'_, AppState>,
 ) -> Result<db_notes::NoteMeta, ChronicleError> {
-    let app_state = state.lock().expect("Failed to lock state");
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
 
-    let vault_path = app_state
-        .vault_path
-        .as_ref()
-        .ok_or(ChronicleError::NoVaultOpen)?;
-    let db = app_state.db.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
+    blocking(move || new_note(&vault_path, &db, &title, content, current_path.as_deref())).await
+}
 
-    // Generate filename from title
-    let filename = sanitize_filename(&title) + ".md";
-    let full_path = vault_path.join(&filename);
+/// Write and index a new note; shared by `create_note` and the CLI
+pub fn new_note(
+    vault_path: &Path,
+    db: &Database,
+    title: &str,
+    content: Option<String>,
+    current_path: Option<&str>,
+) -> Result<db_notes::NoteMeta, ChronicleError> {
+    let config = AppConfig::load();
+    let now = Local::now().naive_local();
+    let id = new_note_id(&db.conn(), &config, now)?;
 
-    if full_path.exists() {
+    // Generate path from the configured location and filename template
+    let filename = new_note_path(title, current_path, &config.new_notes, now, id.as_deref());
+    let full_path = resolve_vault_path(vault_path, &filename)?;
+
+    if full_path.exists() || is_placeholder(&full_path) {
         return Err(ChronicleError::NoteExists(filename));
     }
 
-    // Create content with title heading
-    let note_content = content.unwrap_or_else(|| format!("# {}\n\n", title));
+    if let Some(parent) = full_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let note_content = new_note_content(vault_path, &config, &filename, title, content, now)?;
     fs::write(&full_path, &note_content)?;
 
     // Index the new note
-    let indexer = Indexer::new(vault_path.clone())?;
+    let indexer = Indexer::new(vault_path.to_path_buf())?;
     indexer.index_file(db, &full_path)?;
 
     let conn = db.conn();
@@ -96,108 +197,545 @@ pub async fn create_note(
     Ok(meta)
 }
 
+/// Content for a new note at `filename`: the given content, else the
+/// folder's default template, else a title heading, with the folder's
+/// default frontmatter filled in where missing
+fn new_note_content(
+    vault_path: &Path,
+    config: &AppConfig,
+    filename: &str,
+    title: &str,
+    content: Option<String>,
+    now: NaiveDateTime,
+) -> Result<String, ChronicleError> {
+    let defaults = config.new_notes.folder_defaults_for(filename);
+    let template = match (&content, defaults.and_then(|d| d.template.as_deref())) {
+        (None, Some(name)) => Some(folder_template(vault_path, config, name, title, now)?),
+        _ => None,
+    };
+    // Create content with title heading
+    let mut note_content = content
+        .or(template)
+        .unwrap_or_else(|| format!("# {}\n\n", title));
+    for (key, value) in defaults.map(|d| &d.frontmatter).into_iter().flatten() {
+        if get_frontmatter_property(&note_content, key).is_none() {
+            note_content = set_frontmatter_value(&note_content, key, value);
+        }
+    }
+    Ok(note_content)
+}
+
+/// A note to create with `create_notes`
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewNoteSpec {
+    pub title: String,
+    /// Vault folder to create it in, instead of where new notes go
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Content, else the folder's template or a title heading as usual
+    #[serde(default)]
+    pub content: Option<String>,
+    /// Tags added to the note's frontmatter
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Create many notes at once, for importers, templates scaffolding a
+/// project and scripts. They're indexed in one transaction and announced
+/// with one `notes_created` event; if any can't be created, none are.
+#[tauri::command]
+pub async fn create_notes<R: Runtime>(
+    notes: Vec<NewNoteSpec>,
+    state: State<'_, AppState>,
+    app: AppHandle<R>,
+) -> Result<Vec<db_notes::NoteMeta>, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    let created = blocking(move || new_notes(&vault_path, &db, &notes)).await?;
+    let paths = created.iter().map(|note| note.path.clone()).collect();
+    let _ = app.emit("vault-event", VaultEventPayload::NotesCreated { paths });
+    Ok(created)
+}
+
+/// Write and index a batch of new notes; shared by `create_notes` and the
+/// CLI
+pub fn new_notes(
+    vault_path: &Path,
+    db: &Database,
+    notes: &[NewNoteSpec],
+) -> Result<Vec<db_notes::NoteMeta>, ChronicleError> {
+    let now = Local::now().naive_local();
+    write_new_notes(vault_path, db, &AppConfig::load(), notes, now)
+}
+
+fn write_new_notes(
+    vault_path: &Path,
+    db: &Database,
+    config: &AppConfig,
+    notes: &[NewNoteSpec],
+    now: NaiveDateTime,
+) -> Result<Vec<db_notes::NoteMeta>, ChronicleError> {
+    // Work out every path and content first, so a clash fails the batch
+    // before anything is written
+    let mut planned: Vec<(String, PathBuf, String)> = Vec::new();
+    let mut ids = HashSet::new();
+    for note in notes {
+        let id = new_note_id_besides(&db.conn(), config, now, &ids)?;
+        let mut placement = config.new_notes.clone();
+        if let Some(folder) = &note.folder {
+            placement.location = NewNoteLocation::Folder;
+            placement.folder = folder.clone();
+        }
+        let filename = new_note_path(&note.title, None, &placement, now, id.as_deref());
+        let full_path = resolve_vault_path(vault_path, &filename)?;
+        let taken = planned.iter().any(|(other, _, _)| other.eq_ignore_ascii_case(&filename));
+        if taken || full_path.exists() || is_placeholder(&full_path) {
+            return Err(ChronicleError::NoteExists(filename));
+        }
+
+        let content = note.content.clone();
+        let mut content = new_note_content(vault_path, config, &filename, &note.title, content, now)?;
+        if !note.tags.is_empty() {
+            content = add_frontmatter_tags(&content, &note.tags);
+        }
+        ids.extend(id);
+        planned.push((filename, full_path, content));
+    }
+
+    let written = write_all(&planned).and_then(|paths| {
+        Indexer::new(vault_path.to_path_buf())?.index_files(db, &paths)?;
+        Ok(())
+    });
+    if let Err(e) = written {
+        for (_, full_path, _) in &planned {
+            let _ = fs::remove_file(full_path);
+        }
+        return Err(e);
+    }
+
+    let conn = db.conn();
+    planned
+        .into_iter()
+        .map(|(filename, _, _)| {
+            db_notes::get_note_by_path(&conn, &filename)?
+                .ok_or(ChronicleError::NoteNotFound(filename))
+        })
+        .collect()
+}
+
+/// Write planned notes, stopping at the first that fails. Returns the paths
+/// written.
+fn write_all(planned: &[(String, PathBuf, String)]) -> Result<Vec<PathBuf>, ChronicleError> {
+    let mut paths = Vec::new();
+    for (_, full_path, content) in planned {
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(full_path, content)?;
+        paths.push(full_path.clone());
+    }
+    Ok(paths)
+}
+
+/// Add tags to a note's frontmatter `tags`, after any already there
+fn add_frontmatter_tags(content: &str, tags: &[String]) -> String {
+    let mut all: Vec<String> = frontmatter_properties(content)
+        .into_iter()
+        .filter(|(key, _)| key == "tags")
+        .map(|(_, value)| value)
+        .collect();
+    for tag in tags {
+        let tag = tag.trim().trim_start_matches('#');
+        if !tag.is_empty() && !all.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            all.push(tag.to_string());
+        }
+    }
+    set_frontmatter_value(content, "tags", &serde_json::Value::from(all))
+}
+
+/// Render a folder's default template, given as a vault path or a name in
+/// the templates folder
+fn folder_template(
+    vault_path: &Path,
+    config: &AppConfig,
+    template: &str,
+    title: &str,
+    now: NaiveDateTime,
+) -> Result<String, ChronicleError> {
+    let in_templates_folder = format!(
+        "{}/{}.md",
+        config.new_notes.templates_folder.trim_matches('/'),
+        template.trim_end_matches(".md")
+    );
+    let path = [template, in_templates_folder.as_str()]
+        .into_iter()
+        .filter_map(|candidate| resolve_vault_path(vault_path, candidate).ok())
+        .find(|path| path.is_file())
+        .ok_or_else(|| ChronicleError::NoteNotFound(template.to_string()))?;
+
+    let prompts = HashMap::new();
+    let ctx = TemplateContext {
+        date: now.date(),
+        now,
+        date_format: "%Y-%m-%d",
+        locale: &config.locale,
+        title: Some(title),
+        prompts: &prompts,
+    };
+    Ok(render_template(&fs::read_to_string(path)?, &ctx))
+}
+
 /// Save note content
 #[tauri::command]
 pub async fn save_note(
     path: String,
     content: String,
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
 ) -> Result<db_notes::NoteMeta, ChronicleError> {
-    let app_state = state.lock().expect("Failed to lock state");
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+    let index_queue = state.index_queue();
 
-    let vault_path = app_state
-        .vault_path
-        .as_ref()
-        .ok_or(ChronicleError::NoVaultOpen)?;
-    let db = app_state.db.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
+    blocking(move || {
+        let full_path = resolve_vault_path(&vault_path, &path)?;
+        ensure_downloaded(&full_path, &path)?;
+        if !full_path.exists() {
+            return Err(ChronicleError::NoteNotFound(path));
+        }
 
-    let full_path = vault_path.join(&path);
-    if !full_path.exists() {
-        return Err(ChronicleError::NoteNotFound(path));
-    }
+        let config = AppConfig::load();
+        let mut content = transform_on_save(&config.plugins.scripts, &path, content);
+        if config.formatting.format_on_save {
+            content = format_with_index(&content, &config.formatting, &db);
+        }
+        if let Err(e) = snapshot_current(&vault_path, &path, &config.snapshots, true) {
+            tracing::warn!(path = %path, error = %e, "Failed to snapshot note");
+        }
+        fs::write(&full_path, &content)?;
 
-    fs::write(&full_path, &content)?;
+        // The note now holds everything the draft did
+        if let Err(e) = remove_draft(&vault_path, &path) {
+            tracing::warn!(path = %path, error = %e, "Failed to discard draft");
+        }
 
-    // Re-index the note
-    let indexer = Indexer::new(vault_path.clone())?;
-    indexer.index_file(db, &full_path)?;
+        // A note already in the index is re-indexed in the background, and
+        // the save answers with its entry as that will leave it
+        let indexed = db_notes::get_note_by_path(&db.conn(), &path)?;
+        let queued = match (index_queue, indexed) {
+            (Some(queue), Some(meta)) => {
+                queue.push(full_path.clone());
+                Some(saved_meta(meta, &full_path, &content)?)
+            }
+            _ => {
+                Indexer::new(vault_path.clone())?.index_file(&db, &full_path)?;
+                None
+            }
+        };
 
-    let conn = db.conn();
-    let meta = db_notes::get_note_by_path(&conn, &path)?
-        .ok_or(ChronicleError::NoteNotFound(path))?;
+        // A failed auto-commit must not fail the save itself
+        let sync_config = config.sync;
+        if sync_config.auto_commit_on_save && GitRepo::is_repo(&vault_path) {
+            let result = open_repo(&vault_path, &sync_config).and_then(|repo| {
+                repo.commit_paths(&[&path], &format!("Update {}", path))
+                    .map_err(ChronicleError::from)
+            });
+            if let Err(e) = result {
+                tracing::warn!(path = %path, error = %e, "Failed to auto-commit note");
+            }
+        }
+
+        match queued {
+            Some(meta) => Ok(meta),
+            None => db_notes::get_note_by_path(&db.conn(), &path)?
+                .ok_or(ChronicleError::NoteNotFound(path)),
+        }
+    })
+    .await
+}
 
+/// A note's index entry updated for content just saved, as indexing it
+/// will leave it
+fn saved_meta(
+    mut meta: db_notes::NoteMeta,
+    full_path: &Path,
+    content: &str,
+) -> Result<db_notes::NoteMeta, ChronicleError> {
+    let filename = full_path.file_name().and_then(|s| s.to_str()).unwrap_or("unknown");
+    let parsed = parse_note(content, filename);
+    meta.title = parsed.title;
+    meta.word_count = parsed.word_count as i32;
+    meta.modified_at = fs::metadata(full_path)?.modified().ok().map(chrono_from_systemtime);
     Ok(meta)
 }
 
+/// What deleting a note would leave dangling elsewhere in the vault
+#[derive(Debug, Clone, Serialize)]
+pub struct DeleteImpact {
+    /// Links from other notes that would break, with their line as context
+    pub broken_links: Vec<Backlink>,
+    /// `![[embeds]]` of the note in other notes that would show nothing
+    pub broken_embeds: Vec<Backlink>,
+    /// Tasks in other notes that link to the note
+    pub linked_tasks: Vec<TaskRecord>,
+    /// Unfinished tasks in the note itself
+    pub open_tasks: Vec<TaskRecord>,
+}
+
+/// Work out what deleting the note at `path` would break
+pub(crate) fn delete_impact(
+    vault_path: &Path,
+    conn: &Connection,
+    path: &str,
+) -> Result<DeleteImpact, ChronicleError> {
+    let mut impact = DeleteImpact {
+        broken_links: Vec::new(),
+        broken_embeds: Vec::new(),
+        linked_tasks: Vec::new(),
+        open_tasks: db_tasks::get_note_tasks(conn, path)?
+            .into_iter()
+            .filter(|task| !task.completed)
+            .collect(),
+    };
+
+    for mut backlink in get_backlinks(conn, path)? {
+        if backlink.source_path == path {
+            continue;
+        }
+        let line = backlink.line_number.and_then(|n| {
+            let source = resolve_vault_path(vault_path, &backlink.source_path).ok()?;
+            let content = fs::read_to_string(source).ok()?;
+            content.lines().nth((n as usize).checked_sub(1)?).map(str::to_string)
+        });
+
+        if let Some(n) = backlink.line_number {
+            if let Some(task) = db_tasks::get_task_at(conn, &backlink.source_path, n)? {
+                if !impact.linked_tasks.iter().any(|t| t.id == task.id) {
+                    impact.linked_tasks.push(task);
+                }
+            }
+        }
+        let embeds_note = line.as_deref().is_some_and(|line| {
+            embedded_targets(line).into_iter().any(|target| {
+                resolve_link_target(conn, target).ok().flatten().as_deref() == Some(path)
+            })
+        });
+        backlink.context = line.map(|line| line.trim().to_string());
+        if embeds_note {
+            impact.broken_embeds.push(backlink);
+        } else {
+            impact.broken_links.push(backlink);
+        }
+    }
+
+    Ok(impact)
+}
+
+/// Names of the notes a line embeds with `![[name]]`, without any heading,
+/// block or alias
+fn embedded_targets(line: &str) -> Vec<&str> {
+    line.split("![[")
+        .skip(1)
+        .filter_map(|rest| rest.split("]]").next())
+        .map(|inner| inner.split(['|', '#']).next().unwrap_or_default().trim())
+        .filter(|target| !target.is_empty())
+        .collect()
+}
+
+/// Preview what deleting a note would break, so the UI can warn first
+#[tauri::command]
+pub async fn get_delete_impact(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<DeleteImpact, ChronicleError> {
+    let vault_path = state.vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || delete_impact(&vault_path, &db.conn(), &path)).await
+}
+
 /// Delete a note
 #[tauri::command]
 pub async fn delete_note(
     path: String,
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
+) -> Result<(), ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || remove_note(&vault_path, &db, &path)).await
+}
+
+/// Delete a note's file, index entry and draft, journaling it for undo
+pub(crate) fn remove_note(
+    vault_path: &Path,
+    db: &Database,
+    path: &str,
 ) -> Result<(), ChronicleError> {
-    let app_state = state.lock().expect("Failed to lock state");
+    let full_path = resolve_vault_path(vault_path, path)?;
 
-    let vault_path = app_state
-        .vault_path
-        .as_ref()
-        .ok_or(ChronicleError::NoVaultOpen)?;
-    let db = app_state.db.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
+    // Snapshot before anything is removed so the delete can be undone
+    if full_path.exists() {
+        let content = fs::read_to_string(&full_path)?;
+        UndoJournal::new(vault_path).record_delete(path, &content)?;
+    }
+    discard_note(vault_path, db, path)
+}
 
-    let full_path = vault_path.join(&path);
+/// Remove a note's file, index entry and draft without journaling it, for
+/// callers that journal it themselves
+pub(crate) fn discard_note(
+    vault_path: &Path,
+    db: &Database,
+    path: &str,
+) -> Result<(), ChronicleError> {
+    let full_path = resolve_vault_path(vault_path, path)?;
 
     // Remove from index first
-    let indexer = Indexer::new(vault_path.clone())?;
+    let indexer = Indexer::new(vault_path.to_path_buf())?;
     indexer.remove_file(db, &full_path)?;
 
     // Delete file
     if full_path.exists() {
         fs::remove_file(&full_path)?;
     }
+    remove_draft(vault_path, path)?;
 
     Ok(())
 }
 
-/// Rename a note
+/// A renamed or moved note
+#[derive(Debug, Clone, Serialize)]
+pub struct RenamedNote {
+    #[serde(flatten)]
+    pub note: db_notes::NoteMeta,
+    /// Notes whose links were rewritten to the new path
+    pub updated_files: Vec<String>,
+}
+
+/// Rename a note, updating links to it across the vault
 #[tauri::command]
 pub async fn rename_note(
     old_path: String,
     new_path: String,
-    state: State<'_, Mutex<AppState>>,
-) -> Result<db_notes::NoteMeta, ChronicleError> {
-    let app_state = state.lock().expect("Failed to lock state");
+    state: State<'_, AppState>,
+) -> Result<RenamedNote, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
 
-    let vault_path = app_state
-        .vault_path
-        .as_ref()
-        .ok_or(ChronicleError::NoVaultOpen)?;
-    let db = app_state.db.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
+    blocking(move || move_note(&vault_path, &db, &old_path, &new_path)).await
+}
 
-    let old_full = vault_path.join(&old_path);
-    let new_full = vault_path.join(&new_path);
+/// Move a note's file, index entry and snapshots to `new_path`, and point
+/// links to it at the new path
+pub(crate) fn move_note(
+    vault_path: &Path,
+    db: &Database,
+    old_path: &str,
+    new_path: &str,
+) -> Result<RenamedNote, ChronicleError> {
+    let old_full = resolve_vault_path(vault_path, old_path)?;
+    let new_full = resolve_vault_path(vault_path, new_path)?;
 
+    ensure_downloaded(&old_full, old_path)?;
     if !old_full.exists() {
-        return Err(ChronicleError::NoteNotFound(old_path));
+        return Err(ChronicleError::NoteNotFound(old_path.to_string()));
     }
 
-    if new_full.exists() {
-        return Err(ChronicleError::NoteExists(new_path));
+    if new_full.exists() || is_placeholder(&new_full) {
+        return Err(ChronicleError::NoteExists(new_path.to_string()));
     }
 
     // Rename file
     fs::rename(&old_full, &new_full)?;
 
+    if let Err(e) = UndoJournal::new(vault_path).record_rename(old_path, new_path) {
+        tracing::warn!(from = %old_path, to = %new_path, error = %e, "Failed to journal rename");
+    }
+    if let Err(e) = SnapshotStore::new(vault_path).rename(old_path, new_path) {
+        tracing::warn!(from = %old_path, to = %new_path, error = %e, "Failed to move snapshots");
+    }
+
     // Update index
-    let conn = db.conn();
-    db_notes::rename_note(&conn, &old_path, &new_path)?;
+    db_notes::rename_note(&db.conn(), old_path, new_path)?;
+    let updated_files = update_links_to(vault_path, db, old_path, new_path)?;
 
-    let meta = db_notes::get_note_by_path(&conn, &new_path)?
-        .ok_or(ChronicleError::NoteNotFound(new_path))?;
+    let note = db_notes::get_note_by_path(&db.conn(), new_path)?
+        .ok_or_else(|| ChronicleError::NoteNotFound(new_path.to_string()))?;
 
-    Ok(meta)
+    Ok(RenamedNote { note, updated_files })
+}
+
+/// Rewrite `[[links]]` and Markdown links to the note moved from `old_path`
+/// so they point at `new_path`, and the moved note's own relative Markdown
+/// links so they still lead where they did; reindex the notes changed.
+/// Links by Zettelkasten or stable ID still resolve and are left alone.
+/// Returns the notes changed.
+fn update_links_to(
+    vault_path: &Path,
+    db: &Database,
+    old_path: &str,
+    new_path: &str,
+) -> Result<Vec<String>, ChronicleError> {
+    let mut targets_by_source: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (source, target) in get_path_link_sources(&db.conn(), old_path)? {
+        targets_by_source.entry(source).or_default().push(target);
+    }
+    // The moved note's links to itself are rebased with the rest of its own
+    for source in get_markdown_link_sources(&db.conn(), old_path)? {
+        if source != new_path {
+            targets_by_source.entry(source).or_default();
+        }
+    }
+
+    let indexer = Indexer::new(vault_path.to_path_buf())?;
+    let mut updated = Vec::new();
+    for (source, targets) in targets_by_source {
+        let full_path = resolve_vault_path(vault_path, &source)?;
+        let mut content = fs::read_to_string(&full_path)?;
+        let mut changed = 0;
+        for target in targets {
+            // Keep the `.md` if the link spelled it out
+            let edit = LinkEdit::Retarget(if target.to_lowercase().ends_with(".md") {
+                new_path
+            } else {
+                new_path.strip_suffix(".md").unwrap_or(new_path)
+            });
+            let (rewritten, count) = rewrite_links(&content, &target, &edit);
+            content = rewritten;
+            changed += count;
+        }
+        if source != new_path {
+            let edit = LinkEdit::Retarget(new_path);
+            let (rewritten, count) = rewrite_markdown_links(&content, &source, old_path, &edit);
+            content = rewritten;
+            changed += count;
+        }
+        if changed == 0 {
+            continue;
+        }
+        fs::write(&full_path, content)?;
+        indexer.index_file(db, &full_path)?;
+        updated.push(source);
+    }
+
+    let full_path = resolve_vault_path(vault_path, new_path)?;
+    if full_path.extension().is_some_and(|ext| ext == "md") {
+        let content = fs::read_to_string(&full_path)?;
+        let (rebased, count) = rebase_markdown_links(&content, old_path, new_path);
+        if count > 0 {
+            fs::write(&full_path, rebased)?;
+            indexer.index_file(db, &full_path)?;
+            if !updated.iter().any(|path| path == new_path) {
+                updated.push(new_path.to_string());
+            }
+        }
+    }
+    Ok(updated)
 }
 
 /// Sanitize a string for use as a filename
-fn sanitize_filename(name: &str) -> String {
+pub(crate) fn sanitize_filename(name: &str) -> String {
     name.chars()
         .map(|c| {
             if c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' {
@@ -212,42 +750,330 @@ fn sanitize_filename(name: &str) -> String {
         .to_lowercase()
 }
 
+/// Build the vault-relative path for a new note
+///
+/// A Zettelkasten `id` fills `{{id}}` in the template, or starts the
+/// filename when the template doesn't place it.
+pub(crate) fn new_note_path(
+    title: &str,
+    current_path: Option<&str>,
+    config: &NewNoteConfig,
+    now: NaiveDateTime,
+    id: Option<&str>,
+) -> String {
+    let folder = match config.location {
+        NewNoteLocation::VaultRoot => String::new(),
+        NewNoteLocation::Folder => config.folder.trim_matches('/').to_string(),
+        NewNoteLocation::CurrentFolder => current_path
+            .and_then(|p| Path::new(p).parent())
+            .map(|p| p.to_string_lossy().trim_matches('/').to_string())
+            .unwrap_or_default(),
+    };
+
+    let mut stem = config
+        .filename_template
+        .replace("{{slug}}", &sanitize_filename(title))
+        .replace("{{title}}", title.trim())
+        .replace("{{date}}", &now.format("%Y-%m-%d").to_string())
+        .replace("{{time}}", &now.format("%H%M%S").to_string())
+        .replace("{{id}}", id.unwrap_or_default());
+    if let Some(id) = id.filter(|_| !config.filename_template.contains("{{id}}")) {
+        stem = format!("{}-{}", id, stem);
+    }
+
+    // The template may not introduce separators or reserved characters
+    stem = stem
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c => c,
+        })
+        .collect::<String>()
+        .trim()
+        .to_string();
+    if stem.is_empty() {
+        stem = "untitled".to_string();
+    }
+
+    if folder.is_empty() {
+        format!("{}.md", stem)
+    } else {
+        format!("{}/{}.md", folder, stem)
+    }
+}
+
 /// Update tags for a note
 #[tauri::command]
 pub async fn update_note_tags(
     path: String,
     tags: Vec<String>,
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
 ) -> Result<db_notes::NoteMeta, ChronicleError> {
-    let app_state = state.lock().expect("Failed to lock state");
-
-    let vault_path = app_state
-        .vault_path
-        .as_ref()
-        .ok_or(ChronicleError::NoVaultOpen)?;
-    let db = app_state.db.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-
-    let full_path = vault_path.join(&path);
-    if !full_path.exists() {
-        return Err(ChronicleError::NoteNotFound(path));
-    }
-
-    // Read current content
-    let content = fs::read_to_string(&full_path)?;
-    
-    // Update tags in content
-    let new_content = crate::vault::update_note_tags(&content, &tags);
-    
-    // Write back
-    fs::write(&full_path, &new_content)?;
-
-    // Re-index the note
-    let indexer = Indexer::new(vault_path.clone())?;
-    indexer.index_file(db, &full_path)?;
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
 
-    let conn = db.conn();
-    let meta = db_notes::get_note_by_path(&conn, &path)?
-        .ok_or(ChronicleError::NoteNotFound(path))?;
+    blocking(move || {
+        let full_path = resolve_vault_path(&vault_path, &path)?;
+        if !full_path.exists() {
+            return Err(ChronicleError::NoteNotFound(path));
+        }
 
-    Ok(meta)
+        // Read current content
+        let content = fs::read_to_string(&full_path)?;
+
+        // Update tags in content
+        let new_content = crate::vault::update_note_tags(&content, &tags);
+
+        // Write back
+        fs::write(&full_path, &new_content)?;
+
+        // Re-index the note
+        let indexer = Indexer::new(vault_path.clone())?;
+        indexer.index_file(&db, &full_path)?;
+
+        let conn = db.conn();
+        let meta = db_notes::get_note_by_path(&conn, &path)?
+            .ok_or(ChronicleError::NoteNotFound(path))?;
+
+        Ok(meta)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn fixed_now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, 9)
+            .unwrap()
+            .and_hms_opt(14, 30, 5)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_new_note_path_default() {
+        let config = NewNoteConfig::default();
+        let path = new_note_path("My Note", Some("projects/alpha.md"), &config, fixed_now(), None);
+        assert_eq!(path, "my-note.md");
+    }
+
+    #[test]
+    fn test_new_note_path_fixed_folder() {
+        let config = NewNoteConfig {
+            location: NewNoteLocation::Folder,
+            folder: "/inbox/".to_string(),
+            ..Default::default()
+        };
+        let path = new_note_path("My Note", None, &config, fixed_now(), None);
+        assert_eq!(path, "inbox/my-note.md");
+    }
+
+    #[test]
+    fn test_new_note_path_current_folder() {
+        let config = NewNoteConfig {
+            location: NewNoteLocation::CurrentFolder,
+            ..Default::default()
+        };
+        let path = new_note_path("My Note", Some("projects/alpha.md"), &config, fixed_now(), None);
+        assert_eq!(path, "projects/my-note.md");
+
+        // Falls back to the vault root without an open note
+        let path = new_note_path("My Note", None, &config, fixed_now(), None);
+        assert_eq!(path, "my-note.md");
+    }
+
+    #[test]
+    fn test_new_note_path_template() {
+        let config = NewNoteConfig {
+            filename_template: "{{date}}-{{slug}}".to_string(),
+            ..Default::default()
+        };
+        let path = new_note_path("My Note", None, &config, fixed_now(), None);
+        assert_eq!(path, "2026-03-09-my-note.md");
+
+        let config = NewNoteConfig {
+            filename_template: "{{time}} {{title}}".to_string(),
+            ..Default::default()
+        };
+        let path = new_note_path("A/B: test", None, &config, fixed_now(), None);
+        assert_eq!(path, "143005 A-B- test.md");
+    }
+
+    #[test]
+    fn test_new_note_path_zettel_id() {
+        let config = NewNoteConfig::default();
+        let path = new_note_path("My Note", None, &config, fixed_now(), Some("202603091430"));
+        assert_eq!(path, "202603091430-my-note.md");
+
+        let config = NewNoteConfig {
+            filename_template: "{{id}} {{title}}".to_string(),
+            ..Default::default()
+        };
+        let path = new_note_path("My Note", None, &config, fixed_now(), Some("202603091430"));
+        assert_eq!(path, "202603091430 My Note.md");
+    }
+
+    #[test]
+    fn test_write_new_notes() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        let db = Database::open_memory().unwrap();
+        let config = AppConfig::default();
+        let spec = |title: &str, folder: Option<&str>, content: Option<&str>, tags: &[&str]| {
+            NewNoteSpec {
+                title: title.to_string(),
+                folder: folder.map(str::to_string),
+                content: content.map(str::to_string),
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+            }
+        };
+
+        let notes = [
+            spec("Overview", Some("projects/alpha"), None, &["project"]),
+            spec("Tasks", Some("projects/alpha"), Some("---\ntags: [todo]\n---\n"), &["#Project"]),
+        ];
+        let created = write_new_notes(root, &db, &config, &notes, fixed_now()).unwrap();
+        let paths: Vec<&str> = created.iter().map(|n| n.path.as_str()).collect();
+        assert_eq!(paths, vec!["projects/alpha/overview.md", "projects/alpha/tasks.md"]);
+        assert_eq!(get_note_tags(&db.conn(), created[1].id).unwrap(), vec!["project", "todo"]);
+
+        // One clash stops the whole batch
+        let notes = [
+            spec("Notes", None, None, &[]),
+            spec("Overview", Some("projects/alpha"), None, &[]),
+        ];
+        let err = write_new_notes(root, &db, &config, &notes, fixed_now()).unwrap_err();
+        assert!(matches!(err, ChronicleError::NoteExists(_)));
+        assert!(!root.join("notes.md").exists());
+        let notes = [spec("Notes", None, None, &[]), spec("notes", None, None, &[])];
+        assert!(write_new_notes(root, &db, &config, &notes, fixed_now()).is_err());
+        assert_eq!(db_notes::list_notes(&db.conn()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_delete_impact() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("plan.md"), "# Plan\n\n- [ ] Draft\n- [x] Outline\n").unwrap();
+        fs::write(root.join("notes.md"), "# Notes\n\nSee [[plan]].\n![[plan|Goals]]\n").unwrap();
+        fs::write(root.join("todo.md"), "# Todo\n\n- [ ] Finish [[Plan|the plan]]\n").unwrap();
+        let db = Database::open_memory().unwrap();
+        Indexer::new(root.to_path_buf()).unwrap().full_index(&db).unwrap();
+
+        let impact = delete_impact(root, &db.conn(), "plan.md").unwrap();
+        let lines = |links: &[Backlink]| -> Vec<(String, Option<String>)> {
+            links.iter().map(|l| (l.source_path.clone(), l.context.clone())).collect()
+        };
+        let mut broken = lines(&impact.broken_links);
+        broken.sort();
+        assert_eq!(
+            broken,
+            vec![
+                ("notes.md".to_string(), Some("See [[plan]].".to_string())),
+                ("todo.md".to_string(), Some("- [ ] Finish [[Plan|the plan]]".to_string())),
+            ]
+        );
+        assert_eq!(
+            lines(&impact.broken_embeds),
+            vec![("notes.md".to_string(), Some("![[plan|Goals]]".to_string()))]
+        );
+        assert_eq!(impact.linked_tasks.len(), 1);
+        assert_eq!(impact.linked_tasks[0].note_path, "todo.md");
+        let open: Vec<&str> = impact.open_tasks.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(open, vec!["Draft"]);
+    }
+
+    #[test]
+    fn test_move_note_updates_links() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir(root.join("archive")).unwrap();
+        fs::write(root.join("plan.md"), "# Plan\n\nSee [[plan#Goals]].\n").unwrap();
+        fs::write(
+            root.join("notes.md"),
+            "# Notes\n\n[[Plan|the plan]] and ![[plan.md]]\n\n```\n[[plan]]\n```\n",
+        )
+        .unwrap();
+        fs::write(root.join("other.md"), "# Other\n\n[[planning]]\n").unwrap();
+        let db = Database::open_memory().unwrap();
+        Indexer::new(root.to_path_buf()).unwrap().full_index(&db).unwrap();
+
+        let moved = move_note(root, &db, "plan.md", "archive/plan-2024.md").unwrap();
+        assert_eq!(moved.note.path, "archive/plan-2024.md");
+        assert_eq!(moved.updated_files, vec!["archive/plan-2024.md", "notes.md"]);
+        assert_eq!(
+            fs::read_to_string(root.join("notes.md")).unwrap(),
+            "# Notes\n\n[[archive/plan-2024|the plan]] and ![[archive/plan-2024.md]]\n\n\
+             ```\n[[plan]]\n```\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("archive/plan-2024.md")).unwrap(),
+            "# Plan\n\nSee [[archive/plan-2024#Goals]].\n"
+        );
+        let backlinks = get_backlinks(&db.conn(), "archive/plan-2024.md").unwrap();
+        assert_eq!(backlinks.len(), 2);
+        assert!(backlinks.iter().all(|b| b.source_path == "notes.md"));
+    }
+
+    #[test]
+    fn test_move_note_updates_markdown_links() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        for folder in ["archive", "Daily", "Refs"] {
+            fs::create_dir(root.join(folder)).unwrap();
+        }
+        fs::write(root.join("plan.md"), "# Plan\n\n[ref](Refs/source.md), [self](plan.md)\n")
+            .unwrap();
+        fs::write(root.join("Refs/source.md"), "# Source\n").unwrap();
+        fs::write(root.join("Daily/today.md"), "# Today\n\nSee [the plan](../plan.md#Goals).\n")
+            .unwrap();
+        let db = Database::open_memory().unwrap();
+        Indexer::new(root.to_path_buf()).unwrap().full_index(&db).unwrap();
+
+        let moved = move_note(root, &db, "plan.md", "archive/plan-2024.md").unwrap();
+        assert_eq!(moved.updated_files, vec!["Daily/today.md", "archive/plan-2024.md"]);
+        assert_eq!(
+            fs::read_to_string(root.join("Daily/today.md")).unwrap(),
+            "# Today\n\nSee [the plan](../archive/plan-2024.md#Goals).\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("archive/plan-2024.md")).unwrap(),
+            "# Plan\n\n[ref](../Refs/source.md), [self](plan-2024.md)\n"
+        );
+        let backlinks = get_backlinks(&db.conn(), "archive/plan-2024.md").unwrap();
+        assert!(backlinks.iter().any(|b| b.source_path == "Daily/today.md"));
+        let backlinks = get_backlinks(&db.conn(), "Refs/source.md").unwrap();
+        assert_eq!(backlinks.len(), 1);
+    }
+
+    #[test]
+    fn test_run_query_blocks() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        for (path, title, tags, pages) in [
+            ("books/dune.md", "Dune", vec!["book".to_string()], "412"),
+            ("books/emma.md", "Emma", vec!["book".to_string()], "474"),
+            ("books/index.md", "Books", vec!["book".to_string()], "1000"),
+            ("plan.md", "Plan", vec![], "9"),
+        ] {
+            let id = db_notes::upsert_note(&conn, path, title, None, None, "h", 0).unwrap();
+            crate::db::tags::set_note_tags(&conn, id, &tags).unwrap();
+            let props = [("pages".to_string(), pages.to_string())];
+            crate::db::properties::replace_note_properties(&conn, id, &props).unwrap();
+        }
+
+        let content = "# Books\n\n```chronicle-query\nall_tags: [book]\nlimit: 1\n\
+                       rollups: [{ key: pages, op: sum }]\n```\n\n\
+                       ```chronicle-query\nall_tags: [book\n```\n";
+        let results = run_query_blocks(&conn, content, "books/index.md").unwrap();
+        assert_eq!((results[0].start_line, results[0].end_line), (3, 7));
+        let titles: Vec<&str> = results[0].notes.iter().map(|n| n.title.as_str()).collect();
+        assert_eq!(titles, vec!["Dune"]);
+        // Rolled up over every match, not just those within the limit
+        assert_eq!(results[0].rollups[0].value, Some(886.0));
+        assert!(results[0].error.is_none());
+        assert!(results[1].notes.is_empty() && results[1].error.is_some());
+    }
 }
@@ -0,0 +1,202 @@
+//! OPML export of the folder tree or tag hierarchy, for outliners and
+//! mind-mapping tools
+
+use chrono::Utc;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::{notes::list_notes, tags::get_note_tags};
+use crate::error::ChronicleError;
+
+/// What the outline is built from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpmlMode {
+    /// Folders, with each note under its folder
+    Folders,
+    /// Tags, nested at `/` (`project/alpha`), with each note under every
+    /// tag it has. Untagged notes are left out.
+    Tags,
+}
+
+/// Export the vault's structure as an OPML document
+#[tauri::command]
+pub async fn export_opml(
+    mode: OpmlMode,
+    state: State<'_, AppState>,
+) -> Result<String, ChronicleError> {
+    let vault_path = state.vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        let title = vault_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Chronicle".to_string());
+        Ok(render_opml(&title, &build_outline(&conn, mode)?))
+    })
+    .await
+}
+
+/// One level of the outline: child groups, then the notes at this level
+#[derive(Debug, Default)]
+struct Outline {
+    groups: BTreeMap<String, Outline>,
+    /// (title, path)
+    notes: Vec<(String, String)>,
+}
+
+impl Outline {
+    fn insert(&mut self, group: &[&str], title: &str, path: &str) {
+        match group.split_first() {
+            Some((first, rest)) => {
+                let group = self.groups.entry(first.to_string()).or_default();
+                group.insert(rest, title, path);
+            }
+            None => self.notes.push((title.to_string(), path.to_string())),
+        }
+    }
+}
+
+fn build_outline(conn: &Connection, mode: OpmlMode) -> Result<Outline, ChronicleError> {
+    let mut outline = Outline::default();
+    for note in list_notes(conn)? {
+        match mode {
+            OpmlMode::Folders => {
+                let folders: Vec<&str> = note.path.split('/').collect();
+                outline.insert(&folders[..folders.len() - 1], &note.title, &note.path);
+            }
+            OpmlMode::Tags => {
+                for tag in get_note_tags(conn, note.id)? {
+                    let levels: Vec<&str> = tag.split('/').filter(|l| !l.is_empty()).collect();
+                    outline.insert(&levels, &note.title, &note.path);
+                }
+            }
+        }
+    }
+    Ok(outline)
+}
+
+fn render_opml(title: &str, outline: &Outline) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n");
+    out.push_str(&format!(
+        "  <head>\n    <title>{}</title>\n    <dateCreated>{}</dateCreated>\n  </head>\n  <body>\n",
+        escape_xml(title),
+        Utc::now().to_rfc2822()
+    ));
+    render_level(outline, 2, &mut out);
+    out.push_str("  </body>\n</opml>\n");
+    out
+}
+
+fn render_level(outline: &Outline, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    for (name, group) in &outline.groups {
+        out.push_str(&format!("{}<outline text=\"{}\">\n", indent, escape_xml(name)));
+        render_level(group, depth + 1, out);
+        out.push_str(&format!("{}</outline>\n", indent));
+    }
+
+    let mut notes: Vec<&(String, String)> = outline.notes.iter().collect();
+    notes.sort_by_key(|(title, _)| title.to_lowercase());
+    for (title, path) in notes {
+        out.push_str(&format!(
+            "{}<outline text=\"{}\" path=\"{}\"/>\n",
+            indent,
+            escape_xml(title),
+            escape_xml(path)
+        ));
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\n', "&#10;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{notes::upsert_note, tags::set_note_tags, Database};
+
+    fn setup() -> Database {
+        let db = Database::open_memory().unwrap();
+        {
+            let conn = db.conn();
+            let notes = [
+                ("inbox.md", "Inbox", vec!["todo"]),
+                ("projects/alpha.md", "Alpha & Co", vec!["project/alpha", "todo"]),
+                ("projects/beta/plan.md", "Plan", vec!["project/beta"]),
+                ("loose.md", "Loose", vec![]),
+            ];
+            for (path, title, tags) in notes {
+                let id = upsert_note(&conn, path, title, None, None, "h", 0).unwrap();
+                let tags: Vec<String> = tags.into_iter().map(String::from).collect();
+                set_note_tags(&conn, id, &tags).unwrap();
+            }
+        }
+        db
+    }
+
+    fn body(opml: &str) -> &str {
+        let start = opml.find("<body>").unwrap();
+        &opml[start..]
+    }
+
+    #[test]
+    fn test_folder_outline() {
+        let db = setup();
+        let opml = render_opml("Vault", &build_outline(&db.conn(), OpmlMode::Folders).unwrap());
+
+        assert!(opml.contains("<title>Vault</title>"));
+        assert_eq!(
+            body(&opml),
+            "<body>
+    <outline text=\"projects\">
+      <outline text=\"beta\">
+        <outline text=\"Plan\" path=\"projects/beta/plan.md\"/>
+      </outline>
+      <outline text=\"Alpha &amp; Co\" path=\"projects/alpha.md\"/>
+    </outline>
+    <outline text=\"Inbox\" path=\"inbox.md\"/>
+    <outline text=\"Loose\" path=\"loose.md\"/>
+  </body>
+</opml>
+"
+        );
+    }
+
+    #[test]
+    fn test_tag_outline() {
+        let db = setup();
+        let opml = render_opml("Vault", &build_outline(&db.conn(), OpmlMode::Tags).unwrap());
+
+        assert_eq!(
+            body(&opml),
+            "<body>
+    <outline text=\"project\">
+      <outline text=\"alpha\">
+        <outline text=\"Alpha &amp; Co\" path=\"projects/alpha.md\"/>
+      </outline>
+      <outline text=\"beta\">
+        <outline text=\"Plan\" path=\"projects/beta/plan.md\"/>
+      </outline>
+    </outline>
+    <outline text=\"todo\">
+      <outline text=\"Alpha &amp; Co\" path=\"projects/alpha.md\"/>
+      <outline text=\"Inbox\" path=\"inbox.md\"/>
+    </outline>
+  </body>
+</opml>
+"
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Script plugin commands
+
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::notes::new_note;
+use crate::commands::vault::AppState;
+use crate::db::{notes as db_notes, Database};
+use crate::error::ChronicleError;
+use crate::models::{AppConfig, ScriptHook, ScriptPlugin};
+use crate::plugins::{check_capabilities, run_script, HookNote, HookRequest, HookResponse};
+use crate::vault::{remove_draft, resolve_vault_path, Indexer};
+
+/// What a command or importer script did
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScriptResult {
+    pub message: Option<String>,
+    /// The current note, if the script rewrote it
+    pub updated: Option<db_notes::NoteMeta>,
+    pub created: Vec<db_notes::NoteMeta>,
+}
+
+/// Configured scripts, for the command palette and import menu
+#[tauri::command]
+pub async fn list_script_plugins() -> Result<Vec<ScriptPlugin>, ChronicleError> {
+    Ok(AppConfig::load().plugins.scripts)
+}
+
+/// Run a command script, optionally on the open note
+#[tauri::command]
+pub async fn run_script_command(
+    name: String,
+    path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ScriptResult, ChronicleError> {
+    let plugin = find_script(&name, ScriptHook::Command)?;
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let note = match &path {
+            Some(path) => {
+                let full_path = resolve_vault_path(&vault_path, path)?;
+                if !full_path.exists() {
+                    return Err(ChronicleError::NoteNotFound(path.clone()));
+                }
+                Some(HookNote {
+                    path: path.clone(),
+                    content: fs::read_to_string(&full_path)?,
+                })
+            }
+            None => None,
+        };
+
+        let request = HookRequest::new(&plugin, note, None);
+        let response = run_script(&plugin, &request)?;
+        apply_response(&vault_path, &db, &plugin, path.as_deref(), response)
+    })
+    .await
+}
+
+/// Import an outside file through an importer script
+#[tauri::command]
+pub async fn import_with_script(
+    name: String,
+    file: String,
+    state: State<'_, AppState>,
+) -> Result<ScriptResult, ChronicleError> {
+    let plugin = find_script(&name, ScriptHook::Import)?;
+    check_import_file(&plugin, &file)?;
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let request = HookRequest::new(&plugin, None, Some(file));
+        let response = run_script(&plugin, &request)?;
+        apply_response(&vault_path, &db, &plugin, None, response)
+    })
+    .await
+}
+
+fn find_script(name: &str, hook: ScriptHook) -> Result<ScriptPlugin, ChronicleError> {
+    AppConfig::load()
+        .plugins
+        .script(name)
+        .filter(|s| s.hook == hook)
+        .cloned()
+        .ok_or_else(|| ChronicleError::PluginNotFound(name.to_string()))
+}
+
+/// Only hand an importer files with one of its extensions
+fn check_import_file(plugin: &ScriptPlugin, file: &str) -> Result<(), ChronicleError> {
+    let extension = Path::new(file).extension().and_then(|e| e.to_str()).unwrap_or_default();
+    let handled = plugin
+        .extensions
+        .iter()
+        .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(extension));
+    if extension.is_empty() || !handled {
+        return Err(ChronicleError::PluginFailed(format!(
+            "{} doesn't import {}",
+            plugin.name, file
+        )));
+    }
+    Ok(())
+}
+
+/// Carry out what a script asked for, within its capabilities
+fn apply_response(
+    vault_path: &Path,
+    db: &Database,
+    plugin: &ScriptPlugin,
+    path: Option<&str>,
+    response: HookResponse,
+) -> Result<ScriptResult, ChronicleError> {
+    check_capabilities(plugin, &response)?;
+    let mut result = ScriptResult {
+        message: response.message,
+        ..Default::default()
+    };
+
+    if let Some(content) = response.content {
+        let path = path.ok_or_else(|| {
+            ChronicleError::PluginFailed(format!("{} returned content but no note is open", plugin.name))
+        })?;
+        let full_path = resolve_vault_path(vault_path, path)?;
+        fs::write(&full_path, &content)?;
+
+        // A draft predating the script's edit would undo it when restored
+        if let Err(e) = remove_draft(vault_path, path) {
+            tracing::warn!(path = %path, error = %e, "Failed to discard draft");
+        }
+
+        let indexer = Indexer::new(vault_path.to_path_buf())?;
+        indexer.index_file(db, &full_path)?;
+        let conn = db.conn();
+        result.updated = db_notes::get_note_by_path(&conn, path)?;
+    }
+
+    for note in response.notes {
+        result
+            .created
+            .push(new_note(vault_path, db, &note.title, note.content, path)?);
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Capability;
+    use crate::plugins::NewHookNote;
+
+    fn script(capabilities: &[Capability]) -> ScriptPlugin {
+        ScriptPlugin {
+            name: "test".to_string(),
+            hook: ScriptHook::Command,
+            command: "true".to_string(),
+            args: Vec::new(),
+            capabilities: capabilities.to_vec(),
+            extensions: Vec::new(),
+            timeout_secs: 5,
+        }
+    }
+
+    #[test]
+    fn test_apply_response() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let db = Database::open_memory().unwrap();
+        fs::write(temp.path().join("a.md"), "# A").unwrap();
+
+        let response = HookResponse {
+            content: Some("# A\n\nRewritten".to_string()),
+            notes: vec![NewHookNote {
+                title: "Spin Off".to_string(),
+                content: None,
+            }],
+            message: Some("Done".to_string()),
+        };
+
+        let limited = script(&[Capability::NoteWrite]);
+        assert!(apply_response(temp.path(), &db, &limited, Some("a.md"), response.clone()).is_err());
+        assert_eq!(fs::read_to_string(temp.path().join("a.md")).unwrap(), "# A");
+
+        let full = script(&[Capability::NoteWrite, Capability::NoteCreate]);
+        let result = apply_response(temp.path(), &db, &full, Some("a.md"), response).unwrap();
+        assert_eq!(result.message.as_deref(), Some("Done"));
+        assert_eq!(result.updated.unwrap().path, "a.md");
+        assert_eq!(result.created[0].title, "Spin Off");
+        assert!(fs::read_to_string(temp.path().join("a.md")).unwrap().ends_with("Rewritten"));
+    }
+
+    #[test]
+    fn test_check_import_file() {
+        let importer = ScriptPlugin {
+            hook: ScriptHook::Import,
+            extensions: vec!["opml".to_string()],
+            ..script(&[Capability::NoteCreate])
+        };
+        assert!(check_import_file(&importer, "/home/me/Feeds.OPML").is_ok());
+        assert!(check_import_file(&importer, "/home/me/.ssh/id_ed25519").is_err());
+        assert!(check_import_file(&importer, "/home/me/feeds.opml.txt").is_err());
+        assert!(check_import_file(&script(&[]), "/home/me/feeds.opml").is_err());
+    }
+}
@@ -0,0 +1,48 @@
+//! Note preview rendering
+
+use std::fs;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::embeds::{load_embed, DEFAULT_EMBED_DEPTH};
+use crate::commands::vault::AppState;
+use crate::db::links::resolve_link_target;
+use crate::error::ChronicleError;
+use crate::publish::{render_markdown, RenderLinks};
+use crate::vault::{parse_frontmatter, resolve_embeds_in, resolve_vault_path};
+
+/// Render a note to HTML as the preview and exports show it, without its
+/// frontmatter. Renders `content` if given, as the note at `path` (for
+/// unsaved edits), else the saved note at `path`.
+#[tauri::command]
+pub async fn render_note_html(
+    path: Option<String>,
+    content: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, ChronicleError> {
+    let vault_path = state.vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let path = path.unwrap_or_default();
+        let content = match content {
+            Some(content) => content,
+            None => {
+                let full_path = resolve_vault_path(&vault_path, &path)?;
+                if !full_path.is_file() {
+                    return Err(ChronicleError::NoteNotFound(path));
+                }
+                fs::read_to_string(&full_path)?
+            }
+        };
+        let (_, body_start) = parse_frontmatter(&content);
+        let body = &content[body_start..];
+
+        let conn = db.conn();
+        let load = |name: &str| load_embed(&vault_path, &conn, name);
+        let embeds = resolve_embeds_in(&path, body, DEFAULT_EMBED_DEPTH, &load);
+        let resolve = |name: &str| resolve_link_target(&conn, name).ok().flatten();
+        Ok(render_markdown(body, &RenderLinks { resolve: &resolve, embeds: &embeds }))
+    })
+    .await
+}
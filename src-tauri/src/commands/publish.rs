@@ -6,10 +6,15 @@ use std::path::{Path, PathBuf};
 
 use pulldown_cmark::{html, Parser};
 use serde::{Deserialize, Serialize};
-use tauri::command;
+use tauri::{command, State};
 
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
 use crate::error::Result;
-use crate::vault::Vault;
+use crate::models::AppConfig;
+use crate::publish::{
+    collect_notes, render_site, PublishManifest, PublishReport, PublishStatusReport,
+};
 
 /// Configuration for web publishing.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -709,12 +714,47 @@ document.getElementById('search-input').addEventListener('input', function(e) {
 /// Publish vault as static website.
 #[command]
 pub async fn publish_vault(
-    vault_path: String,
     config: PublishConfig,
+    state: State<'_, AppState>,
 ) -> Result<String> {
-    let generator = SiteGenerator::new(config, PathBuf::from(&vault_path));
-    generator.generate()?;
-    Ok(format!("Published to {}", vault_path))
+    let vault_path = state.vault_path()?;
+
+    blocking(move || {
+        let output = vault_path.join(&config.output_dir);
+        SiteGenerator::new(config, vault_path).generate()?;
+        Ok(format!("Published to {}", output.display()))
+    })
+    .await
+}
+
+/// Publish the notes marked `publish: true` to the configured target.
+#[command]
+pub async fn publish_notes(state: State<'_, AppState>) -> Result<PublishReport> {
+    let vault_path = state.vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        crate::publish::publish(&vault_path, &conn, &AppConfig::load().publish)
+    })
+    .await
+}
+
+/// Which marked notes are published, stale, new or withdrawn.
+#[command]
+pub async fn publish_status(state: State<'_, AppState>) -> Result<PublishStatusReport> {
+    let vault_path = state.vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        let notes = collect_notes(&vault_path, &conn)?;
+        let config = AppConfig::load().publish;
+        let files = render_site(&notes, &config);
+        let manifest = PublishManifest::load(&vault_path, config.target.as_ref());
+        Ok(crate::publish::publish_status(&notes, &files, &manifest))
+    })
+    .await
 }
 
 #[cfg(test)]
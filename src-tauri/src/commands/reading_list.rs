@@ -0,0 +1,56 @@
+//! Reading list commands: web links collected from notes
+
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::{reading as db_reading, DomainCount, ReadingItem, ReadingStatus};
+use crate::error::ChronicleError;
+
+/// Reading list entries, newest first, optionally filtered by status and domain
+#[tauri::command]
+pub async fn list_reading_list(
+    status: Option<ReadingStatus>,
+    domain: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ReadingItem>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        Ok(db_reading::list_reading_items(&conn, status, domain.as_deref())?)
+    })
+    .await
+}
+
+/// Domains on the reading list with their unread and total counts
+#[tauri::command]
+pub async fn reading_list_domains(
+    state: State<'_, AppState>,
+) -> Result<Vec<DomainCount>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        Ok(db_reading::reading_domains(&conn)?)
+    })
+    .await
+}
+
+/// Mark a reading list entry read or unread
+#[tauri::command]
+pub async fn set_reading_status(
+    id: i64,
+    status: ReadingStatus,
+    state: State<'_, AppState>,
+) -> Result<ReadingItem, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        db_reading::set_reading_status(&conn, id, status, &now)?
+            .ok_or_else(|| ChronicleError::ReadingItemNotFound(id.to_string()))
+    })
+    .await
+}
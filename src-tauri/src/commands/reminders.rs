@@ -0,0 +1,89 @@
+//! Reminder commands and the scheduler that fires them
+
+use chrono::Local;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::reminders::{due_reminders, mark_reminder_fired, upcoming_reminders, Reminder};
+use crate::error::ChronicleError;
+use crate::vault::REMIND_AT_FORMAT;
+
+/// How often the scheduler looks for due reminders
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Reminders still to come, soonest first
+#[tauri::command]
+pub async fn list_upcoming_reminders(
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<Reminder>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        let now = Local::now().format(REMIND_AT_FORMAT).to_string();
+        Ok(upcoming_reminders(&conn, &now, limit.unwrap_or(50))?)
+    })
+    .await
+}
+
+/// Fire due reminders of the open vault as system notifications, and as
+/// `reminder` events for the frontend, for as long as the app runs.
+/// Reminders that came due while the app was closed fire on the first check.
+pub fn start_reminder_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = fire_due_reminders(&app).await {
+                tracing::warn!(error = %e, "Failed to check reminders");
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+async fn fire_due_reminders(app: &AppHandle) -> Result<(), ChronicleError> {
+    let Some(state) = app.try_state::<AppState>() else {
+        return Ok(());
+    };
+    // No vault open (or it's unavailable): nothing to remind of yet. A
+    // read-only instance leaves reminders to the one that has the vault,
+    // so each fires once.
+    let Some((_, db)) = state.background_vault() else {
+        return Ok(());
+    };
+
+    // Marked before notifying, so a failing notification isn't retried
+    // every check
+    let due = blocking(move || {
+        let conn = db.conn();
+        let now = Local::now().format(REMIND_AT_FORMAT).to_string();
+        let due = due_reminders(&conn, &now)?;
+        for reminder in &due {
+            mark_reminder_fired(&conn, reminder.id)?;
+        }
+        Ok(due)
+    })
+    .await?;
+
+    for reminder in due {
+        let body = if reminder.text.is_empty() {
+            reminder.note_path.clone()
+        } else {
+            reminder.text.clone()
+        };
+        if let Err(e) = app
+            .notification()
+            .builder()
+            .title(&reminder.note_title)
+            .body(body)
+            .show()
+        {
+            tracing::warn!(path = %reminder.note_path, error = %e, "Failed to show reminder");
+        }
+        let _ = app.emit("reminder", reminder);
+    }
+    Ok(())
+}
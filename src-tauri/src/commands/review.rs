@@ -0,0 +1,53 @@
+//! Review queue commands: resurfacing old notes for another read
+
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::{notes as db_notes, review as db_review, ReviewItem, ReviewStrategy};
+use crate::error::ChronicleError;
+
+/// Notes count as stale after this many days without being touched
+const DEFAULT_REVIEW_DAYS: u32 = 30;
+const DEFAULT_REVIEW_LIMIT: usize = 10;
+
+/// Notes not modified, opened or reviewed in the last `days` days
+#[tauri::command]
+pub async fn get_review_queue(
+    strategy: Option<ReviewStrategy>,
+    days: Option<u32>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ReviewItem>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let days = days.unwrap_or(DEFAULT_REVIEW_DAYS);
+        let cutoff = (chrono::Utc::now() - chrono::Duration::days(days.into()))
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let conn = db.conn();
+        Ok(db_review::review_queue(
+            &conn,
+            strategy.unwrap_or_default(),
+            &cutoff,
+            limit.unwrap_or(DEFAULT_REVIEW_LIMIT),
+        )?)
+    })
+    .await
+}
+
+/// Mark a note reviewed, keeping it out of the queue for another `days`
+#[tauri::command]
+pub async fn mark_reviewed(path: String, state: State<'_, AppState>) -> Result<(), ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        let note = db_notes::get_note_by_path(&conn, &path)?
+            .ok_or(ChronicleError::NoteNotFound(path))?;
+        let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        db_review::mark_note_reviewed(&conn, note.id, &now)?;
+        Ok(())
+    })
+    .await
+}
@@ -1,65 +1,330 @@
 //! Search commands
 
-use std::fs;
-use std::sync::Mutex;
+use chrono::Local;
+use rusqlite::Connection;
+use std::collections::HashSet;
+use std::path::Path;
 use tauri::State;
 
+use crate::commands::blocking;
 use crate::commands::vault::AppState;
-use crate::db::{links::get_backlinks, search::search_notes as db_search, Backlink, SearchResult};
+use crate::db::{
+    links::get_backlinks, notes::list_note_titles, ocr::search_attachment_text,
+    pdf_text::search_pdf_text, saved_searches as db_saved_searches,
+    search::{self as fts, search_notes as db_search}, smart_folders::notes_matching,
+    AttachmentSearchResult, Backlink, Database, SearchPage, SearchResult, SearchSort,
+    SearchSuggestion,
+};
 use crate::error::ChronicleError;
+use crate::vault::{
+    quick_switch_matches, read_saved_searches, write_saved_searches, QuickSwitchMatch, SavedSearch,
+    SmartFilter,
+};
 
 /// Search notes
 #[tauri::command]
 pub async fn search_notes(
     query: String,
     limit: Option<usize>,
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
 ) -> Result<Vec<SearchResult>, ChronicleError> {
-    let app_state = state.lock().expect("Failed to lock state");
-    let db = app_state.db.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-    let conn = db.conn();
+    let db = state.db()?;
 
-    let results = db_search(&conn, &query, limit.unwrap_or(20))?;
-    Ok(results)
+    blocking(move || {
+        let conn = db.conn();
+
+        let results = db_search(&conn, &query, limit.unwrap_or(20))?;
+        Ok(results)
+    })
+    .await
+}
+
+/// A page of search results in `sort` order (best match first unless
+/// given), with the total count, for scrolling through large result sets.
+/// Ask for the next page with the returned `next_offset`.
+#[tauri::command]
+pub async fn search_notes_page(
+    query: String,
+    sort: Option<SearchSort>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<SearchPage, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let (sort, offset) = (sort.unwrap_or_default(), offset.unwrap_or(0));
+        Ok(fts::search_notes_page(&db.conn(), &query, sort, offset, limit.unwrap_or(20))?)
+    })
+    .await
+}
+
+/// Words in the index completing `prefix`, for type-ahead in the search
+/// box, or close to it if it looks misspelt
+#[tauri::command]
+pub async fn suggest_search_terms(
+    prefix: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SearchSuggestion>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || Ok(fts::suggest_search_terms(&db.conn(), &prefix, limit.unwrap_or(8))?)).await
+}
+
+/// A corrected query to offer when `query` finds nothing, if any of its
+/// words has a close match in the index
+#[tauri::command]
+pub async fn did_you_mean(
+    query: String,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || Ok(fts::did_you_mean(&db.conn(), &query)?)).await
 }
 
-/// Get backlinks to a note with surrounding context
+/// Notes whose title or path loosely matches `query`, best first, for
+/// jumping to a note. Unlike `search_notes` this forgives typos and
+/// skipped letters.
+#[tauri::command]
+pub async fn quick_switch(
+    query: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<QuickSwitchMatch>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let notes = list_note_titles(&db.conn())?;
+        Ok(quick_switch_matches(&query, &notes, limit.unwrap_or(20)))
+    })
+    .await
+}
+
+/// Search the text recognized in image attachments and extracted from
+/// PDFs, best matches first
+#[tauri::command]
+pub async fn search_attachments(
+    query: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<AttachmentSearchResult>, ChronicleError> {
+    let db = state.db()?;
+    let limit = limit.unwrap_or(20);
+
+    blocking(move || {
+        let conn = db.conn();
+        let mut results = search_attachment_text(&conn, &query, limit)?;
+        results.extend(search_pdf_text(&conn, &query, limit)?);
+        results.sort_by(|a, b| a.rank.total_cmp(&b.rank));
+        results.truncate(limit);
+        Ok(results)
+    })
+    .await
+}
+
+/// Get backlinks to a note with the line each link is on, as cached when
+/// the source note was indexed
 #[tauri::command]
 pub async fn get_backlinks_cmd(
     path: String,
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
 ) -> Result<Vec<Backlink>, ChronicleError> {
-    let app_state = state.lock().expect("Failed to lock state");
-    let vault_path = app_state
-        .vault_path
-        .as_ref()
-        .ok_or(ChronicleError::NoVaultOpen)?;
-    let db = app_state.db.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-    let conn = db.conn();
-
-    let mut backlinks = get_backlinks(&conn, &path)?;
-    
-    // Add context by reading source files
-    for backlink in &mut backlinks {
-        if let Some(line_num) = backlink.line_number {
-            let source_path = vault_path.join(&backlink.source_path);
-            if let Ok(content) = fs::read_to_string(&source_path) {
-                let lines: Vec<&str> = content.lines().collect();
-                let idx = (line_num - 1) as usize;
-                if idx < lines.len() {
-                    // Get the line containing the link, trimmed
-                    let line = lines[idx].trim();
-                    // Truncate if too long
-                    let context = if line.len() > 120 {
-                        format!("{}...", &line[..117])
-                    } else {
-                        line.to_string()
-                    };
-                    backlink.context = Some(context);
-                }
-            }
+    let db = state.db()?;
+
+    blocking(move || Ok(get_backlinks(&db.conn(), &path)?)).await
+}
+
+/// Save a named search, replacing any with the same name. Pinned searches
+/// are shown in the sidebar.
+#[tauri::command]
+pub async fn save_search(
+    name: String,
+    query: String,
+    filter: Option<SmartFilter>,
+    pinned: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<SavedSearch, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(ChronicleError::InvalidFilter(
+            "Saved search name must not be empty".to_string(),
+        ));
+    }
+    let filter = filter.unwrap_or_default();
+    filter.validate().map_err(ChronicleError::InvalidFilter)?;
+    let search = SavedSearch {
+        name,
+        query: query.trim().to_string(),
+        filter,
+        pinned: pinned.unwrap_or(false),
+        updated_at: Local::now().to_rfc3339(),
+    };
+
+    blocking(move || {
+        db_saved_searches::save_search(&db.conn(), &search)?;
+        mirror_saved_searches(&vault_path, &db)?;
+        Ok(search)
+    })
+    .await
+}
+
+/// All saved searches, pinned ones first, then by name
+#[tauri::command]
+pub async fn list_saved_searches(
+    state: State<'_, AppState>,
+) -> Result<Vec<SavedSearch>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || Ok(db_saved_searches::list_saved_searches(&db.conn())?)).await
+}
+
+/// Delete a saved search
+#[tauri::command]
+pub async fn delete_saved_search(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<(), ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        if !db_saved_searches::delete_saved_search(&db.conn(), &name)? {
+            return Err(ChronicleError::SavedSearchNotFound(name));
         }
+        mirror_saved_searches(&vault_path, &db)
+    })
+    .await
+}
+
+/// Run a saved search by name: its query's matches among the notes its
+/// filter lets through, best first, or every such note by title if it has
+/// no query
+#[tauri::command]
+pub async fn run_saved_search(
+    name: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SearchResult>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        let search = db_saved_searches::get_saved_search(&conn, &name)?
+            .ok_or(ChronicleError::SavedSearchNotFound(name))?;
+        run_search(&conn, &search, limit.unwrap_or(20))
+    })
+    .await
+}
+
+fn run_search(
+    conn: &Connection,
+    search: &SavedSearch,
+    limit: usize,
+) -> Result<Vec<SearchResult>, ChronicleError> {
+    let notes = notes_matching(conn, &search.filter)?;
+    if search.query.is_empty() {
+        return Ok(notes
+            .into_iter()
+            .take(limit)
+            .map(|note| SearchResult {
+                id: note.id,
+                path: note.path,
+                title: note.title,
+                snippet: String::new(),
+                rank: 0.0,
+                match_count: 0,
+            })
+            .collect());
+    }
+
+    // Search every note, as the filter may leave out the best matches
+    let allowed: HashSet<i64> = notes.iter().map(|note| note.id).collect();
+    let mut results = db_search(conn, &search.query, i64::MAX as usize)?;
+    results.retain(|result| allowed.contains(&result.id));
+    results.truncate(limit);
+    Ok(results)
+}
+
+/// Load the vault's saved searches file into the index, after the vault
+/// opens or a sync brings in other devices' changes
+pub(crate) fn restore_saved_searches(
+    vault_path: &Path,
+    db: &Database,
+) -> Result<(), ChronicleError> {
+    if let Some(searches) = read_saved_searches(vault_path)? {
+        db_saved_searches::replace_saved_searches(&db.conn(), &searches)?;
+    }
+    Ok(())
+}
+
+fn mirror_saved_searches(vault_path: &Path, db: &Database) -> Result<(), ChronicleError> {
+    let searches = db_saved_searches::list_saved_searches(&db.conn())?;
+    write_saved_searches(vault_path, &searches)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{notes::upsert_note, search::update_fts, tags::set_note_tags};
+
+    #[test]
+    fn test_run_search() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        for (path, text, tag) in [
+            ("rust.md", "Lifetimes and the borrow checker", "rust"),
+            ("cpp.md", "No borrow checker here", "cpp"),
+            ("async.md", "Futures and executors", "rust"),
+        ] {
+            let id = upsert_note(&conn, path, path, None, None, "h", 0).unwrap();
+            update_fts(&conn, id, path, text).unwrap();
+            set_note_tags(&conn, id, &[tag.to_string()]).unwrap();
+        }
+        let mut search = SavedSearch {
+            name: "Rust borrowing".to_string(),
+            query: "borrow".to_string(),
+            filter: SmartFilter {
+                all_tags: vec!["rust".to_string()],
+                ..Default::default()
+            },
+            pinned: true,
+            updated_at: "2026-10-01T09:00:00Z".to_string(),
+        };
+
+        let paths = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.path).collect()
+        };
+        assert_eq!(paths(run_search(&conn, &search, 20).unwrap()), vec!["rust.md"]);
+        search.query.clear();
+        assert_eq!(paths(run_search(&conn, &search, 20).unwrap()), vec!["async.md", "rust.md"]);
+        assert_eq!(run_search(&conn, &search, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_saved_searches_survive_a_rebuilt_index() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let db = Database::open_memory().unwrap();
+        let search = SavedSearch {
+            name: "Inbox".to_string(),
+            query: String::new(),
+            filter: SmartFilter {
+                untagged: true,
+                ..Default::default()
+            },
+            pinned: true,
+            updated_at: "2026-10-01T09:00:00Z".to_string(),
+        };
+        db_saved_searches::save_search(&db.conn(), &search).unwrap();
+        mirror_saved_searches(temp.path(), &db).unwrap();
+
+        let rebuilt = Database::open_memory().unwrap();
+        restore_saved_searches(temp.path(), &rebuilt).unwrap();
+        assert_eq!(db_saved_searches::list_saved_searches(&rebuilt.conn()).unwrap(), vec![search]);
     }
-    
-    Ok(backlinks)
 }
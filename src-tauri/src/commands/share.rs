@@ -0,0 +1,131 @@
+//! Sharing commands: clean copies of single notes, and notes combined into
+//! one document, for people outside the vault
+
+use std::fs;
+use std::path::PathBuf;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::{links as db_links, notes as db_notes};
+use crate::error::ChronicleError;
+use crate::models::{AppConfig, PublishFormat};
+use crate::publish::{
+    export_shareable, gather_collection, render_collection, write_collection, CollectionExport,
+    CollectionFormat, ShareExport, SourceNote,
+};
+use crate::vault::{resolve_markdown_link, resolve_vault_path, LinkKind};
+
+/// Links followed from the starting notes when no depth is given
+const DEFAULT_COLLECTION_DEPTH: usize = 2;
+
+/// Export a note for sending to a colleague: links to other notes become
+/// plain text, frontmatter is dropped apart from `keep_properties`, and
+/// embedded attachments are zipped up with it. Writes into `dest_dir`,
+/// as Markdown unless `format` says otherwise.
+#[tauri::command]
+pub async fn export_note_shareable(
+    path: String,
+    dest_dir: String,
+    format: Option<PublishFormat>,
+    keep_properties: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<ShareExport, ChronicleError> {
+    let vault_path = state.vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let dest_dir = PathBuf::from(&dest_dir);
+        if !dest_dir.is_absolute() {
+            return Err(ChronicleError::InvalidPath(dest_dir.to_string_lossy().to_string()));
+        }
+        let full_path = resolve_vault_path(&vault_path, &path)?;
+        if !full_path.exists() {
+            return Err(ChronicleError::NoteNotFound(path));
+        }
+        let content = fs::read_to_string(&full_path)?;
+        let title = match db_notes::get_note_by_path(&db.conn(), &path)? {
+            Some(meta) => meta.title,
+            None => path.trim_end_matches(".md").rsplit('/').next().unwrap_or(&path).to_string(),
+        };
+
+        export_shareable(
+            &vault_path,
+            &path,
+            &title,
+            &content,
+            format.unwrap_or(PublishFormat::Markdown),
+            &keep_properties.unwrap_or_default(),
+            &dest_dir,
+        )
+    })
+    .await
+}
+
+/// Combine a note, or every note in a folder, with the notes they link to
+/// (up to `max_depth` links away) into one document with a table of
+/// contents, for printing. Writes HTML, or PDF through the configured
+/// converter, into `dest_dir`.
+#[tauri::command]
+pub async fn export_collection(
+    root: String,
+    dest_dir: String,
+    format: Option<CollectionFormat>,
+    max_depth: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<CollectionExport, ChronicleError> {
+    let vault_path = state.vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let dest_dir = PathBuf::from(&dest_dir);
+        if !dest_dir.is_absolute() {
+            return Err(ChronicleError::InvalidPath(dest_dir.to_string_lossy().to_string()));
+        }
+        let conn = db.conn();
+        let folder = root.trim_matches('/');
+        let (title, roots) = match db_notes::get_note_by_path(&conn, &root)? {
+            Some(meta) => (meta.title, vec![meta.path]),
+            None if resolve_vault_path(&vault_path, folder)?.is_dir() => {
+                let mut paths: Vec<String> = db_notes::list_notes_in_folder(&conn, folder)?
+                    .into_iter()
+                    .map(|meta| meta.path)
+                    .collect();
+                paths.sort();
+                let name = folder.rsplit('/').next().unwrap_or(folder);
+                (if name.is_empty() { "Notes" } else { name }.to_string(), paths)
+            }
+            None => return Err(ChronicleError::NoteNotFound(root)),
+        };
+
+        let resolve = |from: &str, target: &str, kind: LinkKind| match kind {
+            LinkKind::Wiki => {
+                let name = target.split('#').next().unwrap_or_default();
+                db_links::resolve_link_target(&conn, name).ok().flatten()
+            }
+            LinkKind::Markdown => resolve_markdown_link(from, target)
+                .filter(|path| matches!(db_notes::get_note_by_path(&conn, path), Ok(Some(_)))),
+        };
+        let load = |path: &str| {
+            let meta = db_notes::get_note_by_path(&conn, path).ok()??;
+            let content = fs::read_to_string(vault_path.join(&meta.path)).ok()?;
+            Some(SourceNote {
+                path: meta.path,
+                title: meta.title,
+                content,
+            })
+        };
+        let depth = max_depth.unwrap_or(DEFAULT_COLLECTION_DEPTH);
+        let notes = gather_collection(&roots, depth, load, &resolve);
+
+        let html = render_collection(&title, &notes, &resolve);
+        let format = format.unwrap_or_default();
+        let pdf_command = AppConfig::load().publish.pdf_command;
+        let path = write_collection(&title, &html, format, &pdf_command, &dest_dir)?;
+        Ok(CollectionExport {
+            path,
+            notes: notes.into_iter().map(|note| note.path).collect(),
+        })
+    })
+    .await
+}
@@ -0,0 +1,140 @@
+//! Smart folder commands: saved filters evaluated against the index
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::{notes::NoteMeta, smart_folders as db_smart_folders, Database};
+use crate::error::ChronicleError;
+use crate::vault::{read_smart_folders, write_smart_folders, SmartFilter, SmartFolder};
+
+/// A smart folder with the notes currently matching its filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmartFolderContents {
+    pub folder: SmartFolder,
+    pub notes: Vec<NoteMeta>,
+}
+
+/// Create a smart folder, or update the one with `id`
+#[tauri::command]
+pub async fn save_smart_folder(
+    id: Option<i64>,
+    name: String,
+    filter: SmartFilter,
+    state: State<'_, AppState>,
+) -> Result<SmartFolder, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(ChronicleError::InvalidFilter(
+            "Smart folder name must not be empty".to_string(),
+        ));
+    }
+    filter.validate().map_err(ChronicleError::InvalidFilter)?;
+
+    blocking(move || {
+        let folder = {
+            let conn = db.conn();
+            if let Some(id) = id {
+                if db_smart_folders::get_smart_folder(&conn, id)?.is_none() {
+                    return Err(ChronicleError::SmartFolderNotFound(id.to_string()));
+                }
+            }
+            let updated_at = Local::now().to_rfc3339();
+            db_smart_folders::save_smart_folder(&conn, id, &name, &filter, &updated_at)?
+        };
+        mirror_smart_folders(&vault_path, &db)?;
+        Ok(folder)
+    })
+    .await
+}
+
+/// A smart folder and the notes in it, by title
+#[tauri::command]
+pub async fn get_smart_folder(
+    id: i64,
+    state: State<'_, AppState>,
+) -> Result<SmartFolderContents, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        let folder = db_smart_folders::get_smart_folder(&conn, id)?
+            .ok_or_else(|| ChronicleError::SmartFolderNotFound(id.to_string()))?;
+        let notes = db_smart_folders::notes_matching(&conn, &folder.filter)?;
+        Ok(SmartFolderContents { folder, notes })
+    })
+    .await
+}
+
+/// All smart folders, by name
+#[tauri::command]
+pub async fn list_smart_folders(
+    state: State<'_, AppState>,
+) -> Result<Vec<SmartFolder>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        Ok(db_smart_folders::list_smart_folders(&conn)?)
+    })
+    .await
+}
+
+/// Delete a smart folder
+#[tauri::command]
+pub async fn delete_smart_folder(id: i64, state: State<'_, AppState>) -> Result<(), ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        if !db_smart_folders::delete_smart_folder(&db.conn(), id)? {
+            return Err(ChronicleError::SmartFolderNotFound(id.to_string()));
+        }
+        mirror_smart_folders(&vault_path, &db)
+    })
+    .await
+}
+
+/// Load the vault's smart folders file into the index, after the vault
+/// opens or a sync brings in other devices' changes
+pub(crate) fn restore_smart_folders(vault_path: &Path, db: &Database) -> Result<(), ChronicleError> {
+    if let Some(folders) = read_smart_folders(vault_path)? {
+        db_smart_folders::replace_smart_folders(&db.conn(), &folders)?;
+    }
+    Ok(())
+}
+
+fn mirror_smart_folders(vault_path: &Path, db: &Database) -> Result<(), ChronicleError> {
+    let folders = db_smart_folders::list_smart_folders(&db.conn())?;
+    write_smart_folders(vault_path, &folders)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smart_folders_survive_a_rebuilt_index() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let db = Database::open_memory().unwrap();
+        let filter = SmartFilter {
+            any_tags: vec!["project".to_string()],
+            ..Default::default()
+        };
+        let folder =
+            db_smart_folders::save_smart_folder(&db.conn(), None, "Projects", &filter, "2025-02-01T09:00:00+01:00")
+                .unwrap();
+        mirror_smart_folders(temp.path(), &db).unwrap();
+
+        let rebuilt = Database::open_memory().unwrap();
+        restore_smart_folders(temp.path(), &rebuilt).unwrap();
+        assert_eq!(db_smart_folders::list_smart_folders(&rebuilt.conn()).unwrap(), vec![folder]);
+    }
+}
@@ -0,0 +1,79 @@
+//! Snapshot commands: local version history of notes
+
+use chrono::Duration;
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::notes::{self as db_notes, NoteMeta};
+use crate::error::ChronicleError;
+use crate::models::{AppConfig, SnapshotConfig};
+use crate::vault::{resolve_vault_path, Indexer, NoteSnapshot, SnapshotStore};
+
+/// Keep the note's current content as a snapshot before it is overwritten.
+/// With `debounce` off, a snapshot is taken however recent the last one is.
+pub(crate) fn snapshot_current(
+    vault_path: &Path,
+    path: &str,
+    config: &SnapshotConfig,
+    debounce: bool,
+) -> Result<Option<NoteSnapshot>, ChronicleError> {
+    let full_path = resolve_vault_path(vault_path, path)?;
+    if !config.enabled || !full_path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&full_path)?;
+    let window = if debounce {
+        Duration::minutes(i64::from(config.debounce_minutes))
+    } else {
+        Duration::zero()
+    };
+    Ok(SnapshotStore::new(vault_path).record(path, &content, window, config.keep)?)
+}
+
+/// Earlier versions of a note, newest first
+#[tauri::command]
+pub async fn list_note_snapshots(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<NoteSnapshot>, ChronicleError> {
+    let vault_path = state.vault_path()?;
+
+    blocking(move || Ok(SnapshotStore::new(&vault_path).list(&path)?)).await
+}
+
+/// Replace a note's content with one of its snapshots
+///
+/// The content being replaced is snapshotted first, so a restore can be
+/// undone the same way. A deleted note is brought back.
+#[tauri::command]
+pub async fn restore_snapshot(
+    path: String,
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<NoteMeta, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let full_path = resolve_vault_path(&vault_path, &path)?;
+        let content = SnapshotStore::new(&vault_path)
+            .read(&path, &id)?
+            .ok_or(ChronicleError::SnapshotNotFound(id))?;
+
+        snapshot_current(&vault_path, &path, &AppConfig::load().snapshots, false)?;
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&full_path, content)?;
+
+        Indexer::new(vault_path.clone())?.index_file(&db, &full_path)?;
+
+        let conn = db.conn();
+        db_notes::get_note_by_path(&conn, &path)?.ok_or(ChronicleError::NoteNotFound(path))
+    })
+    .await
+}
@@ -0,0 +1,68 @@
+//! Spaced-repetition review commands
+
+use chrono::Local;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::cards::{self as db_cards, CardRecord};
+use crate::error::ChronicleError;
+use crate::srs::MAX_GRADE;
+
+/// Cards returned per session when no limit is given
+const DEFAULT_DUE_LIMIT: usize = 100;
+
+/// Cards due for review today, overdue ones first
+#[tauri::command]
+pub async fn get_due_cards(
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<CardRecord>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+        let conn = db.conn();
+
+        let cards = db_cards::get_due_cards(&conn, &today, limit.unwrap_or(DEFAULT_DUE_LIMIT))?;
+        Ok(cards)
+    })
+    .await
+}
+
+/// Record a review graded 0 (forgotten) to 5 (perfect recall) and return
+/// the rescheduled card
+#[tauri::command]
+pub async fn review_card(
+    id: i64,
+    grade: u8,
+    state: State<'_, AppState>,
+) -> Result<CardRecord, ChronicleError> {
+    if grade > MAX_GRADE {
+        return Err(ChronicleError::InvalidGrade(grade.to_string()));
+    }
+    let db = state.db()?;
+
+    blocking(move || {
+        let today = Local::now().date_naive();
+        let conn = db.conn();
+
+        let card = db_cards::get_card(&conn, id)?
+            .ok_or_else(|| ChronicleError::CardNotFound(id.to_string()))?;
+
+        let schedule = card.schedule.review(grade);
+        let due = schedule.due_after(today).format("%Y-%m-%d").to_string();
+        db_cards::update_card_schedule(
+            &conn,
+            id,
+            &schedule,
+            &due,
+            &today.format("%Y-%m-%d").to_string(),
+        )?;
+
+        let card = db_cards::get_card(&conn, id)?
+            .ok_or_else(|| ChronicleError::CardNotFound(id.to_string()))?;
+        Ok(card)
+    })
+    .await
+}
@@ -1,12 +1,20 @@
 //! Sync commands for Tauri
 
-use std::sync::Mutex;
+use std::path::Path;
 use tauri::State;
 
+use crate::commands::blocking;
+use crate::commands::search::restore_saved_searches;
+use crate::commands::smart_folders::restore_smart_folders;
+use crate::commands::tags::restore_tag_meta;
 use crate::commands::vault::AppState;
+use crate::commands::views::restore_views;
+use crate::commands::workspaces::restore_workspaces;
 use crate::error::ChronicleError;
+use crate::models::{AppConfig, SyncConfig};
 use crate::sync::{ConflictInfo, ConflictResolution, GitRepo, SyncStatus};
 use crate::sync::conflict::{parse_conflict_markers, resolve_conflict};
+use crate::vault::resolve_vault_path;
 
 /// Result type for sync operations
 #[derive(serde::Serialize)]
@@ -17,163 +25,197 @@ pub struct SyncResult {
     pub message: String,
 }
 
+/// Open the vault repository with the configured author, adding the
+/// configured remote if the repository has none yet
+pub(crate) fn open_repo(vault_path: &Path, config: &SyncConfig) -> Result<GitRepo, ChronicleError> {
+    let repo = GitRepo::open(vault_path)?
+        .with_author(&config.author_name, &config.author_email);
+
+    if repo.remote_url().is_none() {
+        if let Some(url) = config.remote_url.as_deref().filter(|u| !u.trim().is_empty()) {
+            repo.set_remote(url.trim())?;
+        }
+    }
+
+    Ok(repo)
+}
+
 /// Get current sync status
 #[tauri::command]
-pub async fn sync_status(state: State<'_, Mutex<AppState>>) -> Result<SyncStatus, ChronicleError> {
-    let state = state.lock().map_err(|_| ChronicleError::LockFailed)?;
-    
-    let vault_path = state.vault_path.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-    
-    if !GitRepo::is_repo(vault_path) {
-        return Ok(SyncStatus::uninitialized());
-    }
-    
-    let repo = GitRepo::open(vault_path).map_err(|e| ChronicleError::SyncError(e.to_string()))?;
-    repo.status().map_err(|e| ChronicleError::SyncError(e.to_string()))
+pub async fn sync_status(state: State<'_, AppState>) -> Result<SyncStatus, ChronicleError> {
+    let vault_path = state.vault_path()?;
+
+    blocking(move || {
+        if !GitRepo::is_repo(&vault_path) {
+            return Ok(SyncStatus::uninitialized());
+        }
+
+        let repo = open_repo(&vault_path, &AppConfig::load().sync)?;
+        Ok(repo.status()?)
+    })
+    .await
 }
 
 /// Initialize git repository for sync
+///
+/// Without an explicit `remote_url`, the remote from the sync config is used.
 #[tauri::command]
 pub async fn sync_init(
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
     remote_url: Option<String>,
 ) -> Result<SyncStatus, ChronicleError> {
-    let state = state.lock().map_err(|_| ChronicleError::LockFailed)?;
-    
-    let vault_path = state.vault_path.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-    
-    let repo = if GitRepo::is_repo(vault_path) {
-        GitRepo::open(vault_path).map_err(|e| ChronicleError::SyncError(e.to_string()))?
-    } else {
-        GitRepo::init(vault_path).map_err(|e| ChronicleError::SyncError(e.to_string()))?
-    };
-    
-    if let Some(url) = remote_url {
-        repo.set_remote(&url).map_err(|e| ChronicleError::SyncError(e.to_string()))?;
-    }
-    
-    repo.status().map_err(|e| ChronicleError::SyncError(e.to_string()))
-}
+    let vault_path = state.writable_vault_path()?;
 
-/// Push local changes to remote
-#[tauri::command]
-pub async fn sync_push(state: State<'_, Mutex<AppState>>) -> Result<SyncResult, ChronicleError> {
-    let state = state.lock().map_err(|_| ChronicleError::LockFailed)?;
-    
-    let vault_path = state.vault_path.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-    
-    let repo = GitRepo::open(vault_path).map_err(|e| ChronicleError::SyncError(e.to_string()))?;
-    
-    // Commit any pending changes
-    let changed_files = repo.changed_files().map_err(|e| ChronicleError::SyncError(e.to_string()))?;
-    if !changed_files.is_empty() {
-        let message = format!("Update {} notes", changed_files.len());
-        repo.commit(&message).map_err(|e| ChronicleError::SyncError(e.to_string()))?;
-    }
-    
-    // Push to remote
-    repo.push().map_err(|e| ChronicleError::SyncError(e.to_string()))?;
-    
-    Ok(SyncResult {
-        success: true,
-        files_changed: changed_files,
-        conflicts: Vec::new(),
-        message: "Push successful".to_string(),
+    blocking(move || {
+        let config = AppConfig::load().sync;
+
+        if !GitRepo::is_repo(&vault_path) {
+            GitRepo::init(&vault_path)?;
+        }
+        let repo = open_repo(&vault_path, &config)?;
+
+        if let Some(url) = remote_url {
+            repo.set_remote(&url)?;
+        }
+
+        Ok(repo.status()?)
     })
+    .await
 }
 
-/// Pull remote changes
+/// Push local changes to remote
 #[tauri::command]
-pub async fn sync_pull(state: State<'_, Mutex<AppState>>) -> Result<SyncResult, ChronicleError> {
-    let state = state.lock().map_err(|_| ChronicleError::LockFailed)?;
-    
-    let vault_path = state.vault_path.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-    
-    let repo = GitRepo::open(vault_path).map_err(|e| ChronicleError::SyncError(e.to_string()))?;
-    
-    // Commit any pending changes first
-    if repo.is_dirty().map_err(|e| ChronicleError::SyncError(e.to_string()))? {
-        repo.commit("Auto-commit before pull").map_err(|e| ChronicleError::SyncError(e.to_string()))?;
-    }
-    
-    // Pull from remote
-    let conflicts = repo.pull().map_err(|e| ChronicleError::SyncError(e.to_string()))?;
-    
-    if conflicts.is_empty() {
+pub async fn sync_push(state: State<'_, AppState>) -> Result<SyncResult, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+
+    blocking(move || {
+        let repo = open_repo(&vault_path, &AppConfig::load().sync)?;
+
+        // Commit any pending changes
+        let changed_files = repo.changed_files()?;
+        if !changed_files.is_empty() {
+            let message = format!("Update {} notes", changed_files.len());
+            repo.commit(&message)?;
+        }
+
+        // Push to remote
+        repo.push()?;
+
         Ok(SyncResult {
             success: true,
-            files_changed: Vec::new(),
+            files_changed: changed_files,
             conflicts: Vec::new(),
-            message: "Pull successful".to_string(),
+            message: "Push successful".to_string(),
         })
-    } else {
-        Ok(SyncResult {
-            success: false,
-            files_changed: Vec::new(),
-            conflicts,
-            message: "Conflicts detected".to_string(),
-        })
-    }
+    })
+    .await
+}
+
+/// Pull remote changes using the configured pull strategy
+#[tauri::command]
+pub async fn sync_pull(state: State<'_, AppState>) -> Result<SyncResult, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let config = AppConfig::load().sync;
+
+        let repo = open_repo(&vault_path, &config)?;
+
+        // Commit any pending changes first
+        if repo.is_dirty()? {
+            repo.commit("Auto-commit before pull")?;
+        }
+
+        // Pull from remote
+        let conflicts = repo.pull(config.pull_strategy)?;
+
+        if conflicts.is_empty() {
+            restore_workspaces(&vault_path, &db)?;
+            restore_smart_folders(&vault_path, &db)?;
+            restore_views(&vault_path, &db)?;
+            restore_saved_searches(&vault_path, &db)?;
+            restore_tag_meta(&vault_path, &db)?;
+            Ok(SyncResult {
+                success: true,
+                files_changed: Vec::new(),
+                conflicts: Vec::new(),
+                message: "Pull successful".to_string(),
+            })
+        } else {
+            Ok(SyncResult {
+                success: false,
+                files_changed: Vec::new(),
+                conflicts,
+                message: "Conflicts detected".to_string(),
+            })
+        }
+    })
+    .await
 }
 
 /// Get conflict details for a file
 #[tauri::command]
 pub async fn sync_get_conflict(
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
     path: String,
 ) -> Result<ConflictInfo, ChronicleError> {
-    let state = state.lock().map_err(|_| ChronicleError::LockFailed)?;
-    
-    let vault_path = state.vault_path.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-    let file_path = vault_path.join(&path);
-    
-    let content = std::fs::read_to_string(&file_path)
-        .map_err(|e| ChronicleError::Io(e.to_string()))?;
-    
-    let (local, remote, base) = parse_conflict_markers(&content)
-        .ok_or_else(|| ChronicleError::SyncError("No conflict markers found".to_string()))?;
-    
-    Ok(ConflictInfo {
-        path,
-        local_content: local,
-        remote_content: remote,
-        base_content: base,
+    let vault_path = state.vault_path()?;
+
+    blocking(move || {
+        let file_path = resolve_vault_path(&vault_path, &path)?;
+
+        let content = std::fs::read_to_string(&file_path)
+            .map_err(|e| ChronicleError::Io(e.to_string()))?;
+
+        let (local, remote, base) = parse_conflict_markers(&content)
+            .ok_or_else(|| ChronicleError::SyncError("No conflict markers found".to_string()))?;
+
+        Ok(ConflictInfo {
+            path,
+            local_content: local,
+            remote_content: remote,
+            base_content: base,
+        })
     })
+    .await
 }
 
 /// Resolve a conflict
 #[tauri::command]
 pub async fn sync_resolve_conflict(
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
     path: String,
     resolution: ConflictResolution,
 ) -> Result<SyncResult, ChronicleError> {
-    let state = state.lock().map_err(|_| ChronicleError::LockFailed)?;
-    
-    let vault_path = state.vault_path.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-    let file_path = vault_path.join(&path);
-    
-    // Read the conflicted file
-    let content = std::fs::read_to_string(&file_path)
-        .map_err(|e| ChronicleError::Io(e.to_string()))?;
-    
-    let (local, remote, _) = parse_conflict_markers(&content)
-        .ok_or_else(|| ChronicleError::SyncError("No conflict markers found".to_string()))?;
-    
-    // Resolve the conflict
-    let created_files = resolve_conflict(vault_path, &path, resolution, &local, &remote)
-        .map_err(|e| ChronicleError::Io(e.to_string()))?;
-    
-    // Mark as resolved in git
-    let repo = GitRepo::open(vault_path).map_err(|e| ChronicleError::SyncError(e.to_string()))?;
-    for file in &created_files {
-        repo.resolve_conflict(file).map_err(|e| ChronicleError::SyncError(e.to_string()))?;
-    }
-    
-    Ok(SyncResult {
-        success: true,
-        files_changed: created_files,
-        conflicts: Vec::new(),
-        message: "Conflict resolved".to_string(),
+    let vault_path = state.writable_vault_path()?;
+
+    blocking(move || {
+        let file_path = resolve_vault_path(&vault_path, &path)?;
+
+        // Read the conflicted file
+        let content = std::fs::read_to_string(&file_path)
+            .map_err(|e| ChronicleError::Io(e.to_string()))?;
+
+        let (local, remote, _) = parse_conflict_markers(&content)
+            .ok_or_else(|| ChronicleError::SyncError("No conflict markers found".to_string()))?;
+
+        // Resolve the conflict
+        let created_files = resolve_conflict(&vault_path, &path, resolution, &local, &remote)
+            .map_err(|e| ChronicleError::Io(e.to_string()))?;
+
+        // Mark as resolved in git
+        let repo = open_repo(&vault_path, &AppConfig::load().sync)?;
+        for file in &created_files {
+            repo.resolve_conflict(file)?;
+        }
+
+        Ok(SyncResult {
+            success: true,
+            files_changed: created_files,
+            conflicts: Vec::new(),
+            message: "Conflict resolved".to_string(),
+        })
     })
+    .await
 }
@@ -1,45 +1,226 @@
 //! Tag commands
 
-use std::sync::Mutex;
+use std::fs;
+use std::path::Path;
 use tauri::State;
 
+use crate::commands::blocking;
 use crate::commands::vault::AppState;
 use crate::db::notes::NoteMeta;
 use crate::db::{
     notes::get_note_by_id,
-    tags::{get_notes_by_tag as db_get_notes_by_tag, list_tags as db_list_tags, TagInfo},
+    tags::{
+        get_notes_by_tag as db_get_notes_by_tag, get_tag_occurrences as db_get_tag_occurrences,
+        list_tag_meta, list_tags as db_list_tags, rename_tag as db_rename_tag, replace_tag_meta,
+        set_tag_meta, tag_tree, TagInfo, TagOccurrence, TagTreeNode,
+    },
+    Database,
 };
 use crate::error::ChronicleError;
+use crate::vault::{
+    read_tag_meta, rename_tag_in_note, resolve_vault_path, write_all_or_restore, write_tag_meta,
+    Indexer, TagMeta,
+};
 
 /// List all tags
 #[tauri::command]
-pub async fn list_tags(state: State<'_, Mutex<AppState>>) -> Result<Vec<TagInfo>, ChronicleError> {
-    let app_state = state.lock().expect("Failed to lock state");
-    let db = app_state.db.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-    let conn = db.conn();
+pub async fn list_tags(state: State<'_, AppState>) -> Result<Vec<TagInfo>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
 
-    let tags = db_list_tags(&conn)?;
-    Ok(tags)
+        let tags = db_list_tags(&conn)?;
+        Ok(tags)
+    })
+    .await
 }
 
-/// Get notes with a specific tag
+/// Get notes with a tag or one of its nested tags
 #[tauri::command]
 pub async fn get_notes_by_tag(
     tag: String,
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
 ) -> Result<Vec<NoteMeta>, ChronicleError> {
-    let app_state = state.lock().expect("Failed to lock state");
-    let db = app_state.db.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-    let conn = db.conn();
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
 
-    let note_ids = db_get_notes_by_tag(&conn, &tag)?;
-    let mut notes = Vec::new();
+        let note_ids = db_get_notes_by_tag(&conn, &tag)?;
+        let mut notes = Vec::new();
 
-    for id in note_ids {
-        if let Some(note) = get_note_by_id(&conn, id)? {
-            notes.push(note);
+        for id in note_ids {
+            if let Some(note) = get_note_by_id(&conn, id)? {
+                notes.push(note);
+            }
         }
+
+        Ok(notes)
+    })
+    .await
+}
+
+/// Where an inline `#tag` is used, by note and line
+#[tauri::command]
+pub async fn get_tag_occurrences(
+    tag: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<TagOccurrence>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || Ok(db_get_tag_occurrences(&db.conn(), tag.trim_start_matches('#'))?)).await
+}
+
+/// Tags in use as a tree, nested at `/`, with note counts
+#[tauri::command]
+pub async fn list_tag_tree(state: State<'_, AppState>) -> Result<Vec<TagTreeNode>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || Ok(tag_tree(&db.conn())?)).await
+}
+
+/// Set a tag's color, icon and description; empty fields are cleared
+#[tauri::command]
+pub async fn update_tag_meta(
+    tag: String,
+    meta: TagMeta,
+    state: State<'_, AppState>,
+) -> Result<(), ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || apply_tag_meta(&vault_path, &db, &tag, meta)).await
+}
+
+/// Validate and store a tag's style, in the index and the vault's tag file
+fn apply_tag_meta(
+    vault_path: &Path,
+    db: &Database,
+    tag: &str,
+    meta: TagMeta,
+) -> Result<(), ChronicleError> {
+    let tag = tag.trim().trim_start_matches('#');
+    if tag.is_empty() {
+        return Err(ChronicleError::InvalidTag(tag.to_string()));
     }
+    let meta = meta.normalized().map_err(ChronicleError::InvalidTag)?;
 
-    Ok(notes)
+    set_tag_meta(&db.conn(), tag, &meta)?;
+    mirror_tag_meta(vault_path, db)
+}
+
+/// Rename a tag and the tags nested under it, rewriting the frontmatter
+/// and inline `#tags` of every note using them. Nothing is written unless
+/// every note can be. Returns the rewritten notes.
+#[tauri::command]
+pub async fn rename_tag(
+    old: String,
+    new: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    let old = old.trim().trim_start_matches('#').to_string();
+    let new = new.trim().trim_start_matches('#').trim_matches('/').to_string();
+    if old.is_empty() {
+        return Err(ChronicleError::InvalidTag(old));
+    }
+    if new.is_empty() || new.contains(char::is_whitespace) || new.contains('#') {
+        return Err(ChronicleError::InvalidTag(new));
+    }
+
+    blocking(move || {
+        let conn = db.conn();
+        let mut pending = Vec::new();
+        for id in db_get_notes_by_tag(&conn, &old)? {
+            let Some(note) = get_note_by_id(&conn, id)? else {
+                continue;
+            };
+            if !note.path.ends_with(".md") {
+                continue;
+            }
+            let full_path = resolve_vault_path(&vault_path, &note.path)?;
+            let original = fs::read_to_string(&full_path)?;
+            let updated = rename_tag_in_note(&original, &old, &new);
+            if updated != original {
+                pending.push((note.path, full_path, original, updated));
+            }
+        }
+        drop(conn);
+
+        let writes: Vec<_> = pending
+            .iter()
+            .map(|(_, full_path, original, updated)| {
+                (full_path.as_path(), original.as_str(), updated.as_str())
+            })
+            .collect();
+        write_all_or_restore(&writes)?;
+
+        db_rename_tag(&db.conn(), &old, &new)?;
+        mirror_tag_meta(&vault_path, &db)?;
+        let indexer = Indexer::new(vault_path)?;
+        for (_, full_path, _, _) in &pending {
+            indexer.index_file(&db, full_path)?;
+        }
+        Ok(pending.into_iter().map(|(path, ..)| path).collect())
+    })
+    .await
+}
+
+/// Load the vault's tag styles into the index, after the vault opens or a
+/// sync brings in other devices' changes
+pub(crate) fn restore_tag_meta(vault_path: &Path, db: &Database) -> Result<(), ChronicleError> {
+    if let Some(tags) = read_tag_meta(vault_path)? {
+        replace_tag_meta(&db.conn(), &tags)?;
+    }
+    Ok(())
+}
+
+fn mirror_tag_meta(vault_path: &Path, db: &Database) -> Result<(), ChronicleError> {
+    let tags = list_tag_meta(&db.conn())?;
+    write_tag_meta(vault_path, &tags)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_tag_meta() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let db = Database::open_memory().unwrap();
+        let meta = TagMeta {
+            color: Some("#0af".to_string()),
+            ..Default::default()
+        };
+        apply_tag_meta(temp.path(), &db, " #ideas ", meta).unwrap();
+
+        let stored = read_tag_meta(temp.path()).unwrap().unwrap();
+        assert_eq!(stored, list_tag_meta(&db.conn()).unwrap());
+        assert_eq!(stored[0].name, "ideas");
+
+        assert!(matches!(
+            apply_tag_meta(temp.path(), &db, "#", TagMeta::default()),
+            Err(ChronicleError::InvalidTag(_))
+        ));
+    }
+
+    #[test]
+    fn test_tag_meta_survives_a_rebuilt_index() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let db = Database::open_memory().unwrap();
+        let meta = TagMeta {
+            color: Some("#0af".to_string()),
+            ..Default::default()
+        };
+        set_tag_meta(&db.conn(), "ideas", &meta).unwrap();
+        mirror_tag_meta(temp.path(), &db).unwrap();
+
+        let rebuilt = Database::open_memory().unwrap();
+        restore_tag_meta(temp.path(), &rebuilt).unwrap();
+        assert_eq!(list_tag_meta(&rebuilt.conn()).unwrap(), list_tag_meta(&db.conn()).unwrap());
+    }
 }
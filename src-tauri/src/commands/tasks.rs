@@ -0,0 +1,69 @@
+//! Task dashboard commands
+
+use chrono::NaiveDate;
+use std::fs;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::tasks::{self as db_tasks, TaskFilter, TaskRecord};
+use crate::error::ChronicleError;
+use crate::vault::{remove_draft, resolve_vault_path, toggle_task_line, Indexer};
+
+/// Tasks across the vault matching a filter
+#[tauri::command]
+pub async fn query_tasks(
+    filter: Option<TaskFilter>,
+    state: State<'_, AppState>,
+) -> Result<Vec<TaskRecord>, ChronicleError> {
+    let filter = filter.unwrap_or_default();
+    for date in [&filter.due_before, &filter.due_after].into_iter().flatten() {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| ChronicleError::InvalidDate(date.clone()))?;
+    }
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        let tasks = db_tasks::query_tasks(&conn, &filter)?;
+        Ok(tasks)
+    })
+    .await
+}
+
+/// Check or uncheck the task on a line (1-indexed) of a note
+#[tauri::command]
+pub async fn toggle_task(
+    path: String,
+    line: usize,
+    state: State<'_, AppState>,
+) -> Result<TaskRecord, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let full_path = resolve_vault_path(&vault_path, &path)?;
+        if !full_path.exists() {
+            return Err(ChronicleError::NoteNotFound(path));
+        }
+
+        let content = fs::read_to_string(&full_path)?;
+        let not_found = || ChronicleError::TaskNotFound(format!("{}:{}", path, line));
+        let updated = toggle_task_line(&content, line).ok_or_else(not_found)?;
+        fs::write(&full_path, &updated)?;
+
+        // A draft predating the toggle would undo it when restored
+        if let Err(e) = remove_draft(&vault_path, &path) {
+            tracing::warn!(path = %path, error = %e, "Failed to discard draft");
+        }
+
+        let indexer = Indexer::new(vault_path.clone())?;
+        indexer.index_file(&db, &full_path)?;
+
+        let conn = db.conn();
+        let task = db_tasks::get_task_at(&conn, &path, line as i32)?.ok_or_else(not_found)?;
+
+        Ok(task)
+    })
+    .await
+}
@@ -0,0 +1,107 @@
+//! Note template commands
+//!
+//! Templates are Markdown files in the `new_notes.templates_folder`. Their
+//! `{{prompt:...}}` labels are returned with the list so the frontend can
+//! ask for values before creating the note.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::notes::new_note;
+use crate::commands::vault::AppState;
+use crate::db::notes as db_notes;
+use crate::error::ChronicleError;
+use crate::models::AppConfig;
+use crate::vault::{render_template, resolve_vault_path, template_prompts, TemplateContext};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoteTemplate {
+    /// Vault-relative path of the template file
+    pub path: String,
+    /// File name without `.md`
+    pub name: String,
+    /// Labels to ask for, in order
+    pub prompts: Vec<String>,
+}
+
+/// Templates in the configured folder, by name
+#[tauri::command]
+pub async fn list_templates(
+    state: State<'_, AppState>,
+) -> Result<Vec<NoteTemplate>, ChronicleError> {
+    let vault_path = state.vault_path()?;
+    let folder = AppConfig::load()
+        .new_notes
+        .templates_folder
+        .trim_matches('/')
+        .to_string();
+
+    blocking(move || {
+        let dir = resolve_vault_path(&vault_path, &folder)?;
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut templates = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|e| e != "md") || !path.is_file() {
+                continue;
+            }
+            let name = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let content = fs::read_to_string(&path)?;
+            templates.push(NoteTemplate {
+                path: format!("{}/{}.md", folder, name),
+                prompts: template_prompts(&content),
+                name,
+            });
+        }
+        templates.sort_by_key(|t| t.name.to_lowercase());
+
+        Ok(templates)
+    })
+    .await
+}
+
+/// Create a note from a template, filling prompts with `prompts`
+#[tauri::command]
+pub async fn create_note_from_template(
+    title: String,
+    template: String,
+    prompts: HashMap<String, String>,
+    current_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<db_notes::NoteMeta, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        let template_path = resolve_vault_path(&vault_path, &template)?;
+        if !template_path.is_file() {
+            return Err(ChronicleError::NoteNotFound(template));
+        }
+        let source = fs::read_to_string(&template_path)?;
+
+        let config = AppConfig::load();
+        let now = Local::now().naive_local();
+        let ctx = TemplateContext {
+            date: now.date(),
+            now,
+            date_format: "%Y-%m-%d",
+            locale: &config.locale,
+            title: Some(&title),
+            prompts: &prompts,
+        };
+        let content = render_template(&source, &ctx);
+
+        new_note(&vault_path, &db, &title, Some(content), current_path.as_deref())
+    })
+    .await
+}
@@ -0,0 +1,136 @@
+//! Undo commands
+
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::notes::move_note;
+use crate::commands::vault::AppState;
+use crate::db::Database;
+use crate::error::ChronicleError;
+use crate::vault::{resolve_vault_path, Indexer, UndoEntry, UndoJournal, UndoOperation};
+
+/// Revert the most recent delete, rename or merge. Returns the entry that was
+/// undone, or `None` if the journal is empty.
+#[tauri::command]
+pub async fn undo_last_operation(
+    state: State<'_, AppState>,
+) -> Result<Option<UndoEntry>, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || undo_last(&vault_path, &db)).await
+}
+
+fn undo_last(vault_path: &Path, db: &Database) -> Result<Option<UndoEntry>, ChronicleError> {
+    let journal = UndoJournal::new(vault_path);
+    let Some(entry) = journal.last()? else {
+        return Ok(None);
+    };
+
+    match &entry.operation {
+        UndoOperation::Delete { path } => {
+            let full_path = resolve_vault_path(vault_path, path)?;
+            if full_path.exists() {
+                return Err(ChronicleError::NoteExists(path.clone()));
+            }
+            if let Some(parent) = full_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&full_path, journal.snapshot(&entry)?)?;
+            Indexer::new(vault_path.to_path_buf())?.index_file(db, &full_path)?;
+        }
+        UndoOperation::Rename { from, to } => {
+            if let Some(parent) = resolve_vault_path(vault_path, from)?.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            // Moving it back reverts the links and snapshots moved with it
+            move_note(vault_path, db, to, from)?;
+        }
+        UndoOperation::Merge { path, into } => {
+            let full_path = resolve_vault_path(vault_path, path)?;
+            if full_path.exists() {
+                return Err(ChronicleError::NoteExists(path.clone()));
+            }
+            let target_path = resolve_vault_path(vault_path, into)?;
+            if let Some(parent) = full_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let indexer = Indexer::new(vault_path.to_path_buf())?;
+            fs::write(&target_path, journal.target_snapshot(&entry)?)?;
+            indexer.index_file(db, &target_path)?;
+            fs::write(&full_path, journal.snapshot(&entry)?)?;
+            indexer.index_file(db, &full_path)?;
+        }
+    }
+
+    // Only forget the entry once it has actually been reverted, along with
+    // the move back journaled since
+    journal.pop_through(&entry.id)?;
+    Ok(Some(entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{apply_inbox_action, InboxAction};
+    use crate::db::links::get_backlinks;
+    use crate::models::AppConfig;
+
+    #[test]
+    fn test_undo_rename_reverts_links() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        let notes = "# Notes\n\n[[plan]] and [the plan](plan.md)\n";
+        fs::write(root.join("plan.md"), "# Plan\n").unwrap();
+        fs::write(root.join("notes.md"), notes).unwrap();
+        let db = Database::open_memory().unwrap();
+        Indexer::new(root.to_path_buf()).unwrap().full_index(&db).unwrap();
+
+        fs::create_dir(root.join("archive")).unwrap();
+        move_note(root, &db, "plan.md", "archive/plan.md").unwrap();
+        assert_ne!(fs::read_to_string(root.join("notes.md")).unwrap(), notes);
+
+        let undone = undo_last(root, &db).unwrap().unwrap();
+        assert_eq!(
+            undone.operation,
+            UndoOperation::Rename { from: "plan.md".to_string(), to: "archive/plan.md".to_string() }
+        );
+        assert!(root.join("plan.md").exists());
+        assert!(!root.join("archive/plan.md").exists());
+        assert_eq!(fs::read_to_string(root.join("notes.md")).unwrap(), notes);
+        assert_eq!(get_backlinks(&db.conn(), "plan.md").unwrap().len(), 2);
+
+        // The move back isn't left to be undone in turn
+        assert_eq!(undo_last(root, &db).unwrap(), None);
+    }
+
+    #[test]
+    fn test_undo_merge_restores_both_notes() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir(root.join("inbox")).unwrap();
+        let idea = "---\ntags: [idea]\n---\n# Idea\n\nMore cats";
+        let pets = "# Pets\n\nDogs\n";
+        fs::write(root.join("inbox/idea.md"), idea).unwrap();
+        fs::write(root.join("pets.md"), pets).unwrap();
+        let db = Database::open_memory().unwrap();
+        Indexer::new(root.to_path_buf()).unwrap().full_index(&db).unwrap();
+
+        let merge = InboxAction::Merge { into: "pets.md".to_string() };
+        apply_inbox_action(root, &db, &AppConfig::default(), "inbox/idea.md", merge).unwrap();
+        assert!(!root.join("inbox/idea.md").exists());
+
+        let undone = undo_last(root, &db).unwrap().unwrap();
+        assert_eq!(
+            undone.operation,
+            UndoOperation::Merge { path: "inbox/idea.md".to_string(), into: "pets.md".to_string() }
+        );
+        assert_eq!(fs::read_to_string(root.join("inbox/idea.md")).unwrap(), idea);
+        assert_eq!(fs::read_to_string(root.join("pets.md")).unwrap(), pets);
+        let conn = db.conn();
+        assert!(crate::db::notes::get_note_by_path(&conn, "inbox/idea.md").unwrap().is_some());
+        assert_eq!(undo_last(root, &db).unwrap(), None);
+    }
+}
@@ -1,14 +1,26 @@
 //! Vault management commands
 
 use serde::Serialize;
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use tauri::{AppHandle, Emitter, State};
 
-use crate::db::schema::Database;
+use crate::commands::blocking;
+use crate::commands::search::restore_saved_searches;
+use crate::commands::smart_folders::restore_smart_folders;
+use crate::commands::tags::restore_tag_meta;
+use crate::commands::views::restore_views;
+use crate::commands::workspaces::restore_workspaces;
+use crate::db::{is_plaintext_database, remove_database, Database};
 use crate::error::ChronicleError;
-use crate::models::VaultInfo;
-use crate::vault::{Indexer, VaultWatcher};
+use crate::models::{AppConfig, DatabaseConfig, VaultInfo};
+use crate::sync::GitRepo;
+use crate::vault::{
+    extract_pdf_text, index_attachment_text, index_pdf_text, push_change, read_obsidian_settings,
+    recognize_text, scaffold_vault, IndexQueue, Indexer, InstanceLock, VaultFs, VaultTemplate,
+    VaultWatcher, PDF_TEXT_AVAILABLE,
+};
 
 /// Events emitted to frontend
 #[derive(Clone, Serialize)]
@@ -16,6 +28,9 @@ use crate::vault::{Indexer, VaultWatcher};
 pub enum VaultEventPayload {
     #[serde(rename = "note_created")]
     NoteCreated { path: String },
+    /// A batch made with `create_notes`
+    #[serde(rename = "notes_created")]
+    NotesCreated { paths: Vec<String> },
     #[serde(rename = "note_modified")]
     NoteModified { path: String },
     #[serde(rename = "note_deleted")]
@@ -24,54 +39,309 @@ pub enum VaultEventPayload {
     NoteRenamed { old_path: String, new_path: String },
     #[serde(rename = "index_complete")]
     IndexComplete { note_count: usize },
+    #[serde(rename = "vault_unavailable")]
+    VaultUnavailable { path: String },
+    #[serde(rename = "vault_restored")]
+    VaultRestored { note_count: usize },
+    /// The instance that had the vault open for writing let go of it, and
+    /// this one took over
+    #[serde(rename = "vault_writable")]
+    VaultWritable { note_count: usize },
 }
 
 /// Application state
+///
+/// Each part is locked independently and commands clone the handles they
+/// need, so a long index or sync never blocks `get_note` or search.
+///
+/// A vault whose folder disappears (unmounted drive, dropped network share)
+/// stays open but unavailable until the folder returns.
+///
+/// A vault another instance already has open is opened read-only: notes
+/// can be read and searched, and changes the other instance makes show up,
+/// but nothing is written until it lets go.
 #[derive(Default)]
 pub struct AppState {
-    pub db: Option<Database>,
-    pub vault_path: Option<PathBuf>,
-    pub watcher: Option<VaultWatcher>,
+    db: RwLock<Option<Database>>,
+    vault_path: RwLock<Option<PathBuf>>,
+    watcher: Mutex<Option<VaultWatcher>>,
+    /// Key for an encrypted index, kept so the vault can be reopened
+    db_key: RwLock<Option<String>>,
+    /// Where the vault really lives when the open folder is a working copy
+    /// of it, as for Android folders picked through the system picker
+    storage: RwLock<Option<Arc<dyn VaultFs>>>,
+    /// Held while this instance is the vault's writer; `None` when read-only
+    instance_lock: Mutex<Option<InstanceLock>>,
+    /// Indexes saved notes off the save's path
+    index_queue: Mutex<Option<Arc<IndexQueue>>>,
+    unavailable: AtomicBool,
+}
+
+impl AppState {
+    /// Database handle for the open vault
+    pub fn db(&self) -> Result<Database, ChronicleError> {
+        self.ensure_available()?;
+        read_lock(&self.db).clone().ok_or(ChronicleError::NoVaultOpen)
+    }
+
+    /// Root of the open vault
+    pub fn vault_path(&self) -> Result<PathBuf, ChronicleError> {
+        self.ensure_available()?;
+        self.root().ok_or(ChronicleError::NoVaultOpen)
+    }
+
+    /// Root of the open vault, for commands that change it. Fails while
+    /// another instance has the vault open for writing.
+    pub fn writable_vault_path(&self) -> Result<PathBuf, ChronicleError> {
+        let path = self.vault_path()?;
+        if self.is_read_only() {
+            return Err(ChronicleError::VaultReadOnly(path.to_string_lossy().to_string()));
+        }
+        Ok(path)
+    }
+
+    /// The open vault and its database for background jobs that write to
+    /// it, like the reminder and backup schedulers. `None` while no vault
+    /// is open, it's unavailable, or another instance has it and runs them.
+    pub fn background_vault(&self) -> Option<(PathBuf, Database)> {
+        Some((self.writable_vault_path().ok()?, self.db().ok()?))
+    }
+
+    /// Whether the open vault is read-only because another instance has it
+    pub fn is_read_only(&self) -> bool {
+        self.root().is_some() && lock(&self.instance_lock).is_none()
+    }
+
+    /// Root of the open vault, whether or not it is currently available
+    pub fn root(&self) -> Option<PathBuf> {
+        read_lock(&self.vault_path).clone()
+    }
+
+    pub fn is_available(&self) -> bool {
+        !self.unavailable.load(Ordering::SeqCst)
+    }
+
+    fn ensure_available(&self) -> Result<(), ChronicleError> {
+        if self.is_available() {
+            return Ok(());
+        }
+        let path = self.root().unwrap_or_default();
+        Err(ChronicleError::VaultUnavailable(path.to_string_lossy().to_string()))
+    }
+
+    /// Drop the handles into a vault whose folder has gone away, keeping
+    /// its path so it can be reopened when the folder returns
+    pub fn mark_unavailable(&self) {
+        self.unavailable.store(true, Ordering::SeqCst);
+        *lock(&self.watcher) = None;
+        *lock(&self.index_queue) = None;
+        *write_lock(&self.db) = None;
+        *lock(&self.instance_lock) = None;
+    }
+
+    /// Watcher for the open vault, if any
+    pub fn watcher(&self) -> MutexGuard<'_, Option<VaultWatcher>> {
+        lock(&self.watcher)
+    }
+
+    /// Make `vault_path` the open vault, read-only unless `instance_lock`
+    /// is given
+    pub fn set_vault(
+        &self,
+        db: Database,
+        vault_path: PathBuf,
+        watcher: VaultWatcher,
+        instance_lock: Option<InstanceLock>,
+    ) {
+        let index_queue = IndexQueue::start(vault_path.clone(), db.clone());
+        *lock(&self.index_queue) = Some(Arc::new(index_queue));
+        *write_lock(&self.db) = Some(db);
+        *write_lock(&self.vault_path) = Some(vault_path);
+        *lock(&self.watcher) = Some(watcher);
+        self.set_instance_lock(instance_lock);
+        self.unavailable.store(false, Ordering::SeqCst);
+    }
+
+    fn set_instance_lock(&self, instance_lock: Option<InstanceLock>) {
+        *lock(&self.instance_lock) = instance_lock;
+    }
+
+    /// Key the open vault's database was unlocked with
+    pub fn db_key(&self) -> Option<String> {
+        read_lock(&self.db_key).clone()
+    }
+
+    pub fn set_db_key(&self, key: Option<String>) {
+        *write_lock(&self.db_key) = key;
+    }
+
+    /// Storage changes to the open vault are written back to, if its
+    /// folder is a working copy
+    pub fn storage(&self) -> Option<Arc<dyn VaultFs>> {
+        read_lock(&self.storage).clone()
+    }
+
+    pub fn set_storage(&self, storage: Option<Arc<dyn VaultFs>>) {
+        *write_lock(&self.storage) = storage;
+    }
+
+    /// Queue that indexes the open vault's saved notes
+    pub fn index_queue(&self) -> Option<Arc<IndexQueue>> {
+        lock(&self.index_queue).clone()
+    }
+
+    /// Index every note still waiting in the queue. The queue can outlive
+    /// the state through clones held elsewhere, so closing doesn't rely on
+    /// dropping it.
+    pub fn flush_index_queue(&self) {
+        if let Some(queue) = self.index_queue() {
+            queue.flush();
+        }
+    }
+
+    /// Forget the open vault
+    pub fn clear(&self) {
+        self.flush_index_queue();
+        *write_lock(&self.db_key) = None;
+        *write_lock(&self.storage) = None;
+        *lock(&self.watcher) = None;
+        *lock(&self.index_queue) = None;
+        *lock(&self.instance_lock) = None;
+        *write_lock(&self.vault_path) = None;
+        *write_lock(&self.db) = None;
+        self.unavailable.store(false, Ordering::SeqCst);
+    }
+}
+
+// A panic in a command while holding one of these locks poisons it, but the
+// state only holds handles and paths that stay usable, so recover instead of
+// breaking every later command.
+
+fn read_lock<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|poisoned| {
+        tracing::warn!("App state lock was poisoned; recovering");
+        lock.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+fn write_lock<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(|poisoned| {
+        tracing::warn!("App state lock was poisoned; recovering");
+        lock.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+fn lock<T>(lock: &Mutex<T>) -> MutexGuard<'_, T> {
+    lock.lock().unwrap_or_else(|poisoned| {
+        tracing::warn!("App state lock was poisoned; recovering");
+        lock.clear_poison();
+        poisoned.into_inner()
+    })
 }
 
 
+/// Create a vault in `path`, which must be missing or empty, laid out from
+/// `template` and optionally made a git repository. The config is set up
+/// for the new vault; open it with `open_vault`. Returns the notes written.
+#[tauri::command]
+pub async fn create_vault(
+    path: String,
+    template: Option<VaultTemplate>,
+    git_init: Option<bool>,
+) -> Result<Vec<String>, ChronicleError> {
+    blocking(move || {
+        let root = PathBuf::from(&path);
+        let template = template.unwrap_or_default();
+        let mut config = AppConfig::load();
+
+        let notes = scaffold_vault(&root, template, &config).map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => ChronicleError::VaultNotEmpty(path.clone()),
+            _ => e.into(),
+        })?;
+        if git_init.unwrap_or(false) {
+            GitRepo::init(&root)?;
+        }
+
+        template.seed_config(&mut config);
+        config.vault.path = Some(path);
+        config.save()?;
+        Ok(notes)
+    })
+    .await
+}
+
 /// Open a vault directory
 #[tauri::command]
 pub async fn open_vault(
     path: String,
-    state: State<'_, Mutex<AppState>>,
+    db_key: Option<String>,
+    state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<VaultInfo, ChronicleError> {
-    let vault_path = PathBuf::from(&path);
+    let (vault_path, storage) = if is_content_uri(&path) {
+        let (root, storage) = mirror_content_uri(&app, &path).await?;
+        (root, Some(storage))
+    } else {
+        let vault_path = PathBuf::from(&path);
+        if !vault_path.exists() {
+            return Err(ChronicleError::VaultNotFound(path));
+        }
+        (vault_path, None)
+    };
 
-    if !vault_path.exists() {
-        return Err(ChronicleError::VaultNotFound(path));
-    }
+    let root = vault_path.clone();
+    let key = db_key.clone();
+    let loaded = blocking(move || load_vault(root, key.as_deref())).await?;
+    let LoadedVault {
+        db,
+        note_count,
+        watcher,
+        instance_lock,
+    } = loaded;
+    let read_only = instance_lock.is_none();
 
-    // Database path in vault directory
-    let db_path = vault_path.join(".chronicle").join("chronicle.db");
-    if let Some(parent) = db_path.parent() {
-        std::fs::create_dir_all(parent)?;
+    // OCR is slow, so it catches up in the background instead of holding
+    // up the open. A read-only instance leaves it to the vault's writer.
+    let ocr = AppConfig::load().ocr;
+    if ocr.enabled && !read_only {
+        let (root, db) = (vault_path.clone(), db.clone());
+        tauri::async_runtime::spawn_blocking(move || {
+            match index_attachment_text(&root, &db, |image| recognize_text(image, &ocr)) {
+                Ok(report) => tracing::info!(
+                    recognized = report.recognized,
+                    failed = report.failed.len(),
+                    "Attachment text recognition finished"
+                ),
+                Err(e) => tracing::warn!(error = %e, "Attachment text recognition failed"),
+            }
+        });
+    }
+    if PDF_TEXT_AVAILABLE && !read_only {
+        let (root, db) = (vault_path.clone(), db.clone());
+        tauri::async_runtime::spawn_blocking(move || {
+            match index_pdf_text(&root, &db, extract_pdf_text) {
+                Ok(report) => tracing::info!(
+                    extracted = report.extracted,
+                    failed = report.failed.len(),
+                    "PDF text extraction finished"
+                ),
+                Err(e) => tracing::warn!(error = %e, "PDF text extraction failed"),
+            }
+        });
     }
-
-    // Open database
-    let db = Database::open(&db_path).map_err(|e| ChronicleError::Database(e.to_string()))?;
-
-    // Index vault
-    let indexer = Indexer::new(vault_path.clone())?;
-    let note_count = indexer.full_index(&db)?;
-
-    // Start file watcher
-    let watcher =
-        VaultWatcher::new(vault_path.clone()).map_err(|e| ChronicleError::Io(e.to_string()))?;
 
     // Update state
-    {
-        let mut app_state = state.lock().expect("Failed to lock state");
-        app_state.db = Some(db);
-        app_state.vault_path = Some(vault_path.clone());
-        app_state.watcher = Some(watcher);
-    }
+    state.set_vault(db, vault_path.clone(), watcher, instance_lock);
+    state.set_db_key(db_key);
+    // A working copy is reported by the URI it mirrors, which is what
+    // reopens it
+    let shown_path = match storage {
+        Some(_) => path,
+        None => vault_path.to_string_lossy().to_string(),
+    };
+    state.set_storage(storage);
 
     // Emit index complete event
     let _ = app.emit(
@@ -80,124 +350,543 @@ pub async fn open_vault(
     );
 
     Ok(VaultInfo {
-        path: vault_path.to_string_lossy().to_string(),
+        path: shown_path,
         note_count,
         is_open: true,
+        read_only,
+    })
+}
+
+/// Tree URIs of Android folders picked through the system picker
+fn is_content_uri(path: &str) -> bool {
+    path.starts_with("content://")
+}
+
+/// Copy a vault picked through the Storage Access Framework into app
+/// storage, returning the working copy's folder and the vault's storage
+#[cfg(target_os = "android")]
+async fn mirror_content_uri(
+    app: &AppHandle,
+    uri: &str,
+) -> Result<(PathBuf, Arc<dyn VaultFs>), ChronicleError> {
+    use crate::vault::{mirror_vault, saf};
+
+    let storage: Arc<dyn VaultFs> = Arc::new(saf::SafFs::new(app, uri.to_string()));
+    let root = saf::mirror_dir(app, uri)?;
+    let (source, local) = (storage.clone(), root.clone());
+    let files = blocking(move || Ok(mirror_vault(source.as_ref(), &local)?)).await?;
+    tracing::info!(uri = %uri, files, "Mirrored vault into app storage");
+    Ok((root, storage))
+}
+
+#[cfg(not(target_os = "android"))]
+async fn mirror_content_uri(
+    _app: &AppHandle,
+    uri: &str,
+) -> Result<(PathBuf, Arc<dyn VaultFs>), ChronicleError> {
+    Err(ChronicleError::VaultNotFound(uri.to_string()))
+}
+
+/// Let the user pick a vault folder with the system picker, returning the
+/// content URI to open it with. Only Android needs this; elsewhere the
+/// frontend's folder dialog gives a plain path.
+#[cfg(target_os = "android")]
+#[tauri::command]
+pub async fn pick_vault_folder(app: AppHandle) -> Result<String, ChronicleError> {
+    blocking(move || Ok(crate::vault::saf::pick_folder(&app)?)).await
+}
+
+#[cfg(not(target_os = "android"))]
+#[tauri::command]
+pub async fn pick_vault_folder() -> Result<String, ChronicleError> {
+    Err(ChronicleError::InvalidPath(
+        "The system folder picker is only used on Android".to_string(),
+    ))
+}
+
+/// A vault opened by `load_vault`
+struct LoadedVault {
+    db: Database,
+    note_count: usize,
+    watcher: VaultWatcher,
+    /// `None` if another instance has the vault, which is then read-only
+    instance_lock: Option<InstanceLock>,
+}
+
+/// Open the vault database, index the vault and start watching it. If
+/// another instance has the vault open, it keeps the index up to date, so
+/// the vault is only attached to read-only.
+fn load_vault(root: PathBuf, db_key: Option<&str>) -> Result<LoadedVault, ChronicleError> {
+    let instance_lock = InstanceLock::acquire(&root)?;
+    let db = open_index(&root, db_key, instance_lock.is_some())?;
+    let note_count = match instance_lock {
+        Some(_) => {
+            follow_obsidian_settings(&root);
+            index_vault(&root, &db)?
+        }
+        None => {
+            tracing::info!(path = ?root, "Vault is open in another instance; opening read-only");
+            crate::db::notes::list_notes(&db.conn())?.len()
+        }
+    };
+
+    // Start file watcher
+    let watcher = VaultWatcher::new(root).map_err(|e| ChronicleError::Io(e.to_string()))?;
+
+    Ok(LoadedVault {
+        db,
+        note_count,
+        watcher,
+        instance_lock,
+    })
+}
+
+/// Index the vault and load the settings it carries into the index.
+/// Returns the note count.
+fn index_vault(root: &Path, db: &Database) -> Result<usize, ChronicleError> {
+    let indexer = Indexer::new(root.to_path_buf())?;
+    let note_count = indexer.full_index(db)?;
+    if let Err(e) = restore_workspaces(root, db) {
+        tracing::warn!(error = %e, "Failed to load saved workspaces");
+    }
+    if let Err(e) = restore_smart_folders(root, db) {
+        tracing::warn!(error = %e, "Failed to load smart folders");
+    }
+    if let Err(e) = restore_views(root, db) {
+        tracing::warn!(error = %e, "Failed to load saved views");
+    }
+    if let Err(e) = restore_saved_searches(root, db) {
+        tracing::warn!(error = %e, "Failed to load saved searches");
+    }
+    if let Err(e) = restore_tag_meta(root, db) {
+        tracing::warn!(error = %e, "Failed to load tag styles");
+    }
+    Ok(note_count)
+}
+
+/// Bring the config in line with the vault's Obsidian settings, so a vault
+/// shared with Obsidian puts new files in the same places
+fn follow_obsidian_settings(root: &Path) {
+    let mut config = AppConfig::load();
+    if !config.vault.follow_obsidian_settings {
+        return;
+    }
+    let settings = match read_obsidian_settings(root) {
+        Ok(Some(settings)) => settings,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to read Obsidian settings");
+            return;
+        }
+    };
+
+    let changed = settings.apply(&mut config);
+    if changed.is_empty() {
+        return;
+    }
+    let saved = config
+        .validate()
+        .and_then(|_| config.save().map_err(|e| e.to_string()));
+    match saved {
+        Ok(()) => tracing::info!(settings = ?changed, "Applied Obsidian settings"),
+        Err(e) => tracing::warn!(error = %e, "Failed to apply Obsidian settings"),
+    }
+}
+
+/// Open the index database in the vault's `.chronicle` folder
+pub fn open_index(
+    root: &Path,
+    db_key: Option<&str>,
+    writable: bool,
+) -> Result<Database, ChronicleError> {
+    let db_path = root.join(".chronicle").join("chronicle.db");
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    open_database(&db_path, &AppConfig::load().database, db_key, writable)
+}
+
+/// Open the index database, encrypted or not as configured
+///
+/// The index can always be rebuilt from the notes, so a database left over
+/// from the other mode is deleted and recreated rather than migrated;
+/// unless `writable` is false, as it is then the live index of the
+/// instance that has the vault open.
+fn open_database(
+    db_path: &Path,
+    config: &DatabaseConfig,
+    key: Option<&str>,
+    writable: bool,
+) -> Result<Database, ChronicleError> {
+    let plaintext = if db_path.exists() {
+        Some(is_plaintext_database(db_path)?)
+    } else {
+        None
+    };
+    if !writable && plaintext == Some(config.encrypted) {
+        return Err(ChronicleError::Database(format!(
+            "The index at {} is {} and in use by the instance that has the vault open",
+            db_path.display(),
+            if config.encrypted { "not encrypted" } else { "encrypted" }
+        )));
+    }
+
+    if !config.encrypted {
+        if plaintext == Some(false) {
+            tracing::info!(path = ?db_path, "Replacing encrypted index with a plaintext one");
+            remove_database(db_path)?;
+        }
+        return Database::open(db_path).map_err(|e| ChronicleError::Database(e.to_string()));
+    }
+
+    if !Database::supports_encryption() {
+        return Err(ChronicleError::Database(
+            "Database encryption needs a build with the sqlcipher feature".to_string(),
+        ));
+    }
+    let key = key
+        .filter(|k| !k.is_empty())
+        .ok_or(ChronicleError::DatabaseKeyRequired)?;
+    if plaintext == Some(true) {
+        tracing::info!(path = ?db_path, "Replacing plaintext index with an encrypted one");
+        remove_database(db_path)?;
+    }
+
+    Database::open_encrypted(db_path, key).map_err(|e| {
+        if e.sqlite_error_code() == Some(rusqlite::ErrorCode::NotADatabase) {
+            ChronicleError::InvalidDatabaseKey
+        } else {
+            ChronicleError::Database(e.to_string())
+        }
     })
 }
 
 /// Get current vault info
 #[tauri::command]
 pub async fn get_vault_info(
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
 ) -> Result<VaultInfo, ChronicleError> {
-    let app_state = state.lock().expect("Failed to lock state");
-
-    match &app_state.vault_path {
+    match state.root() {
         Some(path) => {
-            let note_count = if let Some(db) = &app_state.db {
-                let conn = db.conn();
-                crate::db::notes::list_notes(&conn)
-                    .map(|notes| notes.len())
-                    .unwrap_or(0)
-            } else {
-                0
+            let note_count = match state.db() {
+                Ok(db) => blocking(move || {
+                    let conn = db.conn();
+                    Ok(crate::db::notes::list_notes(&conn)?.len())
+                })
+                .await
+                .unwrap_or(0),
+                Err(_) => 0,
             };
 
             Ok(VaultInfo {
                 path: path.to_string_lossy().to_string(),
                 note_count,
                 is_open: true,
+                read_only: state.is_read_only(),
             })
         }
         None => Ok(VaultInfo {
             path: String::new(),
             note_count: 0,
             is_open: false,
+            read_only: false,
         }),
     }
 }
 
 /// Close the current vault
 #[tauri::command]
-pub async fn close_vault(state: State<'_, Mutex<AppState>>) -> Result<(), ChronicleError> {
-    let mut app_state = state.lock().expect("Failed to lock state");
-
-    app_state.db = None;
-    app_state.vault_path = None;
-    app_state.watcher = None;
-
+pub async fn close_vault(state: State<'_, AppState>) -> Result<(), ChronicleError> {
+    state.clear();
     Ok(())
 }
 
 /// Poll for file system events (call periodically from frontend)
 #[tauri::command]
 pub async fn poll_vault_events(
-    state: State<'_, Mutex<AppState>>,
+    state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<(), ChronicleError> {
-    let app_state = state.lock().expect("Failed to lock state");
-    
-    if let Some(watcher) = &app_state.watcher {
-        let db = app_state.db.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-        let vault_path = app_state.vault_path.as_ref().ok_or(ChronicleError::NoVaultOpen)?;
-        
-        let events = watcher.drain_events();
+    let Some(root) = state.root() else {
+        return Ok(());
+    };
+
+    let present = {
+        let root = root.clone();
+        blocking(move || Ok(root.is_dir())).await?
+    };
+    match (present, state.is_available()) {
+        (true, true) => {}
+        (false, true) => {
+            tracing::warn!(path = ?root, "Vault folder disappeared");
+            state.mark_unavailable();
+            let _ = app.emit(
+                "vault-event",
+                VaultEventPayload::VaultUnavailable { path: root.to_string_lossy().to_string() },
+            );
+            return Ok(());
+        }
+        (false, false) => return Ok(()),
+        (true, false) => {
+            // The old database handle and watcher died with the mount
+            let loaded = blocking({
+                let root = root.clone();
+                let key = state.db_key();
+                move || load_vault(root, key.as_deref())
+            })
+            .await?;
+            let note_count = loaded.note_count;
+            state.set_vault(loaded.db, root.clone(), loaded.watcher, loaded.instance_lock);
+            tracing::info!(path = ?root, "Vault folder is back");
+            let _ = app.emit("vault-event", VaultEventPayload::VaultRestored { note_count });
+            return Ok(());
+        }
+    }
+
+    // Take the vault over once the instance that had it lets go
+    if state.is_read_only() {
+        let db = state.db()?;
+        let taken = blocking({
+            let root = root.clone();
+            move || match InstanceLock::acquire(&root)? {
+                Some(lock) => Ok(Some((lock, index_vault(&root, &db)?))),
+                None => Ok(None),
+            }
+        })
+        .await?;
+        if let Some((lock, note_count)) = taken {
+            state.set_instance_lock(Some(lock));
+            tracing::info!(path = ?root, "Vault is now writable");
+            let _ = app.emit("vault-event", VaultEventPayload::VaultWritable { note_count });
+        }
+    }
+    // A read-only instance leaves indexing and write-back to the writer and
+    // only passes on what changed
+    let writable = !state.is_read_only();
+
+    // Only hold the watcher lock long enough to take the queued events
+    let events = state.watcher().as_ref().map(|watcher| watcher.drain_events());
+
+    let Some(events) = events else {
+        return Ok(());
+    };
+    let db = state.db()?;
+    let vault_path = state.vault_path()?;
+    let storage = state.storage().filter(|_| writable);
+    let index_queue = state.index_queue().filter(|_| writable);
+
+    blocking(move || {
         let indexer = Indexer::new(vault_path.clone())?;
-        
+
         for event in events {
+            if let Some(storage) = &storage {
+                if let Err(e) = push_change(storage.as_ref(), &vault_path, &event) {
+                    tracing::warn!(
+                        event = ?event,
+                        error = %e,
+                        "Failed to write change back to vault"
+                    );
+                }
+            }
             match event {
                 crate::vault::VaultEvent::Created(path) => {
                     // Index the new file
-                    if let Err(e) = indexer.index_file(db, &path) {
-                        eprintln!("Failed to index created file: {}", e);
+                    if writable {
+                        if let Err(e) = indexer.index_file(&db, &path) {
+                            tracing::warn!(path = ?path, error = %e, "Failed to index created file");
+                        }
                     }
-                    let rel_path = path.strip_prefix(vault_path)
+                    let rel_path = path.strip_prefix(&vault_path)
                         .map(|p| p.to_string_lossy().to_string())
                         .unwrap_or_else(|_| path.to_string_lossy().to_string());
                     let _ = app.emit("vault-event", VaultEventPayload::NoteCreated { path: rel_path });
                 }
                 crate::vault::VaultEvent::Modified(path) => {
-                    // Re-index the file
-                    if let Err(e) = indexer.index_file(db, &path) {
-                        eprintln!("Failed to index modified file: {}", e);
+                    // Re-index the file through the queue, which the save
+                    // that caused the event has usually queued it on already
+                    if let Some(queue) = &index_queue {
+                        queue.push(path.clone());
+                    } else if writable {
+                        if let Err(e) = indexer.index_file(&db, &path) {
+                            tracing::warn!(path = ?path, error = %e, "Failed to index modified file");
+                        }
                     }
-                    let rel_path = path.strip_prefix(vault_path)
+                    let rel_path = path.strip_prefix(&vault_path)
                         .map(|p| p.to_string_lossy().to_string())
                         .unwrap_or_else(|_| path.to_string_lossy().to_string());
                     let _ = app.emit("vault-event", VaultEventPayload::NoteModified { path: rel_path });
                 }
                 crate::vault::VaultEvent::Deleted(path) => {
                     // Remove from index
-                    if let Err(e) = indexer.remove_file(db, &path) {
-                        eprintln!("Failed to remove deleted file from index: {}", e);
+                    if writable {
+                        if let Err(e) = indexer.remove_file(&db, &path) {
+                            tracing::warn!(path = ?path, error = %e, "Failed to remove deleted file from index");
+                        }
                     }
-                    let rel_path = path.strip_prefix(vault_path)
+                    let rel_path = path.strip_prefix(&vault_path)
                         .map(|p| p.to_string_lossy().to_string())
                         .unwrap_or_else(|_| path.to_string_lossy().to_string());
                     let _ = app.emit("vault-event", VaultEventPayload::NoteDeleted { path: rel_path });
                 }
                 crate::vault::VaultEvent::Renamed { from, to } => {
                     // Update index for rename
-                    let old_rel = from.strip_prefix(vault_path)
+                    let old_rel = from.strip_prefix(&vault_path)
                         .map(|p| p.to_string_lossy().to_string())
                         .unwrap_or_else(|_| from.to_string_lossy().to_string());
-                    let new_rel = to.strip_prefix(vault_path)
+                    let new_rel = to.strip_prefix(&vault_path)
                         .map(|p| p.to_string_lossy().to_string())
                         .unwrap_or_else(|_| to.to_string_lossy().to_string());
-                    
+
                     // Rename in DB
-                    let conn = db.conn();
-                    let _ = crate::db::notes::rename_note(&conn, &old_rel, &new_rel);
-                    
-                    let _ = app.emit("vault-event", VaultEventPayload::NoteRenamed { 
-                        old_path: old_rel, 
-                        new_path: new_rel 
+                    if writable {
+                        let conn = db.conn();
+                        let _ = crate::db::notes::rename_note(&conn, &old_rel, &new_rel);
+                    }
+
+                    let _ = app.emit("vault-event", VaultEventPayload::NoteRenamed {
+                        old_path: old_rel,
+                        new_path: new_rel
                     });
                 }
             }
         }
+
+        Ok(())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_state_open_and_clear() {
+        let temp = TempDir::new().unwrap();
+        let state = AppState::default();
+        assert!(matches!(state.db(), Err(ChronicleError::NoVaultOpen)));
+
+        let db = Database::open_memory().unwrap();
+        let watcher = VaultWatcher::new(temp.path().to_path_buf()).unwrap();
+        let instance_lock = InstanceLock::acquire(temp.path()).unwrap();
+        state.set_vault(db, temp.path().to_path_buf(), watcher, instance_lock);
+
+        // Handles outlive the locks, so holding one doesn't block the others
+        let db = state.db().unwrap();
+        let _conn = db.conn();
+        assert_eq!(state.vault_path().unwrap(), temp.path());
+        assert!(state.watcher().is_some());
+
+        state.mark_unavailable();
+        assert!(matches!(state.vault_path(), Err(ChronicleError::VaultUnavailable(_))));
+        assert!(matches!(state.db(), Err(ChronicleError::VaultUnavailable(_))));
+        assert_eq!(state.root().unwrap(), temp.path());
+        assert!(state.watcher().is_none());
+
+        state.set_db_key(Some("secret".to_string()));
+        state.clear();
+        assert!(state.is_available());
+        assert_eq!(state.db_key(), None);
+        assert!(matches!(state.vault_path(), Err(ChronicleError::NoVaultOpen)));
+        assert!(state.watcher().is_none());
+    }
+
+    #[test]
+    fn test_second_instance_is_read_only() {
+        let temp = TempDir::new().unwrap();
+        let first = AppState::default();
+        let loaded = load_vault(temp.path().to_path_buf(), None).unwrap();
+        assert!(loaded.instance_lock.is_some());
+        first.set_vault(loaded.db, temp.path().to_path_buf(), loaded.watcher, loaded.instance_lock);
+        assert!(!first.is_read_only());
+
+        let second = AppState::default();
+        let loaded = load_vault(temp.path().to_path_buf(), None).unwrap();
+        assert!(loaded.instance_lock.is_none());
+        second.set_vault(loaded.db, temp.path().to_path_buf(), loaded.watcher, None);
+        assert!(second.is_read_only());
+        assert!(second.db().is_ok());
+        assert!(matches!(second.writable_vault_path(), Err(ChronicleError::VaultReadOnly(_))));
+
+        // Once the first instance closes the vault, the second can take it over
+        first.clear();
+        assert!(InstanceLock::acquire(temp.path()).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_background_jobs_run_in_one_instance() {
+        let temp = TempDir::new().unwrap();
+        let first = AppState::default();
+        assert!(first.background_vault().is_none());
+        let loaded = load_vault(temp.path().to_path_buf(), None).unwrap();
+        first.set_vault(loaded.db, temp.path().to_path_buf(), loaded.watcher, loaded.instance_lock);
+        let (vault_path, _db) = first.background_vault().unwrap();
+        assert_eq!(vault_path, temp.path());
+
+        let second = AppState::default();
+        let loaded = load_vault(temp.path().to_path_buf(), None).unwrap();
+        second.set_vault(loaded.db, temp.path().to_path_buf(), loaded.watcher, None);
+        assert!(second.background_vault().is_none());
+
+        first.mark_unavailable();
+        assert!(first.background_vault().is_none());
+    }
+
+    #[test]
+    fn test_close_indexes_queued_saves() {
+        let temp = TempDir::new().unwrap();
+        let state = AppState::default();
+        let loaded = load_vault(temp.path().to_path_buf(), None).unwrap();
+        state.set_vault(loaded.db, temp.path().to_path_buf(), loaded.watcher, loaded.instance_lock);
+        let db = state.db().unwrap();
+
+        let path = temp.path().join("Saved.md");
+        std::fs::write(&path, "# Saved\n").unwrap();
+        // A clone held elsewhere keeps the queue alive past the close
+        let queue = state.index_queue().unwrap();
+        queue.push(path);
+        state.clear();
+
+        let meta = crate::db::notes::get_note_by_path(&db.conn(), "Saved.md").unwrap();
+        assert_eq!(meta.unwrap().title, "Saved");
+        drop(queue);
+    }
+
+    #[test]
+    fn test_open_database_plaintext_replaces_encrypted() {
+        let temp = TempDir::new().unwrap();
+        let db_path = temp.path().join("chronicle.db");
+        std::fs::write(&db_path, [0x5a; 64]).unwrap();
+
+        open_database(&db_path, &DatabaseConfig::default(), None, true).unwrap();
+        assert!(is_plaintext_database(&db_path).unwrap());
+    }
+
+    #[test]
+    fn test_open_database_read_only_keeps_other_mode() {
+        let temp = TempDir::new().unwrap();
+        let db_path = temp.path().join("chronicle.db");
+        std::fs::write(&db_path, [0x5a; 64]).unwrap();
+
+        let result = open_database(&db_path, &DatabaseConfig::default(), None, false);
+        assert!(matches!(result, Err(ChronicleError::Database(_))));
+        assert_eq!(std::fs::read(&db_path).unwrap(), [0x5a; 64]);
+    }
+
+    #[test]
+    fn test_open_database_encrypted_needs_key() {
+        let temp = TempDir::new().unwrap();
+        let db_path = temp.path().join("chronicle.db");
+        let config = DatabaseConfig { encrypted: true, ..Default::default() };
+
+        let result = open_database(&db_path, &config, None, true);
+        if Database::supports_encryption() {
+            assert!(matches!(result, Err(ChronicleError::DatabaseKeyRequired)));
+        } else {
+            assert!(matches!(result, Err(ChronicleError::Database(_))));
+        }
+        assert!(!db_path.exists());
     }
-    
-    Ok(())
 }
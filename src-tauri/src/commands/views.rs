@@ -0,0 +1,138 @@
+//! Saved list view commands: backend-defined note lists like "Recent" or
+//! "By project", evaluated against the index
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::{views as db_views, Database, ViewGroup};
+use crate::error::ChronicleError;
+use crate::vault::{read_views, write_views, ListView, ViewSpec};
+
+/// A view with the notes it currently lists
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewResults {
+    pub view: ListView,
+    pub groups: Vec<ViewGroup>,
+}
+
+/// Create a view, or update the one with `id`
+#[tauri::command]
+pub async fn save_view(
+    id: Option<i64>,
+    name: String,
+    spec: ViewSpec,
+    state: State<'_, AppState>,
+) -> Result<ListView, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(ChronicleError::InvalidFilter("View name must not be empty".to_string()));
+    }
+    spec.validate().map_err(ChronicleError::InvalidFilter)?;
+
+    blocking(move || {
+        let view = {
+            let conn = db.conn();
+            if let Some(id) = id {
+                if db_views::get_view(&conn, id)?.is_none() {
+                    return Err(ChronicleError::ViewNotFound(id.to_string()));
+                }
+            }
+            let updated_at = Local::now().to_rfc3339();
+            db_views::save_view(&conn, id, &name, &spec, &updated_at)?
+        };
+        mirror_views(&vault_path, &db)?;
+        Ok(view)
+    })
+    .await
+}
+
+/// All views, by name
+#[tauri::command]
+pub async fn list_views(state: State<'_, AppState>) -> Result<Vec<ListView>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        Ok(db_views::list_views(&conn)?)
+    })
+    .await
+}
+
+/// A view and the notes it lists, sorted and grouped
+#[tauri::command]
+pub async fn get_view_results(
+    id: i64,
+    state: State<'_, AppState>,
+) -> Result<ViewResults, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        let view = db_views::get_view(&conn, id)?
+            .ok_or_else(|| ChronicleError::ViewNotFound(id.to_string()))?;
+        let groups = db_views::view_results(&conn, &view.spec)?;
+        Ok(ViewResults { view, groups })
+    })
+    .await
+}
+
+/// Delete a view
+#[tauri::command]
+pub async fn delete_view(id: i64, state: State<'_, AppState>) -> Result<(), ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        if !db_views::delete_view(&db.conn(), id)? {
+            return Err(ChronicleError::ViewNotFound(id.to_string()));
+        }
+        mirror_views(&vault_path, &db)
+    })
+    .await
+}
+
+/// Load the vault's views file into the index, after the vault opens or a
+/// sync brings in other devices' changes
+pub(crate) fn restore_views(vault_path: &Path, db: &Database) -> Result<(), ChronicleError> {
+    if let Some(views) = read_views(vault_path)? {
+        db_views::replace_views(&db.conn(), &views)?;
+    }
+    Ok(())
+}
+
+fn mirror_views(vault_path: &Path, db: &Database) -> Result<(), ChronicleError> {
+    let views = db_views::list_views(&db.conn())?;
+    write_views(vault_path, &views)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::ViewGrouping;
+
+    #[test]
+    fn test_views_survive_a_rebuilt_index() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let db = Database::open_memory().unwrap();
+        let spec = ViewSpec {
+            group_by: Some(ViewGrouping::Tag),
+            ..Default::default()
+        };
+        let view =
+            db_views::save_view(&db.conn(), None, "By tag", &spec, "2025-02-01T09:00:00+01:00")
+                .unwrap();
+        mirror_views(temp.path(), &db).unwrap();
+
+        let rebuilt = Database::open_memory().unwrap();
+        restore_views(temp.path(), &rebuilt).unwrap();
+        assert_eq!(db_views::list_views(&rebuilt.conn()).unwrap(), vec![view]);
+    }
+}
@@ -0,0 +1,129 @@
+//! Workspace commands: saving and restoring sets of open notes and layout
+
+use chrono::Local;
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::{workspaces as db_workspaces, Database};
+use crate::error::ChronicleError;
+use crate::vault::{read_workspaces, write_workspaces, Workspace};
+
+/// Save the open notes and layout under `name`, replacing any workspace of
+/// that name
+#[tauri::command]
+pub async fn save_workspace(
+    name: String,
+    open_notes: Vec<String>,
+    layout: Option<serde_json::Value>,
+    state: State<'_, AppState>,
+) -> Result<Workspace, ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(ChronicleError::InvalidConfig(
+            "Workspace name must not be empty".to_string(),
+        ));
+    }
+
+    blocking(move || {
+        let workspace = Workspace {
+            name,
+            open_notes,
+            layout: layout.unwrap_or_default(),
+            updated_at: Local::now().to_rfc3339(),
+        };
+        db_workspaces::save_workspace(&db.conn(), &workspace)?;
+        mirror_workspaces(&vault_path, &db)?;
+        Ok(workspace)
+    })
+    .await
+}
+
+/// A saved workspace
+#[tauri::command]
+pub async fn load_workspace(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<Workspace, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        db_workspaces::get_workspace(&conn, &name)?.ok_or(ChronicleError::WorkspaceNotFound(name))
+    })
+    .await
+}
+
+/// All saved workspaces, by name
+#[tauri::command]
+pub async fn list_workspaces(state: State<'_, AppState>) -> Result<Vec<Workspace>, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        Ok(db_workspaces::list_workspaces(&conn)?)
+    })
+    .await
+}
+
+/// Delete a saved workspace
+#[tauri::command]
+pub async fn delete_workspace(name: String, state: State<'_, AppState>) -> Result<(), ChronicleError> {
+    let vault_path = state.writable_vault_path()?;
+    let db = state.db()?;
+
+    blocking(move || {
+        if !db_workspaces::delete_workspace(&db.conn(), &name)? {
+            return Err(ChronicleError::WorkspaceNotFound(name));
+        }
+        mirror_workspaces(&vault_path, &db)
+    })
+    .await
+}
+
+/// Load the vault's workspaces file into the index, after the vault opens
+/// or a sync brings in other devices' changes
+pub(crate) fn restore_workspaces(vault_path: &Path, db: &Database) -> Result<(), ChronicleError> {
+    if let Some(workspaces) = read_workspaces(vault_path)? {
+        db_workspaces::replace_workspaces(&db.conn(), &workspaces)?;
+    }
+    Ok(())
+}
+
+fn mirror_workspaces(vault_path: &Path, db: &Database) -> Result<(), ChronicleError> {
+    let workspaces = db_workspaces::list_workspaces(&db.conn())?;
+    write_workspaces(vault_path, &workspaces)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspaces_survive_a_rebuilt_index() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let db = Database::open_memory().unwrap();
+        let workspace = Workspace {
+            name: "thesis".to_string(),
+            open_notes: vec!["thesis/outline.md".to_string()],
+            layout: serde_json::json!({ "sidebar": 300 }),
+            updated_at: "2025-02-01T09:00:00+01:00".to_string(),
+        };
+        db_workspaces::save_workspace(&db.conn(), &workspace).unwrap();
+        mirror_workspaces(temp.path(), &db).unwrap();
+
+        let rebuilt = Database::open_memory().unwrap();
+        restore_workspaces(temp.path(), &rebuilt).unwrap();
+        assert_eq!(db_workspaces::list_workspaces(&rebuilt.conn()).unwrap(), vec![workspace]);
+
+        // A vault without the file keeps what the index has
+        let other = tempfile::TempDir::new().unwrap();
+        restore_workspaces(other.path(), &rebuilt).unwrap();
+        assert_eq!(db_workspaces::list_workspaces(&rebuilt.conn()).unwrap().len(), 1);
+    }
+}
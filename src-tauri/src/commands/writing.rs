@@ -0,0 +1,131 @@
+//! Writing goal commands
+
+use chrono::{Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::writing::words_written_by_day;
+use crate::error::ChronicleError;
+use crate::models::AppConfig;
+
+/// Progress towards the configured writing goals
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WritingProgress {
+    /// Words written today
+    pub today: i64,
+    pub daily_goal: u32,
+    /// Words written since the start of the week
+    pub this_week: i64,
+    /// 0 when no weekly goal is set
+    pub weekly_goal: u32,
+    /// Days in a row the daily goal was met, up to today (or yesterday
+    /// while today's goal is still open)
+    pub current_streak: u32,
+    pub best_streak: u32,
+}
+
+/// Words written today and this week, and daily-goal streaks
+#[tauri::command]
+pub async fn get_writing_progress(
+    state: State<'_, AppState>,
+) -> Result<WritingProgress, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let config = AppConfig::load();
+        let history = words_written_by_day(&db.conn(), "0000-00-00")?;
+
+        let days: HashMap<NaiveDate, i64> = history
+            .into_iter()
+            .filter_map(|(date, words)| {
+                NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok().map(|d| (d, words))
+            })
+            .collect();
+
+        let today = Local::now().date_naive();
+        let week_start = config.locale.week_start_of(today);
+        let goal = config.writing_goals.daily_words;
+        let (current_streak, best_streak) = streaks(&days, goal, today);
+
+        Ok(WritingProgress {
+            today: days.get(&today).copied().unwrap_or(0),
+            daily_goal: goal,
+            this_week: days
+                .iter()
+                .filter(|(d, _)| **d >= week_start && **d <= today)
+                .map(|(_, words)| words)
+                .sum(),
+            weekly_goal: config.writing_goals.weekly_words,
+            current_streak,
+            best_streak,
+        })
+    })
+    .await
+}
+
+/// Current and best runs of consecutive days meeting `goal` (any writing
+/// when `goal` is 0)
+fn streaks(days: &HashMap<NaiveDate, i64>, goal: u32, today: NaiveDate) -> (u32, u32) {
+    let met = |date: &NaiveDate| {
+        days.get(date)
+            .is_some_and(|words| *words > 0 && *words >= i64::from(goal))
+    };
+
+    // Today only breaks the streak once it's over
+    let mut day = if met(&today) { today } else { today - Duration::days(1) };
+    let mut current = 0;
+    while met(&day) {
+        current += 1;
+        day -= Duration::days(1);
+    }
+
+    let mut met_days: Vec<&NaiveDate> = days.keys().filter(|d| met(d)).collect();
+    met_days.sort();
+    let mut best = 0;
+    let mut run = 0;
+    let mut previous: Option<&NaiveDate> = None;
+    for date in met_days {
+        run = match previous {
+            Some(p) if *date - *p == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        best = best.max(run);
+        previous = Some(date);
+    }
+
+    (current, best.max(current))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap()
+    }
+
+    #[test]
+    fn test_streaks() {
+        let days: HashMap<NaiveDate, i64> = [
+            (date(1), 600),
+            (date(2), 700),
+            (date(3), 500),
+            (date(4), 100),
+            (date(8), 900),
+            (date(9), 550),
+            (date(10), 20),
+        ]
+        .into_iter()
+        .collect();
+
+        // Today (10th) is still short of the goal; the streak runs to yesterday
+        assert_eq!(streaks(&days, 500, date(10)), (2, 3));
+        // A missed day ends the current streak
+        assert_eq!(streaks(&days, 500, date(11)), (0, 3));
+        // Without a goal any writing counts
+        assert_eq!(streaks(&days, 0, date(10)), (3, 4));
+    }
+}
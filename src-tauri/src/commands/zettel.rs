@@ -0,0 +1,84 @@
+//! Zettelkasten ID commands
+
+use chrono::{Local, NaiveDateTime};
+use rusqlite::Connection;
+use std::collections::HashSet;
+use tauri::State;
+
+use crate::commands::blocking;
+use crate::commands::vault::AppState;
+use crate::db::zettel::zettel_id_exists;
+use crate::error::ChronicleError;
+use crate::models::{AppConfig, ZettelIdScheme};
+use crate::vault;
+
+/// Generate an ID in the configured scheme that no note uses yet
+#[tauri::command]
+pub async fn generate_zettel_id(state: State<'_, AppState>) -> Result<String, ChronicleError> {
+    let db = state.db()?;
+
+    blocking(move || {
+        let conn = db.conn();
+        let scheme = AppConfig::load().zettel.id_scheme;
+        Ok(next_zettel_id(&conn, scheme, Local::now().naive_local(), &HashSet::new())?)
+    })
+    .await
+}
+
+/// ID for a new note's filename, when prefixed filenames are on or the
+/// filename template uses `{{id}}`
+pub(crate) fn new_note_id(
+    conn: &Connection,
+    config: &AppConfig,
+    now: NaiveDateTime,
+) -> Result<Option<String>, ChronicleError> {
+    new_note_id_besides(conn, config, now, &HashSet::new())
+}
+
+/// Like `new_note_id`, also passing over `reserved` IDs, given to notes of
+/// a batch that aren't indexed yet
+pub(crate) fn new_note_id_besides(
+    conn: &Connection,
+    config: &AppConfig,
+    now: NaiveDateTime,
+    reserved: &HashSet<String>,
+) -> Result<Option<String>, ChronicleError> {
+    let wanted = config.zettel.prefix_filenames || config.new_notes.filename_template.contains("{{id}}");
+    if !wanted {
+        return Ok(None);
+    }
+    Ok(Some(next_zettel_id(conn, config.zettel.id_scheme, now, reserved)?))
+}
+
+fn next_zettel_id(
+    conn: &Connection,
+    scheme: ZettelIdScheme,
+    now: NaiveDateTime,
+    reserved: &HashSet<String>,
+) -> rusqlite::Result<String> {
+    vault::generate_zettel_id(scheme, now, |id| {
+        Ok(reserved.contains(id) || zettel_id_exists(conn, id)?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{notes::upsert_note, zettel::set_zettel_id, Database};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_new_note_id() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let now = NaiveDate::from_ymd_opt(2025, 1, 27).unwrap().and_hms_opt(14, 30, 0).unwrap();
+
+        let mut config = AppConfig::default();
+        assert_eq!(new_note_id(&conn, &config, now).unwrap(), None);
+
+        config.zettel.prefix_filenames = true;
+        let id = upsert_note(&conn, "202501271430.md", "Taken", None, None, "h", 0).unwrap();
+        set_zettel_id(&conn, id, Some("202501271430")).unwrap();
+        assert_eq!(new_note_id(&conn, &config, now).unwrap().as_deref(), Some("202501271431"));
+    }
+}
@@ -0,0 +1,206 @@
+//! Flashcard database operations
+
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::srs::{Card, CardKind, Schedule};
+
+/// A stored card with its note and review state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardRecord {
+    pub id: i64,
+    pub note_path: String,
+    pub note_title: String,
+    pub kind: CardKind,
+    pub front: String,
+    pub back: String,
+    pub line_number: Option<i32>,
+    #[serde(flatten)]
+    pub schedule: Schedule,
+    /// Next review date (YYYY-MM-DD); `None` for cards never reviewed
+    pub due: Option<String>,
+    pub last_reviewed: Option<String>,
+}
+
+const CARD_COLUMNS: &str = r#"
+    c.id, n.path, n.title, c.kind, c.front, c.back, c.line_number,
+    c.ease, c.interval_days, c.repetitions, c.due, c.last_reviewed
+"#;
+
+fn card_from_row(row: &Row) -> Result<CardRecord> {
+    let kind: String = row.get(3)?;
+    Ok(CardRecord {
+        id: row.get(0)?,
+        note_path: row.get(1)?,
+        note_title: row.get(2)?,
+        kind: CardKind::parse(&kind).unwrap_or(CardKind::Basic),
+        front: row.get(4)?,
+        back: row.get(5)?,
+        line_number: row.get(6)?,
+        schedule: Schedule {
+            ease: row.get(7)?,
+            interval_days: row.get(8)?,
+            repetitions: row.get(9)?,
+        },
+        due: row.get(10)?,
+        last_reviewed: row.get(11)?,
+    })
+}
+
+/// Set the cards for a note. Cards whose key is unchanged keep their
+/// schedule; cards no longer in the note are removed.
+pub fn replace_cards(conn: &Connection, note_id: i64, cards: &[Card]) -> Result<()> {
+    let keys: HashSet<&str> = cards.iter().map(|c| c.key.as_str()).collect();
+
+    let existing: Vec<String> = {
+        let mut stmt = conn.prepare("SELECT card_key FROM cards WHERE note_id = ?1")?;
+        let rows = stmt.query_map(params![note_id], |row| row.get(0))?;
+        rows.collect::<Result<_>>()?
+    };
+    for key in existing.iter().filter(|k| !keys.contains(k.as_str())) {
+        conn.execute(
+            "DELETE FROM cards WHERE note_id = ?1 AND card_key = ?2",
+            params![note_id, key],
+        )?;
+    }
+
+    let mut stmt = conn.prepare(
+        r#"
+        INSERT INTO cards (note_id, card_key, kind, front, back, line_number)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        ON CONFLICT(note_id, card_key) DO UPDATE SET
+            kind = excluded.kind,
+            front = excluded.front,
+            back = excluded.back,
+            line_number = excluded.line_number
+        "#,
+    )?;
+    for card in cards {
+        stmt.execute(params![
+            note_id,
+            card.key,
+            card.kind.as_str(),
+            card.front,
+            card.back,
+            card.line_number as i32
+        ])?;
+    }
+
+    Ok(())
+}
+
+/// Cards due on or before `today` (YYYY-MM-DD), overdue first, then cards
+/// that have never been reviewed
+pub fn get_due_cards(conn: &Connection, today: &str, limit: usize) -> Result<Vec<CardRecord>> {
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {}
+        FROM cards c
+        JOIN notes n ON c.note_id = n.id
+        WHERE c.due IS NULL OR c.due <= ?1
+        ORDER BY c.due IS NULL, c.due, n.path, c.line_number
+        LIMIT ?2
+        "#,
+        CARD_COLUMNS
+    ))?;
+
+    let rows = stmt.query_map(params![today, limit as i64], card_from_row)?;
+    rows.collect()
+}
+
+/// Get a card by ID
+pub fn get_card(conn: &Connection, id: i64) -> Result<Option<CardRecord>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM cards c JOIN notes n ON c.note_id = n.id WHERE c.id = ?1",
+            CARD_COLUMNS
+        ),
+        params![id],
+        card_from_row,
+    )
+    .optional()
+}
+
+/// Store a card's schedule after a review
+pub fn update_card_schedule(
+    conn: &Connection,
+    id: i64,
+    schedule: &Schedule,
+    due: &str,
+    reviewed: &str,
+) -> Result<bool> {
+    let rows_affected = conn.execute(
+        r#"
+        UPDATE cards
+        SET ease = ?1, interval_days = ?2, repetitions = ?3, due = ?4, last_reviewed = ?5
+        WHERE id = ?6
+        "#,
+        params![
+            schedule.ease,
+            schedule.interval_days,
+            schedule.repetitions,
+            due,
+            reviewed,
+            id
+        ],
+    )?;
+    Ok(rows_affected > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{notes::upsert_note, schema::Database};
+    use crate::srs::extract_cards;
+
+    #[test]
+    fn test_replace_cards_keeps_schedule() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let id = upsert_note(&conn, "deck.md", "Deck", None, None, "x", 0).unwrap();
+
+        replace_cards(&conn, id, &extract_cards("Q: One\nA: 1\n\nQ: Two\nA: 2")).unwrap();
+        let due = get_due_cards(&conn, "2026-03-01", 10).unwrap();
+        assert_eq!(due.len(), 2);
+        assert_eq!(due[0].note_path, "deck.md");
+
+        let reviewed = Schedule::default().review(5);
+        update_card_schedule(&conn, due[0].id, &reviewed, "2026-03-02", "2026-03-01").unwrap();
+
+        // Answer edited, second card removed
+        replace_cards(&conn, id, &extract_cards("Q: One\nA: one")).unwrap();
+        let card = get_card(&conn, due[0].id).unwrap().unwrap();
+        assert_eq!(card.back, "one");
+        assert_eq!(card.schedule, reviewed);
+        assert!(get_card(&conn, due[1].id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_get_due_cards_order() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let id = upsert_note(&conn, "deck.md", "Deck", None, None, "x", 0).unwrap();
+        replace_cards(&conn, id, &extract_cards("Q: A\nA: a\n\nQ: B\nA: b\n\nQ: C\nA: c")).unwrap();
+
+        let cards = get_due_cards(&conn, "2026-03-10", 10).unwrap();
+        let schedule = Schedule::default();
+        update_card_schedule(&conn, cards[0].id, &schedule, "2026-03-20", "2026-03-01").unwrap();
+        update_card_schedule(&conn, cards[2].id, &schedule, "2026-03-05", "2026-03-01").unwrap();
+
+        let due = get_due_cards(&conn, "2026-03-10", 10).unwrap();
+        let fronts: Vec<&str> = due.iter().map(|c| c.front.as_str()).collect();
+        assert_eq!(fronts, vec!["C", "B"]);
+    }
+
+    #[test]
+    fn test_cards_removed_with_note() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let id = upsert_note(&conn, "deck.md", "Deck", None, None, "x", 0).unwrap();
+        replace_cards(&conn, id, &extract_cards("Q: One\nA: 1")).unwrap();
+
+        crate::db::notes::delete_note(&conn, "deck.md").unwrap();
+        assert!(get_due_cards(&conn, "2026-03-01", 10).unwrap().is_empty());
+    }
+}
@@ -2,6 +2,9 @@
 
 use rusqlite::{params, Connection, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
+
+use crate::vault::LinkKind;
 
 /// Link between notes
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +15,7 @@ pub struct Link {
     pub target_id: Option<i64>,
     pub display_text: Option<String>,
     pub line_number: Option<i32>,
+    pub kind: LinkKind,
 }
 
 /// Backlink with context
@@ -21,51 +25,143 @@ pub struct Backlink {
     pub source_title: String,
     pub line_number: Option<i32>,
     pub display_text: Option<String>,
-    pub context: Option<String>, // Surrounding text from the source note
+    pub context: Option<String>, // The line the link is on in the source note
+    pub kind: LinkKind,
+}
+
+fn link_kind(kind: String) -> LinkKind {
+    LinkKind::parse(&kind).unwrap_or(LinkKind::Wiki)
 }
 
-/// Replace all links for a note
+/// Replace all links for a note, given as (target_path, display_text,
+/// line_number, kind)
 pub fn replace_links(
     conn: &Connection,
     source_id: i64,
-    links: &[(String, Option<String>, Option<i32>)], // (target_path, display_text, line_number)
+    links: &[(String, Option<String>, Option<i32>, LinkKind)],
 ) -> Result<()> {
     // Delete existing links
-    conn.execute("DELETE FROM links WHERE source_id = ?1", params![source_id])?;
+    conn.prepare_cached("DELETE FROM links WHERE source_id = ?1")?.execute(params![source_id])?;
 
     // Insert new links, ignoring duplicates (same target on same line)
-    let mut stmt = conn.prepare(
-        "INSERT OR IGNORE INTO links (source_id, target_path, display_text, line_number) VALUES (?1, ?2, ?3, ?4)"
+    let mut stmt = conn.prepare_cached(
+        "INSERT OR IGNORE INTO links (source_id, target_path, display_text, line_number, kind)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
     )?;
 
-    for (target_path, display_text, line_number) in links {
-        stmt.execute(params![source_id, target_path, display_text, line_number])?;
+    for (target_path, display_text, line_number, kind) in links {
+        stmt.execute(params![source_id, target_path, display_text, line_number, kind.as_str()])?;
     }
 
-    // Resolve links to existing notes
-    conn.execute(
+    // Resolve links to existing notes, by path or else by Zettelkasten or
+    // stable note ID
+    conn.prepare_cached(
         r#"
-        UPDATE links SET target_id = (
-            SELECT id FROM notes WHERE LOWER(notes.path) = LOWER(links.target_path || '.md')
-            OR LOWER(notes.path) = LOWER(links.target_path)
+        UPDATE links SET target_id = COALESCE(
+            (
+                SELECT id FROM notes WHERE LOWER(notes.path) = LOWER(links.target_path || '.md')
+                OR LOWER(notes.path) = LOWER(links.target_path)
+            ),
+            (
+                SELECT note_id FROM zettel_ids
+                WHERE zettel_ids.zettel_id = links.target_path COLLATE NOCASE
+            ),
+            (SELECT note_id FROM note_uids WHERE note_uids.uid = links.target_path)
         )
         WHERE source_id = ?1
         "#,
-        params![source_id],
-    )?;
+    )?
+    .execute(params![source_id])?;
+
+    Ok(())
+}
+
+/// Store the line each of a note's links is on, by line number, so
+/// backlinks can show it without reading the source note
+pub fn set_link_contexts(
+    conn: &Connection,
+    source_id: i64,
+    contexts: &[(i32, String)],
+) -> Result<()> {
+    let mut stmt = conn
+        .prepare_cached("UPDATE links SET context = ?3 WHERE source_id = ?1 AND line_number = ?2")?;
+    for (line_number, context) in contexts {
+        stmt.execute(params![source_id, line_number, context])?;
+    }
+    Ok(())
+}
 
+/// Resolve links whose target didn't exist when they were stored, e.g.
+/// links to notes added since
+pub fn resolve_dangling_links(conn: &Connection) -> Result<()> {
+    conn.execute(
+        r#"
+        UPDATE links SET target_id = COALESCE(
+            (
+                SELECT id FROM notes WHERE LOWER(notes.path) = LOWER(links.target_path || '.md')
+                OR LOWER(notes.path) = LOWER(links.target_path)
+            ),
+            (
+                SELECT note_id FROM zettel_ids
+                WHERE zettel_ids.zettel_id = links.target_path COLLATE NOCASE
+            ),
+            (SELECT note_id FROM note_uids WHERE note_uids.uid = links.target_path)
+        )
+        WHERE target_id IS NULL
+        "#,
+        [],
+    )?;
     Ok(())
 }
 
-/// Get backlinks to a note (without context - context added at command level)
+/// Vault-relative path of the note a link name points at, matched like
+/// stored links are: by path with or without `.md`, else by Zettelkasten ID
+/// or stable note ID
+pub fn resolve_link_target(conn: &Connection, target: &str) -> Result<Option<String>> {
+    conn.query_row(
+        r#"
+        SELECT COALESCE(
+            (
+                SELECT path FROM notes WHERE LOWER(path) = LOWER(?1 || '.md')
+                OR LOWER(path) = LOWER(?1)
+            ),
+            (
+                SELECT n.path FROM zettel_ids z JOIN notes n ON n.id = z.note_id
+                WHERE z.zettel_id = ?1 COLLATE NOCASE
+            ),
+            (
+                SELECT n.path FROM note_uids u JOIN notes n ON n.id = u.note_id
+                WHERE u.uid = ?1
+            )
+        )
+        "#,
+        params![target],
+        |row| row.get(0),
+    )
+}
+
+/// Get backlinks to a note, with the line each link is on
+///
+/// Links count whether they name the note's path, its Zettelkasten ID or its
+/// stable ID.
 pub fn get_backlinks(conn: &Connection, path: &str) -> Result<Vec<Backlink>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT n.path, n.title, l.line_number, l.display_text
+        SELECT n.path, n.title, l.line_number, l.display_text, l.context, l.kind
         FROM links l
         JOIN notes n ON l.source_id = n.id
         WHERE LOWER(l.target_path) = LOWER(?1)
            OR LOWER(l.target_path || '.md') = LOWER(?1)
+           OR l.target_path = (
+               SELECT z.zettel_id FROM zettel_ids z
+               JOIN notes t ON t.id = z.note_id
+               WHERE t.path = ?1
+           ) COLLATE NOCASE
+           OR l.target_path = (
+               SELECT u.uid FROM note_uids u
+               JOIN notes t ON t.id = u.note_id
+               WHERE t.path = ?1
+           ) COLLATE NOCASE
         ORDER BY n.modified_at DESC
         "#,
     )?;
@@ -76,17 +172,213 @@ pub fn get_backlinks(conn: &Connection, path: &str) -> Result<Vec<Backlink>> {
             source_title: row.get(1)?,
             line_number: row.get(2)?,
             display_text: row.get(3)?,
-            context: None, // Populated at command level with file access
+            context: row.get(4)?,
+            kind: link_kind(row.get(5)?),
+        })
+    })?;
+
+    rows.collect()
+}
+
+/// Notes with wiki links to `path` by its path rather than an ID, with
+/// each target as written, without any `#heading` part
+pub fn get_path_link_sources(conn: &Connection, path: &str) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT DISTINCT path, target FROM (
+            SELECT n.path, CASE WHEN instr(l.target_path, '#') > 0
+                THEN substr(l.target_path, 1, instr(l.target_path, '#') - 1)
+                ELSE l.target_path END AS target
+            FROM links l
+            JOIN notes n ON l.source_id = n.id
+            WHERE l.kind = 'wiki'
+        )
+        WHERE LOWER(target) = LOWER(?1) OR LOWER(target || '.md') = LOWER(?1)
+        ORDER BY path
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![path], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// Notes with Markdown links to `path`, which are stored by the vault path
+/// they lead to
+pub fn get_markdown_link_sources(conn: &Connection, path: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT DISTINCT n.path
+        FROM links l
+        JOIN notes n ON l.source_id = n.id
+        WHERE l.kind = 'markdown'
+          AND (LOWER(l.target_path) = LOWER(?1) OR LOWER(l.target_path || '.md') = LOWER(?1))
+        ORDER BY n.path
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![path], |row| row.get(0))?;
+    rows.collect()
+}
+
+/// A link whose target matches no note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnresolvedLink {
+    /// Link target without any `#heading` part
+    pub target: String,
+    pub source_path: String,
+    pub line_number: Option<i32>,
+    pub display_text: Option<String>,
+}
+
+/// Links that match no note by path, Zettelkasten ID or stable ID, by target
+pub fn get_unresolved_links(conn: &Connection) -> Result<Vec<UnresolvedLink>> {
+    let mut stmt = conn.prepare(
+        r#"
+        WITH named AS (
+            SELECT source_id, line_number, display_text,
+                CASE WHEN instr(target_path, '#') > 0
+                    THEN trim(substr(target_path, 1, instr(target_path, '#') - 1))
+                    ELSE target_path
+                END AS name
+            FROM links
+        )
+        SELECT l.name, n.path, l.line_number, l.display_text
+        FROM named l
+        JOIN notes n ON n.id = l.source_id
+        WHERE l.name <> ''
+          AND NOT EXISTS (
+              SELECT 1 FROM notes t
+              WHERE LOWER(t.path) = LOWER(l.name || '.md') OR LOWER(t.path) = LOWER(l.name)
+          )
+          AND NOT EXISTS (
+              SELECT 1 FROM zettel_ids z WHERE z.zettel_id = l.name COLLATE NOCASE
+          )
+          AND NOT EXISTS (SELECT 1 FROM note_uids u WHERE u.uid = l.name)
+        ORDER BY l.name COLLATE NOCASE, n.path, l.line_number
+        "#,
+    )?;
+
+    let rows = stmt.query_map([], |row| {
+        Ok(UnresolvedLink {
+            target: row.get(0)?,
+            source_path: row.get(1)?,
+            line_number: row.get(2)?,
+            display_text: row.get(3)?,
         })
     })?;
 
     rows.collect()
 }
 
+/// Notes around one note, by ID
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkNeighborhood {
+    /// The note first, then notes one link away, two links away, ...
+    pub note_ids: Vec<i64>,
+    /// Resolved links among them as (source, target)
+    pub links: Vec<(i64, i64)>,
+}
+
+/// Notes within `depth` links of a note, either way, and the links among
+/// them. Only the link table is walked, so the cost follows the size of
+/// the neighborhood rather than of the vault.
+pub fn link_neighborhood(
+    conn: &Connection,
+    note_id: i64,
+    depth: usize,
+) -> Result<LinkNeighborhood> {
+    let mut neighbors = conn.prepare_cached(
+        "SELECT target_id FROM links WHERE source_id = ?1 AND target_id IS NOT NULL
+         UNION SELECT source_id FROM links WHERE target_id = ?1",
+    )?;
+    let mut ids = vec![note_id];
+    let mut seen: HashSet<i64> = HashSet::from([note_id]);
+    let mut frontier = vec![note_id];
+    for _ in 0..depth {
+        let mut next = Vec::new();
+        for id in frontier {
+            let rows = neighbors.query_map(params![id], |row| row.get::<_, i64>(0))?;
+            for neighbor in rows {
+                let neighbor = neighbor?;
+                if seen.insert(neighbor) {
+                    next.push(neighbor);
+                }
+            }
+        }
+        next.sort_unstable();
+        ids.extend(&next);
+        frontier = next;
+    }
+
+    let mut outlinks = conn.prepare_cached(
+        "SELECT DISTINCT target_id FROM links WHERE source_id = ?1 AND target_id IS NOT NULL",
+    )?;
+    let mut edges = Vec::new();
+    for &source in &ids {
+        let rows = outlinks.query_map(params![source], |row| row.get::<_, i64>(0))?;
+        for target in rows {
+            let target = target?;
+            if target != source && seen.contains(&target) {
+                edges.push((source, target));
+            }
+        }
+    }
+    Ok(LinkNeighborhood {
+        note_ids: ids,
+        links: edges,
+    })
+}
+
+/// The fewest links leading from one note to another, as the IDs of the
+/// notes along the way, both ends included, or `None` if no chain of links
+/// connects them. With `either_way`, links are also followed backwards.
+/// Breadth-first over the link table, so it stops as soon as `to` is
+/// reached.
+pub fn shortest_link_path(
+    conn: &Connection,
+    from: i64,
+    to: i64,
+    either_way: bool,
+) -> Result<Option<Vec<i64>>> {
+    let mut neighbors = conn.prepare_cached(if either_way {
+        "SELECT target_id FROM links WHERE source_id = ?1 AND target_id IS NOT NULL
+         UNION SELECT source_id FROM links WHERE target_id = ?1 ORDER BY 1"
+    } else {
+        "SELECT DISTINCT target_id FROM links WHERE source_id = ?1 AND target_id IS NOT NULL
+         ORDER BY 1"
+    })?;
+
+    // Each reached note and the note it was reached from
+    let mut came_from: HashMap<i64, i64> = HashMap::from([(from, from)]);
+    let mut queue = VecDeque::from([from]);
+    while let Some(id) = queue.pop_front() {
+        if id == to {
+            let mut path = vec![to];
+            let mut current = to;
+            while current != from {
+                current = came_from[&current];
+                path.push(current);
+            }
+            path.reverse();
+            return Ok(Some(path));
+        }
+        let rows = neighbors.query_map(params![id], |row| row.get::<_, i64>(0))?;
+        for neighbor in rows {
+            let neighbor = neighbor?;
+            if let Entry::Vacant(entry) = came_from.entry(neighbor) {
+                entry.insert(id);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    Ok(None)
+}
+
 /// Get outgoing links from a note
 pub fn get_outlinks(conn: &Connection, source_id: i64) -> Result<Vec<Link>> {
     let mut stmt = conn.prepare(
-        "SELECT id, source_id, target_path, target_id, display_text, line_number FROM links WHERE source_id = ?1"
+        "SELECT id, source_id, target_path, target_id, display_text, line_number, kind
+         FROM links WHERE source_id = ?1",
     )?;
 
     let rows = stmt.query_map(params![source_id], |row| {
@@ -97,6 +389,7 @@ pub fn get_outlinks(conn: &Connection, source_id: i64) -> Result<Vec<Link>> {
             target_id: row.get(3)?,
             display_text: row.get(4)?,
             line_number: row.get(5)?,
+            kind: link_kind(row.get(6)?),
         })
     })?;
 
@@ -116,13 +409,123 @@ mod tests {
         let id = upsert_note(&conn, "source.md", "Source", None, None, "x", 0).unwrap();
 
         let links = vec![
-            ("target1".to_string(), None, Some(5)),
-            ("target2".to_string(), Some("display".to_string()), Some(10)),
+            ("target1".to_string(), None, Some(5), LinkKind::Wiki),
+            ("target2.md".to_string(), Some("display".to_string()), Some(10), LinkKind::Markdown),
         ];
 
         replace_links(&conn, id, &links).unwrap();
 
         let outlinks = get_outlinks(&conn, id).unwrap();
         assert_eq!(outlinks.len(), 2);
+        assert_eq!(outlinks[1].kind, LinkKind::Markdown);
+    }
+
+    #[test]
+    fn test_links_resolve_by_zettel_id() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+
+        let target = upsert_note(&conn, "202501271430 Atoms.md", "Atoms", None, None, "x", 0).unwrap();
+        crate::db::zettel::set_zettel_id(&conn, target, Some("202501271430")).unwrap();
+        let source = upsert_note(&conn, "source.md", "Source", None, None, "y", 0).unwrap();
+
+        let links = [("202501271430".to_string(), None, Some(3), LinkKind::Wiki)];
+        replace_links(&conn, source, &links).unwrap();
+
+        assert_eq!(get_outlinks(&conn, source).unwrap()[0].target_id, Some(target));
+        let backlinks = get_backlinks(&conn, "202501271430 Atoms.md").unwrap();
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].source_path, "source.md");
+    }
+
+    #[test]
+    fn test_link_contexts() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+
+        let source = upsert_note(&conn, "source.md", "Source", None, None, "y", 0).unwrap();
+        let links = [("target".to_string(), None, Some(3), LinkKind::Wiki)];
+        replace_links(&conn, source, &links).unwrap();
+        set_link_contexts(&conn, source, &[(3, "See [[target]]".to_string())]).unwrap();
+
+        let backlinks = get_backlinks(&conn, "target.md").unwrap();
+        assert_eq!(backlinks[0].context.as_deref(), Some("See [[target]]"));
+    }
+
+    #[test]
+    fn test_links_resolve_by_note_uid() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+
+        let target = upsert_note(&conn, "atoms.md", "Atoms", None, None, "x", 0).unwrap();
+        crate::db::note_ids::set_note_uid(&conn, target, "5e0c9d1a-7f3b-4a2e-8c6d-1b9f4e7a2c0d")
+            .unwrap();
+        let source = upsert_note(&conn, "source.md", "Source", None, None, "y", 0).unwrap();
+        let link = "5E0C9D1A-7F3B-4A2E-8C6D-1B9F4E7A2C0D".to_string();
+        replace_links(&conn, source, &[(link.clone(), None, Some(1), LinkKind::Wiki)]).unwrap();
+
+        assert_eq!(get_outlinks(&conn, source).unwrap()[0].target_id, Some(target));
+        assert_eq!(get_backlinks(&conn, "atoms.md").unwrap().len(), 1);
+        assert_eq!(resolve_link_target(&conn, &link).unwrap().as_deref(), Some("atoms.md"));
+        assert!(get_unresolved_links(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unresolved_links() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+
+        upsert_note(&conn, "Projects/Roadmap.md", "Roadmap", None, None, "x", 0).unwrap();
+        let source = upsert_note(&conn, "source.md", "Source", None, None, "y", 0).unwrap();
+        let links = vec![
+            ("projects/roadmap#Q1".to_string(), None, Some(1), LinkKind::Wiki),
+            ("Missing#Part".to_string(), Some("part".to_string()), Some(2), LinkKind::Wiki),
+            ("missing".to_string(), None, Some(3), LinkKind::Wiki),
+        ];
+        replace_links(&conn, source, &links).unwrap();
+
+        let unresolved = get_unresolved_links(&conn).unwrap();
+        let found: Vec<(&str, Option<i32>)> =
+            unresolved.iter().map(|l| (l.target.as_str(), l.line_number)).collect();
+        assert_eq!(found, vec![("Missing", Some(2)), ("missing", Some(3))]);
+    }
+
+    #[test]
+    fn test_link_neighborhood() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let note = |path: &str, links: &[&str]| {
+            let id = upsert_note(&conn, path, path, None, None, path, 0).unwrap();
+            let links: Vec<_> = links
+                .iter()
+                .enumerate()
+                .map(|(i, l)| (l.to_string(), None, Some(i as i32 + 1), LinkKind::Wiki))
+                .collect();
+            (id, links)
+        };
+        // a -> b -> c -> d, and e -> a
+        let notes = [
+            note("a.md", &["b"]),
+            note("b.md", &["c", "a"]),
+            note("c.md", &["d"]),
+            note("d.md", &[]),
+            note("e.md", &["a", "missing"]),
+        ];
+        for (id, links) in &notes {
+            replace_links(&conn, *id, links).unwrap();
+        }
+        let [a, b, c, d, e] = notes.map(|(id, _)| id);
+
+        let alone = link_neighborhood(&conn, a, 0).unwrap();
+        assert_eq!((alone.note_ids, alone.links), (vec![a], vec![]));
+        let near = link_neighborhood(&conn, a, 1).unwrap();
+        assert_eq!(near.note_ids, vec![a, b, e]);
+        assert_eq!(near.links, vec![(a, b), (b, a), (e, a)]);
+        assert_eq!(link_neighborhood(&conn, a, 2).unwrap().note_ids, vec![a, b, e, c]);
+
+        assert_eq!(shortest_link_path(&conn, e, d, false).unwrap(), Some(vec![e, a, b, c, d]));
+        assert_eq!(shortest_link_path(&conn, d, e, false).unwrap(), None);
+        assert_eq!(shortest_link_path(&conn, d, e, true).unwrap(), Some(vec![d, c, b, a, e]));
+        assert_eq!(shortest_link_path(&conn, c, c, false).unwrap(), Some(vec![c]));
     }
 }
@@ -3,14 +3,44 @@
 //! Handles SQLite database operations for note metadata,
 //! full-text search, links, and tags.
 
+pub mod cards;
 pub mod links;
+pub mod note_ids;
 pub mod notes;
+pub mod ocr;
+pub mod pdf_text;
+pub mod properties;
+pub mod reading;
+pub mod reminders;
+pub mod review;
+pub mod saved_searches;
 pub mod schema;
 pub mod search;
+pub mod smart_folders;
 pub mod tags;
+pub mod tasks;
+pub mod views;
+pub mod workspaces;
+pub mod writing;
+pub mod zettel;
 
+pub use cards::*;
 pub use links::*;
+pub use note_ids::*;
 pub use notes::*;
-pub use schema::{init_db, Database};
+pub use ocr::*;
+pub use pdf_text::*;
+pub use properties::*;
+pub use reading::*;
+pub use reminders::*;
+pub use review::*;
+pub use saved_searches::*;
+pub use schema::{init_db, is_plaintext_database, remove_database, Database};
 pub use search::*;
+pub use smart_folders::*;
 pub use tags::*;
+pub use tasks::*;
+pub use views::*;
+pub use workspaces::*;
+pub use writing::*;
+pub use zettel::*;
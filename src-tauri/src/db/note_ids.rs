@@ -0,0 +1,82 @@
+//! Stable note ID database operations
+
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use std::collections::{BTreeMap, HashMap};
+
+/// Set a note's ID
+pub fn set_note_uid(conn: &Connection, note_id: i64, uid: &str) -> Result<()> {
+    conn.prepare_cached(
+        "INSERT INTO note_uids (note_id, uid) VALUES (?1, ?2)
+         ON CONFLICT(note_id) DO UPDATE SET uid = excluded.uid",
+    )?
+    .execute(params![note_id, uid])?;
+    Ok(())
+}
+
+/// A note's ID, by note ID
+pub fn get_uid_by_note_id(conn: &Connection, note_id: i64) -> Result<Option<String>> {
+    conn.prepare_cached("SELECT uid FROM note_uids WHERE note_id = ?1")?
+        .query_row(params![note_id], |row| row.get(0))
+        .optional()
+}
+
+/// A note's ID, by path
+pub fn get_note_uid(conn: &Connection, path: &str) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT u.uid FROM note_uids u JOIN notes n ON n.id = u.note_id WHERE n.path = ?1",
+        params![path],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// The note that has an ID
+pub fn uid_owner(conn: &Connection, uid: &str) -> Result<Option<i64>> {
+    conn.prepare_cached("SELECT note_id FROM note_uids WHERE uid = ?1")?
+        .query_row(params![uid], |row| row.get(0))
+        .optional()
+}
+
+/// Every note's ID, by path
+pub fn list_note_uids(conn: &Connection) -> Result<BTreeMap<String, String>> {
+    let mut stmt =
+        conn.prepare("SELECT n.path, u.uid FROM note_uids u JOIN notes n ON n.id = u.note_id")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// Note path for every ID, keyed by the lowercased ID
+pub fn uid_paths(conn: &Connection) -> Result<HashMap<String, String>> {
+    let mut stmt =
+        conn.prepare("SELECT u.uid, n.path FROM note_uids u JOIN notes n ON n.id = u.note_id")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?.to_lowercase(), row.get::<_, String>(1)?))
+    })?;
+    rows.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{notes::{rename_note, upsert_note}, schema::Database};
+
+    #[test]
+    fn test_note_uids() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let id = upsert_note(&conn, "a.md", "A", None, None, "h", 0).unwrap();
+        let other = upsert_note(&conn, "b.md", "B", None, None, "h", 0).unwrap();
+
+        set_note_uid(&conn, id, "0c8f3e2a-5b1d-4c7e-9a6f-2d4b8e1c3a5f").unwrap();
+        assert_eq!(uid_owner(&conn, "0C8F3E2A-5B1D-4C7E-9A6F-2D4B8E1C3A5F").unwrap(), Some(id));
+        assert!(set_note_uid(&conn, other, "0c8f3e2a-5b1d-4c7e-9a6f-2d4b8e1c3a5f").is_err());
+
+        // Renames keep the ID
+        rename_note(&conn, "a.md", "notes/a.md").unwrap();
+        let uid = get_note_uid(&conn, "notes/a.md").unwrap();
+        assert_eq!(uid.as_deref(), Some("0c8f3e2a-5b1d-4c7e-9a6f-2d4b8e1c3a5f"));
+        assert_eq!(get_uid_by_note_id(&conn, id).unwrap(), uid);
+        assert_eq!(list_note_uids(&conn).unwrap()["notes/a.md"], uid.unwrap());
+        assert_eq!(uid_paths(&conn).unwrap()["0c8f3e2a-5b1d-4c7e-9a6f-2d4b8e1c3a5f"], "notes/a.md");
+    }
+}
@@ -1,7 +1,8 @@
 //! Note database operations
 
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, Connection, OptionalExtension, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Note metadata stored in database
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,7 +15,7 @@ pub struct NoteMeta {
     pub word_count: i32,
 }
 
-/// Insert or update a note in the database
+/// Insert or update a note in the database, returning its ID
 pub fn upsert_note(
     conn: &Connection,
     path: &str,
@@ -24,7 +25,9 @@ pub fn upsert_note(
     content_hash: &str,
     word_count: i32,
 ) -> Result<i64> {
-    conn.execute(
+    // `last_insert_rowid` isn't set when the upsert takes the update path,
+    // so the ID comes back through RETURNING
+    conn.prepare_cached(
         r#"
         INSERT INTO notes (path, title, created_at, modified_at, content_hash, word_count)
         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
@@ -33,23 +36,59 @@ pub fn upsert_note(
             modified_at = excluded.modified_at,
             content_hash = excluded.content_hash,
             word_count = excluded.word_count
+        RETURNING id
         "#,
-        params![
-            path,
-            title,
-            created_at,
-            modified_at,
-            content_hash,
-            word_count
-        ],
+    )?
+    .query_row(
+        params![path, title, created_at, modified_at, content_hash, word_count],
+        |row| row.get(0),
+    )
+}
+
+/// Content hash and modification time of every indexed note, by path
+pub fn list_note_hashes(conn: &Connection) -> Result<HashMap<String, (String, Option<String>)>> {
+    let mut stmt = conn.prepare("SELECT path, content_hash, modified_at FROM notes")?;
+    let rows = stmt.query_map([], |row| {
+        let hash: Option<String> = row.get(1)?;
+        Ok((row.get(0)?, (hash.unwrap_or_default(), row.get(2)?)))
+    })?;
+    rows.collect()
+}
+
+/// Path and title of every indexed note
+pub fn list_note_titles(conn: &Connection) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare_cached("SELECT path, title FROM notes")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// Record a new modification time for a note whose content didn't change
+pub fn set_note_modified(conn: &Connection, path: &str, modified_at: Option<&str>) -> Result<()> {
+    conn.execute(
+        "UPDATE notes SET modified_at = ?2 WHERE path = ?1",
+        params![path, modified_at],
     )?;
+    Ok(())
+}
 
-    Ok(conn.last_insert_rowid())
+/// A value from the index's bookkeeping
+pub fn get_index_state(conn: &Connection, key: &str) -> Result<Option<String>> {
+    conn.query_row("SELECT value FROM index_state WHERE key = ?1", params![key], |row| row.get(0))
+        .optional()
+}
+
+pub fn set_index_state(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO index_state (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )?;
+    Ok(())
 }
 
 /// Get note by path
 pub fn get_note_by_path(conn: &Connection, path: &str) -> Result<Option<NoteMeta>> {
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare_cached(
         "SELECT id, path, title, created_at, modified_at, word_count FROM notes WHERE path = ?1",
     )?;
 
@@ -111,6 +150,28 @@ pub fn list_notes(conn: &Connection) -> Result<Vec<NoteMeta>> {
     rows.collect()
 }
 
+/// Notes in `folder` and its subfolders, oldest first
+pub fn list_notes_in_folder(conn: &Connection, folder: &str) -> Result<Vec<NoteMeta>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, path, title, created_at, modified_at, word_count FROM notes
+         WHERE substr(path, 1, length(?1) + 1) = ?1 || '/'
+         ORDER BY COALESCE(created_at, modified_at), path",
+    )?;
+
+    let rows = stmt.query_map(params![folder.trim_matches('/')], |row| {
+        Ok(NoteMeta {
+            id: row.get(0)?,
+            path: row.get(1)?,
+            title: row.get(2)?,
+            created_at: row.get(3)?,
+            modified_at: row.get(4)?,
+            word_count: row.get(5)?,
+        })
+    })?;
+
+    rows.collect()
+}
+
 /// Delete note by path
 pub fn delete_note(conn: &Connection, path: &str) -> Result<bool> {
     let rows_affected = conn.execute("DELETE FROM notes WHERE path = ?1", params![path])?;
@@ -154,6 +215,19 @@ mod tests {
         assert_eq!(note.word_count, 100);
     }
 
+    #[test]
+    fn test_upsert_existing_note_returns_its_id() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+
+        let a = upsert_note(&conn, "a.md", "Note A", None, None, "a", 10).unwrap();
+        let b = upsert_note(&conn, "b.md", "Note B", None, None, "b", 20).unwrap();
+        assert_ne!(a, b);
+
+        let again = upsert_note(&conn, "a.md", "Note A v2", None, None, "a2", 11).unwrap();
+        assert_eq!(again, a);
+    }
+
     #[test]
     fn test_list_notes() {
         let db = Database::open_memory().unwrap();
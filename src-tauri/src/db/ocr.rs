@@ -0,0 +1,128 @@
+//! Recognized attachment text: storage and full-text search
+
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::search::escape_fts_query;
+
+/// An attachment whose text matched a search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentSearchResult {
+    pub path: String,
+    pub snippet: String,
+    pub rank: f64,
+    pub kind: AttachmentKind,
+}
+
+/// Where an attachment's searchable text came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachmentKind {
+    /// Recognized in an image
+    Image,
+    /// Extracted from a PDF
+    Pdf,
+}
+
+/// Store the text recognized in an attachment, replacing any earlier text
+pub fn set_attachment_text(conn: &Connection, path: &str, modified_at: i64, text: &str) -> Result<()> {
+    let id: i64 = conn.query_row(
+        "INSERT INTO attachment_text (path, modified_at) VALUES (?1, ?2)
+         ON CONFLICT(path) DO UPDATE SET modified_at = excluded.modified_at
+         RETURNING id",
+        params![path, modified_at],
+        |row| row.get(0),
+    )?;
+    conn.execute("DELETE FROM attachment_text_fts WHERE rowid = ?1", params![id])?;
+    conn.execute(
+        "INSERT INTO attachment_text_fts (rowid, content) VALUES (?1, ?2)",
+        params![id, text],
+    )?;
+    Ok(())
+}
+
+/// Forget an attachment's text
+pub fn delete_attachment_text(conn: &Connection, path: &str) -> Result<()> {
+    let id: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM attachment_text WHERE path = ?1",
+            params![path],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(id) = id {
+        conn.execute("DELETE FROM attachment_text_fts WHERE rowid = ?1", params![id])?;
+        conn.execute("DELETE FROM attachment_text WHERE id = ?1", params![id])?;
+    }
+    Ok(())
+}
+
+/// Modification time each attachment had when it was recognized, by path
+pub fn attachment_text_times(conn: &Connection) -> Result<HashMap<String, i64>> {
+    let mut stmt = conn.prepare("SELECT path, modified_at FROM attachment_text")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// Search recognized attachment text
+pub fn search_attachment_text(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<AttachmentSearchResult>> {
+    let safe_query = escape_fts_query(query);
+    if safe_query.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            a.path,
+            snippet(attachment_text_fts, 0, '<mark>', '</mark>', '...', 32) as snippet,
+            bm25(attachment_text_fts) as rank
+        FROM attachment_text_fts
+        JOIN attachment_text a ON attachment_text_fts.rowid = a.id
+        WHERE attachment_text_fts MATCH ?1
+        ORDER BY rank
+        LIMIT ?2
+        "#,
+    )?;
+    let rows = stmt.query_map(params![safe_query, limit as i64], |row| {
+        Ok(AttachmentSearchResult {
+            path: row.get(0)?,
+            snippet: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+            rank: row.get(2)?,
+            kind: AttachmentKind::Image,
+        })
+    })?;
+    rows.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    #[test]
+    fn test_attachment_text_search() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+
+        set_attachment_text(&conn, "attachments/receipt.png", 1, "Total due: 42 EUR").unwrap();
+        set_attachment_text(&conn, "attachments/shot.png", 1, "error: connection refused").unwrap();
+        set_attachment_text(&conn, "attachments/shot.png", 2, "build succeeded").unwrap();
+
+        let results = search_attachment_text(&conn, "succeeded", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path, "attachments/shot.png");
+        assert!(results[0].snippet.contains("<mark>succeeded</mark>"));
+        assert!(search_attachment_text(&conn, "refused", 10).unwrap().is_empty());
+        assert_eq!(attachment_text_times(&conn).unwrap()["attachments/shot.png"], 2);
+
+        delete_attachment_text(&conn, "attachments/receipt.png").unwrap();
+        assert!(search_attachment_text(&conn, "total", 10).unwrap().is_empty());
+        assert_eq!(attachment_text_times(&conn).unwrap().len(), 1);
+    }
+}
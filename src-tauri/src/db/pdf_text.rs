@@ -0,0 +1,101 @@
+//! Text extracted from PDF attachments: storage and full-text search
+
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use std::collections::HashMap;
+
+use super::ocr::{AttachmentKind, AttachmentSearchResult};
+use super::search::escape_fts_query;
+
+/// Store the text extracted from a PDF, replacing any earlier text
+pub fn set_pdf_text(conn: &Connection, path: &str, modified_at: i64, text: &str) -> Result<()> {
+    let id: i64 = conn.query_row(
+        "INSERT INTO pdf_text (path, modified_at) VALUES (?1, ?2)
+         ON CONFLICT(path) DO UPDATE SET modified_at = excluded.modified_at
+         RETURNING id",
+        params![path, modified_at],
+        |row| row.get(0),
+    )?;
+    conn.execute("DELETE FROM pdf_text_fts WHERE rowid = ?1", params![id])?;
+    conn.execute("INSERT INTO pdf_text_fts (rowid, content) VALUES (?1, ?2)", params![id, text])?;
+    Ok(())
+}
+
+/// Forget a PDF's text
+pub fn delete_pdf_text(conn: &Connection, path: &str) -> Result<()> {
+    let id: Option<i64> = conn
+        .query_row("SELECT id FROM pdf_text WHERE path = ?1", params![path], |row| row.get(0))
+        .optional()?;
+    if let Some(id) = id {
+        conn.execute("DELETE FROM pdf_text_fts WHERE rowid = ?1", params![id])?;
+        conn.execute("DELETE FROM pdf_text WHERE id = ?1", params![id])?;
+    }
+    Ok(())
+}
+
+/// Modification time each PDF had when its text was extracted, by path
+pub fn pdf_text_times(conn: &Connection) -> Result<HashMap<String, i64>> {
+    let mut stmt = conn.prepare("SELECT path, modified_at FROM pdf_text")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// Search extracted PDF text
+pub fn search_pdf_text(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<AttachmentSearchResult>> {
+    let safe_query = escape_fts_query(query);
+    if safe_query.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT
+            p.path,
+            snippet(pdf_text_fts, 0, '<mark>', '</mark>', '...', 32) as snippet,
+            bm25(pdf_text_fts) as rank
+        FROM pdf_text_fts
+        JOIN pdf_text p ON pdf_text_fts.rowid = p.id
+        WHERE pdf_text_fts MATCH ?1
+        ORDER BY rank
+        LIMIT ?2
+        "#,
+    )?;
+    let rows = stmt.query_map(params![safe_query, limit as i64], |row| {
+        Ok(AttachmentSearchResult {
+            path: row.get(0)?,
+            snippet: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+            rank: row.get(2)?,
+            kind: AttachmentKind::Pdf,
+        })
+    })?;
+    rows.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    #[test]
+    fn test_pdf_text_search() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+
+        set_pdf_text(&conn, "reports/q3.pdf", 1, "Quarterly revenue grew 12%").unwrap();
+        set_pdf_text(&conn, "reports/q3.pdf", 2, "Quarterly revenue grew 14%").unwrap();
+
+        let results = search_pdf_text(&conn, "quarterly revenue", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path, "reports/q3.pdf");
+        assert_eq!(results[0].kind, AttachmentKind::Pdf);
+        assert!(results[0].snippet.contains("14%"));
+        assert_eq!(pdf_text_times(&conn).unwrap()["reports/q3.pdf"], 2);
+
+        delete_pdf_text(&conn, "reports/q3.pdf").unwrap();
+        assert!(search_pdf_text(&conn, "revenue", 10).unwrap().is_empty());
+        assert!(pdf_text_times(&conn).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+//! Frontmatter property database operations
+
+use rusqlite::{params, Connection, Result};
+
+use super::notes::NoteMeta;
+
+/// Replace all indexed frontmatter properties for a note
+pub fn replace_note_properties(
+    conn: &Connection,
+    note_id: i64,
+    properties: &[(String, String)],
+) -> Result<()> {
+    conn.prepare_cached("DELETE FROM note_properties WHERE note_id = ?1")?
+        .execute(params![note_id])?;
+
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO note_properties (note_id, key, value, number) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (key, value) in properties {
+        stmt.execute(params![note_id, key, value, property_number(value)])?;
+    }
+    Ok(())
+}
+
+/// A property value as a number: numbers as they are, `true` and `false`
+/// (checkboxes) as 1 and 0
+fn property_number(value: &str) -> Option<f64> {
+    match value.trim() {
+        v if v.eq_ignore_ascii_case("true") => Some(1.0),
+        v if v.eq_ignore_ascii_case("false") => Some(0.0),
+        v => v.parse::<f64>().ok().filter(|n| n.is_finite()),
+    }
+}
+
+/// Every value of a property as `(note ID, number)`, the number `None` for
+/// values that aren't numbers or booleans
+pub fn property_numbers(conn: &Connection, key: &str) -> Result<Vec<(i64, Option<f64>)>> {
+    let mut stmt = conn
+        .prepare("SELECT note_id, number FROM note_properties WHERE key = ?1 ORDER BY rowid")?;
+    let rows = stmt.query_map(params![key], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// Replace the type schema warnings for a note
+pub fn replace_type_warnings(conn: &Connection, note_id: i64, warnings: &[String]) -> Result<()> {
+    conn.prepare_cached("DELETE FROM note_type_warnings WHERE note_id = ?1")?
+        .execute(params![note_id])?;
+
+    let mut stmt =
+        conn.prepare_cached("INSERT INTO note_type_warnings (note_id, message) VALUES (?1, ?2)")?;
+    for message in warnings {
+        stmt.execute(params![note_id, message])?;
+    }
+    Ok(())
+}
+
+/// Every type schema warning as `(path, message)`, by path
+pub fn list_type_warnings(conn: &Connection) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT n.path, w.message FROM note_type_warnings w
+         JOIN notes n ON n.id = w.note_id
+         ORDER BY n.path, w.rowid",
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// Notes whose `type` property is `type_name`, ignoring case, by title
+pub fn list_notes_by_type(conn: &Connection, type_name: &str) -> Result<Vec<NoteMeta>> {
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT n.id, n.path, n.title, n.created_at, n.modified_at, n.word_count
+         FROM notes n JOIN note_properties p ON p.note_id = n.id
+         WHERE p.key = 'type' AND p.value = ?1 COLLATE NOCASE
+         ORDER BY n.title COLLATE NOCASE",
+    )?;
+    let rows = stmt.query_map(params![type_name.trim()], |row| {
+        Ok(NoteMeta {
+            id: row.get(0)?,
+            path: row.get(1)?,
+            title: row.get(2)?,
+            created_at: row.get(3)?,
+            modified_at: row.get(4)?,
+            word_count: row.get(5)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Values of one property of the note at `path`, in frontmatter order
+pub fn get_property_values(conn: &Connection, path: &str, key: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT p.value FROM note_properties p JOIN notes n ON n.id = p.note_id
+         WHERE n.path = ?1 AND p.key = ?2
+         ORDER BY p.rowid",
+    )?;
+    let rows = stmt.query_map(params![path, key], |row| row.get(0))?;
+    rows.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{notes::upsert_note, Database};
+
+    #[test]
+    fn test_notes_by_type_and_warnings() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let ada = upsert_note(&conn, "people/ada.md", "Ada", None, None, "h1", 0).unwrap();
+        let bob = upsert_note(&conn, "people/bob.md", "Bob", None, None, "h2", 0).unwrap();
+        let book = upsert_note(&conn, "books/dune.md", "Dune", None, None, "h3", 0).unwrap();
+        let props = |t: &str| vec![("type".to_string(), t.to_string())];
+        replace_note_properties(&conn, bob, &props("person")).unwrap();
+        replace_note_properties(&conn, ada, &props("Person")).unwrap();
+        replace_note_properties(&conn, book, &props("book")).unwrap();
+
+        let people: Vec<String> =
+            list_notes_by_type(&conn, "person").unwrap().into_iter().map(|n| n.title).collect();
+        assert_eq!(people, vec!["Ada", "Bob"]);
+
+        replace_type_warnings(&conn, bob, &["missing email".to_string()]).unwrap();
+        replace_type_warnings(&conn, ada, &[]).unwrap();
+        assert_eq!(
+            list_type_warnings(&conn).unwrap(),
+            vec![("people/bob.md".to_string(), "missing email".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_property_numbers() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let id = upsert_note(&conn, "log.md", "Log", None, None, "h", 0).unwrap();
+        let props: Vec<(String, String)> = [("hours", "2.5"), ("hours", "three"), ("done", "True")]
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        replace_note_properties(&conn, id, &props).unwrap();
+
+        assert_eq!(property_numbers(&conn, "hours").unwrap(), vec![(id, Some(2.5)), (id, None)]);
+        assert_eq!(property_numbers(&conn, "done").unwrap(), vec![(id, Some(1.0))]);
+        assert_eq!(property_number("NaN"), None);
+    }
+}
@@ -0,0 +1,257 @@
+//! Reading list database operations
+
+use rusqlite::{params, params_from_iter, types::Value, Connection, Result, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::vault::ExtractedUrl;
+
+/// Whether a reading list entry has been read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadingStatus {
+    Unread,
+    Read,
+}
+
+impl ReadingStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            ReadingStatus::Unread => "unread",
+            ReadingStatus::Read => "read",
+        }
+    }
+}
+
+/// A web link collected from the vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadingItem {
+    pub id: i64,
+    pub url: String,
+    /// Link text, if the URL was ever linked with some
+    pub title: Option<String>,
+    /// Host without `www.`
+    pub domain: String,
+    pub status: ReadingStatus,
+    /// Note the URL was first found in
+    pub added_from_note: String,
+    pub added_at: String,
+    pub read_at: Option<String>,
+}
+
+/// Reading list size for one domain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainCount {
+    pub domain: String,
+    pub unread: i64,
+    pub total: i64,
+}
+
+const READING_COLUMNS: &str = "id, url, title, domain, status, added_from_note, added_at, read_at";
+
+fn item_from_row(row: &Row) -> Result<ReadingItem> {
+    let status: String = row.get(4)?;
+    Ok(ReadingItem {
+        id: row.get(0)?,
+        url: row.get(1)?,
+        title: row.get(2)?,
+        domain: row.get(3)?,
+        status: if status == "read" {
+            ReadingStatus::Read
+        } else {
+            ReadingStatus::Unread
+        },
+        added_from_note: row.get(5)?,
+        added_at: row.get(6)?,
+        read_at: row.get(7)?,
+    })
+}
+
+/// Add a note's URLs to the reading list. URLs already on it keep their
+/// status and note, and only gain a title if they had none. Entries stay
+/// when the link is later removed from the note.
+pub fn add_reading_items(
+    conn: &Connection,
+    note_path: &str,
+    urls: &[ExtractedUrl],
+    now: &str,
+) -> Result<()> {
+    let mut stmt = conn.prepare(
+        r#"
+        INSERT INTO reading_list (url, title, domain, status, added_from_note, added_at)
+        VALUES (?1, ?2, ?3, 'unread', ?4, ?5)
+        ON CONFLICT(url) DO UPDATE SET
+            title = COALESCE(reading_list.title, excluded.title)
+        "#,
+    )?;
+    for url in urls {
+        stmt.execute(params![
+            url.url,
+            url.title,
+            url_domain(&url.url),
+            note_path,
+            now
+        ])?;
+    }
+    Ok(())
+}
+
+/// Reading list entries, newest first, optionally by status and domain
+pub fn list_reading_items(
+    conn: &Connection,
+    status: Option<ReadingStatus>,
+    domain: Option<&str>,
+) -> Result<Vec<ReadingItem>> {
+    let mut conditions = Vec::new();
+    let mut values: Vec<Value> = Vec::new();
+    if let Some(status) = status {
+        values.push(Value::Text(status.as_str().to_string()));
+        conditions.push(format!("status = ?{}", values.len()));
+    }
+    if let Some(domain) = domain {
+        values.push(Value::Text(domain.trim().to_lowercase()));
+        conditions.push(format!("domain = ?{}", values.len()));
+    }
+    let filter = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM reading_list {} ORDER BY added_at DESC, id DESC",
+        READING_COLUMNS, filter
+    ))?;
+    let rows = stmt.query_map(params_from_iter(values), item_from_row)?;
+    rows.collect()
+}
+
+/// Domains on the reading list, most unread first
+pub fn reading_domains(conn: &Connection) -> Result<Vec<DomainCount>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT domain, SUM(status = 'unread') AS unread, COUNT(*) AS total
+        FROM reading_list
+        GROUP BY domain
+        ORDER BY unread DESC, total DESC, domain
+        "#,
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(DomainCount {
+            domain: row.get(0)?,
+            unread: row.get(1)?,
+            total: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Mark an entry read or unread. Returns the updated entry, or `None` if
+/// there is no such entry.
+pub fn set_reading_status(
+    conn: &Connection,
+    id: i64,
+    status: ReadingStatus,
+    now: &str,
+) -> Result<Option<ReadingItem>> {
+    let read_at = (status == ReadingStatus::Read).then_some(now);
+    let updated = conn.execute(
+        "UPDATE reading_list SET status = ?1, read_at = ?2 WHERE id = ?3",
+        params![status.as_str(), read_at, id],
+    )?;
+    if updated == 0 {
+        return Ok(None);
+    }
+    conn.query_row(
+        &format!("SELECT {} FROM reading_list WHERE id = ?1", READING_COLUMNS),
+        params![id],
+        item_from_row,
+    )
+    .map(Some)
+}
+
+/// Lowercased host of a URL, without `www.`, credentials or port
+pub fn url_domain(url: &str) -> String {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = host.rsplit('@').next().unwrap_or_default();
+    let host = host.split(':').next().unwrap_or_default().to_lowercase();
+    host.strip_prefix("www.")
+        .map(str::to_string)
+        .unwrap_or(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    fn url(url: &str, title: Option<&str>) -> ExtractedUrl {
+        ExtractedUrl {
+            url: url.to_string(),
+            title: title.map(String::from),
+            line_number: 1,
+        }
+    }
+
+    #[test]
+    fn test_reading_list() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+
+        add_reading_items(
+            &conn,
+            "inbox.md",
+            &[
+                url("https://www.example.com/a", None),
+                url("https://blog.rust-lang.org/", Some("Rust Blog")),
+            ],
+            "2025-02-01T09:00:00Z",
+        )
+        .unwrap();
+        // Seen again from another note: the title fills in, the note stays
+        add_reading_items(
+            &conn,
+            "later.md",
+            &[url("https://www.example.com/a", Some("Example"))],
+            "2025-02-02T09:00:00Z",
+        )
+        .unwrap();
+
+        let items = list_reading_items(&conn, None, Some("example.com")).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title.as_deref(), Some("Example"));
+        assert_eq!(items[0].added_from_note, "inbox.md");
+        assert_eq!(items[0].status, ReadingStatus::Unread);
+
+        let read = set_reading_status(
+            &conn,
+            items[0].id,
+            ReadingStatus::Read,
+            "2025-02-03T09:00:00Z",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(read.read_at.as_deref(), Some("2025-02-03T09:00:00Z"));
+        assert!(set_reading_status(&conn, 999, ReadingStatus::Read, "now")
+            .unwrap()
+            .is_none());
+
+        let unread = list_reading_items(&conn, Some(ReadingStatus::Unread), None).unwrap();
+        assert_eq!(unread.len(), 1);
+        assert_eq!(unread[0].domain, "blog.rust-lang.org");
+
+        let domains = reading_domains(&conn).unwrap();
+        assert_eq!(domains[0].domain, "blog.rust-lang.org");
+        assert_eq!((domains[1].unread, domains[1].total), (0, 1));
+    }
+
+    #[test]
+    fn test_url_domain() {
+        assert_eq!(
+            url_domain("https://www.Example.com/path?q=1"),
+            "example.com"
+        );
+        assert_eq!(url_domain("http://user:pw@host.io:8080#top"), "host.io");
+        assert_eq!(url_domain("https://docs.rs"), "docs.rs");
+    }
+}
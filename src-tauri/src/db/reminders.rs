@@ -0,0 +1,172 @@
+//! Reminder database operations
+
+use rusqlite::{params, Connection, Result, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::vault::ExtractedReminder;
+
+/// A reminder with the note it was set in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: i64,
+    pub note_path: String,
+    pub note_title: String,
+    /// Local time, `YYYY-MM-DD HH:MM`
+    pub remind_at: String,
+    /// Reminder text; empty for a note's frontmatter reminder
+    pub text: String,
+    pub line_number: Option<i32>,
+}
+
+const REMINDER_COLUMNS: &str = "r.id, n.path, n.title, r.remind_at, r.text, r.line_number";
+
+fn reminder_from_row(row: &Row) -> Result<Reminder> {
+    Ok(Reminder {
+        id: row.get(0)?,
+        note_path: row.get(1)?,
+        note_title: row.get(2)?,
+        remind_at: row.get(3)?,
+        text: row.get(4)?,
+        line_number: row.get(5)?,
+    })
+}
+
+/// Replace all reminders for a note, keeping whether unchanged ones have
+/// fired. Reminders new to the index that are already past `now` count as
+/// fired, so indexing old notes doesn't set off a burst of notifications.
+pub fn replace_reminders(
+    conn: &Connection,
+    note_id: i64,
+    reminders: &[ExtractedReminder],
+    now: &str,
+) -> Result<()> {
+    let keys: HashSet<(&str, &str)> = reminders
+        .iter()
+        .map(|r| (r.remind_at.as_str(), r.text.as_str()))
+        .collect();
+
+    let existing: Vec<(i64, String, String)> = {
+        let mut stmt = conn.prepare("SELECT id, remind_at, text FROM reminders WHERE note_id = ?1")?;
+        let rows = stmt.query_map(params![note_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<Result<_>>()?
+    };
+    for (id, _, _) in existing
+        .iter()
+        .filter(|(_, at, text)| !keys.contains(&(at.as_str(), text.as_str())))
+    {
+        conn.execute("DELETE FROM reminders WHERE id = ?1", params![id])?;
+    }
+
+    let mut stmt = conn.prepare(
+        r#"
+        INSERT INTO reminders (note_id, remind_at, text, line_number, fired)
+        VALUES (?1, ?2, ?3, ?4, ?2 <= ?5)
+        ON CONFLICT(note_id, remind_at, text) DO UPDATE SET
+            line_number = excluded.line_number
+        "#,
+    )?;
+    for reminder in reminders {
+        stmt.execute(params![
+            note_id,
+            reminder.remind_at,
+            reminder.text,
+            reminder.line_number.map(|l| l as i32),
+            now
+        ])?;
+    }
+
+    Ok(())
+}
+
+/// Reminders after `now`, soonest first
+pub fn upcoming_reminders(conn: &Connection, now: &str, limit: usize) -> Result<Vec<Reminder>> {
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {}
+        FROM reminders r
+        JOIN notes n ON r.note_id = n.id
+        WHERE r.remind_at > ?1
+        ORDER BY r.remind_at, n.path, r.line_number
+        LIMIT ?2
+        "#,
+        REMINDER_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![now, limit as i64], reminder_from_row)?;
+    rows.collect()
+}
+
+/// Reminders at or before `now` that haven't fired yet, oldest first
+pub fn due_reminders(conn: &Connection, now: &str) -> Result<Vec<Reminder>> {
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {}
+        FROM reminders r
+        JOIN notes n ON r.note_id = n.id
+        WHERE r.fired = 0 AND r.remind_at <= ?1
+        ORDER BY r.remind_at, n.path, r.line_number
+        "#,
+        REMINDER_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![now], reminder_from_row)?;
+    rows.collect()
+}
+
+/// Record that a reminder's notification went out
+pub fn mark_reminder_fired(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute("UPDATE reminders SET fired = 1 WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{notes::upsert_note, Database};
+
+    fn reminder(at: &str, text: &str, line: usize) -> ExtractedReminder {
+        ExtractedReminder {
+            line_number: Some(line),
+            remind_at: at.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_reminder_lifecycle() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let id = upsert_note(&conn, "plans.md", "Plans", None, None, "h", 0).unwrap();
+
+        let now = "2025-02-01 12:00";
+        let reminders = vec![
+            reminder("2025-01-01 09:00", "Already past", 1),
+            reminder("2025-02-01 13:00", "Call dentist", 2),
+            reminder("2025-03-01 09:00", "Renew passport", 3),
+        ];
+        replace_reminders(&conn, id, &reminders, now).unwrap();
+
+        let upcoming = upcoming_reminders(&conn, now, 10).unwrap();
+        let texts: Vec<&str> = upcoming.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(texts, vec!["Call dentist", "Renew passport"]);
+        assert_eq!(upcoming[0].note_title, "Plans");
+
+        // Past when first indexed, so it never fires
+        assert!(due_reminders(&conn, now).unwrap().is_empty());
+
+        let due = due_reminders(&conn, "2025-02-01 13:00").unwrap();
+        assert_eq!(due.len(), 1);
+        mark_reminder_fired(&conn, due[0].id).unwrap();
+
+        // Re-indexing keeps the fired state of unchanged reminders; a
+        // changed time is a new reminder
+        let reminders = vec![
+            reminder("2025-02-01 13:00", "Call dentist", 4),
+            reminder("2025-02-01 12:30", "Renew passport", 5),
+        ];
+        replace_reminders(&conn, id, &reminders, now).unwrap();
+        let due = due_reminders(&conn, "2025-02-01 14:00").unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].text, "Renew passport");
+        assert_eq!(upcoming_reminders(&conn, "2025-01-01 00:00", 10).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,145 @@
+//! Review queue database operations: resurfacing notes that haven't been
+//! touched in a while
+
+use rusqlite::{params, Connection, Result};
+use serde::{Deserialize, Serialize};
+
+/// How to order notes in the review queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewStrategy {
+    /// Random, with well-linked notes more likely to come up
+    #[default]
+    Weighted,
+    /// Least recently touched first
+    Oldest,
+    /// Most links first
+    Connected,
+}
+
+/// A note due for another look
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewItem {
+    pub path: String,
+    pub title: String,
+    /// Latest of the note's modification, last open and last review
+    pub last_seen: String,
+    pub last_reviewed_at: Option<String>,
+    /// Links into and out of the note
+    pub link_count: i64,
+}
+
+/// Record that a note was opened
+pub fn record_note_opened(conn: &Connection, note_id: i64, now: &str) -> Result<()> {
+    conn.execute(
+        r#"
+        INSERT INTO note_activity (note_id, last_opened_at) VALUES (?1, ?2)
+        ON CONFLICT(note_id) DO UPDATE SET last_opened_at = excluded.last_opened_at
+        "#,
+        params![note_id, now],
+    )?;
+    Ok(())
+}
+
+/// Record that a note was reviewed, taking it out of the queue until it
+/// goes stale again
+pub fn mark_note_reviewed(conn: &Connection, note_id: i64, now: &str) -> Result<()> {
+    conn.execute(
+        r#"
+        INSERT INTO note_activity (note_id, last_reviewed_at) VALUES (?1, ?2)
+        ON CONFLICT(note_id) DO UPDATE SET last_reviewed_at = excluded.last_reviewed_at
+        "#,
+        params![note_id, now],
+    )?;
+    Ok(())
+}
+
+/// Up to `limit` notes not modified, opened or reviewed since `cutoff`
+/// (an RFC 3339 UTC timestamp, like the stored times)
+pub fn review_queue(
+    conn: &Connection,
+    strategy: ReviewStrategy,
+    cutoff: &str,
+    limit: usize,
+) -> Result<Vec<ReviewItem>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT path, title, last_seen, last_reviewed_at, link_count, roll FROM (
+            SELECT n.path, n.title, a.last_reviewed_at,
+                MAX(COALESCE(n.modified_at, ''), COALESCE(a.last_opened_at, ''),
+                    COALESCE(a.last_reviewed_at, '')) AS last_seen,
+                (SELECT COUNT(*) FROM links l
+                 WHERE l.source_id = n.id OR l.target_id = n.id) AS link_count,
+                ABS(RANDOM() % 1000000) AS roll
+            FROM notes n
+            LEFT JOIN note_activity a ON a.note_id = n.id
+        )
+        WHERE last_seen < ?1
+        "#,
+    )?;
+    let rows = stmt.query_map(params![cutoff], |row| {
+        let item = ReviewItem {
+            path: row.get(0)?,
+            title: row.get(1)?,
+            last_seen: row.get(2)?,
+            last_reviewed_at: row.get(3)?,
+            link_count: row.get(4)?,
+        };
+        let roll: i64 = row.get(5)?;
+        Ok((item, roll))
+    })?;
+    let mut candidates = rows.collect::<Result<Vec<_>>>()?;
+
+    match strategy {
+        ReviewStrategy::Oldest => candidates.sort_by(|(a, _), (b, _)| a.last_seen.cmp(&b.last_seen)),
+        ReviewStrategy::Connected => candidates.sort_by(|(a, _), (b, _)| {
+            b.link_count.cmp(&a.link_count).then_with(|| a.last_seen.cmp(&b.last_seen))
+        }),
+        ReviewStrategy::Weighted => {
+            // Weighted sampling without replacement: each note draws
+            // u^(1/weight) and the highest draws win
+            let key = |(item, roll): &(ReviewItem, i64)| {
+                let u = (*roll as f64 + 1.0) / 1_000_001.0;
+                u.powf(1.0 / (1 + item.link_count) as f64)
+            };
+            candidates.sort_by(|a, b| key(b).total_cmp(&key(a)));
+        }
+    }
+
+    Ok(candidates.into_iter().take(limit).map(|(item, _)| item).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{links::replace_links, notes::upsert_note, Database};
+    use crate::vault::LinkKind;
+
+    #[test]
+    fn test_review_queue() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let old = upsert_note(&conn, "old.md", "Old", None, Some("2024-01-01T00:00:00Z"), "h", 0).unwrap();
+        let hub = upsert_note(&conn, "hub.md", "Hub", None, Some("2024-03-01T00:00:00Z"), "h", 0).unwrap();
+        upsert_note(&conn, "fresh.md", "Fresh", None, Some("2025-01-20T00:00:00Z"), "h", 0).unwrap();
+        let opened = upsert_note(&conn, "opened.md", "Opened", None, Some("2024-01-01T00:00:00Z"), "h", 0).unwrap();
+        let links = [
+            ("old.md".to_string(), None, Some(1), LinkKind::Wiki),
+            ("fresh.md".to_string(), None, Some(2), LinkKind::Wiki),
+        ];
+        replace_links(&conn, hub, &links).unwrap();
+        record_note_opened(&conn, opened, "2025-01-25T00:00:00Z").unwrap();
+
+        let cutoff = "2025-01-01T00:00:00Z";
+        let paths = |strategy| -> Vec<String> {
+            review_queue(&conn, strategy, cutoff, 10).unwrap().into_iter().map(|i| i.path).collect()
+        };
+        assert_eq!(paths(ReviewStrategy::Oldest), vec!["old.md", "hub.md"]);
+        assert_eq!(paths(ReviewStrategy::Connected), vec!["hub.md", "old.md"]);
+        assert_eq!(paths(ReviewStrategy::Weighted).len(), 2);
+        assert_eq!(review_queue(&conn, ReviewStrategy::Oldest, cutoff, 1).unwrap().len(), 1);
+
+        mark_note_reviewed(&conn, old, "2025-01-30T00:00:00Z").unwrap();
+        assert_eq!(paths(ReviewStrategy::Oldest), vec!["hub.md"]);
+    }
+}
@@ -0,0 +1,107 @@
+//! Saved search database operations
+
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+
+use crate::vault::SavedSearch;
+
+fn saved_search_from_row(row: &Row) -> Result<SavedSearch> {
+    let filter: String = row.get(2)?;
+    Ok(SavedSearch {
+        name: row.get(0)?,
+        query: row.get(1)?,
+        filter: serde_json::from_str(&filter).unwrap_or_default(),
+        pinned: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+/// Save a search, replacing any with the same name (ignoring case)
+pub fn save_search(conn: &Connection, search: &SavedSearch) -> Result<()> {
+    conn.execute(
+        r#"
+        INSERT INTO saved_searches (name, query, filter, pinned, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        ON CONFLICT(name) DO UPDATE SET
+            name = excluded.name,
+            query = excluded.query,
+            filter = excluded.filter,
+            pinned = excluded.pinned,
+            updated_at = excluded.updated_at
+        "#,
+        params![
+            search.name,
+            search.query,
+            serde_json::to_string(&search.filter).unwrap_or_default(),
+            search.pinned,
+            search.updated_at
+        ],
+    )?;
+    Ok(())
+}
+
+/// A saved search by name, ignoring case
+pub fn get_saved_search(conn: &Connection, name: &str) -> Result<Option<SavedSearch>> {
+    conn.query_row(
+        "SELECT name, query, filter, pinned, updated_at FROM saved_searches WHERE name = ?1",
+        params![name],
+        saved_search_from_row,
+    )
+    .optional()
+}
+
+/// All saved searches, pinned ones first, then by name
+pub fn list_saved_searches(conn: &Connection) -> Result<Vec<SavedSearch>> {
+    let mut stmt = conn.prepare(
+        "SELECT name, query, filter, pinned, updated_at FROM saved_searches
+         ORDER BY pinned DESC, name",
+    )?;
+    let rows = stmt.query_map([], saved_search_from_row)?;
+    rows.collect()
+}
+
+/// Delete a saved search. Returns whether it existed.
+pub fn delete_saved_search(conn: &Connection, name: &str) -> Result<bool> {
+    let deleted = conn.execute("DELETE FROM saved_searches WHERE name = ?1", params![name])?;
+    Ok(deleted > 0)
+}
+
+/// Replace every saved search, e.g. with the ones from the vault's file
+pub fn replace_saved_searches(conn: &Connection, searches: &[SavedSearch]) -> Result<()> {
+    conn.execute("DELETE FROM saved_searches", [])?;
+    for search in searches {
+        save_search(conn, search)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    #[test]
+    fn test_saved_search_crud() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let search = |name: &str, pinned: bool| SavedSearch {
+            name: name.to_string(),
+            query: "borrow checker".to_string(),
+            filter: Default::default(),
+            pinned,
+            updated_at: "2026-10-01T09:00:00Z".to_string(),
+        };
+        save_search(&conn, &search("rust", false)).unwrap();
+        save_search(&conn, &search("Inbox", true)).unwrap();
+        // Same name in another case replaces it
+        save_search(&conn, &search("Rust", true)).unwrap();
+
+        let names: Vec<String> =
+            list_saved_searches(&conn).unwrap().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, vec!["Inbox", "Rust"]);
+        assert_eq!(get_saved_search(&conn, "RUST").unwrap(), Some(search("Rust", true)));
+
+        assert!(delete_saved_search(&conn, "inbox").unwrap());
+        replace_saved_searches(&conn, &[]).unwrap();
+        assert!(list_saved_searches(&conn).unwrap().is_empty());
+    }
+}
@@ -1,10 +1,17 @@
 //! Database schema and initialization
 
-use rusqlite::{Connection, Result};
+use rusqlite::{params, Connection, Result};
+use std::fs;
+use std::io::Read;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 
+/// Prepared statements kept for reuse; enough for everything indexing a
+/// note runs
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
 /// Database wrapper with connection pooling
+#[derive(Clone)]
 pub struct Database {
     conn: Arc<Mutex<Connection>>,
 }
@@ -12,7 +19,28 @@ pub struct Database {
 impl Database {
     /// Open or create database at path
     pub fn open(path: &Path) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Open or create an SQLCipher-encrypted database at path. A wrong key
+    /// surfaces as `ErrorCode::NotADatabase`.
+    pub fn open_encrypted(path: &Path, key: &str) -> Result<Self> {
         let conn = Connection::open(path)?;
+        // Must be the first statement on the connection
+        conn.pragma_update(None, "key", key)?;
+        Self::init(conn)
+    }
+
+    /// Whether SQLite was built with SQLCipher. Without it `PRAGMA key` is
+    /// silently ignored, so callers must check before relying on it.
+    pub fn supports_encryption() -> bool {
+        Connection::open_in_memory()
+            .and_then(|conn| conn.query_row("PRAGMA cipher_version", [], |row| row.get::<_, String>(0)))
+            .is_ok()
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
 
         // Enable foreign keys
         conn.execute_batch("PRAGMA foreign_keys = ON;")?;
@@ -20,6 +48,9 @@ impl Database {
         // Use WAL mode for better concurrency
         conn.execute_batch("PRAGMA journal_mode = WAL;")?;
 
+        // Another instance may be writing to the same index
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+
         // Initialize schema
         init_schema(&conn)?;
 
@@ -31,6 +62,7 @@ impl Database {
     /// Open in-memory database (for testing)
     pub fn open_memory() -> Result<Self> {
         let conn = Connection::open_in_memory()?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
         conn.execute_batch("PRAGMA foreign_keys = ON;")?;
         init_schema(&conn)?;
 
@@ -40,11 +72,60 @@ impl Database {
     }
 
     /// Get connection for operations
+    ///
+    /// A panic while the connection was held poisons the mutex but leaves
+    /// SQLite itself consistent, so the poison is cleared rather than
+    /// propagated.
     pub fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
-        self.conn.lock().expect("Database mutex poisoned")
+        self.conn.lock().unwrap_or_else(|poisoned| {
+            tracing::warn!("Database lock was poisoned; recovering");
+            self.conn.clear_poison();
+            poisoned.into_inner()
+        })
+    }
+
+    /// Run `f` in a transaction, committing if it succeeds and rolling back
+    /// if it fails. The connection stays locked throughout, so `f` must use
+    /// the connection it is given rather than calling `conn()`.
+    pub fn transaction<T, E>(
+        &self,
+        f: impl FnOnce(&Connection) -> std::result::Result<T, E>,
+    ) -> std::result::Result<T, E>
+    where
+        E: From<rusqlite::Error>,
+    {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let value = f(&tx)?;
+        tx.commit()?;
+        Ok(value)
     }
 }
 
+/// Whether the file at `path` is an unencrypted SQLite database. Empty
+/// files count as plaintext since SQLite hasn't written a header yet.
+pub fn is_plaintext_database(path: &Path) -> std::io::Result<bool> {
+    let mut header = Vec::with_capacity(16);
+    fs::File::open(path)?.take(16).read_to_end(&mut header)?;
+    Ok(header.is_empty() || header == b"SQLite format 3\0")
+}
+
+/// Remove a database file along with its WAL and shared-memory files
+pub fn remove_database(path: &Path) -> std::io::Result<()> {
+    for suffix in ["", "-wal", "-shm"] {
+        let file = path.with_file_name(format!(
+            "{}{}",
+            path.file_name().unwrap_or_default().to_string_lossy(),
+            suffix
+        ));
+        match fs::remove_file(file) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
 /// Initialize database schema
 pub fn init_db(conn: &Connection) -> Result<()> {
     init_schema(conn)
@@ -52,9 +133,32 @@ pub fn init_db(conn: &Connection) -> Result<()> {
 
 fn init_schema(conn: &Connection) -> Result<()> {
     conn.execute_batch(SCHEMA)?;
+    for (table, column, definition) in ADDED_COLUMNS {
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2)",
+            params![table, column],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            let sql = format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition);
+            conn.execute_batch(&sql)?;
+        }
+    }
     Ok(())
 }
 
+/// Columns added to tables after they were first created, as (table,
+/// column, definition). `CREATE TABLE IF NOT EXISTS` leaves older
+/// databases without them.
+const ADDED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("links", "context", "TEXT"),
+    ("links", "kind", "TEXT NOT NULL DEFAULT 'wiki'"),
+    ("tags", "color", "TEXT"),
+    ("tags", "icon", "TEXT"),
+    ("tags", "description", "TEXT"),
+    ("note_properties", "number", "REAL"),
+];
+
 const SCHEMA: &str = r#"
 -- Notes metadata (synced from filesystem)
 CREATE TABLE IF NOT EXISTS notes (
@@ -67,6 +171,12 @@ CREATE TABLE IF NOT EXISTS notes (
     word_count INTEGER DEFAULT 0
 );
 
+-- Index bookkeeping, e.g. the settings notes were last indexed with
+CREATE TABLE IF NOT EXISTS index_state (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
 -- Full-text search index
 -- Note: Using standalone FTS table (not external content) because notes table
 -- doesn't store content - content lives in files. We manually sync on index.
@@ -76,6 +186,36 @@ CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts USING fts5(
     tokenize = 'porter unicode61'
 );
 
+-- The words in notes_fts (as stems), with how many notes have each, for
+-- search suggestions
+CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts_vocab USING fts5vocab(notes_fts, 'row');
+
+-- Text recognized in image attachments (OCR), searched separately from
+-- notes. Rows are keyed by the attachment's path and refreshed when its
+-- modification time changes.
+CREATE TABLE IF NOT EXISTS attachment_text (
+    id INTEGER PRIMARY KEY,
+    path TEXT UNIQUE NOT NULL,
+    modified_at INTEGER NOT NULL
+);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS attachment_text_fts USING fts5(
+    content,
+    tokenize = 'porter unicode61'
+);
+
+-- Text extracted from PDF attachments, kept like attachment_text
+CREATE TABLE IF NOT EXISTS pdf_text (
+    id INTEGER PRIMARY KEY,
+    path TEXT UNIQUE NOT NULL,
+    modified_at INTEGER NOT NULL
+);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS pdf_text_fts USING fts5(
+    content,
+    tokenize = 'porter unicode61'
+);
+
 -- Links between notes
 CREATE TABLE IF NOT EXISTS links (
     id INTEGER PRIMARY KEY,
@@ -84,13 +224,22 @@ CREATE TABLE IF NOT EXISTS links (
     target_id INTEGER REFERENCES notes(id) ON DELETE SET NULL,
     display_text TEXT,
     line_number INTEGER,
+    -- The line the link is on, for backlinks
+    context TEXT,
+    -- 'wiki' for [[links]], 'markdown' for [text](path.md)
+    kind TEXT NOT NULL DEFAULT 'wiki',
     UNIQUE(source_id, target_path, line_number)
 );
 
--- Tags
+-- Tags. Nested tags keep their whole path as the name (`project/alpha`),
+-- so a tag's descendants are the names starting with it plus `/`.
 CREATE TABLE IF NOT EXISTS tags (
     id INTEGER PRIMARY KEY,
-    name TEXT UNIQUE NOT NULL COLLATE NOCASE
+    name TEXT UNIQUE NOT NULL COLLATE NOCASE,
+    -- How the tag is shown; mirrored to .chronicle/tags.json
+    color TEXT,
+    icon TEXT,
+    description TEXT
 );
 
 -- Note-tag relationships
@@ -100,12 +249,164 @@ CREATE TABLE IF NOT EXISTS note_tags (
     PRIMARY KEY (note_id, tag_id)
 );
 
+-- Where inline #tags are used, one row per occurrence
+CREATE TABLE IF NOT EXISTS tag_occurrences (
+    note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    line_number INTEGER NOT NULL
+);
+
+-- Flashcards extracted from notes, with their SM-2 schedule
+CREATE TABLE IF NOT EXISTS cards (
+    id INTEGER PRIMARY KEY,
+    note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    card_key TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    front TEXT NOT NULL,
+    back TEXT NOT NULL,
+    line_number INTEGER,
+    ease REAL NOT NULL DEFAULT 2.5,
+    interval_days INTEGER NOT NULL DEFAULT 0,
+    repetitions INTEGER NOT NULL DEFAULT 0,
+    due TEXT,
+    last_reviewed TEXT,
+    UNIQUE(note_id, card_key)
+);
+
+-- Checklist items extracted from notes
+CREATE TABLE IF NOT EXISTS tasks (
+    id INTEGER PRIMARY KEY,
+    note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    line_number INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    completed INTEGER NOT NULL DEFAULT 0,
+    due TEXT,
+    tags TEXT NOT NULL DEFAULT ''
+);
+
+-- Reminders set in notes. `fired` survives re-indexing as long as the
+-- reminder's time and text are unchanged.
+CREATE TABLE IF NOT EXISTS reminders (
+    id INTEGER PRIMARY KEY,
+    note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    remind_at TEXT NOT NULL,
+    text TEXT NOT NULL,
+    line_number INTEGER,
+    fired INTEGER NOT NULL DEFAULT 0,
+    UNIQUE(note_id, remind_at, text)
+);
+
+-- Scalar frontmatter properties, one row per value (list items get a row
+-- each), for smart folder predicates. `number` holds numbers, and booleans
+-- as 1 or 0, for query rollups.
+CREATE TABLE IF NOT EXISTS note_properties (
+    note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    number REAL
+);
+
+-- Problems with notes against their type's schema, for the health report
+CREATE TABLE IF NOT EXISTS note_type_warnings (
+    note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    message TEXT NOT NULL
+);
+
+-- Saved note filters; mirrored to .chronicle/smart_folders.json
+CREATE TABLE IF NOT EXISTS smart_folders (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    filter TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- Named full-text searches with a filter; mirrored to
+-- .chronicle/saved_searches.json
+CREATE TABLE IF NOT EXISTS saved_searches (
+    name TEXT PRIMARY KEY COLLATE NOCASE,
+    query TEXT NOT NULL,
+    filter TEXT NOT NULL,
+    pinned INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL
+);
+
+-- Saved list views (filter, columns, sort, grouping); mirrored to
+-- .chronicle/views.json
+CREATE TABLE IF NOT EXISTS list_views (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    spec TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- When notes were last opened and reviewed, for the review queue
+CREATE TABLE IF NOT EXISTS note_activity (
+    note_id INTEGER PRIMARY KEY REFERENCES notes(id) ON DELETE CASCADE,
+    last_opened_at TEXT,
+    last_reviewed_at TEXT
+);
+
+-- Web links collected from notes, as a reading queue. Not tied to the
+-- notes table: entries and their status outlive the links.
+CREATE TABLE IF NOT EXISTS reading_list (
+    id INTEGER PRIMARY KEY,
+    url TEXT UNIQUE NOT NULL,
+    title TEXT,
+    domain TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'unread',
+    added_from_note TEXT NOT NULL,
+    added_at TEXT NOT NULL,
+    read_at TEXT
+);
+
+-- Saved workspaces; mirrored to `.chronicle/workspaces.json`, which is
+-- reloaded into this table when the vault opens
+CREATE TABLE IF NOT EXISTS workspaces (
+    name TEXT PRIMARY KEY,
+    open_notes TEXT NOT NULL,
+    layout TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- Per-day word counts for writing goals. `baseline` is the note's count
+-- before its first change that day; no foreign key so deleting a note
+-- doesn't rewrite past progress.
+CREATE TABLE IF NOT EXISTS word_counts (
+    path TEXT NOT NULL,
+    date TEXT NOT NULL,
+    baseline INTEGER NOT NULL,
+    word_count INTEGER NOT NULL,
+    PRIMARY KEY (path, date)
+);
+
+-- Zettelkasten IDs, from the id frontmatter property or the filename
+CREATE TABLE IF NOT EXISTS zettel_ids (
+    note_id INTEGER PRIMARY KEY REFERENCES notes(id) ON DELETE CASCADE,
+    zettel_id TEXT NOT NULL
+);
+
+-- Stable note IDs that survive renames; see vault::note_ids
+CREATE TABLE IF NOT EXISTS note_uids (
+    note_id INTEGER PRIMARY KEY REFERENCES notes(id) ON DELETE CASCADE,
+    uid TEXT NOT NULL UNIQUE COLLATE NOCASE
+);
+
 -- Indexes for performance
 CREATE INDEX IF NOT EXISTS idx_links_source ON links(source_id);
 CREATE INDEX IF NOT EXISTS idx_links_target ON links(target_id);
 CREATE INDEX IF NOT EXISTS idx_links_target_path ON links(target_path);
 CREATE INDEX IF NOT EXISTS idx_notes_modified ON notes(modified_at);
 CREATE INDEX IF NOT EXISTS idx_notes_path ON notes(path);
+CREATE INDEX IF NOT EXISTS idx_cards_due ON cards(due);
+CREATE INDEX IF NOT EXISTS idx_tasks_note ON tasks(note_id);
+CREATE INDEX IF NOT EXISTS idx_tag_occurrences_tag ON tag_occurrences(tag_id);
+CREATE INDEX IF NOT EXISTS idx_tasks_due ON tasks(due);
+CREATE INDEX IF NOT EXISTS idx_reminders_at ON reminders(remind_at);
+CREATE INDEX IF NOT EXISTS idx_note_properties_note ON note_properties(note_id);
+CREATE INDEX IF NOT EXISTS idx_note_properties_key ON note_properties(key, value);
+CREATE INDEX IF NOT EXISTS idx_reading_list_domain ON reading_list(domain);
+CREATE INDEX IF NOT EXISTS idx_word_counts_date ON word_counts(date);
+CREATE INDEX IF NOT EXISTS idx_zettel_ids_id ON zettel_ids(zettel_id COLLATE NOCASE);
 "#;
 
 #[cfg(test)]
@@ -129,6 +430,40 @@ mod tests {
         assert_eq!(count, 1);
     }
 
+    #[test]
+    fn test_init_adds_new_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE links (id INTEGER PRIMARY KEY, source_id INTEGER, target_path TEXT,
+                 target_id INTEGER, display_text TEXT, line_number INTEGER);",
+        )
+        .unwrap();
+
+        init_db(&conn).unwrap();
+        init_db(&conn).unwrap();
+        conn.execute("UPDATE links SET context = 'x', kind = 'markdown'", []).unwrap();
+    }
+
+    #[test]
+    fn test_transaction_rolls_back_on_error() {
+        let db = Database::open_memory().unwrap();
+        let insert = |conn: &Connection, path: &str| {
+            conn.execute("INSERT INTO notes (path, title) VALUES (?1, 'T')", params![path])
+        };
+
+        db.transaction(|conn| insert(conn, "kept.md")).unwrap();
+        let failed: Result<()> = db.transaction(|conn| {
+            insert(conn, "dropped.md")?;
+            insert(conn, "kept.md")?;
+            Ok(())
+        });
+        assert!(failed.is_err());
+
+        let count: i64 =
+            db.conn().query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 1);
+    }
+
     #[test]
     fn test_foreign_keys_enabled() {
         let db = Database::open_memory().expect("Failed to create database");
@@ -140,4 +475,41 @@ mod tests {
 
         assert_eq!(fk_enabled, 1);
     }
+
+    #[test]
+    fn test_plaintext_detection_and_removal() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("chronicle.db");
+        Database::open(&path).expect("Failed to create database");
+        assert!(is_plaintext_database(&path).unwrap());
+
+        fs::write(&path, [0x5a; 64]).unwrap();
+        assert!(!is_plaintext_database(&path).unwrap());
+
+        remove_database(&path).unwrap();
+        assert!(!path.exists());
+        assert!(!temp.path().join("chronicle.db-wal").exists());
+    }
+
+    #[test]
+    fn test_conn_recovers_from_poisoning() {
+        let db = Database::open_memory().expect("Failed to create database");
+
+        let result = std::thread::scope(|s| {
+            s.spawn(|| {
+                let _conn = db.conn();
+                panic!("command panicked while holding the connection");
+            })
+            .join()
+        });
+        assert!(result.is_err());
+
+        let conn = db.conn();
+        let one: i32 = conn
+            .query_row("SELECT 1", [], |row| row.get(0))
+            .expect("Failed to query");
+        assert_eq!(one, 1);
+        drop(conn);
+        assert!(!db.conn.is_poisoned());
+    }
 }
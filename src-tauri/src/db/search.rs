@@ -1,6 +1,6 @@
 //! Full-text search operations
 
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, params_from_iter, types::Value, Connection, Result};
 use serde::{Deserialize, Serialize};
 
 /// Search result with snippet
@@ -17,13 +17,13 @@ pub struct SearchResult {
 /// Update FTS index for a note
 pub fn update_fts(conn: &Connection, note_id: i64, title: &str, content: &str) -> Result<()> {
     // Delete existing entry
-    conn.execute("DELETE FROM notes_fts WHERE rowid = ?1", params![note_id])?;
+    conn.prepare_cached("DELETE FROM notes_fts WHERE rowid = ?1")?.execute(params![note_id])?;
 
     // Insert new entry
-    conn.execute(
+    conn.prepare_cached(
         "INSERT INTO notes_fts (rowid, title, content) VALUES (?1, ?2, ?3)",
-        params![note_id, title, content],
-    )?;
+    )?
+    .execute(params![note_id, title, content])?;
 
     Ok(())
 }
@@ -34,49 +34,243 @@ pub fn delete_fts(conn: &Connection, note_id: i64) -> Result<()> {
     Ok(())
 }
 
-/// Search notes using FTS5
-pub fn search_notes(conn: &Connection, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
-    // Escape FTS5 special characters
-    let safe_query = escape_fts_query(query);
+/// A search query split into its parts. `tag:`, `path:` and `title:`
+/// terms scope the search; every other word or "quoted phrase" has to
+/// appear in the note. A note must match every part.
+#[derive(Debug, Default, PartialEq)]
+pub struct SearchQuery {
+    pub text: Vec<String>,
+    /// Tags the note has, or has a nested tag of
+    pub tags: Vec<String>,
+    /// Path prefixes, e.g. a folder
+    pub paths: Vec<String>,
+    pub titles: Vec<String>,
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> Self {
+        let mut parsed = SearchQuery::default();
+        let push = |list: &mut Vec<String>, value: &str| {
+            if !value.is_empty() {
+                list.push(value.to_string());
+            }
+        };
+        for term in split_terms(query) {
+            let scoped = term.split_once(':').map(|(k, v)| (k.to_lowercase(), v.trim_matches('"')));
+            match scoped {
+                Some((key, value)) if key == "tag" => {
+                    push(&mut parsed.tags, value.trim_start_matches('#'))
+                }
+                Some((key, value)) if key == "path" => push(&mut parsed.paths, value),
+                Some((key, value)) if key == "title" => push(&mut parsed.titles, value),
+                _ => push(&mut parsed.text, term.trim_matches('"')),
+            }
+        }
+        parsed
+    }
+
+    fn is_empty(&self) -> bool {
+        self.text.is_empty()
+            && self.tags.is_empty()
+            && self.paths.is_empty()
+            && self.titles.is_empty()
+    }
+
+    /// FTS5 expression for the text and title terms, empty if there are none
+    fn fts_expression(&self) -> String {
+        let text = self.text.iter().map(|t| escape_fts_query(t));
+        let titles = self.titles.iter().map(|t| format!("title : {}", escape_fts_query(t)));
+        text.chain(titles).collect::<Vec<_>>().join(" AND ")
+    }
+}
+
+/// Split a query on whitespace, keeping "quoted phrases" together
+fn split_terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    terms.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        terms.push(current);
+    }
+    terms
+}
+
+/// How to order search results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchSort {
+    /// Best match first; for a query with only scopes, newest first
+    #[default]
+    Relevance,
+    /// Most recently modified first
+    Modified,
+    /// By title, A to Z
+    Title,
+}
+
+/// One page of search results
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    /// Matching notes on every page together
+    pub total: usize,
+    /// Offset to ask for the next page with, `None` on the last page
+    pub next_offset: Option<usize>,
+}
 
-    if safe_query.is_empty() {
+/// Search notes using FTS5. The query can be scoped with `tag:`, `path:`
+/// and `title:` terms, see [`SearchQuery`]; a query with only those lists
+/// the matching notes, most recently modified first, without snippets.
+pub fn search_notes(conn: &Connection, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
+    let parsed = SearchQuery::parse(query);
+    if parsed.is_empty() {
         return Ok(vec![]);
     }
+    run_search(conn, &parsed, SearchSort::Relevance, 0, limit)
+}
 
-    // Get raw query for match counting (without FTS escaping)
-    let raw_query = query.trim().to_lowercase();
-
-    let mut stmt = conn.prepare(
-        r#"
-        SELECT 
-            n.id,
-            n.path,
-            n.title,
-            snippet(notes_fts, 1, '<mark>', '</mark>', '...', 32) as snippet,
-            bm25(notes_fts) as rank,
-            notes_fts.content as content
-        FROM notes_fts
-        JOIN notes n ON notes_fts.rowid = n.id
-        WHERE notes_fts MATCH ?1
-        ORDER BY rank
-        LIMIT ?2
-        "#,
-    )?;
+/// Like [`search_notes`], `limit` results from `offset` on in `sort` order,
+/// with the number of matching notes. Ties are broken by path, so pages
+/// neither overlap nor skip notes while the index stays the same.
+pub fn search_notes_page(
+    conn: &Connection,
+    query: &str,
+    sort: SearchSort,
+    offset: usize,
+    limit: usize,
+) -> Result<SearchPage> {
+    let parsed = SearchQuery::parse(query);
+    if parsed.is_empty() {
+        return Ok(SearchPage { results: Vec::new(), total: 0, next_offset: None });
+    }
+    let results = run_search(conn, &parsed, sort, offset, limit)?;
+
+    let filter = SearchFilter::new(&parsed);
+    let sql = format!("SELECT COUNT(*) FROM {} WHERE {}", filter.from(), filter.condition());
+    let total = conn.query_row(&sql, params_from_iter(filter.values), |row| row.get::<_, i64>(0))?;
+    let total = total as usize;
+
+    let end = offset + results.len();
+    Ok(SearchPage {
+        results,
+        total,
+        next_offset: (end < total).then_some(end),
+    })
+}
+
+/// The notes a parsed query matches, as SQL
+struct SearchFilter {
+    conditions: Vec<String>,
+    values: Vec<Value>,
+    use_fts: bool,
+}
+
+impl SearchFilter {
+    fn new(parsed: &SearchQuery) -> Self {
+        let mut conditions = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+        let bind = |value: String, values: &mut Vec<Value>| {
+            values.push(Value::Text(value));
+            values.len()
+        };
+
+        let fts = parsed.fts_expression();
+        let use_fts = !fts.is_empty();
+        if use_fts {
+            let n = bind(fts, &mut values);
+            conditions.push(format!("notes_fts MATCH ?{}", n));
+        }
+        for tag in &parsed.tags {
+            let n = bind(tag.clone(), &mut values);
+            conditions.push(format!(
+                "EXISTS (SELECT 1 FROM note_tags nt JOIN tags g ON nt.tag_id = g.id \
+                 WHERE nt.note_id = n.id AND (g.name = ?{n} COLLATE NOCASE \
+                 OR lower(substr(g.name, 1, length(?{n}) + 1)) = lower(?{n} || '/')))"
+            ));
+        }
+        for path in &parsed.paths {
+            let n = bind(path.trim_start_matches('/').to_string(), &mut values);
+            conditions.push(format!("lower(substr(n.path, 1, length(?{n}))) = lower(?{n})"));
+        }
+        Self { conditions, values, use_fts }
+    }
+
+    fn from(&self) -> &'static str {
+        if self.use_fts {
+            "notes_fts JOIN notes n ON notes_fts.rowid = n.id"
+        } else {
+            "notes n"
+        }
+    }
+
+    fn condition(&self) -> String {
+        self.conditions.join(" AND ")
+    }
+}
 
+fn run_search(
+    conn: &Connection,
+    parsed: &SearchQuery,
+    sort: SearchSort,
+    offset: usize,
+    limit: usize,
+) -> Result<Vec<SearchResult>> {
+    let filter = SearchFilter::new(parsed);
+    let columns = if filter.use_fts {
+        "n.id, n.path, n.title, \
+         snippet(notes_fts, 1, '<mark>', '</mark>', '...', 32) as snippet, \
+         bm25(notes_fts) as rank, notes_fts.content as content"
+    } else {
+        "n.id, n.path, n.title, NULL, 0.0, NULL"
+    };
+    let order = match sort {
+        SearchSort::Relevance if filter.use_fts => "rank, n.path",
+        SearchSort::Relevance | SearchSort::Modified => "n.modified_at DESC, n.path",
+        SearchSort::Title => "n.title COLLATE NOCASE, n.path",
+    };
+    let mut values = filter.values.clone();
+    values.push(Value::Integer(limit as i64));
+    values.push(Value::Integer(offset as i64));
+    let n = values.len();
+    let sql = format!(
+        "SELECT {} FROM {} WHERE {} ORDER BY {} LIMIT ?{} OFFSET ?{}",
+        columns,
+        filter.from(),
+        filter.condition(),
+        order,
+        n - 1,
+        n
+    );
+
+    // Terms to count matches of, as typed (without FTS escaping)
+    let terms: Vec<String> =
+        parsed.text.iter().chain(&parsed.titles).map(|t| t.to_lowercase()).collect();
+
+    let mut stmt = conn.prepare(&sql)?;
     let mut results = Vec::new();
-    let mut rows = stmt.query(params![safe_query, limit as i64])?;
-    
+    let mut rows = stmt.query(params_from_iter(values))?;
+
     while let Some(row) = rows.next()? {
         let content: String = row.get::<_, Option<String>>(5)?.unwrap_or_default();
         let title: String = row.get(2)?;
-        
+
         // Count occurrences in title and content
         let text = format!("{} {}", title, content).to_lowercase();
-        let match_count = if !raw_query.is_empty() {
-            text.matches(&raw_query).count() as i32
-        } else {
-            0
-        };
+        let match_count: usize = terms.iter().map(|term| text.matches(term.as_str()).count()).sum();
 
         results.push(SearchResult {
             id: row.get(0)?,
@@ -84,15 +278,165 @@ pub fn search_notes(conn: &Connection, query: &str, limit: usize) -> Result<Vec<
             title,
             snippet: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
             rank: row.get(4)?,
-            match_count: match_count.max(1), // At least 1 if it matched
+            match_count: (match_count as i32).max(1), // At least 1 if it matched
         });
     }
 
     Ok(results)
 }
 
+/// Most typos a word can have and still be corrected
+fn max_typos(word: &[char]) -> usize {
+    if word.len() <= 4 {
+        1
+    } else {
+        2
+    }
+}
+
+/// The index holds stems ("meet" for "meeting"), so this many characters
+/// past a stem are free when comparing a typed word with it
+const STEM_SUFFIX: usize = 3;
+
+/// A word from the search index
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchSuggestion {
+    /// The word's stem, as the index holds it; searching for it finds the
+    /// word in all its forms
+    pub term: String,
+    /// How many notes contain it
+    pub note_count: i64,
+}
+
+/// Words in the index starting with `prefix`, in the most notes first, to
+/// complete a word being typed. If none do, `prefix` is taken to be
+/// misspelt and the words whose start is closest to it are suggested.
+pub fn suggest_search_terms(
+    conn: &Connection,
+    prefix: &str,
+    limit: usize,
+) -> Result<Vec<SearchSuggestion>> {
+    let prefix = prefix.trim().to_lowercase();
+    if prefix.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn.prepare_cached(
+        "SELECT term, doc FROM notes_fts_vocab WHERE term >= ?1 AND term < ?2
+         ORDER BY doc DESC, term LIMIT ?3",
+    )?;
+    let completions = stmt
+        .query_map(params![prefix, format!("{}\u{10FFFF}", prefix), limit as i64], |row| {
+            Ok(SearchSuggestion { term: row.get(0)?, note_count: row.get(1)? })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    if !completions.is_empty() {
+        return Ok(completions);
+    }
+
+    let prefix: Vec<char> = prefix.chars().collect();
+    let mut close = close_terms(conn, &prefix, |term| {
+        // The rest of the word is still to be typed
+        typo_distance(&prefix, term, usize::MAX)
+    })?;
+    close.truncate(limit);
+    Ok(close.into_iter().map(|(_, suggestion)| suggestion).collect())
+}
+
+/// `query` with every word no note contains replaced by the closest word in
+/// the index, for when a search finds nothing. `None` if no word is missing
+/// or none of the missing ones has a close match. Phrases and `tag:`-style
+/// scopes are left as they are.
+pub fn did_you_mean(conn: &Connection, query: &str) -> Result<Option<String>> {
+    let mut corrected = false;
+    let mut terms = split_terms(query);
+    for term in &mut terms {
+        let is_word = term.chars().all(char::is_alphanumeric);
+        if !is_word || is_indexed(conn, term)? {
+            continue;
+        }
+        let word: Vec<char> = term.to_lowercase().chars().collect();
+        let best = close_terms(conn, &word, |stem| {
+            let free = if stem.len() >= 3 { STEM_SUFFIX } else { 0 };
+            typo_distance(stem, &word, free)
+        })?;
+        if let Some((_, suggestion)) = best.into_iter().next() {
+            *term = suggestion.term;
+            corrected = true;
+        }
+    }
+    Ok(corrected.then(|| terms.join(" ")))
+}
+
+/// Whether any note contains `word`, in any form
+fn is_indexed(conn: &Connection, word: &str) -> Result<bool> {
+    let mut stmt = conn.prepare_cached("SELECT 1 FROM notes_fts WHERE notes_fts MATCH ?1 LIMIT 1")?;
+    stmt.exists(params![escape_fts_query(word)])
+}
+
+/// Words in the index within `max_typos` of `word` by `distance`, closest
+/// and then most common first, with their distance. Only words with the
+/// same first letter are compared, as typos there are rare.
+fn close_terms(
+    conn: &Connection,
+    word: &[char],
+    distance: impl Fn(&[char]) -> usize,
+) -> Result<Vec<(usize, SearchSuggestion)>> {
+    let Some(&first) = word.first() else {
+        return Ok(Vec::new());
+    };
+    let next = char::from_u32(first as u32 + 1).unwrap_or(char::MAX);
+    let mut stmt = conn.prepare_cached(
+        "SELECT term, doc FROM notes_fts_vocab WHERE term >= ?1 AND term < ?2",
+    )?;
+    let rows = stmt.query_map(params![first.to_string(), next.to_string()], |row| {
+        Ok(SearchSuggestion { term: row.get(0)?, note_count: row.get(1)? })
+    })?;
+
+    let mut close = Vec::new();
+    let mut term = Vec::new();
+    for suggestion in rows {
+        let suggestion = suggestion?;
+        term.clear();
+        term.extend(suggestion.term.chars());
+        let distance = distance(&term);
+        if distance <= max_typos(word) {
+            close.push((distance, suggestion));
+        }
+    }
+    close.sort_by(|(a, x), (b, y)| {
+        a.cmp(b).then(y.note_count.cmp(&x.note_count)).then_with(|| x.term.cmp(&y.term))
+    });
+    Ok(close)
+}
+
+/// Edits (insertions, deletions, substitutions and swaps of neighbours)
+/// turning `a` into the start of `b`, counting the characters of `b` left
+/// after that start beyond the first `free_tail` as edits too
+fn typo_distance(a: &[char], b: &[char], free_tail: usize) -> usize {
+    // Rows of the edit distance table between prefixes of `a` and `b`
+    let mut before: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 0..a.len() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for j in 0..b.len() {
+            let substitution = previous[j] + usize::from(a[i] != b[j]);
+            row[j + 1] = substitution.min(previous[j + 1] + 1).min(row[j] + 1);
+            if i > 0 && j > 0 && a[i] == b[j - 1] && a[i - 1] == b[j] {
+                row[j + 1] = row[j + 1].min(before[j - 1] + 1);
+            }
+        }
+        before = std::mem::replace(&mut previous, row);
+    }
+    previous
+        .iter()
+        .enumerate()
+        .map(|(j, edits)| edits + (b.len() - j).saturating_sub(free_tail))
+        .min()
+        .unwrap_or(0)
+}
+
 /// Escape special FTS5 characters in query
-fn escape_fts_query(query: &str) -> String {
+pub(crate) fn escape_fts_query(query: &str) -> String {
     // For simple queries, wrap terms in quotes
     // This handles most special characters
     let trimmed = query.trim();
@@ -110,7 +454,7 @@ fn escape_fts_query(query: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::db::{notes::upsert_note, schema::Database};
+    use crate::db::{notes::upsert_note, schema::Database, tags::set_note_tags};
 
     #[test]
     fn test_update_and_search_fts() {
@@ -140,6 +484,122 @@ mod tests {
         assert!(results.is_empty());
     }
 
+    #[test]
+    fn test_parse_search_query() {
+        let parsed =
+            SearchQuery::parse(r#"tag:#Rust path:projects/ title:"road map" async "error handling""#);
+        assert_eq!(parsed.tags, vec!["Rust"]);
+        assert_eq!(parsed.paths, vec!["projects/"]);
+        assert_eq!(parsed.titles, vec!["road map"]);
+        assert_eq!(parsed.text, vec!["async", "error handling"]);
+
+        // Unknown prefixes are plain text
+        assert_eq!(SearchQuery::parse("http://example.com").text, vec!["http://example.com"]);
+    }
+
+    #[test]
+    fn test_scoped_search() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let note = |path: &str, title: &str, content: &str, tags: &[&str]| {
+            let id = upsert_note(&conn, path, title, None, Some("2024-01-01"), "x", 10).unwrap();
+            update_fts(&conn, id, title, content).unwrap();
+            let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+            set_note_tags(&conn, id, &tags).unwrap();
+        };
+        note("projects/alpha.md", "Alpha plan", "Rust borrow checker notes", &["rust/async"]);
+        note("projects/beta.md", "Beta", "Python notes on the checker", &["python"]);
+        note("journal.md", "Journal", "Rust checker in the journal", &["rust"]);
+
+        let paths = |query: &str| -> Vec<String> {
+            let mut paths: Vec<String> =
+                search_notes(&conn, query, 10).unwrap().into_iter().map(|r| r.path).collect();
+            paths.sort();
+            paths
+        };
+        // Words no longer have to be next to each other
+        assert_eq!(paths("rust checker"), vec!["journal.md", "projects/alpha.md"]);
+        assert_eq!(paths("tag:rust checker"), vec!["journal.md", "projects/alpha.md"]);
+        assert_eq!(paths("path:projects/ checker"), vec!["projects/alpha.md", "projects/beta.md"]);
+        assert_eq!(paths("path:Projects/ tag:rust"), vec!["projects/alpha.md"]);
+        assert_eq!(paths("title:plan"), vec!["projects/alpha.md"]);
+        assert!(paths("title:notes").is_empty());
+        assert!(paths("tag:").is_empty());
+    }
+
+    #[test]
+    fn test_search_pages() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let notes = [
+            ("a.md", "Cherry", "2024-01-03", "rust rust rust"),
+            ("b.md", "apple", "2024-01-01", "rust"),
+            ("c.md", "Banana", "2024-01-02", "rust rust"),
+            ("d.md", "Durian", "2024-01-04", "go"),
+        ];
+        for (path, title, modified, content) in notes {
+            let id = upsert_note(&conn, path, title, None, Some(modified), "x", 1).unwrap();
+            update_fts(&conn, id, title, content).unwrap();
+        }
+
+        let page = |sort, offset| search_notes_page(&conn, "rust", sort, offset, 2).unwrap();
+        let paths = |page: &SearchPage| -> Vec<String> {
+            page.results.iter().map(|r| r.path.clone()).collect()
+        };
+
+        let first = page(SearchSort::Title, 0);
+        assert_eq!(paths(&first), vec!["b.md", "c.md"]);
+        assert_eq!((first.total, first.next_offset), (3, Some(2)));
+        let last = page(SearchSort::Title, 2);
+        assert_eq!(paths(&last), vec!["a.md"]);
+        assert_eq!(last.next_offset, None);
+
+        assert_eq!(paths(&page(SearchSort::Modified, 0)), vec!["a.md", "c.md"]);
+        assert_eq!(paths(&page(SearchSort::Relevance, 0))[0], "a.md");
+
+        let scoped = search_notes_page(&conn, "path:d", SearchSort::Relevance, 0, 10).unwrap();
+        assert_eq!((scoped.total, scoped.results.len()), (1, 1));
+        assert_eq!(search_notes_page(&conn, "", SearchSort::Title, 0, 10).unwrap().total, 0);
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let contents = ["Meeting about the roadmap", "Roadmap review", "Road trip planning"];
+        for (i, content) in contents.iter().enumerate() {
+            let path = format!("{}.md", i);
+            let id = upsert_note(&conn, &path, "Note", None, None, "x", 3).unwrap();
+            update_fts(&conn, id, "Note", content).unwrap();
+        }
+
+        let terms = |prefix: &str| -> Vec<String> {
+            suggest_search_terms(&conn, prefix, 5).unwrap().into_iter().map(|s| s.term).collect()
+        };
+        // In the most notes first
+        assert_eq!(terms("Road"), vec!["roadmap", "road"]);
+        assert_eq!(terms("plan"), vec!["plan"]);
+        // A typo in the prefix
+        assert_eq!(terms("raodm")[0], "roadmap");
+        assert!(terms("xyz").is_empty());
+
+        assert_eq!(
+            did_you_mean(&conn, "meetnig raodmap tag:work").unwrap().as_deref(),
+            Some("meet roadmap tag:work")
+        );
+        assert_eq!(did_you_mean(&conn, "meetings roadmap").unwrap(), None);
+        assert_eq!(did_you_mean(&conn, "zebra").unwrap(), None);
+    }
+
+    #[test]
+    fn test_typo_distance() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(typo_distance(&chars("road"), &chars("raod"), 0), 1);
+        assert_eq!(typo_distance(&chars("rust"), &chars("rusty"), 0), 1);
+        assert_eq!(typo_distance(&chars("meet"), &chars("meeting"), STEM_SUFFIX), 0);
+        assert_eq!(typo_distance(&chars("raodm"), &chars("roadmap"), usize::MAX), 1);
+    }
+
     #[test]
     fn test_escape_fts_query() {
         assert_eq!(escape_fts_query("hello"), "\"hello\"");
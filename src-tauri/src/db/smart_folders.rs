@@ -0,0 +1,361 @@
+//! Smart folder database operations
+
+use rusqlite::{params, params_from_iter, types::Value, Connection, OptionalExtension, Result, Row};
+
+use crate::db::notes::NoteMeta;
+use crate::db::search::escape_fts_query;
+use crate::vault::{PropertyOp, SmartFilter, SmartFolder};
+
+const TAG_MATCH: &str = "SELECT 1 FROM note_tags nt JOIN tags g ON nt.tag_id = g.id \
+    WHERE nt.note_id = n.id AND g.name = ?{} COLLATE NOCASE";
+
+fn smart_folder_from_row(row: &Row) -> Result<SmartFolder> {
+    let filter: String = row.get(2)?;
+    Ok(SmartFolder {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        filter: serde_json::from_str(&filter).unwrap_or_default(),
+        updated_at: row.get(3)?,
+    })
+}
+
+/// Create a smart folder, or replace the one with the same ID. Returns the
+/// folder with its ID filled in.
+pub fn save_smart_folder(
+    conn: &Connection,
+    id: Option<i64>,
+    name: &str,
+    filter: &SmartFilter,
+    updated_at: &str,
+) -> Result<SmartFolder> {
+    let id = conn.query_row(
+        r#"
+        INSERT INTO smart_folders (id, name, filter, updated_at)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name,
+            filter = excluded.filter,
+            updated_at = excluded.updated_at
+        RETURNING id
+        "#,
+        params![
+            id,
+            name,
+            serde_json::to_string(filter).unwrap_or_default(),
+            updated_at
+        ],
+        |row| row.get(0),
+    )?;
+    Ok(SmartFolder {
+        id,
+        name: name.to_string(),
+        filter: filter.clone(),
+        updated_at: updated_at.to_string(),
+    })
+}
+
+/// A smart folder by ID
+pub fn get_smart_folder(conn: &Connection, id: i64) -> Result<Option<SmartFolder>> {
+    conn.query_row(
+        "SELECT id, name, filter, updated_at FROM smart_folders WHERE id = ?1",
+        params![id],
+        smart_folder_from_row,
+    )
+    .optional()
+}
+
+/// All smart folders, by name
+pub fn list_smart_folders(conn: &Connection) -> Result<Vec<SmartFolder>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, filter, updated_at FROM smart_folders ORDER BY name COLLATE NOCASE, id",
+    )?;
+    let rows = stmt.query_map([], smart_folder_from_row)?;
+    rows.collect()
+}
+
+/// Delete a smart folder. Returns whether it existed.
+pub fn delete_smart_folder(conn: &Connection, id: i64) -> Result<bool> {
+    let deleted = conn.execute("DELETE FROM smart_folders WHERE id = ?1", params![id])?;
+    Ok(deleted > 0)
+}
+
+/// Replace every smart folder, e.g. with the ones from the vault's file
+pub fn replace_smart_folders(conn: &Connection, folders: &[SmartFolder]) -> Result<()> {
+    conn.execute("DELETE FROM smart_folders", [])?;
+    for folder in folders {
+        save_smart_folder(conn, Some(folder.id), &folder.name, &folder.filter, &folder.updated_at)?;
+    }
+    Ok(())
+}
+
+/// Notes matching a filter, by title
+pub fn notes_matching(conn: &Connection, filter: &SmartFilter) -> Result<Vec<NoteMeta>> {
+    let (where_clause, values) = filter_clause(filter);
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT n.id, n.path, n.title, n.created_at, n.modified_at, n.word_count
+        FROM notes n
+        {}
+        ORDER BY n.title COLLATE NOCASE, n.path
+        "#,
+        where_clause
+    ))?;
+    let rows = stmt.query_map(params_from_iter(values), |row| {
+        Ok(NoteMeta {
+            id: row.get(0)?,
+            path: row.get(1)?,
+            title: row.get(2)?,
+            created_at: row.get(3)?,
+            modified_at: row.get(4)?,
+            word_count: row.get(5)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Resolved links between notes matching a filter, as (source, target)
+/// paths, each pair once
+pub fn links_matching(conn: &Connection, filter: &SmartFilter) -> Result<Vec<(String, String)>> {
+    let (where_clause, values) = filter_clause(filter);
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        WITH kept AS (SELECT n.id, n.path FROM notes n {})
+        SELECT DISTINCT s.path, t.path
+        FROM links l
+        JOIN kept s ON s.id = l.source_id
+        JOIN kept t ON t.id = l.target_id
+        WHERE s.id <> t.id
+        ORDER BY s.path, t.path
+        "#,
+        where_clause
+    ))?;
+    let rows = stmt.query_map(params_from_iter(values), |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// A `WHERE` clause over notes aliased `n` selecting those matching a
+/// filter, and its parameters. Empty if the filter has no criteria.
+fn filter_clause(filter: &SmartFilter) -> (String, Vec<Value>) {
+    let mut conditions = Vec::new();
+    let mut values: Vec<Value> = Vec::new();
+    let bind = |value: &str, values: &mut Vec<Value>| {
+        values.push(Value::Text(value.to_string()));
+        values.len()
+    };
+
+    if let Some(query) = filter.query.as_deref().map(escape_fts_query) {
+        if !query.is_empty() {
+            let n = bind(&query, &mut values);
+            conditions.push(format!(
+                "n.id IN (SELECT rowid FROM notes_fts WHERE notes_fts MATCH ?{})",
+                n
+            ));
+        }
+    }
+    for tag in &filter.all_tags {
+        let n = bind(tag.trim_start_matches('#'), &mut values);
+        conditions.push(format!("EXISTS ({})", TAG_MATCH.replace("{}", &n.to_string())));
+    }
+    if !filter.any_tags.is_empty() {
+        let any: Vec<String> = filter
+            .any_tags
+            .iter()
+            .map(|tag| {
+                let n = bind(tag.trim_start_matches('#'), &mut values);
+                format!("EXISTS ({})", TAG_MATCH.replace("{}", &n.to_string()))
+            })
+            .collect();
+        conditions.push(format!("({})", any.join(" OR ")));
+    }
+    for tag in &filter.without_tags {
+        let n = bind(tag.trim_start_matches('#'), &mut values);
+        conditions.push(format!("NOT EXISTS ({})", TAG_MATCH.replace("{}", &n.to_string())));
+    }
+    if filter.untagged {
+        let tagged = "SELECT 1 FROM note_tags nt WHERE nt.note_id = n.id";
+        conditions.push(format!("NOT EXISTS ({})", tagged));
+    }
+    if !filter.paths.is_empty() {
+        let globs: Vec<String> = filter
+            .paths
+            .iter()
+            .map(|glob| format!("n.path GLOB ?{}", bind(glob.trim_start_matches('/'), &mut values)))
+            .collect();
+        conditions.push(format!("({})", globs.join(" OR ")));
+    }
+    for predicate in &filter.properties {
+        let key = bind(&predicate.key, &mut values);
+        let value = predicate.value.as_deref().unwrap_or_default();
+        let property = |test: &str| {
+            format!(
+                "SELECT 1 FROM note_properties p WHERE p.note_id = n.id AND p.key = ?{}{}",
+                key, test
+            )
+        };
+        conditions.push(match predicate.op {
+            PropertyOp::Equals => format!(
+                "EXISTS ({})",
+                property(&format!(" AND p.value = ?{} COLLATE NOCASE", bind(value, &mut values)))
+            ),
+            PropertyOp::NotEquals => format!(
+                "NOT EXISTS ({})",
+                property(&format!(" AND p.value = ?{} COLLATE NOCASE", bind(value, &mut values)))
+            ),
+            PropertyOp::Contains => {
+                let n = bind(value, &mut values);
+                format!("EXISTS ({})", property(&format!(" AND instr(lower(p.value), lower(?{})) > 0", n)))
+            }
+            PropertyOp::Exists => format!("EXISTS ({})", property("")),
+            PropertyOp::Missing => format!("NOT EXISTS ({})", property("")),
+        });
+    }
+    let dates = [
+        ("n.created_at", ">=", &filter.created_after),
+        ("n.created_at", "<=", &filter.created_before),
+        ("n.modified_at", ">=", &filter.modified_after),
+        ("n.modified_at", "<=", &filter.modified_before),
+    ];
+    for (column, op, date) in dates {
+        if let Some(date) = date {
+            let n = bind(date, &mut values);
+            conditions.push(format!("substr({}, 1, 10) {} ?{}", column, op, n));
+        }
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+    (where_clause, values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{notes::upsert_note, properties::replace_note_properties, tags::set_note_tags, Database};
+    use crate::db::links::replace_links;
+    use crate::vault::{LinkKind, PropertyPredicate};
+
+    fn note(conn: &Connection, path: &str, modified: &str, tags: &[&str], props: &[(&str, &str)]) {
+        let id = upsert_note(conn, path, path, None, Some(modified), "h", 0).unwrap();
+        let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+        set_note_tags(conn, id, &tags).unwrap();
+        let props: Vec<(String, String)> =
+            props.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        replace_note_properties(conn, id, &props).unwrap();
+    }
+
+    fn paths(conn: &Connection, filter: &SmartFilter) -> Vec<String> {
+        notes_matching(conn, filter).unwrap().into_iter().map(|n| n.path).collect()
+    }
+
+    #[test]
+    fn test_notes_matching() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        note(&conn, "projects/site.md", "2025-01-10T09:00:00Z", &["project"], &[("status", "Open")]);
+        note(&conn, "projects/old/book.md", "2024-06-01T09:00:00Z", &["project", "writing"], &[("status", "done")]);
+        note(&conn, "inbox.md", "2025-01-12T09:00:00Z", &["writing"], &[]);
+
+        let open_projects = SmartFilter {
+            all_tags: vec!["#project".to_string()],
+            properties: vec![PropertyPredicate {
+                key: "status".to_string(),
+                op: PropertyOp::Equals,
+                value: Some("open".to_string()),
+            }],
+            ..Default::default()
+        };
+        assert_eq!(paths(&conn, &open_projects), vec!["projects/site.md"]);
+
+        let filter = SmartFilter {
+            paths: vec!["projects/*".to_string()],
+            without_tags: vec!["writing".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(paths(&conn, &filter), vec!["projects/site.md"]);
+
+        let filter = SmartFilter {
+            any_tags: vec!["writing".to_string(), "missing".to_string()],
+            modified_after: Some("2025-01-01".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(paths(&conn, &filter), vec!["inbox.md"]);
+
+        let filter = SmartFilter {
+            properties: vec![PropertyPredicate {
+                key: "status".to_string(),
+                op: PropertyOp::Missing,
+                value: None,
+            }],
+            ..Default::default()
+        };
+        assert_eq!(paths(&conn, &filter), vec!["inbox.md"]);
+        assert_eq!(paths(&conn, &SmartFilter::default()).len(), 3);
+
+        note(&conn, "scratch.md", "2025-01-13T09:00:00Z", &[], &[]);
+        let untagged = SmartFilter {
+            untagged: true,
+            ..Default::default()
+        };
+        assert_eq!(paths(&conn, &untagged), vec!["scratch.md"]);
+
+        let inbox = crate::db::notes::get_note_by_path(&conn, "inbox.md").unwrap().unwrap();
+        crate::db::search::update_fts(&conn, inbox.id, "Inbox", "Sourdough starter notes").unwrap();
+        let filter = SmartFilter {
+            query: Some("sourdough".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(paths(&conn, &filter), vec!["inbox.md"]);
+    }
+
+    #[test]
+    fn test_links_matching() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        note(&conn, "work/a.md", "2025-01-10T09:00:00Z", &["web"], &[]);
+        note(&conn, "work/b.md", "2025-01-10T09:00:00Z", &[], &[]);
+        note(&conn, "home/c.md", "2025-01-10T09:00:00Z", &["web"], &[]);
+        let id = |path: &str| crate::db::notes::get_note_by_path(&conn, path).unwrap().unwrap().id;
+        let link = |target: &str, line| (target.to_string(), None, Some(line), LinkKind::Wiki);
+        replace_links(&conn, id("work/a.md"), &[link("work/b", 1), link("home/c", 2)]).unwrap();
+        replace_links(&conn, id("home/c.md"), &[link("work/a", 1), link("work/a", 2)]).unwrap();
+
+        let pair = |s: &str, t: &str| (s.to_string(), t.to_string());
+        let work = SmartFilter {
+            paths: vec!["work/*".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(links_matching(&conn, &work).unwrap(), vec![pair("work/a.md", "work/b.md")]);
+        let web = SmartFilter {
+            any_tags: vec!["web".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            links_matching(&conn, &web).unwrap(),
+            vec![pair("home/c.md", "work/a.md"), pair("work/a.md", "home/c.md")]
+        );
+    }
+
+    #[test]
+    fn test_smart_folder_crud() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let filter = SmartFilter {
+            all_tags: vec!["project".to_string()],
+            ..Default::default()
+        };
+
+        let saved = save_smart_folder(&conn, None, "Projects", &filter, "2025-02-01T09:00:00Z").unwrap();
+        assert_eq!(get_smart_folder(&conn, saved.id).unwrap(), Some(saved.clone()));
+
+        let renamed = save_smart_folder(&conn, Some(saved.id), "Open projects", &filter, "2025-02-02T09:00:00Z")
+            .unwrap();
+        assert_eq!(renamed.id, saved.id);
+        assert_eq!(list_smart_folders(&conn).unwrap(), vec![renamed]);
+
+        assert!(delete_smart_folder(&conn, saved.id).unwrap());
+        assert!(!delete_smart_folder(&conn, saved.id).unwrap());
+    }
+}
@@ -1,7 +1,10 @@
 //! Tag database operations
 
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, Connection, OptionalExtension, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::vault::{ExtractedTag, StyledTag, TagMeta};
 
 /// Tag with note count
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,22 +12,22 @@ pub struct TagInfo {
     pub id: i64,
     pub name: String,
     pub count: i32,
+    #[serde(flatten)]
+    pub meta: TagMeta,
 }
 
 /// Get or create a tag, return its ID
 pub fn get_or_create_tag(conn: &Connection, name: &str) -> Result<i64> {
     // Try to get existing tag
-    let existing: Result<i64> = conn.query_row(
-        "SELECT id FROM tags WHERE name = ?1 COLLATE NOCASE",
-        params![name],
-        |row| row.get(0),
-    );
+    let existing: Result<i64> = conn
+        .prepare_cached("SELECT id FROM tags WHERE name = ?1 COLLATE NOCASE")?
+        .query_row(params![name], |row| row.get(0));
 
     match existing {
         Ok(id) => Ok(id),
         Err(_) => {
             // Create new tag
-            conn.execute("INSERT INTO tags (name) VALUES (?1)", params![name])?;
+            conn.prepare_cached("INSERT INTO tags (name) VALUES (?1)")?.execute(params![name])?;
             Ok(conn.last_insert_rowid())
         }
     }
@@ -33,20 +36,116 @@ pub fn get_or_create_tag(conn: &Connection, name: &str) -> Result<i64> {
 /// Set tags for a note (replaces existing)
 pub fn set_note_tags(conn: &Connection, note_id: i64, tags: &[String]) -> Result<()> {
     // Remove existing tags
-    conn.execute("DELETE FROM note_tags WHERE note_id = ?1", params![note_id])?;
+    conn.prepare_cached("DELETE FROM note_tags WHERE note_id = ?1")?.execute(params![note_id])?;
 
     // Add new tags
     for tag_name in tags {
         let tag_id = get_or_create_tag(conn, tag_name)?;
-        conn.execute(
+        conn.prepare_cached(
             "INSERT OR IGNORE INTO note_tags (note_id, tag_id) VALUES (?1, ?2)",
-            params![note_id, tag_id],
-        )?;
+        )?
+        .execute(params![note_id, tag_id])?;
+    }
+
+    Ok(())
+}
+
+/// Record where a note uses inline tags (replaces existing). The tags
+/// themselves are set with `set_note_tags`.
+pub fn replace_tag_occurrences(
+    conn: &Connection,
+    note_id: i64,
+    tags: &[ExtractedTag],
+) -> Result<()> {
+    conn.prepare_cached("DELETE FROM tag_occurrences WHERE note_id = ?1")?
+        .execute(params![note_id])?;
+
+    for tag in tags {
+        let tag_id = get_or_create_tag(conn, &tag.name)?;
+        conn.prepare_cached(
+            "INSERT INTO tag_occurrences (note_id, tag_id, line_number) VALUES (?1, ?2, ?3)",
+        )?
+        .execute(params![note_id, tag_id, tag.line_number as i64])?;
     }
 
     Ok(())
 }
 
+/// Where an inline tag is used
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagOccurrence {
+    pub path: String,
+    pub title: String,
+    pub line_number: i64,
+}
+
+/// Every inline use of a tag, by note path and line
+pub fn get_tag_occurrences(conn: &Connection, tag_name: &str) -> Result<Vec<TagOccurrence>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT n.path, n.title, o.line_number
+        FROM tag_occurrences o
+        JOIN tags t ON o.tag_id = t.id
+        JOIN notes n ON o.note_id = n.id
+        WHERE t.name = ?1 COLLATE NOCASE
+        ORDER BY n.path, o.line_number
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![tag_name], |row| {
+        Ok(TagOccurrence {
+            path: row.get(0)?,
+            title: row.get(1)?,
+            line_number: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Rename a tag and the tags nested under it (`old/sub` becomes
+/// `new/sub`). A tag renamed to one that already exists is merged into it.
+pub fn rename_tag(conn: &Connection, old: &str, new: &str) -> Result<()> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT id, name FROM tags
+        WHERE name = ?1 COLLATE NOCASE
+           OR lower(substr(name, 1, length(?1) + 1)) = lower(?1 || '/')
+        "#,
+    )?;
+    let tags: Vec<(i64, String)> = stmt
+        .query_map(params![old], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_>>()?;
+
+    for (id, name) in tags {
+        let renamed = format!("{}{}", new, &name[old.len()..]);
+        let existing: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM tags WHERE name = ?1 COLLATE NOCASE AND id != ?2",
+                params![renamed, id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match existing {
+            Some(target) => {
+                conn.execute(
+                    "INSERT OR IGNORE INTO note_tags (note_id, tag_id)
+                     SELECT note_id, ?2 FROM note_tags WHERE tag_id = ?1",
+                    params![id, target],
+                )?;
+                conn.execute(
+                    "UPDATE tag_occurrences SET tag_id = ?2 WHERE tag_id = ?1",
+                    params![id, target],
+                )?;
+                conn.execute("DELETE FROM tags WHERE id = ?1", params![id])?;
+            }
+            None => {
+                conn.execute("UPDATE tags SET name = ?2 WHERE id = ?1", params![id, renamed])?;
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Get tags for a note
 pub fn get_note_tags(conn: &Connection, note_id: i64) -> Result<Vec<String>> {
     let mut stmt = conn.prepare(
@@ -63,11 +162,26 @@ pub fn get_note_tags(conn: &Connection, note_id: i64) -> Result<Vec<String>> {
     rows.collect()
 }
 
+/// Every note's tags as (note path, tag name), by path
+pub fn note_tag_pairs(conn: &Connection) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT n.path, t.name
+        FROM note_tags nt
+        JOIN notes n ON n.id = nt.note_id
+        JOIN tags t ON t.id = nt.tag_id
+        ORDER BY n.path, t.name
+        "#,
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
 /// List all tags with counts
 pub fn list_tags(conn: &Connection) -> Result<Vec<TagInfo>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT t.id, t.name, COUNT(nt.note_id) as count
+        SELECT t.id, t.name, COUNT(nt.note_id) as count, t.color, t.icon, t.description
         FROM tags t
         LEFT JOIN note_tags nt ON t.id = nt.tag_id
         GROUP BY t.id, t.name
@@ -81,20 +195,66 @@ pub fn list_tags(conn: &Connection) -> Result<Vec<TagInfo>> {
             id: row.get(0)?,
             name: row.get(1)?,
             count: row.get(2)?,
+            meta: TagMeta {
+                color: row.get(3)?,
+                icon: row.get(4)?,
+                description: row.get(5)?,
+            },
         })
     })?;
 
     rows.collect()
 }
 
-/// Get notes with a specific tag
+/// Set how a tag is shown, adding the tag if it isn't used yet
+pub fn set_tag_meta(conn: &Connection, name: &str, meta: &TagMeta) -> Result<()> {
+    let id = get_or_create_tag(conn, name)?;
+    conn.execute(
+        "UPDATE tags SET color = ?2, icon = ?3, description = ?4 WHERE id = ?1",
+        params![id, meta.color, meta.icon, meta.description],
+    )?;
+    Ok(())
+}
+
+/// Every tag with a color, icon or description, by name
+pub fn list_tag_meta(conn: &Connection) -> Result<Vec<StyledTag>> {
+    let mut stmt = conn.prepare(
+        "SELECT name, color, icon, description FROM tags
+         WHERE color IS NOT NULL OR icon IS NOT NULL OR description IS NOT NULL
+         ORDER BY name",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(StyledTag {
+            name: row.get(0)?,
+            meta: TagMeta {
+                color: row.get(1)?,
+                icon: row.get(2)?,
+                description: row.get(3)?,
+            },
+        })
+    })?;
+    rows.collect()
+}
+
+/// Replace how every tag is shown, e.g. with the styles from the vault's
+/// file
+pub fn replace_tag_meta(conn: &Connection, tags: &[StyledTag]) -> Result<()> {
+    conn.execute("UPDATE tags SET color = NULL, icon = NULL, description = NULL", [])?;
+    for tag in tags {
+        set_tag_meta(conn, &tag.name, &tag.meta)?;
+    }
+    Ok(())
+}
+
+/// Get notes with a specific tag or one of its nested tags (`tag/sub`)
 pub fn get_notes_by_tag(conn: &Connection, tag_name: &str) -> Result<Vec<i64>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT nt.note_id
+        SELECT DISTINCT nt.note_id
         FROM note_tags nt
         JOIN tags t ON nt.tag_id = t.id
         WHERE t.name = ?1 COLLATE NOCASE
+           OR lower(substr(t.name, 1, length(?1) + 1)) = lower(?1 || '/')
         "#,
     )?;
 
@@ -102,6 +262,93 @@ pub fn get_notes_by_tag(conn: &Connection, tag_name: &str) -> Result<Vec<i64>> {
     rows.collect()
 }
 
+/// Notes tagged `tag` or one of its nested tags (`tag/sub`), as
+/// `(path, title, tag)` with a row per matching tag
+pub fn get_notes_in_tag_tree(
+    conn: &Connection,
+    tag: &str,
+) -> Result<Vec<(String, String, String)>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT n.path, n.title, t.name
+        FROM notes n
+        JOIN note_tags nt ON nt.note_id = n.id
+        JOIN tags t ON t.id = nt.tag_id
+        WHERE t.name = ?1 COLLATE NOCASE
+           OR lower(substr(t.name, 1, length(?1) + 1)) = lower(?1 || '/')
+        ORDER BY n.path, t.name
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![tag], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+    rows.collect()
+}
+
+/// A level of the tag hierarchy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagTreeNode {
+    /// Last part of the tag (`alpha` for `project/alpha`)
+    pub name: String,
+    /// The full tag
+    pub path: String,
+    /// Notes with this tag or one nested under it
+    pub count: i32,
+    pub children: Vec<TagTreeNode>,
+}
+
+/// Tags in use, nested at `/`. Levels without a tag of their own (`project`
+/// when only `project/alpha` is used) are filled in.
+pub fn tag_tree(conn: &Connection) -> Result<Vec<TagTreeNode>> {
+    #[derive(Default)]
+    struct Level {
+        path: String,
+        notes: HashSet<i64>,
+        children: BTreeMap<String, Level>,
+    }
+
+    fn build(children: BTreeMap<String, Level>) -> Vec<TagTreeNode> {
+        children
+            .into_values()
+            .map(|level| TagTreeNode {
+                name: level.path.rsplit('/').next().unwrap_or_default().to_string(),
+                count: level.notes.len() as i32,
+                children: build(level.children),
+                path: level.path,
+            })
+            .collect()
+    }
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT t.name, nt.note_id
+        FROM tags t
+        JOIN note_tags nt ON nt.tag_id = t.id
+        ORDER BY t.name
+        "#,
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+
+    let mut root = Level::default();
+    for row in rows {
+        let (name, note_id) = row?;
+        let mut level = &mut root;
+        let mut path = String::new();
+        for part in name.split('/').filter(|p| !p.is_empty()) {
+            if !path.is_empty() {
+                path.push('/');
+            }
+            path.push_str(part);
+            level = level.children.entry(part.to_lowercase()).or_insert_with(|| Level {
+                path: path.clone(),
+                ..Default::default()
+            });
+            level.notes.insert(note_id);
+        }
+    }
+
+    Ok(build(root.children))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -127,6 +374,77 @@ mod tests {
         assert!(tags.contains(&"programming".to_string()));
     }
 
+    #[test]
+    fn test_tag_occurrences() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+
+        let a = upsert_note(&conn, "a.md", "A", None, None, "x", 0).unwrap();
+        let b = upsert_note(&conn, "b.md", "B", None, None, "x", 0).unwrap();
+        let tag = |name: &str, line_number| ExtractedTag {
+            name: name.to_string(),
+            line_number,
+        };
+        replace_tag_occurrences(&conn, b, &[tag("Idea", 3), tag("todo", 4)]).unwrap();
+        replace_tag_occurrences(&conn, a, &[tag("idea", 7), tag("idea", 2)]).unwrap();
+
+        let found = get_tag_occurrences(&conn, "IDEA").unwrap();
+        let found: Vec<(&str, i64)> =
+            found.iter().map(|o| (o.path.as_str(), o.line_number)).collect();
+        assert_eq!(found, vec![("a.md", 2), ("a.md", 7), ("b.md", 3)]);
+
+        replace_tag_occurrences(&conn, a, &[]).unwrap();
+        assert_eq!(get_tag_occurrences(&conn, "idea").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_nested_tags() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+
+        let alpha = upsert_note(&conn, "alpha.md", "Alpha", None, None, "x", 0).unwrap();
+        let beta = upsert_note(&conn, "beta.md", "Beta", None, None, "x", 0).unwrap();
+        let other = upsert_note(&conn, "other.md", "Other", None, None, "x", 0).unwrap();
+        set_note_tags(&conn, alpha, &["project/alpha".to_string(), "Project".to_string()])
+            .unwrap();
+        set_note_tags(&conn, beta, &["project/beta/v2".to_string()]).unwrap();
+        set_note_tags(&conn, other, &["projects".to_string()]).unwrap();
+
+        let mut notes = get_notes_by_tag(&conn, "project").unwrap();
+        notes.sort();
+        assert_eq!(notes, vec![alpha, beta]);
+        assert_eq!(get_notes_by_tag(&conn, "project/beta").unwrap(), vec![beta]);
+
+        let tree = tag_tree(&conn).unwrap();
+        assert_eq!(tree.len(), 2);
+        assert_eq!((tree[0].path.as_str(), tree[0].count), ("Project", 2));
+        let children: Vec<(&str, &str, i32)> = tree[0]
+            .children
+            .iter()
+            .map(|c| (c.name.as_str(), c.path.as_str(), c.count))
+            .collect();
+        assert_eq!(children, vec![("alpha", "project/alpha", 1), ("beta", "project/beta", 1)]);
+        assert_eq!(tree[0].children[1].children[0].path, "project/beta/v2");
+        assert_eq!((tree[1].name.as_str(), tree[1].count), ("projects", 1));
+    }
+
+    #[test]
+    fn test_rename_tag() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+
+        let a = upsert_note(&conn, "a.md", "A", None, None, "x", 0).unwrap();
+        let b = upsert_note(&conn, "b.md", "B", None, None, "x", 0).unwrap();
+        set_note_tags(&conn, a, &["project".to_string(), "project/alpha".to_string()]).unwrap();
+        set_note_tags(&conn, b, &["work".to_string(), "projects".to_string()]).unwrap();
+
+        rename_tag(&conn, "Project", "work").unwrap();
+        assert_eq!(get_note_tags(&conn, a).unwrap(), vec!["work", "work/alpha"]);
+        assert_eq!(get_note_tags(&conn, b).unwrap(), vec!["projects", "work"]);
+        let names: Vec<String> = list_tags(&conn).unwrap().into_iter().map(|t| t.name).collect();
+        assert_eq!(names, vec!["projects", "work", "work/alpha"]);
+    }
+
     #[test]
     fn test_list_tags() {
         let db = Database::open_memory().unwrap();
@@ -145,6 +463,31 @@ mod tests {
         assert_eq!(rust_tag.count, 2);
     }
 
+    #[test]
+    fn test_tag_meta() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let note = upsert_note(&conn, "a.md", "A", None, None, "x", 0).unwrap();
+        set_note_tags(&conn, note, &["rust".to_string()]).unwrap();
+
+        let meta = TagMeta {
+            color: Some("#dea584".to_string()),
+            icon: Some("🦀".to_string()),
+            description: None,
+        };
+        set_tag_meta(&conn, "Rust", &meta).unwrap();
+        set_tag_meta(&conn, "unused", &TagMeta::default()).unwrap();
+        assert_eq!(list_tags(&conn).unwrap()[0].meta, meta);
+        let styled = StyledTag {
+            name: "rust".to_string(),
+            meta,
+        };
+        assert_eq!(list_tag_meta(&conn).unwrap(), vec![styled]);
+
+        replace_tag_meta(&conn, &[]).unwrap();
+        assert!(list_tag_meta(&conn).unwrap().is_empty());
+    }
+
     #[test]
     fn test_case_insensitive_tags() {
         let db = Database::open_memory().unwrap();
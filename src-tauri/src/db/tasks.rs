@@ -0,0 +1,224 @@
+//! Task database operations
+
+use rusqlite::{params, params_from_iter, types::Value, Connection, OptionalExtension, Result, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::vault::ExtractedTask;
+
+/// A task with the note it lives in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRecord {
+    pub id: i64,
+    pub note_path: String,
+    pub note_title: String,
+    pub line_number: i32,
+    pub text: String,
+    pub completed: bool,
+    pub due: Option<String>,
+    /// Inline tags on the task itself
+    pub tags: Vec<String>,
+}
+
+/// Criteria for `query_tasks`; unset fields don't filter
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TaskFilter {
+    /// Inline tag on the task or tag on its note
+    pub tag: Option<String>,
+    /// Vault-relative folder, including subfolders
+    pub folder: Option<String>,
+    /// Due on or before this date (YYYY-MM-DD)
+    pub due_before: Option<String>,
+    /// Due on or after this date (YYYY-MM-DD)
+    pub due_after: Option<String>,
+    pub completed: Option<bool>,
+}
+
+const TASK_COLUMNS: &str =
+    "t.id, n.path, n.title, t.line_number, t.text, t.completed, t.due, t.tags";
+
+fn task_from_row(row: &Row) -> Result<TaskRecord> {
+    let tags: String = row.get(7)?;
+    Ok(TaskRecord {
+        id: row.get(0)?,
+        note_path: row.get(1)?,
+        note_title: row.get(2)?,
+        line_number: row.get(3)?,
+        text: row.get(4)?,
+        completed: row.get(5)?,
+        due: row.get(6)?,
+        tags: tags.split_whitespace().map(String::from).collect(),
+    })
+}
+
+/// Replace all tasks for a note
+pub fn replace_tasks(conn: &Connection, note_id: i64, tasks: &[ExtractedTask]) -> Result<()> {
+    conn.prepare_cached("DELETE FROM tasks WHERE note_id = ?1")?.execute(params![note_id])?;
+
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO tasks (note_id, line_number, text, completed, due, tags) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    for task in tasks {
+        // Space-delimited so a tag can be matched with LIKE '% tag %'
+        let tags = if task.tags.is_empty() {
+            String::new()
+        } else {
+            format!(" {} ", task.tags.join(" "))
+        };
+        stmt.execute(params![
+            note_id,
+            task.line_number as i32,
+            task.text,
+            task.completed,
+            task.due,
+            tags
+        ])?;
+    }
+
+    Ok(())
+}
+
+/// Tasks matching a filter, soonest due first and undated last
+pub fn query_tasks(conn: &Connection, filter: &TaskFilter) -> Result<Vec<TaskRecord>> {
+    let mut conditions = Vec::new();
+    let mut values: Vec<Value> = Vec::new();
+
+    if let Some(tag) = &filter.tag {
+        let tag = tag.trim_start_matches('#').to_lowercase();
+        conditions.push(format!(
+            r#"(t.tags LIKE '% ' || ?{0} || ' %'
+               OR EXISTS (
+                   SELECT 1 FROM note_tags nt JOIN tags g ON nt.tag_id = g.id
+                   WHERE nt.note_id = n.id AND g.name = ?{0} COLLATE NOCASE
+               ))"#,
+            values.len() + 1
+        ));
+        values.push(Value::Text(tag));
+    }
+    if let Some(folder) = filter.folder.as_deref().map(|f| f.trim_matches('/')) {
+        if !folder.is_empty() {
+            let prefix = format!("{}/", folder);
+            conditions.push(format!(
+                "substr(n.path, 1, {}) = ?{}",
+                prefix.chars().count(),
+                values.len() + 1
+            ));
+            values.push(Value::Text(prefix));
+        }
+    }
+    if let Some(before) = &filter.due_before {
+        conditions.push(format!("t.due <= ?{}", values.len() + 1));
+        values.push(Value::Text(before.clone()));
+    }
+    if let Some(after) = &filter.due_after {
+        conditions.push(format!("t.due >= ?{}", values.len() + 1));
+        values.push(Value::Text(after.clone()));
+    }
+    if let Some(completed) = filter.completed {
+        conditions.push(format!("t.completed = ?{}", values.len() + 1));
+        values.push(Value::Integer(completed as i64));
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {}
+        FROM tasks t
+        JOIN notes n ON t.note_id = n.id
+        {}
+        ORDER BY t.due IS NULL, t.due, n.path, t.line_number
+        "#,
+        TASK_COLUMNS, where_clause
+    ))?;
+
+    let rows = stmt.query_map(params_from_iter(values), task_from_row)?;
+    rows.collect()
+}
+
+/// Every task in a note, in order
+pub fn get_note_tasks(conn: &Connection, path: &str) -> Result<Vec<TaskRecord>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM tasks t JOIN notes n ON t.note_id = n.id
+         WHERE n.path = ?1 ORDER BY t.line_number",
+        TASK_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![path], task_from_row)?;
+    rows.collect()
+}
+
+/// Task on a line (1-indexed) of a note
+pub fn get_task_at(conn: &Connection, path: &str, line_number: i32) -> Result<Option<TaskRecord>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM tasks t JOIN notes n ON t.note_id = n.id WHERE n.path = ?1 AND t.line_number = ?2",
+            TASK_COLUMNS
+        ),
+        params![path, line_number],
+        task_from_row,
+    )
+    .optional()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{notes::upsert_note, schema::Database, tags::set_note_tags};
+    use crate::vault::extract_tasks;
+
+    fn texts(tasks: &[TaskRecord]) -> Vec<&str> {
+        tasks.iter().map(|t| t.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_query_tasks() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+
+        let a = upsert_note(&conn, "work/a.md", "A", None, None, "a", 0).unwrap();
+        let b = upsert_note(&conn, "home.md", "Home", None, None, "b", 0).unwrap();
+        let c = upsert_note(&conn, "workshop.md", "Shop", None, None, "c", 0).unwrap();
+        set_note_tags(&conn, b, &["chores".to_string()]).unwrap();
+
+        replace_tasks(&conn, a, &extract_tasks("- [ ] Report due:2026-03-12 #urgent\n- [x] Email due:2026-03-01")).unwrap();
+        replace_tasks(&conn, b, &extract_tasks("- [ ] Dishes\n- [ ] Laundry due:2026-03-05")).unwrap();
+        replace_tasks(&conn, c, &extract_tasks("- [ ] Sand")).unwrap();
+
+        let all = query_tasks(&conn, &TaskFilter::default()).unwrap();
+        assert_eq!(texts(&all), vec!["Email due:2026-03-01", "Laundry due:2026-03-05", "Report due:2026-03-12 #urgent", "Dishes", "Sand"]);
+
+        let filter = TaskFilter {
+            folder: Some("work/".to_string()),
+            completed: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(texts(&query_tasks(&conn, &filter).unwrap()), vec!["Report due:2026-03-12 #urgent"]);
+
+        let filter = TaskFilter {
+            tag: Some("#Urgent".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(query_tasks(&conn, &filter).unwrap()[0].tags, vec!["urgent"]);
+
+        let filter = TaskFilter {
+            tag: Some("chores".to_string()),
+            due_before: Some("2026-03-10".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(texts(&query_tasks(&conn, &filter).unwrap()), vec!["Laundry due:2026-03-05"]);
+
+        let filter = TaskFilter {
+            due_after: Some("2026-03-05".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(query_tasks(&conn, &filter).unwrap().len(), 2);
+
+        let task = get_task_at(&conn, "home.md", 2).unwrap().unwrap();
+        assert_eq!(task.text, "Laundry due:2026-03-05");
+        assert!(get_task_at(&conn, "home.md", 3).unwrap().is_none());
+    }
+}
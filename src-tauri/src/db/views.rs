@@ -0,0 +1,262 @@
+//! Saved list view database operations
+
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::db::notes::NoteMeta;
+use crate::db::properties::get_property_values;
+use crate::db::smart_folders::notes_matching;
+use crate::db::tags::get_note_tags;
+use crate::vault::{ListView, SortField, ViewGrouping, ViewSpec};
+
+/// A note in a view, with the values of its property columns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewRow {
+    pub note: NoteMeta,
+    /// List values are joined with `, `; missing properties are left out
+    pub properties: BTreeMap<String, String>,
+}
+
+/// Notes sharing a folder, tag or property value. `key` is `None` for the
+/// notes of an ungrouped view and for notes without the tag or property.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewGroup {
+    pub key: Option<String>,
+    pub rows: Vec<ViewRow>,
+}
+
+fn view_from_row(row: &Row) -> Result<ListView> {
+    let spec: String = row.get(2)?;
+    Ok(ListView {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        spec: serde_json::from_str(&spec).unwrap_or_default(),
+        updated_at: row.get(3)?,
+    })
+}
+
+/// Create a view, or replace the one with the same ID. Returns the view
+/// with its ID filled in.
+pub fn save_view(
+    conn: &Connection,
+    id: Option<i64>,
+    name: &str,
+    spec: &ViewSpec,
+    updated_at: &str,
+) -> Result<ListView> {
+    let id = conn.query_row(
+        r#"
+        INSERT INTO list_views (id, name, spec, updated_at)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name,
+            spec = excluded.spec,
+            updated_at = excluded.updated_at
+        RETURNING id
+        "#,
+        params![id, name, serde_json::to_string(spec).unwrap_or_default(), updated_at],
+        |row| row.get(0),
+    )?;
+    Ok(ListView {
+        id,
+        name: name.to_string(),
+        spec: spec.clone(),
+        updated_at: updated_at.to_string(),
+    })
+}
+
+/// A view by ID
+pub fn get_view(conn: &Connection, id: i64) -> Result<Option<ListView>> {
+    conn.query_row(
+        "SELECT id, name, spec, updated_at FROM list_views WHERE id = ?1",
+        params![id],
+        view_from_row,
+    )
+    .optional()
+}
+
+/// All views, by name
+pub fn list_views(conn: &Connection) -> Result<Vec<ListView>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, spec, updated_at FROM list_views ORDER BY name COLLATE NOCASE, id",
+    )?;
+    let rows = stmt.query_map([], view_from_row)?;
+    rows.collect()
+}
+
+/// Delete a view. Returns whether it existed.
+pub fn delete_view(conn: &Connection, id: i64) -> Result<bool> {
+    let deleted = conn.execute("DELETE FROM list_views WHERE id = ?1", params![id])?;
+    Ok(deleted > 0)
+}
+
+/// Replace every view, e.g. with the ones from the vault's file
+pub fn replace_views(conn: &Connection, views: &[ListView]) -> Result<()> {
+    conn.execute("DELETE FROM list_views", [])?;
+    for view in views {
+        save_view(conn, Some(view.id), &view.name, &view.spec, &view.updated_at)?;
+    }
+    Ok(())
+}
+
+/// The notes a view lists: filtered, sorted, cut to its limit and grouped.
+/// Groups are in key order, with notes lacking a key last.
+pub fn view_results(conn: &Connection, spec: &ViewSpec) -> Result<Vec<ViewGroup>> {
+    let mut notes = notes_matching(conn, &spec.filter)?;
+    // Already by title, so equal values stay in title order
+    match spec.sort.field {
+        SortField::Title => {}
+        SortField::Path => notes.sort_by(|a, b| a.path.cmp(&b.path)),
+        SortField::Created => notes.sort_by(|a, b| a.created_at.cmp(&b.created_at)),
+        SortField::Modified => notes.sort_by(|a, b| a.modified_at.cmp(&b.modified_at)),
+        SortField::WordCount => notes.sort_by_key(|note| note.word_count),
+    }
+    if spec.sort.descending {
+        notes.reverse();
+    }
+    if let Some(limit) = spec.limit {
+        notes.truncate(limit);
+    }
+
+    let mut groups: BTreeMap<Option<String>, Vec<ViewRow>> = BTreeMap::new();
+    for note in notes {
+        let keys = match &spec.group_by {
+            None => vec![],
+            Some(ViewGrouping::Folder) => {
+                vec![note.path.rsplit_once('/').map(|(folder, _)| folder.to_string())]
+            }
+            Some(ViewGrouping::Tag) => {
+                get_note_tags(conn, note.id)?.into_iter().map(Some).collect()
+            }
+            Some(ViewGrouping::Property { key }) => {
+                get_property_values(conn, &note.path, key)?.into_iter().map(Some).collect()
+            }
+        };
+        let mut properties = BTreeMap::new();
+        for column in spec.property_columns() {
+            let values = get_property_values(conn, &note.path, column)?;
+            if !values.is_empty() {
+                properties.insert(column.to_string(), values.join(", "));
+            }
+        }
+
+        let row = ViewRow { note, properties };
+        if keys.is_empty() {
+            groups.entry(None).or_default().push(row);
+        } else {
+            for key in keys {
+                groups.entry(key).or_default().push(row.clone());
+            }
+        }
+    }
+
+    // `None` sorts first in the map; list it last
+    let mut groups: Vec<ViewGroup> =
+        groups.into_iter().map(|(key, rows)| ViewGroup { key, rows }).collect();
+    if groups.first().is_some_and(|group| group.key.is_none()) {
+        groups.rotate_left(1);
+    }
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{notes::upsert_note, properties::replace_note_properties, tags::set_note_tags};
+    use crate::db::Database;
+    use crate::vault::{SmartFilter, ViewSort};
+
+    fn note(conn: &Connection, path: &str, modified: &str, tags: &[&str], project: Option<&str>) {
+        let id = upsert_note(conn, path, path, None, Some(modified), "h", 0).unwrap();
+        let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+        set_note_tags(conn, id, &tags).unwrap();
+        let props: Vec<(String, String)> =
+            project.iter().map(|p| ("project".to_string(), p.to_string())).collect();
+        replace_note_properties(conn, id, &props).unwrap();
+    }
+
+    fn keys_and_paths(groups: &[ViewGroup]) -> Vec<(Option<&str>, Vec<&str>)> {
+        groups
+            .iter()
+            .map(|g| {
+                let paths = g.rows.iter().map(|r| r.note.path.as_str()).collect();
+                (g.key.as_deref(), paths)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_view_results() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        note(&conn, "work/site.md", "2025-01-10T09:00:00Z", &["web"], Some("site"));
+        note(&conn, "work/api.md", "2025-01-12T09:00:00Z", &["web", "rust"], Some("api"));
+        note(&conn, "inbox.md", "2025-01-11T09:00:00Z", &[], None);
+
+        let recent = ViewSpec {
+            sort: ViewSort {
+                field: SortField::Modified,
+                descending: true,
+            },
+            limit: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            keys_and_paths(&view_results(&conn, &recent).unwrap()),
+            vec![(None, vec!["work/api.md", "inbox.md"])]
+        );
+
+        let by_project = ViewSpec {
+            columns: vec!["title".to_string(), "project".to_string()],
+            group_by: Some(ViewGrouping::Property {
+                key: "project".to_string(),
+            }),
+            ..Default::default()
+        };
+        let groups = view_results(&conn, &by_project).unwrap();
+        assert_eq!(
+            keys_and_paths(&groups),
+            vec![
+                (Some("api"), vec!["work/api.md"]),
+                (Some("site"), vec!["work/site.md"]),
+                (None, vec!["inbox.md"]),
+            ]
+        );
+        assert_eq!(groups[0].rows[0].properties["project"], "api");
+        assert!(groups[2].rows[0].properties.is_empty());
+
+        let by_tag = ViewSpec {
+            filter: SmartFilter {
+                paths: vec!["work/*".to_string()],
+                ..Default::default()
+            },
+            group_by: Some(ViewGrouping::Tag),
+            ..Default::default()
+        };
+        assert_eq!(
+            keys_and_paths(&view_results(&conn, &by_tag).unwrap()),
+            vec![
+                (Some("rust"), vec!["work/api.md"]),
+                (Some("web"), vec!["work/api.md", "work/site.md"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_view_crud() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let spec = ViewSpec {
+            group_by: Some(ViewGrouping::Folder),
+            ..Default::default()
+        };
+
+        let saved = save_view(&conn, None, "By folder", &spec, "2025-02-01T09:00:00Z").unwrap();
+        assert_eq!(get_view(&conn, saved.id).unwrap(), Some(saved.clone()));
+        assert_eq!(list_views(&conn).unwrap(), vec![saved.clone()]);
+
+        replace_views(&conn, &[]).unwrap();
+        assert!(!delete_view(&conn, saved.id).unwrap());
+    }
+}
@@ -0,0 +1,110 @@
+//! Workspace database operations
+
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+
+use crate::vault::Workspace;
+
+fn workspace_from_row(row: &Row) -> Result<Workspace> {
+    let open_notes: String = row.get(1)?;
+    let layout: String = row.get(2)?;
+    Ok(Workspace {
+        name: row.get(0)?,
+        open_notes: serde_json::from_str(&open_notes).unwrap_or_default(),
+        layout: serde_json::from_str(&layout).unwrap_or_default(),
+        updated_at: row.get(3)?,
+    })
+}
+
+/// Create or replace a workspace
+pub fn save_workspace(conn: &Connection, workspace: &Workspace) -> Result<()> {
+    conn.execute(
+        r#"
+        INSERT INTO workspaces (name, open_notes, layout, updated_at)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(name) DO UPDATE SET
+            open_notes = excluded.open_notes,
+            layout = excluded.layout,
+            updated_at = excluded.updated_at
+        "#,
+        params![
+            workspace.name,
+            serde_json::to_string(&workspace.open_notes).unwrap_or_default(),
+            workspace.layout.to_string(),
+            workspace.updated_at
+        ],
+    )?;
+    Ok(())
+}
+
+/// A workspace by name
+pub fn get_workspace(conn: &Connection, name: &str) -> Result<Option<Workspace>> {
+    conn.query_row(
+        "SELECT name, open_notes, layout, updated_at FROM workspaces WHERE name = ?1",
+        params![name],
+        workspace_from_row,
+    )
+    .optional()
+}
+
+/// All workspaces, by name
+pub fn list_workspaces(conn: &Connection) -> Result<Vec<Workspace>> {
+    let mut stmt = conn.prepare(
+        "SELECT name, open_notes, layout, updated_at FROM workspaces ORDER BY name COLLATE NOCASE",
+    )?;
+    let rows = stmt.query_map([], workspace_from_row)?;
+    rows.collect()
+}
+
+/// Delete a workspace. Returns whether it existed.
+pub fn delete_workspace(conn: &Connection, name: &str) -> Result<bool> {
+    let deleted = conn.execute("DELETE FROM workspaces WHERE name = ?1", params![name])?;
+    Ok(deleted > 0)
+}
+
+/// Replace every workspace, e.g. with the ones from the vault's file
+pub fn replace_workspaces(conn: &Connection, workspaces: &[Workspace]) -> Result<()> {
+    conn.execute("DELETE FROM workspaces", [])?;
+    for workspace in workspaces {
+        save_workspace(conn, workspace)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    fn workspace(name: &str, notes: &[&str]) -> Workspace {
+        Workspace {
+            name: name.to_string(),
+            open_notes: notes.iter().map(|n| n.to_string()).collect(),
+            layout: serde_json::json!({ "sidebar": 250, "split": "vertical" }),
+            updated_at: "2025-02-01T09:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_workspaces() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+
+        save_workspace(&conn, &workspace("thesis", &["thesis/outline.md"])).unwrap();
+        save_workspace(&conn, &workspace("Sprint planning", &["work/sprint.md"])).unwrap();
+        save_workspace(&conn, &workspace("thesis", &["thesis/outline.md", "thesis/ch1.md"])).unwrap();
+
+        let thesis = get_workspace(&conn, "thesis").unwrap().unwrap();
+        assert_eq!(thesis.open_notes, vec!["thesis/outline.md", "thesis/ch1.md"]);
+        assert_eq!(thesis.layout["split"], "vertical");
+
+        let names: Vec<String> = list_workspaces(&conn).unwrap().into_iter().map(|w| w.name).collect();
+        assert_eq!(names, vec!["Sprint planning", "thesis"]);
+
+        assert!(delete_workspace(&conn, "thesis").unwrap());
+        assert!(!delete_workspace(&conn, "thesis").unwrap());
+
+        replace_workspaces(&conn, &[workspace("reading", &[])]).unwrap();
+        let names: Vec<String> = list_workspaces(&conn).unwrap().into_iter().map(|w| w.name).collect();
+        assert_eq!(names, vec!["reading"]);
+    }
+}
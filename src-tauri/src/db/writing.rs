@@ -0,0 +1,70 @@
+//! Word-count history for writing goals
+
+use rusqlite::{params, Connection, Result};
+
+/// Record a note's word count for a day (YYYY-MM-DD). The first record of
+/// the day keeps `baseline`; later ones only move the count. Nothing is
+/// stored for a note that hasn't changed that day.
+pub fn record_word_count(
+    conn: &Connection,
+    path: &str,
+    date: &str,
+    baseline: i32,
+    word_count: i32,
+) -> Result<()> {
+    let updated = conn
+        .prepare_cached("UPDATE word_counts SET word_count = ?3 WHERE path = ?1 AND date = ?2")?
+        .execute(params![path, date, word_count])?;
+    if updated == 0 && baseline != word_count {
+        conn.prepare_cached(
+            "INSERT INTO word_counts (path, date, baseline, word_count) VALUES (?1, ?2, ?3, ?4)",
+        )?
+        .execute(params![path, date, baseline, word_count])?;
+    }
+    Ok(())
+}
+
+/// Words written per day on or after `since` (YYYY-MM-DD), oldest first.
+/// Deleting text never counts as negative progress.
+pub fn words_written_by_day(conn: &Connection, since: &str) -> Result<Vec<(String, i64)>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT date, SUM(MAX(word_count - baseline, 0)) AS written
+        FROM word_counts
+        WHERE date >= ?1
+        GROUP BY date
+        ORDER BY date
+        "#,
+    )?;
+
+    let rows = stmt.query_map(params![since], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::Database;
+
+    #[test]
+    fn test_words_written_by_day() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+
+        // Unchanged note: nothing recorded
+        record_word_count(&conn, "a.md", "2026-03-01", 100, 100).unwrap();
+        assert!(words_written_by_day(&conn, "2026-01-01").unwrap().is_empty());
+
+        record_word_count(&conn, "a.md", "2026-03-01", 100, 150).unwrap();
+        record_word_count(&conn, "a.md", "2026-03-01", 150, 180).unwrap();
+        record_word_count(&conn, "b.md", "2026-03-01", 0, 20).unwrap();
+        record_word_count(&conn, "a.md", "2026-03-02", 180, 120).unwrap();
+
+        let days = words_written_by_day(&conn, "2026-01-01").unwrap();
+        assert_eq!(
+            days,
+            vec![("2026-03-01".to_string(), 100), ("2026-03-02".to_string(), 0)]
+        );
+        assert_eq!(words_written_by_day(&conn, "2026-03-02").unwrap().len(), 1);
+    }
+}
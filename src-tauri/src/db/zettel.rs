@@ -0,0 +1,71 @@
+//! Zettelkasten ID database operations
+
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use std::collections::HashMap;
+
+/// Set or clear a note's ID
+pub fn set_zettel_id(conn: &Connection, note_id: i64, zettel_id: Option<&str>) -> Result<()> {
+    match zettel_id {
+        Some(id) => conn
+            .prepare_cached(
+                "INSERT INTO zettel_ids (note_id, zettel_id) VALUES (?1, ?2)
+                 ON CONFLICT(note_id) DO UPDATE SET zettel_id = excluded.zettel_id",
+            )?
+            .execute(params![note_id, id])?,
+        None => conn
+            .prepare_cached("DELETE FROM zettel_ids WHERE note_id = ?1")?
+            .execute(params![note_id])?,
+    };
+    Ok(())
+}
+
+/// Whether any note already uses an ID
+pub fn zettel_id_exists(conn: &Connection, zettel_id: &str) -> Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM zettel_ids WHERE zettel_id = ?1 COLLATE NOCASE)",
+        params![zettel_id],
+        |row| row.get(0),
+    )
+}
+
+/// A note's ID, by path
+pub fn get_zettel_id(conn: &Connection, path: &str) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT z.zettel_id FROM zettel_ids z JOIN notes n ON n.id = z.note_id WHERE n.path = ?1",
+        params![path],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Note path for every ID, keyed by the lowercased ID
+pub fn zettel_paths(conn: &Connection) -> Result<HashMap<String, String>> {
+    let mut stmt = conn.prepare(
+        "SELECT z.zettel_id, n.path FROM zettel_ids z JOIN notes n ON n.id = z.note_id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?.to_lowercase(), row.get::<_, String>(1)?))
+    })?;
+    rows.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{notes::upsert_note, schema::Database};
+
+    #[test]
+    fn test_zettel_ids() {
+        let db = Database::open_memory().unwrap();
+        let conn = db.conn();
+        let id = upsert_note(&conn, "202501271430 Atoms.md", "Atoms", None, None, "h", 0).unwrap();
+
+        set_zettel_id(&conn, id, Some("202501271430")).unwrap();
+        assert!(zettel_id_exists(&conn, "202501271430").unwrap());
+        assert_eq!(get_zettel_id(&conn, "202501271430 Atoms.md").unwrap().as_deref(), Some("202501271430"));
+        assert_eq!(zettel_paths(&conn).unwrap()["202501271430"], "202501271430 Atoms.md");
+
+        set_zettel_id(&conn, id, None).unwrap();
+        assert!(!zettel_id_exists(&conn, "202501271430").unwrap());
+    }
+}
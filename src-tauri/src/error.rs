@@ -1,13 +1,26 @@
 //! Error types for Chronicle
 
+use serde::ser::SerializeStruct;
 use serde::Serialize;
 use thiserror::Error;
 
+/// Result alias for fallible Chronicle operations
+pub type Result<T> = std::result::Result<T, ChronicleError>;
+
 #[derive(Error, Debug)]
 pub enum ChronicleError {
     #[error("Vault not found: {0}")]
     VaultNotFound(String),
 
+    #[error("Vault is unavailable: {0}")]
+    VaultUnavailable(String),
+
+    #[error("Vault is open read-only while another instance has it: {0}")]
+    VaultReadOnly(String),
+
+    #[error("Folder is not empty: {0}")]
+    VaultNotEmpty(String),
+
     #[error("Note not found: {0}")]
     NoteNotFound(String),
 
@@ -26,9 +39,30 @@ pub enum ChronicleError {
     #[error("IO error: {0}")]
     Io(String),
 
+    #[error("An encryption key is required to open the vault database")]
+    DatabaseKeyRequired,
+
+    #[error("The database key is incorrect")]
+    InvalidDatabaseKey,
+
     #[error("Sync error: {0}")]
     SyncError(String),
 
+    #[error("Authentication failed: {0}")]
+    AuthenticationFailed(String),
+
+    #[error("Network unreachable: {0}")]
+    NetworkUnreachable(String),
+
+    #[error("Remote rejected the push: {0}")]
+    RemoteRejected(String),
+
+    #[error("Merge conflict: {0}")]
+    MergeConflict(String),
+
+    #[error("HEAD is detached; check out a branch to sync")]
+    DetachedHead,
+
     #[error("Lock failed")]
     LockFailed,
 
@@ -37,15 +71,226 @@ pub enum ChronicleError {
 
     #[error("Invalid direction: {0} (use 'prev' or 'next')")]
     InvalidDirection(String),
+
+    #[error("Background task failed: {0}")]
+    Task(String),
+
+    #[error("Logging error: {0}")]
+    Logging(String),
+
+    #[error("Invalid attachment: {0}")]
+    InvalidAttachment(String),
+
+    #[error("Invalid config: {0}")]
+    InvalidConfig(String),
+
+    #[error("Card not found: {0}")]
+    CardNotFound(String),
+
+    #[error("Invalid review grade: {0} (use 0-5)")]
+    InvalidGrade(String),
+
+    #[error("No task at {0}")]
+    TaskNotFound(String),
+
+    #[error("Invalid frontmatter property: {0}")]
+    InvalidProperty(String),
+
+    #[error("Invalid tag: {0}")]
+    InvalidTag(String),
+
+    #[error("Nothing to import in {0}")]
+    InvalidImport(String),
+
+    #[error("Citation not found: {0}")]
+    CitationNotFound(String),
+
+    #[error("Plugin not found: {0}")]
+    PluginNotFound(String),
+
+    #[error("Plugin failed: {0}")]
+    PluginFailed(String),
+
+    #[error("Plugin lacks the capability: {0}")]
+    CapabilityDenied(String),
+
+    #[error("No publish target configured")]
+    NoPublishTarget,
+
+    #[error("Publish failed: {0}")]
+    PublishFailed(String),
+
+    #[error("Could not fetch page: {0}")]
+    FetchFailed(String),
+
+    #[error("Invalid canvas: {0}")]
+    InvalidCanvas(String),
+
+    #[error("Workspace not found: {0}")]
+    WorkspaceNotFound(String),
+
+    #[error("Smart folder not found: {0}")]
+    SmartFolderNotFound(String),
+
+    #[error("View not found: {0}")]
+    ViewNotFound(String),
+
+    #[error("Saved search not found: {0}")]
+    SavedSearchNotFound(String),
+
+    #[error("Invalid filter: {0}")]
+    InvalidFilter(String),
+
+    #[error("Reading list entry not found: {0}")]
+    ReadingItemNotFound(String),
+
+    #[error("Backup not found: {0}")]
+    BackupNotFound(String),
+
+    #[error("Snapshot not found: {0}")]
+    SnapshotNotFound(String),
+
+    #[error("File hasn't finished downloading from cloud storage: {0}")]
+    NotDownloaded(String),
+
+    #[error("OCR is turned off in the config")]
+    OcrDisabled,
+
+    #[error("Text recognition failed: {0}")]
+    OcrFailed(String),
+
+    #[error("This build can't read PDFs")]
+    PdfTextUnavailable,
+}
+
+impl ChronicleError {
+    /// Stable machine-readable code the frontend can branch on
+    pub fn code(&self) -> &'static str {
+        match self {
+            ChronicleError::VaultNotFound(_) => "vault_not_found",
+            ChronicleError::VaultUnavailable(_) => "vault_unavailable",
+            ChronicleError::VaultReadOnly(_) => "vault_read_only",
+            ChronicleError::VaultNotEmpty(_) => "vault_not_empty",
+            ChronicleError::NoteNotFound(_) => "note_not_found",
+            ChronicleError::NoteExists(_) => "note_exists",
+            ChronicleError::InvalidPath(_) => "invalid_path",
+            ChronicleError::NoVaultOpen => "no_vault_open",
+            ChronicleError::Database(_) => "database",
+            ChronicleError::Io(_) => "io",
+            ChronicleError::DatabaseKeyRequired => "database_key_required",
+            ChronicleError::InvalidDatabaseKey => "invalid_database_key",
+            ChronicleError::SyncError(_) => "sync",
+            ChronicleError::AuthenticationFailed(_) => "authentication_failed",
+            ChronicleError::NetworkUnreachable(_) => "network_unreachable",
+            ChronicleError::RemoteRejected(_) => "remote_rejected",
+            ChronicleError::MergeConflict(_) => "merge_conflict",
+            ChronicleError::DetachedHead => "detached_head",
+            ChronicleError::LockFailed => "lock_failed",
+            ChronicleError::InvalidDate(_) => "invalid_date",
+            ChronicleError::InvalidDirection(_) => "invalid_direction",
+            ChronicleError::Task(_) => "task",
+            ChronicleError::Logging(_) => "logging",
+            ChronicleError::InvalidAttachment(_) => "invalid_attachment",
+            ChronicleError::InvalidConfig(_) => "invalid_config",
+            ChronicleError::CardNotFound(_) => "card_not_found",
+            ChronicleError::InvalidGrade(_) => "invalid_grade",
+            ChronicleError::TaskNotFound(_) => "task_not_found",
+            ChronicleError::InvalidProperty(_) => "invalid_property",
+            ChronicleError::InvalidTag(_) => "invalid_tag",
+            ChronicleError::InvalidImport(_) => "invalid_import",
+            ChronicleError::CitationNotFound(_) => "citation_not_found",
+            ChronicleError::PluginNotFound(_) => "plugin_not_found",
+            ChronicleError::PluginFailed(_) => "plugin_failed",
+            ChronicleError::CapabilityDenied(_) => "capability_denied",
+            ChronicleError::NoPublishTarget => "no_publish_target",
+            ChronicleError::PublishFailed(_) => "publish_failed",
+            ChronicleError::FetchFailed(_) => "fetch_failed",
+            ChronicleError::InvalidCanvas(_) => "invalid_canvas",
+            ChronicleError::WorkspaceNotFound(_) => "workspace_not_found",
+            ChronicleError::SmartFolderNotFound(_) => "smart_folder_not_found",
+            ChronicleError::ViewNotFound(_) => "view_not_found",
+            ChronicleError::SavedSearchNotFound(_) => "saved_search_not_found",
+            ChronicleError::InvalidFilter(_) => "invalid_filter",
+            ChronicleError::ReadingItemNotFound(_) => "reading_item_not_found",
+            ChronicleError::BackupNotFound(_) => "backup_not_found",
+            ChronicleError::SnapshotNotFound(_) => "snapshot_not_found",
+            ChronicleError::NotDownloaded(_) => "not_downloaded",
+            ChronicleError::OcrDisabled => "ocr_disabled",
+            ChronicleError::OcrFailed(_) => "ocr_failed",
+            ChronicleError::PdfTextUnavailable => "pdf_text_unavailable",
+        }
+    }
+
+    /// The value the error is about (a path, date, or underlying error text)
+    pub fn context(&self) -> Option<&str> {
+        match self {
+            ChronicleError::VaultNotFound(s)
+            | ChronicleError::VaultUnavailable(s)
+            | ChronicleError::VaultReadOnly(s)
+            | ChronicleError::VaultNotEmpty(s)
+            | ChronicleError::NoteNotFound(s)
+            | ChronicleError::NoteExists(s)
+            | ChronicleError::InvalidPath(s)
+            | ChronicleError::Database(s)
+            | ChronicleError::Io(s)
+            | ChronicleError::SyncError(s)
+            | ChronicleError::AuthenticationFailed(s)
+            | ChronicleError::NetworkUnreachable(s)
+            | ChronicleError::RemoteRejected(s)
+            | ChronicleError::MergeConflict(s)
+            | ChronicleError::InvalidDate(s)
+            | ChronicleError::InvalidDirection(s)
+            | ChronicleError::Task(s)
+            | ChronicleError::Logging(s)
+            | ChronicleError::InvalidAttachment(s)
+            | ChronicleError::InvalidConfig(s)
+            | ChronicleError::CardNotFound(s)
+            | ChronicleError::InvalidGrade(s)
+            | ChronicleError::TaskNotFound(s)
+            | ChronicleError::InvalidProperty(s)
+            | ChronicleError::InvalidTag(s)
+            | ChronicleError::InvalidImport(s)
+            | ChronicleError::CitationNotFound(s)
+            | ChronicleError::PluginNotFound(s)
+            | ChronicleError::PluginFailed(s)
+            | ChronicleError::CapabilityDenied(s)
+            | ChronicleError::PublishFailed(s)
+            | ChronicleError::FetchFailed(s)
+            | ChronicleError::InvalidCanvas(s)
+            | ChronicleError::WorkspaceNotFound(s)
+            | ChronicleError::SmartFolderNotFound(s)
+            | ChronicleError::ViewNotFound(s)
+            | ChronicleError::SavedSearchNotFound(s)
+            | ChronicleError::InvalidFilter(s)
+            | ChronicleError::ReadingItemNotFound(s)
+            | ChronicleError::BackupNotFound(s)
+            | ChronicleError::SnapshotNotFound(s)
+            | ChronicleError::NotDownloaded(s)
+            | ChronicleError::OcrFailed(s) => Some(s),
+            ChronicleError::NoVaultOpen
+            | ChronicleError::LockFailed
+            | ChronicleError::DetachedHead
+            | ChronicleError::DatabaseKeyRequired
+            | ChronicleError::InvalidDatabaseKey
+            | ChronicleError::NoPublishTarget
+            | ChronicleError::OcrDisabled
+            | ChronicleError::PdfTextUnavailable => None,
+        }
+    }
 }
 
-// Make error serializable for Tauri
+// Serialize as `{ code, message, context }` so the frontend can branch on
+// error kinds without parsing messages
 impl Serialize for ChronicleError {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        let mut error = serializer.serialize_struct("ChronicleError", 3)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.serialize_field("context", &self.context())?;
+        error.end()
     }
 }
 
@@ -61,6 +306,26 @@ impl From<std::io::Error> for ChronicleError {
     }
 }
 
+impl From<serde_json::Error> for ChronicleError {
+    fn from(err: serde_json::Error) -> Self {
+        ChronicleError::Io(err.to_string())
+    }
+}
+
+impl From<crate::sync::GitError> for ChronicleError {
+    fn from(err: crate::sync::GitError) -> Self {
+        use crate::sync::GitError;
+        match err {
+            GitError::AuthenticationFailed(m) => ChronicleError::AuthenticationFailed(m),
+            GitError::NetworkUnreachable(m) => ChronicleError::NetworkUnreachable(m),
+            GitError::RemoteRejected(m) => ChronicleError::RemoteRejected(m),
+            GitError::MergeConflict(m) => ChronicleError::MergeConflict(m),
+            GitError::DetachedHead => ChronicleError::DetachedHead,
+            other => ChronicleError::SyncError(other.to_string()),
+        }
+    }
+}
+
 impl From<crate::vault::IndexError> for ChronicleError {
     fn from(err: crate::vault::IndexError) -> Self {
         match err {
@@ -69,6 +334,47 @@ impl From<crate::vault::IndexError> for ChronicleError {
             }
             crate::vault::IndexError::Database(e) => ChronicleError::Database(e.to_string()),
             crate::vault::IndexError::Io(e) => ChronicleError::Io(e.to_string()),
+            crate::vault::IndexError::InvalidCanvas(path, e) => {
+                ChronicleError::InvalidCanvas(format!("{}: {}", path.to_string_lossy(), e))
+            }
+            err @ (crate::vault::IndexError::InvalidDrawing(..)
+            | crate::vault::IndexError::InvalidSidecar(..)) => ChronicleError::Io(err.to_string()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_with_context() {
+        let err = ChronicleError::NoteNotFound("notes/a.md".to_string());
+        let json = serde_json::to_value(&err).unwrap();
+
+        assert_eq!(json["code"], "note_not_found");
+        assert_eq!(json["message"], "Note not found: notes/a.md");
+        assert_eq!(json["context"], "notes/a.md");
+    }
+
+    #[test]
+    fn test_git_error_mapping() {
+        use crate::sync::GitError;
+
+        let err: ChronicleError = GitError::AuthenticationFailed("bad token".to_string()).into();
+        assert_eq!(err.code(), "authentication_failed");
+
+        let err: ChronicleError = GitError::NoRemote.into();
+        assert_eq!(err.code(), "sync");
+        assert_eq!(err.context(), Some("No remote configured"));
+    }
+
+    #[test]
+    fn test_serialize_without_context() {
+        let json = serde_json::to_value(ChronicleError::NoVaultOpen).unwrap();
+
+        assert_eq!(json["code"], "no_vault_open");
+        assert_eq!(json["message"], "No vault is open");
+        assert!(json["context"].is_null());
+    }
+}
@@ -1,40 +1,192 @@
 // Chronicle - Personal Knowledge Graph
 
+pub mod citations;
+pub mod cli;
 pub mod commands;
 pub mod db;
 pub mod error;
+pub mod logging;
 pub mod models;
+pub mod plugins;
+pub mod publish;
+pub mod server;
+pub mod srs;
 pub mod sync;
 pub mod vault;
+pub mod web;
 
 use commands::vault::AppState;
+use models::AppConfig;
 use std::sync::Mutex;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    // Held until exit so buffered log lines get flushed
+    let _log_guard = match logging::init() {
+        Ok(guard) => Some(guard),
+        Err(e) => {
+            eprintln!("Failed to initialize logging: {}", e);
+            None
+        }
+    };
+
+    let builder = tauri::Builder::default();
+    // Registered first so a second launch, as from a chronicle:// link on
+    // Windows and Linux, hands its arguments to the running app
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.set_focus();
+        }
+    }));
+
+    let builder = builder
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
-        .manage(Mutex::new(AppState::default()))
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_deep_link::init());
+    #[cfg(target_os = "android")]
+    let builder = builder.plugin(vault::saf::init());
+
+    builder
+        .manage(AppState::default())
+        .manage(server::ServerState::default())
+        .manage(commands::DeepLinkQueue::default())
+        .setup(|app| {
+            match commands::ConfigWatcher::start(app.handle().clone()) {
+                Ok(watcher) => {
+                    app.manage(Mutex::new(watcher));
+                }
+                Err(e) => tracing::error!(error = %e, "Failed to watch config file"),
+            }
+            server::apply_config(app.handle(), &AppConfig::load().server);
+            commands::start_reminder_scheduler(app.handle().clone());
+            commands::start_backup_scheduler(app.handle().clone());
+            start_deep_links(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
+            commands::create_vault,
             commands::open_vault,
             commands::get_vault_info,
             commands::close_vault,
             commands::poll_vault_events,
+            commands::pick_vault_folder,
+            commands::take_deep_links,
             commands::list_notes,
             commands::get_note,
+            commands::resolve_embeds,
+            commands::render_note_html,
+            commands::get_broken_link_report,
+            commands::list_unresolved_links,
+            commands::fix_broken_link,
+            commands::check_vault_health,
+            commands::format_note,
             commands::create_note,
+            commands::create_notes,
             commands::save_note,
+            commands::list_note_snapshots,
+            commands::restore_snapshot,
+            commands::get_delete_impact,
             commands::delete_note,
             commands::rename_note,
             commands::update_note_tags,
+            commands::bulk_edit_properties,
+            commands::undo_last_operation,
+            commands::import_attachment,
+            commands::recognize_attachments,
+            commands::extract_pdf_attachments,
+            commands::import_eml,
+            commands::import_roam,
+            commands::import_tiddlywiki,
+            commands::suggest_auto_links,
+            commands::apply_auto_links,
+            commands::get_due_cards,
+            commands::review_card,
+            commands::query_tasks,
+            commands::toggle_task,
+            commands::get_calendar_data,
+            commands::get_writing_progress,
+            commands::get_board,
+            commands::move_card,
+            commands::quick_capture,
+            commands::capture_url,
+            commands::list_script_plugins,
+            commands::run_script_command,
+            commands::import_with_script,
+            commands::list_templates,
+            commands::create_note_from_template,
+            commands::search_citations,
+            commands::insert_citation,
+            commands::generate_zettel_id,
+            commands::publish_vault,
+            commands::publish_notes,
+            commands::publish_status,
+            commands::export_note_shareable,
+            commands::export_collection,
+            commands::list_upcoming_reminders,
+            commands::get_review_queue,
+            commands::mark_reviewed,
+            commands::list_reading_list,
+            commands::reading_list_domains,
+            commands::set_reading_status,
+            commands::get_canvas,
+            commands::save_canvas,
+            commands::save_workspace,
+            commands::load_workspace,
+            commands::list_workspaces,
+            commands::save_smart_folder,
+            commands::get_smart_folder,
+            commands::list_smart_folders,
+            commands::delete_smart_folder,
+            commands::save_view,
+            commands::list_views,
+            commands::get_view_results,
+            commands::delete_view,
+            commands::delete_workspace,
+            commands::create_backup,
+            commands::list_backups,
+            commands::restore_backup,
+            commands::export_opml,
+            commands::save_draft,
+            commands::get_draft,
+            commands::discard_draft,
+            commands::list_drafts,
             commands::search_notes,
+            commands::search_notes_page,
+            commands::quick_switch,
+            commands::suggest_search_terms,
+            commands::did_you_mean,
+            commands::save_search,
+            commands::list_saved_searches,
+            commands::delete_saved_search,
+            commands::run_saved_search,
+            commands::search_attachments,
             commands::get_backlinks_cmd,
             commands::get_graph_data,
+            commands::get_graph_overview,
+            commands::get_local_graph,
+            commands::get_filtered_graph,
+            commands::find_path,
+            commands::compare_graph,
             commands::list_tags,
             commands::get_notes_by_tag,
+            commands::get_tag_occurrences,
+            commands::list_tag_tree,
+            commands::rename_tag,
+            commands::update_tag_meta,
+            commands::list_notes_by_type,
+            commands::get_inbox_count,
+            commands::get_next_inbox_item,
+            commands::process_inbox_item,
+            commands::generate_moc,
+            commands::get_breadcrumbs,
             commands::get_config,
             commands::save_config,
+            commands::get_custom_css,
+            commands::get_recent_logs,
+            commands::set_log_level,
             // Sync commands
             commands::sync_status,
             commands::sync_init,
@@ -50,6 +202,38 @@ pub fn run() {
             commands::get_daily_note_path,
             commands::daily_note_exists,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Saves still waiting out the debounce would otherwise never
+            // reach the index
+            if let tauri::RunEvent::Exit = event {
+                app.state::<AppState>().flush_index_queue();
+            }
+        });
+}
+
+/// Route `chronicle://` links to the frontend, including one the app was
+/// launched with
+fn start_deep_links(app: &tauri::AppHandle) {
+    use tauri_plugin_deep_link::DeepLinkExt;
+
+    // Installed builds register the scheme from tauri.conf.json; this
+    // covers AppImages and development runs
+    #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+    if let Err(e) = app.deep_link().register_all() {
+        tracing::warn!(error = %e, "Failed to register the chronicle:// scheme");
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        commands::queue_deep_links(&handle, event.urls().iter().map(|url| url.to_string()));
+    });
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => {
+            commands::queue_deep_links(app, urls.iter().map(|url| url.to_string()))
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!(error = %e, "Failed to read the launch link"),
+    }
 }
@@ -0,0 +1,141 @@
+//! Application logging
+//!
+//! Logs go to a daily-rotated file under the config dir (and stderr), so
+//! users can attach recent output to bug reports.
+
+use crate::error::{ChronicleError, Result};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Registry};
+
+const LOG_FILE_PREFIX: &str = "chronicle";
+const LOG_FILE_SUFFIX: &str = "log";
+const MAX_LOG_FILES: usize = 7;
+
+static LEVEL_HANDLE: OnceLock<reload::Handle<LevelFilter, Registry>> = OnceLock::new();
+
+/// Directory holding the rotated log files
+pub fn log_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("chronicle")
+        .join("logs")
+}
+
+/// Install the global subscriber. The returned guard flushes buffered
+/// lines on drop, so keep it alive for the lifetime of the app.
+pub fn init() -> Result<WorkerGuard> {
+    let dir = log_dir();
+    fs::create_dir_all(&dir)?;
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(&dir)
+        .map_err(|e| ChronicleError::Logging(e.to_string()))?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    let (level, handle) = reload::Layer::new(LevelFilter::INFO);
+    tracing_subscriber::registry()
+        .with(level)
+        .with(fmt::layer().with_writer(writer).with_ansi(false))
+        .with(fmt::layer().with_writer(io::stderr))
+        .try_init()
+        .map_err(|e| ChronicleError::Logging(e.to_string()))?;
+
+    let _ = LEVEL_HANDLE.set(handle);
+    Ok(guard)
+}
+
+/// Change the maximum level at runtime (`trace`, `debug`, `info`, `warn`,
+/// `error` or `off`)
+pub fn set_level(level: &str) -> Result<()> {
+    let filter = LevelFilter::from_str(level.trim())
+        .map_err(|_| ChronicleError::Logging(format!("Unknown log level: {}", level)))?;
+    let handle = LEVEL_HANDLE
+        .get()
+        .ok_or_else(|| ChronicleError::Logging("Logging is not initialized".to_string()))?;
+    handle
+        .reload(filter)
+        .map_err(|e| ChronicleError::Logging(e.to_string()))?;
+    tracing::info!(level = %filter, "Log level changed");
+    Ok(())
+}
+
+/// Last `limit` lines across the log files in `dir`, oldest first
+pub fn recent_lines(dir: &Path, limit: usize) -> io::Result<Vec<String>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    // Rotated names embed the date, so lexical order is chronological
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(LOG_FILE_PREFIX) && n.ends_with(LOG_FILE_SUFFIX))
+        })
+        .collect();
+    files.sort();
+
+    let mut lines = Vec::new();
+    for file in files.iter().rev() {
+        let content = fs::read_to_string(file)?;
+        let mut chunk: Vec<String> = content.lines().map(str::to_string).collect();
+        let wanted = limit - lines.len();
+        if chunk.len() > wanted {
+            chunk.drain(..chunk.len() - wanted);
+        }
+        chunk.append(&mut lines);
+        lines = chunk;
+        if lines.len() >= limit {
+            break;
+        }
+    }
+
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_recent_lines_spans_rotated_files() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("chronicle.2024-01-01.log"), "a\nb\nc\n").unwrap();
+        fs::write(temp.path().join("chronicle.2024-01-02.log"), "d\ne\n").unwrap();
+        fs::write(temp.path().join("other.txt"), "ignored\n").unwrap();
+
+        let lines = recent_lines(temp.path(), 3).unwrap();
+        assert_eq!(lines, vec!["c", "d", "e"]);
+
+        let all = recent_lines(temp.path(), 100).unwrap();
+        assert_eq!(all, vec!["a", "b", "c", "d", "e"]);
+    }
+
+    #[test]
+    fn test_recent_lines_missing_dir() {
+        let temp = TempDir::new().unwrap();
+        let lines = recent_lines(&temp.path().join("logs"), 10).unwrap();
+        assert!(lines.is_empty());
+    }
+
+    #[test]
+    fn test_set_level_rejects_unknown() {
+        let err = set_level("loud").unwrap_err();
+        assert_eq!(err.code(), "logging");
+    }
+}
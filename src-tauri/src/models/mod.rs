@@ -1,8 +1,15 @@
 //! Data models for Chronicle
 
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Datelike, Duration, Local, Locale, NaiveDate};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+
+use crate::db::notes::NoteMeta;
+use crate::sync::PullStrategy;
+use crate::vault::RollupValue;
 
 /// Information about the current vault
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +17,8 @@ pub struct VaultInfo {
     pub path: String,
     pub note_count: usize,
     pub is_open: bool,
+    /// Another instance has the vault open, so this one can't write to it
+    pub read_only: bool,
 }
 
 /// Full note content
@@ -21,7 +30,28 @@ pub struct Note {
     pub word_count: i32,
     pub created_at: Option<String>,
     pub modified_at: Option<String>,
+    /// `created_at` formatted with the locale settings
+    pub created_display: Option<String>,
+    /// `modified_at` formatted with the locale settings
+    pub modified_display: Option<String>,
     pub tags: Vec<String>,
+    /// Results of the note's `chronicle-query` blocks, when asked for
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub queries: Vec<QueryResult>,
+}
+
+/// The notes an inline query block matches, and its rollups
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryResult {
+    /// 1-indexed lines of the block's opening and closing fences
+    pub start_line: usize,
+    pub end_line: usize,
+    pub notes: Vec<NoteMeta>,
+    /// The block's rollups, in order
+    #[serde(default)]
+    pub rollups: Vec<RollupValue>,
+    /// Why the block couldn't be run, e.g. invalid YAML
+    pub error: Option<String>,
 }
 
 /// Graph data for visualization
@@ -34,9 +64,31 @@ pub struct GraphData {
 /// Node in the graph
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphNode {
+    /// The note's path, or `tag:` and the lowercased name for tags
     pub id: String,
+    #[serde(default)]
+    pub kind: GraphNodeKind,
+    /// Stable ID that stays the same when the note is moved or renamed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uid: Option<String>,
     pub title: String,
     pub word_count: i32,
+    /// Number of links to and from the node
+    #[serde(default)]
+    pub degree: usize,
+    /// Share of PageRank, from 0 to 1; higher for nodes linked from other
+    /// well-linked nodes
+    #[serde(default)]
+    pub pagerank: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphNodeKind {
+    #[default]
+    Note,
+    /// A tag, linked to the notes carrying it
+    Tag,
 }
 
 /// Edge in the graph
@@ -44,11 +96,28 @@ pub struct GraphNode {
 pub struct GraphEdge {
     pub source: String,
     pub target: String,
+    /// Recency weight from 0 to 1, when asked for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
 }
 
+/// Current config schema version. Bump this and append a step to
+/// `CONFIG_MIGRATIONS` whenever a setting is renamed or restructured.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Migration steps; entry `n` upgrades a version-`n` table to version `n + 1`
+const CONFIG_MIGRATIONS: &[fn(&mut toml::Table)] = &[
+    // v0 -> v1: unversioned files; the layout is unchanged, only the
+    // version key is introduced.
+    |_| {},
+];
+
 /// Application configuration
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AppConfig {
+    /// Schema version of the file this config was read from
+    #[serde(default = "default_config_version")]
+    pub version: u32,
     #[serde(default)]
     pub vault: VaultConfig,
     #[serde(default)]
@@ -59,14 +128,57 @@ pub struct AppConfig {
     pub ui: UiConfig,
     #[serde(default)]
     pub daily_notes: DailyNotesConfig,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    #[serde(default)]
+    pub new_notes: NewNoteConfig,
+    #[serde(default)]
+    pub sync: SyncConfig,
+    #[serde(default)]
+    pub attachments: AttachmentsConfig,
+    #[serde(default)]
+    pub locale: LocaleConfig,
+    #[serde(default)]
+    pub database: DatabaseConfig,
+    #[serde(default)]
+    pub writing_goals: WritingGoalsConfig,
+    #[serde(default)]
+    pub server: ServerConfig,
+    #[serde(default)]
+    pub plugins: PluginsConfig,
+    #[serde(default)]
+    pub citations: CitationsConfig,
+    #[serde(default)]
+    pub zettel: ZettelConfig,
+    #[serde(default)]
+    pub publish: PublishingConfig,
+    #[serde(default)]
+    pub ocr: OcrConfig,
+    #[serde(default)]
+    pub formatting: FormattingConfig,
+    #[serde(default)]
+    pub backup: BackupConfig,
+    #[serde(default)]
+    pub snapshots: SnapshotConfig,
+    #[serde(default)]
+    pub inbox: InboxConfig,
+    #[serde(default)]
+    pub breadcrumbs: BreadcrumbsConfig,
+    /// Schemas for notes by their `type` property, keyed by type name
+    #[serde(default)]
+    pub note_types: BTreeMap<String, NoteTypeSchema>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VaultConfig {
     pub path: Option<String>,
+    /// Take the attachment folder, daily note format and templates folder
+    /// from a vault's `.obsidian` settings when it is opened
+    #[serde(default = "default_true")]
+    pub follow_obsidian_settings: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EditorConfig {
     #[serde(default = "default_font_family")]
     pub font_family: String,
@@ -80,7 +192,7 @@ pub struct EditorConfig {
     pub vim_mode: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GraphConfig {
     #[serde(default = "default_true")]
     pub physics_enabled: bool,
@@ -92,7 +204,7 @@ pub struct GraphConfig {
     pub node_size: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UiConfig {
     #[serde(default = "default_sidebar_width")]
     pub sidebar_width: u32,
@@ -104,7 +216,7 @@ pub struct UiConfig {
     pub show_tags: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DailyNotesConfig {
     /// Folder for daily notes (relative to vault root)
     #[serde(default = "default_daily_folder")]
@@ -123,7 +235,455 @@ pub struct DailyNotesConfig {
     pub link_next_day: bool,
 }
 
+/// Color scheme preference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeMode {
+    Light,
+    Dark,
+    /// Follow the operating system setting
+    #[default]
+    System,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    #[serde(default)]
+    pub mode: ThemeMode,
+    /// Accent color as a hex string (`#rgb` or `#rrggbb`)
+    #[serde(default = "default_accent_color")]
+    pub accent_color: String,
+    /// Optional user stylesheet, absolute or relative to the config directory
+    #[serde(default)]
+    pub custom_css: Option<String>,
+}
+
+/// Where `create_note` places new notes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NewNoteLocation {
+    #[default]
+    VaultRoot,
+    /// The folder named by `NewNoteConfig::folder`
+    Folder,
+    /// Next to the note that is currently open
+    CurrentFolder,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewNoteConfig {
+    #[serde(default)]
+    pub location: NewNoteLocation,
+    /// Folder for new notes when `location` is `folder` (relative to vault root)
+    #[serde(default = "default_new_note_folder")]
+    pub folder: String,
+    /// Filename template without extension. Supports `{{slug}}`, `{{title}}`,
+    /// `{{date}}` (YYYY-MM-DD), `{{time}}` (HHMMSS) and `{{id}}` (a fresh
+    /// Zettelkasten ID).
+    #[serde(default = "default_filename_template")]
+    pub filename_template: String,
+    /// Vault folder holding note templates (see `crate::vault::template`)
+    #[serde(default = "default_templates_folder")]
+    pub templates_folder: String,
+    /// Template and frontmatter for notes created in particular folders
+    #[serde(default)]
+    pub folder_defaults: Vec<FolderDefaults>,
+}
+
+/// Properties notes of one type need (see `crate::vault::note_types`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NoteTypeSchema {
+    /// Properties that must be set; ones with a default always are
+    #[serde(default)]
+    pub required: Vec<String>,
+    /// Values for properties the note doesn't set, used when indexing
+    #[serde(default)]
+    pub defaults: BTreeMap<String, serde_json::Value>,
+}
+
+/// Defaults for new notes in a folder and its subfolders
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FolderDefaults {
+    /// Vault-relative folder
+    pub folder: String,
+    /// Template for notes created without content, as a vault path or a
+    /// name in the templates folder
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Properties added to each new note's frontmatter unless it already
+    /// has them
+    #[serde(default)]
+    pub frontmatter: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct SyncConfig {
+    /// Remote to configure as `origin` when a vault repository has none
+    #[serde(default)]
+    pub remote_url: Option<String>,
+    /// Minutes between automatic syncs (0 disables auto-sync)
+    #[serde(default)]
+    pub auto_sync_interval: u32,
+    #[serde(default)]
+    pub pull_strategy: PullStrategy,
+    /// Commit author name (empty uses "Chronicle")
+    #[serde(default)]
+    pub author_name: String,
+    /// Commit author email (empty uses "chronicle@local")
+    #[serde(default)]
+    pub author_email: String,
+    /// Commit each note to git when it is saved
+    #[serde(default)]
+    pub auto_commit_on_save: bool,
+}
+
+/// Where pasted and imported attachments are stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentLocation {
+    /// One vault-level folder (`AttachmentsConfig::folder`)
+    #[default]
+    VaultFolder,
+    /// A subfolder next to the note (`AttachmentsConfig::subfolder`)
+    NoteSubfolder,
+}
+
+/// Syntax used for links inserted when an attachment is imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbedStyle {
+    /// `![name](relative/path.png)`
+    #[default]
+    Markdown,
+    /// `![[attachments/path.png]]`
+    Wiki,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttachmentsConfig {
+    #[serde(default)]
+    pub location: AttachmentLocation,
+    /// Vault-level attachments folder (relative to vault root)
+    #[serde(default = "default_attachments_folder")]
+    pub folder: String,
+    /// Subfolder name used with `note_subfolder`; `{{note}}` expands to the
+    /// note's filename without extension
+    #[serde(default = "default_attachments_subfolder")]
+    pub subfolder: String,
+    /// Filename template without extension. Supports `{{name}}` (original
+    /// name), `{{note}}`, `{{date}}` (YYYY-MM-DD) and `{{time}}` (HHMMSS).
+    #[serde(default = "default_attachment_name_template")]
+    pub name_template: String,
+    #[serde(default)]
+    pub embed_style: EmbedStyle,
+}
+
+/// First day of the week for calendar queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+    Saturday,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocaleConfig {
+    /// POSIX locale name used for month and weekday names (e.g. `de_DE`)
+    #[serde(default = "default_locale")]
+    pub locale: String,
+    /// Date format for display strings (strftime format)
+    #[serde(default = "default_display_date_format")]
+    pub date_format: String,
+    /// Time format for display strings (strftime format)
+    #[serde(default = "default_time_format")]
+    pub time_format: String,
+    #[serde(default)]
+    pub week_start: WeekStart,
+}
+
+/// Settings for the vault's `.chronicle/chronicle.db` index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct DatabaseConfig {
+    /// Encrypt the index with SQLCipher. Requires a build with the
+    /// `sqlcipher` feature and a key when the vault is opened.
+    #[serde(default)]
+    pub encrypted: bool,
+    /// Threads reading and parsing notes during a full index. 0 uses one
+    /// per CPU core; 1 indexes on a single thread.
+    #[serde(default)]
+    pub index_threads: usize,
+}
+
+/// Word-count targets for `get_writing_progress`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WritingGoalsConfig {
+    /// Words per day; days reaching it count towards the streak.
+    /// 0 means any writing at all keeps the streak going.
+    #[serde(default = "default_daily_words")]
+    pub daily_words: u32,
+    /// Words per week (weeks start on `locale.week_start`); 0 disables it
+    #[serde(default)]
+    pub weekly_words: u32,
+}
+
+/// Local HTTP server for the browser clipper and REST API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerConfig {
+    /// Listen on 127.0.0.1. Off by default.
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_server_port")]
+    pub port: u16,
+    /// Shared secret clients send as `Authorization: Bearer <token>`
+    #[serde(default)]
+    pub token: String,
+}
+
+/// Bibliography used for `[[@key]]` citations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CitationsConfig {
+    /// `.bib` file, absolute or relative to the vault root
+    #[serde(default)]
+    pub bib_file: Option<String>,
+    /// Folder for literature notes, one per cite key (relative to vault root)
+    #[serde(default = "default_literature_folder")]
+    pub literature_folder: String,
+}
+
+/// Unique note IDs for Zettelkasten-style linking (`[[202501271430]]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct ZettelConfig {
+    #[serde(default)]
+    pub id_scheme: ZettelIdScheme,
+    /// Start new note filenames with a fresh ID (`202501271430-my-note.md`)
+    #[serde(default)]
+    pub prefix_filenames: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ZettelIdScheme {
+    /// Minute timestamp such as `202501271430`
+    #[default]
+    Timestamp,
+    /// 26-character ULID, sortable and unique without coordination
+    Ulid,
+}
+
+/// Where and how notes marked `publish: true` are published (see
+/// `crate::publish`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublishingConfig {
+    #[serde(default)]
+    pub format: PublishFormat,
+    /// Unset until the user picks a target; publishing fails without one
+    #[serde(default)]
+    pub target: Option<PublishTarget>,
+    /// Title of the generated index page (HTML only)
+    #[serde(default = "default_publish_title")]
+    pub site_title: String,
+    /// HTML-to-PDF converter for PDF exports, run as `<command> <input.html>
+    /// <output.pdf>` and looked up on `PATH` unless absolute
+    #[serde(default = "default_pdf_command")]
+    pub pdf_command: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PublishFormat {
+    #[default]
+    Html,
+    /// Markdown with wiki links rewritten to relative links, for static
+    /// site generators
+    Markdown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PublishTarget {
+    /// A local folder, absolute or relative to the vault root
+    Folder { path: String },
+    /// A branch of the vault's git repository (such as `gh-pages`), replaced
+    /// with the published files on every publish
+    Git {
+        #[serde(default = "default_publish_branch")]
+        branch: String,
+        /// Push the branch to `origin` afterwards
+        #[serde(default)]
+        push: bool,
+    },
+    /// A directory on a server, uploaded over SFTP. Authenticates with the
+    /// private key file if set, otherwise the SSH agent.
+    Sftp {
+        host: String,
+        #[serde(default = "default_sftp_port")]
+        port: u16,
+        user: String,
+        remote_dir: String,
+        #[serde(default)]
+        key_file: Option<String>,
+    },
+}
+
+/// Text recognition over image attachments so screenshots and scans show
+/// up in search. Runs the Tesseract command-line tool, which must be
+/// installed separately.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OcrConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Tesseract executable, looked up on `PATH` unless absolute
+    #[serde(default = "default_ocr_command")]
+    pub command: String,
+    /// Tesseract language codes joined with `+` (e.g. `eng+deu`)
+    #[serde(default = "default_ocr_languages")]
+    pub languages: String,
+}
+
+/// Markdown normalization rules for `format_note`, which keep a synced
+/// vault's diffs down to real edits. Fenced code and frontmatter are never
+/// touched.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormattingConfig {
+    /// Also format notes whenever they are saved
+    #[serde(default)]
+    pub format_on_save: bool,
+    /// Strip trailing spaces and tabs, keeping two-space line breaks, and
+    /// end the note with a single newline
+    #[serde(default = "default_true")]
+    pub trim_trailing_whitespace: bool,
+    /// One blank line around headings and one space after the `#`s
+    #[serde(default = "default_true")]
+    pub heading_spacing: bool,
+    /// Marker for unordered list items
+    #[serde(default)]
+    pub list_marker: ListMarker,
+    /// Rewrite `[[links]]` to the exact casing of the note they point at
+    #[serde(default = "default_true")]
+    pub link_casing: bool,
+}
+
+/// Zip archives of the open vault, taken on demand and, when enabled, on
+/// a schedule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// Back up on a schedule while a vault is open
+    #[serde(default)]
+    pub enabled: bool,
+    /// Folder for the archives; `backups` in the config folder when unset
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default = "default_backup_interval_hours")]
+    pub interval_hours: u32,
+    /// Archives kept per vault, the oldest removed first; 0 keeps all
+    #[serde(default = "default_backup_keep")]
+    pub keep: usize,
+    /// Leave out everything but notes, canvases and drawings when off
+    #[serde(default = "default_true")]
+    pub include_attachments: bool,
+}
+
+/// Local version history kept on every save, for vaults without git
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Saves this soon after the last snapshot of a note don't take another
+    #[serde(default = "default_snapshot_debounce_minutes")]
+    pub debounce_minutes: u32,
+    /// Versions kept per note, the oldest dropped first; 0 keeps all
+    #[serde(default = "default_snapshot_keep")]
+    pub keep: usize,
+}
+
+/// Where captured notes wait to be filed, tagged, merged or deleted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InboxConfig {
+    /// Vault-relative folder; notes in its subfolders count too
+    #[serde(default = "default_inbox_folder")]
+    pub folder: String,
+}
+
+/// Hierarchical navigation on top of links
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreadcrumbsConfig {
+    /// Frontmatter property linking a note to its parent (`up: "[[Projects]]"`)
+    #[serde(default = "default_parent_property")]
+    pub parent_property: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ListMarker {
+    #[default]
+    #[serde(rename = "-")]
+    Dash,
+    #[serde(rename = "*")]
+    Asterisk,
+    #[serde(rename = "+")]
+    Plus,
+    /// Leave markers as written
+    #[serde(rename = "keep")]
+    Keep,
+}
+
+/// External scripts run at hook points (see `crate::plugins`)
+///
+/// Scripts live in the app config rather than the vault, so opening or
+/// syncing a vault never runs code the user didn't set up.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct PluginsConfig {
+    #[serde(default)]
+    pub scripts: Vec<ScriptPlugin>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptPlugin {
+    /// Unique name, used to run the script as a command or importer
+    pub name: String,
+    pub hook: ScriptHook,
+    /// Program to run; it gets a JSON request on stdin and answers on stdout
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// What the script may read and change; anything else it returns is refused
+    #[serde(default)]
+    pub capabilities: Vec<Capability>,
+    /// File extensions an importer handles (without the dot)
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default = "default_script_timeout")]
+    pub timeout_secs: u64,
+}
+
+/// When a script runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptHook {
+    /// Transforms note content before it is written
+    NoteSave,
+    /// Run on demand from the command palette
+    Command,
+    /// Turns an outside file into notes
+    Import,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Capability {
+    /// Receive the current note's content
+    #[serde(rename = "note:read")]
+    NoteRead,
+    /// Replace the current note's content
+    #[serde(rename = "note:write")]
+    NoteWrite,
+    /// Create new notes
+    #[serde(rename = "note:create")]
+    NoteCreate,
+}
+
 // Default value functions
+fn default_config_version() -> u32 { CONFIG_VERSION }
 fn default_font_family() -> String { "JetBrains Mono".to_string() }
 fn default_font_size() -> u32 { 14 }
 fn default_line_height() -> f32 { 1.6 }
@@ -135,6 +695,32 @@ fn default_sidebar_width() -> u32 { 250 }
 fn default_panel_width() -> u32 { 250 }
 fn default_daily_folder() -> String { "daily".to_string() }
 fn default_date_format() -> String { "%Y-%m-%d".to_string() }
+fn default_new_note_folder() -> String { "inbox".to_string() }
+fn default_inbox_folder() -> String { "inbox".to_string() }
+fn default_parent_property() -> String { "up".to_string() }
+fn default_filename_template() -> String { "{{slug}}".to_string() }
+fn default_templates_folder() -> String { "templates".to_string() }
+fn default_attachments_folder() -> String { "attachments".to_string() }
+fn default_attachments_subfolder() -> String { "attachments".to_string() }
+fn default_attachment_name_template() -> String { "{{name}}".to_string() }
+fn default_daily_words() -> u32 { 500 }
+fn default_server_port() -> u16 { 27183 }
+fn default_script_timeout() -> u64 { 10 }
+fn default_literature_folder() -> String { "literature".to_string() }
+fn default_publish_title() -> String { "My Notes".to_string() }
+fn default_publish_branch() -> String { "gh-pages".to_string() }
+fn default_pdf_command() -> String { "wkhtmltopdf".to_string() }
+fn default_sftp_port() -> u16 { 22 }
+fn default_ocr_command() -> String { "tesseract".to_string() }
+fn default_ocr_languages() -> String { "eng".to_string() }
+fn default_backup_interval_hours() -> u32 { 24 }
+fn default_backup_keep() -> usize { 10 }
+fn default_snapshot_debounce_minutes() -> u32 { 10 }
+fn default_snapshot_keep() -> usize { 50 }
+fn default_locale() -> String { "en_US".to_string() }
+fn default_display_date_format() -> String { "%b %-d, %Y".to_string() }
+fn default_time_format() -> String { "%H:%M".to_string() }
+fn default_accent_color() -> String { "#e94560".to_string() }
 fn default_daily_template() -> String {
     r#"# {{date}}
 
@@ -149,6 +735,47 @@ fn default_daily_template() -> String {
 "#.to_string()
 }
 
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            vault: VaultConfig::default(),
+            editor: EditorConfig::default(),
+            graph: GraphConfig::default(),
+            ui: UiConfig::default(),
+            daily_notes: DailyNotesConfig::default(),
+            theme: ThemeConfig::default(),
+            new_notes: NewNoteConfig::default(),
+            sync: SyncConfig::default(),
+            attachments: AttachmentsConfig::default(),
+            locale: LocaleConfig::default(),
+            database: DatabaseConfig::default(),
+            writing_goals: WritingGoalsConfig::default(),
+            server: ServerConfig::default(),
+            plugins: PluginsConfig::default(),
+            citations: CitationsConfig::default(),
+            zettel: ZettelConfig::default(),
+            publish: PublishingConfig::default(),
+            ocr: OcrConfig::default(),
+            formatting: FormattingConfig::default(),
+            backup: BackupConfig::default(),
+            snapshots: SnapshotConfig::default(),
+            inbox: InboxConfig::default(),
+            breadcrumbs: BreadcrumbsConfig::default(),
+            note_types: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for VaultConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            follow_obsidian_settings: true,
+        }
+    }
+}
+
 impl Default for EditorConfig {
     fn default() -> Self {
         Self {
@@ -195,6 +822,440 @@ impl Default for DailyNotesConfig {
     }
 }
 
+impl Default for NewNoteConfig {
+    fn default() -> Self {
+        Self {
+            location: NewNoteLocation::default(),
+            folder: default_new_note_folder(),
+            filename_template: default_filename_template(),
+            templates_folder: default_templates_folder(),
+            folder_defaults: Vec::new(),
+        }
+    }
+}
+
+impl NewNoteConfig {
+    /// Defaults for a new note at `path`, from the deepest folder that
+    /// contains it
+    pub fn folder_defaults_for(&self, path: &str) -> Option<&FolderDefaults> {
+        self.folder_defaults
+            .iter()
+            .filter(|d| {
+                let folder = d.folder.trim_matches('/');
+                path.strip_prefix(folder).is_some_and(|rest| rest.starts_with('/'))
+            })
+            .max_by_key(|d| d.folder.trim_matches('/').len())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for defaults in &self.folder_defaults {
+            if !folder_inside_vault(&defaults.folder) {
+                return Err(format!(
+                    "Folder defaults need a folder inside the vault: {:?}",
+                    defaults.folder
+                ));
+            }
+            // TOML has no null, so the config couldn't be saved
+            if let Some((key, _)) = defaults.frontmatter.iter().find(|(_, v)| v.is_null()) {
+                return Err(format!(
+                    "Default property {} in {} has no value",
+                    key, defaults.folder
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for AttachmentsConfig {
+    fn default() -> Self {
+        Self {
+            location: AttachmentLocation::default(),
+            folder: default_attachments_folder(),
+            subfolder: default_attachments_subfolder(),
+            name_template: default_attachment_name_template(),
+            embed_style: EmbedStyle::default(),
+        }
+    }
+}
+
+impl Default for InboxConfig {
+    fn default() -> Self {
+        Self {
+            folder: default_inbox_folder(),
+        }
+    }
+}
+
+impl Default for BreadcrumbsConfig {
+    fn default() -> Self {
+        Self {
+            parent_property: default_parent_property(),
+        }
+    }
+}
+
+impl BreadcrumbsConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.parent_property.trim().is_empty() {
+            return Err("Breadcrumbs need a parent property".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl InboxConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !folder_inside_vault(&self.folder) {
+            return Err(format!("Inbox folder must be inside the vault: {:?}", self.folder));
+        }
+        Ok(())
+    }
+}
+
+/// Whether a configured folder names a folder inside the vault, rather than
+/// the vault root or somewhere outside it
+fn folder_inside_vault(folder: &str) -> bool {
+    let folder = Path::new(folder.trim_matches('/'));
+    !folder.as_os_str().is_empty()
+        && !folder.is_absolute()
+        && !folder.components().any(|c| matches!(c, Component::ParentDir))
+}
+
+impl Default for WritingGoalsConfig {
+    fn default() -> Self {
+        Self {
+            daily_words: default_daily_words(),
+            weekly_words: 0,
+        }
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_server_port(),
+            token: String::new(),
+        }
+    }
+}
+
+/// Shortest token accepted for the local server
+const MIN_SERVER_TOKEN_LEN: usize = 16;
+
+impl ServerConfig {
+    /// An enabled server needs a token that is hard to guess
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enabled && self.token.chars().count() < MIN_SERVER_TOKEN_LEN {
+            return Err(format!(
+                "Server token must be at least {} characters",
+                MIN_SERVER_TOKEN_LEN
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for CitationsConfig {
+    fn default() -> Self {
+        Self {
+            bib_file: None,
+            literature_folder: default_literature_folder(),
+        }
+    }
+}
+
+impl Default for PublishingConfig {
+    fn default() -> Self {
+        Self {
+            format: PublishFormat::default(),
+            target: None,
+            site_title: default_publish_title(),
+            pdf_command: default_pdf_command(),
+        }
+    }
+}
+
+impl Default for FormattingConfig {
+    fn default() -> Self {
+        Self {
+            format_on_save: false,
+            trim_trailing_whitespace: true,
+            heading_spacing: true,
+            list_marker: ListMarker::default(),
+            link_casing: true,
+        }
+    }
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            location: None,
+            interval_hours: default_backup_interval_hours(),
+            keep: default_backup_keep(),
+            include_attachments: true,
+        }
+    }
+}
+
+impl BackupConfig {
+    /// Folder the archives go to
+    pub fn dir(&self) -> PathBuf {
+        match self.location.as_deref().map(str::trim).filter(|l| !l.is_empty()) {
+            Some(location) => PathBuf::from(location),
+            None => dirs::config_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("chronicle")
+                .join("backups"),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_hours == 0 {
+            return Err("Backup interval must be at least an hour".to_string());
+        }
+        if self.location.as_deref().is_some_and(|l| !Path::new(l.trim()).is_absolute()) {
+            return Err("Backup location must be an absolute path".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            debounce_minutes: default_snapshot_debounce_minutes(),
+            keep: default_snapshot_keep(),
+        }
+    }
+}
+
+impl Default for OcrConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            command: default_ocr_command(),
+            languages: default_ocr_languages(),
+        }
+    }
+}
+
+impl OcrConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.command.trim().is_empty() {
+            return Err("OCR command must not be empty".to_string());
+        }
+        let valid_language = |code: &str| {
+            !code.is_empty() && code.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        };
+        if !self.languages.split('+').all(valid_language) {
+            return Err(format!("Invalid OCR languages: {}", self.languages));
+        }
+        Ok(())
+    }
+}
+
+impl PublishingConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.target {
+            None => Ok(()),
+            Some(PublishTarget::Folder { path }) if path.trim().is_empty() => {
+                Err("Publish folder must not be empty".to_string())
+            }
+            Some(PublishTarget::Git { branch, .. })
+                if !git2::Branch::name_is_valid(branch).unwrap_or(false) =>
+            {
+                Err(format!("Invalid publish branch name: {}", branch))
+            }
+            Some(PublishTarget::Sftp { host, user, remote_dir, .. })
+                if [host, user, remote_dir].iter().any(|v| v.trim().is_empty()) =>
+            {
+                Err("SFTP publishing needs a host, user and remote directory".to_string())
+            }
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+impl PluginsConfig {
+    /// Look up a script by name
+    pub fn script(&self, name: &str) -> Option<&ScriptPlugin> {
+        self.scripts.iter().find(|s| s.name == name)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let mut names = std::collections::HashSet::new();
+        for script in &self.scripts {
+            if script.name.trim().is_empty() || script.command.trim().is_empty() {
+                return Err("Plugin scripts need a name and a command".to_string());
+            }
+            if !names.insert(script.name.as_str()) {
+                return Err(format!("Duplicate plugin script name: {}", script.name));
+            }
+            if script.timeout_secs == 0 {
+                return Err(format!("Plugin script {} needs a timeout above zero", script.name));
+            }
+            // A save transform that can't see or change the note does nothing
+            let transforms = [Capability::NoteRead, Capability::NoteWrite];
+            if script.hook == ScriptHook::NoteSave
+                && !transforms.iter().all(|c| script.capabilities.contains(c))
+            {
+                return Err(format!(
+                    "Save hook {} needs the note:read and note:write capabilities",
+                    script.name
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AttachmentsConfig {
+    /// Folders must stay inside the vault
+    pub fn validate(&self) -> Result<(), String> {
+        for (label, value) in [("folder", &self.folder), ("subfolder", &self.subfolder)] {
+            let path = Path::new(value);
+            let escapes = path.is_absolute()
+                || path.components().any(|c| matches!(c, Component::ParentDir));
+            if escapes {
+                return Err(format!("Attachment {} must be inside the vault: {}", label, value));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for LocaleConfig {
+    fn default() -> Self {
+        Self {
+            locale: default_locale(),
+            date_format: default_display_date_format(),
+            time_format: default_time_format(),
+            week_start: WeekStart::default(),
+        }
+    }
+}
+
+impl LocaleConfig {
+    /// Parse the configured locale, accepting `en-US` as well as `en_US`
+    pub fn chrono_locale(&self) -> Option<Locale> {
+        Locale::try_from(self.locale.replace('-', "_").as_str()).ok()
+    }
+
+    /// Format a date with localized month and weekday names
+    pub fn format_date(&self, date: &NaiveDate, format: &str) -> String {
+        match self.chrono_locale() {
+            Some(locale) => date.format_localized(format, locale).to_string(),
+            None => date.format(format).to_string(),
+        }
+    }
+
+    /// Date display string
+    pub fn display_date(&self, date: &NaiveDate) -> String {
+        self.format_date(date, &self.date_format)
+    }
+
+    /// Display string for an ISO 8601 timestamp, in local time
+    pub fn display_timestamp(&self, timestamp: &str) -> Option<String> {
+        let local = DateTime::parse_from_rfc3339(timestamp)
+            .ok()?
+            .with_timezone(&Local);
+        let format = format!("{} {}", self.date_format, self.time_format);
+        Some(match self.chrono_locale() {
+            Some(locale) => local.format_localized(&format, locale).to_string(),
+            None => local.format(&format).to_string(),
+        })
+    }
+
+    /// First day of the week containing `date`
+    pub fn week_start_of(&self, date: NaiveDate) -> NaiveDate {
+        let offset = match self.week_start {
+            WeekStart::Monday => date.weekday().num_days_from_monday(),
+            WeekStart::Sunday => date.weekday().num_days_from_sunday(),
+            WeekStart::Saturday => (date.weekday().num_days_from_sunday() + 1) % 7,
+        };
+        date - Duration::days(offset as i64)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.chrono_locale().is_none() {
+            return Err(format!("Unknown locale: {}", self.locale));
+        }
+        for format in [&self.date_format, &self.time_format] {
+            validate_strftime(format)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reject empty or malformed strftime format strings
+pub fn validate_strftime(format: &str) -> Result<(), String> {
+    if format.trim().is_empty() || StrftimeItems::new(format).any(|i| matches!(i, Item::Error)) {
+        return Err(format!("Invalid date format: {}", format));
+    }
+    Ok(())
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            mode: ThemeMode::default(),
+            accent_color: default_accent_color(),
+            custom_css: None,
+        }
+    }
+}
+
+impl ThemeConfig {
+    /// Resolve the custom stylesheet path, if one is configured
+    pub fn custom_css_path(&self) -> Option<PathBuf> {
+        let raw = self.custom_css.as_deref()?.trim();
+        if raw.is_empty() {
+            return None;
+        }
+        let path = PathBuf::from(raw);
+        if path.is_absolute() {
+            Some(path)
+        } else {
+            AppConfig::config_path().parent().map(|dir| dir.join(path))
+        }
+    }
+
+    /// Check accent color syntax and that the custom stylesheet is a readable .css file
+    pub fn validate(&self) -> Result<(), String> {
+        if !is_hex_color(&self.accent_color) {
+            return Err(format!("Invalid accent color: {}", self.accent_color));
+        }
+
+        if let Some(path) = self.custom_css_path() {
+            if !path.is_file() {
+                return Err(format!("Custom CSS file not found: {}", path.display()));
+            }
+            let is_css = path
+                .extension()
+                .map(|e| e.eq_ignore_ascii_case("css"))
+                .unwrap_or(false);
+            if !is_css {
+                return Err(format!("Custom CSS must be a .css file: {}", path.display()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Check for `#rgb` or `#rrggbb`
+fn is_hex_color(value: &str) -> bool {
+    match value.strip_prefix('#') {
+        Some(hex) => (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => false,
+    }
+}
+
 impl AppConfig {
     /// Get the config file path
     pub fn config_path() -> PathBuf {
@@ -206,17 +1267,67 @@ impl AppConfig {
     
     /// Load config from file, or return default
     pub fn load() -> Self {
+        Self::try_load().unwrap_or_default()
+    }
+
+    /// Load config from file, reporting read and parse failures instead of
+    /// falling back to defaults. A missing file yields the default config.
+    ///
+    /// Files written by an older schema are migrated and written back, with
+    /// the original kept alongside as `config.toml.v<old>.bak`.
+    pub fn try_load() -> Result<Self, String> {
         let path = Self::config_path();
-        if path.exists() {
-            match fs::read_to_string(&path) {
-                Ok(content) => toml::from_str(&content).unwrap_or_default(),
-                Err(_) => Self::default(),
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        let (config, migrated_from) = Self::parse(&content)?;
+
+        if let Some(old_version) = migrated_from {
+            let backup = path.with_extension(format!("toml.v{}.bak", old_version));
+            if let Err(e) = fs::copy(&path, &backup).and_then(|_| config.save()) {
+                tracing::warn!(error = %e, "Failed to write migrated config");
             }
-        } else {
-            Self::default()
         }
+
+        Ok(config)
+    }
+
+    /// Parse config TOML, migrating older schema versions. Returns the
+    /// config and, if a migration ran, the version it was upgraded from.
+    pub fn parse(content: &str) -> Result<(Self, Option<u32>), String> {
+        let mut table: toml::Table = toml::from_str(content).map_err(|e| e.to_string())?;
+        let migrated_from = migrate_config(&mut table)?;
+        let config = toml::Value::Table(table)
+            .try_into()
+            .map_err(|e: toml::de::Error| e.to_string())?;
+        Ok((config, migrated_from))
     }
     
+    /// Validate settings that the backend is responsible for
+    pub fn validate(&self) -> Result<(), String> {
+        self.theme.validate()?;
+        self.attachments.validate()?;
+        self.locale.validate()?;
+        self.server.validate()?;
+        self.plugins.validate()?;
+        self.publish.validate()?;
+        self.ocr.validate()?;
+        self.backup.validate()?;
+        self.new_notes.validate()?;
+        self.inbox.validate()?;
+        self.breadcrumbs.validate()?;
+        for (name, schema) in &self.note_types {
+            if name.trim().is_empty() {
+                return Err("Note types need a name".to_string());
+            }
+            if let Some((key, _)) = schema.defaults.iter().find(|(_, v)| v.is_null()) {
+                return Err(format!("Default property {} of type {} has no value", key, name));
+            }
+        }
+        validate_strftime(&self.daily_notes.date_format)
+    }
+
     /// Save config to file
     pub fn save(&self) -> Result<(), std::io::Error> {
         let path = Self::config_path();
@@ -227,3 +1338,320 @@ impl AppConfig {
         fs::write(path, content)
     }
 }
+
+/// Upgrade a raw config table to `CONFIG_VERSION` in place.
+/// Files from a newer version are left untouched.
+fn migrate_config(table: &mut toml::Table) -> Result<Option<u32>, String> {
+    let version = match table.get("version") {
+        None => 0,
+        Some(toml::Value::Integer(v)) if *v >= 0 => *v as u32,
+        Some(other) => return Err(format!("Invalid config version: {}", other)),
+    };
+
+    if version >= CONFIG_VERSION {
+        return Ok(None);
+    }
+
+    for step in &CONFIG_MIGRATIONS[version as usize..] {
+        step(table);
+    }
+    table.insert(
+        "version".to_string(),
+        toml::Value::Integer(CONFIG_VERSION as i64),
+    );
+
+    Ok(Some(version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_migrations_cover_every_version() {
+        assert_eq!(CONFIG_MIGRATIONS.len(), CONFIG_VERSION as usize);
+    }
+
+    #[test]
+    fn test_parse_unversioned_config() {
+        let (config, migrated_from) =
+            AppConfig::parse("[editor]\nfont_size = 18\n").unwrap();
+        assert_eq!(migrated_from, Some(0));
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.editor.font_size, 18);
+    }
+
+    #[test]
+    fn test_parse_current_config() {
+        let content = toml::to_string_pretty(&AppConfig::default()).unwrap();
+        let (config, migrated_from) = AppConfig::parse(&content).unwrap();
+        assert!(migrated_from.is_none());
+        assert_eq!(config, AppConfig::default());
+    }
+
+    #[test]
+    fn test_parse_newer_config_is_not_downgraded() {
+        let content = format!("version = {}\n", CONFIG_VERSION + 1);
+        let (config, migrated_from) = AppConfig::parse(&content).unwrap();
+        assert!(migrated_from.is_none());
+        assert_eq!(config.version, CONFIG_VERSION + 1);
+    }
+
+    #[test]
+    fn test_parse_invalid_version() {
+        assert!(AppConfig::parse("version = \"one\"\n").is_err());
+    }
+
+    #[test]
+    fn test_default_theme() {
+        let theme = ThemeConfig::default();
+        assert_eq!(theme.mode, ThemeMode::System);
+        assert!(theme.custom_css.is_none());
+        assert!(theme.validate().is_ok());
+    }
+
+    #[test]
+    fn test_theme_from_toml() {
+        let config: AppConfig =
+            toml::from_str("[theme]\nmode = \"dark\"\naccent_color = \"#3a7\"\n").unwrap();
+        assert_eq!(config.theme.mode, ThemeMode::Dark);
+        assert_eq!(config.theme.accent_color, "#3a7");
+    }
+
+    #[test]
+    fn test_attachment_folders_must_stay_in_vault() {
+        assert!(AttachmentsConfig::default().validate().is_ok());
+
+        let config = AttachmentsConfig {
+            folder: "../outside".to_string(),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = AttachmentsConfig {
+            subfolder: "/tmp".to_string(),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_locale_formatting() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
+
+        let locale = LocaleConfig::default();
+        assert_eq!(locale.display_date(&date), "Mar 9, 2026");
+
+        let german = LocaleConfig {
+            locale: "de-DE".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(german.format_date(&date, "%A, %d. %B"), "Montag, 09. März");
+    }
+
+    #[test]
+    fn test_week_start_of() {
+        // 2026-03-11 is a Wednesday
+        let date = NaiveDate::from_ymd_opt(2026, 3, 11).unwrap();
+        let mut locale = LocaleConfig::default();
+        assert_eq!(locale.week_start_of(date), NaiveDate::from_ymd_opt(2026, 3, 9).unwrap());
+
+        locale.week_start = WeekStart::Sunday;
+        assert_eq!(locale.week_start_of(date), NaiveDate::from_ymd_opt(2026, 3, 8).unwrap());
+
+        locale.week_start = WeekStart::Saturday;
+        assert_eq!(locale.week_start_of(date), NaiveDate::from_ymd_opt(2026, 3, 7).unwrap());
+    }
+
+    #[test]
+    fn test_locale_validation() {
+        assert!(LocaleConfig::default().validate().is_ok());
+
+        let unknown = LocaleConfig {
+            locale: "xx_YY".to_string(),
+            ..Default::default()
+        };
+        assert!(unknown.validate().is_err());
+
+        let bad_format = LocaleConfig {
+            date_format: "%Q".to_string(),
+            ..Default::default()
+        };
+        assert!(bad_format.validate().is_err());
+    }
+
+    #[test]
+    fn test_server_token_validation() {
+        assert!(ServerConfig::default().validate().is_ok());
+
+        let mut server = ServerConfig {
+            enabled: true,
+            token: "short".to_string(),
+            ..Default::default()
+        };
+        assert!(server.validate().is_err());
+
+        server.token = "a-long-enough-secret".to_string();
+        assert!(server.validate().is_ok());
+    }
+
+    #[test]
+    fn test_plugins_config() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            [[plugins.scripts]]
+            name = "format"
+            hook = "note_save"
+            command = "prettier"
+            args = ["--parser", "markdown"]
+            capabilities = ["note:read", "note:write"]
+            "#,
+        )
+        .unwrap();
+        let script = config.plugins.script("format").unwrap();
+        assert_eq!(script.hook, ScriptHook::NoteSave);
+        assert_eq!(script.timeout_secs, 10);
+        assert!(config.plugins.validate().is_ok());
+
+        let mut plugins = config.plugins.clone();
+        plugins.scripts[0].capabilities = vec![Capability::NoteRead];
+        assert!(plugins.validate().is_err());
+
+        let mut plugins = config.plugins.clone();
+        plugins.scripts.push(plugins.scripts[0].clone());
+        assert!(plugins.validate().is_err());
+    }
+
+    #[test]
+    fn test_publish_config() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            [publish]
+            format = "markdown"
+
+            [publish.target]
+            type = "git"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.publish.format, PublishFormat::Markdown);
+        assert_eq!(
+            config.publish.target,
+            Some(PublishTarget::Git {
+                branch: "gh-pages".to_string(),
+                push: false
+            })
+        );
+        assert!(config.publish.validate().is_ok());
+
+        let mut publish = config.publish.clone();
+        publish.target = Some(PublishTarget::Git {
+            branch: "bad..name".to_string(),
+            push: false,
+        });
+        assert!(publish.validate().is_err());
+
+        publish.target = Some(PublishTarget::Sftp {
+            host: "example.com".to_string(),
+            port: 22,
+            user: String::new(),
+            remote_dir: "/var/www".to_string(),
+            key_file: None,
+        });
+        assert!(publish.validate().is_err());
+    }
+
+    #[test]
+    fn test_ocr_languages_validation() {
+        let mut ocr = OcrConfig::default();
+        assert!(ocr.validate().is_ok());
+
+        ocr.languages = "eng+chi_sim".to_string();
+        assert!(ocr.validate().is_ok());
+
+        ocr.languages = "eng+".to_string();
+        assert!(ocr.validate().is_err());
+
+        ocr.languages = "eng; rm -rf".to_string();
+        assert!(ocr.validate().is_err());
+    }
+
+    #[test]
+    fn test_backup_validation() {
+        let mut backup = BackupConfig::default();
+        assert!(backup.validate().is_ok());
+        assert!(backup.dir().ends_with("chronicle/backups"));
+
+        backup.location = Some("backups".to_string());
+        assert!(backup.validate().is_err());
+
+        backup.location = Some(std::env::temp_dir().to_string_lossy().to_string());
+        assert!(backup.validate().is_ok());
+
+        backup.interval_hours = 0;
+        assert!(backup.validate().is_err());
+    }
+
+    #[test]
+    fn test_folder_defaults() {
+        let toml = r#"
+            [[new_notes.folder_defaults]]
+            folder = "meetings"
+            template = "meeting"
+            frontmatter = { type = "meeting" }
+
+            [[new_notes.folder_defaults]]
+            folder = "meetings/standups/"
+            frontmatter = { type = "standup", recurring = true }
+        "#;
+        let config: AppConfig = toml::from_str(toml).unwrap();
+        config.validate().unwrap();
+
+        let new_notes = &config.new_notes;
+        let meeting = new_notes.folder_defaults_for("meetings/kickoff.md").unwrap();
+        assert_eq!(meeting.template.as_deref(), Some("meeting"));
+        let standup = new_notes.folder_defaults_for("meetings/standups/monday.md").unwrap();
+        assert_eq!(standup.frontmatter["recurring"], serde_json::Value::Bool(true));
+        assert!(new_notes.folder_defaults_for("meetings.md").is_none());
+        assert!(new_notes.folder_defaults_for("meetingsarchive/old.md").is_none());
+
+        let mut config = AppConfig::default();
+        config.new_notes.folder_defaults.push(FolderDefaults {
+            folder: "../outside".to_string(),
+            template: None,
+            frontmatter: BTreeMap::new(),
+        });
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_invalid_accent_color() {
+        let theme = ThemeConfig {
+            accent_color: "red".to_string(),
+            ..Default::default()
+        };
+        assert!(theme.validate().is_err());
+    }
+
+    #[test]
+    fn test_custom_css_validation() {
+        let temp = TempDir::new().unwrap();
+        let css = temp.path().join("theme.css");
+        let txt = temp.path().join("theme.txt");
+        fs::write(&css, "body {}").unwrap();
+        fs::write(&txt, "body {}").unwrap();
+
+        let mut theme = ThemeConfig {
+            custom_css: Some(css.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        assert!(theme.validate().is_ok());
+
+        theme.custom_css = Some(txt.to_string_lossy().to_string());
+        assert!(theme.validate().is_err());
+
+        theme.custom_css = Some(temp.path().join("missing.css").to_string_lossy().to_string());
+        assert!(theme.validate().is_err());
+    }
+}
@@ -0,0 +1,190 @@
+//! Script plugins
+//!
+//! Scripts configured under `[[plugins.scripts]]` run at hook points: a
+//! note-save transform, on-demand commands and importers. Each run gets a
+//! JSON `HookRequest` on stdin and answers with a JSON `HookResponse` on
+//! stdout. The host only hands a script the note if it has `note:read`, and
+//! refuses changes it lacks `note:write` or `note:create` for. Scripts aren't
+//! told where the vault is and don't run from it, so the request is their
+//! only view of it.
+
+pub mod runner;
+
+pub use runner::run_script;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ChronicleError;
+use crate::models::{Capability, ScriptHook, ScriptPlugin};
+
+/// A note passed to or from a script
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HookNote {
+    pub path: String,
+    pub content: String,
+}
+
+/// What a script gets on stdin
+#[derive(Debug, Clone, Serialize)]
+pub struct HookRequest {
+    pub hook: ScriptHook,
+    /// The current note, for scripts with `note:read`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<HookNote>,
+    /// File to import, for importers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+/// A note a script wants created
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewHookNote {
+    pub title: String,
+    #[serde(default)]
+    pub content: Option<String>,
+}
+
+/// What a script may print on stdout; every field is optional
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HookResponse {
+    /// New content for the current note (`note:write`)
+    pub content: Option<String>,
+    /// Notes to create (`note:create`)
+    pub notes: Vec<NewHookNote>,
+    /// Shown to the user
+    pub message: Option<String>,
+}
+
+impl HookRequest {
+    /// Build a request, leaving the note out unless the script may read it
+    pub fn new(plugin: &ScriptPlugin, note: Option<HookNote>, file: Option<String>) -> Self {
+        Self {
+            hook: plugin.hook,
+            note: note.filter(|_| plugin.capabilities.contains(&Capability::NoteRead)),
+            file,
+        }
+    }
+}
+
+/// Reject a response that uses capabilities the script wasn't given
+pub fn check_capabilities(plugin: &ScriptPlugin, response: &HookResponse) -> Result<(), ChronicleError> {
+    let denied = |capability: &str| {
+        ChronicleError::CapabilityDenied(format!("{} needs {}", plugin.name, capability))
+    };
+    if response.content.is_some() && !plugin.capabilities.contains(&Capability::NoteWrite) {
+        return Err(denied("note:write"));
+    }
+    if !response.notes.is_empty() && !plugin.capabilities.contains(&Capability::NoteCreate) {
+        return Err(denied("note:create"));
+    }
+    Ok(())
+}
+
+/// Run every save hook over `content` in config order. A failing script is
+/// logged and skipped so plugins can never stop a note from being saved.
+pub fn transform_on_save(scripts: &[ScriptPlugin], path: &str, content: String) -> String {
+    scripts
+        .iter()
+        .filter(|s| s.hook == ScriptHook::NoteSave)
+        .fold(content, |content, plugin| {
+            let note = HookNote {
+                path: path.to_string(),
+                content: content.clone(),
+            };
+            let request = HookRequest::new(plugin, Some(note), None);
+            let result = run_script(plugin, &request)
+                .and_then(|response| check_capabilities(plugin, &response).map(|_| response));
+
+            match result {
+                Ok(HookResponse {
+                    content: Some(transformed),
+                    ..
+                }) => transformed,
+                Ok(_) => content,
+                Err(e) => {
+                    tracing::warn!(plugin = %plugin.name, path = %path, error = %e, "Save hook failed");
+                    content
+                }
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(hook: ScriptHook, capabilities: &[Capability]) -> ScriptPlugin {
+        ScriptPlugin {
+            name: "test".to_string(),
+            hook,
+            command: "sh".to_string(),
+            args: Vec::new(),
+            capabilities: capabilities.to_vec(),
+            extensions: Vec::new(),
+            timeout_secs: 5,
+        }
+    }
+
+    #[test]
+    fn test_request_withholds_note_without_read() {
+        let note = HookNote {
+            path: "a.md".to_string(),
+            content: "secret".to_string(),
+        };
+        let plugin = script(ScriptHook::Command, &[]);
+        let request = HookRequest::new(&plugin, Some(note.clone()), None);
+        assert!(request.note.is_none());
+
+        let plugin = script(ScriptHook::Command, &[Capability::NoteRead]);
+        let request = HookRequest::new(&plugin, Some(note), None);
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["hook"], "command");
+        assert_eq!(json["note"]["content"], "secret");
+        assert!(json.get("file").is_none());
+        assert!(json.get("vault").is_none());
+    }
+
+    #[test]
+    fn test_check_capabilities() {
+        let write = HookResponse {
+            content: Some("new".to_string()),
+            ..Default::default()
+        };
+        let create = HookResponse {
+            notes: vec![NewHookNote {
+                title: "New".to_string(),
+                content: None,
+            }],
+            ..Default::default()
+        };
+        let message = HookResponse {
+            message: Some("done".to_string()),
+            ..Default::default()
+        };
+
+        let reader = script(ScriptHook::Command, &[Capability::NoteRead]);
+        assert!(check_capabilities(&reader, &write).is_err());
+        assert!(check_capabilities(&reader, &create).is_err());
+        assert!(check_capabilities(&reader, &message).is_ok());
+
+        let writer = script(ScriptHook::Command, &[Capability::NoteWrite, Capability::NoteCreate]);
+        assert!(check_capabilities(&writer, &write).is_ok());
+        assert!(check_capabilities(&writer, &create).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_transform_on_save() {
+        let mut formatter = script(ScriptHook::NoteSave, &[Capability::NoteRead, Capability::NoteWrite]);
+        formatter.args = vec![
+            "-c".to_string(),
+            r##"cat > /dev/null; printf '{"content":"# Formatted\\n"}'"##.to_string(),
+        ];
+        let mut broken = formatter.clone();
+        broken.args = vec!["-c".to_string(), "exit 3".to_string()];
+
+        let content = transform_on_save(&[broken, formatter], "a.md", "# a".to_string());
+        assert_eq!(content, "# Formatted\n");
+    }
+}
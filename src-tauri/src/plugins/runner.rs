@@ -0,0 +1,144 @@
+//! Running a plugin script as a child process
+
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{HookRequest, HookResponse};
+use crate::error::ChronicleError;
+use crate::models::ScriptPlugin;
+
+/// Longest stderr excerpt kept in an error
+const MAX_STDERR_LEN: usize = 500;
+
+/// Run a script with `request` on stdin and parse its stdout. Empty output
+/// is an empty response. Scripts run from the temp dir rather than the
+/// vault, which they only see through the request.
+pub fn run_script(
+    plugin: &ScriptPlugin,
+    request: &HookRequest,
+) -> Result<HookResponse, ChronicleError> {
+    let failed = |detail: String| ChronicleError::PluginFailed(format!("{}: {}", plugin.name, detail));
+
+    let input = serde_json::to_vec(request)?;
+    let mut child = Command::new(&plugin.command)
+        .args(&plugin.args)
+        .current_dir(std::env::temp_dir())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| failed(format!("could not start {}: {}", plugin.command, e)))?;
+
+    // Each pipe gets its own thread so a script that writes before it
+    // finishes reading can't deadlock against us
+    let mut stdin = child.stdin.take();
+    let writer = thread::spawn(move || {
+        if let Some(stdin) = stdin.as_mut() {
+            // A script that ignores its input closes the pipe early
+            let _ = stdin.write_all(&input);
+        }
+    });
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let deadline = Instant::now() + Duration::from_secs(plugin.timeout_secs);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(failed(format!("timed out after {}s", plugin.timeout_secs)));
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+
+    let _ = writer.join();
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    if !status.success() {
+        let mut detail = format!("exited with {}", status);
+        let stderr = String::from_utf8_lossy(&stderr);
+        let stderr = stderr.trim();
+        if !stderr.is_empty() {
+            let excerpt: String = stderr.chars().take(MAX_STDERR_LEN).collect();
+            detail.push_str(&format!(": {}", excerpt));
+        }
+        return Err(failed(detail));
+    }
+
+    if stdout.iter().all(u8::is_ascii_whitespace) {
+        return Ok(HookResponse::default());
+    }
+    serde_json::from_slice(&stdout).map_err(|e| failed(format!("invalid response: {}", e)))
+}
+
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::models::{Capability, ScriptHook};
+    use crate::plugins::HookNote;
+
+    fn shell(script: &str, timeout_secs: u64) -> ScriptPlugin {
+        ScriptPlugin {
+            name: "test".to_string(),
+            hook: ScriptHook::Command,
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            capabilities: vec![Capability::NoteRead],
+            extensions: Vec::new(),
+            timeout_secs,
+        }
+    }
+
+    fn request(plugin: &ScriptPlugin) -> HookRequest {
+        let note = HookNote {
+            path: "a.md".to_string(),
+            content: "hello".to_string(),
+        };
+        HookRequest::new(plugin, Some(note), None)
+    }
+
+    #[test]
+    fn test_run_script_round_trip() {
+        // Echo the note content back as the message
+        let plugin = shell(
+            r#"input=$(cat); case "$input" in *'"content":"hello"'*) printf '{"message":"got it"}';; esac"#,
+            5,
+        );
+        let response = run_script(&plugin, &request(&plugin)).unwrap();
+        assert_eq!(response.message.as_deref(), Some("got it"));
+
+        let silent = shell("cat > /dev/null", 5);
+        let response = run_script(&silent, &request(&silent)).unwrap();
+        assert_eq!(response, HookResponse::default());
+    }
+
+    #[test]
+    fn test_run_script_failures() {
+        let failing = shell("echo broken >&2; exit 2", 5);
+        let err = run_script(&failing, &request(&failing)).unwrap_err();
+        assert!(err.to_string().contains("broken"));
+
+        let garbage = shell("echo not json", 5);
+        assert!(run_script(&garbage, &request(&garbage)).is_err());
+
+        let slow = shell("sleep 5", 1);
+        let err = run_script(&slow, &request(&slow)).unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+    }
+}
@@ -0,0 +1,277 @@
+//! A note and the notes it links to, combined into one printable document
+//!
+//! Notes are gathered breadth-first from the starting notes up to a link
+//! depth, each once. The document opens with a table of contents; links
+//! between included notes jump to their section, and links to anything
+//! else keep only their text.
+
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+
+use super::share::file_stem;
+use super::{escape_html, markdown_to_html, SourceNote, PAGE_CSS};
+use crate::error::ChronicleError;
+use crate::vault::{extract_links, markdown_link_path, parse_frontmatter, LinkKind};
+
+static NOTE_LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"!?\[\[([^\]|]+)(?:\|([^\]]+))?\]\]").expect("Invalid wiki link regex")
+});
+
+static MARKDOWN_LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(!?)\[([^\]]*)\]\((?:<([^>]+)>|([^)\s]+))(?:\s+"[^"]*")?\)"#)
+        .expect("Invalid Markdown link regex")
+});
+
+/// Notes in a collection start on a new page when printed
+const COLLECTION_CSS: &str = "nav.toc ol{padding-left:1.5rem}\
+@media print{section.note{break-before:page}a{color:inherit;text-decoration:none}}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectionFormat {
+    #[default]
+    Html,
+    /// HTML converted by the configured `pdf_command`
+    Pdf,
+}
+
+/// What `export_collection` wrote
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionExport {
+    pub path: String,
+    /// Vault-relative paths of the included notes, in document order
+    pub notes: Vec<String>,
+}
+
+/// Gather `roots` and the notes they link to, up to `max_depth` links away.
+/// `load` reads a note by path; `resolve` gives the note a link from one
+/// note points at, as `(from, target, kind)` like `extract_links` reports.
+pub fn gather_collection(
+    roots: &[String],
+    max_depth: usize,
+    mut load: impl FnMut(&str) -> Option<SourceNote>,
+    resolve: &impl Fn(&str, &str, LinkKind) -> Option<String>,
+) -> Vec<SourceNote> {
+    let mut seen: HashSet<String> = roots.iter().cloned().collect();
+    let mut queue: VecDeque<(String, usize)> = roots.iter().map(|r| (r.clone(), 0)).collect();
+    let mut notes = Vec::new();
+
+    while let Some((path, depth)) = queue.pop_front() {
+        let Some(note) = load(&path) else {
+            continue;
+        };
+        if depth < max_depth {
+            for link in extract_links(&note.content) {
+                if let Some(target) = resolve(&note.path, &link.target, link.kind) {
+                    if seen.insert(target.clone()) {
+                        queue.push_back((target, depth + 1));
+                    }
+                }
+            }
+        }
+        notes.push(note);
+    }
+    notes
+}
+
+/// Render gathered notes as one HTML document with a table of contents
+pub fn render_collection(
+    title: &str,
+    notes: &[SourceNote],
+    resolve: &impl Fn(&str, &str, LinkKind) -> Option<String>,
+) -> String {
+    let anchors: HashMap<&str, String> = notes
+        .iter()
+        .enumerate()
+        .map(|(i, note)| (note.path.as_str(), format!("note-{}", i + 1)))
+        .collect();
+
+    let mut toc = String::new();
+    let mut sections = String::new();
+    for note in notes {
+        let anchor = &anchors[note.path.as_str()];
+        let title = escape_html(&note.title);
+        toc.push_str(&format!("<li><a href=\"#{}\">{}</a></li>\n", anchor, title));
+
+        let (_, body_start) = parse_frontmatter(&note.content);
+        let body = drop_leading_title(&note.content[body_start..]);
+        let body = link_sections(body, &note.path, &anchors, resolve);
+        sections.push_str(&format!(
+            "<section class=\"note\" id=\"{}\">\n<h1>{}</h1>\n{}</section>\n",
+            anchor,
+            title,
+            markdown_to_html(&body)
+        ));
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>{css}{collection_css}</style>\n</head>\n<body>\n\
+         <nav class=\"toc\">\n<h1>{title}</h1>\n<ol>\n{toc}</ol>\n</nav>\n{sections}</body>\n\
+         </html>\n",
+        title = escape_html(title),
+        css = PAGE_CSS,
+        collection_css = COLLECTION_CSS,
+    )
+}
+
+/// Write a rendered collection into `dest_dir` as `<title>.html`, or as a
+/// PDF made by running `pdf_command <input.html> <output.pdf>`
+pub fn write_collection(
+    title: &str,
+    html: &str,
+    format: CollectionFormat,
+    pdf_command: &str,
+    dest_dir: &Path,
+) -> Result<String, ChronicleError> {
+    let stem = file_stem(title);
+    fs::create_dir_all(dest_dir)?;
+    let html_path = dest_dir.join(format!("{}.html", stem));
+    if format == CollectionFormat::Html {
+        fs::write(&html_path, html)?;
+        return Ok(html_path.to_string_lossy().to_string());
+    }
+
+    let source = dest_dir.join(format!(".{}.print.html", stem));
+    let pdf_path = dest_dir.join(format!("{}.pdf", stem));
+    fs::write(&source, html)?;
+    let output = Command::new(pdf_command)
+        .arg(&source)
+        .arg(&pdf_path)
+        .stdin(Stdio::null())
+        .output();
+    let _ = fs::remove_file(&source);
+
+    let output = output.map_err(|e| {
+        ChronicleError::PublishFailed(format!("could not start {}: {}", pdf_command, e))
+    })?;
+    if !output.status.success() {
+        return Err(ChronicleError::PublishFailed(format!(
+            "{} failed: {}",
+            pdf_command,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(pdf_path.to_string_lossy().to_string())
+}
+
+/// Drop a `# Title` heading opening the note, since its section has one
+fn drop_leading_title(body: &str) -> &str {
+    let trimmed = body.trim_start();
+    match trimmed.split_once('\n') {
+        Some((first, rest)) if first.starts_with("# ") => rest,
+        None if trimmed.starts_with("# ") => "",
+        _ => body,
+    }
+}
+
+/// Point links to included notes at their sections; other note links keep
+/// only their text, as do note embeds. Images and web links are left
+/// alone.
+fn link_sections(
+    body: &str,
+    from: &str,
+    anchors: &HashMap<&str, String>,
+    resolve: &impl Fn(&str, &str, LinkKind) -> Option<String>,
+) -> String {
+    let anchor_for = |target: &str, kind| {
+        resolve(from, target, kind).and_then(|path| anchors.get(path.as_str()))
+    };
+
+    let body = NOTE_LINK_RE.replace_all(body, |caps: &Captures| {
+        let target = caps[1].trim();
+        let text = match caps.get(2) {
+            Some(m) => m.as_str().trim(),
+            None => target.split('#').next().filter(|t| !t.is_empty()).unwrap_or(target),
+        };
+        match anchor_for(target, LinkKind::Wiki) {
+            Some(anchor) => format!("[{}](#{})", text, anchor),
+            None => text.to_string(),
+        }
+    });
+    MARKDOWN_LINK_RE
+        .replace_all(&body, |caps: &Captures| {
+            let raw = caps.get(3).or(caps.get(4)).map_or("", |m| m.as_str());
+            let Some(target) = markdown_link_path(raw).filter(|_| &caps[1] != "!") else {
+                return caps[0].to_string();
+            };
+            match anchor_for(&target, LinkKind::Markdown) {
+                Some(anchor) => format!("[{}](#{})", &caps[2], anchor),
+                None => caps[2].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(path: &str, content: &str) -> SourceNote {
+        SourceNote {
+            path: path.to_string(),
+            title: path.trim_end_matches(".md").to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    fn vault() -> Vec<SourceNote> {
+        vec![
+            note("a.md", "# A\n\nSee [[b]] and [c](c.md), and [[a]] again."),
+            note("b.md", "---\ntags: [x]\n---\nBack to [[a|the start]], on to [[d#Intro]]."),
+            note("c.md", "An ![image](pic.png) and [a site](https://example.com)."),
+            note("d.md", "The end."),
+        ]
+    }
+
+    fn resolve(from: &str, target: &str, kind: LinkKind) -> Option<String> {
+        let path = match kind {
+            LinkKind::Wiki => format!("{}.md", target.split('#').next().unwrap_or_default()),
+            LinkKind::Markdown => crate::vault::resolve_markdown_link(from, target)?,
+        };
+        vault().iter().any(|n| n.path == path).then_some(path)
+    }
+
+    fn load(path: &str) -> Option<SourceNote> {
+        vault().into_iter().find(|n| n.path == path)
+    }
+
+    #[test]
+    fn test_gather_collection() {
+        let paths = |notes: Vec<SourceNote>| -> Vec<String> {
+            notes.into_iter().map(|n| n.path).collect()
+        };
+        let roots = vec!["a.md".to_string()];
+
+        assert_eq!(paths(gather_collection(&roots, 0, load, &resolve)), vec!["a.md"]);
+        assert_eq!(
+            paths(gather_collection(&roots, 1, load, &resolve)),
+            vec!["a.md", "b.md", "c.md"]
+        );
+        assert_eq!(
+            paths(gather_collection(&roots, 5, load, &resolve)),
+            vec!["a.md", "b.md", "c.md", "d.md"]
+        );
+    }
+
+    #[test]
+    fn test_render_collection() {
+        let notes = gather_collection(&["a.md".to_string()], 1, load, &resolve);
+        let html = render_collection("Trail", &notes, &resolve);
+
+        assert!(html.contains("<title>Trail</title>"));
+        assert!(html.contains("<li><a href=\"#note-2\">b</a></li>"));
+        assert!(html.contains("<section class=\"note\" id=\"note-1\">\n<h1>a</h1>\n<p>See"));
+        assert!(html.contains("<a href=\"#note-2\">b</a> and <a href=\"#note-3\">c</a>"));
+        // Frontmatter is dropped, and `d` isn't included so its link is text
+        assert!(!html.contains("tags:"));
+        assert!(html.contains("<a href=\"#note-1\">the start</a>, on to d."));
+        assert!(html.contains("<img src=\"pic.png\""));
+        assert!(html.contains("<a href=\"https://example.com\">a site</a>"));
+    }
+}
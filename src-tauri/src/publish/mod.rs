@@ -0,0 +1,530 @@
+//! Selective publishing
+//!
+//! Only notes with `publish: true` in their frontmatter are published. They
+//! are rendered to HTML or to Markdown with wiki links rewritten, and sent to
+//! the configured target (see `targets`). A link to a note that isn't
+//! published is reduced to its text, so publishing never exposes the path of
+//! a private note.
+//!
+//! What was last sent is recorded in `.chronicle/publish.json`; status
+//! compares a fresh render against it.
+
+mod collection;
+mod render;
+mod share;
+mod targets;
+
+pub use collection::*;
+pub use render::*;
+pub use share::*;
+
+use chrono::Utc;
+use regex::Regex;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use crate::db::notes::list_notes;
+use crate::error::ChronicleError;
+use crate::models::{PublishFormat, PublishTarget, PublishingConfig};
+use crate::vault::{get_frontmatter_property, note_zettel_id, parse_frontmatter};
+
+static WIKI_LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[\[([^\]|#]+)(#[^\]|]*)?(?:\|([^\]]+))?\]\]").expect("Invalid wiki link regex")
+});
+
+/// A note marked for publishing
+#[derive(Debug, Clone)]
+pub struct SourceNote {
+    pub path: String,
+    pub title: String,
+    pub content: String,
+}
+
+/// A rendered file, with its path relative to the publish root
+#[derive(Debug, Clone)]
+pub struct PublishedFile {
+    pub path: String,
+    /// Note the file was rendered from; `None` for the index page
+    pub source: Option<String>,
+    pub contents: Vec<u8>,
+}
+
+/// Files sent by the last publish, keyed by output path
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PublishManifest {
+    pub published_at: Option<String>,
+    /// Where they were sent; a different target starts from scratch
+    #[serde(default)]
+    pub target: Option<PublishTarget>,
+    pub files: BTreeMap<String, ManifestEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub source: Option<String>,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PublishState {
+    /// Published and unchanged since
+    Published,
+    /// Published, but the note (or a note it links to) has changed
+    Stale,
+    /// Marked for publishing but never published
+    New,
+    /// Published before but no longer marked; removed on the next publish
+    Withdrawn,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotePublishStatus {
+    pub path: String,
+    pub title: String,
+    pub state: PublishState,
+    /// Path of the published file, relative to the publish root
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishStatusReport {
+    pub published_at: Option<String>,
+    pub notes: Vec<NotePublishStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishReport {
+    /// Files written because they were new or changed
+    pub updated: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+}
+
+/// Whether a note opts in to publishing
+pub fn is_marked_published(content: &str) -> bool {
+    get_frontmatter_property(content, "publish").is_some_and(|v| v.trim() == "true")
+}
+
+/// Every indexed note marked for publishing
+pub fn collect_notes(vault_path: &Path, conn: &Connection) -> Result<Vec<SourceNote>, ChronicleError> {
+    let mut notes = Vec::new();
+    for meta in list_notes(conn)? {
+        let Ok(content) = fs::read_to_string(vault_path.join(&meta.path)) else {
+            continue;
+        };
+        if is_marked_published(&content) {
+            notes.push(SourceNote {
+                path: meta.path,
+                title: meta.title,
+                content,
+            });
+        }
+    }
+    notes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(notes)
+}
+
+/// Render the notes, plus an index page for HTML
+pub fn render_site(notes: &[SourceNote], config: &PublishingConfig) -> Vec<PublishedFile> {
+    let outputs: HashMap<&str, String> = notes
+        .iter()
+        .map(|n| (n.path.as_str(), output_path(&n.path, config.format)))
+        .collect();
+
+    // Link targets a note can be reached by: its path, its filename and
+    // its Zettelkasten ID. The first note claiming a key keeps it.
+    let mut targets: HashMap<String, &str> = HashMap::new();
+    for note in notes {
+        let output = outputs[note.path.as_str()].as_str();
+        let without_ext = note.path.strip_suffix(".md").unwrap_or(&note.path);
+        let stem = without_ext.rsplit('/').next().unwrap_or(without_ext);
+        let filename = note.path.rsplit('/').next().unwrap_or(&note.path);
+        let mut keys = vec![without_ext.to_lowercase(), stem.to_lowercase()];
+        keys.extend(note_zettel_id(&note.content, filename).map(|id| id.to_lowercase()));
+        for key in keys {
+            targets.entry(key).or_insert(output);
+        }
+    }
+
+    let mut files: Vec<PublishedFile> = notes
+        .iter()
+        .map(|note| {
+            let output = &outputs[note.path.as_str()];
+            let markdown = rewrite_links(&note.content, output, &targets);
+            let contents = match config.format {
+                PublishFormat::Markdown => markdown,
+                PublishFormat::Html => {
+                    let (_, body_start) = parse_frontmatter(&markdown);
+                    html_page(&note.title, &markdown_to_html(&markdown[body_start..]), output, config)
+                }
+            };
+            PublishedFile {
+                path: output.clone(),
+                source: Some(note.path.clone()),
+                contents: contents.into_bytes(),
+            }
+        })
+        .collect();
+
+    if config.format == PublishFormat::Html {
+        files.push(PublishedFile {
+            path: "index.html".to_string(),
+            source: None,
+            contents: index_page(notes, &outputs, config).into_bytes(),
+        });
+    }
+    files
+}
+
+/// Per-note status: what a publish now would add, update or withdraw
+pub fn publish_status(
+    notes: &[SourceNote],
+    files: &[PublishedFile],
+    manifest: &PublishManifest,
+) -> PublishStatusReport {
+    let mut statuses: Vec<NotePublishStatus> = files
+        .iter()
+        .filter_map(|file| {
+            let source = file.source.as_ref()?;
+            let title = notes.iter().find(|n| &n.path == source).map(|n| n.title.clone())?;
+            let state = match manifest.files.get(&file.path) {
+                None => PublishState::New,
+                Some(entry) if entry.hash == content_hash(&file.contents) => PublishState::Published,
+                Some(_) => PublishState::Stale,
+            };
+            Some(NotePublishStatus {
+                path: source.clone(),
+                title,
+                state,
+                output: file.path.clone(),
+            })
+        })
+        .collect();
+
+    for (output, entry) in &manifest.files {
+        let Some(source) = &entry.source else { continue };
+        if !files.iter().any(|f| &f.path == output) {
+            statuses.push(NotePublishStatus {
+                path: source.clone(),
+                title: source.clone(),
+                state: PublishState::Withdrawn,
+                output: output.clone(),
+            });
+        }
+    }
+
+    PublishStatusReport {
+        published_at: manifest.published_at.clone(),
+        notes: statuses,
+    }
+}
+
+/// Render the marked notes and send what changed to the configured target
+pub fn publish(
+    vault_path: &Path,
+    conn: &Connection,
+    config: &PublishingConfig,
+) -> Result<PublishReport, ChronicleError> {
+    let target = config.target.as_ref().ok_or(ChronicleError::NoPublishTarget)?;
+
+    let notes = collect_notes(vault_path, conn)?;
+    let files = render_site(&notes, config);
+    let manifest = PublishManifest::load(vault_path, Some(target));
+
+    let changed: Vec<&PublishedFile> = files
+        .iter()
+        .filter(|f| {
+            manifest.files.get(&f.path).map(|e| e.hash.as_str()) != Some(content_hash(&f.contents).as_str())
+        })
+        .collect();
+    let removed: Vec<String> = manifest
+        .files
+        .keys()
+        .filter(|path| !files.iter().any(|f| &f.path == *path))
+        .cloned()
+        .collect();
+
+    targets::deploy(
+        vault_path,
+        target,
+        config.format,
+        &targets::Deployment {
+            files: &files,
+            changed: &changed,
+            removed: &removed,
+        },
+    )?;
+
+    let report = PublishReport {
+        updated: changed.len(),
+        removed: removed.len(),
+        unchanged: files.len() - changed.len(),
+    };
+    let mut manifest = PublishManifest::from_files(&files);
+    manifest.target = Some(target.clone());
+    manifest.save(vault_path)?;
+    Ok(report)
+}
+
+impl PublishManifest {
+    fn path(vault_path: &Path) -> PathBuf {
+        vault_path.join(".chronicle").join("publish.json")
+    }
+
+    /// The last publish to `target`, or an empty manifest if there was none
+    pub fn load(vault_path: &Path, target: Option<&PublishTarget>) -> Self {
+        let manifest: Self = fs::read_to_string(Self::path(vault_path))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        if manifest.target.as_ref() == target {
+            manifest
+        } else {
+            Self::default()
+        }
+    }
+
+    pub fn save(&self, vault_path: &Path) -> Result<(), ChronicleError> {
+        let path = Self::path(vault_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    fn from_files(files: &[PublishedFile]) -> Self {
+        Self {
+            published_at: Some(Utc::now().to_rfc3339()),
+            target: None,
+            files: files
+                .iter()
+                .map(|f| {
+                    let entry = ManifestEntry {
+                        source: f.source.clone(),
+                        hash: content_hash(&f.contents),
+                    };
+                    (f.path.clone(), entry)
+                })
+                .collect(),
+        }
+    }
+}
+
+fn output_path(note_path: &str, format: PublishFormat) -> String {
+    match format {
+        PublishFormat::Markdown => note_path.to_string(),
+        PublishFormat::Html => {
+            format!("{}.html", note_path.strip_suffix(".md").unwrap_or(note_path))
+        }
+    }
+}
+
+/// Point wiki links at published files; links to anything else keep only
+/// their text
+fn rewrite_links(content: &str, from: &str, targets: &HashMap<String, &str>) -> String {
+    WIKI_LINK_RE
+        .replace_all(content, |caps: &regex::Captures| {
+            let target = caps[1].trim();
+            let text = caps.get(3).map_or(target, |m| m.as_str().trim());
+            let key = target.strip_suffix(".md").unwrap_or(target).to_lowercase();
+            match targets.get(&key) {
+                Some(to) => format!("[{}]({})", text, relative_href(from, to)),
+                None => text.to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Link from one published file to another, relative to the first
+fn relative_href(from: &str, to: &str) -> String {
+    let from_dirs: Vec<&str> = from.split('/').collect();
+    let from_dirs = &from_dirs[..from_dirs.len() - 1];
+    let to_parts: Vec<&str> = to.split('/').collect();
+
+    let common = from_dirs
+        .iter()
+        .zip(&to_parts)
+        .take_while(|(a, b)| a == b)
+        .count()
+        .min(to_parts.len() - 1);
+
+    let mut parts: Vec<String> = vec!["..".to_string(); from_dirs.len() - common];
+    parts.extend(to_parts[common..].iter().map(|p| encode_path_segment(p)));
+    parts.join("/")
+}
+
+fn encode_path_segment(segment: &str) -> String {
+    let mut out = String::new();
+    for c in segment.chars() {
+        match c {
+            ' ' => out.push_str("%20"),
+            '(' => out.push_str("%28"),
+            ')' => out.push_str("%29"),
+            '#' => out.push_str("%23"),
+            '?' => out.push_str("%3F"),
+            '%' => out.push_str("%25"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn markdown_to_html(markdown: &str) -> String {
+    render_markdown(markdown, &NO_LINKS)
+}
+
+fn html_page(title: &str, body: &str, output: &str, config: &PublishingConfig) -> String {
+    let index = relative_href(output, "index.html");
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title} - {site}</title>\n<style>{css}</style>\n</head>\n<body>\n\
+         <nav><a href=\"{index}\">{site}</a></nav>\n<article>\n{body}</article>\n</body>\n</html>\n",
+        title = escape_html(title),
+        site = escape_html(&config.site_title),
+        css = PAGE_CSS,
+    )
+}
+
+fn index_page(notes: &[SourceNote], outputs: &HashMap<&str, String>, config: &PublishingConfig) -> String {
+    let mut sorted: Vec<&SourceNote> = notes.iter().collect();
+    sorted.sort_by_key(|n| n.title.to_lowercase());
+
+    let items: String = sorted
+        .iter()
+        .map(|n| {
+            let href = relative_href("index.html", &outputs[n.path.as_str()]);
+            format!("<li><a href=\"{}\">{}</a></li>\n", href, escape_html(&n.title))
+        })
+        .collect();
+    html_page(&config.site_title, &format!("<ul>\n{}</ul>\n", items), "index.html", config)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// FNV-1a, which unlike the std hasher is stable across Rust releases, so
+/// the manifest stays valid after an update
+fn content_hash(contents: &[u8]) -> String {
+    let hash = contents.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+const PAGE_CSS: &str = "body{font-family:system-ui,sans-serif;max-width:760px;margin:0 auto;\
+padding:2rem;line-height:1.6}nav{margin-bottom:2rem}pre{overflow-x:auto}";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(path: &str, content: &str) -> SourceNote {
+        SourceNote {
+            path: path.to_string(),
+            title: path.trim_end_matches(".md").to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    fn file<'a>(files: &'a [PublishedFile], path: &str) -> &'a str {
+        let file = files.iter().find(|f| f.path == path).unwrap();
+        std::str::from_utf8(&file.contents).unwrap()
+    }
+
+    #[test]
+    fn test_is_marked_published() {
+        assert!(is_marked_published("---\npublish: true\n---\n# A"));
+        assert!(!is_marked_published("---\npublish: false\n---\n# A"));
+        assert!(!is_marked_published("# A\n\npublish: true"));
+    }
+
+    #[test]
+    fn test_markdown_links_resolve_to_published_notes_only() {
+        let notes = vec![
+            note("essays/rust.md", "See [[tools/cargo]], [[Cargo|the tool]] and [[private]]."),
+            note("tools/cargo.md", "# Cargo"),
+        ];
+        let config = PublishingConfig {
+            format: PublishFormat::Markdown,
+            ..Default::default()
+        };
+        let files = render_site(&notes, &config);
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(
+            file(&files, "essays/rust.md"),
+            "See [tools/cargo](../tools/cargo.md), [the tool](../tools/cargo.md) and private."
+        );
+    }
+
+    #[test]
+    fn test_html_pages_and_index() {
+        let notes = vec![
+            note("202501271430 Atoms.md", "---\npublish: true\n---\n# Atoms\n\n<b>&</b>"),
+            note("notes/b.md", "Built from [[202501271430]]."),
+        ];
+        let files = render_site(&notes, &PublishingConfig::default());
+
+        let atoms = file(&files, "202501271430 Atoms.html");
+        assert!(!atoms.contains("publish: true"));
+        assert!(atoms.contains("<h1>Atoms</h1>"));
+        let b = file(&files, "notes/b.html");
+        assert!(b.contains("<a href=\"../202501271430%20Atoms.html\">202501271430</a>"));
+        assert!(b.contains("<a href=\"../index.html\">"));
+        assert!(file(&files, "index.html").contains("<a href=\"notes/b.html\">notes/b</a>"));
+    }
+
+    #[test]
+    fn test_publish_status() {
+        let notes = vec![note("a.md", "A"), note("b.md", "B")];
+        let config = PublishingConfig {
+            format: PublishFormat::Markdown,
+            ..Default::default()
+        };
+        let files = render_site(&notes, &config);
+        let mut manifest = PublishManifest::from_files(&files);
+        manifest.files.get_mut("b.md").unwrap().hash = "old".to_string();
+        manifest.files.insert(
+            "gone.md".to_string(),
+            ManifestEntry {
+                source: Some("gone.md".to_string()),
+                hash: "x".to_string(),
+            },
+        );
+        let notes = [notes, vec![note("c.md", "C")]].concat();
+        let files = render_site(&notes, &config);
+
+        let states: Vec<(String, PublishState)> = publish_status(&notes, &files, &manifest)
+            .notes
+            .into_iter()
+            .map(|s| (s.path, s.state))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                ("a.md".to_string(), PublishState::Published),
+                ("b.md".to_string(), PublishState::Stale),
+                ("c.md".to_string(), PublishState::New),
+                ("gone.md".to_string(), PublishState::Withdrawn),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relative_href() {
+        assert_eq!(relative_href("a.html", "b.html"), "b.html");
+        assert_eq!(relative_href("x/a.html", "x/y/b.html"), "y/b.html");
+        assert_eq!(relative_href("x/y/a.html", "z/b c.html"), "../../z/b%20c.html");
+    }
+}
@@ -0,0 +1,318 @@
+//! Markdown to HTML, with Chronicle's extensions
+//!
+//! The one renderer behind the preview pane and every export. On top of
+//! CommonMark with tables, strikethrough and task lists it renders:
+//!
+//! - `[[wiki links]]` as `<a class="wiki-link">`, marked `unresolved` when
+//!   no note has that name
+//! - `![[embeds]]`: notes inline in `<div class="embed">`, images as
+//!   `<img>` and other files as links
+//! - `> [!note] Title` callouts as `<div class="callout">`
+//! - `#tags` as `<span class="tag">`
+//!
+//! Links and embeds carry the vault-relative path in `data-path` so the
+//! preview can open them. Nothing is rewritten inside code, and as when
+//! indexing, headings hold no tags and all-digit ones (`#123`) don't count.
+
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag, TagEnd};
+use regex::{Captures, Regex};
+use std::sync::LazyLock;
+
+use super::escape_html;
+use crate::vault::{is_image_path, EmbedStatus, ResolvedEmbed};
+
+/// Embeds, wiki links and tags, in the order they can overlap
+static INLINE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(!)?\[\[([^\]|]+)(?:\|([^\]]+))?\]\]|(^|\s)#([\w/-]+)")
+        .expect("Invalid inline extension regex")
+});
+
+static CALLOUT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\[!([\w-]+)\][+-]?[ \t]*(.*)$").expect("Invalid callout regex")
+});
+
+/// How the wiki links and embeds in a note resolve
+pub struct RenderLinks<'a> {
+    /// Vault-relative path of the note a link name points at
+    pub resolve: &'a dyn Fn(&str) -> Option<String>,
+    /// The note's embeds, as found by `resolve_embeds_in`
+    pub embeds: &'a [ResolvedEmbed],
+}
+
+fn resolve_nothing(_: &str) -> Option<String> {
+    None
+}
+
+/// For Markdown whose links were already rewritten, as for publishing
+pub const NO_LINKS: RenderLinks<'static> = RenderLinks {
+    resolve: &resolve_nothing,
+    embeds: &[],
+};
+
+/// Render a note body (without frontmatter) to an HTML fragment
+pub fn render_markdown(markdown: &str, links: &RenderLinks) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let events = merge_text(Parser::new_ext(markdown, options));
+
+    let mut out = Vec::with_capacity(events.len());
+    // Per open block quote, whether it is a callout
+    let mut quotes: Vec<bool> = Vec::new();
+    let (mut in_code, mut in_heading) = (false, false);
+    let mut i = 0;
+    while i < events.len() {
+        match &events[i] {
+            Event::Start(Tag::CodeBlock(_)) => in_code = true,
+            Event::End(TagEnd::CodeBlock) => in_code = false,
+            Event::Start(Tag::Heading { .. }) => in_heading = true,
+            Event::End(TagEnd::Heading(_)) => in_heading = false,
+            Event::Start(Tag::BlockQuote) => {
+                if let Some((html, skip)) = callout_start(&events[i + 1..]) {
+                    quotes.push(true);
+                    out.push(Event::Html(html.into()));
+                    i += 1 + skip;
+                    continue;
+                }
+                quotes.push(false);
+            }
+            // Pops plain quotes too, which then end as usual
+            Event::End(TagEnd::BlockQuote) if quotes.pop() == Some(true) => {
+                out.push(Event::Html("</div>\n</div>\n".into()));
+                i += 1;
+                continue;
+            }
+            Event::Text(text) if !in_code => {
+                out.push(Event::InlineHtml(render_inline(text, links, !in_heading).into()));
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        out.push(events[i].clone());
+        i += 1;
+    }
+
+    let mut html = String::new();
+    html::push_html(&mut html, out.into_iter());
+    html
+}
+
+/// Join runs of text, which the parser splits at brackets, so a wiki link
+/// arrives in one piece
+fn merge_text(parser: Parser<'_>) -> Vec<Event<'_>> {
+    let mut events: Vec<Event> = Vec::new();
+    for event in parser {
+        if let (Event::Text(text), Some(Event::Text(previous))) = (&event, events.last_mut()) {
+            *previous = CowStr::from(format!("{}{}", previous, text));
+            continue;
+        }
+        events.push(event);
+    }
+    events
+}
+
+/// If the events after a block quote's start open a callout, its opening
+/// HTML and how many of the events it replaces
+fn callout_start(events: &[Event]) -> Option<(String, usize)> {
+    let (Some(Event::Start(Tag::Paragraph)), Some(Event::Text(first))) =
+        (events.first(), events.get(1))
+    else {
+        return None;
+    };
+    let captures = CALLOUT_RE.captures(first)?;
+    let kind = captures[1].to_lowercase();
+    let title = match captures[2].trim() {
+        "" => {
+            let mut chars = kind.chars();
+            chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+        }
+        title => title.to_string(),
+    };
+    let html = format!(
+        "<div class=\"callout\" data-callout=\"{}\">\n<div class=\"callout-title\">{}</div>\n\
+         <div class=\"callout-content\">\n",
+        escape_html(&kind),
+        escape_html(&title)
+    );
+
+    // The rest of the first paragraph, if any, stays in the callout
+    match events.get(2) {
+        Some(Event::End(TagEnd::Paragraph)) => Some((html, 3)),
+        Some(Event::SoftBreak | Event::HardBreak) => Some((html + "<p>", 3)),
+        _ => Some((html + "<p>", 2)),
+    }
+}
+
+/// Escape text and render the wiki links, embeds and, with `tags`, the
+/// tags in it
+fn render_inline(text: &str, links: &RenderLinks, tags: bool) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+    for captures in INLINE_RE.captures_iter(text) {
+        let whole = captures.get(0).expect("match has a whole capture");
+        let tag = captures.get(5).map(|m| m.as_str().trim_end_matches('/'));
+        let is_tag = tag.is_some_and(|tag| {
+            !tag.chars().all(|c| c.is_ascii_digit() || c == '/' || c == '-')
+        });
+        if tag.is_some() && !(tags && is_tag) {
+            continue;
+        }
+        out.push_str(&escape_html(&text[last..whole.start()]));
+        last = whole.end();
+
+        if let Some(tag) = tag {
+            // After the whitespace it needs
+            out.push_str(&escape_html(&captures[4]));
+            out.push_str(&format!(
+                "<span class=\"tag\" data-tag=\"{}\">#{}</span>",
+                escape_html(tag),
+                escape_html(&captures[5])
+            ));
+        } else if captures.get(1).is_some() {
+            out.push_str(&render_embed(&captures, links));
+        } else {
+            out.push_str(&render_link(&captures, links));
+        }
+    }
+    out.push_str(&escape_html(&text[last..]));
+    out
+}
+
+fn render_link(captures: &Captures, links: &RenderLinks) -> String {
+    let target = captures[2].trim();
+    let text = captures.get(3).map_or(target, |m| m.as_str().trim());
+    let (name, anchor) = match target.split_once('#') {
+        Some((name, anchor)) => (name.trim(), Some(anchor.trim())),
+        None => (target, None),
+    };
+    match (links.resolve)(name) {
+        Some(path) => {
+            let href = match anchor {
+                Some(anchor) => format!("{}#{}", path, anchor),
+                None => path.clone(),
+            };
+            format!(
+                "<a class=\"wiki-link\" href=\"{}\" data-path=\"{}\">{}</a>",
+                escape_html(&href),
+                escape_html(&path),
+                escape_html(text)
+            )
+        }
+        None => format!(
+            "<a class=\"wiki-link unresolved\" data-target=\"{}\">{}</a>",
+            escape_html(target),
+            escape_html(text)
+        ),
+    }
+}
+
+fn render_embed(captures: &Captures, links: &RenderLinks) -> String {
+    let target = captures[2].trim();
+    let text = captures.get(3).map_or(target, |m| m.as_str().trim());
+    let embed = links.embeds.iter().find(|e| e.target == target);
+
+    match embed {
+        Some(embed) => match (embed.status, &embed.path, &embed.content) {
+            (EmbedStatus::Resolved, Some(path), Some(content)) => {
+                let nested = RenderLinks {
+                    resolve: links.resolve,
+                    embeds: &embed.embeds,
+                };
+                format!(
+                    "<div class=\"embed\" data-path=\"{}\">\n{}</div>",
+                    escape_html(path),
+                    render_markdown(content, &nested)
+                )
+            }
+            (EmbedStatus::Attachment, Some(path), _) if is_image_path(path) => format!(
+                "<img class=\"embed\" src=\"{}\" alt=\"{}\" data-path=\"{}\">",
+                escape_html(path),
+                escape_html(text),
+                escape_html(path)
+            ),
+            // Other files, and notes embedded too deep or in themselves
+            (_, Some(path), _) => format!(
+                "<a class=\"embed-link\" href=\"{}\" data-path=\"{}\">{}</a>",
+                escape_html(path),
+                escape_html(path),
+                escape_html(text)
+            ),
+            (_, None, _) => unresolved_embed(target, text),
+        },
+        None => unresolved_embed(target, text),
+    }
+}
+
+fn unresolved_embed(target: &str, text: &str) -> String {
+    format!(
+        "<span class=\"embed unresolved\" data-target=\"{}\">{}</span>",
+        escape_html(target),
+        escape_html(text)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(markdown: &str) -> String {
+        let resolve = |name: &str| (name == "Beta").then(|| "notes/Beta.md".to_string());
+        let embeds = [ResolvedEmbed {
+            target: "Beta".to_string(),
+            display: None,
+            line_number: 1,
+            status: EmbedStatus::Resolved,
+            path: Some("notes/Beta.md".to_string()),
+            content: Some("Beta *body*\n".to_string()),
+            embeds: Vec::new(),
+        }];
+        render_markdown(markdown, &RenderLinks { resolve: &resolve, embeds: &embeds })
+    }
+
+    #[test]
+    fn test_wiki_links_and_tags() {
+        let html = render("See [[Beta#Plan|the plan]], [[Gamma]] and #project/alpha, not #1.\n");
+        assert!(html.contains(
+            r#"<a class="wiki-link" href="notes/Beta.md#Plan" data-path="notes/Beta.md">the plan</a>"#
+        ));
+        assert!(html.contains(r#"<a class="wiki-link unresolved" data-target="Gamma">Gamma</a>"#));
+        assert!(html.contains(r#"<span class="tag" data-tag="project/alpha">#project/alpha</span>"#));
+        assert!(html.contains("not #1."));
+
+        // Code is left alone
+        let html = render("`[[Beta]]`\n\n```\n#tag [[Beta]]\n```\n");
+        assert!(!html.contains("wiki-link"));
+        assert!(!html.contains("class=\"tag\""));
+    }
+
+    #[test]
+    fn test_embeds() {
+        let html = render("![[Beta]]\n\n![[Missing]]\n");
+        assert!(html.contains(
+            "<div class=\"embed\" data-path=\"notes/Beta.md\">\n<p>Beta <em>body</em></p>\n</div>"
+        ));
+        assert!(html.contains(r#"<span class="embed unresolved" data-target="Missing">Missing</span>"#));
+    }
+
+    #[test]
+    fn test_callouts() {
+        let html = render("> [!warning] Careful\n> Mind the **gap**\n\n> Plain quote\n");
+        assert!(html.contains(
+            "<div class=\"callout\" data-callout=\"warning\">\n<div class=\"callout-title\">Careful</div>"
+        ));
+        assert!(html.contains("<p>Mind the <strong>gap</strong></p>\n</div>\n</div>"));
+        assert!(html.contains("<blockquote>\n<p>Plain quote</p>\n</blockquote>"));
+
+        let html = render("> [!tip]\n>\n> Body\n");
+        assert!(html.contains("<div class=\"callout-title\">Tip</div>"));
+        assert!(html.contains("<p>Body</p>"));
+    }
+
+    #[test]
+    fn test_text_is_escaped() {
+        let html = render("1 < 2 & [[\"Q&A\"]]\n");
+        assert!(html.contains(concat!(
+            "1 &lt; 2 &amp; <a class=\"wiki-link unresolved\" data-target=\"&quot;Q&amp;A&quot;\">",
+            "&quot;Q&amp;A&quot;</a>"
+        )));
+    }
+}
@@ -0,0 +1,333 @@
+//! Single notes cleaned up for sending to someone outside the vault
+//!
+//! Wiki links and note embeds become plain text, since the recipient has
+//! none of the notes they point at, and Obsidian-style `%% comments %%`
+//! are dropped. Frontmatter is removed except for the properties asked
+//! for. Embedded attachments are collected so they can travel alongside
+//! the note, with the note's links pointing at `attachments/`.
+
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use super::{escape_html, markdown_to_html, PAGE_CSS, WIKI_LINK_RE};
+use crate::error::ChronicleError;
+use crate::models::PublishFormat;
+use crate::vault::{frontmatter_text, parse_frontmatter, resolve_vault_path};
+
+static WIKI_EMBED_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"!\[\[([^\]|#]+)(#[^\]|]*)?(?:\|([^\]]+))?\]\]").expect("Invalid embed regex")
+});
+
+/// Markdown images with a local source; links with a scheme stay as they are
+static MARKDOWN_IMAGE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"!\[([^\]]*)\]\(([^)\s]+)\)").expect("Invalid Markdown image regex")
+});
+
+static COMMENT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)%%.*?%%").expect("Invalid comment regex"));
+
+/// Folder attachments are bundled under, next to the note
+pub const SHARED_ATTACHMENTS_DIR: &str = "attachments";
+
+/// A note's content ready to share
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedNote {
+    pub markdown: String,
+    /// Attachments as linked from the note, with the name each is bundled
+    /// under in `attachments/`
+    pub attachments: Vec<(String, String)>,
+}
+
+/// What `export_shareable` wrote
+#[derive(Debug, Clone, Serialize)]
+pub struct ShareExport {
+    /// The note file, or a zip of it and its attachments
+    pub path: String,
+    pub attachments: usize,
+    /// Attachments the note links to that aren't in the vault
+    pub missing_attachments: Vec<String>,
+}
+
+/// Strip a note down for sharing, keeping only the frontmatter properties
+/// in `keep_properties`
+pub fn shareable_markdown(content: &str, keep_properties: &[String]) -> SharedNote {
+    let (_, body_start) = parse_frontmatter(content);
+    let body = COMMENT_RE.replace_all(&content[body_start..], "");
+
+    let mut bundled = Bundle::default();
+    let body = MARKDOWN_IMAGE_RE.replace_all(&body, |caps: &regex::Captures| {
+        let source = &caps[2];
+        if source.contains("://") || source.starts_with("data:") {
+            return caps[0].to_string();
+        }
+        format!("![{}]({})", &caps[1], bundled.add(&source.replace("%20", " ")))
+    });
+    // After Markdown images, so the images these turn into are left alone
+    let body = WIKI_EMBED_RE.replace_all(&body, |caps: &regex::Captures| {
+        let target = caps[1].trim();
+        let is_note = Path::new(target).extension().is_none_or(|ext| ext == "md");
+        if is_note {
+            let text = caps.get(3).map_or(target, |m| m.as_str().trim());
+            return text.to_string();
+        }
+        // `|300` sizes an image rather than naming it
+        let alt = caps
+            .get(3)
+            .map(|m| m.as_str().trim())
+            .filter(|alt| !alt.chars().all(|c| c.is_ascii_digit() || c == 'x'))
+            .unwrap_or("");
+        format!("![{}]({})", alt, bundled.add(target))
+    });
+    let body = WIKI_LINK_RE.replace_all(&body, |caps: &regex::Captures| {
+        let target = caps[1].trim();
+        caps.get(3).map_or(target, |m| m.as_str().trim()).to_string()
+    });
+
+    let markdown = match kept_frontmatter(content, keep_properties) {
+        Some(yaml) => format!("---\n{}---\n{}", yaml, body.trim_start_matches('\n')),
+        None => body.trim_start_matches('\n').to_string(),
+    };
+    SharedNote {
+        markdown,
+        attachments: bundled.attachments,
+    }
+}
+
+fn kept_frontmatter(content: &str, keep_properties: &[String]) -> Option<String> {
+    let mut properties: serde_yaml::Mapping =
+        serde_yaml::from_str(&frontmatter_text(content)).ok()?;
+    properties.retain(|key, _| {
+        key.as_str()
+            .is_some_and(|key| keep_properties.iter().any(|keep| keep == key))
+    });
+    if properties.is_empty() {
+        return None;
+    }
+    serde_yaml::to_string(&properties).ok()
+}
+
+/// Attachments collected from a note, each under a unique file name
+#[derive(Default)]
+struct Bundle {
+    attachments: Vec<(String, String)>,
+    names: HashMap<String, String>,
+}
+
+impl Bundle {
+    /// Link to use for `target` in the shared note
+    fn add(&mut self, target: &str) -> String {
+        if let Some(name) = self.names.get(target) {
+            return format!("{}/{}", SHARED_ATTACHMENTS_DIR, name.replace(' ', "%20"));
+        }
+
+        let file_name = target.rsplit('/').next().unwrap_or(target);
+        let mut name = file_name.to_string();
+        let mut n = 2;
+        while self.attachments.iter().any(|(_, taken)| *taken == name) {
+            name = format!("{}-{}", n, file_name);
+            n += 1;
+        }
+        self.names.insert(target.to_string(), name.clone());
+        self.attachments.push((target.to_string(), name.clone()));
+        format!("{}/{}", SHARED_ATTACHMENTS_DIR, name.replace(' ', "%20"))
+    }
+}
+
+/// Find an attachment linked from `note_path`: by vault-relative path, or
+/// relative to the note's folder
+fn find_attachment(vault_path: &Path, note_path: &str, target: &str) -> Option<PathBuf> {
+    let note_dir = note_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let candidates = [target.to_string(), format!("{}/{}", note_dir, target)];
+    candidates
+        .iter()
+        .filter_map(|candidate| resolve_vault_path(vault_path, candidate).ok())
+        .find(|path| path.is_file())
+}
+
+/// Write a shareable copy of a note into `dest_dir`: the note alone when it
+/// has no attachments, otherwise a zip of the note and `attachments/`
+pub fn export_shareable(
+    vault_path: &Path,
+    note_path: &str,
+    title: &str,
+    content: &str,
+    format: PublishFormat,
+    keep_properties: &[String],
+    dest_dir: &Path,
+) -> Result<ShareExport, ChronicleError> {
+    let shared = shareable_markdown(content, keep_properties);
+    let (extension, contents) = match format {
+        PublishFormat::Markdown => ("md", shared.markdown.clone()),
+        PublishFormat::Html => ("html", share_page(title, &markdown_to_html(&shared.markdown))),
+    };
+
+    let mut found = Vec::new();
+    let mut missing_attachments = Vec::new();
+    for (target, name) in &shared.attachments {
+        match find_attachment(vault_path, note_path, target) {
+            Some(path) => found.push((path, format!("{}/{}", SHARED_ATTACHMENTS_DIR, name))),
+            None => missing_attachments.push(target.clone()),
+        }
+    }
+
+    let stem = file_stem(title);
+    fs::create_dir_all(dest_dir)?;
+    let path = if found.is_empty() {
+        let path = dest_dir.join(format!("{}.{}", stem, extension));
+        fs::write(&path, contents)?;
+        path
+    } else {
+        let path = dest_dir.join(format!("{}.zip", stem));
+        let note_name = format!("{}.{}", stem, extension);
+        write_bundle(&path, &note_name, contents.as_bytes(), &found)?;
+        path
+    };
+
+    Ok(ShareExport {
+        path: path.to_string_lossy().to_string(),
+        attachments: found.len(),
+        missing_attachments,
+    })
+}
+
+/// A file name from a note title, without characters file systems reject
+pub(super) fn file_stem(title: &str) -> String {
+    let stem: String = title
+        .chars()
+        .map(|c| if r#"/\:*?"<>|"#.contains(c) { '-' } else { c })
+        .collect();
+    match stem.trim() {
+        "" => "note".to_string(),
+        stem => stem.to_string(),
+    }
+}
+
+fn write_bundle(
+    archive: &Path,
+    note_name: &str,
+    note: &[u8],
+    attachments: &[(PathBuf, String)],
+) -> io::Result<()> {
+    let mut zip = ZipWriter::new(BufWriter::new(File::create(archive)?));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file(note_name, options).map_err(io::Error::other)?;
+    zip.write_all(note)?;
+    for (path, name) in attachments {
+        zip.start_file(name.as_str(), options).map_err(io::Error::other)?;
+        io::copy(&mut File::open(path)?, &mut zip)?;
+    }
+    zip.finish().map_err(io::Error::other)?;
+    Ok(())
+}
+
+fn share_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n<style>{css}</style>\n</head>\n<body>\n\
+         <article>\n{body}</article>\n</body>\n</html>\n",
+        title = escape_html(title),
+        css = PAGE_CSS,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use tempfile::TempDir;
+    use zip::ZipArchive;
+
+    #[test]
+    fn test_shareable_markdown() {
+        let content = "---\ntags: [private]\nauthor: Sam\nstatus: draft\n---\n\
+                       See [[Roadmap|the roadmap]] and [[Team/People#Sam]].\n\
+                       ![[Secret note]]\n\
+                       %% ask Sam about budget %%\n\
+                       ![[diagram.png|400]] ![chart](img/chart.png) ![logo](https://x.io/l.png)\n";
+        let shared = shareable_markdown(content, &["author".to_string()]);
+
+        assert_eq!(
+            shared.markdown,
+            "---\nauthor: Sam\n---\n\
+             See the roadmap and Team/People.\n\
+             Secret note\n\n\
+             ![](attachments/diagram.png) ![chart](attachments/chart.png) \
+             ![logo](https://x.io/l.png)\n"
+        );
+        assert_eq!(
+            shared.attachments,
+            vec![
+                ("img/chart.png".to_string(), "chart.png".to_string()),
+                ("diagram.png".to_string(), "diagram.png".to_string()),
+            ]
+        );
+
+        // Nothing kept: no frontmatter at all
+        let shared = shareable_markdown(content, &[]);
+        assert!(shared.markdown.starts_with("See the roadmap"));
+    }
+
+    #[test]
+    fn test_attachment_name_clashes() {
+        let shared = shareable_markdown("![[a/pic.png]] ![[b/pic.png]] ![[a/pic.png]]", &[]);
+        assert_eq!(
+            shared.markdown,
+            "![](attachments/pic.png) ![](attachments/2-pic.png) ![](attachments/pic.png)"
+        );
+        assert_eq!(shared.attachments.len(), 2);
+    }
+
+    #[test]
+    fn test_export_shareable() {
+        let vault = TempDir::new().unwrap();
+        let dest = TempDir::new().unwrap();
+        fs::create_dir_all(vault.path().join("notes/img")).unwrap();
+        fs::write(vault.path().join("notes/img/chart.png"), [1u8, 2, 3]).unwrap();
+
+        let plain = export_shareable(
+            vault.path(),
+            "notes/plain.md",
+            "Plain: v2",
+            "Just [[text]]",
+            PublishFormat::Markdown,
+            &[],
+            dest.path(),
+        )
+        .unwrap();
+        assert!(plain.path.ends_with("Plain- v2.md"));
+        assert_eq!(fs::read_to_string(&plain.path).unwrap(), "Just text");
+
+        let bundled = export_shareable(
+            vault.path(),
+            "notes/report.md",
+            "Report",
+            "![](img/chart.png) ![[missing.pdf]]",
+            PublishFormat::Html,
+            &[],
+            dest.path(),
+        )
+        .unwrap();
+        assert!(bundled.path.ends_with("Report.zip"));
+        assert_eq!(bundled.attachments, 1);
+        assert_eq!(bundled.missing_attachments, vec!["missing.pdf".to_string()]);
+
+        let mut archive = ZipArchive::new(File::open(&bundled.path).unwrap()).unwrap();
+        let mut html = String::new();
+        archive
+            .by_name("Report.html")
+            .unwrap()
+            .read_to_string(&mut html)
+            .unwrap();
+        assert!(html.contains("src=\"attachments/chart.png\""));
+        assert!(archive.by_name("attachments/chart.png").is_ok());
+    }
+}
@@ -0,0 +1,316 @@
+//! Publish targets: a local folder, a git branch, or a server over SFTP
+
+use ssh2::{CheckResult, KnownHostFileKind, Session};
+use std::fs;
+use std::io::Write;
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+
+use super::PublishedFile;
+use crate::error::ChronicleError;
+use crate::models::{PublishFormat, PublishTarget};
+use crate::sync::GitRepo;
+
+/// What a publish sends
+pub(super) struct Deployment<'a> {
+    /// Every rendered file
+    pub files: &'a [PublishedFile],
+    /// Rendered files that differ from the last publish
+    pub changed: &'a [&'a PublishedFile],
+    /// Previously published files that are no longer rendered
+    pub removed: &'a [String],
+}
+
+pub(super) fn deploy(
+    vault_path: &Path,
+    target: &PublishTarget,
+    format: PublishFormat,
+    deployment: &Deployment,
+) -> Result<(), ChronicleError> {
+    match target {
+        PublishTarget::Folder { path } => deploy_folder(vault_path, path, format, deployment),
+        PublishTarget::Git { branch, push } => deploy_git(vault_path, branch, *push, deployment),
+        PublishTarget::Sftp {
+            host,
+            port,
+            user,
+            remote_dir,
+            key_file,
+        } => {
+            let session = connect(host, *port, user, key_file.as_deref())?;
+            deploy_sftp(&session, remote_dir, deployment)
+        }
+    }
+}
+
+fn deploy_folder(
+    vault_path: &Path,
+    folder: &str,
+    format: PublishFormat,
+    deployment: &Deployment,
+) -> Result<(), ChronicleError> {
+    let root = vault_path.join(folder);
+    // Published Markdown inside the vault would be indexed as more notes
+    if format == PublishFormat::Markdown && is_inside(&root, vault_path)? {
+        return Err(ChronicleError::PublishFailed(
+            "Markdown must be published to a folder outside the vault".to_string(),
+        ));
+    }
+
+    for file in deployment.changed {
+        let path = output_file(&root, &file.path)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, &file.contents)?;
+    }
+    for removed in deployment.removed {
+        let path = output_file(&root, removed)?;
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether `path` lands inside `dir` once `..` components and symlinks are
+/// resolved. The path itself doesn't need to exist yet.
+fn is_inside(path: &Path, dir: &Path) -> Result<bool, ChronicleError> {
+    let mut resolved = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                resolved.pop();
+            }
+            other => resolved.push(other),
+        }
+    }
+
+    let mut existing = resolved.as_path();
+    while !existing.exists() {
+        match existing.parent() {
+            Some(parent) => existing = parent,
+            None => return Ok(resolved.starts_with(dir)),
+        }
+    }
+    let missing = resolved.strip_prefix(existing).unwrap_or(Path::new(""));
+    Ok(existing.canonicalize()?.join(missing).starts_with(dir.canonicalize()?))
+}
+
+/// Output paths come from note paths, but still must not leave the root
+fn output_file(root: &Path, relative: &str) -> Result<PathBuf, ChronicleError> {
+    if Path::new(relative)
+        .components()
+        .any(|c| !matches!(c, std::path::Component::Normal(_)))
+    {
+        return Err(ChronicleError::InvalidPath(relative.to_string()));
+    }
+    Ok(root.join(relative))
+}
+
+fn deploy_git(
+    vault_path: &Path,
+    branch: &str,
+    push: bool,
+    deployment: &Deployment,
+) -> Result<(), ChronicleError> {
+    let repo = GitRepo::open(vault_path)?;
+    // Replacing the checked-out branch's tree would throw away the vault
+    if repo.current_branch().ok().as_deref() == Some(branch) {
+        return Err(ChronicleError::PublishFailed(format!(
+            "Can't publish to the checked-out branch {}",
+            branch
+        )));
+    }
+
+    let files: Vec<(String, Vec<u8>)> = deployment
+        .files
+        .iter()
+        .map(|f| (f.path.clone(), f.contents.clone()))
+        .collect();
+    repo.commit_files_to_branch(branch, &files, "Publish notes")?;
+
+    if push {
+        repo.push_branch(branch)?;
+    }
+    Ok(())
+}
+
+fn connect(
+    host: &str,
+    port: u16,
+    user: &str,
+    key_file: Option<&str>,
+) -> Result<Session, ChronicleError> {
+    let failed = |e: ssh2::Error| ChronicleError::PublishFailed(e.to_string());
+
+    let tcp = TcpStream::connect((host, port))
+        .map_err(|e| ChronicleError::NetworkUnreachable(format!("{}: {}", host, e)))?;
+    let mut session = Session::new().map_err(failed)?;
+    session.set_tcp_stream(tcp);
+    session.handshake().map_err(failed)?;
+
+    let (key, _) = session.host_key().ok_or_else(|| {
+        ChronicleError::PublishFailed(format!("{} sent no host key", host))
+    })?;
+    let known_hosts = dirs::home_dir()
+        .map(|home| home.join(".ssh").join("known_hosts"))
+        .ok_or_else(|| ChronicleError::PublishFailed("No home folder for known_hosts".into()))?;
+    verify_host_key(&session, &known_hosts, host, port, key)?;
+
+    let auth = match key_file {
+        Some(key) => {
+            let key = match (key.strip_prefix("~/"), dirs::home_dir()) {
+                (Some(rest), Some(home)) => home.join(rest),
+                _ => PathBuf::from(key),
+            };
+            session.userauth_pubkey_file(user, None, &key, None)
+        }
+        None => session.userauth_agent(user),
+    };
+    if auth.is_err() || !session.authenticated() {
+        return Err(ChronicleError::AuthenticationFailed(format!("{}@{}", user, host)));
+    }
+    Ok(session)
+}
+
+/// Fail unless `key` is the key `known_hosts` has for `host`, so notes are
+/// never sent to a server that isn't the one the user trusted with ssh
+fn verify_host_key(
+    session: &Session,
+    known_hosts: &Path,
+    host: &str,
+    port: u16,
+    key: &[u8],
+) -> Result<(), ChronicleError> {
+    let mut known = session
+        .known_hosts()
+        .map_err(|e| ChronicleError::PublishFailed(e.to_string()))?;
+    if known_hosts.exists() {
+        known.read_file(known_hosts, KnownHostFileKind::OpenSSH).map_err(|e| {
+            ChronicleError::PublishFailed(format!("Can't read {}: {}", known_hosts.display(), e))
+        })?;
+    }
+    match known.check_port(host, port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::Mismatch => Err(ChronicleError::PublishFailed(format!(
+            "The host key of {} doesn't match the one in {}; it may not be the server you expect",
+            host,
+            known_hosts.display()
+        ))),
+        CheckResult::NotFound | CheckResult::Failure => Err(ChronicleError::PublishFailed(
+            format!(
+                "{} is not in {}; connect to it with ssh once to trust its host key",
+                host,
+                known_hosts.display()
+            ),
+        )),
+    }
+}
+
+fn deploy_sftp(
+    session: &Session,
+    remote_dir: &str,
+    deployment: &Deployment,
+) -> Result<(), ChronicleError> {
+    let failed = |e: ssh2::Error| ChronicleError::PublishFailed(e.to_string());
+    let sftp = session.sftp().map_err(failed)?;
+    let root = Path::new(remote_dir);
+
+    for file in deployment.changed {
+        let path = output_file(root, &file.path)?;
+        // mkdir -p: create each missing parent in turn
+        let mut dir = PathBuf::new();
+        for component in path.parent().unwrap_or(root).components() {
+            dir.push(component);
+            if sftp.stat(&dir).is_err() {
+                sftp.mkdir(&dir, 0o755).map_err(failed)?;
+            }
+        }
+        let mut remote = sftp.create(&path).map_err(failed)?;
+        remote.write_all(&file.contents)?;
+    }
+    for removed in deployment.removed {
+        let path = output_file(root, removed)?;
+        if sftp.stat(&path).is_ok() {
+            sftp.unlink(&path).map_err(failed)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rendered(path: &str, contents: &str) -> PublishedFile {
+        PublishedFile {
+            path: path.to_string(),
+            source: Some(path.to_string()),
+            contents: contents.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_deploy_folder() {
+        let vault = tempfile::TempDir::new().unwrap();
+        let out = tempfile::TempDir::new().unwrap();
+        fs::write(out.path().join("old.md"), "old").unwrap();
+
+        let files = vec![rendered("notes/a.md", "A")];
+        let changed: Vec<&PublishedFile> = files.iter().collect();
+        let removed = vec!["old.md".to_string()];
+        let deployment = Deployment {
+            files: &files,
+            changed: &changed,
+            removed: &removed,
+        };
+        let target = PublishTarget::Folder {
+            path: out.path().to_string_lossy().to_string(),
+        };
+        deploy(vault.path(), &target, PublishFormat::Markdown, &deployment).unwrap();
+
+        assert_eq!(fs::read_to_string(out.path().join("notes/a.md")).unwrap(), "A");
+        assert!(!out.path().join("old.md").exists());
+
+        // Markdown can't go back into the vault
+        let inside = PublishTarget::Folder {
+            path: "site".to_string(),
+        };
+        assert!(deploy(vault.path(), &inside, PublishFormat::Markdown, &deployment).is_err());
+        assert!(deploy(vault.path(), &inside, PublishFormat::Html, &deployment).is_ok());
+
+        // Nor by stepping out and back in again
+        let name = vault.path().file_name().unwrap().to_string_lossy();
+        let round_trip = PublishTarget::Folder {
+            path: format!("../{}/site", name),
+        };
+        assert!(deploy(vault.path(), &round_trip, PublishFormat::Markdown, &deployment).is_err());
+    }
+
+    #[test]
+    fn test_output_file_stays_in_root() {
+        assert!(output_file(Path::new("/site"), "a/b.html").is_ok());
+        assert!(output_file(Path::new("/site"), "../etc/passwd").is_err());
+        assert!(output_file(Path::new("/site"), "/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn test_verify_host_key() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let known_hosts = temp.path().join("known_hosts");
+        let session = Session::new().unwrap();
+        let trusted = b"trusted host key".as_slice();
+        let mut known = session.known_hosts().unwrap();
+        known.add("example.com", trusted, "", ssh2::KnownHostKeyFormat::SshRsa).unwrap();
+        known.write_file(&known_hosts, KnownHostFileKind::OpenSSH).unwrap();
+
+        assert!(verify_host_key(&session, &known_hosts, "example.com", 22, trusted).is_ok());
+        let mismatch =
+            verify_host_key(&session, &known_hosts, "example.com", 22, b"another host key");
+        assert!(mismatch.unwrap_err().to_string().contains("doesn't match"));
+        let unknown = verify_host_key(&session, &known_hosts, "example.org", 22, trusted);
+        assert!(unknown.unwrap_err().to_string().contains("is not in"));
+    }
+}
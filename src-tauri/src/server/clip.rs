@@ -0,0 +1,23 @@
+//! `POST /clip`: save a page or selection sent by the browser extension
+
+use axum::extract::State;
+use axum::Json;
+use serde::Serialize;
+use tauri::{Manager, Runtime};
+
+use super::{ApiError, ServerContext};
+use crate::commands::{quick_capture, AppState, Capture};
+
+#[derive(Debug, Serialize)]
+pub(super) struct ClipResponse {
+    /// Vault-relative path of the new note
+    path: String,
+}
+
+pub(super) async fn clip<R: Runtime>(
+    State(context): State<ServerContext<R>>,
+    Json(capture): Json<Capture>,
+) -> Result<Json<ClipResponse>, ApiError> {
+    let note = quick_capture(capture, context.app.state::<AppState>()).await?;
+    Ok(Json(ClipResponse { path: note.path }))
+}
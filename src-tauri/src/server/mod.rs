@@ -0,0 +1,220 @@
+//! Local HTTP server for browser integrations and scripts
+//!
+//! Routes:
+//! - `POST /clip`: quick capture from the browser extension
+//! - `GET /notes`, `POST /notes`: list and create notes
+//! - `GET /notes/{path}`, `PUT /notes/{path}`: read and save a note
+//! - `GET /search?q=...&limit=...`: full-text search
+//!
+//! The server only listens on 127.0.0.1 and every request must carry the
+//! configured token as `Authorization: Bearer <token>`. It is off unless
+//! enabled in the `server` config section.
+
+mod clip;
+mod notes;
+
+use axum::extract::{Request, State};
+use axum::http::{header::AUTHORIZATION, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::net::TcpListener;
+use tokio::sync::{oneshot, Mutex};
+
+use crate::error::ChronicleError;
+use crate::models::ServerConfig;
+
+/// What handlers get from the router
+pub(crate) struct ServerContext<R: Runtime> {
+    pub app: AppHandle<R>,
+    token: Arc<str>,
+}
+
+// Derived `Clone` would need `R: Clone`, which runtimes aren't
+impl<R: Runtime> Clone for ServerContext<R> {
+    fn clone(&self) -> Self {
+        Self {
+            app: self.app.clone(),
+            token: self.token.clone(),
+        }
+    }
+}
+
+/// A running server, stopped with `stop`
+pub struct LocalServer {
+    config: ServerConfig,
+    shutdown: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl LocalServer {
+    /// Bind to the configured port and serve until stopped
+    pub async fn start(app: AppHandle, config: ServerConfig) -> Result<Self, ChronicleError> {
+        config.validate().map_err(ChronicleError::InvalidConfig)?;
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, config.port)).await?;
+
+        let context = ServerContext {
+            app,
+            token: Arc::from(config.token.as_str()),
+        };
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let port = config.port;
+
+        let task = tauri::async_runtime::spawn(async move {
+            let result = axum::serve(listener, router(context))
+                .with_graceful_shutdown(async {
+                    let _ = stopped.await;
+                })
+                .await;
+            if let Err(e) = result {
+                tracing::error!(port, error = %e, "Local server failed");
+            }
+        });
+
+        tracing::info!(port, "Local server listening");
+        Ok(Self {
+            config,
+            shutdown,
+            task,
+        })
+    }
+
+    /// Stop accepting requests and wait for in-flight ones to finish
+    pub async fn stop(self) {
+        let _ = self.shutdown.send(());
+        let _ = self.task.await;
+    }
+}
+
+/// The running server, if any; managed by the app
+#[derive(Default)]
+pub struct ServerState(Mutex<Option<LocalServer>>);
+
+/// Start, restart or stop the server to match the config. Runs in the
+/// background; failures are logged.
+pub fn apply_config(app: &AppHandle, config: &ServerConfig) {
+    let app = app.clone();
+    let config = config.clone();
+
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = app.try_state::<ServerState>() else {
+            return;
+        };
+        let mut running = state.0.lock().await;
+
+        if running.as_ref().map(|s| &s.config) == Some(&config) {
+            return;
+        }
+        if let Some(server) = running.take() {
+            server.stop().await;
+            tracing::info!("Local server stopped");
+        }
+        if !config.enabled {
+            return;
+        }
+
+        match LocalServer::start(app.clone(), config).await {
+            Ok(server) => *running = Some(server),
+            Err(e) => tracing::error!(error = %e, "Failed to start local server"),
+        }
+    });
+}
+
+fn router<R: Runtime>(context: ServerContext<R>) -> Router {
+    Router::new()
+        .route("/clip", post(clip::clip::<R>))
+        .route("/notes", get(notes::list::<R>).post(notes::create::<R>))
+        .route("/notes/batch", post(notes::create_batch::<R>))
+        .route("/notes/*path", get(notes::get::<R>).put(notes::save::<R>))
+        .route("/search", get(notes::search::<R>))
+        .layer(middleware::from_fn_with_state(context.clone(), require_token::<R>))
+        .with_state(context)
+}
+
+async fn require_token<R: Runtime>(
+    State(context): State<ServerContext<R>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let provided = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if tokens_match(token, &context.token) => next.run(request).await,
+        _ => StatusCode::UNAUTHORIZED.into_response(),
+    }
+}
+
+/// Compare without exiting early, so timing doesn't reveal a matching prefix
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    !expected.is_empty()
+        && provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// A command error sent as JSON with a matching status code
+pub(crate) struct ApiError(ChronicleError);
+
+impl From<ChronicleError> for ApiError {
+    fn from(e: ChronicleError) -> Self {
+        Self(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (status_for(&self.0), Json(self.0)).into_response()
+    }
+}
+
+fn status_for(error: &ChronicleError) -> StatusCode {
+    match error {
+        ChronicleError::NoteNotFound(_) => StatusCode::NOT_FOUND,
+        ChronicleError::NoteExists(_) | ChronicleError::VaultReadOnly(_) => StatusCode::CONFLICT,
+        ChronicleError::InvalidPath(_) | ChronicleError::InvalidConfig(_) => StatusCode::BAD_REQUEST,
+        ChronicleError::NoVaultOpen
+        | ChronicleError::VaultUnavailable(_)
+        | ChronicleError::NotDownloaded(_) => {
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("secret-token-123", "secret-token-123"));
+        assert!(!tokens_match("secret-token-124", "secret-token-123"));
+        assert!(!tokens_match("secret", "secret-token-123"));
+        assert!(!tokens_match("", ""));
+    }
+
+    #[test]
+    fn test_status_for() {
+        assert_eq!(status_for(&ChronicleError::NoVaultOpen), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            status_for(&ChronicleError::NoteNotFound("a.md".to_string())),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            status_for(&ChronicleError::Io("disk full".to_string())),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
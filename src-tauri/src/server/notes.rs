@@ -0,0 +1,182 @@
+//! REST routes mirroring the note commands
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use serde::Deserialize;
+use tauri::{Manager, Runtime};
+
+use super::{ApiError, ServerContext};
+use crate::commands::{self, AppState, NewNoteSpec};
+use crate::db::{notes::NoteMeta, SearchResult};
+use crate::models::Note;
+
+#[derive(Debug, Deserialize)]
+pub(super) struct NewNote {
+    title: String,
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct NoteContent {
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct SearchParams {
+    q: String,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// `GET /notes`
+pub(super) async fn list<R: Runtime>(
+    State(context): State<ServerContext<R>>,
+) -> Result<Json<Vec<NoteMeta>>, ApiError> {
+    let notes = commands::list_notes(context.app.state::<AppState>()).await?;
+    Ok(Json(notes))
+}
+
+/// `GET /notes/{path}`
+pub(super) async fn get<R: Runtime>(
+    State(context): State<ServerContext<R>>,
+    Path(path): Path<String>,
+) -> Result<Json<Note>, ApiError> {
+    let note = commands::get_note(path, None, context.app.state::<AppState>()).await?;
+    Ok(Json(note))
+}
+
+/// `POST /notes`, placed like notes created in the app
+pub(super) async fn create<R: Runtime>(
+    State(context): State<ServerContext<R>>,
+    Json(note): Json<NewNote>,
+) -> Result<(StatusCode, Json<NoteMeta>), ApiError> {
+    let meta = commands::create_note(note.title, note.content, None, context.app.state::<AppState>())
+        .await?;
+    Ok((StatusCode::CREATED, Json(meta)))
+}
+
+/// `POST /notes/batch` creates a list of notes together; if any can't be
+/// created, none are
+pub(super) async fn create_batch<R: Runtime>(
+    State(context): State<ServerContext<R>>,
+    Json(notes): Json<Vec<NewNoteSpec>>,
+) -> Result<(StatusCode, Json<Vec<NoteMeta>>), ApiError> {
+    let created =
+        commands::create_notes(notes, context.app.state::<AppState>(), context.app.clone()).await?;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+/// `PUT /notes/{path}` replaces the content of an existing note
+pub(super) async fn save<R: Runtime>(
+    State(context): State<ServerContext<R>>,
+    Path(path): Path<String>,
+    Json(body): Json<NoteContent>,
+) -> Result<Json<NoteMeta>, ApiError> {
+    let meta = commands::save_note(path, body.content, context.app.state::<AppState>()).await?;
+    Ok(Json(meta))
+}
+
+/// `GET /search?q=...&limit=...`
+pub(super) async fn search<R: Runtime>(
+    State(context): State<ServerContext<R>>,
+    Query(params): Query<SearchParams>,
+) -> Result<Json<Vec<SearchResult>>, ApiError> {
+    let results =
+        commands::search_notes(params.q, params.limit, context.app.state::<AppState>()).await?;
+    Ok(Json(results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::router;
+    use super::*;
+    use crate::vault::{InstanceLock, VaultWatcher};
+    use axum::body::{to_bytes, Body};
+    use axum::http::{header, Request};
+    use axum::Router;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    const TOKEN: &str = "test-token";
+
+    /// A router over a fresh vault, with the vault kept alive alongside
+    fn server() -> (tempfile::TempDir, Router) {
+        let temp = tempfile::TempDir::new().unwrap();
+        let app = tauri::test::mock_app();
+        app.manage(AppState::default());
+
+        let state = app.state::<AppState>();
+        let db = crate::db::Database::open_memory().unwrap();
+        let watcher = VaultWatcher::new(temp.path().to_path_buf()).unwrap();
+        let instance_lock = InstanceLock::acquire(temp.path()).unwrap();
+        state.set_vault(db, temp.path().to_path_buf(), watcher, instance_lock);
+
+        let context = ServerContext {
+            app: app.handle().clone(),
+            token: Arc::from(TOKEN),
+        };
+        (temp, router(context))
+    }
+
+    fn request(method: &str, uri: &str, body: Option<Value>) -> Request<Body> {
+        let builder = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", TOKEN));
+        match body {
+            Some(body) => builder
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+            None => builder.body(Body::empty()).unwrap(),
+        }
+    }
+
+    async fn json_body(response: axum::response::Response) -> Value {
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_requests_need_token() {
+        let (_vault, router) = server();
+        let request = Request::builder().uri("/notes").body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let request = Request::builder()
+            .uri("/notes")
+            .header(header::AUTHORIZATION, "Bearer wrong-token")
+            .body(Body::empty())
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_missing_note_is_not_found() {
+        let (_vault, router) = server();
+        let response = router.oneshot(request("GET", "/notes/missing.md", None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_create_then_read() {
+        let (_vault, router) = server();
+        let note = json!({ "title": "Server Note", "content": "# Server Note\n\nFrom a script" });
+        let response = router.clone().oneshot(request("POST", "/notes", Some(note))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let created = json_body(response).await;
+        let path = created["path"].as_str().unwrap();
+
+        let uri = format!("/notes/{}", path);
+        let response = router.oneshot(request("GET", &uri, None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let note = json_body(response).await;
+        assert_eq!(note["path"], created["path"]);
+        assert!(note["content"].as_str().unwrap().contains("From a script"));
+    }
+}
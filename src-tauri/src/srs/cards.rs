@@ -0,0 +1,211 @@
+//! Flashcard extraction from note content
+//!
+//! Two formats are recognised:
+//!
+//! ```text
+//! Q: What does SM-2 stand for?
+//! A: SuperMemo 2
+//!
+//! The capital of France is {{c::Paris}}.
+//! ```
+//!
+//! A question runs until its `A:` line and an answer until the next blank
+//! line or `Q:`. Every line with one or more `{{c::...}}` deletions (`c1::`,
+//! `c2::` and so on are accepted too) becomes one cloze card.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+use crate::vault::parse_frontmatter;
+
+static CLOZE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{c\d*::(.+?)\}\}").expect("Invalid cloze regex"));
+
+/// Text shown in place of a cloze deletion
+const CLOZE_PLACEHOLDER: &str = "[...]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CardKind {
+    Basic,
+    Cloze,
+}
+
+impl CardKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CardKind::Basic => "basic",
+            CardKind::Cloze => "cloze",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "basic" => Some(CardKind::Basic),
+            "cloze" => Some(CardKind::Cloze),
+            _ => None,
+        }
+    }
+}
+
+/// A card as written in a note
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Card {
+    /// Stable identity within the note, derived from the front. Editing the
+    /// answer keeps a card's schedule; rewording the question starts over.
+    pub key: String,
+    pub kind: CardKind,
+    pub front: String,
+    pub back: String,
+    /// 1-indexed line the card starts on
+    pub line_number: usize,
+}
+
+/// Extract all cards from a note, skipping frontmatter and fenced code
+pub fn extract_cards(content: &str) -> Vec<Card> {
+    let (_, body_start) = parse_frontmatter(content);
+    let first_line = content[..body_start].matches('\n').count();
+
+    let mut cards = Vec::new();
+    let mut pending: Option<PendingCard> = None;
+    let mut in_fence = false;
+
+    for (i, line) in content[body_start..].lines().enumerate() {
+        let line_number = first_line + i + 1;
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            finish(&mut pending, &mut cards);
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        if let Some(question) = trimmed.strip_prefix("Q:") {
+            finish(&mut pending, &mut cards);
+            pending = Some(PendingCard {
+                front: vec![question.trim().to_string()],
+                back: Vec::new(),
+                in_answer: false,
+                line_number,
+            });
+            continue;
+        }
+
+        if let Some(card) = pending.as_mut() {
+            if let Some(answer) = trimmed.strip_prefix("A:").filter(|_| !card.in_answer) {
+                card.in_answer = true;
+                card.back.push(answer.trim().to_string());
+                continue;
+            }
+            if trimmed.is_empty() {
+                if card.in_answer {
+                    finish(&mut pending, &mut cards);
+                }
+                continue;
+            }
+            if card.in_answer {
+                card.back.push(trimmed.to_string());
+            } else {
+                card.front.push(trimmed.to_string());
+            }
+            continue;
+        }
+
+        if CLOZE_RE.is_match(trimmed) {
+            let front = CLOZE_RE.replace_all(trimmed, CLOZE_PLACEHOLDER).to_string();
+            cards.push(Card {
+                key: card_key(CardKind::Cloze, &front),
+                kind: CardKind::Cloze,
+                back: CLOZE_RE.replace_all(trimmed, "$1").to_string(),
+                front,
+                line_number,
+            });
+        }
+    }
+    finish(&mut pending, &mut cards);
+
+    cards
+}
+
+struct PendingCard {
+    front: Vec<String>,
+    back: Vec<String>,
+    in_answer: bool,
+    line_number: usize,
+}
+
+/// Turn a pending Q/A block into a card; questions without an answer are
+/// dropped
+fn finish(pending: &mut Option<PendingCard>, cards: &mut Vec<Card>) {
+    let Some(card) = pending.take() else {
+        return;
+    };
+    let front = card.front.join("\n").trim().to_string();
+    let back = card.back.join("\n").trim().to_string();
+    if !card.in_answer || front.is_empty() || back.is_empty() {
+        return;
+    }
+    cards.push(Card {
+        key: card_key(CardKind::Basic, &front),
+        kind: CardKind::Basic,
+        front,
+        back,
+        line_number: card.line_number,
+    });
+}
+
+/// FNV-1a of the kind and front. Stored in the database, so unlike
+/// `DefaultHasher` it must not change between Rust releases.
+fn card_key(kind: CardKind, front: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in kind.as_str().bytes().chain([0]).chain(front.bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_basic_cards() {
+        let content = "---\ntags: [srs]\n---\n# Deck\n\nQ: What is SM-2?\nA: A spaced-repetition\nalgorithm.\n\nQ: Unanswered\n\nQ: Two\nlines?\nA: Yes";
+        let cards = extract_cards(content);
+
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].kind, CardKind::Basic);
+        assert_eq!(cards[0].front, "What is SM-2?");
+        assert_eq!(cards[0].back, "A spaced-repetition\nalgorithm.");
+        assert_eq!(cards[0].line_number, 6);
+        assert_eq!(cards[1].front, "Two\nlines?");
+        assert_eq!(cards[1].back, "Yes");
+    }
+
+    #[test]
+    fn test_extract_cloze_cards() {
+        let content = "The capital of {{c1::France}} is {{c2::Paris}}.\n\n```\n{{c::ignored}}\n```\nPlain line.";
+        let cards = extract_cards(content);
+
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].kind, CardKind::Cloze);
+        assert_eq!(cards[0].front, "The capital of [...] is [...].");
+        assert_eq!(cards[0].back, "The capital of France is Paris.");
+        assert_eq!(cards[0].line_number, 1);
+    }
+
+    #[test]
+    fn test_card_key_tracks_front_only() {
+        let a = extract_cards("Q: Question\nA: One");
+        let b = extract_cards("Q: Question\nA: Two");
+        let c = extract_cards("Q: Other question\nA: One");
+
+        assert_eq!(a[0].key, b[0].key);
+        assert_ne!(a[0].key, c[0].key);
+    }
+}
@@ -0,0 +1,10 @@
+//! Spaced-repetition module for Chronicle
+//!
+//! Flashcards are written inline in notes and picked up by the indexer;
+//! their review schedule (SM-2) lives in the database.
+
+pub mod cards;
+pub mod schedule;
+
+pub use cards::{extract_cards, Card, CardKind};
+pub use schedule::{Schedule, MAX_GRADE};
@@ -0,0 +1,110 @@
+//! SM-2 review scheduling
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// Ease factor new cards start with
+pub const INITIAL_EASE: f64 = 2.5;
+
+/// SM-2 never lets the ease factor drop below this
+const MIN_EASE: f64 = 1.3;
+
+/// Lowest grade that counts as a successful recall
+const PASSING_GRADE: u8 = 3;
+
+/// Highest grade (perfect recall)
+pub const MAX_GRADE: u8 = 5;
+
+/// Scheduling state of a card
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub ease: f64,
+    pub interval_days: i64,
+    /// Successful reviews in a row
+    pub repetitions: i64,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            ease: INITIAL_EASE,
+            interval_days: 0,
+            repetitions: 0,
+        }
+    }
+}
+
+impl Schedule {
+    /// Schedule after a review graded 0 (blackout) to 5 (perfect). Grades
+    /// above `MAX_GRADE` are treated as `MAX_GRADE`.
+    pub fn review(self, grade: u8) -> Self {
+        let grade = grade.min(MAX_GRADE);
+
+        let (interval_days, repetitions) = if grade >= PASSING_GRADE {
+            let interval = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval_days as f64 * self.ease).round() as i64,
+            };
+            (interval, self.repetitions + 1)
+        } else {
+            // Forgotten cards start over but keep their (lowered) ease
+            (1, 0)
+        };
+
+        let miss = f64::from(MAX_GRADE - grade);
+        let ease = (self.ease + 0.1 - miss * (0.08 + miss * 0.02)).max(MIN_EASE);
+
+        Self {
+            ease,
+            interval_days,
+            repetitions,
+        }
+    }
+
+    /// Next due date for a review done on `today`
+    pub fn due_after(&self, today: NaiveDate) -> NaiveDate {
+        today + Duration::days(self.interval_days)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intervals_grow() {
+        let s = Schedule::default().review(4);
+        assert_eq!((s.interval_days, s.repetitions), (1, 1));
+
+        let s = s.review(4);
+        assert_eq!((s.interval_days, s.repetitions), (6, 2));
+
+        let s = s.review(4);
+        assert_eq!(s.interval_days, 15);
+        assert!((s.ease - INITIAL_EASE).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_failed_review_resets() {
+        let s = Schedule::default().review(5).review(5).review(1);
+        assert_eq!((s.interval_days, s.repetitions), (1, 0));
+        assert!(s.ease < INITIAL_EASE + 0.2);
+    }
+
+    #[test]
+    fn test_ease_has_floor() {
+        let mut s = Schedule::default();
+        for _ in 0..20 {
+            s = s.review(0);
+        }
+        assert_eq!(s.ease, MIN_EASE);
+    }
+
+    #[test]
+    fn test_due_after() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 30).unwrap();
+        let s = Schedule::default().review(4).review(4);
+        assert_eq!(s.due_after(today), NaiveDate::from_ymd_opt(2026, 4, 5).unwrap());
+    }
+}
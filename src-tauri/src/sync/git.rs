@@ -1,9 +1,13 @@
 //! Git operations for sync
 
+use chrono::{Local, NaiveDate, TimeZone};
 use git2::{
-    Cred, FetchOptions, MergeOptions, PushOptions,
-    RemoteCallbacks, Repository, Signature, StatusOptions,
+    AnnotatedCommit, Commit, Cred, ErrorClass, ErrorCode, FetchOptions, Index, IndexEntry,
+    IndexTime, MergeOptions, PushOptions, RemoteCallbacks, Repository, Signature, Sort,
+    StatusOptions, TreeWalkMode, TreeWalkResult,
 };
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::path::Path;
 use thiserror::Error;
 
@@ -18,38 +22,130 @@ pub enum GitError {
     NoRemote,
     #[error("Remote URL required")]
     RemoteRequired,
+    #[error("Local and remote history have diverged; fast-forward not possible")]
+    NotFastForward,
+    #[error("Authentication failed: {0}")]
+    AuthenticationFailed(String),
+    #[error("Network unreachable: {0}")]
+    NetworkUnreachable(String),
+    #[error("Remote rejected the push: {0}")]
+    RemoteRejected(String),
+    #[error("Merge conflict: {0}")]
+    MergeConflict(String),
+    #[error("HEAD is detached; check out a branch to sync")]
+    DetachedHead,
     #[error("Git error: {0}")]
-    Git(#[from] git2::Error),
+    Git(git2::Error),
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
 
+// Classify libgit2 failures so callers can tell "check your credentials"
+// apart from "you're offline" without parsing messages
+impl From<git2::Error> for GitError {
+    fn from(err: git2::Error) -> Self {
+        let message = err.message().to_string();
+        match (err.code(), err.class()) {
+            (ErrorCode::Auth, _) => GitError::AuthenticationFailed(message),
+            (ErrorCode::NotFastForward, _) => GitError::RemoteRejected(message),
+            (ErrorCode::Conflict | ErrorCode::MergeConflict, _) => GitError::MergeConflict(message),
+            (_, ErrorClass::Net) => GitError::NetworkUnreachable(message),
+            (_, ErrorClass::Ssh | ErrorClass::Http) if is_auth_message(&message) => {
+                GitError::AuthenticationFailed(message)
+            }
+            (_, ErrorClass::Ssh | ErrorClass::Http) => GitError::NetworkUnreachable(message),
+            _ => GitError::Git(err),
+        }
+    }
+}
+
+/// SSH and HTTP transports report credential problems with a generic code
+fn is_auth_message(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    ["auth", "credential", "permission denied", "401", "403", "agent"]
+        .iter()
+        .any(|needle| lower.contains(needle))
+}
+
+/// How remote changes are integrated on pull
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PullStrategy {
+    /// Merge remote changes, creating a merge commit when histories diverge
+    #[default]
+    Merge,
+    /// Replay local commits on top of the remote; falls back to a merge
+    /// when the replay hits conflicts
+    Rebase,
+    /// Only accept remote changes that fast-forward the local branch
+    FastForwardOnly,
+}
+
+/// Notes as they were in one commit
+#[derive(Debug, Clone)]
+pub struct NotesAtRevision {
+    pub commit: String,
+    /// Commit time, Unix seconds
+    pub time: i64,
+    /// `(path, content)` of every Markdown file outside hidden folders
+    pub notes: Vec<(String, String)>,
+}
+
+const DEFAULT_AUTHOR_NAME: &str = "Chronicle";
+const DEFAULT_AUTHOR_EMAIL: &str = "chronicle@local";
+
 /// Git repository wrapper for Chronicle sync operations
 pub struct GitRepo {
     repo: Repository,
+    author_name: String,
+    author_email: String,
 }
 
 impl GitRepo {
     /// Open existing repository at path
     pub fn open(path: &Path) -> Result<Self, GitError> {
         let repo = Repository::open(path)?;
-        Ok(Self { repo })
+        Ok(Self::wrap(repo))
     }
 
     /// Initialize new repository at path
     pub fn init(path: &Path) -> Result<Self, GitError> {
-        let repo = Repository::init(path)?;
+        let repo = Self::wrap(Repository::init(path)?);
         
         // Create initial commit so we have a HEAD
         {
-            let sig = Signature::now("Chronicle", "chronicle@local")?;
-            let mut index = repo.index()?;
+            let sig = repo.signature()?;
+            let mut index = repo.repo.index()?;
             let tree_id = index.write_tree()?;
-            let tree = repo.find_tree(tree_id)?;
-            repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])?;
+            let tree = repo.repo.find_tree(tree_id)?;
+            repo.repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])?;
         }
         
-        Ok(Self { repo })
+        Ok(repo)
+    }
+
+    fn wrap(repo: Repository) -> Self {
+        Self {
+            repo,
+            author_name: DEFAULT_AUTHOR_NAME.to_string(),
+            author_email: DEFAULT_AUTHOR_EMAIL.to_string(),
+        }
+    }
+
+    /// Use a custom identity for commits made through this handle.
+    /// Empty values keep the default Chronicle identity.
+    pub fn with_author(mut self, name: &str, email: &str) -> Self {
+        if !name.trim().is_empty() {
+            self.author_name = name.trim().to_string();
+        }
+        if !email.trim().is_empty() {
+            self.author_email = email.trim().to_string();
+        }
+        self
+    }
+
+    fn signature(&self) -> Result<Signature<'static>, GitError> {
+        Ok(Signature::now(&self.author_name, &self.author_email)?)
     }
 
     /// Check if path is a git repository
@@ -59,6 +155,9 @@ impl GitRepo {
 
     /// Get current branch name
     pub fn current_branch(&self) -> Result<String, GitError> {
+        if self.repo.head_detached()? {
+            return Err(GitError::DetachedHead);
+        }
         let head = self.repo.head()?;
         let branch = head.shorthand().unwrap_or("main");
         Ok(branch.to_string())
@@ -88,9 +187,29 @@ impl GitRepo {
         index.add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)?;
         index.write()?;
 
+        self.commit_index(&mut index, message)
+    }
+
+    /// Stage only the given vault-relative paths and commit them
+    pub fn commit_paths(&self, paths: &[&str], message: &str) -> Result<String, GitError> {
+        let mut index = self.repo.index()?;
+        let workdir = self.repo.workdir().ok_or(GitError::NotInitialized)?;
+        for path in paths {
+            if workdir.join(path).exists() {
+                index.add_path(Path::new(path))?;
+            } else {
+                index.remove_path(Path::new(path))?;
+            }
+        }
+        index.write()?;
+
+        self.commit_index(&mut index, message)
+    }
+
+    fn commit_index(&self, index: &mut git2::Index, message: &str) -> Result<String, GitError> {
         let tree_id = index.write_tree()?;
         let tree = self.repo.find_tree(tree_id)?;
-        let sig = Signature::now("Chronicle", "chronicle@local")?;
+        let sig = self.signature()?;
 
         let parent = self.repo.head().ok().and_then(|h| h.peel_to_commit().ok());
         let parents: Vec<_> = parent.iter().collect();
@@ -165,23 +284,87 @@ impl GitRepo {
 
     /// Push to remote
     pub fn push(&self) -> Result<(), GitError> {
+        self.push_branch(&self.current_branch()?)
+    }
+
+    /// Push a branch to `origin`, whether or not it is checked out
+    pub fn push_branch(&self, branch: &str) -> Result<(), GitError> {
         let mut remote = self.repo.find_remote("origin").map_err(|_| GitError::NoRemote)?;
+        let rejection = RefCell::new(None);
         let mut callbacks = RemoteCallbacks::new();
         callbacks.credentials(|_url, username, _allowed| {
             Cred::ssh_key_from_agent(username.unwrap_or("git"))
         });
 
+        // The server reports per-ref rejections (hooks, protected
+        // branches) through this callback rather than as an error
+        callbacks.push_update_reference(|refname, status| {
+            if let Some(reason) = status {
+                *rejection.borrow_mut() = Some(format!("{}: {}", refname, reason));
+            }
+            Ok(())
+        });
+
         let mut push_opts = PushOptions::new();
         push_opts.remote_callbacks(callbacks);
 
-        let branch = self.current_branch()?;
         let refspec = format!("refs/heads/{}:refs/heads/{}", branch, branch);
         remote.push(&[&refspec], Some(&mut push_opts))?;
-        Ok(())
+        drop(push_opts);
+
+        match rejection.into_inner() {
+            Some(reason) => Err(GitError::RemoteRejected(reason)),
+            None => Ok(()),
+        }
     }
 
-    /// Pull (fetch + merge) from remote
-    pub fn pull(&self) -> Result<Vec<String>, GitError> {
+    /// Commit `files` (path and contents) as the whole tree of `branch`, on
+    /// top of its current tip, without touching the working tree. Returns
+    /// `None` when the branch already has exactly these files.
+    pub fn commit_files_to_branch(
+        &self,
+        branch: &str,
+        files: &[(String, Vec<u8>)],
+        message: &str,
+    ) -> Result<Option<String>, GitError> {
+        let mut index = Index::new()?;
+        for (path, contents) in files {
+            index.add(&IndexEntry {
+                ctime: IndexTime::new(0, 0),
+                mtime: IndexTime::new(0, 0),
+                dev: 0,
+                ino: 0,
+                mode: 0o100644,
+                uid: 0,
+                gid: 0,
+                file_size: contents.len() as u32,
+                id: self.repo.blob(contents)?,
+                flags: 0,
+                flags_extended: 0,
+                path: path.as_bytes().to_vec(),
+            })?;
+        }
+        let tree = self.repo.find_tree(index.write_tree_to(&self.repo)?)?;
+
+        let refname = format!("refs/heads/{}", branch);
+        let parent = match self.repo.find_reference(&refname) {
+            Ok(reference) => Some(reference.peel_to_commit()?),
+            Err(e) if e.code() == ErrorCode::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        if parent.as_ref().is_some_and(|p| p.tree_id() == tree.id()) {
+            return Ok(None);
+        }
+
+        let sig = self.signature()?;
+        let parents: Vec<&Commit> = parent.iter().collect();
+        let oid = self.repo.commit(Some(&refname), &sig, &sig, message, &tree, &parents)?;
+        Ok(Some(oid.to_string()))
+    }
+
+    /// Pull (fetch + integrate) from remote using the given strategy.
+    /// Returns the files left in conflict, if any.
+    pub fn pull(&self, strategy: PullStrategy) -> Result<Vec<String>, GitError> {
         self.fetch()?;
 
         let branch = self.current_branch()?;
@@ -204,8 +387,50 @@ impl GitRepo {
             return Ok(Vec::new());
         }
 
+        match strategy {
+            PullStrategy::FastForwardOnly => return Err(GitError::NotFastForward),
+            PullStrategy::Rebase => {
+                if self.rebase_onto(&fetch_commit)? {
+                    return Ok(Vec::new());
+                }
+                // Replay conflicted and was aborted; merge so conflict markers
+                // end up in the working tree for resolution.
+            }
+            PullStrategy::Merge => {}
+        }
+
+        self.merge_commit(&fetch_commit)
+    }
+
+    /// Replay local commits onto `upstream`. Returns `false` (with the
+    /// rebase aborted) if any commit conflicts.
+    fn rebase_onto(&self, upstream: &AnnotatedCommit) -> Result<bool, GitError> {
+        let sig = self.signature()?;
+        let mut rebase = self.repo.rebase(None, Some(upstream), None, None)?;
+
+        while let Some(op) = rebase.next() {
+            op?;
+            if self.repo.index()?.has_conflicts() {
+                rebase.abort()?;
+                return Ok(false);
+            }
+            if let Err(e) = rebase.commit(None, &sig, None) {
+                // Commits already present upstream are skipped
+                if e.code() != ErrorCode::Applied {
+                    rebase.abort()?;
+                    return Err(e.into());
+                }
+            }
+        }
+
+        rebase.finish(Some(&sig))?;
+        Ok(true)
+    }
+
+    /// Merge `fetch_commit` into HEAD, committing unless conflicts remain
+    fn merge_commit(&self, fetch_commit: &AnnotatedCommit) -> Result<Vec<String>, GitError> {
         // Need to do a real merge
-        self.repo.merge(&[&fetch_commit], Some(MergeOptions::new().fail_on_conflict(false)), None)?;
+        self.repo.merge(&[fetch_commit], Some(MergeOptions::new().fail_on_conflict(false)), None)?;
 
         // Check for conflicts
         let mut index = self.repo.index()?;
@@ -220,7 +445,7 @@ impl GitRepo {
         }
 
         // Commit the merge
-        let sig = Signature::now("Chronicle", "chronicle@local")?;
+        let sig = self.signature()?;
         let tree_id = index.write_tree()?;
         let tree = self.repo.find_tree(tree_id)?;
         let head_commit = self.repo.head()?.peel_to_commit()?;
@@ -280,6 +505,65 @@ impl GitRepo {
         index.write()?;
         Ok(())
     }
+
+    /// Commit a revision names: anything `git rev-parse` accepts, or a date
+    /// (`YYYY-MM-DD`) for the last commit on the current branch by the end
+    /// of that day
+    fn find_revision(&self, rev: &str) -> Result<Commit<'_>, GitError> {
+        let Ok(date) = NaiveDate::parse_from_str(rev.trim(), "%Y-%m-%d") else {
+            return Ok(self.repo.revparse_single(rev.trim())?.peel_to_commit()?);
+        };
+
+        let end_of_day = date.and_hms_opt(23, 59, 59).expect("valid time");
+        let cutoff = Local
+            .from_local_datetime(&end_of_day)
+            .latest()
+            .map_or(i64::MAX, |t| t.timestamp());
+        let mut walk = self.repo.revwalk()?;
+        walk.push_head()?;
+        walk.set_sorting(Sort::TIME)?;
+        for oid in walk {
+            let commit = self.repo.find_commit(oid?)?;
+            if commit.time().seconds() <= cutoff {
+                return Ok(commit);
+            }
+        }
+        Err(git2::Error::from_str(&format!("No commit on or before {}", rev)).into())
+    }
+
+    /// The Markdown notes committed at a revision (see `find_revision`)
+    pub fn notes_at(&self, rev: &str) -> Result<NotesAtRevision, GitError> {
+        let commit = self.find_revision(rev)?;
+        let tree = commit.tree()?;
+
+        let mut notes = Vec::new();
+        tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+            let Some(name) = entry.name() else {
+                return TreeWalkResult::Ok;
+            };
+            if name.starts_with('.') {
+                return TreeWalkResult::Skip;
+            }
+            if !name.ends_with(".md") {
+                return TreeWalkResult::Ok;
+            }
+            let content = entry
+                .to_object(&self.repo)
+                .ok()
+                .and_then(|object| object.into_blob().ok())
+                .and_then(|blob| String::from_utf8(blob.content().to_vec()).ok());
+            if let Some(content) = content {
+                notes.push((format!("{}{}", dir, name), content));
+            }
+            TreeWalkResult::Ok
+        })?;
+
+        Ok(NotesAtRevision {
+            commit: commit.id().to_string(),
+            time: commit.time().seconds(),
+            notes,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -325,6 +609,53 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_notes_at_revision() {
+        let temp = TempDir::new().unwrap();
+        let repo = GitRepo::init(temp.path()).unwrap();
+        fs::create_dir_all(temp.path().join("projects")).unwrap();
+        fs::create_dir_all(temp.path().join(".chronicle")).unwrap();
+        fs::write(temp.path().join("projects/alpha.md"), "# Alpha").unwrap();
+        fs::write(temp.path().join(".chronicle/x.md"), "hidden").unwrap();
+        fs::write(temp.path().join("image.png"), [0u8]).unwrap();
+        let first = repo.commit("Add alpha").unwrap();
+        fs::write(temp.path().join("beta.md"), "# Beta").unwrap();
+        repo.commit("Add beta").unwrap();
+
+        let before = repo.notes_at(&first).unwrap();
+        assert_eq!(before.notes, vec![("projects/alpha.md".to_string(), "# Alpha".to_string())]);
+        assert_eq!(repo.notes_at("HEAD").unwrap().notes.len(), 2);
+        assert_eq!(repo.notes_at("HEAD~1").unwrap().commit, first);
+
+        // By date: everything was committed today, nothing before 2000
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        assert_eq!(repo.notes_at(&today).unwrap().notes.len(), 2);
+        assert!(repo.notes_at("1999-12-31").is_err());
+    }
+
+    #[test]
+    fn test_commit_files_to_branch() {
+        let temp = TempDir::new().unwrap();
+        let repo = GitRepo::init(temp.path()).unwrap();
+        let checked_out = repo.current_branch().unwrap();
+
+        let files = vec![
+            ("index.html".to_string(), b"<h1>Notes</h1>".to_vec()),
+            ("notes/a.html".to_string(), b"<p>A</p>".to_vec()),
+        ];
+        let first = repo.commit_files_to_branch("gh-pages", &files, "Publish").unwrap();
+        assert!(first.is_some());
+        // Same files again: nothing to commit
+        assert_eq!(repo.commit_files_to_branch("gh-pages", &files, "Publish").unwrap(), None);
+
+        let branch = repo.repo.find_branch("gh-pages", git2::BranchType::Local).unwrap();
+        let tree = branch.get().peel_to_tree().unwrap();
+        assert!(tree.get_path(Path::new("notes/a.html")).is_ok());
+        // The working tree and checked-out branch are left alone
+        assert_eq!(repo.current_branch().unwrap(), checked_out);
+        assert!(!temp.path().join("index.html").exists());
+    }
+
     #[test]
     fn test_is_dirty() {
         let temp = TempDir::new().unwrap();
@@ -363,6 +694,135 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_commit_uses_custom_author() {
+        let temp = TempDir::new().unwrap();
+        let repo = GitRepo::init(temp.path())
+            .unwrap()
+            .with_author("Ada Lovelace", "ada@example.com");
+
+        fs::write(temp.path().join("test.md"), "# Test").unwrap();
+        repo.commit("Add test note").unwrap();
+
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.author().name(), Some("Ada Lovelace"));
+        assert_eq!(head.author().email(), Some("ada@example.com"));
+    }
+
+    #[test]
+    fn test_commit_paths_only_stages_given_files() {
+        let temp = TempDir::new().unwrap();
+        let repo = GitRepo::init(temp.path()).unwrap();
+
+        fs::write(temp.path().join("a.md"), "# A").unwrap();
+        fs::write(temp.path().join("b.md"), "# B").unwrap();
+        repo.commit_paths(&["a.md"], "Update a.md").unwrap();
+
+        let files = repo.changed_files().unwrap();
+        assert_eq!(files, vec!["b.md".to_string()]);
+    }
+
+    /// Two clones of a shared bare remote, each with its own commit
+    fn diverged_clones() -> (TempDir, GitRepo, GitRepo) {
+        let temp = TempDir::new().unwrap();
+        let remote = temp.path().join("remote.git");
+        let local = temp.path().join("local");
+        let other = temp.path().join("other");
+        Repository::init_bare(&remote).unwrap();
+
+        let local_repo = GitRepo::init(&local).unwrap();
+        local_repo.set_remote(remote.to_str().unwrap()).unwrap();
+        fs::write(local.join("shared.md"), "# Shared").unwrap();
+        local_repo.commit("Shared note").unwrap();
+        local_repo.push().unwrap();
+
+        Repository::clone(remote.to_str().unwrap(), &other).unwrap();
+        let other_repo = GitRepo::open(&other).unwrap();
+        fs::write(other.join("remote.md"), "# Remote").unwrap();
+        other_repo.commit("Remote note").unwrap();
+        other_repo.push().unwrap();
+
+        fs::write(local.join("local.md"), "# Local").unwrap();
+        local_repo.commit("Local note").unwrap();
+
+        (temp, local_repo, other_repo)
+    }
+
+    #[test]
+    fn test_pull_fast_forward_only_rejects_divergence() {
+        let (_temp, local, _other) = diverged_clones();
+        let result = local.pull(PullStrategy::FastForwardOnly);
+        assert!(matches!(result, Err(GitError::NotFastForward)));
+    }
+
+    #[test]
+    fn test_pull_rebase_linearizes_history() {
+        let (temp, local, _other) = diverged_clones();
+        let conflicts = local.pull(PullStrategy::Rebase).unwrap();
+        assert!(conflicts.is_empty());
+
+        assert!(temp.path().join("local/remote.md").exists());
+        assert!(temp.path().join("local/local.md").exists());
+
+        let head = local.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_count(), 1);
+        assert_eq!(head.message(), Some("Local note"));
+    }
+
+    #[test]
+    fn test_pull_merge_creates_merge_commit() {
+        let (temp, local, _other) = diverged_clones();
+        let conflicts = local.pull(PullStrategy::Merge).unwrap();
+        assert!(conflicts.is_empty());
+
+        assert!(temp.path().join("local/remote.md").exists());
+        let head = local.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_count(), 2);
+    }
+
+    #[test]
+    fn test_push_diverged_is_rejected() {
+        let (_temp, local, _other) = diverged_clones();
+        let result = local.push();
+        assert!(matches!(result, Err(GitError::RemoteRejected(_))));
+    }
+
+    #[test]
+    fn test_current_branch_detached_head() {
+        let temp = TempDir::new().unwrap();
+        let repo = GitRepo::init(temp.path()).unwrap();
+        let head = repo.repo.head().unwrap().target().unwrap();
+        repo.repo.set_head_detached(head).unwrap();
+
+        assert!(matches!(repo.current_branch(), Err(GitError::DetachedHead)));
+    }
+
+    #[test]
+    fn test_classify_git2_errors() {
+        let auth = git2::Error::new(ErrorCode::Auth, ErrorClass::Http, "bad token");
+        assert!(matches!(GitError::from(auth), GitError::AuthenticationFailed(_)));
+
+        let ssh = git2::Error::new(
+            ErrorCode::GenericError,
+            ErrorClass::Ssh,
+            "failed connecting with agent",
+        );
+        assert!(matches!(GitError::from(ssh), GitError::AuthenticationFailed(_)));
+
+        let offline = git2::Error::new(
+            ErrorCode::GenericError,
+            ErrorClass::Net,
+            "failed to resolve address for github.com",
+        );
+        assert!(matches!(GitError::from(offline), GitError::NetworkUnreachable(_)));
+
+        let conflict = git2::Error::new(ErrorCode::Conflict, ErrorClass::Checkout, "1 conflict");
+        assert!(matches!(GitError::from(conflict), GitError::MergeConflict(_)));
+
+        let other = git2::Error::new(ErrorCode::NotFound, ErrorClass::Reference, "missing");
+        assert!(matches!(GitError::from(other), GitError::Git(_)));
+    }
+
     #[test]
     fn test_status() {
         let temp = TempDir::new().unwrap();
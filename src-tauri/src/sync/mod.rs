@@ -7,6 +7,6 @@ pub mod git;
 pub mod conflict;
 pub mod status;
 
-pub use git::{GitRepo, GitError};
+pub use git::{GitRepo, GitError, NotesAtRevision, PullStrategy};
 pub use conflict::{ConflictInfo, ConflictResolution};
 pub use status::SyncStatus;
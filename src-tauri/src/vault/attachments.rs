@@ -0,0 +1,236 @@
+//! Attachment storage - where pasted and imported files live in the vault
+
+use chrono::NaiveDateTime;
+use std::path::Path;
+
+use crate::models::{AttachmentLocation, AttachmentsConfig, EmbedStyle};
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "avif"];
+
+/// Vault-relative folder for attachments of the note at `note_path`
+pub fn attachment_dir(note_path: &str, config: &AttachmentsConfig) -> String {
+    match config.location {
+        AttachmentLocation::VaultFolder => config.folder.trim_matches('/').to_string(),
+        AttachmentLocation::NoteSubfolder => {
+            let note = Path::new(note_path);
+            let stem = note
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let subfolder = config.subfolder.replace("{{note}}", &stem);
+            let subfolder = subfolder.trim_matches('/');
+
+            match note.parent().map(|p| p.to_string_lossy().to_string()) {
+                // An empty subfolder keeps attachments next to the note
+                Some(parent) if subfolder.is_empty() => parent,
+                Some(parent) if !parent.is_empty() => format!("{}/{}", parent, subfolder),
+                _ => subfolder.to_string(),
+            }
+        }
+    }
+}
+
+/// Attachment filename built from the configured name template.
+/// The original extension is always kept.
+pub fn attachment_file_name(
+    original_name: &str,
+    note_path: &str,
+    config: &AttachmentsConfig,
+    now: NaiveDateTime,
+) -> String {
+    let original = Path::new(original_name);
+    let name = original
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "attachment".to_string());
+    let extension = original.extension().map(|e| e.to_string_lossy().to_lowercase());
+    let note = Path::new(note_path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let stem = config
+        .name_template
+        .replace("{{name}}", &name)
+        .replace("{{note}}", &note)
+        .replace("{{date}}", &now.format("%Y-%m-%d").to_string())
+        .replace("{{time}}", &now.format("%H%M%S").to_string());
+    let stem = sanitize_component(&stem);
+    let stem = if stem.is_empty() { name } else { stem };
+
+    match extension {
+        Some(ext) if !ext.is_empty() => format!("{}.{}", stem, ext),
+        _ => stem,
+    }
+}
+
+/// `file_name`, or `stem-1.ext`, `stem-2.ext`, ... if that is already
+/// taken in `dir`
+pub fn unique_file_name(dir: &Path, file_name: &str) -> String {
+    if !dir.join(file_name).exists() {
+        return file_name.to_string();
+    }
+
+    let path = Path::new(file_name);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_name.to_string());
+    let extension = path.extension().map(|e| e.to_string_lossy().to_string());
+
+    (1..)
+        .map(|n| match &extension {
+            Some(ext) => format!("{}-{}.{}", stem, n, ext),
+            None => format!("{}-{}", stem, n),
+        })
+        .find(|candidate| !dir.join(candidate).exists())
+        .expect("unbounded counter always finds a free name")
+}
+
+/// Whether a file is an image, going by its extension
+pub fn is_image_path(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// Link text for an attachment at vault-relative `attachment_path`,
+/// inserted into the note at `note_path`. Images are embedded, other
+/// files linked.
+pub fn attachment_embed(note_path: &str, attachment_path: &str, style: EmbedStyle) -> String {
+    let file_name = attachment_path.rsplit('/').next().unwrap_or(attachment_path);
+    let bang = if is_image_path(file_name) { "!" } else { "" };
+
+    match style {
+        EmbedStyle::Wiki => format!("{}[[{}]]", bang, attachment_path),
+        EmbedStyle::Markdown => {
+            let target = relative_link(note_path, attachment_path);
+            // Angle brackets keep paths with spaces intact in CommonMark
+            let target = if target.contains(' ') {
+                format!("<{}>", target)
+            } else {
+                target
+            };
+            format!("{}[{}]({})", bang, file_name, target)
+        }
+    }
+}
+
+/// Path to `target` relative to the folder containing `from_note`, both
+/// vault-relative with `/` separators
+fn relative_link(from_note: &str, target: &str) -> String {
+    let from_dir: Vec<&str> = from_note.split('/').filter(|s| !s.is_empty()).collect();
+    let from_dir = &from_dir[..from_dir.len().saturating_sub(1)];
+    let target: Vec<&str> = target.split('/').filter(|s| !s.is_empty()).collect();
+
+    let common = from_dir
+        .iter()
+        .zip(&target)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut parts: Vec<&str> = vec![".."; from_dir.len() - common];
+    parts.extend_from_slice(&target[common..]);
+    parts.join("/")
+}
+
+/// Replace path separators and characters reserved on common filesystems
+fn sanitize_component(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c => c,
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn fixed_now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, 9)
+            .unwrap()
+            .and_hms_opt(14, 30, 5)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_attachment_dir_vault_folder() {
+        let config = AttachmentsConfig::default();
+        assert_eq!(attachment_dir("projects/alpha.md", &config), "attachments");
+    }
+
+    #[test]
+    fn test_attachment_dir_note_subfolder() {
+        let config = AttachmentsConfig {
+            location: AttachmentLocation::NoteSubfolder,
+            subfolder: "{{note}}.assets".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            attachment_dir("projects/alpha.md", &config),
+            "projects/alpha.assets"
+        );
+        assert_eq!(attachment_dir("root.md", &config), "root.assets");
+    }
+
+    #[test]
+    fn test_attachment_file_name_default() {
+        let config = AttachmentsConfig::default();
+        let name = attachment_file_name("Screen Shot.PNG", "alpha.md", &config, fixed_now());
+        assert_eq!(name, "Screen Shot.png");
+    }
+
+    #[test]
+    fn test_attachment_file_name_template() {
+        let config = AttachmentsConfig {
+            name_template: "{{note}}-{{date}}-{{time}}".to_string(),
+            ..Default::default()
+        };
+        let name = attachment_file_name("image.png", "notes/alpha.md", &config, fixed_now());
+        assert_eq!(name, "alpha-2026-03-09-143005.png");
+    }
+
+    #[test]
+    fn test_unique_file_name() {
+        let temp = tempfile::TempDir::new().unwrap();
+        assert_eq!(unique_file_name(temp.path(), "image.png"), "image.png");
+
+        std::fs::write(temp.path().join("image.png"), b"").unwrap();
+        std::fs::write(temp.path().join("image-1.png"), b"").unwrap();
+        assert_eq!(unique_file_name(temp.path(), "image.png"), "image-2.png");
+    }
+
+    #[test]
+    fn test_attachment_embed_markdown() {
+        assert_eq!(
+            attachment_embed("alpha.md", "attachments/shot.png", EmbedStyle::Markdown),
+            "![shot.png](attachments/shot.png)"
+        );
+        assert_eq!(
+            attachment_embed("projects/alpha.md", "attachments/Screen Shot.png", EmbedStyle::Markdown),
+            "![Screen Shot.png](<../attachments/Screen Shot.png>)"
+        );
+        assert_eq!(
+            attachment_embed("projects/alpha.md", "projects/alpha.assets/spec.pdf", EmbedStyle::Markdown),
+            "[spec.pdf](alpha.assets/spec.pdf)"
+        );
+    }
+
+    #[test]
+    fn test_attachment_embed_wiki() {
+        assert_eq!(
+            attachment_embed("projects/alpha.md", "attachments/shot.png", EmbedStyle::Wiki),
+            "![[attachments/shot.png]]"
+        );
+        assert_eq!(
+            attachment_embed("alpha.md", "attachments/spec.pdf", EmbedStyle::Wiki),
+            "[[attachments/spec.pdf]]"
+        );
+    }
+}
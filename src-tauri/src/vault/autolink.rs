@@ -0,0 +1,332 @@
+//! Auto-link suggestions
+//!
+//! Finds plain-text mentions of other notes' titles and aliases that could
+//! be turned into wiki links.
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::LazyLock;
+
+use super::parser::{extract_links, parse_frontmatter};
+
+/// Shorter names match too much ordinary text
+const MIN_NAME_CHARS: usize = 3;
+
+/// Spans that are never rewritten: inline code, existing links, autolinks,
+/// bare URLs and tags
+static SKIP_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"`[^`\n]*`|\[\[[^\]\n]*\]\]|\[[^\]\n]*\]\([^)\n]*\)|<[^>\s]+>|https?://\S+|#[\w/-]+")
+        .expect("Invalid auto-link skip regex")
+});
+
+/// A note that mentions can link to
+#[derive(Debug, Clone)]
+pub struct LinkTarget {
+    /// Vault-relative path of the note
+    pub path: String,
+    /// Title followed by any aliases
+    pub names: Vec<String>,
+}
+
+/// A mention that could become a wiki link
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AutoLinkSuggestion {
+    /// Byte offset of the mention in the note
+    pub start: usize,
+    /// Byte offset just past the mention
+    pub end: usize,
+    /// 1-indexed line of the mention
+    pub line_number: usize,
+    /// Mentioned text, as written in the note
+    pub text: String,
+    /// Path of the note it would link to
+    pub target_path: String,
+    /// Wiki link that replaces the mention
+    pub link: String,
+}
+
+/// Find mentions of the targets in a note. Only the first mention of each
+/// target is suggested, and targets the note already links to are skipped.
+pub fn find_auto_links(content: &str, targets: &[LinkTarget]) -> Vec<AutoLinkSuggestion> {
+    let linked: HashSet<String> = extract_links(content)
+        .into_iter()
+        .map(|link| link.target.to_lowercase())
+        .collect();
+
+    // Lowercased name -> target. Names shared by several notes are ambiguous
+    // and never suggested.
+    let mut by_name: HashMap<String, Option<usize>> = HashMap::new();
+    for (i, target) in targets.iter().enumerate() {
+        if !is_linkable(&target.path)
+            || linked.contains(&link_target(&target.path).to_lowercase())
+            || target
+                .names
+                .iter()
+                .any(|name| linked.contains(&name.trim().to_lowercase()))
+        {
+            continue;
+        }
+        for name in &target.names {
+            let name = name.trim();
+            if name.chars().count() < MIN_NAME_CHARS || !is_linkable(name) {
+                continue;
+            }
+            by_name
+                .entry(name.to_lowercase())
+                .and_modify(|t| {
+                    if *t != Some(i) {
+                        *t = None;
+                    }
+                })
+                .or_insert(Some(i));
+        }
+    }
+
+    let mut names: Vec<&String> = by_name
+        .iter()
+        .filter(|(_, target)| target.is_some())
+        .map(|(name, _)| name)
+        .collect();
+    if names.is_empty() {
+        return Vec::new();
+    }
+
+    // Longest first, so "Rust Book" wins over "Rust" at the same position
+    names.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+    let pattern = names
+        .iter()
+        .map(|name| word_pattern(name))
+        .collect::<Vec<_>>()
+        .join("|");
+    let re = match RegexBuilder::new(&pattern)
+        .case_insensitive(true)
+        .size_limit(64 << 20)
+        .build()
+    {
+        Ok(re) => re,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to build auto-link pattern");
+            return Vec::new();
+        }
+    };
+
+    let skipped = skipped_ranges(content);
+    let mut seen = HashSet::new();
+    let mut suggestions = Vec::new();
+
+    for m in re.find_iter(content) {
+        if skipped
+            .iter()
+            .any(|r| r.start < m.end() && m.start() < r.end)
+        {
+            continue;
+        }
+        let Some(Some(i)) = by_name.get(&m.as_str().to_lowercase()) else {
+            continue;
+        };
+        if !seen.insert(*i) {
+            continue;
+        }
+
+        let target_path = targets[*i].path.clone();
+        suggestions.push(AutoLinkSuggestion {
+            start: m.start(),
+            end: m.end(),
+            line_number: content[..m.start()].matches('\n').count() + 1,
+            text: m.as_str().to_string(),
+            link: wiki_link(&target_path, m.as_str()),
+            target_path,
+        });
+    }
+
+    suggestions
+}
+
+/// Replace accepted mentions with wiki links. Suggestions whose text no
+/// longer matches the content (the note changed since they were made) or
+/// that overlap another one are skipped.
+///
+/// Returns the new content and the number of links inserted.
+pub fn insert_auto_links(content: &str, accepted: &[AutoLinkSuggestion]) -> (String, usize) {
+    let mut accepted: Vec<&AutoLinkSuggestion> = accepted.iter().collect();
+    // Back to front so earlier offsets stay valid
+    accepted.sort_by_key(|s| std::cmp::Reverse(s.start));
+
+    let mut result = content.to_string();
+    let mut applied = 0;
+    let mut limit = content.len();
+
+    for suggestion in accepted {
+        let range = suggestion.start..suggestion.end;
+        if range.end > limit || content.get(range.clone()) != Some(suggestion.text.as_str()) {
+            continue;
+        }
+        // Rebuilt rather than trusted, so only well-formed links are written
+        result.replace_range(range, &wiki_link(&suggestion.target_path, &suggestion.text));
+        limit = suggestion.start;
+        applied += 1;
+    }
+
+    (result, applied)
+}
+
+/// Byte ranges mentions must not fall in: frontmatter, headings, fenced
+/// code and the spans matched by `SKIP_RE`
+fn skipped_ranges(content: &str) -> Vec<Range<usize>> {
+    let (_, body_start) = parse_frontmatter(content);
+    let mut ranges = Vec::new();
+    ranges.push(0..body_start);
+
+    let mut offset = 0;
+    let mut fence_start = None;
+    for line in content.split_inclusive('\n') {
+        let end = offset + line.len();
+        let trimmed = line.trim_start();
+        if offset >= body_start {
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                match fence_start.take() {
+                    Some(start) => ranges.push(start..end),
+                    None => fence_start = Some(offset),
+                }
+            } else if fence_start.is_none()
+                && trimmed.starts_with('#')
+                && trimmed.trim_start_matches('#').starts_with([' ', '\t'])
+            {
+                ranges.push(offset..end);
+            }
+        }
+        offset = end;
+    }
+    if let Some(start) = fence_start {
+        ranges.push(start..content.len());
+    }
+
+    ranges.extend(SKIP_RE.find_iter(content).map(|m| m.range()));
+    ranges
+}
+
+/// Escaped name, anchored at word boundaries where it starts or ends with a
+/// word character
+fn word_pattern(name: &str) -> String {
+    let boundary = |c: Option<char>| {
+        if c.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            r"\b"
+        } else {
+            ""
+        }
+    };
+    format!(
+        "{}{}{}",
+        boundary(name.chars().next()),
+        regex::escape(name),
+        boundary(name.chars().last())
+    )
+}
+
+/// Link target for a note path, matching how links are resolved
+fn link_target(path: &str) -> &str {
+    path.strip_suffix(".md").unwrap_or(path)
+}
+
+/// Characters that would break out of a `[[target|text]]` link
+fn is_linkable(s: &str) -> bool {
+    !s.contains(['[', ']', '|'])
+}
+
+pub(crate) fn wiki_link(target_path: &str, text: &str) -> String {
+    let target = link_target(target_path);
+    if target == text {
+        format!("[[{}]]", text)
+    } else {
+        format!("[[{}|{}]]", target, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(path: &str, names: &[&str]) -> LinkTarget {
+        LinkTarget {
+            path: path.to_string(),
+            names: names.iter().map(|n| n.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_find_auto_links() {
+        let content = "I read the rust book about Rust.\nThen rustacean things.";
+        let targets = [
+            target("Rust.md", &["Rust"]),
+            target("books/rust-book.md", &["Rust Book"]),
+        ];
+
+        let suggestions = find_auto_links(content, &targets);
+        assert_eq!(suggestions.len(), 2);
+
+        assert_eq!(suggestions[0].text, "rust book");
+        assert_eq!(suggestions[0].link, "[[books/rust-book|rust book]]");
+        assert_eq!(&content[suggestions[0].start..suggestions[0].end], "rust book");
+
+        // First standalone mention only; "rustacean" is not a word match
+        assert_eq!(suggestions[1].text, "Rust");
+        assert_eq!(suggestions[1].link, "[[Rust]]");
+        assert_eq!(suggestions[1].line_number, 1);
+    }
+
+    #[test]
+    fn test_find_auto_links_skips_code_links_and_headings() {
+        let content = "---\ntitle: Notes on Rust\n---\n# Rust\n\n`Rust` and [Rust](https://rust-lang.org) #rust\n\n```\nRust\n```\nLater, Rust.";
+        let suggestions = find_auto_links(content, &[target("Rust.md", &["Rust"])]);
+
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].line_number, 11);
+    }
+
+    #[test]
+    fn test_find_auto_links_skips_linked_and_ambiguous() {
+        let content = "Rust, Go and Zig. See [[rust]].";
+        let targets = [
+            target("Rust.md", &["Rust"]),
+            target("go.md", &["Go"]),
+            target("a/zig.md", &["Zig"]),
+            target("b/zig.md", &["Zig"]),
+        ];
+
+        assert!(find_auto_links(content, &targets).is_empty());
+    }
+
+    #[test]
+    fn test_find_auto_links_aliases() {
+        let content = "Everything about rustlang.";
+        let suggestions = find_auto_links(content, &[target("Rust.md", &["Rust", "rustlang"])]);
+
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].link, "[[Rust|rustlang]]");
+    }
+
+    #[test]
+    fn test_insert_auto_links() {
+        let content = "Rust and the Rust Book.";
+        let targets = [
+            target("Rust.md", &["Rust"]),
+            target("rust-book.md", &["Rust Book"]),
+        ];
+        let suggestions = find_auto_links(content, &targets);
+
+        let (updated, applied) = insert_auto_links(content, &suggestions);
+        assert_eq!(applied, 2);
+        assert_eq!(updated, "[[Rust]] and the [[rust-book|Rust Book]].");
+    }
+
+    #[test]
+    fn test_insert_auto_links_skips_stale() {
+        let suggestions = find_auto_links("Rust is fun.", &[target("Rust.md", &["Rust"])]);
+
+        let (updated, applied) = insert_auto_links("Go is fun.", &suggestions);
+        assert_eq!(applied, 0);
+        assert_eq!(updated, "Go is fun.");
+    }
+}
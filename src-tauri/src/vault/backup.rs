@@ -0,0 +1,328 @@
+//! Timestamped zip backups of a vault
+//!
+//! An archive holds the vault's notes and attachments plus the JSON files
+//! under `.chronicle` (workspaces, smart folders). The index database is
+//! left out since it is rebuilt from the notes, as are hidden folders such
+//! as `.git`.
+
+use chrono::{Local, NaiveDateTime, TimeZone};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::indexer::{is_indexed_file, walkdir};
+use super::sidecar::is_sidecar;
+
+/// Timestamp in archive names, sortable as text
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+const TIMESTAMP_LEN: usize = 15;
+const BACKUP_EXTENSION: &str = "zip";
+
+/// An archive in the backup folder
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BackupInfo {
+    /// File name, which `restore_backup` takes
+    pub name: String,
+    pub path: String,
+    /// Archive size in bytes
+    pub size: u64,
+    /// RFC 3339 timestamp of when the backup was taken
+    pub created_at: String,
+}
+
+/// Name the vault's archives start with
+fn archive_prefix(vault_path: &Path) -> String {
+    let name = vault_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if name.is_empty() {
+        "vault".to_string()
+    } else {
+        name
+    }
+}
+
+/// When an archive of this vault was taken and its tiebreak for backups
+/// taken within the same second, or `None` for any other file
+fn parse_archive_name(prefix: &str, name: &str) -> Option<(NaiveDateTime, u32)> {
+    let stem = name
+        .strip_suffix(BACKUP_EXTENSION)?
+        .strip_suffix('.')?
+        .strip_prefix(prefix)?
+        .strip_prefix('-')?;
+    let timestamp = stem.get(..TIMESTAMP_LEN)?;
+    let taken = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+    let sequence = match &stem[TIMESTAMP_LEN..] {
+        "" => 0,
+        rest => rest.strip_prefix('-')?.parse().ok()?,
+    };
+    Some((taken, sequence))
+}
+
+/// Files that go into an archive, with their archive names
+fn backup_files(
+    vault_path: &Path,
+    backup_dir: &Path,
+    include_attachments: bool,
+) -> io::Result<Vec<(PathBuf, String)>> {
+    let mut files: Vec<PathBuf> = walkdir(vault_path)?
+        .into_iter()
+        // A backup folder inside the vault would otherwise back itself up
+        .filter(|path| !path.starts_with(backup_dir))
+        .filter(|path| include_attachments || is_indexed_file(path) || is_sidecar(path))
+        .collect();
+
+    let meta_dir = vault_path.join(".chronicle");
+    if meta_dir.is_dir() {
+        for entry in fs::read_dir(&meta_dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().is_some_and(|e| e == "json") {
+                files.push(path);
+            }
+        }
+    }
+
+    Ok(files
+        .into_iter()
+        .filter_map(|path| {
+            let rel = path.strip_prefix(vault_path).ok()?;
+            let name = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            Some((path, name))
+        })
+        .collect())
+}
+
+/// Zip the vault into `backup_dir` under a timestamped name
+pub fn create_backup(
+    vault_path: &Path,
+    backup_dir: &Path,
+    include_attachments: bool,
+) -> io::Result<BackupInfo> {
+    fs::create_dir_all(backup_dir)?;
+
+    let prefix = archive_prefix(vault_path);
+    let timestamp = Local::now().format(TIMESTAMP_FORMAT).to_string();
+    let mut name = format!("{}-{}.{}", prefix, timestamp, BACKUP_EXTENSION);
+    let mut sequence = 1;
+    while backup_dir.join(&name).exists() {
+        name = format!("{}-{}-{}.{}", prefix, timestamp, sequence, BACKUP_EXTENSION);
+        sequence += 1;
+    }
+    let path = backup_dir.join(&name);
+
+    // Write then rename so an interrupted backup never looks complete
+    let tmp = path.with_extension("zip.tmp");
+    let written = write_archive(
+        &tmp,
+        &backup_files(vault_path, backup_dir, include_attachments)?,
+    )
+    .and_then(|_| fs::rename(&tmp, &path));
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+
+    backup_info(&prefix, &path)?
+        .ok_or_else(|| io::Error::other(format!("Unexpected backup name: {}", name)))
+}
+
+fn write_archive(archive: &Path, files: &[(PathBuf, String)]) -> io::Result<()> {
+    let mut zip = ZipWriter::new(BufWriter::new(File::create(archive)?));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for (path, name) in files {
+        zip.start_file(name.as_str(), options).map_err(io::Error::other)?;
+        io::copy(&mut File::open(path)?, &mut zip)?;
+    }
+    zip.finish().map_err(io::Error::other)?;
+    Ok(())
+}
+
+fn backup_info(prefix: &str, path: &Path) -> io::Result<Option<BackupInfo>> {
+    let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+        return Ok(None);
+    };
+    let Some((taken, _)) = parse_archive_name(prefix, &name) else {
+        return Ok(None);
+    };
+    let created_at = Local
+        .from_local_datetime(&taken)
+        .earliest()
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_else(|| taken.to_string());
+
+    Ok(Some(BackupInfo {
+        name,
+        path: path.to_string_lossy().to_string(),
+        size: fs::metadata(path)?.len(),
+        created_at,
+    }))
+}
+
+/// Archives of this vault in `backup_dir`, newest first
+pub fn list_backups(vault_path: &Path, backup_dir: &Path) -> io::Result<Vec<BackupInfo>> {
+    if !backup_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let prefix = archive_prefix(vault_path);
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(backup_dir)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            continue;
+        };
+        if let Some(order) = parse_archive_name(&prefix, &name) {
+            if let Some(info) = backup_info(&prefix, &path)? {
+                backups.push((order, info));
+            }
+        }
+    }
+
+    backups.sort_by_key(|(order, _)| Reverse(*order));
+    Ok(backups.into_iter().map(|(_, info)| info).collect())
+}
+
+/// Delete all but the newest `keep` archives of this vault, returning how
+/// many were removed. Zero keeps everything.
+pub fn prune_backups(vault_path: &Path, backup_dir: &Path, keep: usize) -> io::Result<usize> {
+    if keep == 0 {
+        return Ok(0);
+    }
+
+    let mut removed = 0;
+    for old in list_backups(vault_path, backup_dir)?.into_iter().skip(keep) {
+        fs::remove_file(&old.path)?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Extract an archive over the vault, returning the number of files
+/// written. Files the archive doesn't contain are left alone.
+pub fn restore_backup(vault_path: &Path, archive: &Path) -> io::Result<usize> {
+    let mut zip = ZipArchive::new(BufReader::new(File::open(archive)?))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let mut restored = 0;
+    for i in 0..zip.len() {
+        let mut entry = zip
+            .by_index(i)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if entry.is_dir() {
+            continue;
+        }
+        // Entries that would land outside the vault are skipped
+        let Some(rel) = entry.enclosed_name() else {
+            tracing::warn!(entry = entry.name(), "Skipping unsafe backup entry");
+            continue;
+        };
+        let target = vault_path.join(&rel);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut entry, &mut File::create(&target)?)?;
+        restored += 1;
+    }
+
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn vault() -> (TempDir, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("notes");
+        fs::create_dir_all(root.join("projects")).unwrap();
+        fs::create_dir_all(root.join(".chronicle")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(root.join("index.md"), "# Index").unwrap();
+        fs::write(root.join("projects/plan.md"), "# Plan").unwrap();
+        fs::write(root.join("projects/photo.png"), [0u8, 1, 2]).unwrap();
+        fs::write(root.join(".chronicle/workspaces.json"), "[]").unwrap();
+        fs::write(root.join(".chronicle/chronicle.db"), "index").unwrap();
+        fs::write(root.join(".git/HEAD"), "ref").unwrap();
+        (dir, root)
+    }
+
+    #[test]
+    fn test_backup_round_trip() {
+        let (dir, root) = vault();
+        let backups = dir.path().join("backups");
+
+        let info = create_backup(&root, &backups, true).unwrap();
+        assert!(info.name.starts_with("notes-"));
+        assert!(info.size > 0);
+
+        fs::write(root.join("index.md"), "changed").unwrap();
+        fs::remove_file(root.join("projects/plan.md")).unwrap();
+
+        let restored = restore_backup(&root, Path::new(&info.path)).unwrap();
+        assert_eq!(restored, 4);
+        assert_eq!(fs::read_to_string(root.join("index.md")).unwrap(), "# Index");
+        assert_eq!(fs::read_to_string(root.join("projects/plan.md")).unwrap(), "# Plan");
+    }
+
+    #[test]
+    fn test_backup_skips_index_and_hidden_folders() {
+        let (dir, root) = vault();
+        let info = create_backup(&root, &dir.path().join("backups"), false).unwrap();
+
+        let restore_to = dir.path().join("restored");
+        fs::create_dir_all(&restore_to).unwrap();
+        restore_backup(&restore_to, Path::new(&info.path)).unwrap();
+
+        assert!(restore_to.join("index.md").exists());
+        assert!(restore_to.join(".chronicle/workspaces.json").exists());
+        assert!(!restore_to.join("projects/photo.png").exists());
+        assert!(!restore_to.join(".chronicle/chronicle.db").exists());
+        assert!(!restore_to.join(".git").exists());
+    }
+
+    #[test]
+    fn test_list_and_prune_backups() {
+        let (dir, root) = vault();
+        let backups = dir.path().join("backups");
+        fs::create_dir_all(&backups).unwrap();
+        fs::write(backups.join("other-20240101-000000.zip"), "").unwrap();
+        fs::write(backups.join("notes-20240101-000000.zip"), "").unwrap();
+        fs::write(backups.join("notes-20240102-000000.zip"), "").unwrap();
+        fs::write(backups.join("notes-20240102-000000-1.zip"), "").unwrap();
+        fs::write(backups.join("notes-backup.zip"), "").unwrap();
+
+        let names: Vec<_> = list_backups(&root, &backups)
+            .unwrap()
+            .into_iter()
+            .map(|b| b.name)
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "notes-20240102-000000-1.zip",
+                "notes-20240102-000000.zip",
+                "notes-20240101-000000.zip",
+            ]
+        );
+
+        assert_eq!(prune_backups(&root, &backups, 2).unwrap(), 1);
+        assert!(!backups.join("notes-20240101-000000.zip").exists());
+        assert!(backups.join("other-20240101-000000.zip").exists());
+        assert_eq!(prune_backups(&root, &backups, 0).unwrap(), 0);
+    }
+}
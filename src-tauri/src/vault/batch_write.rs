@@ -0,0 +1,51 @@
+//! Rewriting several notes as one change
+
+use std::fs;
+use std::path::Path;
+
+use crate::error::ChronicleError;
+
+/// Write each `(path, original, updated)` in order. If a write fails, the
+/// notes already written get their original content back, so a batch edit
+/// is never left half applied.
+pub fn write_all_or_restore(writes: &[(&Path, &str, &str)]) -> Result<(), ChronicleError> {
+    for (i, (path, _, updated)) in writes.iter().enumerate() {
+        if let Err(e) = fs::write(path, updated) {
+            for (written, original, _) in &writes[..i] {
+                if let Err(e) = fs::write(written, original) {
+                    tracing::error!(
+                        path = %written.display(),
+                        error = %e,
+                        "Failed to restore note"
+                    );
+                }
+            }
+            return Err(e.into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_failed_write_restores_earlier_notes() {
+        let temp = TempDir::new().unwrap();
+        let first = temp.path().join("a.md");
+        fs::write(&first, "# A").unwrap();
+        let missing = temp.path().join("gone/b.md");
+
+        let writes = [
+            (first.as_path(), "# A", "# A edited"),
+            (missing.as_path(), "# B", "# B edited"),
+        ];
+        assert!(write_all_or_restore(&writes).is_err());
+        assert_eq!(fs::read_to_string(&first).unwrap(), "# A");
+
+        write_all_or_restore(&writes[..1]).unwrap();
+        assert_eq!(fs::read_to_string(&first).unwrap(), "# A edited");
+    }
+}
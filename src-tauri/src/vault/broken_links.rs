@@ -0,0 +1,465 @@
+//! Broken links: grouping unresolved links, suggesting notes they may have
+//! meant, and rewriting them across notes
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use super::parser::{markdown_link_path, resolve_markdown_link, MARKDOWN_LINK_RE};
+use crate::db::UnresolvedLink;
+
+static LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(!?)\[\[([^\]|]+)(?:\|([^\]]+))?\]\]").expect("Invalid link regex")
+});
+
+/// Fewest characters in common, as a share of the longer name, for a note
+/// to be suggested
+const MIN_SIMILARITY: f64 = 0.5;
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Every link to a missing note, by target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrokenLink {
+    pub target: String,
+    pub occurrences: Vec<LinkOccurrence>,
+    /// Paths of existing notes with similar names, closest first
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkOccurrence {
+    pub source_path: String,
+    pub line_number: Option<i32>,
+    pub display_text: Option<String>,
+}
+
+/// How often a missing note is linked to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnresolvedTarget {
+    pub target: String,
+    /// Notes linking to it
+    pub source_count: usize,
+    pub link_count: usize,
+}
+
+/// How to rewrite links to a target
+pub enum LinkEdit<'a> {
+    /// Point them at another note, keeping any `#heading` and display text
+    Retarget(&'a str),
+    /// Replace them, embeds included, with their display text or else the
+    /// target's name
+    Unlink,
+}
+
+/// Group unresolved links by target, ignoring case, with suggestions from
+/// `note_paths`
+pub fn group_broken_links(links: Vec<UnresolvedLink>, note_paths: &[String]) -> Vec<BrokenLink> {
+    let mut groups: Vec<BrokenLink> = Vec::new();
+    for link in links {
+        let occurrence = LinkOccurrence {
+            source_path: link.source_path,
+            line_number: link.line_number,
+            display_text: link.display_text,
+        };
+        match groups.iter_mut().find(|g| g.target.eq_ignore_ascii_case(&link.target)) {
+            Some(group) => group.occurrences.push(occurrence),
+            None => groups.push(BrokenLink {
+                suggestions: suggest_notes(&link.target, note_paths),
+                target: link.target,
+                occurrences: vec![occurrence],
+            }),
+        }
+    }
+    groups
+}
+
+/// Count unresolved links by target, ignoring case, most linked first
+pub fn count_unresolved_links(links: &[UnresolvedLink]) -> Vec<UnresolvedTarget> {
+    let mut targets: Vec<(UnresolvedTarget, HashSet<&str>)> = Vec::new();
+    for link in links {
+        let found = targets
+            .iter()
+            .position(|(t, _)| t.target.eq_ignore_ascii_case(&link.target));
+        let index = match found {
+            Some(index) => index,
+            None => {
+                let target = UnresolvedTarget {
+                    target: link.target.clone(),
+                    source_count: 0,
+                    link_count: 0,
+                };
+                targets.push((target, HashSet::new()));
+                targets.len() - 1
+            }
+        };
+        let (target, sources) = &mut targets[index];
+        sources.insert(&link.source_path);
+        target.link_count += 1;
+        target.source_count = sources.len();
+    }
+
+    let mut targets: Vec<UnresolvedTarget> = targets.into_iter().map(|(t, _)| t).collect();
+    targets.sort_by(|a, b| {
+        b.source_count
+            .cmp(&a.source_count)
+            .then(b.link_count.cmp(&a.link_count))
+            .then_with(|| a.target.to_lowercase().cmp(&b.target.to_lowercase()))
+    });
+    targets
+}
+
+/// Notes whose path or filename is close to `target`, closest first
+pub fn suggest_notes(target: &str, note_paths: &[String]) -> Vec<String> {
+    let target = target.to_lowercase();
+    let target_name = target.rsplit('/').next().unwrap_or(&target);
+
+    let mut scored: Vec<(f64, &String)> = note_paths
+        .iter()
+        .filter_map(|path| {
+            let lower = path.to_lowercase();
+            let stem = lower.strip_suffix(".md").unwrap_or(&lower);
+            let name = stem.rsplit('/').next().unwrap_or(stem);
+            let score = similarity(&target, stem).max(similarity(target_name, name));
+            (score >= MIN_SIMILARITY).then_some((score, path))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    scored.into_iter().take(MAX_SUGGESTIONS).map(|(_, path)| path.clone()).collect()
+}
+
+/// 1 minus the edit distance as a share of the longer string
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
+/// Rewrite every link and embed to `target` (ignoring case and any
+/// `#heading`), outside fenced code. Returns the new content and how many
+/// links changed.
+pub fn rewrite_links(content: &str, target: &str, edit: &LinkEdit) -> (String, usize) {
+    let mut changed = 0;
+    let mut in_fence = false;
+    let mut lines = Vec::new();
+
+    for line in content.split('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        if in_fence || !line.contains("[[") {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let rewritten = LINK_RE.replace_all(line, |c: &regex::Captures| {
+            let link = c[2].trim();
+            let (name, anchor) = match link.find('#') {
+                Some(i) => (link[..i].trim(), &link[i..]),
+                None => (link, ""),
+            };
+            if !name.eq_ignore_ascii_case(target) {
+                return c[0].to_string();
+            }
+            changed += 1;
+            match edit {
+                LinkEdit::Retarget(new_target) => {
+                    let display = c.get(3).map(|d| format!("|{}", d.as_str())).unwrap_or_default();
+                    format!("{}[[{}{}{}]]", &c[1], new_target, anchor, display)
+                }
+                LinkEdit::Unlink => c.get(3).map_or(name, |d| d.as_str().trim()).to_string(),
+            }
+        });
+        lines.push(rewritten.into_owned());
+    }
+
+    (lines.join("\n"), changed)
+}
+
+/// Rewrite every Markdown link in the note at `source_path` that leads to
+/// the vault path `target` (ignoring case, any `#heading` and a missing
+/// `.md`), outside fenced code. Retargeted links stay relative to the
+/// note's folder, or to the vault root if they were, and keep leaving out
+/// `.md` if they did. Returns the new content and how many links changed.
+pub fn rewrite_markdown_links(
+    content: &str,
+    source_path: &str,
+    target: &str,
+    edit: &LinkEdit,
+) -> (String, usize) {
+    replace_markdown_links(content, |link| {
+        let path = resolve_markdown_link(source_path, &link.path)?;
+        let bare = !path.eq_ignore_ascii_case(target);
+        if bare && !format!("{}.md", path).eq_ignore_ascii_case(target) {
+            return None;
+        }
+        Some(match edit {
+            LinkEdit::Retarget(new_target) => {
+                let new_target = if bare {
+                    new_target.strip_suffix(".md").unwrap_or(new_target)
+                } else {
+                    new_target
+                };
+                MarkdownReplacement::Target(link.written_as(source_path, new_target))
+            }
+            LinkEdit::Unlink => MarkdownReplacement::Text,
+        })
+    })
+}
+
+/// Keep the relative Markdown links of a note moved from `old_path` to
+/// `new_path` leading where they did, including links to itself. Returns
+/// the new content and how many links changed.
+pub fn rebase_markdown_links(content: &str, old_path: &str, new_path: &str) -> (String, usize) {
+    replace_markdown_links(content, |link| {
+        if link.path.starts_with('/') {
+            return None;
+        }
+        let path = resolve_markdown_link(old_path, &link.path)?;
+        let path = if path.eq_ignore_ascii_case(old_path) { new_path.to_string() } else { path };
+        let written = link.written_as(new_path, &path);
+        (written != link.raw).then_some(MarkdownReplacement::Target(written))
+    })
+}
+
+/// A Markdown link's target as found in a note
+struct MarkdownLinkTarget<'a> {
+    /// The target as written, including any `#heading`
+    raw: &'a str,
+    /// Decoded path, without the `#heading`
+    path: String,
+    /// `#heading`, if any
+    anchor: &'a str,
+    /// Whether the target is in `<...>`, so needs no escaping
+    angled: bool,
+}
+
+impl MarkdownLinkTarget<'_> {
+    /// How a link from the note at `source_path` to the vault path `path`
+    /// is written: rooted if this one was, otherwise relative
+    fn written_as(&self, source_path: &str, path: &str) -> String {
+        let path = if self.path.starts_with('/') {
+            format!("/{}", path)
+        } else {
+            relative_link(source_path, path)
+        };
+        let path = if self.angled {
+            path
+        } else {
+            path.replace(' ', "%20").replace('(', "%28").replace(')', "%29")
+        };
+        format!("{}{}", path, self.anchor)
+    }
+}
+
+enum MarkdownReplacement {
+    /// Point the link at this target, as written
+    Target(String),
+    /// Replace the link with its text
+    Text,
+}
+
+/// Apply `replace` to every Markdown link outside fenced code, except
+/// images and URLs
+fn replace_markdown_links(
+    content: &str,
+    mut replace: impl FnMut(&MarkdownLinkTarget) -> Option<MarkdownReplacement>,
+) -> (String, usize) {
+    let mut changed = 0;
+    let mut in_fence = false;
+    let mut lines = Vec::new();
+
+    for line in content.split('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        if in_fence || !line.contains("](") {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let rewritten = MARKDOWN_LINK_RE.replace_all(line, |c: &regex::Captures| {
+            let whole = c.get(0).expect("match has a whole capture");
+            let (angled, raw) = match c.get(3) {
+                Some(raw) => (true, raw),
+                None => (false, c.get(4).expect("link has a target")),
+            };
+            let anchor = raw.as_str().find('#').map_or("", |i| &raw.as_str()[i..]);
+            let path = markdown_link_path(raw.as_str());
+            let replacement = match path {
+                Some(path) if &c[1] != "!" => {
+                    replace(&MarkdownLinkTarget { raw: raw.as_str(), path, anchor, angled })
+                }
+                _ => None,
+            };
+            let Some(replacement) = replacement else {
+                return whole.as_str().to_string();
+            };
+            changed += 1;
+            match replacement {
+                MarkdownReplacement::Target(target) => {
+                    // Splice the target in, keeping the text and any title
+                    let (start, end) = (raw.start() - whole.start(), raw.end() - whole.start());
+                    let link = whole.as_str();
+                    format!("{}{}{}", &link[..start], target, &link[end..])
+                }
+                MarkdownReplacement::Text => {
+                    let text = c[2].trim();
+                    if text.is_empty() {
+                        let path = markdown_link_path(raw.as_str()).unwrap_or_default();
+                        let name = path.rsplit('/').next().unwrap_or_default();
+                        name.strip_suffix(".md").unwrap_or(name).to_string()
+                    } else {
+                        text.to_string()
+                    }
+                }
+            }
+        });
+        lines.push(rewritten.into_owned());
+    }
+
+    (lines.join("\n"), changed)
+}
+
+/// The relative path from the note at `source_path` to the vault path
+/// `target`
+fn relative_link(source_path: &str, target: &str) -> String {
+    let mut from: Vec<&str> = source_path.split('/').collect();
+    from.pop();
+    let to: Vec<&str> = target.split('/').collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts = vec![".."; from.len() - common];
+    parts.extend(&to[common..]);
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_suggest_notes() {
+        let notes = paths(&["Projects/Roadmap.md", "roadmaps.md", "recipes.md", "daily/2025-01-01.md"]);
+        assert_eq!(suggest_notes("Roadmap", &notes), vec!["Projects/Roadmap.md", "roadmaps.md"]);
+        assert_eq!(suggest_notes("projects/road-map", &notes)[0], "Projects/Roadmap.md");
+        assert!(suggest_notes("zzz", &notes).is_empty());
+    }
+
+    #[test]
+    fn test_group_broken_links() {
+        let link = |target: &str, source: &str| UnresolvedLink {
+            target: target.to_string(),
+            source_path: source.to_string(),
+            line_number: Some(1),
+            display_text: None,
+        };
+        let groups = group_broken_links(
+            vec![link("Roadmap", "a.md"), link("roadmap", "b.md"), link("Nowhere", "a.md")],
+            &paths(&["Projects/Roadmap.md"]),
+        );
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].occurrences.len(), 2);
+        assert_eq!(groups[0].suggestions, vec!["Projects/Roadmap.md"]);
+        assert!(groups[1].suggestions.is_empty());
+    }
+
+    #[test]
+    fn test_count_unresolved_links() {
+        let link = |target: &str, source: &str, line: i32| UnresolvedLink {
+            target: target.to_string(),
+            source_path: source.to_string(),
+            line_number: Some(line),
+            display_text: None,
+        };
+        let links = vec![
+            link("Nowhere", "a.md", 1),
+            link("Roadmap", "a.md", 2),
+            link("roadmap", "a.md", 3),
+            link("Roadmap", "b.md", 1),
+        ];
+        let count = |target: &str, source_count, link_count| UnresolvedTarget {
+            target: target.to_string(),
+            source_count,
+            link_count,
+        };
+        assert_eq!(
+            count_unresolved_links(&links),
+            vec![count("Roadmap", 2, 3), count("Nowhere", 1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_rewrite_links() {
+        let content = "See [[roadmap]], [[Roadmap#Q1|the plan]] and ![[roadmap]].\n```\n[[roadmap]]\n```\n[[Other]]\n";
+
+        let (retargeted, n) = rewrite_links(content, "Roadmap", &LinkEdit::Retarget("Projects/Roadmap"));
+        assert_eq!(n, 3);
+        assert_eq!(
+            retargeted,
+            "See [[Projects/Roadmap]], [[Projects/Roadmap#Q1|the plan]] and ![[Projects/Roadmap]].\n```\n[[roadmap]]\n```\n[[Other]]\n"
+        );
+
+        let (unlinked, n) = rewrite_links(content, "roadmap", &LinkEdit::Unlink);
+        assert_eq!(n, 3);
+        assert_eq!(unlinked, "See roadmap, the plan and roadmap.\n```\n[[roadmap]]\n```\n[[Other]]\n");
+    }
+
+    #[test]
+    fn test_rewrite_markdown_links() {
+        let content = "[plan](../Roadmap.md#q1 \"Plan\"), [root](/Roadmap.md), \
+                       ![img](../Roadmap.md)\n\
+                       ```\n[plan](../Roadmap.md)\n```\n[other](Other.md) [bare](../Roadmap)\n";
+        let retarget = LinkEdit::Retarget("Projects/Road map.md");
+        let (retargeted, n) =
+            rewrite_markdown_links(content, "Daily/Today.md", "roadmap.md", &retarget);
+        assert_eq!(n, 3);
+        assert_eq!(
+            retargeted,
+            "[plan](../Projects/Road%20map.md#q1 \"Plan\"), [root](/Projects/Road%20map.md), \
+             ![img](../Roadmap.md)\n```\n[plan](../Roadmap.md)\n```\n\
+             [other](Other.md) [bare](../Projects/Road%20map)\n"
+        );
+
+        let (unlinked, n) =
+            rewrite_markdown_links(content, "Daily/Today.md", "Roadmap.md", &LinkEdit::Unlink);
+        assert_eq!(n, 3);
+        assert!(unlinked.ends_with("[other](Other.md) bare\n"));
+        assert!(unlinked.starts_with("plan, root, ![img](../Roadmap.md)\n"));
+    }
+
+    #[test]
+    fn test_rebase_markdown_links() {
+        let content = "[a](Sibling.md), [self](Note.md#top), [b](/Root.md), [c](<../Up Here.md>)";
+        let (rebased, n) = rebase_markdown_links(content, "A/Note.md", "A/B/Note.md");
+        assert_eq!(n, 2);
+        assert_eq!(
+            rebased,
+            "[a](../Sibling.md), [self](Note.md#top), [b](/Root.md), [c](<../../Up Here.md>)"
+        );
+
+        // Renaming in place only changes links to itself
+        assert_eq!(rebase_markdown_links(content, "A/Note.md", "A/Renamed.md").1, 1);
+        assert_eq!(relative_link("Note.md", "A/B.md"), "A/B.md");
+        assert_eq!(relative_link("A/B/Note.md", "A/C.md"), "../C.md");
+    }
+}
@@ -0,0 +1,235 @@
+//! Canvas files (`.canvas`, the JSON Canvas format Obsidian uses)
+//!
+//! A canvas is a board of cards: text in Markdown, embedded vault files,
+//! web links and groups, joined by edges. It is indexed like a note so
+//! the files and `[[links]]` on it show up in the graph and backlinks.
+//! Fields Chronicle doesn't know about are kept on save.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::parser::{extract_links, LinkKind};
+
+pub const CANVAS_EXTENSION: &str = "canvas";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Canvas {
+    #[serde(default)]
+    pub nodes: Vec<CanvasNode>,
+    #[serde(default)]
+    pub edges: Vec<CanvasEdge>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CanvasNode {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: CanvasNodeKind,
+    pub x: Number,
+    pub y: Number,
+    pub width: Number,
+    pub height: Number,
+    /// Markdown of a text node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Vault-relative path of a file node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Heading or block in the file (`#Heading`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subpath: Option<String>,
+    /// URL of a link node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Title of a group node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CanvasNodeKind {
+    Text,
+    File,
+    Link,
+    Group,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanvasEdge {
+    pub id: String,
+    pub from_node: String,
+    pub to_node: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Whether the file at `path` is a canvas
+pub fn is_canvas_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case(CANVAS_EXTENSION))
+}
+
+impl Canvas {
+    /// Parse and validate a canvas file's content
+    pub fn parse(content: &str) -> Result<Self, String> {
+        // Obsidian writes an empty file for a new canvas
+        if content.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        let canvas: Canvas = serde_json::from_str(content).map_err(|e| e.to_string())?;
+        canvas.validate()?;
+        Ok(canvas)
+    }
+
+    /// Node IDs must be unique, edges must join existing nodes, and each
+    /// node needs the field its type is about
+    pub fn validate(&self) -> Result<(), String> {
+        let mut ids = HashSet::new();
+        for node in &self.nodes {
+            if !ids.insert(node.id.as_str()) {
+                return Err(format!("Duplicate node id: {}", node.id));
+            }
+            let missing = match node.kind {
+                CanvasNodeKind::Text => node.text.is_none().then_some("text"),
+                CanvasNodeKind::File => node.file.as_deref().is_none_or(str::is_empty).then_some("file"),
+                CanvasNodeKind::Link => node.url.as_deref().is_none_or(str::is_empty).then_some("url"),
+                CanvasNodeKind::Group => None,
+            };
+            if let Some(field) = missing {
+                return Err(format!("Node {} is missing its {}", node.id, field));
+            }
+        }
+
+        let mut edge_ids = HashSet::new();
+        for edge in &self.edges {
+            if !edge_ids.insert(edge.id.as_str()) {
+                return Err(format!("Duplicate edge id: {}", edge.id));
+            }
+            for end in [&edge.from_node, &edge.to_node] {
+                if !ids.contains(end.as_str()) {
+                    return Err(format!("Edge {} points to unknown node {}", edge.id, end));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Link targets on the canvas: file nodes, which name vault paths like
+    /// wiki links do, and links in text nodes, each once, as (target,
+    /// display text, kind)
+    pub fn links(&self) -> Vec<(String, Option<String>, LinkKind)> {
+        let mut seen = HashSet::new();
+        let mut links = Vec::new();
+        for node in &self.nodes {
+            let targets: Vec<(String, Option<String>, LinkKind)> = match node.kind {
+                CanvasNodeKind::File => {
+                    node.file.iter().map(|f| (f.clone(), None, LinkKind::Wiki)).collect()
+                }
+                CanvasNodeKind::Text => extract_links(node.text.as_deref().unwrap_or_default())
+                    .into_iter()
+                    .map(|l| (l.target, l.display, l.kind))
+                    .collect(),
+                _ => Vec::new(),
+            };
+            for link in targets {
+                if seen.insert(link.0.to_lowercase()) {
+                    links.push(link);
+                }
+            }
+        }
+        links
+    }
+
+    /// Searchable text: text cards, group labels, link URLs and edge labels
+    pub fn text(&self) -> String {
+        let nodes = self.nodes.iter().filter_map(|n| match n.kind {
+            CanvasNodeKind::Text => n.text.as_deref(),
+            CanvasNodeKind::Group => n.label.as_deref(),
+            CanvasNodeKind::Link => n.url.as_deref(),
+            CanvasNodeKind::File => None,
+        });
+        let edges = self.edges.iter().filter_map(|e| e.label.as_deref());
+        nodes.chain(edges).collect::<Vec<_>>().join("\n\n")
+    }
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            extra: Map::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANVAS: &str = r##"{
+        "nodes": [
+            {"id": "a", "type": "text", "text": "See [[Ideas]] and [[ideas|again]]", "x": 0, "y": 0, "width": 250, "height": 60},
+            {"id": "b", "type": "file", "file": "projects/alpha.md", "subpath": "#Goals", "x": 300, "y": 0, "width": 400, "height": 400, "color": "4"},
+            {"id": "c", "type": "link", "url": "https://example.com", "x": 0, "y": 100, "width": 250, "height": 60},
+            {"id": "g", "type": "group", "label": "Research", "x": -20, "y": -20, "width": 800, "height": 500}
+        ],
+        "edges": [
+            {"id": "e1", "fromNode": "a", "fromSide": "right", "toNode": "b", "toSide": "left", "label": "expands"}
+        ]
+    }"##;
+
+    #[test]
+    fn test_parse_canvas() {
+        let canvas = Canvas::parse(CANVAS).unwrap();
+
+        assert_eq!(
+            canvas.links(),
+            vec![
+                ("Ideas".to_string(), None, LinkKind::Wiki),
+                ("projects/alpha.md".to_string(), None, LinkKind::Wiki),
+            ]
+        );
+        let text = canvas.text();
+        assert!(text.contains("See [[Ideas]]"));
+        assert!(text.contains("Research"));
+        assert!(text.contains("expands"));
+
+        // Unknown fields survive a round trip
+        let saved: Value = serde_json::to_value(&canvas).unwrap();
+        assert_eq!(saved["nodes"][1]["color"], "4");
+        assert_eq!(saved["edges"][0]["fromSide"], "right");
+        assert_eq!(saved["nodes"][0]["x"], 0);
+
+        assert_eq!(Canvas::parse("").unwrap(), Canvas::default());
+    }
+
+    #[test]
+    fn test_invalid_canvas() {
+        let node = |id: &str, kind: &str| format!(r#"{{"id": "{}", "type": "{}", "x": 0, "y": 0, "width": 1, "height": 1}}"#, id, kind);
+
+        let missing_file = format!(r#"{{"nodes": [{}]}}"#, node("a", "file"));
+        assert!(Canvas::parse(&missing_file).unwrap_err().contains("file"));
+
+        let duplicate = format!(r#"{{"nodes": [{}, {}]}}"#, node("a", "group"), node("a", "group"));
+        assert!(Canvas::parse(&duplicate).is_err());
+
+        let dangling = format!(
+            r#"{{"nodes": [{}], "edges": [{{"id": "e", "fromNode": "a", "toNode": "b"}}]}}"#,
+            node("a", "group")
+        );
+        assert!(Canvas::parse(&dangling).unwrap_err().contains("unknown node b"));
+
+        assert!(Canvas::parse(r#"{"nodes": [{"id": "a", "type": "video"}]}"#).is_err());
+    }
+}
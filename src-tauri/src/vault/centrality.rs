@@ -0,0 +1,136 @@
+//! How central each note is to the link graph, so the graph view can size
+//! nodes by importance rather than length
+
+use std::collections::HashMap;
+
+use crate::models::GraphData;
+
+/// Share of rank passed along links; the rest is spread evenly, as if the
+/// reader sometimes jumps to a random note
+const DAMPING: f64 = 0.85;
+const MAX_ITERATIONS: usize = 100;
+/// Stop once no node's rank moves by more than this
+const TOLERANCE: f64 = 1e-9;
+
+/// Fill in each node's degree and PageRank. Edges whose ends aren't nodes
+/// are skipped; a target may leave out `.md`, as indexed wiki links do.
+/// Edges carrying a recency weight pass rank in proportion to it.
+pub fn add_centrality(graph: &mut GraphData) {
+    let n = graph.nodes.len();
+    if n == 0 {
+        return;
+    }
+    let index: HashMap<&str, usize> =
+        graph.nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), i)).collect();
+    let find = |id: &str| {
+        index.get(id).or_else(|| index.get(format!("{}.md", id).as_str())).copied()
+    };
+
+    let mut degree = vec![0; n];
+    let mut links = Vec::new();
+    for edge in &graph.edges {
+        let (Some(source), Some(target)) = (find(&edge.source), find(&edge.target)) else {
+            continue;
+        };
+        degree[source] += 1;
+        degree[target] += 1;
+        if source != target {
+            links.push((source, target, edge.weight.unwrap_or(1.0)));
+        }
+    }
+    let rank = pagerank(n, &links);
+
+    for (i, node) in graph.nodes.iter_mut().enumerate() {
+        node.degree = degree[i];
+        node.pagerank = rank[i];
+    }
+}
+
+/// PageRank over weighted links `(source, target, weight)`. Ranks sum to 1;
+/// a node with no outgoing weight shares its rank with every node.
+fn pagerank(n: usize, links: &[(usize, usize, f64)]) -> Vec<f64> {
+    let mut out_weight = vec![0.0; n];
+    for &(source, _, weight) in links {
+        out_weight[source] += weight;
+    }
+
+    let mut rank = vec![1.0 / n as f64; n];
+    for _ in 0..MAX_ITERATIONS {
+        let dangling: f64 = (0..n).filter(|&i| out_weight[i] <= 0.0).map(|i| rank[i]).sum();
+        let base = (1.0 - DAMPING) / n as f64 + DAMPING * dangling / n as f64;
+        let mut next = vec![base; n];
+        for &(source, target, weight) in links {
+            if out_weight[source] > 0.0 {
+                next[target] += DAMPING * rank[source] * weight / out_weight[source];
+            }
+        }
+        let change = rank.iter().zip(&next).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+        rank = next;
+        if change < TOLERANCE {
+            break;
+        }
+    }
+    rank
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{GraphEdge, GraphNode, GraphNodeKind};
+
+    fn graph(nodes: &[&str], edges: &[(&str, &str)]) -> GraphData {
+        GraphData {
+            nodes: nodes
+                .iter()
+                .map(|id| GraphNode {
+                    id: id.to_string(),
+                    kind: GraphNodeKind::Note,
+                    uid: None,
+                    title: id.to_string(),
+                    word_count: 0,
+                    degree: 0,
+                    pagerank: 0.0,
+                })
+                .collect(),
+            edges: edges
+                .iter()
+                .map(|(source, target)| GraphEdge {
+                    source: source.to_string(),
+                    target: target.to_string(),
+                    weight: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_add_centrality() {
+        // Three notes link to a hub, which links back to one of them
+        let mut g = graph(
+            &["hub.md", "a.md", "b.md", "c.md"],
+            &[
+                ("a.md", "hub"),
+                ("b.md", "hub.md"),
+                ("c.md", "hub.md"),
+                ("hub.md", "a.md"),
+                ("a.md", "gone.md"),
+            ],
+        );
+        add_centrality(&mut g);
+
+        let degrees: Vec<usize> = g.nodes.iter().map(|n| n.degree).collect();
+        assert_eq!(degrees, vec![4, 2, 1, 1]);
+        let total: f64 = g.nodes.iter().map(|n| n.pagerank).sum();
+        assert!((total - 1.0).abs() < 1e-6);
+        let rank = |i: usize| g.nodes[i].pagerank;
+        assert!(rank(0) > rank(1) && rank(1) > rank(2));
+        assert!((rank(2) - rank(3)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pagerank_without_links() {
+        let mut g = graph(&["a.md", "b.md"], &[]);
+        add_centrality(&mut g);
+        assert!(g.nodes.iter().all(|n| n.degree == 0 && (n.pagerank - 0.5).abs() < 1e-9));
+    }
+}
@@ -0,0 +1,181 @@
+//! Vaults in cloud-synced folders (iCloud Drive, OneDrive)
+//!
+//! These providers leave placeholders for files they haven't downloaded:
+//! iCloud Drive swaps `note.md` for a hidden `.note.md.icloud` stub, or on
+//! newer macOS keeps a "dataless" file whose contents arrive on first read;
+//! OneDrive marks files it recalls on access. Writing over a placeholder
+//! makes the provider keep both versions as a conflict, so notes are
+//! downloaded before they're read or saved.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long reads and saves wait for a placeholder to download
+pub const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The hidden stub iCloud Drive keeps in place of an undownloaded file
+pub fn icloud_stub(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    Some(path.with_file_name(format!(".{}.icloud", name)))
+}
+
+/// The file an iCloud stub stands for
+pub fn stub_target(stub: &Path) -> Option<PathBuf> {
+    let name = stub.file_name()?.to_str()?;
+    let name = name.strip_prefix('.')?.strip_suffix(".icloud")?;
+    (!name.is_empty()).then(|| stub.with_file_name(name))
+}
+
+/// Whether `path` is only a placeholder for a file that isn't downloaded
+pub fn is_placeholder(path: &Path) -> bool {
+    match fs::metadata(path) {
+        Ok(meta) => is_dataless(&meta),
+        Err(_) => icloud_stub(path).is_some_and(|stub| stub.exists()),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn is_dataless(meta: &fs::Metadata) -> bool {
+    use std::os::macos::fs::MetadataExt;
+    // `SF_DATALESS` from <sys/stat.h>
+    const SF_DATALESS: u32 = 0x4000_0000;
+    meta.st_flags() & SF_DATALESS != 0
+}
+
+#[cfg(windows)]
+fn is_dataless(meta: &fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_OFFLINE: u32 = 0x1000;
+    const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x4_0000;
+    const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x40_0000;
+    let recalled = FILE_ATTRIBUTE_OFFLINE
+        | FILE_ATTRIBUTE_RECALL_ON_OPEN
+        | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS;
+    meta.file_attributes() & recalled != 0
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn is_dataless(_meta: &fs::Metadata) -> bool {
+    false
+}
+
+/// Ask iCloud Drive to download `path`, without waiting for it
+#[cfg(target_os = "macos")]
+pub fn request_download(path: &Path) -> io::Result<()> {
+    // `brctl` ships with macOS and handles both stubs and dataless files
+    let status = std::process::Command::new("brctl")
+        .arg("download")
+        .arg(path)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("brctl download failed for {:?}", path)))
+    }
+}
+
+/// Ask the provider to download `path`, without waiting for it. Other
+/// providers download a file when it is read, so this reads it in the
+/// background.
+#[cfg(not(target_os = "macos"))]
+pub fn request_download(path: &Path) -> io::Result<()> {
+    use std::io::Read;
+
+    let path = path.to_path_buf();
+    thread::spawn(move || {
+        if let Err(e) = fs::File::open(&path).and_then(|mut f| f.read(&mut [0u8; 1])) {
+            tracing::warn!(path = ?path, error = %e, "Failed to download file");
+        }
+    });
+    Ok(())
+}
+
+/// Make sure `path` is downloaded, requesting it and waiting up to
+/// `timeout`. Fails with `TimedOut` if it still isn't there.
+pub fn wait_until_downloaded(path: &Path, timeout: Duration) -> io::Result<()> {
+    if !is_placeholder(path) {
+        return Ok(());
+    }
+
+    request_download(path)?;
+    let deadline = Instant::now() + timeout;
+    while is_placeholder(path) {
+        if Instant::now() >= deadline {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("{:?} is still downloading", path),
+            ));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+    Ok(())
+}
+
+/// Files in the vault that are only placeholders, outside hidden folders
+pub fn placeholder_files(vault_path: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_placeholders(vault_path, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn collect_placeholders(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            if !hidden {
+                collect_placeholders(&path, files)?;
+            }
+        } else if hidden {
+            files.extend(stub_target(&path));
+        } else if entry.metadata().is_ok_and(|meta| is_dataless(&meta)) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_icloud_stub_names() {
+        let note = Path::new("/vault/notes/plan.md");
+        let stub = icloud_stub(note).unwrap();
+        assert_eq!(stub, Path::new("/vault/notes/.plan.md.icloud"));
+        assert_eq!(stub_target(&stub).unwrap(), note);
+        assert_eq!(stub_target(Path::new("/vault/.obsidian")), None);
+        assert_eq!(stub_target(Path::new("/vault/..icloud")), None);
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("notes")).unwrap();
+        fs::create_dir_all(root.join(".trash")).unwrap();
+        fs::write(root.join("local.md"), "# Local").unwrap();
+        fs::write(root.join("notes/.remote.md.icloud"), "").unwrap();
+        fs::write(root.join(".trash/.old.md.icloud"), "").unwrap();
+
+        assert!(!is_placeholder(&root.join("local.md")));
+        assert!(is_placeholder(&root.join("notes/remote.md")));
+        assert!(!is_placeholder(&root.join("missing.md")));
+        assert_eq!(placeholder_files(root).unwrap(), vec![root.join("notes/remote.md")]);
+
+        // Nothing to wait for once the file is local
+        wait_until_downloaded(&root.join("local.md"), Duration::ZERO).unwrap();
+    }
+}
@@ -0,0 +1,126 @@
+//! Unsaved editor drafts, kept under `.chronicle/drafts/` so a crash or
+//! power loss only costs the last few seconds of typing
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const DRAFT_EXTENSION: &str = "draft";
+
+/// Editor content that hasn't been saved to the note yet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Draft {
+    /// Vault-relative path of the note
+    pub path: String,
+    pub content: String,
+    /// RFC 3339 timestamp of the last flush
+    pub saved_at: String,
+}
+
+/// Folder holding the drafts for a vault
+pub fn drafts_dir(vault_path: &Path) -> PathBuf {
+    vault_path.join(".chronicle").join("drafts")
+}
+
+/// Draft file for a note. The note path is escaped into a single flat
+/// filename; the real path is stored inside the draft.
+fn draft_file(vault_path: &Path, note_path: &str) -> PathBuf {
+    let escaped = note_path
+        .replace('%', "%25")
+        .replace('/', "%2F")
+        .replace('\\', "%5C");
+    drafts_dir(vault_path).join(format!("{}.{}", escaped, DRAFT_EXTENSION))
+}
+
+/// Write a draft, replacing any earlier one for the same note
+pub fn write_draft(vault_path: &Path, draft: &Draft) -> io::Result<()> {
+    let file = draft_file(vault_path, &draft.path);
+    fs::create_dir_all(drafts_dir(vault_path))?;
+
+    // Write then rename so a crash mid-flush never leaves a torn draft
+    let tmp = file.with_extension("tmp");
+    fs::write(&tmp, serde_json::to_vec(draft)?)?;
+    fs::rename(&tmp, &file)
+}
+
+/// Draft for a note, if one exists
+pub fn read_draft(vault_path: &Path, note_path: &str) -> io::Result<Option<Draft>> {
+    let file = draft_file(vault_path, note_path);
+    if !file.exists() {
+        return Ok(None);
+    }
+    let content = fs::read(&file)?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+/// Remove the draft for a note. Missing drafts are not an error.
+pub fn remove_draft(vault_path: &Path, note_path: &str) -> io::Result<()> {
+    match fs::remove_file(draft_file(vault_path, note_path)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// All drafts in the vault, e.g. to offer recovery after a crash.
+/// Unreadable draft files are skipped.
+pub fn all_drafts(vault_path: &Path) -> io::Result<Vec<Draft>> {
+    let dir = drafts_dir(vault_path);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut drafts: Vec<Draft> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == DRAFT_EXTENSION))
+        .filter_map(|path| fs::read(path).ok())
+        .filter_map(|content| serde_json::from_slice(&content).ok())
+        .collect();
+    drafts.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(drafts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn draft(path: &str, content: &str) -> Draft {
+        Draft {
+            path: path.to_string(),
+            content: content.to_string(),
+            saved_at: "2024-01-15T10:30:00+00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_write_read_remove() {
+        let temp = TempDir::new().unwrap();
+        let d = draft("folder/note.md", "unsaved text");
+
+        write_draft(temp.path(), &d).unwrap();
+        assert_eq!(read_draft(temp.path(), "folder/note.md").unwrap(), Some(d));
+        assert_eq!(read_draft(temp.path(), "note.md").unwrap(), None);
+
+        remove_draft(temp.path(), "folder/note.md").unwrap();
+        assert_eq!(read_draft(temp.path(), "folder/note.md").unwrap(), None);
+        // Discarding twice is fine
+        remove_draft(temp.path(), "folder/note.md").unwrap();
+    }
+
+    #[test]
+    fn test_all_drafts_keeps_paths_distinct() {
+        let temp = TempDir::new().unwrap();
+        write_draft(temp.path(), &draft("a/b.md", "one")).unwrap();
+        write_draft(temp.path(), &draft("a%2Fb.md", "two")).unwrap();
+        write_draft(temp.path(), &draft("a/b.md", "three")).unwrap();
+
+        let drafts = all_drafts(temp.path()).unwrap();
+        assert_eq!(drafts.len(), 2);
+        assert_eq!(drafts[0].path, "a%2Fb.md");
+        assert_eq!(drafts[0].content, "two");
+        assert_eq!(drafts[1].path, "a/b.md");
+        assert_eq!(drafts[1].content, "three");
+    }
+}
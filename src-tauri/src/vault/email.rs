@@ -0,0 +1,171 @@
+//! Saved emails (`.eml` files) turned into notes
+
+use mail_parser::{Address, MessageParser, MimeHeaders, PartType};
+use serde_json::Value;
+
+use crate::vault::{set_frontmatter_property, set_frontmatter_value};
+
+/// An email read from a saved message
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Email {
+    pub subject: Option<String>,
+    /// Sender as `Name <address>`
+    pub from: Option<String>,
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    /// Sent date, RFC 3339
+    pub date: Option<String>,
+    pub message_id: Option<String>,
+    /// The plain text body, or the HTML body converted to Markdown
+    pub body: String,
+    /// Attached files as (file name, content)
+    pub attachments: Vec<(String, Vec<u8>)>,
+}
+
+impl Email {
+    /// The subject, or a placeholder for emails without one
+    pub fn title(&self) -> String {
+        self.subject
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .unwrap_or("Untitled email")
+            .to_string()
+    }
+}
+
+/// Parse a saved email. `None` means the data isn't an email.
+pub fn parse_email(raw: &[u8]) -> Option<Email> {
+    let message = MessageParser::default().parse(raw)?;
+    // Anything without the usual headers is a plain file, not a message
+    if message.from().is_none() && message.subject().is_none() && message.date().is_none() {
+        return None;
+    }
+
+    let body = match message.text_part(0).map(|part| &part.body) {
+        Some(PartType::Text(text)) => text.to_string(),
+        _ => message
+            .body_html(0)
+            .map(|html| html2md::parse_html(&html))
+            .unwrap_or_default(),
+    };
+    let attachments = message
+        .attachments()
+        .map(|part| {
+            let default_name = if part.is_message() { "message.eml" } else { "attachment" };
+            let name = part.attachment_name().unwrap_or(default_name);
+            (name.to_string(), part.contents().to_vec())
+        })
+        .collect();
+
+    Some(Email {
+        subject: message.subject().map(String::from),
+        from: message.from().and_then(|a| addresses(a).into_iter().next()),
+        to: message.to().map(addresses).unwrap_or_default(),
+        cc: message.cc().map(addresses).unwrap_or_default(),
+        date: message.date().map(|d| d.to_rfc3339()),
+        message_id: message.message_id().map(String::from),
+        body: body.trim().to_string(),
+        attachments,
+    })
+}
+
+fn addresses(address: &Address) -> Vec<String> {
+    address
+        .iter()
+        .filter_map(|addr| match (addr.name.as_deref(), addr.address.as_deref()) {
+            (Some(name), Some(email)) => Some(format!("{} <{}>", name, email)),
+            (None, Some(email)) => Some(email.to_string()),
+            (Some(name), None) => Some(name.to_string()),
+            (None, None) => None,
+        })
+        .collect()
+}
+
+/// Render an email as a note: headers in the frontmatter, then the body and
+/// a list of the attachments, given as embeds
+pub fn email_note(email: &Email, attachment_embeds: &[String]) -> String {
+    let mut content = format!("# {}\n", email.title());
+    if !email.body.is_empty() {
+        content.push('\n');
+        content.push_str(&email.body);
+        content.push('\n');
+    }
+    if !attachment_embeds.is_empty() {
+        content.push_str("\n## Attachments\n\n");
+        for embed in attachment_embeds {
+            content.push_str(&format!("- {}\n", embed));
+        }
+    }
+
+    let scalars = [
+        ("from", &email.from),
+        ("date", &email.date),
+        ("message_id", &email.message_id),
+    ];
+    for (key, value) in scalars {
+        if let Some(value) = value {
+            content = set_frontmatter_property(&content, key, value);
+        }
+    }
+    for (key, list) in [("to", &email.to), ("cc", &email.cc)] {
+        if !list.is_empty() {
+            content = set_frontmatter_value(&content, key, &Value::from(list.clone()));
+        }
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::get_frontmatter_property;
+
+    const EML: &str = "From: Ada Lovelace <ada@example.com>\r\n\
+To: Charles <charles@example.com>, team@example.com\r\n\
+Subject: Engine notes\r\n\
+Date: Mon, 9 Mar 2026 14:30:05 +0000\r\n\
+Message-ID: <123@example.com>\r\n\
+MIME-Version: 1.0\r\n\
+Content-Type: multipart/mixed; boundary=\"b\"\r\n\
+\r\n\
+--b\r\n\
+Content-Type: text/html; charset=utf-8\r\n\
+\r\n\
+<p>The <b>mill</b> is done.</p>\r\n\
+--b\r\n\
+Content-Type: application/pdf\r\n\
+Content-Disposition: attachment; filename=\"plan.pdf\"\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+JVBERg==\r\n\
+--b--\r\n";
+
+    #[test]
+    fn test_parse_email() {
+        let email = parse_email(EML.as_bytes()).unwrap();
+        assert_eq!(email.title(), "Engine notes");
+        assert_eq!(email.from.as_deref(), Some("Ada Lovelace <ada@example.com>"));
+        assert_eq!(email.to, vec!["Charles <charles@example.com>", "team@example.com"]);
+        assert_eq!(email.date.as_deref(), Some("2026-03-09T14:30:05Z"));
+        assert_eq!(email.message_id.as_deref(), Some("123@example.com"));
+        assert_eq!(email.body, "The **mill** is done.");
+        assert_eq!(email.attachments, vec![("plan.pdf".to_string(), b"%PDF".to_vec())]);
+
+        assert!(parse_email(b"just some text").is_none());
+    }
+
+    #[test]
+    fn test_email_note() {
+        let email = parse_email(EML.as_bytes()).unwrap();
+        let content = email_note(&email, &["![[attachments/plan.pdf]]".to_string()]);
+
+        assert_eq!(
+            get_frontmatter_property(&content, "from").as_deref(),
+            Some("Ada Lovelace <ada@example.com>")
+        );
+        assert!(content.contains("---\n# Engine notes\n\nThe **mill** is done.\n"));
+        assert!(content.ends_with("## Attachments\n\n- ![[attachments/plan.pdf]]\n"));
+        assert!(content.contains("to: ["));
+    }
+}
@@ -0,0 +1,330 @@
+//! Transclusion: resolving `![[embed]]`s to the content they show, so the
+//! preview can render them in one round trip
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+use super::parser::parse_frontmatter;
+
+static EMBED_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"!\[\[([^\]|]+)(?:\|([^\]]+))?\]\]").expect("Invalid embed regex")
+});
+
+static HEADING_LINE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(#{1,6})\s+(.*?)\s*#*\s*$").expect("Invalid heading line regex")
+});
+
+/// What an embed's link name points at
+pub enum EmbedSource {
+    Note { path: String, content: String },
+    /// An image or other non-note file, which the preview shows itself
+    Attachment { path: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbedStatus {
+    Resolved,
+    Attachment,
+    /// No note or file by that name
+    Missing,
+    /// The note exists but has no such heading or block
+    SectionMissing,
+    /// Embedding it would repeat an embed it sits inside
+    Cycle,
+    /// Nested deeper than the requested depth
+    TooDeep,
+}
+
+/// An embed and, when it resolved to a note, the Markdown it shows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedEmbed {
+    /// Link as written, e.g. `Note#Section` or `Note#^block`
+    pub target: String,
+    pub display: Option<String>,
+    /// Line of the embed (1-indexed) within the content it appears in
+    pub line_number: usize,
+    pub status: EmbedStatus,
+    /// Vault-relative path the link resolved to
+    pub path: Option<String>,
+    /// The whole note without frontmatter, or just the section or block
+    pub content: Option<String>,
+    /// Embeds within `content`
+    pub embeds: Vec<ResolvedEmbed>,
+}
+
+/// Resolve the embeds in `content`, the body of the note at `path`,
+/// following embeds in embedded content up to `depth` levels. `load` finds
+/// what a link name (without `#anchor`) points at.
+pub fn resolve_embeds_in(
+    path: &str,
+    content: &str,
+    depth: usize,
+    load: &dyn Fn(&str) -> Option<EmbedSource>,
+) -> Vec<ResolvedEmbed> {
+    let mut stack = vec![(path.to_string(), None)];
+    resolve_level(content, depth, load, &mut stack)
+}
+
+fn resolve_level(
+    content: &str,
+    depth: usize,
+    load: &dyn Fn(&str) -> Option<EmbedSource>,
+    stack: &mut Vec<(String, Option<String>)>,
+) -> Vec<ResolvedEmbed> {
+    let mut embeds = Vec::new();
+    let mut in_fence = false;
+
+    for (line_num, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        for captures in EMBED_RE.captures_iter(line) {
+            let target = captures[1].trim().to_string();
+            let mut embed = ResolvedEmbed {
+                display: captures.get(2).map(|m| m.as_str().trim().to_string()),
+                line_number: line_num + 1,
+                status: EmbedStatus::Missing,
+                path: None,
+                content: None,
+                embeds: Vec::new(),
+                target,
+            };
+            resolve_one(&mut embed, depth, load, stack);
+            embeds.push(embed);
+        }
+    }
+
+    embeds
+}
+
+fn resolve_one(
+    embed: &mut ResolvedEmbed,
+    depth: usize,
+    load: &dyn Fn(&str) -> Option<EmbedSource>,
+    stack: &mut Vec<(String, Option<String>)>,
+) {
+    let (name, anchor) = match embed.target.split_once('#') {
+        Some((name, anchor)) => (name.trim(), Some(anchor.trim().to_string())),
+        None => (embed.target.as_str(), None),
+    };
+    // `![[#Section]]` embeds part of the note it's in
+    let source = if name.is_empty() {
+        stack.last().map(|(path, _)| path.clone()).and_then(|p| load(&p))
+    } else {
+        load(name)
+    };
+
+    let (path, note) = match source {
+        None => return,
+        Some(EmbedSource::Attachment { path }) => {
+            embed.status = EmbedStatus::Attachment;
+            embed.path = Some(path);
+            return;
+        }
+        Some(EmbedSource::Note { path, content }) => (path, content),
+    };
+    embed.path = Some(path.clone());
+
+    if depth == 0 {
+        embed.status = EmbedStatus::TooDeep;
+        return;
+    }
+    let repeats = stack
+        .iter()
+        .any(|(p, a)| p.eq_ignore_ascii_case(&path) && *a == anchor);
+    if repeats {
+        embed.status = EmbedStatus::Cycle;
+        return;
+    }
+
+    let (_, body_start) = parse_frontmatter(&note);
+    let body = &note[body_start..];
+    let part = match anchor.as_deref() {
+        None => Some(body.to_string()),
+        Some(anchor) => match anchor.strip_prefix('^') {
+            Some(block_id) => extract_block(body, block_id),
+            // `Heading#Subheading` embeds the innermost heading
+            None => extract_section(body, anchor.rsplit('#').next().unwrap_or(anchor)),
+        },
+    };
+    let Some(part) = part else {
+        embed.status = EmbedStatus::SectionMissing;
+        return;
+    };
+
+    stack.push((path, anchor));
+    embed.embeds = resolve_level(&part, depth - 1, load, stack);
+    stack.pop();
+    embed.status = EmbedStatus::Resolved;
+    embed.content = Some(part);
+}
+
+/// A heading and everything under it, up to the next heading of the same
+/// or a higher level. Headings match ignoring case.
+pub fn extract_section(content: &str, heading: &str) -> Option<String> {
+    let heading = heading.trim();
+    let lines: Vec<&str> = content.lines().collect();
+    let mut in_fence = false;
+    let mut start: Option<(usize, usize)> = None;
+
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let Some(captures) = HEADING_LINE_RE.captures(line) else {
+            continue;
+        };
+        let level = captures[1].len();
+        match start {
+            None if captures[2].to_lowercase() == heading.to_lowercase() => {
+                start = Some((i, level));
+            }
+            Some((from, start_level)) if level <= start_level => {
+                return Some(lines[from..i].join("\n").trim_end().to_string());
+            }
+            _ => {}
+        }
+    }
+
+    start.map(|(from, _)| lines[from..].join("\n").trim_end().to_string())
+}
+
+/// The paragraph or list item marked with `^block_id`, without the marker.
+/// A marker on a line of its own tags the block above it, as used for
+/// quotes and tables.
+pub fn extract_block(content: &str, block_id: &str) -> Option<String> {
+    let marker = format!("^{}", block_id);
+    let lines: Vec<&str> = content.lines().collect();
+    let index = lines.iter().position(|line| {
+        let line = line.trim_end();
+        line == marker || line.ends_with(&format!(" {}", marker))
+    })?;
+
+    let strip = |line: &str| {
+        let line = line.trim_end();
+        line.strip_suffix(&marker).unwrap_or(line).trim_end().to_string()
+    };
+    let is_list_item = |line: &str| {
+        let line = line.trim_start();
+        line.starts_with(['-', '*', '+'])
+            || line.split_once(". ").is_some_and(|(n, _)| n.parse::<u32>().is_ok())
+    };
+
+    let end = if lines[index].trim_end() == marker {
+        (0..index).rev().find(|&i| !lines[i].trim().is_empty())?
+    } else if is_list_item(lines[index]) {
+        return Some(strip(lines[index]));
+    } else {
+        index
+    };
+    let mut start = end;
+    while start > 0 && !lines[start - 1].trim().is_empty() {
+        start -= 1;
+    }
+
+    let block: Vec<String> = lines[start..=end].iter().map(|line| strip(line)).collect();
+    let block = block.join("\n");
+    (!block.trim().is_empty()).then_some(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn vault() -> HashMap<&'static str, &'static str> {
+        HashMap::from([
+            (
+                "recipes.md",
+                "---\ntags: [food]\n---\n# Recipes\n\n## Bread\nFlour and water.\n### Notes\nKnead well.\n## Soup\nSee ![[stock]]\n",
+            ),
+            ("stock.md", "Simmer bones.\n\nSalt to taste. ^salt\n\n![[recipes#Soup]]\n"),
+            ("loop.md", "![[loop]]\n"),
+        ])
+    }
+
+    fn load(name: &str) -> Option<EmbedSource> {
+        let path = if name.contains('.') { name.to_string() } else { format!("{}.md", name) };
+        match vault().get(path.as_str()) {
+            Some(content) => Some(EmbedSource::Note {
+                path,
+                content: content.to_string(),
+            }),
+            None if path.ends_with(".png") => Some(EmbedSource::Attachment { path }),
+            None => None,
+        }
+    }
+
+    fn resolve(path: &str, depth: usize) -> Vec<ResolvedEmbed> {
+        resolve_embeds_in(path, vault().get(path).copied().unwrap_or_default(), depth, &load)
+    }
+
+    #[test]
+    fn test_extract_section() {
+        let body = "# Recipes\n## Bread\nFlour.\n### Notes\nKnead.\n## Soup\nStock.";
+        assert_eq!(extract_section(body, "bread").unwrap(), "## Bread\nFlour.\n### Notes\nKnead.");
+        assert_eq!(extract_section(body, "Soup").unwrap(), "## Soup\nStock.");
+        assert!(extract_section(body, "Cake").is_none());
+    }
+
+    #[test]
+    fn test_extract_block() {
+        let body = "First line\nsecond line ^para\n\n- item one ^item\n- item two\n\n> quote\n\n^quote";
+        assert_eq!(extract_block(body, "para").unwrap(), "First line\nsecond line");
+        assert_eq!(extract_block(body, "item").unwrap(), "- item one");
+        assert_eq!(extract_block(body, "quote").unwrap(), "> quote");
+        assert!(extract_block(body, "nope").is_none());
+    }
+
+    #[test]
+    fn test_resolve_embeds() {
+        let content = "![[recipes#Bread]]\n![[stock#^salt|Salt]]\n![[photo.png]]\n![[missing]]\n![[recipes#Cake]]";
+        let embeds = resolve_embeds_in("today.md", content, 2, &load);
+
+        let statuses: Vec<EmbedStatus> = embeds.iter().map(|e| e.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                EmbedStatus::Resolved,
+                EmbedStatus::Resolved,
+                EmbedStatus::Attachment,
+                EmbedStatus::Missing,
+                EmbedStatus::SectionMissing,
+            ]
+        );
+        assert!(embeds[0].content.as_deref().unwrap().ends_with("Knead well."));
+        assert_eq!(embeds[1].content.as_deref(), Some("Salt to taste."));
+        assert_eq!(embeds[1].display.as_deref(), Some("Salt"));
+        assert_eq!(embeds[2].path.as_deref(), Some("photo.png"));
+    }
+
+    #[test]
+    fn test_resolve_embeds_cycles_and_depth() {
+        // recipes#Soup embeds stock, which embeds recipes#Soup again
+        let embeds = resolve("recipes.md", 5);
+        assert_eq!(embeds[0].status, EmbedStatus::Resolved);
+        let soup = &embeds[0].embeds[0];
+        assert_eq!(soup.target, "recipes#Soup");
+        assert_eq!(soup.status, EmbedStatus::Resolved);
+        assert_eq!(soup.embeds[0].status, EmbedStatus::Cycle);
+
+        assert_eq!(resolve("loop.md", 5)[0].status, EmbedStatus::Cycle);
+
+        let shallow = resolve("recipes.md", 1);
+        assert_eq!(shallow[0].status, EmbedStatus::Resolved);
+        assert_eq!(shallow[0].embeds[0].status, EmbedStatus::TooDeep);
+    }
+}
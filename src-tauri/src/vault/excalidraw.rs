@@ -0,0 +1,219 @@
+//! Excalidraw drawings: plain `.excalidraw` scenes and the Markdown files
+//! (`.excalidraw.md`) the Obsidian Excalidraw plugin writes
+//!
+//! A drawing's text elements are indexed for search, and the notes it
+//! links to count as links, so sketches take part in backlinks.
+
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use super::parser::{extract_links, parse_frontmatter, ExtractedLink, LinkKind};
+
+/// Start of the scene data in a Markdown drawing
+static DRAWING_HEADING_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^#{1,2} Drawing\s*$").expect("Invalid drawing heading regex"));
+
+/// Uncompressed scene JSON; the plugin's default `compressed-json` can't
+/// be read without its decompressor and is skipped
+static SCENE_JSON_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)```json\r?\n(.*?)\r?\n```").expect("Invalid scene regex"));
+
+/// Block reference the plugin appends to each text element (` ^a1b2c3`)
+static BLOCK_ID_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s\^[\w-]+$").expect("Invalid block id regex"));
+
+/// What a drawing contributes to the index
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Drawing {
+    /// Text elements, for search
+    pub text: String,
+    /// Linked notes, each once
+    pub links: Vec<DrawingLink>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawingLink {
+    pub target: String,
+    pub display: Option<String>,
+    /// 1-indexed line, for links in the Markdown part of a `.excalidraw.md`
+    pub line_number: Option<usize>,
+    pub kind: LinkKind,
+}
+
+/// Whether the file at `path` is an Excalidraw drawing
+pub fn is_excalidraw_file(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    name.ends_with(".excalidraw") || name.ends_with(".excalidraw.md")
+}
+
+/// A drawing's filename without the `.excalidraw(.md)` suffix
+pub fn drawing_title(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let lower = name.to_lowercase();
+    [".excalidraw.md", ".excalidraw"]
+        .iter()
+        .find(|suffix| lower.ends_with(*suffix))
+        .map(|suffix| name[..name.len() - suffix.len()].to_string())
+        .unwrap_or(name)
+}
+
+/// Read a drawing; `markdown` for the `.excalidraw.md` flavor
+pub fn parse_drawing(content: &str, markdown: bool) -> Result<Drawing, String> {
+    if markdown {
+        return Ok(parse_markdown_drawing(content));
+    }
+    if content.trim().is_empty() {
+        return Ok(Drawing::default());
+    }
+    let scene: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    if !scene["elements"].is_array() {
+        return Err("Not an Excalidraw scene: no elements".to_string());
+    }
+    let mut drawing = Drawing::default();
+    add_scene(&mut drawing, &scene, true);
+    Ok(drawing)
+}
+
+/// The plugin keeps text elements as Markdown lines above the scene, so
+/// links there have line numbers; element links only live in the scene
+fn parse_markdown_drawing(content: &str) -> Drawing {
+    let (_, body_start) = parse_frontmatter(content);
+    let first_line = content[..body_start].matches('\n').count();
+    let body = &content[body_start..];
+    let (notes, scene) = match DRAWING_HEADING_RE.find(body) {
+        Some(m) => body.split_at(m.start()),
+        None => (body, ""),
+    };
+
+    let text: Vec<String> = notes
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && *line != "%%" && !line.starts_with("==⚠"))
+        .map(|line| BLOCK_ID_RE.replace(line, "").to_string())
+        .collect();
+    let mut drawing = Drawing {
+        text: text.join("\n"),
+        links: Vec::new(),
+    };
+    for link in extract_links(notes) {
+        let line_number = Some(first_line + link.line_number);
+        push_link(&mut drawing, link, line_number);
+    }
+
+    let scene = SCENE_JSON_RE
+        .captures(scene)
+        .and_then(|caps| serde_json::from_str::<Value>(&caps[1]).ok());
+    if let Some(scene) = scene {
+        add_scene(&mut drawing, &scene, false);
+    }
+    drawing
+}
+
+/// Links from the scene's elements, and their text unless the Markdown
+/// part already had it
+fn add_scene(drawing: &mut Drawing, scene: &Value, with_text: bool) {
+    let elements = scene["elements"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let mut text = Vec::new();
+
+    for element in elements {
+        if element["isDeleted"].as_bool() == Some(true) {
+            continue;
+        }
+        if element["type"] == "text" {
+            let content = element["originalText"].as_str().or(element["text"].as_str());
+            if let Some(content) = content {
+                text.push(content.to_string());
+                for link in extract_links(content) {
+                    push_link(drawing, link, None);
+                }
+            }
+        }
+        // Elements can link to a note (`[[Note]]`) or a URL
+        if let Some(link) = element["link"].as_str() {
+            for link in extract_links(link) {
+                push_link(drawing, link, None);
+            }
+        }
+    }
+
+    if with_text {
+        drawing.text = text.join("\n");
+    }
+}
+
+fn push_link(drawing: &mut Drawing, link: ExtractedLink, line_number: Option<usize>) {
+    let seen: HashSet<String> = drawing.links.iter().map(|l| l.target.to_lowercase()).collect();
+    if !seen.contains(&link.target.to_lowercase()) {
+        drawing.links.push(DrawingLink {
+            target: link.target,
+            display: link.display,
+            line_number,
+            kind: link.kind,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn targets(drawing: &Drawing) -> Vec<(&str, Option<usize>)> {
+        drawing.links.iter().map(|l| (l.target.as_str(), l.line_number)).collect()
+    }
+
+    #[test]
+    fn test_parse_scene() {
+        let scene = r#"{
+            "type": "excalidraw",
+            "elements": [
+                {"type": "text", "text": "Queue\nworker", "originalText": "Queue worker see [[Architecture]]"},
+                {"type": "rectangle", "link": "[[Deploy Notes]]"},
+                {"type": "text", "text": "erased", "isDeleted": true},
+                {"type": "arrow", "link": "https://example.com"}
+            ],
+            "appState": {}
+        }"#;
+        let drawing = parse_drawing(scene, false).unwrap();
+
+        assert_eq!(drawing.text, "Queue worker see [[Architecture]]");
+        assert_eq!(targets(&drawing), vec![("Architecture", None), ("Deploy Notes", None)]);
+        assert!(parse_drawing(r#"{"type": "other"}"#, false).is_err());
+    }
+
+    #[test]
+    fn test_parse_markdown_drawing() {
+        let content = "---\nexcalidraw-plugin: parsed\n---\n\
+            ==⚠  Switch to EXCALIDRAW VIEW in the MORE OPTIONS menu of this document. ⚠==\n\n\
+            # Excalidraw Data\n## Text Elements\n\
+            Load balancer ^a1b2c3\n\n\
+            Details in [[Infra]] ^d4e5f6\n\n\
+            %%\n## Drawing\n```json\n\
+            {\"elements\": [{\"type\": \"ellipse\", \"link\": \"[[Runbook]]\"}, {\"type\": \"text\", \"text\": \"Load balancer\"}]}\n\
+            ```\n%%\n";
+        let drawing = parse_drawing(content, true).unwrap();
+
+        assert!(drawing.text.contains("Load balancer\n"));
+        assert!(drawing.text.contains("Details in [[Infra]]"));
+        assert!(!drawing.text.contains("^a1b2c3"));
+        assert!(!drawing.text.contains("elements"));
+        assert_eq!(targets(&drawing), vec![("Infra", Some(10)), ("Runbook", None)]);
+    }
+
+    #[test]
+    fn test_drawing_files() {
+        assert!(is_excalidraw_file(Path::new("sketches/System.excalidraw")));
+        assert!(is_excalidraw_file(Path::new("sketches/System.Excalidraw.md")));
+        assert!(!is_excalidraw_file(Path::new("notes/excalidraw.md")));
+        assert_eq!(drawing_title(Path::new("a/System.excalidraw.md")), "System");
+        assert_eq!(drawing_title(Path::new("a/System.excalidraw")), "System");
+    }
+}
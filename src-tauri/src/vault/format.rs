@@ -0,0 +1,185 @@
+//! Markdown normalization for `format_note`
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+use super::parser::parse_frontmatter;
+use crate::models::{FormattingConfig, ListMarker};
+
+static ATX_HEADING_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(#{1,6})[ \t]+(\S.*?)[ \t]*$").expect("Invalid ATX heading regex")
+});
+
+static LIST_ITEM_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\s*)[-*+]([ \t]+\S)").expect("Invalid list item regex"));
+
+static THEMATIC_BREAK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(?:(?:\*\s*){3,}|(?:-\s*){3,}|(?:_\s*){3,})$")
+        .expect("Invalid thematic break regex")
+});
+
+static LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[\[([^\]|#]+)((?:#[^\]|]*)?(?:\|[^\]]*)?)\]\]").expect("Invalid link regex")
+});
+
+/// Normalize a note's Markdown by the configured rules. `link_name` gives
+/// the exactly-cased name for a link target, if it points at a note.
+pub fn format_markdown(
+    content: &str,
+    rules: &FormattingConfig,
+    link_name: &dyn Fn(&str) -> Option<String>,
+) -> String {
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let (_, body_start) = parse_frontmatter(content);
+    let (head, body) = content.split_at(body_start);
+
+    let mut out: Vec<String> = Vec::new();
+    let mut in_fence = false;
+    let mut after_heading = false;
+
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+        if in_fence || is_fence {
+            if is_fence {
+                in_fence = !in_fence;
+            }
+            if after_heading {
+                out.push(String::new());
+                after_heading = false;
+            }
+            out.push(line.to_string());
+            continue;
+        }
+
+        let mut line = if rules.trim_trailing_whitespace {
+            trim_trailing(line)
+        } else {
+            line.to_string()
+        };
+        if rules.link_casing {
+            line = fix_link_casing(&line, link_name);
+        }
+        if let Some(marker) = list_marker_char(rules.list_marker) {
+            if !THEMATIC_BREAK_RE.is_match(&line) {
+                line = LIST_ITEM_RE
+                    .replace(&line, |c: &regex::Captures| format!("{}{}{}", &c[1], marker, &c[2]))
+                    .into_owned();
+            }
+        }
+
+        if !rules.heading_spacing {
+            out.push(line);
+            continue;
+        }
+        if let Some(captures) = ATX_HEADING_RE.captures(&line) {
+            let heading = format!("{} {}", &captures[1], &captures[2]);
+            if out.iter().any(|l| !l.trim().is_empty()) {
+                while out.last().is_some_and(|l| l.trim().is_empty()) {
+                    out.pop();
+                }
+                out.push(String::new());
+            }
+            out.push(heading);
+            after_heading = true;
+        } else if after_heading && line.trim().is_empty() {
+            // Blank lines after a heading collapse into the one added below
+        } else {
+            if after_heading {
+                out.push(String::new());
+                after_heading = false;
+            }
+            out.push(line);
+        }
+    }
+
+    let mut body = out.join(newline);
+    if rules.trim_trailing_whitespace {
+        let trimmed_len = body.trim_end().len();
+        body.truncate(trimmed_len);
+        if !body.is_empty() {
+            body.push_str(newline);
+        }
+    } else if content.ends_with('\n') && !body.is_empty() {
+        body.push_str(newline);
+    }
+    format!("{}{}", head, body)
+}
+
+/// Trim trailing whitespace, but keep a Markdown hard line break (two or
+/// more trailing spaces) as exactly two spaces
+fn trim_trailing(line: &str) -> String {
+    let trimmed = line.trim_end();
+    let trailing = &line[trimmed.len()..];
+    if !trimmed.is_empty() && trailing.len() >= 2 && trailing.chars().all(|c| c == ' ') {
+        format!("{}  ", trimmed)
+    } else {
+        trimmed.to_string()
+    }
+}
+
+fn fix_link_casing(line: &str, link_name: &dyn Fn(&str) -> Option<String>) -> String {
+    LINK_RE
+        .replace_all(line, |c: &regex::Captures| {
+            let name = c[1].trim();
+            let fixed = link_name(name)
+                .filter(|exact| exact != name && exact.eq_ignore_ascii_case(name))
+                .unwrap_or_else(|| c[1].to_string());
+            format!("[[{}{}]]", fixed, &c[2])
+        })
+        .into_owned()
+}
+
+fn list_marker_char(marker: ListMarker) -> Option<char> {
+    match marker {
+        ListMarker::Dash => Some('-'),
+        ListMarker::Asterisk => Some('*'),
+        ListMarker::Plus => Some('+'),
+        ListMarker::Keep => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(content: &str) -> String {
+        let link_name = |name: &str| {
+            name.eq_ignore_ascii_case("projects/roadmap").then(|| "Projects/Roadmap".to_string())
+        };
+        format_markdown(content, &FormattingConfig::default(), &link_name)
+    }
+
+    #[test]
+    fn test_format_markdown() {
+        let content = "---\ntitle: Plan  \n---\n# Plan   \nIntro text \nhard break     \n\n\n\n##  Tasks\n* one\n+ two\n  * nested\n* * *\n#tag stays\nSee [[projects/roadmap#Q1|the roadmap]] and [[Other]].\n\n\n";
+        let expected = "---\ntitle: Plan  \n---\n# Plan\n\nIntro text\nhard break  \n\n## Tasks\n\n- one\n- two\n  - nested\n* * *\n#tag stays\nSee [[Projects/Roadmap#Q1|the roadmap]] and [[Other]].\n";
+        assert_eq!(format(content), expected);
+    }
+
+    #[test]
+    fn test_format_leaves_code_alone() {
+        let content = "Text\n```md\n#  Not a heading   \n* item\n```\n# After";
+        assert_eq!(format(content), "Text\n```md\n#  Not a heading   \n* item\n```\n\n# After\n");
+    }
+
+    #[test]
+    fn test_format_is_idempotent() {
+        let once = format("# A\ntext\r\n* item  \r\n## B\r\n");
+        assert!(once.contains("\r\n"));
+        assert_eq!(format(&once), once);
+    }
+
+    #[test]
+    fn test_format_rules_can_be_turned_off() {
+        let rules = FormattingConfig {
+            trim_trailing_whitespace: false,
+            heading_spacing: false,
+            list_marker: ListMarker::Keep,
+            link_casing: false,
+            ..Default::default()
+        };
+        let content = "# A  \n* item\n[[projects/roadmap]]\n";
+        assert_eq!(format_markdown(content, &rules, &|_| None), content);
+    }
+}
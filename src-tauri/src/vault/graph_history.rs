@@ -0,0 +1,202 @@
+//! How the link graph changed between two versions of the vault
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::note_ids::frontmatter_uid;
+use super::parser::{extract_links, parse_note, resolve_markdown_link, LinkKind};
+use super::zettel::note_zettel_id;
+use crate::models::{GraphData, GraphEdge, GraphNode, GraphNodeKind};
+
+/// Build the link graph of a set of notes, given as `(path, content)`.
+/// Links resolve like indexed ones: by path with or without `.md`, else by
+/// Zettelkasten ID or a stable ID pinned in frontmatter. Markdown links
+/// are relative to the linking note.
+pub fn graph_from_notes(notes: &[(String, String)]) -> GraphData {
+    let mut by_path: HashMap<String, &str> = HashMap::new();
+    let mut by_id: HashMap<String, &str> = HashMap::new();
+    for (path, content) in notes {
+        by_path.insert(path.to_lowercase(), path);
+        let filename = path.rsplit('/').next().unwrap_or(path);
+        if let Some(id) = note_zettel_id(content, filename) {
+            by_id.insert(id.to_lowercase(), path);
+        }
+        if let Some(uid) = frontmatter_uid(content) {
+            by_id.entry(uid.to_lowercase()).or_insert(path);
+        }
+    }
+
+    let mut nodes = Vec::new();
+    let mut edges = BTreeSet::new();
+    for (path, content) in notes {
+        let filename = path.rsplit('/').next().unwrap_or(path);
+        let parsed = parse_note(content, filename);
+        nodes.push(GraphNode {
+            id: path.clone(),
+            kind: GraphNodeKind::Note,
+            uid: frontmatter_uid(content),
+            title: parsed.title,
+            word_count: parsed.word_count as i32,
+            degree: 0,
+            pagerank: 0.0,
+        });
+
+        for link in extract_links(content) {
+            let target = match link.kind {
+                LinkKind::Wiki => link.target.split('#').next().unwrap_or("").trim().to_lowercase(),
+                LinkKind::Markdown => match resolve_markdown_link(path, &link.target) {
+                    Some(target) => target.to_lowercase(),
+                    None => continue,
+                },
+            };
+            let resolved = by_path
+                .get(&format!("{}.md", target))
+                .or_else(|| by_path.get(&target))
+                .or_else(|| by_id.get(&target));
+            if let Some(resolved) = resolved {
+                edges.insert((path.clone(), resolved.to_string()));
+            }
+        }
+    }
+
+    GraphData {
+        nodes,
+        edges: edges
+            .into_iter()
+            .map(|(source, target)| GraphEdge {
+                source,
+                target,
+                weight: None,
+            })
+            .collect(),
+    }
+}
+
+/// Nodes and edges added and removed between two graphs
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphDiff {
+    pub added_nodes: Vec<GraphNode>,
+    pub removed_nodes: Vec<GraphNode>,
+    pub added_edges: Vec<GraphEdge>,
+    pub removed_edges: Vec<GraphEdge>,
+    /// Notes in both graphs, in the `after` version
+    pub unchanged_nodes: usize,
+}
+
+/// Compare two graphs. With `folder`, only notes in it count, along with
+/// the links from them.
+pub fn diff_graphs(before: &GraphData, after: &GraphData, folder: Option<&str>) -> GraphDiff {
+    let prefix = folder
+        .map(|f| f.trim_matches('/'))
+        .filter(|f| !f.is_empty())
+        .map(|f| format!("{}/", f));
+    let in_scope = |path: &str| prefix.as_deref().is_none_or(|p| path.starts_with(p));
+
+    let nodes = |graph: &GraphData| -> BTreeMap<String, GraphNode> {
+        graph
+            .nodes
+            .iter()
+            .filter(|n| in_scope(&n.id))
+            .map(|n| (n.id.clone(), n.clone()))
+            .collect()
+    };
+    let edges = |graph: &GraphData| -> BTreeSet<(String, String)> {
+        graph
+            .edges
+            .iter()
+            .filter(|e| in_scope(&e.source))
+            .map(|e| (e.source.clone(), e.target.clone()))
+            .collect()
+    };
+    let (nodes_before, nodes_after) = (nodes(before), nodes(after));
+    let (edges_before, edges_after) = (edges(before), edges(after));
+
+    let only_in = |a: &BTreeMap<String, GraphNode>, b: &BTreeMap<String, GraphNode>| {
+        a.iter()
+            .filter(|(id, _)| !b.contains_key(*id))
+            .map(|(_, node)| node.clone())
+            .collect()
+    };
+    let to_edges = |edges: std::collections::btree_set::Difference<'_, (String, String)>| {
+        edges
+            .map(|(source, target)| GraphEdge {
+                source: source.clone(),
+                target: target.clone(),
+                weight: None,
+            })
+            .collect()
+    };
+
+    GraphDiff {
+        added_nodes: only_in(&nodes_after, &nodes_before),
+        removed_nodes: only_in(&nodes_before, &nodes_after),
+        added_edges: to_edges(edges_after.difference(&edges_before)),
+        removed_edges: to_edges(edges_before.difference(&edges_after)),
+        unchanged_nodes: nodes_after.keys().filter(|id| nodes_before.contains_key(*id)).count(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notes(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter().map(|(p, c)| (p.to_string(), c.to_string())).collect()
+    }
+
+    fn edge_pairs(edges: &[GraphEdge]) -> Vec<(&str, &str)> {
+        edges.iter().map(|e| (e.source.as_str(), e.target.as_str())).collect()
+    }
+
+    #[test]
+    fn test_graph_from_notes() {
+        let graph = graph_from_notes(&notes(&[
+            ("alpha.md", "[[beta]] [[Beta#Plan]] [[202401011200]] [[missing]]"),
+            ("beta.md", "# Beta"),
+            ("202401011200 Idea.md", "An idea"),
+            ("notes/gamma.md", "[Beta](../beta.md)"),
+        ]));
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(
+            edge_pairs(&graph.edges),
+            vec![
+                ("alpha.md", "202401011200 Idea.md"),
+                ("alpha.md", "beta.md"),
+                ("notes/gamma.md", "beta.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_graphs() {
+        let before = graph_from_notes(&notes(&[
+            ("projects/alpha/plan.md", "[[projects/alpha/old]]"),
+            ("projects/alpha/old.md", ""),
+            ("inbox.md", "[[projects/alpha/plan]]"),
+        ]));
+        let after = graph_from_notes(&notes(&[
+            ("projects/alpha/plan.md", "[[projects/alpha/notes]]"),
+            ("projects/alpha/notes.md", ""),
+            ("journal.md", "[[projects/alpha/plan]]"),
+        ]));
+
+        let diff = diff_graphs(&before, &after, Some("projects/alpha"));
+        let ids = |nodes: &[GraphNode]| nodes.iter().map(|n| n.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&diff.added_nodes), vec!["projects/alpha/notes.md"]);
+        assert_eq!(ids(&diff.removed_nodes), vec!["projects/alpha/old.md"]);
+        assert_eq!(
+            edge_pairs(&diff.added_edges),
+            vec![("projects/alpha/plan.md", "projects/alpha/notes.md")]
+        );
+        assert_eq!(
+            edge_pairs(&diff.removed_edges),
+            vec![("projects/alpha/plan.md", "projects/alpha/old.md")]
+        );
+        assert_eq!(diff.unchanged_nodes, 1);
+
+        // Whole vault: the inbox and journal notes count too
+        let diff = diff_graphs(&before, &after, None);
+        assert_eq!(diff.added_nodes.len(), 2);
+        assert_eq!(diff.removed_edges.len(), 2);
+    }
+}
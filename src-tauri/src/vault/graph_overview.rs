@@ -0,0 +1,257 @@
+//! A down-sampled graph for vaults too large to draw whole
+//!
+//! The overview keeps the largest connected group of notes and, of those,
+//! the best-connected ones. Every note left out is summarized in the
+//! cluster of the shown note nearest to it, so the graph view can draw the
+//! overview first and expand a cluster with the local graph around it.
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+use crate::models::GraphData;
+
+/// Members named in a cluster's summary
+const CLUSTER_SAMPLE: usize = 3;
+
+/// The notes left out of an overview that are nearest to one shown note
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphCluster {
+    /// The shown note the cluster hangs off
+    pub anchor: String,
+    /// Notes in the cluster
+    pub size: usize,
+    /// Links from the anchor to the cluster's furthest note; the local
+    /// graph around the anchor at this depth holds the whole cluster
+    pub depth: usize,
+    /// Titles of the cluster's best-connected notes
+    pub titles: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphOverview {
+    #[serde(flatten)]
+    pub graph: GraphData,
+    /// Clusters of the notes left out, largest first
+    pub clusters: Vec<GraphCluster>,
+    /// Notes in the largest connected group, shown or not
+    pub component_size: usize,
+    /// Nodes in the whole graph
+    pub total_nodes: usize,
+}
+
+/// The largest connected part of `graph`, cut down to its `max_nodes`
+/// highest-degree nodes (at least one) and the edges among them, with the
+/// rest summarized in clusters. Degrees and ranks are kept as they were in
+/// the whole graph.
+pub fn graph_overview(graph: &GraphData, max_nodes: usize) -> GraphOverview {
+    let n = graph.nodes.len();
+    let index: HashMap<&str, usize> =
+        graph.nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), i)).collect();
+    let find = |id: &str| {
+        index.get(id).or_else(|| index.get(format!("{}.md", id).as_str())).copied()
+    };
+
+    // Links either way, and each edge's ends
+    let mut neighbors = vec![Vec::new(); n];
+    let mut ends = Vec::with_capacity(graph.edges.len());
+    for edge in &graph.edges {
+        let found = find(&edge.source).zip(find(&edge.target));
+        if let Some((source, target)) = found.filter(|(s, t)| s != t) {
+            neighbors[source].push(target);
+            neighbors[target].push(source);
+        }
+        ends.push(found);
+    }
+
+    let component = largest_component(&neighbors);
+    let mut ranked = component.clone();
+    let nodes = &graph.nodes;
+    ranked.sort_by(|&a, &b| {
+        let (a, b) = (&nodes[a], &nodes[b]);
+        b.degree.cmp(&a.degree).then(b.pagerank.total_cmp(&a.pagerank)).then(a.id.cmp(&b.id))
+    });
+    ranked.truncate(max_nodes.max(1));
+    let mut shown = vec![false; n];
+    for &i in &ranked {
+        shown[i] = true;
+    }
+
+    // Each note left out goes to the nearest shown note, the best-connected
+    // one on a tie, as the search starts from those first
+    let mut anchor: Vec<Option<(usize, usize)>> = vec![None; n];
+    let mut queue: VecDeque<usize> = ranked.iter().copied().collect();
+    for &i in &ranked {
+        anchor[i] = Some((i, 0));
+    }
+    while let Some(i) = queue.pop_front() {
+        let (root, depth) = anchor[i].expect("queued nodes have an anchor");
+        for &next in &neighbors[i] {
+            if anchor[next].is_none() {
+                anchor[next] = Some((root, depth + 1));
+                queue.push_back(next);
+            }
+        }
+    }
+
+    let mut members: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+    for &i in component.iter().filter(|&&i| !shown[i]) {
+        if let Some((root, depth)) = anchor[i] {
+            members.entry(root).or_default().push((i, depth));
+        }
+    }
+    let mut clusters: Vec<GraphCluster> = members
+        .into_iter()
+        .map(|(root, mut members)| {
+            members.sort_by(|&(a, _), &(b, _)| nodes[b].degree.cmp(&nodes[a].degree));
+            GraphCluster {
+                anchor: nodes[root].id.clone(),
+                size: members.len(),
+                depth: members.iter().map(|&(_, depth)| depth).max().unwrap_or(0),
+                titles: members
+                    .iter()
+                    .take(CLUSTER_SAMPLE)
+                    .map(|&(i, _)| nodes[i].title.clone())
+                    .collect(),
+            }
+        })
+        .collect();
+    clusters.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.anchor.cmp(&b.anchor)));
+
+    let edges = graph
+        .edges
+        .iter()
+        .zip(&ends)
+        .filter(|(_, ends)| ends.is_some_and(|(source, target)| shown[source] && shown[target]))
+        .map(|(edge, _)| edge.clone())
+        .collect();
+    GraphOverview {
+        graph: GraphData {
+            nodes: nodes.iter().zip(&shown).filter(|(_, s)| **s).map(|(n, _)| n.clone()).collect(),
+            edges,
+        },
+        clusters,
+        component_size: component.len(),
+        total_nodes: n,
+    }
+}
+
+/// The nodes of the largest connected component, in node order
+fn largest_component(neighbors: &[Vec<usize>]) -> Vec<usize> {
+    let mut component = vec![usize::MAX; neighbors.len()];
+    let mut sizes = Vec::new();
+    for start in 0..neighbors.len() {
+        if component[start] != usize::MAX {
+            continue;
+        }
+        let id = sizes.len();
+        component[start] = id;
+        let mut size = 0;
+        let mut stack = vec![start];
+        while let Some(i) = stack.pop() {
+            size += 1;
+            for &next in &neighbors[i] {
+                if component[next] == usize::MAX {
+                    component[next] = id;
+                    stack.push(next);
+                }
+            }
+        }
+        sizes.push(size);
+    }
+
+    // The first of the largest, so ties don't depend on more than order
+    let Some(largest) = sizes.iter().enumerate().rev().max_by_key(|(_, size)| **size) else {
+        return Vec::new();
+    };
+    let largest = largest.0;
+    (0..neighbors.len()).filter(|&i| component[i] == largest).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{GraphEdge, GraphNode, GraphNodeKind};
+    use crate::vault::add_centrality;
+
+    fn graph(edges: &[(&str, &str)], lone: &[&str]) -> GraphData {
+        let mut ids: Vec<&str> =
+            edges.iter().flat_map(|(a, b)| [*a, *b]).chain(lone.iter().copied()).collect();
+        ids.sort();
+        ids.dedup();
+        let mut graph = GraphData {
+            nodes: ids
+                .iter()
+                .map(|id| GraphNode {
+                    id: format!("{}.md", id),
+                    kind: GraphNodeKind::Note,
+                    uid: None,
+                    title: id.to_string(),
+                    word_count: 0,
+                    degree: 0,
+                    pagerank: 0.0,
+                })
+                .collect(),
+            edges: edges
+                .iter()
+                .map(|(source, target)| GraphEdge {
+                    source: format!("{}.md", source),
+                    target: target.to_string(),
+                    weight: None,
+                })
+                .collect(),
+        };
+        add_centrality(&mut graph);
+        graph
+    }
+
+    #[test]
+    fn test_graph_overview() {
+        // Two hubs joined by a link, each with leaves, one of them two deep;
+        // a separate pair and a note with no links
+        let g = graph(
+            &[
+                ("hub", "a1"),
+                ("hub", "a2"),
+                ("hub", "a3"),
+                ("a3", "a4"),
+                ("other", "hub"),
+                ("other", "b1"),
+                ("other", "b2"),
+                ("x", "y"),
+            ],
+            &["lone"],
+        );
+        let overview = graph_overview(&g, 2);
+
+        assert_eq!(overview.total_nodes, 11);
+        assert_eq!(overview.component_size, 8);
+        let shown: Vec<&str> = overview.graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(shown, vec!["hub.md", "other.md"]);
+        assert_eq!(overview.graph.nodes[0].degree, 4);
+        assert_eq!(overview.graph.edges.len(), 1);
+
+        assert_eq!(
+            overview.clusters[0],
+            GraphCluster {
+                anchor: "hub.md".to_string(),
+                size: 4,
+                depth: 2,
+                titles: vec!["a3".to_string(), "a1".to_string(), "a2".to_string()],
+            }
+        );
+        assert_eq!(overview.clusters[1].anchor, "other.md");
+        assert_eq!(overview.clusters[1].size, 2);
+    }
+
+    #[test]
+    fn test_small_graph_is_whole() {
+        let g = graph(&[("a", "b"), ("b", "c")], &[]);
+        let overview = graph_overview(&g, 10);
+        assert_eq!(overview.graph.nodes.len(), 3);
+        assert_eq!(overview.graph.edges.len(), 2);
+        assert!(overview.clusters.is_empty());
+
+        let empty = graph_overview(&GraphData { nodes: Vec::new(), edges: Vec::new() }, 10);
+        assert!(empty.graph.nodes.is_empty());
+    }
+}
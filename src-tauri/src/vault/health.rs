@@ -0,0 +1,286 @@
+//! Vault health checks: problems in notes and files that are easy to miss
+//! until something stops working
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::broken_links::BrokenLink;
+use super::indexer::{is_indexed_file, walkdir};
+use super::parser::{frontmatter_error, parse_frontmatter};
+
+/// Notes larger than this make the editor and indexer sluggish
+pub const MAX_NOTE_BYTES: u64 = 1024 * 1024;
+/// Attachments larger than this bloat backups and synced repositories
+pub const MAX_ATTACHMENT_BYTES: u64 = 50 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthIssueKind {
+    EmptyNote,
+    InvalidFrontmatter,
+    DuplicateTitle,
+    BrokenLink,
+    OversizedFile,
+    NonUtf8File,
+    TypeSchema,
+}
+
+/// A problem with one file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthIssue {
+    pub kind: HealthIssueKind,
+    pub severity: Severity,
+    /// Vault-relative path of the file
+    pub path: String,
+    pub message: String,
+    pub suggested_fix: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub files_checked: usize,
+    /// Most severe first
+    pub issues: Vec<HealthIssue>,
+}
+
+impl HealthReport {
+    pub fn new(files_checked: usize, mut issues: Vec<HealthIssue>) -> Self {
+        issues.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.path.cmp(&b.path)));
+        Self {
+            files_checked,
+            issues,
+        }
+    }
+}
+
+/// Check every file in the vault on its own: size, encoding, and for notes
+/// their frontmatter and whether they have any content. Returns the number
+/// of files checked and the issues found.
+pub fn check_vault_files(vault_path: &Path) -> io::Result<(usize, Vec<HealthIssue>)> {
+    let files = walkdir(vault_path)?;
+    let mut issues = Vec::new();
+
+    for file in &files {
+        let rel_path = file
+            .strip_prefix(vault_path)
+            .unwrap_or(file)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let size = fs::metadata(file)?.len();
+
+        if !is_indexed_file(file) {
+            if size > MAX_ATTACHMENT_BYTES {
+                issues.push(oversized(rel_path, size, MAX_ATTACHMENT_BYTES));
+            }
+            continue;
+        }
+        if size > MAX_NOTE_BYTES {
+            issues.push(oversized(rel_path.clone(), size, MAX_NOTE_BYTES));
+        }
+
+        match String::from_utf8(fs::read(file)?) {
+            Ok(content) if file.extension().is_some_and(|e| e == "md") => {
+                issues.extend(check_note(&rel_path, &content));
+            }
+            Ok(_) => {}
+            Err(_) => issues.push(HealthIssue {
+                kind: HealthIssueKind::NonUtf8File,
+                severity: Severity::Error,
+                path: rel_path,
+                message: "File is not valid UTF-8 and is left out of the index".to_string(),
+                suggested_fix: "Re-save the file with UTF-8 encoding".to_string(),
+            }),
+        }
+    }
+
+    Ok((files.len(), issues))
+}
+
+/// Issues within a single note's content
+pub fn check_note(path: &str, content: &str) -> Vec<HealthIssue> {
+    let mut issues = Vec::new();
+
+    if let Some(error) = frontmatter_error(content) {
+        issues.push(HealthIssue {
+            kind: HealthIssueKind::InvalidFrontmatter,
+            severity: Severity::Warning,
+            path: path.to_string(),
+            message: format!(
+                "Frontmatter can't be read, so its tags and properties are ignored: {}",
+                error
+            ),
+            suggested_fix: "Fix the YAML between the --- lines".to_string(),
+        });
+    }
+
+    let (_, body_start) = parse_frontmatter(content);
+    let body_is_empty = content[body_start..]
+        .lines()
+        .all(|line| line.trim().is_empty() || line.trim_start().starts_with("# "));
+    if body_is_empty {
+        issues.push(HealthIssue {
+            kind: HealthIssueKind::EmptyNote,
+            severity: Severity::Info,
+            path: path.to_string(),
+            message: "Note has no content besides its title".to_string(),
+            suggested_fix: "Write something in it or delete it".to_string(),
+        });
+    }
+
+    issues
+}
+
+/// Notes sharing a title, compared ignoring case, which makes links by
+/// title ambiguous. Takes `(path, title)` pairs.
+pub fn duplicate_title_issues(notes: &[(String, String)]) -> Vec<HealthIssue> {
+    let mut by_title: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for (path, title) in notes {
+        by_title.entry(title.trim().to_lowercase()).or_default().push(path);
+    }
+
+    let mut issues = Vec::new();
+    for paths in by_title.values().filter(|paths| paths.len() > 1) {
+        for path in paths {
+            let others: Vec<&str> = paths.iter().copied().filter(|p| p != path).collect();
+            issues.push(HealthIssue {
+                kind: HealthIssueKind::DuplicateTitle,
+                severity: Severity::Warning,
+                path: path.to_string(),
+                message: format!("Title is also used by {}", others.join(", ")),
+                suggested_fix: "Give one of the notes a distinct title".to_string(),
+            });
+        }
+    }
+    issues
+}
+
+/// One issue per note linking to a missing note
+pub fn broken_link_issues(broken: &[BrokenLink]) -> Vec<HealthIssue> {
+    let mut issues = Vec::new();
+    for link in broken {
+        let suggested_fix = match link.suggestions.first() {
+            Some(suggestion) => format!("Point the link at {}", suggestion),
+            None => "Create the missing note or remove the link".to_string(),
+        };
+        for occurrence in &link.occurrences {
+            let message = match occurrence.line_number {
+                Some(line) => {
+                    format!("Links to missing note [[{}]] on line {}", link.target, line)
+                }
+                None => format!("Links to missing note [[{}]]", link.target),
+            };
+            issues.push(HealthIssue {
+                kind: HealthIssueKind::BrokenLink,
+                severity: Severity::Warning,
+                path: occurrence.source_path.clone(),
+                message,
+                suggested_fix: suggested_fix.clone(),
+            });
+        }
+    }
+    issues
+}
+
+/// Notes that don't match their type's schema. Takes `(path, message)`
+/// pairs from the index.
+pub fn type_schema_issues(warnings: &[(String, String)]) -> Vec<HealthIssue> {
+    warnings
+        .iter()
+        .map(|(path, message)| HealthIssue {
+            kind: HealthIssueKind::TypeSchema,
+            severity: Severity::Warning,
+            path: path.clone(),
+            message: message.clone(),
+            suggested_fix: "Set the property in the note's frontmatter, or fix its type"
+                .to_string(),
+        })
+        .collect()
+}
+
+fn oversized(path: String, size: u64, limit: u64) -> HealthIssue {
+    const MIB: f64 = 1024.0 * 1024.0;
+    HealthIssue {
+        kind: HealthIssueKind::OversizedFile,
+        severity: Severity::Warning,
+        path,
+        message: format!(
+            "File is {:.1} MiB, over the {:.0} MiB limit",
+            size as f64 / MIB,
+            limit as f64 / MIB
+        ),
+        suggested_fix: if limit == MAX_NOTE_BYTES {
+            "Split the note into smaller notes".to_string()
+        } else {
+            "Compress the file or keep it outside the vault".to_string()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn kinds(issues: &[HealthIssue], path: &str) -> Vec<HealthIssueKind> {
+        issues.iter().filter(|i| i.path == path).map(|i| i.kind).collect()
+    }
+
+    #[test]
+    fn test_check_vault_files() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("ok.md"), "# Ok\n\nSome text").unwrap();
+        fs::write(root.join("empty.md"), "---\ntags: [x]\n---\n# Empty\n\n").unwrap();
+        fs::write(root.join("bad.md"), "---\ntags: [x\n---\nText").unwrap();
+        fs::write(root.join("latin1.md"), [b'c', b'a', b'f', 0xe9]).unwrap();
+        fs::write(root.join("big.md"), "x".repeat(MAX_NOTE_BYTES as usize + 1)).unwrap();
+        fs::write(root.join("photo.png"), [0xffu8, 0xd8]).unwrap();
+
+        let (checked, issues) = check_vault_files(root).unwrap();
+        assert_eq!(checked, 6);
+        assert!(kinds(&issues, "ok.md").is_empty());
+        assert_eq!(kinds(&issues, "empty.md"), vec![HealthIssueKind::EmptyNote]);
+        assert_eq!(kinds(&issues, "bad.md"), vec![HealthIssueKind::InvalidFrontmatter]);
+        assert_eq!(kinds(&issues, "latin1.md"), vec![HealthIssueKind::NonUtf8File]);
+        assert_eq!(kinds(&issues, "big.md"), vec![HealthIssueKind::OversizedFile]);
+        // Binary attachments are only checked for size
+        assert!(kinds(&issues, "photo.png").is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_titles() {
+        let notes = vec![
+            ("a.md".to_string(), "Plan".to_string()),
+            ("work/plan.md".to_string(), "plan".to_string()),
+            ("b.md".to_string(), "Other".to_string()),
+        ];
+        let issues = duplicate_title_issues(&notes);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].path, "a.md");
+        assert_eq!(issues[0].message, "Title is also used by work/plan.md");
+    }
+
+    #[test]
+    fn test_report_sorts_by_severity() {
+        let (_, issues) = {
+            let temp = TempDir::new().unwrap();
+            fs::write(temp.path().join("a.md"), "").unwrap();
+            fs::write(temp.path().join("b.md"), [0xffu8]).unwrap();
+            check_vault_files(temp.path()).unwrap()
+        };
+        let report = HealthReport::new(2, issues);
+        assert_eq!(report.issues[0].severity, Severity::Error);
+        assert_eq!(report.issues[1].severity, Severity::Info);
+    }
+}
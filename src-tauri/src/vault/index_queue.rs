@@ -0,0 +1,184 @@
+//! Background indexing of saved notes
+//!
+//! Saving a note only writes the file; the note is indexed here, off the
+//! save's path. Saves of the same note less than `DEBOUNCE` apart are
+//! indexed once, after the last, so an editor saving on every pause in
+//! typing doesn't rewrite the note's search entry each time.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use super::Indexer;
+use crate::db::Database;
+
+/// How long a note must go unsaved before it is indexed
+pub const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Indexes queued notes on a thread of its own. Dropping it indexes
+/// whatever is still queued before it returns.
+pub struct IndexQueue {
+    shared: Arc<Shared>,
+    worker: Option<JoinHandle<()>>,
+}
+
+struct Shared {
+    pending: Mutex<Pending>,
+    /// Signalled when a note is queued or the queue is closed
+    queued: Condvar,
+    /// Signalled when the worker has indexed everything queued
+    idle: Condvar,
+}
+
+#[derive(Default)]
+struct Pending {
+    /// Notes waiting to be indexed, with when they are due
+    due: HashMap<PathBuf, Instant>,
+    /// Whether the worker is indexing a batch taken off `due`
+    busy: bool,
+    /// Index everything now, rather than when due
+    flushing: bool,
+    closed: bool,
+}
+
+impl IndexQueue {
+    /// Start indexing notes of the vault at `vault_path` into `db`
+    pub fn start(vault_path: PathBuf, db: Database) -> Self {
+        let shared = Arc::new(Shared {
+            pending: Mutex::new(Pending::default()),
+            queued: Condvar::new(),
+            idle: Condvar::new(),
+        });
+        let worker = std::thread::Builder::new()
+            .name("index-queue".to_string())
+            .spawn({
+                let shared = shared.clone();
+                move || run(&shared, &vault_path, &db)
+            })
+            .expect("Failed to spawn index queue thread");
+        Self { shared, worker: Some(worker) }
+    }
+
+    /// Index `path` once it has gone `DEBOUNCE` without being queued again
+    pub fn push(&self, path: PathBuf) {
+        lock(&self.shared.pending).due.insert(path, Instant::now() + DEBOUNCE);
+        self.shared.queued.notify_one();
+    }
+
+    /// Index everything queued now, returning once it is in the index
+    pub fn flush(&self) {
+        let mut pending = lock(&self.shared.pending);
+        pending.flushing = true;
+        self.shared.queued.notify_one();
+        while !pending.due.is_empty() || pending.busy {
+            pending = self.shared.idle.wait(pending).unwrap_or_else(|e| e.into_inner());
+        }
+        pending.flushing = false;
+    }
+}
+
+impl Drop for IndexQueue {
+    fn drop(&mut self) {
+        lock(&self.shared.pending).closed = true;
+        self.shared.queued.notify_one();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn run(shared: &Shared, vault_path: &Path, db: &Database) {
+    let mut pending = lock(&shared.pending);
+    loop {
+        let now = Instant::now();
+        let all = pending.flushing || pending.closed;
+        let batch: Vec<PathBuf> = pending
+            .due
+            .iter()
+            .filter(|(_, due)| all || **due <= now)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        if !batch.is_empty() {
+            for path in &batch {
+                pending.due.remove(path);
+            }
+            pending.busy = true;
+            drop(pending);
+            index_batch(vault_path, db, &batch);
+            pending = lock(&shared.pending);
+            pending.busy = false;
+            continue;
+        }
+
+        if pending.due.is_empty() {
+            shared.idle.notify_all();
+            if pending.closed {
+                return;
+            }
+        }
+        let next = pending.due.values().min().map(|due| due.saturating_duration_since(now));
+        pending = match next {
+            Some(wait) => {
+                let waited = shared.queued.wait_timeout(pending, wait);
+                waited.unwrap_or_else(|e| e.into_inner()).0
+            }
+            None => shared.queued.wait(pending).unwrap_or_else(|e| e.into_inner()),
+        };
+    }
+}
+
+fn index_batch(vault_path: &Path, db: &Database, paths: &[PathBuf]) {
+    let indexer = match Indexer::new(vault_path.to_path_buf()) {
+        Ok(indexer) => indexer,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to start indexing saved notes");
+            return;
+        }
+    };
+    for path in paths {
+        if let Err(e) = indexer.index_file(db, path) {
+            tracing::warn!(path = ?path, error = %e, "Failed to index saved note");
+        }
+    }
+}
+
+fn lock(pending: &Mutex<Pending>) -> MutexGuard<'_, Pending> {
+    pending.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::notes::get_note_by_path;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_saves_are_coalesced_and_flushed() {
+        let temp = TempDir::new().unwrap();
+        let db = Database::open_memory().unwrap();
+        let path = temp.path().join("Note.md");
+        let queue = IndexQueue::start(temp.path().to_path_buf(), db.clone());
+
+        for i in 0..5 {
+            fs::write(&path, format!("# Draft {}\n", i)).unwrap();
+            queue.push(path.clone());
+        }
+        // Still waiting out the debounce
+        assert!(get_note_by_path(&db.conn(), "Note.md").unwrap().is_none());
+
+        queue.flush();
+        let meta = get_note_by_path(&db.conn(), "Note.md").unwrap().unwrap();
+        assert_eq!(meta.title, "Draft 4");
+
+        // Closing indexes what is still queued
+        fs::write(&path, "# Final\n").unwrap();
+        queue.push(path.clone());
+        drop(queue);
+        let meta = get_note_by_path(&db.conn(), "Note.md").unwrap().unwrap();
+        assert_eq!(meta.title, "Final");
+    }
+}
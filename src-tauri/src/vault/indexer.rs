@@ -2,19 +2,58 @@
 
 use crate::db::{
     self,
-    links::replace_links,
-    notes::{delete_note as db_delete_note, get_note_by_path, upsert_note},
+    cards::replace_cards,
+    links::{replace_links, resolve_dangling_links, set_link_contexts},
+    note_ids::{get_uid_by_note_id, list_note_uids, set_note_uid, uid_owner},
+    notes::{
+        delete_note as db_delete_note, get_index_state, get_note_by_path, list_note_hashes,
+        rename_note, set_index_state, set_note_modified, upsert_note,
+    },
+    properties::{replace_note_properties, replace_type_warnings},
+    reading::add_reading_items,
+    reminders::replace_reminders,
     schema::Database,
     search::update_fts,
-    tags::set_note_tags,
+    tags::{replace_tag_occurrences, set_note_tags},
+    tasks::replace_tasks,
+    writing::record_word_count,
+    zettel::set_zettel_id,
 };
-use crate::vault::parser::parse_note;
+use crate::citations::{extract_pandoc_citations, resolve_citation_target};
+use crate::models::{AppConfig, NoteTypeSchema};
+use crate::srs::{extract_cards, Card};
+use crate::vault::canvas::{is_canvas_file, Canvas};
+use crate::vault::cloud::{is_placeholder, placeholder_files, request_download};
+use crate::vault::excalidraw::{drawing_title, is_excalidraw_file, parse_drawing};
+use crate::vault::note_ids::{frontmatter_uid, generate_uid, read_note_ids, write_note_ids};
+use crate::vault::note_types::{type_warnings, with_type_defaults};
+use crate::vault::parser::{
+    extract_urls, frontmatter_properties, link_context, parse_frontmatter, parse_note,
+    resolve_markdown_link, ExtractedTag, ExtractedUrl, LinkKind,
+};
+use crate::vault::reminders::{extract_reminders, ExtractedReminder, REMIND_AT_FORMAT};
+use crate::vault::sidecar::{has_sidecar, sidecar_markdown, sidecar_path, sidecar_target};
+use crate::vault::tasks::{extract_tasks, ExtractedTask};
+use rusqlite::Connection;
+use crate::vault::zettel::note_zettel_id;
 use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
 use thiserror::Error;
 
+/// Bump when extraction changes so existing indexes are rebuilt
+const INDEX_VERSION: u32 = 6;
+const INDEX_SETTINGS_KEY: &str = "settings";
+/// Fewer changed files than this are indexed on one thread
+const PARALLEL_MIN_FILES: usize = 32;
+/// Files written per transaction during a full index
+const WRITE_BATCH_SIZE: usize = 200;
+
 #[derive(Error, Debug)]
 pub enum IndexError {
     #[error("IO error: {0}")]
@@ -25,11 +64,64 @@ pub enum IndexError {
 
     #[error("Vault path does not exist: {0}")]
     VaultNotFound(PathBuf),
+
+    #[error("Invalid canvas {0}: {1}")]
+    InvalidCanvas(PathBuf, String),
+
+    #[error("Invalid drawing {0}: {1}")]
+    InvalidDrawing(PathBuf, String),
+
+    #[error("Invalid sidecar {0}: {1}")]
+    InvalidSidecar(PathBuf, String),
 }
 
 /// Vault indexer
 pub struct Indexer {
     vault_path: PathBuf,
+    /// Where `[[@key]]` citations point
+    literature_folder: String,
+    /// Schemas notes are checked against, by type name
+    note_types: BTreeMap<String, NoteTypeSchema>,
+    /// Threads reading and parsing files during a full index
+    threads: usize,
+}
+
+/// A file to index and the stable ID to give it if it has none yet
+type IndexJob<'a> = (&'a Path, Option<&'a str>);
+
+/// A file read and parsed, ready to be written to the index
+struct PreparedFile {
+    relative_path: String,
+    title: String,
+    created: Option<String>,
+    modified: Option<String>,
+    /// Whether the file was created today, for writing goals
+    created_today: bool,
+    content_hash: String,
+    /// Searchable text
+    text: String,
+    word_count: i32,
+    links: Vec<(String, Option<String>, Option<i32>, LinkKind)>,
+    /// The lines links are on, by line number
+    link_contexts: Vec<(i32, String)>,
+    /// Frontmatter tags, if the file has frontmatter; for notes, inline
+    /// tags as well
+    tags: Option<Vec<String>>,
+    note: Option<NoteExtras>,
+}
+
+/// What Markdown notes have beyond other indexed files
+struct NoteExtras {
+    zettel_id: Option<String>,
+    /// Stable ID pinned in the frontmatter
+    uid: Option<String>,
+    properties: Vec<(String, String)>,
+    type_warnings: Vec<String>,
+    tasks: Vec<ExtractedTask>,
+    inline_tags: Vec<ExtractedTag>,
+    urls: Vec<ExtractedUrl>,
+    reminders: Vec<ExtractedReminder>,
+    cards: Vec<Card>,
 }
 
 impl Indexer {
@@ -38,96 +130,562 @@ impl Indexer {
         if !vault_path.exists() {
             return Err(IndexError::VaultNotFound(vault_path));
         }
-        Ok(Self { vault_path })
+        let config = AppConfig::load();
+        Ok(Self {
+            vault_path,
+            literature_folder: config.citations.literature_folder,
+            note_types: config.note_types,
+            threads: match config.database.index_threads {
+                0 => thread::available_parallelism().map_or(1, |n| n.get()),
+                threads => threads,
+            },
+        })
+    }
+
+    /// Read and parse files on `threads` threads during a full index; 1
+    /// indexes on the calling thread only
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
     }
 
-    /// Full index of all notes in vault
+    /// Full index of all notes in vault. Notes unchanged since they were
+    /// last indexed are skipped, ones moved keep their IDs, and ones whose
+    /// files are gone are removed.
     pub fn full_index(&self, db: &Database) -> Result<usize, IndexError> {
         let mut count = 0;
 
-        for entry in walkdir(&self.vault_path)? {
-            if self.is_markdown_file(&entry) {
-                if let Err(e) = self.index_file(db, &entry) {
-                    eprintln!("Error indexing {:?}: {}", entry, e);
+        // Notes still in the cloud are indexed by the watcher once they
+        // arrive; dataless ones download as they are read below
+        for file in placeholder_files(&self.vault_path)? {
+            if is_indexed_file(&file) {
+                if let Err(e) = request_download(&file) {
+                    tracing::warn!(path = ?file, error = %e, "Failed to request download");
+                }
+            }
+        }
+
+        // Notes indexed with the current settings are only re-indexed if
+        // their content changed
+        let indexed = list_note_hashes(&db.conn())?;
+        let settings = self.settings_signature();
+        let trust_index =
+            get_index_state(&db.conn(), INDEX_SETTINGS_KEY)?.as_deref() == Some(settings.as_str());
+        let known_uids = read_note_ids(&self.vault_path).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Failed to read note IDs");
+            BTreeMap::new()
+        });
+
+        let files: Vec<PathBuf> = walkdir(&self.vault_path)?
+            .into_iter()
+            .filter(|entry| entry.is_file() && (is_indexed_file(entry) || has_sidecar(entry)))
+            .collect();
+        let on_disk: HashSet<String> = files.iter().map(|file| self.relative_path(file)).collect();
+
+        // Notes whose files are gone, by content hash, to spot ones moved or
+        // renamed while the vault was closed. Ones evicted to the cloud stay
+        // until they come back.
+        let mut vanished: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (path, (hash, _)) in &indexed {
+            if !on_disk.contains(path) && !is_placeholder(&self.vault_path.join(path)) {
+                vanished.entry(hash.as_str()).or_default().push(path.as_str());
+            }
+        }
+
+        let mut changed = Vec::new();
+        for entry in &files {
+            let relative_path = self.relative_path(entry);
+
+            // A new file with the same content as exactly one vanished note
+            // is that note moved; it keeps its ID
+            if !indexed.contains_key(&relative_path) && !vanished.is_empty() {
+                let hash =
+                    fs::read_to_string(content_source(entry)).map(|content| hash_content(&content));
+                let unique = hash
+                    .ok()
+                    .filter(|hash| vanished.get(hash.as_str()).is_some_and(|p| p.len() == 1));
+                if let Some(paths) = unique.and_then(|hash| vanished.remove(hash.as_str())) {
+                    rename_note(&db.conn(), paths[0], &relative_path)?;
+                }
+            }
+
+            let known = indexed.get(&relative_path).filter(|_| trust_index);
+            match self.is_unchanged(db, entry, known) {
+                Ok(true) => {
+                    count += 1;
                     continue;
                 }
-                count += 1;
+                Ok(false) => {}
+                Err(e) => tracing::warn!(path = ?entry, error = %e, "Failed to check note"),
+            }
+
+            changed.push((entry.as_path(), known_uids.get(&relative_path).map(String::as_str)));
+        }
+
+        if self.threads > 1 && changed.len() >= PARALLEL_MIN_FILES {
+            count += self.index_parallel(db, &changed)?;
+        } else {
+            for chunk in changed.chunks(WRITE_BATCH_SIZE) {
+                let mut batch = chunk.iter().map(|&job| (job, self.prepare_file(job.0))).collect();
+                count += self.write_batch(db, &mut batch)?;
+            }
+        }
+
+        db.transaction(|conn| {
+            // Notes deleted while the vault was closed
+            for path in vanished.values().flatten() {
+                remove_from_index(conn, path)?;
+            }
+            resolve_dangling_links(conn)?;
+            set_index_state(conn, INDEX_SETTINGS_KEY, &settings)
+        })?;
+
+        let note_ids = list_note_uids(&db.conn())?;
+        if note_ids != known_uids {
+            if let Err(e) = write_note_ids(&self.vault_path, &note_ids) {
+                tracing::warn!(error = %e, "Failed to write note IDs");
             }
         }
 
         Ok(count)
     }
 
-    /// Index a single file
-    pub fn index_file(&self, db: &Database, path: &Path) -> Result<(), IndexError> {
-        let relative_path = path
-            .strip_prefix(&self.vault_path)
+    /// Index files on a pool of worker threads that read and parse them,
+    /// writing the results on this thread a batch per transaction, in file
+    /// order.
+    /// Returns how many were indexed.
+    fn index_parallel(
+        &self,
+        db: &Database,
+        files: &[IndexJob],
+    ) -> Result<usize, IndexError> {
+        let next = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads.min(files.len()) {
+                let (next, sender) = (&next, sender.clone());
+                scope.spawn(move || loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some((path, _)) = files.get(index) else {
+                        break;
+                    };
+                    if sender.send((index, self.prepare_file(path))).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(sender);
+
+            let mut count = 0;
+            let mut pending = BTreeMap::new();
+            let mut batch = Vec::new();
+            let mut next_write = 0;
+            for (index, file) in receiver {
+                pending.insert(index, file);
+                while let Some(file) = pending.remove(&next_write) {
+                    batch.push((files[next_write], file));
+                    next_write += 1;
+                }
+                if batch.len() >= WRITE_BATCH_SIZE {
+                    count += self.write_batch(db, &mut batch)?;
+                }
+            }
+            count += self.write_batch(db, &mut batch)?;
+            Ok(count)
+        })
+    }
+
+    /// Write prepared files in one transaction, emptying `batch`. Files that
+    /// failed to prepare or write are skipped, each written in a savepoint so
+    /// one failing part-way leaves none of it behind. Returns how many were
+    /// written.
+    fn write_batch(
+        &self,
+        db: &Database,
+        batch: &mut Vec<(IndexJob, Result<PreparedFile, IndexError>)>,
+    ) -> Result<usize, IndexError> {
+        db.transaction(|conn| {
+            let mut count = 0;
+            for ((path, known_uid), file) in batch.drain(..) {
+                conn.execute_batch("SAVEPOINT index_note")?;
+                match file.and_then(|file| self.write_file(conn, file, known_uid)) {
+                    Ok(()) => {
+                        conn.execute_batch("RELEASE index_note")?;
+                        count += 1;
+                    }
+                    Err(e) => {
+                        conn.execute_batch("ROLLBACK TO index_note; RELEASE index_note")?;
+                        tracing::warn!(path = ?path, error = %e, "Failed to index note");
+                    }
+                }
+            }
+            Ok(count)
+        })
+    }
+
+    /// Whether the file still matches what was indexed for it, from its
+    /// modification time or, if that changed, its content. A time in whole
+    /// seconds may be from a filesystem that keeps no more, where a change
+    /// in the second the note was indexed wouldn't show, so the content is
+    /// checked then too.
+    fn is_unchanged(
+        &self,
+        db: &Database,
+        path: &Path,
+        known: Option<&(String, Option<String>)>,
+    ) -> Result<bool, IndexError> {
+        let Some((hash, modified)) = known else {
+            return Ok(false);
+        };
+        let source = content_source(path);
+        let mtime = fs::metadata(&source)?.modified().ok().map(chrono_from_systemtime);
+        let precise = mtime.as_deref().is_some_and(|m| !m.ends_with(".000Z"));
+        if precise && mtime == *modified {
+            return Ok(true);
+        }
+        if hash_content(&fs::read_to_string(&source)?) != *hash {
+            return Ok(false);
+        }
+        // Touched but not changed; the next full index can skip reading it
+        set_note_modified(&db.conn(), &self.relative_path(path), mtime.as_deref())?;
+        Ok(true)
+    }
+
+    /// Settings that change what gets indexed for a note; notes indexed
+    /// with different ones are re-indexed even if unchanged
+    fn settings_signature(&self) -> String {
+        let note_types = serde_json::to_string(&self.note_types).unwrap_or_default();
+        hash_content(&format!("{}\n{}\n{}", INDEX_VERSION, self.literature_folder, note_types))
+    }
+
+    fn relative_path(&self, path: &Path) -> String {
+        path.strip_prefix(&self.vault_path)
             .unwrap_or(path)
             .to_string_lossy()
-            .to_string();
+            .to_string()
+    }
 
-        let content = fs::read_to_string(path)?;
+    /// Index a single file. For a sidecar, the file it describes is
+    /// re-indexed, or dropped from the index if the sidecar is gone.
+    pub fn index_file(&self, db: &Database, path: &Path) -> Result<(), IndexError> {
+        match sidecar_target(path) {
+            Some(file) if file.is_file() && has_sidecar(&file) => {
+                self.index_file_with_uid(db, &file, None)
+            }
+            Some(file) => self.remove_file(db, &file),
+            None => self.index_file_with_uid(db, path, None),
+        }
+    }
+
+    /// Index a file, giving it `known_uid` (e.g. from the note IDs file) if
+    /// it has no stable ID yet
+    fn index_file_with_uid(
+        &self,
+        db: &Database,
+        path: &Path,
+        known_uid: Option<&str>,
+    ) -> Result<(), IndexError> {
+        let file = self.prepare_file(path)?;
+        self.write_file(&db.conn(), file, known_uid)
+    }
+
+    /// Read and parse a file: everything indexing it needs that doesn't
+    /// touch the database, so it can run on any thread
+    fn prepare_file(&self, path: &Path) -> Result<PreparedFile, IndexError> {
+        if is_canvas_file(path) {
+            return self.prepare_canvas(path);
+        }
+        if is_excalidraw_file(path) {
+            return self.prepare_drawing(path);
+        }
+        if has_sidecar(path) {
+            return self.prepare_described_file(path);
+        }
+        self.prepare_note(path, &fs::read_to_string(path)?)
+    }
+
+    /// A file described by a sidecar, indexed as a note made of the
+    /// sidecar's metadata and notes. Its hash is the sidecar's, so it's
+    /// re-indexed when the sidecar changes.
+    fn prepare_described_file(&self, path: &Path) -> Result<PreparedFile, IndexError> {
+        let sidecar = sidecar_path(path);
+        let yaml = fs::read_to_string(&sidecar)?;
+        let markdown =
+            sidecar_markdown(&yaml).map_err(|e| IndexError::InvalidSidecar(sidecar, e))?;
+        let mut file = self.prepare_note(path, &markdown)?;
+        file.content_hash = hash_content(&yaml);
+        Ok(file)
+    }
+
+    /// A Markdown note, with its tags, properties, tasks and the rest
+    fn prepare_note(&self, path: &Path, content: &str) -> Result<PreparedFile, IndexError> {
         let filename = path
             .file_name()
             .and_then(|s| s.to_str())
             .unwrap_or("unknown");
 
-        let parsed = parse_note(&content, filename);
-        let content_hash = hash_content(&content);
-
-        let conn = db.conn();
-
-        // Get timestamps from file metadata
-        let metadata = fs::metadata(path)?;
-        let modified = metadata.modified().ok().map(chrono_from_systemtime);
-        let created = metadata.created().ok().map(chrono_from_systemtime);
-
-        // Upsert note
-        let note_id = upsert_note(
-            &conn,
-            &relative_path,
-            &parsed.title,
-            created.as_deref(),
-            modified.as_deref(),
-            &content_hash,
+        let parsed = parse_note(content, filename);
+        // Citations link to their literature notes
+        let links = parsed
+            .links
+            .into_iter()
+            .map(|l| (l.target, l.display, Some(l.line_number as i32), l.kind))
+            .chain(extract_pandoc_citations(content).into_iter().map(|(key, line)| {
+                (format!("@{}", key), None, Some(line as i32), LinkKind::Wiki)
+            }))
+            .collect();
+        let mut file = self.prepare_document(
+            path,
+            content,
+            parsed.title,
+            content.to_string(),
             parsed.word_count as i32,
+            links,
         )?;
 
-        // Update FTS index
-        update_fts(&conn, note_id, &parsed.title, &content)?;
+        let mut tags = parsed.frontmatter.map(|fm| fm.tags).unwrap_or_default();
+        for tag in &parsed.inline_tags {
+            if !tags.iter().any(|t| t.eq_ignore_ascii_case(&tag.name)) {
+                tags.push(tag.name.clone());
+            }
+        }
+        file.tags = Some(tags);
+
+        let properties = with_type_defaults(frontmatter_properties(content), &self.note_types);
+        file.note = Some(NoteExtras {
+            zettel_id: note_zettel_id(content, filename),
+            uid: frontmatter_uid(content),
+            type_warnings: type_warnings(&properties, &self.note_types),
+            properties,
+            tasks: extract_tasks(content),
+            inline_tags: parsed.inline_tags,
+            urls: extract_urls(content),
+            reminders: extract_reminders(content),
+            cards: extract_cards(content),
+        });
+        Ok(file)
+    }
+
+    /// A canvas, indexed like a note: its cards' text for search, and its
+    /// file cards and `[[links]]` as links so it shows up in the graph and
+    /// backlinks
+    fn prepare_canvas(&self, path: &Path) -> Result<PreparedFile, IndexError> {
+        let content = fs::read_to_string(path)?;
+        let canvas = Canvas::parse(&content)
+            .map_err(|e| IndexError::InvalidCanvas(path.to_path_buf(), e))?;
+        let title = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let links = canvas
+            .links()
+            .into_iter()
+            .map(|(target, display, kind)| (target, display, None, kind))
+            .collect();
 
-        // Update links
-        let links: Vec<(String, Option<String>, Option<i32>)> = parsed
+        let text = canvas.text();
+        let word_count = text.split_whitespace().count() as i32;
+        self.prepare_document(path, &content, title, text, word_count, links)
+    }
+
+    /// An Excalidraw drawing: its text elements for search, and the notes
+    /// it links to. Tags in a Markdown drawing's frontmatter count.
+    fn prepare_drawing(&self, path: &Path) -> Result<PreparedFile, IndexError> {
+        let content = fs::read_to_string(path)?;
+        let markdown = self.is_markdown_file(path);
+        let drawing = parse_drawing(&content, markdown)
+            .map_err(|e| IndexError::InvalidDrawing(path.to_path_buf(), e))?;
+        let links = drawing
             .links
             .into_iter()
-            .map(|l| (l.target, l.display, Some(l.line_number as i32)))
+            .map(|l| (l.target, l.display, l.line_number.map(|n| n as i32), l.kind))
             .collect();
-        replace_links(&conn, note_id, &links)?;
 
-        // Update tags from frontmatter
-        if let Some(fm) = parsed.frontmatter {
-            set_note_tags(&conn, note_id, &fm.tags)?;
+        let word_count = drawing.text.split_whitespace().count() as i32;
+        let mut file = self.prepare_document(
+            path,
+            &content,
+            drawing_title(path),
+            drawing.text,
+            word_count,
+            links,
+        )?;
+        if markdown {
+            file.tags = parse_frontmatter(&content).0.map(|fm| fm.tags);
         }
+        Ok(file)
+    }
 
-        Ok(())
+    /// Whether a Markdown link to a vault path can lead to a note: a `.md`
+    /// file, canvas or drawing, a path without an extension, or a file a
+    /// sidecar describes. Links to other files are to attachments.
+    fn may_be_note(&self, target: &str) -> bool {
+        let path = Path::new(target);
+        path.extension().is_none()
+            || is_indexed_file(path)
+            || has_sidecar(&self.vault_path.join(path))
     }
 
-    /// Remove a file from the index
-    pub fn remove_file(&self, db: &Database, path: &Path) -> Result<(), IndexError> {
-        let relative_path = path
-            .strip_prefix(&self.vault_path)
-            .unwrap_or(path)
-            .to_string_lossy()
-            .to_string();
+    /// What every indexed file has: metadata, searchable text and links
+    fn prepare_document(
+        &self,
+        path: &Path,
+        content: &str,
+        title: String,
+        text: String,
+        word_count: i32,
+        links: Vec<(String, Option<String>, Option<i32>, LinkKind)>,
+    ) -> Result<PreparedFile, IndexError> {
+        let metadata = fs::metadata(content_source(path))?;
+        let today = chrono::Local::now().date_naive();
+        let relative_path = self.relative_path(path);
+        // Markdown links are stored by the vault path they lead to; ones
+        // leading out of the vault, or to attachments, are dropped
+        let links: Vec<_> = links
+            .into_iter()
+            .filter_map(|(target, display, line, kind)| {
+                let target = match kind {
+                    LinkKind::Wiki => resolve_citation_target(&target, &self.literature_folder),
+                    LinkKind::Markdown => {
+                        let target = resolve_markdown_link(&relative_path, &target)?;
+                        self.may_be_note(&target).then_some(target)?
+                    }
+                };
+                Some((target, display, line, kind))
+            })
+            .collect();
 
-        let conn = db.conn();
+        let lines: Vec<&str> = content.lines().collect();
+        let mut link_lines: Vec<i32> = links.iter().filter_map(|(_, _, line, _)| *line).collect();
+        link_lines.sort_unstable();
+        link_lines.dedup();
+        let link_contexts = link_lines
+            .into_iter()
+            .filter_map(|line| {
+                let text = lines.get(usize::try_from(line).ok()?.checked_sub(1)?)?;
+                Some((line, link_context(text)))
+            })
+            .collect();
 
-        // Get note ID for FTS cleanup
-        if let Some(note) = get_note_by_path(&conn, &relative_path)? {
-            db::search::delete_fts(&conn, note.id)?;
+        Ok(PreparedFile {
+            relative_path,
+            title,
+            created: metadata.created().ok().map(chrono_from_systemtime),
+            modified: metadata.modified().ok().map(chrono_from_systemtime),
+            created_today: metadata
+                .created()
+                .is_ok_and(|t| chrono::DateTime::<chrono::Local>::from(t).date_naive() == today),
+            content_hash: hash_content(content),
+            text,
+            word_count,
+            links,
+            link_contexts,
+            tags: None,
+            note: None,
+        })
+    }
+
+    /// Write a prepared file to the index, giving it `known_uid` if it has
+    /// no stable ID yet
+    fn write_file(
+        &self,
+        conn: &Connection,
+        file: PreparedFile,
+        known_uid: Option<&str>,
+    ) -> Result<(), IndexError> {
+        // Word count before this change, for writing goals. A note new to the
+        // index only counts as fresh writing if the file was created today;
+        // otherwise it's an existing note being indexed for the first time.
+        let today = chrono::Local::now().date_naive();
+        let baseline = match &file.note {
+            Some(_) => Some(match get_note_by_path(conn, &file.relative_path)? {
+                Some(previous) => previous.word_count,
+                None if file.created_today => 0,
+                None => file.word_count,
+            }),
+            None => None,
+        };
+
+        let note_id = upsert_note(
+            conn,
+            &file.relative_path,
+            &file.title,
+            file.created.as_deref(),
+            file.modified.as_deref(),
+            &file.content_hash,
+            file.word_count,
+        )?;
+
+        if let Some(baseline) = baseline {
+            record_word_count(
+                conn,
+                &file.relative_path,
+                &today.format("%Y-%m-%d").to_string(),
+                baseline,
+                file.word_count,
+            )?;
         }
 
-        db_delete_note(&conn, &relative_path)?;
+        // Update FTS index
+        update_fts(conn, note_id, &file.title, &file.text)?;
+
+        if let Some(note) = &file.note {
+            set_zettel_id(conn, note_id, note.zettel_id.as_deref())?;
+        }
+        replace_links(conn, note_id, &file.links)?;
+        set_link_contexts(conn, note_id, &file.link_contexts)?;
+
+        // Update tags from frontmatter and the body
+        if let Some(tags) = &file.tags {
+            set_note_tags(conn, note_id, tags)?;
+        }
+
+        let pinned_uid = match file.note {
+            Some(note) => {
+                replace_note_properties(conn, note_id, &note.properties)?;
+                replace_type_warnings(conn, note_id, &note.type_warnings)?;
+                replace_tasks(conn, note_id, &note.tasks)?;
+                replace_tag_occurrences(conn, note_id, &note.inline_tags)?;
+
+                let indexed_at =
+                    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+                add_reading_items(conn, &file.relative_path, &note.urls, &indexed_at)?;
+
+                let now = chrono::Local::now().format(REMIND_AT_FORMAT).to_string();
+                replace_reminders(conn, note_id, &note.reminders, &now)?;
+
+                // Update flashcards, keeping the schedule of unchanged ones
+                replace_cards(conn, note_id, &note.cards)?;
+                note.uid
+            }
+            None => None,
+        };
+
+        assign_uid(conn, note_id, pinned_uid.as_deref(), known_uid)
+    }
+
+    /// Index files in one transaction: if any fails, none are indexed
+    pub fn index_files(&self, db: &Database, paths: &[PathBuf]) -> Result<(), IndexError> {
+        let files = paths
+            .iter()
+            .map(|path| self.prepare_file(path))
+            .collect::<Result<Vec<_>, _>>()?;
+        db.transaction(|conn| {
+            for file in files {
+                self.write_file(conn, file, None)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Remove a file from the index. For a sidecar, the file it described
+    /// is re-indexed, which drops it if it has no sidecar any more.
+    pub fn remove_file(&self, db: &Database, path: &Path) -> Result<(), IndexError> {
+        if sidecar_target(path).is_some() {
+            return self.index_file(db, path);
+        }
+        remove_from_index(&db.conn(), &self.relative_path(path))?;
         Ok(())
     }
 
@@ -137,8 +695,63 @@ impl Indexer {
     }
 }
 
+/// Remove the note at a vault-relative path from the index
+fn remove_from_index(conn: &Connection, relative_path: &str) -> rusqlite::Result<()> {
+    // Get note ID for FTS cleanup
+    if let Some(note) = get_note_by_path(conn, relative_path)? {
+        db::search::delete_fts(conn, note.id)?;
+    }
+
+    db_delete_note(conn, relative_path)?;
+    Ok(())
+}
+
+/// Give a note its stable ID: the one pinned in its frontmatter, else the
+/// one it already has, else `known`, else a new one. IDs held by another
+/// note are passed over, so a copied note gets its own.
+fn assign_uid(
+    conn: &Connection,
+    note_id: i64,
+    pinned: Option<&str>,
+    known: Option<&str>,
+) -> Result<(), IndexError> {
+    let current = get_uid_by_note_id(conn, note_id)?;
+    let mut uid = None;
+    for candidate in [pinned, current.as_deref(), known].into_iter().flatten() {
+        match uid_owner(conn, candidate)? {
+            Some(owner) if owner != note_id => {
+                tracing::warn!(uid = %candidate, "Note ID already in use by another note");
+            }
+            _ => {
+                uid = Some(candidate.to_string());
+                break;
+            }
+        }
+    }
+
+    let uid = uid.unwrap_or_else(generate_uid);
+    if current.as_deref() != Some(uid.as_str()) {
+        set_note_uid(conn, note_id, &uid)?;
+    }
+    Ok(())
+}
+
+/// The file read to index `path`: its sidecar if it is described by one
+fn content_source(path: &Path) -> PathBuf {
+    if has_sidecar(path) {
+        sidecar_path(path)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Whether a file belongs in the index: notes, canvases and drawings
+pub fn is_indexed_file(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "md") || is_canvas_file(path) || is_excalidraw_file(path)
+}
+
 /// Walk directory recursively, skipping hidden files/dirs
-fn walkdir(root: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+pub(super) fn walkdir(root: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
     let mut files = Vec::new();
     walkdir_recursive(root, &mut files)?;
     Ok(files)
@@ -176,55 +789,10 @@ fn hash_content(content: &str) -> String {
     format!("{:x}", hasher.finish())
 }
 
-/// Convert SystemTime to ISO 8601 string
-fn chrono_from_systemtime(time: std::time::SystemTime) -> String {
-    let duration = time
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default();
-    let secs = duration.as_secs() as i64;
-
-    // Simple ISO 8601 format without external deps
-    let days_since_epoch = secs / 86400;
-    let time_of_day = secs % 86400;
-
-    // Approximate date calculation (not accounting for leap years exactly)
-    let mut year = 1970;
-    let mut remaining_days = days_since_epoch;
-
-    loop {
-        let days_in_year = if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) {
-            366
-        } else {
-            365
-        };
-        if remaining_days < days_in_year {
-            break;
-        }
-        remaining_days -= days_in_year;
-        year += 1;
-    }
-
-    let month_days = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
-    let mut month = 1;
-    let mut day = remaining_days + 1;
-
-    for days in month_days.iter() {
-        let days_in_month = *days as i64;
-        if day <= days_in_month {
-            break;
-        }
-        day -= days_in_month;
-        month += 1;
-    }
-
-    let hours = time_of_day / 3600;
-    let minutes = (time_of_day % 3600) / 60;
-    let seconds = time_of_day % 60;
-
-    format!(
-        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
-        year, month, day, hours, minutes, seconds
-    )
+/// Convert SystemTime to ISO 8601 string, to the millisecond so a change
+/// within the second a note was indexed still changes its time
+pub(crate) fn chrono_from_systemtime(time: std::time::SystemTime) -> String {
+    chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
 }
 
 #[cfg(test)]
@@ -259,6 +827,52 @@ mod tests {
         (temp, db)
     }
 
+    #[test]
+    fn test_timestamps_handle_leap_years() {
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_709_251_200);
+        assert_eq!(chrono_from_systemtime(time), "2024-03-01T00:00:00.000Z");
+    }
+
+    #[test]
+    fn test_change_within_indexed_second_is_seen() {
+        let (temp, db) = setup_test_vault();
+        let indexer = Indexer::new(temp.path().to_path_buf()).unwrap();
+        // Times in whole seconds, as on a filesystem that keeps no more
+        let path = temp.path().join("note1.md");
+        let second = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_709_251_200);
+        let set_time = |time| {
+            fs::File::options().write(true).open(&path).unwrap().set_modified(time).unwrap()
+        };
+        set_time(second);
+        indexer.full_index(&db).unwrap();
+
+        // Rewritten within the same second, as by a sync right after indexing
+        fs::write(&path, "# Note One\n\nRewritten.").unwrap();
+        set_time(second);
+        indexer.full_index(&db).unwrap();
+
+        let hashes = list_note_hashes(&db.conn()).unwrap();
+        assert_eq!(hashes["note1.md"].0, hash_content("# Note One\n\nRewritten."));
+    }
+
+    #[test]
+    fn test_index_records_words_written() {
+        let (temp, db) = setup_test_vault();
+        let indexer = Indexer::new(temp.path().to_path_buf()).unwrap();
+        let path = temp.path().join("draft.md");
+
+        fs::write(&path, "one two").unwrap();
+        indexer.index_file(&db, &path).unwrap();
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let before = db::writing::words_written_by_day(&db.conn(), &today).unwrap();
+        let before = before.first().map(|(_, words)| *words).unwrap_or(0);
+
+        fs::write(&path, "one two three four five").unwrap();
+        indexer.index_file(&db, &path).unwrap();
+        let after = db::writing::words_written_by_day(&db.conn(), &today).unwrap();
+        assert_eq!(after[0].1 - before, 3);
+    }
+
     #[test]
     fn test_full_index() {
         let (temp, db) = setup_test_vault();
@@ -275,6 +889,46 @@ mod tests {
         assert_eq!(notes.len(), 3);
     }
 
+    #[test]
+    fn test_full_index_skips_unchanged_notes() {
+        let (temp, db) = setup_test_vault();
+        let indexer = Indexer::new(temp.path().to_path_buf()).unwrap();
+        indexer.full_index(&db).unwrap();
+
+        // Stand-in for work a re-index would redo
+        let title = |path: &str| {
+            db::notes::get_note_by_path(&db.conn(), path).unwrap().map(|n| n.title)
+        };
+        db.conn().execute("UPDATE notes SET title = 'Stale'", []).unwrap();
+
+        let set_mtime = |path: &Path, secs: u64| {
+            let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+            fs::File::options().write(true).open(path).unwrap().set_modified(time).unwrap();
+        };
+        // Touched without changes, changed, added and deleted
+        set_mtime(&temp.path().join("note1.md"), 1_700_000_000);
+        fs::write(temp.path().join("subdir/nested.md"), "# Nested Again\n").unwrap();
+        set_mtime(&temp.path().join("subdir/nested.md"), 1_700_000_000);
+        fs::write(temp.path().join("note3.md"), "# Note Three\n").unwrap();
+        fs::write(temp.path().join("note2.md"), "# Note Two\n\n[[note3]]").unwrap();
+        set_mtime(&temp.path().join("note2.md"), 1_700_000_000);
+        let note2_links = |db: &Database| {
+            let conn = db.conn();
+            let id = db::notes::get_note_by_path(&conn, "note2.md").unwrap().unwrap().id;
+            db::links::get_outlinks(&conn, id).unwrap()
+        };
+
+        assert_eq!(indexer.full_index(&db).unwrap(), 4);
+        assert_eq!(title("note1.md").as_deref(), Some("Stale"));
+        assert_eq!(title("subdir/nested.md").as_deref(), Some("Nested Again"));
+        assert_eq!(title("note3.md").as_deref(), Some("Note Three"));
+        assert!(note2_links(&db)[0].target_id.is_some());
+
+        fs::remove_file(temp.path().join("note3.md")).unwrap();
+        indexer.full_index(&db).unwrap();
+        assert_eq!(title("note3.md"), None);
+    }
+
     #[test]
     fn test_index_extracts_links() {
         let (temp, db) = setup_test_vault();
@@ -292,6 +946,189 @@ mod tests {
 
         assert_eq!(links.len(), 1);
         assert_eq!(links[0].target_path, "note1");
+
+        let backlinks = db::links::get_backlinks(&conn, "note1.md").unwrap();
+        assert_eq!(backlinks[0].context.as_deref(), Some("Links to [[note1]]."));
+    }
+
+    #[test]
+    fn test_index_records_zettel_ids() {
+        let (temp, db) = setup_test_vault();
+        fs::write(temp.path().join("202501271430 Atoms.md"), "# Atoms").unwrap();
+        fs::write(temp.path().join("molecules.md"), "---\nid: 202501281000\n---\n# Molecules").unwrap();
+
+        Indexer::new(temp.path().to_path_buf()).unwrap().full_index(&db).unwrap();
+
+        let conn = db.conn();
+        let id = |path| db::zettel::get_zettel_id(&conn, path).unwrap();
+        assert_eq!(id("202501271430 Atoms.md").as_deref(), Some("202501271430"));
+        assert_eq!(id("molecules.md").as_deref(), Some("202501281000"));
+        assert_eq!(id("note1.md"), None);
+    }
+
+    #[test]
+    fn test_note_uids_survive_outside_moves_and_rebuilds() {
+        let (temp, db) = setup_test_vault();
+        let root = temp.path();
+        let pinned = "---\nuid: 7d2f1c4e-3a5b-4e6f-9c8d-0a1b2c3d4e5f\n---\n# Pinned";
+        fs::write(root.join("pinned.md"), pinned).unwrap();
+        fs::write(root.join("copy.md"), pinned).unwrap();
+        let indexer = Indexer::new(root.to_path_buf()).unwrap();
+        indexer.full_index(&db).unwrap();
+
+        let uid = |db: &Database, path: &str| db::note_ids::get_note_uid(&db.conn(), path).unwrap();
+        let note1 = uid(&db, "note1.md").unwrap();
+        let pinned_uids = [uid(&db, "pinned.md").unwrap(), uid(&db, "copy.md").unwrap()];
+        assert!(pinned_uids.contains(&"7d2f1c4e-3a5b-4e6f-9c8d-0a1b2c3d4e5f".to_string()));
+        assert_ne!(pinned_uids[0], pinned_uids[1]);
+
+        // Moved while the vault was closed
+        fs::rename(root.join("note1.md"), root.join("subdir/moved.md")).unwrap();
+        indexer.full_index(&db).unwrap();
+        assert_eq!(uid(&db, "subdir/moved.md").as_deref(), Some(note1.as_str()));
+        assert_eq!(uid(&db, "note1.md"), None);
+
+        // A rebuilt index hands out the same IDs
+        let rebuilt = Database::open_memory().unwrap();
+        indexer.full_index(&rebuilt).unwrap();
+        assert_eq!(uid(&rebuilt, "subdir/moved.md").as_deref(), Some(note1.as_str()));
+        assert_eq!(uid(&rebuilt, "copy.md"), uid(&db, "copy.md"));
+    }
+
+    #[test]
+    fn test_parallel_full_index_matches_serial() {
+        let (temp, _) = setup_test_vault();
+        for i in 0..PARALLEL_MIN_FILES * 2 {
+            let content =
+                format!("---\ntags: [n{}]\n---\n# Note {}\n\n- [ ] task [[note{}]]", i, i, i + 1);
+            fs::write(temp.path().join(format!("note{}.md", i + 3)), content).unwrap();
+        }
+
+        let index = |threads| {
+            let db = Database::open_memory().unwrap();
+            let indexer = Indexer::new(temp.path().to_path_buf()).unwrap().with_threads(threads);
+            let count = indexer.full_index(&db).unwrap();
+            let conn = db.conn();
+            let notes: Vec<(String, String, i32)> = db::notes::list_notes(&conn)
+                .unwrap()
+                .into_iter()
+                .map(|n| (n.path, n.title, n.word_count))
+                .collect();
+            let count_rows = |table: &str| -> usize {
+                let sql = format!("SELECT COUNT(*) FROM {}", table);
+                conn.query_row(&sql, [], |row| row.get(0)).unwrap()
+            };
+            let (links, tasks) = (count_rows("links"), count_rows("tasks"));
+            let uids = db::note_ids::list_note_uids(&conn).unwrap();
+            (count, notes, links, tasks, uids)
+        };
+
+        let serial = index(1);
+        assert_eq!(serial.0, PARALLEL_MIN_FILES * 2 + 3);
+        assert_eq!(index(4), serial);
+    }
+
+    #[test]
+    fn test_index_markdown_links() {
+        let (temp, db) = setup_test_vault();
+        fs::write(
+            temp.path().join("subdir/guide.md"),
+            "# Guide\n\nSee [Note One](../note1.md), [nested](nested.md#deep) and \
+             [the site](https://example.com).\n",
+        )
+        .unwrap();
+
+        let indexer = Indexer::new(temp.path().to_path_buf()).unwrap();
+        indexer.full_index(&db).unwrap();
+
+        let conn = db.conn();
+        for target in ["note1.md", "subdir/nested.md"] {
+            let backlinks = db::links::get_backlinks(&conn, target).unwrap();
+            let from_guide: Vec<_> =
+                backlinks.iter().filter(|b| b.source_path == "subdir/guide.md").collect();
+            assert_eq!(from_guide.len(), 1, "{}", target);
+            assert_eq!(from_guide[0].kind, LinkKind::Markdown);
+        }
+        let guide = db::notes::get_note_by_path(&conn, "subdir/guide.md").unwrap().unwrap();
+        let outlinks = db::links::get_outlinks(&conn, guide.id).unwrap();
+        assert_eq!(outlinks.len(), 2);
+        assert!(outlinks.iter().all(|l| l.target_id.is_some()));
+    }
+
+    #[test]
+    fn test_failing_note_leaves_rest_of_batch() {
+        let (temp, db) = setup_test_vault();
+        fs::write(temp.path().join("bad.md"), "# Bad\n\nLinks to [[explode]].").unwrap();
+        // Fail the bad note's link insert, after its note row is written
+        db.conn()
+            .execute_batch(
+                "CREATE TEMP TRIGGER explode BEFORE INSERT ON links \
+                 WHEN NEW.target_path = 'explode' BEGIN SELECT RAISE(ABORT, 'boom'); END",
+            )
+            .unwrap();
+
+        let count = Indexer::new(temp.path().to_path_buf()).unwrap().full_index(&db).unwrap();
+        assert_eq!(count, 3);
+        let conn = db.conn();
+        assert!(get_note_by_path(&conn, "bad.md").unwrap().is_none());
+        assert!(get_note_by_path(&conn, "note2.md").unwrap().is_some());
+        let fts: i64 = conn
+            .query_row("SELECT COUNT(*) FROM notes_fts WHERE title = 'Bad'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(fts, 0);
+    }
+
+    #[test]
+    fn test_markdown_links_to_attachments_are_not_note_links() {
+        let (temp, db) = setup_test_vault();
+        fs::create_dir(temp.path().join("alpha.assets")).unwrap();
+        fs::write(temp.path().join("alpha.assets/spec.pdf"), b"%PDF").unwrap();
+        fs::write(temp.path().join("alpha.assets/described.pdf"), b"%PDF").unwrap();
+        fs::write(temp.path().join("alpha.assets/described.pdf.meta.yaml"), "note: About it\n")
+            .unwrap();
+        fs::write(
+            temp.path().join("alpha.md"),
+            "# Alpha\n\n[spec.pdf](alpha.assets/spec.pdf), \
+             [described](alpha.assets/described.pdf) and [missing](Missing)\n",
+        )
+        .unwrap();
+
+        Indexer::new(temp.path().to_path_buf()).unwrap().full_index(&db).unwrap();
+
+        let conn = db.conn();
+        let alpha = db::notes::get_note_by_path(&conn, "alpha.md").unwrap().unwrap();
+        let mut targets: Vec<String> = db::links::get_outlinks(&conn, alpha.id)
+            .unwrap()
+            .into_iter()
+            .map(|l| l.target_path)
+            .collect();
+        targets.sort();
+        assert_eq!(targets, vec!["Missing", "alpha.assets/described.pdf"]);
+        let unresolved = db::links::get_unresolved_links(&conn).unwrap();
+        assert!(unresolved.iter().all(|link| !link.target.ends_with(".pdf")));
+    }
+
+    #[test]
+    fn test_index_links_citations_to_literature_notes() {
+        let (temp, db) = setup_test_vault();
+        let indexer = Indexer {
+            vault_path: temp.path().to_path_buf(),
+            literature_folder: "literature".to_string(),
+            note_types: BTreeMap::new(),
+            threads: 1,
+        };
+        let path = temp.path().join("essay.md");
+        fs::write(&path, "# Essay\n\nSee [[@smith2020]] and [@doe2019, p. 2].").unwrap();
+        indexer.index_file(&db, &path).unwrap();
+
+        let conn = db.conn();
+        let note = db::notes::get_note_by_path(&conn, "essay.md").unwrap().unwrap();
+        let targets: Vec<String> = db::links::get_outlinks(&conn, note.id)
+            .unwrap()
+            .into_iter()
+            .map(|l| l.target_path)
+            .collect();
+        assert_eq!(targets, vec!["literature/smith2020", "literature/doe2019"]);
     }
 
     #[test]
@@ -312,6 +1149,122 @@ mod tests {
         assert_eq!(tags, vec!["test"]);
     }
 
+    #[test]
+    fn test_index_inline_tags() {
+        let (temp, db) = setup_test_vault();
+        fs::write(
+            temp.path().join("ideas.md"),
+            "---\ntags: [Idea]\n---\n# Ideas\n\nMore #idea and #project/alpha.\n",
+        )
+        .unwrap();
+        let indexer = Indexer::new(temp.path().to_path_buf()).unwrap();
+        indexer.full_index(&db).unwrap();
+
+        let conn = db.conn();
+        let ideas = db::notes::get_note_by_path(&conn, "ideas.md").unwrap().unwrap();
+        let tags = db::tags::get_note_tags(&conn, ideas.id).unwrap();
+        assert_eq!(tags, vec!["Idea", "project/alpha"]);
+        let occurrences = db::tags::get_tag_occurrences(&conn, "project/alpha").unwrap();
+        assert_eq!(occurrences[0].path, "ideas.md");
+        assert_eq!(occurrences[0].line_number, 6);
+    }
+
+    #[test]
+    fn test_index_canvas() {
+        let (temp, db) = setup_test_vault();
+        fs::write(
+            temp.path().join("board.canvas"),
+            r#"{"nodes": [
+                {"id": "a", "type": "file", "file": "subdir/nested.md", "x": 0, "y": 0, "width": 1, "height": 1},
+                {"id": "b", "type": "text", "text": "Compare with [[note1]]", "x": 0, "y": 0, "width": 1, "height": 1}
+            ]}"#,
+        )
+        .unwrap();
+        fs::write(temp.path().join("broken.canvas"), "{ not json").unwrap();
+
+        let indexer = Indexer::new(temp.path().to_path_buf()).unwrap();
+        assert_eq!(indexer.full_index(&db).unwrap(), 4);
+
+        let conn = db.conn();
+        let canvas = db::notes::get_note_by_path(&conn, "board.canvas").unwrap().unwrap();
+        assert_eq!(canvas.title, "board");
+        for target in ["subdir/nested.md", "note1.md"] {
+            let backlinks = db::links::get_backlinks(&conn, target).unwrap();
+            assert!(backlinks.iter().any(|b| b.source_path == "board.canvas"), "{}", target);
+        }
+        let results = db::search::search_notes(&conn, "compare", 10).unwrap();
+        assert_eq!(results[0].path, "board.canvas");
+    }
+
+    #[test]
+    fn test_index_files_described_by_sidecars() {
+        let (temp, db) = setup_test_vault();
+        let pdf = temp.path().join("report.pdf");
+        fs::write(&pdf, b"%PDF-1.7").unwrap();
+        fs::write(temp.path().join("photo.png"), b"PNG").unwrap();
+        fs::write(
+            sidecar_path(&pdf),
+            "title: Quarterly report\ntags: [finance]\nnote: Figures behind [[note1]].\n",
+        )
+        .unwrap();
+
+        let indexer = Indexer::new(temp.path().to_path_buf()).unwrap();
+        assert_eq!(indexer.full_index(&db).unwrap(), 4);
+        {
+            let conn = db.conn();
+            let report = db::notes::get_note_by_path(&conn, "report.pdf").unwrap().unwrap();
+            assert_eq!(report.title, "Quarterly report");
+            assert_eq!(db::tags::get_note_tags(&conn, report.id).unwrap(), vec!["finance"]);
+            let backlinks = db::links::get_backlinks(&conn, "note1.md").unwrap();
+            assert!(backlinks.iter().any(|b| b.source_path == "report.pdf"));
+            let results = db::search::search_notes(&conn, "figures", 10).unwrap();
+            assert_eq!(results[0].path, "report.pdf");
+            assert!(db::notes::get_note_by_path(&conn, "photo.png").unwrap().is_none());
+        }
+
+        // Editing the sidecar re-indexes the file; deleting it drops the file
+        fs::write(sidecar_path(&pdf), "title: Annual report\n").unwrap();
+        indexer.index_file(&db, &sidecar_path(&pdf)).unwrap();
+        let report = db::notes::get_note_by_path(&db.conn(), "report.pdf").unwrap().unwrap();
+        assert_eq!(report.title, "Annual report");
+
+        fs::remove_file(sidecar_path(&pdf)).unwrap();
+        indexer.remove_file(&db, &sidecar_path(&pdf)).unwrap();
+        assert!(db::notes::get_note_by_path(&db.conn(), "report.pdf").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_index_drawings() {
+        let (temp, db) = setup_test_vault();
+        fs::write(
+            temp.path().join("flow.excalidraw"),
+            r#"{"type": "excalidraw", "elements": [{"type": "text", "text": "Retry queue", "link": "[[note2]]"}]}"#,
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("Sketch.excalidraw.md"),
+            "---\ntags: [diagram]\n---\n# Text Elements\nSee [[note1]] ^abc\n\n# Drawing\n```compressed-json\nN4Ig\n```\n",
+        )
+        .unwrap();
+
+        let indexer = Indexer::new(temp.path().to_path_buf()).unwrap();
+        indexer.full_index(&db).unwrap();
+
+        let conn = db.conn();
+        let sketch = db::notes::get_note_by_path(&conn, "Sketch.excalidraw.md").unwrap().unwrap();
+        assert_eq!(sketch.title, "Sketch");
+        assert_eq!(db::tags::get_note_tags(&conn, sketch.id).unwrap(), vec!["diagram"]);
+
+        let backlinks = db::links::get_backlinks(&conn, "note1.md").unwrap();
+        assert_eq!(backlinks[0].source_path, "Sketch.excalidraw.md");
+        assert_eq!(backlinks[0].line_number, Some(5));
+        let backlinks = db::links::get_backlinks(&conn, "note2.md").unwrap();
+        assert_eq!(backlinks[0].source_path, "flow.excalidraw");
+
+        assert_eq!(db::search::search_notes(&conn, "retry", 10).unwrap()[0].path, "flow.excalidraw");
+        assert!(db::search::search_notes(&conn, "N4Ig", 10).unwrap().is_empty());
+    }
+
     #[test]
     fn test_remove_file() {
         let (temp, db) = setup_test_vault();
@@ -0,0 +1,56 @@
+//! One writer per vault. The first app instance to open a vault holds a
+//! lock on `.chronicle/instance.lock`; others open it read-only, so two
+//! windows don't both index into the same database or overwrite each
+//! other's saves. The operating system drops the lock when its holder
+//! exits, even if it crashes.
+
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Held for as long as this instance may write to the vault
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+fn lock_file(vault_path: &Path) -> PathBuf {
+    vault_path.join(".chronicle").join("instance.lock")
+}
+
+impl InstanceLock {
+    /// Take the vault's lock, or `None` if another instance holds it
+    pub fn acquire(vault_path: &Path) -> io::Result<Option<Self>> {
+        let path = lock_file(vault_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).truncate(false).write(true).open(path)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Ok(None),
+            Err(TryLockError::Error(e)) => return Err(e),
+        }
+
+        // Who holds it, for anyone looking at the file
+        file.set_len(0)?;
+        writeln!(file, "{}", std::process::id())?;
+        Ok(Some(Self { _file: file }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_instance_at_a_time() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let first = InstanceLock::acquire(temp.path()).unwrap();
+        assert!(first.is_some());
+        assert!(InstanceLock::acquire(temp.path()).unwrap().is_none());
+
+        drop(first);
+        assert!(InstanceLock::acquire(temp.path()).unwrap().is_some());
+    }
+}
@@ -0,0 +1,239 @@
+//! Undo journal for destructive note operations
+//!
+//! Deletes keep a snapshot of the note so it can be restored without git.
+//! Moves are renames across folders and are journaled the same way. Merges
+//! keep both the merged note and what the note it went into held before.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Oldest entries (and their snapshots) are dropped past this
+const MAX_ENTRIES: usize = 50;
+
+/// An operation that can be reverted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UndoOperation {
+    /// Note deleted; its content lives in the entry's snapshot
+    Delete { path: String },
+    /// Note renamed or moved from `from` to `to`
+    Rename { from: String, to: String },
+    /// Note at `path` merged into `into` and deleted; its content lives in
+    /// the entry's snapshot, and `into`'s prior content in a second one
+    Merge { path: String, into: String },
+}
+
+/// A journaled operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UndoEntry {
+    pub id: String,
+    pub operation: UndoOperation,
+    /// RFC 3339 timestamp of the operation
+    pub recorded_at: String,
+}
+
+/// Undo journal stored under `.chronicle/undo/` in the vault
+pub struct UndoJournal {
+    dir: PathBuf,
+}
+
+impl UndoJournal {
+    pub fn new(vault_path: &Path) -> Self {
+        Self {
+            dir: vault_path.join(".chronicle").join("undo"),
+        }
+    }
+
+    fn journal_file(&self) -> PathBuf {
+        self.dir.join("journal.json")
+    }
+
+    fn snapshot_file(&self, id: &str) -> PathBuf {
+        self.dir.join("snapshots").join(format!("{}.md", id))
+    }
+
+    /// For a merge, the note merged into as it was before
+    fn target_snapshot_file(&self, id: &str) -> PathBuf {
+        self.dir.join("snapshots").join(format!("{}.target.md", id))
+    }
+
+    fn remove_snapshots(&self, id: &str) {
+        let _ = fs::remove_file(self.snapshot_file(id));
+        let _ = fs::remove_file(self.target_snapshot_file(id));
+    }
+
+    /// Journaled operations, oldest first
+    pub fn entries(&self) -> io::Result<Vec<UndoEntry>> {
+        let file = self.journal_file();
+        if !file.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_slice(&fs::read(file)?)?)
+    }
+
+    fn write_entries(&self, entries: &[UndoEntry]) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let file = self.journal_file();
+        let tmp = file.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(entries)?)?;
+        fs::rename(&tmp, &file)
+    }
+
+    /// Record a delete, snapshotting the note's content
+    pub fn record_delete(&self, path: &str, content: &str) -> io::Result<UndoEntry> {
+        let entry = new_entry(UndoOperation::Delete {
+            path: path.to_string(),
+        });
+        let snapshot = self.snapshot_file(&entry.id);
+        if let Some(parent) = snapshot.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(snapshot, content)?;
+        self.push(entry)
+    }
+
+    /// Record a merge of the note at `path` into `into`, snapshotting the
+    /// note's content and `into`'s content before the merge
+    pub fn record_merge(
+        &self,
+        path: &str,
+        content: &str,
+        into: &str,
+        target_content: &str,
+    ) -> io::Result<UndoEntry> {
+        let entry = new_entry(UndoOperation::Merge {
+            path: path.to_string(),
+            into: into.to_string(),
+        });
+        let snapshot = self.snapshot_file(&entry.id);
+        if let Some(parent) = snapshot.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(snapshot, content)?;
+        fs::write(self.target_snapshot_file(&entry.id), target_content)?;
+        self.push(entry)
+    }
+
+    /// Record a rename or move
+    pub fn record_rename(&self, from: &str, to: &str) -> io::Result<UndoEntry> {
+        self.push(new_entry(UndoOperation::Rename {
+            from: from.to_string(),
+            to: to.to_string(),
+        }))
+    }
+
+    fn push(&self, entry: UndoEntry) -> io::Result<UndoEntry> {
+        let mut entries = self.entries()?;
+        entries.push(entry.clone());
+        if entries.len() > MAX_ENTRIES {
+            let dropped: Vec<UndoEntry> = entries.drain(..entries.len() - MAX_ENTRIES).collect();
+            for old in dropped {
+                self.remove_snapshots(&old.id);
+            }
+        }
+        self.write_entries(&entries)?;
+        Ok(entry)
+    }
+
+    /// Most recent entry, without removing it
+    pub fn last(&self) -> io::Result<Option<UndoEntry>> {
+        Ok(self.entries()?.pop())
+    }
+
+    /// Content saved for a delete or merge entry
+    pub fn snapshot(&self, entry: &UndoEntry) -> io::Result<String> {
+        fs::read_to_string(self.snapshot_file(&entry.id))
+    }
+
+    /// Content a merge entry's target had before the merge
+    pub fn target_snapshot(&self, entry: &UndoEntry) -> io::Result<String> {
+        fs::read_to_string(self.target_snapshot_file(&entry.id))
+    }
+
+    /// Drop the most recent entry once it has been undone
+    pub fn pop(&self) -> io::Result<Option<UndoEntry>> {
+        let mut entries = self.entries()?;
+        let entry = entries.pop();
+        if let Some(entry) = &entry {
+            self.write_entries(&entries)?;
+            self.remove_snapshots(&entry.id);
+        }
+        Ok(entry)
+    }
+
+    /// Drop the entry `id` once it has been undone, with any recorded after
+    /// it while undoing it
+    pub fn pop_through(&self, id: &str) -> io::Result<Option<UndoEntry>> {
+        let mut entries = self.entries()?;
+        let Some(at) = entries.iter().rposition(|entry| entry.id == id) else {
+            return Ok(None);
+        };
+        let dropped: Vec<UndoEntry> = entries.drain(at..).collect();
+        self.write_entries(&entries)?;
+        for entry in &dropped {
+            self.remove_snapshots(&entry.id);
+        }
+        Ok(dropped.into_iter().next())
+    }
+}
+
+fn new_entry(operation: UndoOperation) -> UndoEntry {
+    let now = Local::now();
+    UndoEntry {
+        id: now.format("%Y%m%d%H%M%S%f").to_string(),
+        operation,
+        recorded_at: now.to_rfc3339(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_and_pop() {
+        let temp = TempDir::new().unwrap();
+        let journal = UndoJournal::new(temp.path());
+        assert_eq!(journal.last().unwrap(), None);
+
+        journal.record_rename("a.md", "folder/a.md").unwrap();
+        let deleted = journal.record_delete("b.md", "# B").unwrap();
+
+        let last = journal.last().unwrap().unwrap();
+        assert_eq!(last, deleted);
+        assert_eq!(journal.snapshot(&last).unwrap(), "# B");
+
+        journal.pop().unwrap();
+        assert!(journal.snapshot(&last).is_err());
+        assert_eq!(
+            journal.last().unwrap().unwrap().operation,
+            UndoOperation::Rename {
+                from: "a.md".to_string(),
+                to: "folder/a.md".to_string()
+            }
+        );
+
+        journal.pop().unwrap();
+        assert_eq!(journal.pop().unwrap(), None);
+    }
+
+    #[test]
+    fn test_journal_is_capped() {
+        let temp = TempDir::new().unwrap();
+        let journal = UndoJournal::new(temp.path());
+        let first = journal.record_delete("first.md", "old").unwrap();
+
+        for i in 0..MAX_ENTRIES {
+            journal.record_rename(&format!("{}.md", i), "x.md").unwrap();
+        }
+
+        let entries = journal.entries().unwrap();
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert!(entries.iter().all(|e| e.id != first.id));
+        assert!(journal.snapshot(&first).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! Maps of content: index notes listing every note with a tag or in a
+//! folder. The list lives between marker comments so it can be refreshed
+//! without touching anything written around it.
+
+use std::collections::BTreeMap;
+
+use super::autolink::wiki_link;
+
+/// Start of the generated list. `%%` comments are hidden when reading in
+/// Obsidian and dropped from shared notes.
+pub const MOC_START: &str = "%% moc:start %%";
+pub const MOC_END: &str = "%% moc:end %%";
+
+/// A note to list in a map of content
+#[derive(Debug, Clone, PartialEq)]
+pub struct MocEntry {
+    pub path: String,
+    pub title: String,
+    /// Subtag or subfolder it is listed under; `None` for the top of the list
+    pub group: Option<String>,
+}
+
+/// The marked block listing `entries`: ungrouped notes first, then a
+/// heading per group, each sorted by title
+pub fn render_moc(entries: &[MocEntry]) -> String {
+    let mut groups: BTreeMap<Option<&str>, Vec<&MocEntry>> = BTreeMap::new();
+    for entry in entries {
+        groups.entry(entry.group.as_deref()).or_default().push(entry);
+    }
+
+    let mut sections = Vec::new();
+    for (group, mut notes) in groups {
+        notes.sort_by_key(|n| (n.title.to_lowercase(), n.path.clone()));
+        let list: Vec<String> = notes
+            .iter()
+            .map(|n| {
+                let text = if n.title.contains(['[', ']', '|']) {
+                    n.path.strip_suffix(".md").unwrap_or(&n.path)
+                } else {
+                    &n.title
+                };
+                format!("- {}", wiki_link(&n.path, text))
+            })
+            .collect();
+        sections.push(match group {
+            Some(group) => format!("## {}\n\n{}", group, list.join("\n")),
+            None => list.join("\n"),
+        });
+    }
+
+    format!("{}\n{}\n{}", MOC_START, sections.join("\n\n"), MOC_END)
+}
+
+/// Swap the marked block in `content` for `block`, or append it if the
+/// note has none yet
+pub fn update_moc(content: &str, block: &str) -> String {
+    if let Some(start) = content.find(MOC_START) {
+        if let Some(end) = content[start..].find(MOC_END).map(|i| start + i + MOC_END.len()) {
+            return format!("{}{}{}", &content[..start], block, &content[end..]);
+        }
+    }
+    let content = content.trim_end();
+    if content.is_empty() {
+        format!("{}\n", block)
+    } else {
+        format!("{}\n\n{}\n", content, block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, title: &str, group: Option<&str>) -> MocEntry {
+        MocEntry {
+            path: path.to_string(),
+            title: title.to_string(),
+            group: group.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_render_moc() {
+        let block = render_moc(&[
+            entry("projects/beta/plan.md", "Plan", Some("beta")),
+            entry("projects/overview.md", "Overview", None),
+            entry("projects/alpha.md", "alpha", None),
+            entry("projects/beta/odd.md", "A [draft]", Some("beta")),
+        ]);
+        assert_eq!(
+            block,
+            "%% moc:start %%\n\
+             - [[projects/alpha|alpha]]\n\
+             - [[projects/overview|Overview]]\n\n\
+             ## beta\n\n\
+             - [[projects/beta/odd]]\n\
+             - [[projects/beta/plan|Plan]]\n\
+             %% moc:end %%"
+        );
+    }
+
+    #[test]
+    fn test_update_moc_keeps_manual_edits() {
+        let block = render_moc(&[entry("a.md", "a", None)]);
+        let note = update_moc("# Projects\n\nIntro\n", &block);
+        assert_eq!(note, format!("# Projects\n\nIntro\n\n{}\n", block));
+
+        let edited = note.replace("Intro", "Intro, edited") + "\nOutro\n";
+        let refreshed = update_moc(&edited, &render_moc(&[entry("b.md", "b", None)]));
+        assert_eq!(
+            refreshed,
+            "# Projects\n\nIntro, edited\n\n%% moc:start %%\n- [[b]]\n%% moc:end %%\n\nOutro\n"
+        );
+    }
+}
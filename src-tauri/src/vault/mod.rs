@@ -2,10 +2,100 @@
 //!
 //! Handles vault operations: parsing notes, indexing, file watching.
 
+mod attachments;
+mod autolink;
+mod backup;
+mod batch_write;
+mod broken_links;
+mod canvas;
+mod centrality;
+mod cloud;
+mod drafts;
+mod email;
+mod embeds;
+mod excalidraw;
+mod format;
+mod graph_history;
+mod graph_overview;
+mod health;
+mod index_queue;
 mod indexer;
+mod instance_lock;
+mod journal;
+mod moc;
+mod note_ids;
+mod note_types;
+mod obsidian;
+mod ocr;
 mod parser;
+mod paths;
+mod pdf_text;
+mod queries;
+mod quick_switch;
+mod properties;
+mod reminders;
+mod roam;
+mod saved_searches;
+mod scaffold;
+#[cfg(target_os = "android")]
+pub mod saf;
+mod sidecar;
+mod smart_folders;
+mod snapshots;
+mod storage;
+mod tag_meta;
+mod tasks;
+mod template;
+mod tiddlywiki;
+mod views;
 mod watcher;
+mod workspaces;
+mod zettel;
 
+pub use attachments::*;
+pub use autolink::*;
+pub use backup::*;
+pub use batch_write::*;
+pub use broken_links::*;
+pub use canvas::*;
+pub use centrality::*;
+pub use cloud::*;
+pub use drafts::*;
+pub use email::*;
+pub use embeds::*;
+pub use excalidraw::*;
+pub use format::*;
+pub use graph_history::*;
+pub use graph_overview::*;
+pub use health::*;
+pub use index_queue::*;
 pub use indexer::*;
+pub use instance_lock::*;
+pub use journal::*;
+pub use moc::*;
+pub use note_ids::*;
+pub use note_types::*;
+pub use obsidian::*;
+pub use ocr::*;
 pub use parser::*;
+pub use paths::*;
+pub use pdf_text::*;
+pub use queries::*;
+pub use quick_switch::*;
+pub use properties::*;
+pub use reminders::*;
+pub use roam::*;
+pub use saved_searches::*;
+pub use scaffold::*;
+pub use sidecar::*;
+pub use smart_folders::*;
+pub use snapshots::*;
+pub use storage::*;
+pub use tag_meta::*;
+pub use tasks::*;
+pub use template::*;
+pub use tiddlywiki::*;
+pub use views::*;
 pub use watcher::*;
+pub use workspaces::*;
+pub use zettel::*;
@@ -0,0 +1,99 @@
+//! Stable note IDs
+//!
+//! Every note gets a UUID that follows it through renames and moves, so
+//! links and graph nodes keep their identity. A note's ID is its `uid`
+//! frontmatter property if it has one; otherwise it is assigned when the
+//! note is first indexed and mirrored to `.chronicle/note_ids.json`, so a
+//! rebuilt index hands out the same IDs again.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::vault::get_frontmatter_property;
+use crate::vault::zettel::random_bits;
+
+/// Frontmatter property that pins a note's ID
+pub const UID_PROPERTY: &str = "uid";
+
+/// A new random (version 4) UUID
+pub fn generate_uid() -> String {
+    let bits = random_bits();
+    // Set the version (4) and variant (10xx) bits
+    let bits = (bits & !(0xF << 76) | (0x4 << 76)) & !(0x3 << 62) | (0x2 << 62);
+    let hex = format!("{:032x}", bits);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// ID pinned in a note's frontmatter
+pub fn frontmatter_uid(content: &str) -> Option<String> {
+    get_frontmatter_property(content, UID_PROPERTY)
+        .map(|uid| uid.trim().to_string())
+        .filter(|uid| !uid.is_empty())
+}
+
+#[derive(Serialize, Deserialize)]
+struct NoteIdsFile {
+    /// Note ID by vault-relative path
+    note_ids: BTreeMap<String, String>,
+}
+
+fn note_ids_file(vault_path: &Path) -> PathBuf {
+    vault_path.join(".chronicle").join("note_ids.json")
+}
+
+/// Note IDs saved in the vault by path, empty if it has no IDs file
+pub fn read_note_ids(vault_path: &Path) -> io::Result<BTreeMap<String, String>> {
+    let file = note_ids_file(vault_path);
+    if !file.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let parsed: NoteIdsFile = serde_json::from_slice(&fs::read(file)?)?;
+    Ok(parsed.note_ids)
+}
+
+/// Replace the vault's note IDs file
+pub fn write_note_ids(vault_path: &Path, note_ids: &BTreeMap<String, String>) -> io::Result<()> {
+    let file = note_ids_file(vault_path);
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let content = serde_json::to_vec_pretty(&NoteIdsFile {
+        note_ids: note_ids.clone(),
+    })?;
+    let tmp = file.with_extension("tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, &file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_uid() {
+        let uid = generate_uid();
+        let groups: Vec<usize> = uid.split('-').map(str::len).collect();
+        assert_eq!(groups, vec![8, 4, 4, 4, 12]);
+        assert_eq!(&uid[14..15], "4");
+        assert!(matches!(&uid[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(uid, generate_uid());
+    }
+
+    #[test]
+    fn test_frontmatter_uid() {
+        assert_eq!(frontmatter_uid("---\nuid: abc\n---\n# A").as_deref(), Some("abc"));
+        assert_eq!(frontmatter_uid("---\nuid: ''\n---\n# A"), None);
+        assert_eq!(frontmatter_uid("# A"), None);
+    }
+}
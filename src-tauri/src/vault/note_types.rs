@@ -0,0 +1,148 @@
+//! Note types: a `type` frontmatter property naming a schema from the
+//! config, which lists the properties notes of that type need and the
+//! values they default to
+
+use std::collections::BTreeMap;
+
+use crate::models::NoteTypeSchema;
+
+/// The frontmatter property holding a note's type
+pub const TYPE_PROPERTY: &str = "type";
+
+/// A note's type, from its indexed properties
+pub fn note_type(properties: &[(String, String)]) -> Option<&str> {
+    properties
+        .iter()
+        .find(|(key, value)| key == TYPE_PROPERTY && !value.trim().is_empty())
+        .map(|(_, value)| value.trim())
+}
+
+/// The schema for `type_name`, ignoring case
+pub fn type_schema<'a>(
+    type_name: &str,
+    schemas: &'a BTreeMap<String, NoteTypeSchema>,
+) -> Option<&'a NoteTypeSchema> {
+    schemas
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(type_name))
+        .map(|(_, schema)| schema)
+}
+
+/// Add the type's default values for properties the note doesn't set, so
+/// they can be queried like any other property
+pub fn with_type_defaults(
+    mut properties: Vec<(String, String)>,
+    schemas: &BTreeMap<String, NoteTypeSchema>,
+) -> Vec<(String, String)> {
+    let Some(schema) = note_type(&properties).and_then(|name| type_schema(name, schemas)) else {
+        return properties;
+    };
+    for (key, value) in &schema.defaults {
+        if !properties.iter().any(|(k, _)| k == key) {
+            let values = scalar_values(value);
+            properties.extend(values.into_iter().map(|v| (key.clone(), v)));
+        }
+    }
+    properties
+}
+
+/// Problems with a note against its type's schema: an unknown type, or
+/// required properties that are missing or empty
+pub fn type_warnings(
+    properties: &[(String, String)],
+    schemas: &BTreeMap<String, NoteTypeSchema>,
+) -> Vec<String> {
+    let Some(type_name) = note_type(properties) else {
+        return Vec::new();
+    };
+    let Some(schema) = type_schema(type_name, schemas) else {
+        // Types are optional until any are configured
+        return if schemas.is_empty() {
+            Vec::new()
+        } else {
+            vec![format!("Note type \"{}\" has no schema", type_name)]
+        };
+    };
+
+    schema
+        .required
+        .iter()
+        .filter(|key| {
+            !properties
+                .iter()
+                .any(|(k, value)| k == *key && !value.trim().is_empty())
+        })
+        .map(|key| format!("{} note is missing required property \"{}\"", type_name, key))
+        .collect()
+}
+
+/// Index values for a default, the way frontmatter values are indexed:
+/// one per list item, nothing for maps
+fn scalar_values(value: &serde_json::Value) -> Vec<String> {
+    match value {
+        serde_json::Value::String(s) => vec![s.clone()],
+        serde_json::Value::Number(n) => vec![n.to_string()],
+        serde_json::Value::Bool(b) => vec![b.to_string()],
+        serde_json::Value::Array(items) => items
+            .iter()
+            .filter(|item| !item.is_array())
+            .flat_map(scalar_values)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn props(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn schemas() -> BTreeMap<String, NoteTypeSchema> {
+        let person = NoteTypeSchema {
+            required: vec!["email".to_string(), "company".to_string()],
+            defaults: BTreeMap::from([
+                ("company".to_string(), serde_json::json!("Acme")),
+                ("roles".to_string(), serde_json::json!(["contact", "friend"])),
+            ]),
+        };
+        BTreeMap::from([("person".to_string(), person)])
+    }
+
+    #[test]
+    fn test_type_defaults() {
+        let properties = with_type_defaults(
+            props(&[("type", "Person"), ("company", "Initech")]),
+            &schemas(),
+        );
+        assert_eq!(
+            properties,
+            props(&[
+                ("type", "Person"),
+                ("company", "Initech"),
+                ("roles", "contact"),
+                ("roles", "friend"),
+            ])
+        );
+        let untyped = props(&[("status", "draft")]);
+        assert_eq!(with_type_defaults(untyped.clone(), &schemas()), untyped);
+    }
+
+    #[test]
+    fn test_type_warnings() {
+        let schemas = schemas();
+        let person = with_type_defaults(props(&[("type", "person"), ("email", " ")]), &schemas);
+        assert_eq!(
+            type_warnings(&person, &schemas),
+            vec!["person note is missing required property \"email\""]
+        );
+        let complete = props(&[("type", "person"), ("email", "a@b.c"), ("company", "X")]);
+        assert!(type_warnings(&complete, &schemas).is_empty());
+
+        let book = props(&[("type", "book")]);
+        assert_eq!(type_warnings(&book, &schemas), vec!["Note type \"book\" has no schema"]);
+        assert!(type_warnings(&book, &BTreeMap::new()).is_empty());
+    }
+}
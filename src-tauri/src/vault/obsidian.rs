@@ -0,0 +1,291 @@
+//! Settings shared with Obsidian, read from a vault's `.obsidian` folder so
+//! a vault used with both apps puts attachments, daily notes and templates
+//! in the same places
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::models::{AppConfig, AttachmentLocation, EmbedStyle};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AppSettings {
+    attachment_folder_path: Option<String>,
+    use_markdown_links: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DailyNotesSettings {
+    folder: Option<String>,
+    /// Moment.js date format
+    format: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TemplatesSettings {
+    folder: Option<String>,
+}
+
+/// The Obsidian settings Chronicle follows. Unset fields weren't found.
+#[derive(Debug, Default, PartialEq)]
+pub struct ObsidianSettings {
+    /// `attachmentFolderPath`: `/` for the vault root, `./` for the note's
+    /// folder, `./name` for a subfolder of it, anything else a vault folder
+    pub attachment_folder: Option<String>,
+    pub use_markdown_links: Option<bool>,
+    pub daily_notes_folder: Option<String>,
+    pub daily_notes_format: Option<String>,
+    pub templates_folder: Option<String>,
+}
+
+/// Settings from `.obsidian/`, or `None` if the vault has no such folder.
+/// Missing or unreadable settings files are skipped.
+pub fn read_obsidian_settings(vault_path: &Path) -> io::Result<Option<ObsidianSettings>> {
+    let dir = vault_path.join(".obsidian");
+    if !dir.is_dir() {
+        return Ok(None);
+    }
+
+    let app: AppSettings = read_settings(&dir.join("app.json"))?;
+    let daily: DailyNotesSettings = read_settings(&dir.join("daily-notes.json"))?;
+    let templates: TemplatesSettings = read_settings(&dir.join("templates.json"))?;
+
+    // Obsidian stores cleared settings as empty strings
+    let non_empty = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+    Ok(Some(ObsidianSettings {
+        attachment_folder: app.attachment_folder_path,
+        use_markdown_links: app.use_markdown_links,
+        daily_notes_folder: daily.folder,
+        daily_notes_format: non_empty(daily.format),
+        templates_folder: non_empty(templates.folder),
+    }))
+}
+
+fn read_settings<T: DeserializeOwned + Default>(file: &Path) -> io::Result<T> {
+    if !file.exists() {
+        return Ok(T::default());
+    }
+    match serde_json::from_slice(&fs::read(file)?) {
+        Ok(settings) => Ok(settings),
+        Err(e) => {
+            tracing::warn!(file = ?file, error = %e, "Skipping unreadable Obsidian settings");
+            Ok(T::default())
+        }
+    }
+}
+
+impl ObsidianSettings {
+    /// Update `config` to match, returning the names of the settings that
+    /// changed
+    pub fn apply(&self, config: &mut AppConfig) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+
+        if let Some(path) = &self.attachment_folder {
+            let path = path.trim();
+            let attachments = &mut config.attachments;
+            let location = match path.strip_prefix("./").or((path == ".").then_some("")) {
+                Some(subfolder) => {
+                    if update(&mut attachments.subfolder, subfolder.trim_matches('/')) {
+                        changed.push("attachments.subfolder");
+                    }
+                    AttachmentLocation::NoteSubfolder
+                }
+                None => {
+                    if update(&mut attachments.folder, path.trim_matches('/')) {
+                        changed.push("attachments.folder");
+                    }
+                    AttachmentLocation::VaultFolder
+                }
+            };
+            if attachments.location != location {
+                attachments.location = location;
+                changed.push("attachments.location");
+            }
+        }
+
+        if let Some(markdown) = self.use_markdown_links {
+            let style = if markdown { EmbedStyle::Markdown } else { EmbedStyle::Wiki };
+            if config.attachments.embed_style != style {
+                config.attachments.embed_style = style;
+                changed.push("attachments.embed_style");
+            }
+        }
+
+        let daily_notes = &mut config.daily_notes;
+        if let Some(folder) = &self.daily_notes_folder {
+            if update(&mut daily_notes.folder, folder.trim_matches('/')) {
+                changed.push("daily_notes.folder");
+            }
+        }
+        if let Some(format) = &self.daily_notes_format {
+            match moment_to_strftime(format) {
+                Some(strftime) => {
+                    if update(&mut daily_notes.date_format, &strftime) {
+                        changed.push("daily_notes.date_format");
+                    }
+                }
+                None => tracing::warn!(
+                    format = %format,
+                    "Obsidian daily note format has no strftime equivalent"
+                ),
+            }
+        }
+
+        if let Some(folder) = &self.templates_folder {
+            if update(&mut config.new_notes.templates_folder, folder.trim_matches('/')) {
+                changed.push("new_notes.templates_folder");
+            }
+        }
+
+        changed
+    }
+}
+
+/// Set `field` to `value`, reporting whether it changed
+fn update(field: &mut String, value: &str) -> bool {
+    if field == value {
+        return false;
+    }
+    *field = value.to_string();
+    true
+}
+
+/// Moment.js tokens, longest first so `YYYY` wins over `YY`
+const MOMENT_TOKENS: &[(&str, &str)] = &[
+    ("YYYY", "%Y"),
+    ("YY", "%y"),
+    ("MMMM", "%B"),
+    ("MMM", "%b"),
+    ("MM", "%m"),
+    ("M", "%-m"),
+    ("DDDD", "%j"),
+    ("DD", "%d"),
+    ("D", "%-d"),
+    ("dddd", "%A"),
+    ("ddd", "%a"),
+    ("d", "%w"),
+    ("E", "%u"),
+    ("GGGG", "%G"),
+    ("WW", "%V"),
+    ("W", "%-V"),
+    ("HH", "%H"),
+    ("H", "%-H"),
+    ("hh", "%I"),
+    ("h", "%-I"),
+    ("mm", "%M"),
+    ("m", "%-M"),
+    ("ss", "%S"),
+    ("s", "%-S"),
+    ("A", "%p"),
+    ("a", "%P"),
+];
+
+/// Translate a Moment.js date format, as Obsidian uses, to strftime.
+/// `[escaped]` text is kept literally. Returns `None` for formats using
+/// tokens strftime can't express, such as `Do` (ordinal day).
+pub fn moment_to_strftime(format: &str) -> Option<String> {
+    let mut out = String::new();
+    let mut rest = format;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '[' {
+            let end = rest.find(']')?;
+            out.push_str(&rest[1..end].replace('%', "%%"));
+            rest = &rest[end + 1..];
+            continue;
+        }
+        if let Some((token, strftime)) = MOMENT_TOKENS.iter().find(|(t, _)| rest.starts_with(t)) {
+            // Ordinals (`Do`) and two-letter weekdays (`dd`) have no equivalent
+            let next = &rest[token.len()..];
+            let ordinal = *token == "D" && next.starts_with('o');
+            if ordinal || (*token == "d" && next.starts_with('d')) {
+                return None;
+            }
+            out.push_str(strftime);
+            rest = next;
+            continue;
+        }
+        if c.is_ascii_alphabetic() {
+            return None;
+        }
+        if c == '%' {
+            out.push('%');
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_moment_to_strftime() {
+        assert_eq!(moment_to_strftime("YYYY-MM-DD").as_deref(), Some("%Y-%m-%d"));
+        assert_eq!(
+            moment_to_strftime("dddd, MMMM D YYYY").as_deref(),
+            Some("%A, %B %-d %Y")
+        );
+        assert_eq!(moment_to_strftime("YYYY-[W]WW").as_deref(), Some("%Y-W%V"));
+        assert_eq!(
+            moment_to_strftime("YYYY/MM/YYYY-MM-DD").as_deref(),
+            Some("%Y/%m/%Y-%m-%d")
+        );
+        assert_eq!(moment_to_strftime("MMM Do"), None);
+        assert_eq!(moment_to_strftime("Q-YYYY"), None);
+    }
+
+    #[test]
+    fn test_read_and_apply_settings() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        assert_eq!(read_obsidian_settings(root).unwrap(), None);
+
+        fs::create_dir_all(root.join(".obsidian")).unwrap();
+        fs::write(
+            root.join(".obsidian/app.json"),
+            r#"{"attachmentFolderPath": "./assets", "useMarkdownLinks": false}"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join(".obsidian/daily-notes.json"),
+            r#"{"folder": "Journal/", "format": "DD.MM.YYYY"}"#,
+        )
+        .unwrap();
+        fs::write(root.join(".obsidian/templates.json"), "not json").unwrap();
+
+        let settings = read_obsidian_settings(root).unwrap().unwrap();
+        assert_eq!(settings.templates_folder, None);
+
+        let mut config = AppConfig::default();
+        let changed = settings.apply(&mut config);
+        assert_eq!(config.attachments.location, AttachmentLocation::NoteSubfolder);
+        assert_eq!(config.attachments.subfolder, "assets");
+        assert_eq!(config.attachments.embed_style, EmbedStyle::Wiki);
+        assert_eq!(config.daily_notes.folder, "Journal");
+        assert_eq!(config.daily_notes.date_format, "%d.%m.%Y");
+        assert!(changed.contains(&"daily_notes.date_format"));
+
+        // Already in sync: nothing to change
+        assert!(settings.apply(&mut config).is_empty());
+    }
+
+    #[test]
+    fn test_attachment_folder_at_vault_root() {
+        let settings = ObsidianSettings {
+            attachment_folder: Some("/".to_string()),
+            ..Default::default()
+        };
+        let mut config = AppConfig::default();
+        settings.apply(&mut config);
+        assert_eq!(config.attachments.location, AttachmentLocation::VaultFolder);
+        assert_eq!(config.attachments.folder, "");
+    }
+}
@@ -0,0 +1,151 @@
+//! Text recognition over image attachments, using the Tesseract CLI
+
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::UNIX_EPOCH;
+
+use crate::db::{
+    ocr::{attachment_text_times, delete_attachment_text, set_attachment_text},
+    schema::Database,
+};
+use crate::error::ChronicleError;
+use crate::models::OcrConfig;
+use crate::vault::indexer::walkdir;
+
+/// Raster formats Tesseract reads
+const OCR_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp", "tif", "tiff"];
+
+/// Outcome of an OCR pass over the vault
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OcrReport {
+    /// Images recognized in this pass
+    pub recognized: usize,
+    /// Images unchanged since they were last recognized
+    pub unchanged: usize,
+    /// Images gone from the vault, whose text was dropped
+    pub removed: usize,
+    /// Images Tesseract couldn't read; they are retried on the next pass
+    pub failed: Vec<String>,
+}
+
+/// Whether OCR applies to the file at `path`
+pub fn is_ocr_image(path: &Path) -> bool {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| OCR_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// Recognize the text in an image. `None` means Tesseract ran but couldn't
+/// read this image; failing to run it at all is an error.
+pub fn recognize_text(image: &Path, config: &OcrConfig) -> Result<Option<String>, ChronicleError> {
+    let output = Command::new(&config.command)
+        .arg(image)
+        .arg("stdout")
+        .args(["-l", &config.languages])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ChronicleError::OcrFailed(format!("could not start {}: {}", config.command, e)))?;
+
+    if !output.status.success() {
+        tracing::warn!(
+            path = ?image,
+            stderr = %String::from_utf8_lossy(&output.stderr).trim(),
+            "Text recognition failed"
+        );
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&output.stdout).trim().to_string()))
+}
+
+/// Bring the recognized text in line with the vault's images: recognize new
+/// and modified images with `recognize` and drop text of deleted ones. The
+/// database is only locked between images, since recognition is slow.
+pub fn index_attachment_text(
+    vault_path: &Path,
+    db: &Database,
+    mut recognize: impl FnMut(&Path) -> Result<Option<String>, ChronicleError>,
+) -> Result<OcrReport, ChronicleError> {
+    let mut known = attachment_text_times(&db.conn())?;
+    let mut report = OcrReport::default();
+
+    for path in walkdir(vault_path)? {
+        if !is_ocr_image(&path) {
+            continue;
+        }
+        let relative = path
+            .strip_prefix(vault_path)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let modified = fs::metadata(&path)?
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+
+        if known.remove(&relative) == Some(modified) {
+            report.unchanged += 1;
+            continue;
+        }
+        match recognize(&path)? {
+            Some(text) => {
+                set_attachment_text(&db.conn(), &relative, modified, &text)?;
+                report.recognized += 1;
+            }
+            None => report.failed.push(relative),
+        }
+    }
+
+    // Whatever is left wasn't found on disk
+    let conn = db.conn();
+    for path in known.keys() {
+        delete_attachment_text(&conn, path)?;
+        report.removed += 1;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ocr::search_attachment_text;
+
+    #[test]
+    fn test_index_attachment_text() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let db = Database::open_memory().unwrap();
+        fs::create_dir_all(temp.path().join("attachments")).unwrap();
+        fs::create_dir_all(temp.path().join(".chronicle")).unwrap();
+        fs::write(temp.path().join("attachments/receipt.png"), b"png").unwrap();
+        fs::write(temp.path().join("attachments/blurry.jpg"), b"jpg").unwrap();
+        fs::write(temp.path().join("attachments/diagram.svg"), b"svg").unwrap();
+        fs::write(temp.path().join(".chronicle/cache.png"), b"png").unwrap();
+        fs::write(temp.path().join("note.md"), "# Note").unwrap();
+
+        let calls = std::cell::Cell::new(0);
+        let recognize = |path: &Path| {
+            calls.set(calls.get() + 1);
+            Ok((!path.ends_with("blurry.jpg")).then(|| "Total due: 42 EUR".to_string()))
+        };
+        let report = index_attachment_text(temp.path(), &db, recognize).unwrap();
+        assert_eq!(report.recognized, 1);
+        assert_eq!(report.failed, vec!["attachments/blurry.jpg"]);
+
+        let results = search_attachment_text(&db.conn(), "total", 10).unwrap();
+        assert_eq!(results[0].path, "attachments/receipt.png");
+
+        // Unchanged images aren't recognized again; failed ones are retried
+        fs::remove_file(temp.path().join("attachments/blurry.jpg")).unwrap();
+        let report = index_attachment_text(temp.path(), &db, recognize).unwrap();
+        assert_eq!((report.recognized, report.unchanged), (0, 1));
+        assert_eq!(calls.get(), 2);
+
+        fs::remove_file(temp.path().join("attachments/receipt.png")).unwrap();
+        let report = index_attachment_text(temp.path(), &db, recognize).unwrap();
+        assert_eq!(report.removed, 1);
+        assert!(search_attachment_text(&db.conn(), "total", 10).unwrap().is_empty());
+    }
+}
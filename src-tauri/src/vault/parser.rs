@@ -10,6 +10,8 @@ pub struct ParsedNote {
     pub title: String,
     pub frontmatter: Option<Frontmatter>,
     pub links: Vec<ExtractedLink>,
+    /// Inline `#tags` in the body
+    pub inline_tags: Vec<ExtractedTag>,
     pub word_count: usize,
     pub content: String,
 }
@@ -22,14 +24,62 @@ pub struct Frontmatter {
     pub modified: Option<String>,
     #[serde(default)]
     pub tags: Vec<String>,
+    /// Other names the note can be linked by
+    #[serde(default)]
+    pub aliases: Vec<String>,
 }
 
-/// Extracted wiki-style link
+/// Extracted link to another note
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractedLink {
     pub target: String,
     pub display: Option<String>,
     pub line_number: usize,
+    pub kind: LinkKind,
+}
+
+/// How a link is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    /// `[[Note]]`, naming a note by vault-relative path or ID
+    Wiki,
+    /// `[text](note.md)`, with a path relative to the linking note
+    Markdown,
+}
+
+impl LinkKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkKind::Wiki => "wiki",
+            LinkKind::Markdown => "markdown",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "wiki" => Some(LinkKind::Wiki),
+            "markdown" => Some(LinkKind::Markdown),
+            _ => None,
+        }
+    }
+}
+
+/// Inline `#tag` in a note body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedTag {
+    /// Tag without the `#`
+    pub name: String,
+    pub line_number: usize,
+}
+
+/// Extracted web link
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedUrl {
+    pub url: String,
+    /// Link text of a Markdown link
+    pub title: Option<String>,
+    pub line_number: usize,
 }
 
 // Regex patterns
@@ -41,6 +91,28 @@ static FRONTMATTER_RE: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"(?s)^---\r?\n(.+?)\r?\n---\r?\n?").expect("Invalid frontmatter regex")
 });
 
+/// `[text](target)` or `[text](target "title")`; the target is group 3 if
+/// in `<...>`, else group 4
+pub(crate) static MARKDOWN_LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(!?)\[([^\]]*)\]\((?:<([^>]+)>|([^)\s]+))(?:\s+"[^"]*")?\)"#)
+        .expect("Invalid Markdown link regex")
+});
+
+static MARKDOWN_URL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(!?)\[([^\]]*)\]\((https?://[^)\s]+)\)").expect("Invalid Markdown URL regex")
+});
+
+static BARE_URL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"https?://[^\s<>()\[\]"'`]+"#).expect("Invalid bare URL regex")
+});
+
+static INLINE_TAG_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|\s)#([\w/-]+)").expect("Invalid inline tag regex")
+});
+
+static CODE_SPAN_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"`[^`]*`").expect("Invalid code span regex"));
+
 static HEADING_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^#\s+(.+)$").expect("Invalid heading regex"));
 
@@ -51,19 +123,21 @@ pub fn parse_note(content: &str, filename: &str) -> ParsedNote {
 
     let title = determine_title(&frontmatter, body, filename);
     let links = extract_links(content);
+    let inline_tags = extract_inline_tags(content);
     let word_count = count_words(body);
 
     ParsedNote {
         title,
         frontmatter,
         links,
+        inline_tags,
         word_count,
         content: content.to_string(),
     }
 }
 
 /// Parse YAML frontmatter from content
-fn parse_frontmatter(content: &str) -> (Option<Frontmatter>, usize) {
+pub(crate) fn parse_frontmatter(content: &str) -> (Option<Frontmatter>, usize) {
     if let Some(captures) = FRONTMATTER_RE.captures(content) {
         let yaml_str = captures.get(1).map(|m| m.as_str()).unwrap_or("");
         let frontmatter: Option<Frontmatter> = serde_yaml::from_str(yaml_str).ok();
@@ -74,6 +148,265 @@ fn parse_frontmatter(content: &str) -> (Option<Frontmatter>, usize) {
     }
 }
 
+/// The YAML inside a note's frontmatter, or an empty string
+pub fn frontmatter_text(content: &str) -> String {
+    FRONTMATTER_RE
+        .captures(content)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str().to_string())
+        .unwrap_or_default()
+}
+
+/// Why a note's frontmatter can't be read: YAML that isn't a mapping, or
+/// an opening `---` that is never closed. `None` without frontmatter or
+/// when it is valid.
+pub fn frontmatter_error(content: &str) -> Option<String> {
+    match FRONTMATTER_RE.captures(content).and_then(|c| c.get(1)) {
+        Some(yaml) => serde_yaml::from_str::<serde_yaml::Mapping>(yaml.as_str())
+            .err()
+            .map(|e| e.to_string()),
+        None if content.lines().next().is_some_and(|l| l.trim_end() == "---")
+            && !content.lines().skip(1).any(|l| l.trim_end() == "---") =>
+        {
+            Some("Frontmatter is never closed with ---".to_string())
+        }
+        None => None,
+    }
+}
+
+/// Scalar frontmatter property as a string. Lists, maps and missing keys
+/// give `None`.
+pub fn get_frontmatter_property(content: &str, key: &str) -> Option<String> {
+    let yaml = FRONTMATTER_RE.captures(content)?.get(1)?.as_str();
+    let mapping: serde_yaml::Mapping = serde_yaml::from_str(yaml).ok()?;
+    match mapping.get(key)? {
+        serde_yaml::Value::String(s) => Some(s.clone()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Every top-level frontmatter property with a scalar value, as
+/// `(key, value)` pairs. A list of scalars gives one pair per item; maps
+/// and nested lists are skipped.
+pub fn frontmatter_properties(content: &str) -> Vec<(String, String)> {
+    let Some(yaml) = FRONTMATTER_RE.captures(content).and_then(|c| c.get(1)) else {
+        return Vec::new();
+    };
+    let Ok(mapping) = serde_yaml::from_str::<serde_yaml::Mapping>(yaml.as_str()) else {
+        return Vec::new();
+    };
+
+    let scalar = |value: &serde_yaml::Value| match value {
+        serde_yaml::Value::String(s) => Some(s.clone()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    };
+    let mut properties = Vec::new();
+    for (key, value) in &mapping {
+        let Some(key) = scalar(key) else { continue };
+        match value {
+            serde_yaml::Value::Sequence(items) => {
+                properties.extend(items.iter().filter_map(scalar).map(|v| (key.clone(), v)));
+            }
+            value => properties.extend(scalar(value).map(|v| (key.clone(), v))),
+        }
+    }
+    properties
+}
+
+/// Set a top-level frontmatter property, leaving every other line of the
+/// note untouched. Adds frontmatter if the note has none.
+pub fn set_frontmatter_property(content: &str, key: &str, value: &str) -> String {
+    set_frontmatter_yaml(content, key, &yaml_scalar(value))
+}
+
+/// Set a top-level frontmatter property to any value: strings as with
+/// `set_frontmatter_property`, lists and maps inline
+pub fn set_frontmatter_value(content: &str, key: &str, value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => set_frontmatter_property(content, key, s),
+        // JSON is valid inline YAML
+        value => set_frontmatter_yaml(content, key, &value.to_string()),
+    }
+}
+
+/// Add a tag to the frontmatter `tags`, leaving every other line of the note
+/// untouched. Notes whose frontmatter isn't valid YAML are left as they are.
+pub fn add_frontmatter_tag(content: &str, tag: &str) -> String {
+    let mut tags = match parse_frontmatter(content) {
+        (Some(fm), _) => fm.tags,
+        (None, 0) => Vec::new(),
+        (None, _) => return content.to_string(),
+    };
+    if tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+        return content.to_string();
+    }
+    tags.push(tag.to_string());
+
+    let list: Vec<String> = tags.iter().map(|t| yaml_scalar(t)).collect();
+    set_frontmatter_yaml(content, "tags", &format!("[{}]", list.join(", ")))
+}
+
+/// Remove a tag from the frontmatter `tags`, leaving every other line of
+/// the note untouched. The `tags` key goes when its last tag does.
+pub fn remove_frontmatter_tag(content: &str, tag: &str) -> String {
+    let tag = tag.trim_start_matches('#');
+    let Some(fm) = parse_frontmatter(content).0 else {
+        return content.to_string();
+    };
+    if !fm.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+        return content.to_string();
+    }
+
+    let tags: Vec<String> = fm
+        .tags
+        .iter()
+        .filter(|t| !t.eq_ignore_ascii_case(tag))
+        .map(|t| yaml_scalar(t))
+        .collect();
+    if tags.is_empty() {
+        edit_frontmatter_line(content, "tags", None)
+    } else {
+        set_frontmatter_yaml(content, "tags", &format!("[{}]", tags.join(", ")))
+    }
+}
+
+/// Rename a tag and the tags nested under it (`old/sub` becomes `new/sub`)
+/// in the frontmatter `tags` and inline `#tags`, leaving everything else
+/// untouched. Inline tags in headings and code are left alone, as they
+/// aren't indexed as tags.
+pub fn rename_tag_in_note(content: &str, old: &str, new: &str) -> String {
+    let (frontmatter, body_start) = parse_frontmatter(content);
+    let mut result = String::with_capacity(content.len());
+    result.push_str(&content[..body_start]);
+
+    let mut in_fence = false;
+    for line in content[body_start..].split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            result.push_str(line);
+            continue;
+        }
+        if in_fence || is_heading(trimmed) {
+            result.push_str(line);
+            continue;
+        }
+
+        let code: Vec<_> = CODE_SPAN_RE.find_iter(line).map(|m| m.range()).collect();
+        let mut last = 0;
+        for captures in INLINE_TAG_RE.captures_iter(line) {
+            let tag = captures.get(1).expect("tag group always matches");
+            if code.iter().any(|range| range.contains(&tag.start())) {
+                continue;
+            }
+            if let Some(renamed) = renamed_tag(tag.as_str(), old, new) {
+                result.push_str(&line[last..tag.start()]);
+                result.push_str(&renamed);
+                last = tag.end();
+            }
+        }
+        result.push_str(&line[last..]);
+    }
+
+    let Some(fm) = frontmatter else {
+        return result;
+    };
+    let mut tags: Vec<String> = Vec::new();
+    for tag in &fm.tags {
+        let tag = renamed_tag(tag, old, new).unwrap_or_else(|| tag.clone());
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            tags.push(tag);
+        }
+    }
+    if tags == fm.tags {
+        return result;
+    }
+    let list: Vec<String> = tags.iter().map(|t| yaml_scalar(t)).collect();
+    set_frontmatter_yaml(&result, "tags", &format!("[{}]", list.join(", ")))
+}
+
+/// `tag` with `old` renamed to `new`, if it is `old` or nested under it
+fn renamed_tag(tag: &str, old: &str, new: &str) -> Option<String> {
+    let (head, rest) = (tag.get(..old.len())?, &tag[old.len()..]);
+    (head.eq_ignore_ascii_case(old) && (rest.is_empty() || rest.starts_with('/')))
+        .then(|| format!("{}{}", new, rest))
+}
+
+/// Remove a top-level frontmatter property, leaving every other line of
+/// the note untouched. Frontmatter left empty is removed too.
+pub fn remove_frontmatter_property(content: &str, key: &str) -> String {
+    edit_frontmatter_line(content, key, None)
+}
+
+fn yaml_scalar(value: &str) -> String {
+    let quoted = serde_yaml::to_string(value).unwrap_or_else(|_| value.to_string());
+    quoted.trim_end().to_string()
+}
+
+/// Replace or add `key: <yaml>` on a single frontmatter line
+fn set_frontmatter_yaml(content: &str, key: &str, yaml_value: &str) -> String {
+    edit_frontmatter_line(content, key, Some(yaml_value))
+}
+
+/// Replace, add or (given `None`) remove a top-level frontmatter key along
+/// with the indented lines of its old value
+fn edit_frontmatter_line(content: &str, key: &str, yaml_value: Option<&str>) -> String {
+    let new_line = yaml_value.map(|value| format!("{}: {}", key, value));
+
+    let Some(yaml) = FRONTMATTER_RE.captures(content).and_then(|c| c.get(1)) else {
+        return match new_line {
+            Some(new_line) => format!("---\n{}\n---\n{}", new_line, content),
+            None => content.to_string(),
+        };
+    };
+
+    let prefix = format!("{}:", key);
+    let mut lines = Vec::new();
+    let mut replaced = false;
+    let mut skipping_value = false;
+    for line in yaml.as_str().split('\n') {
+        if skipping_value {
+            // Indented lines belong to the old value (e.g. list items)
+            if line.starts_with([' ', '\t', '-']) {
+                continue;
+            }
+            skipping_value = false;
+        }
+        if !replaced && line.starts_with(&prefix) {
+            let cr = if line.ends_with('\r') { "\r" } else { "" };
+            if let Some(new_line) = &new_line {
+                lines.push(format!("{}{}", new_line, cr));
+            }
+            replaced = true;
+            skipping_value = true;
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    match new_line {
+        Some(new_line) if !replaced => {
+            let cr = if yaml.as_str().contains('\r') { "\r" } else { "" };
+            lines.push(format!("{}{}", new_line, cr));
+        }
+        None if lines.iter().all(|l| l.trim().is_empty()) => {
+            let block_end = FRONTMATTER_RE.find(content).map_or(0, |m| m.end());
+            return content[block_end..].to_string();
+        }
+        _ => {}
+    }
+
+    format!(
+        "{}{}{}",
+        &content[..yaml.start()],
+        lines.join("\n"),
+        &content[yaml.end()..]
+    )
+}
+
 /// Determine title from frontmatter, first heading, or filename
 fn determine_title(frontmatter: &Option<Frontmatter>, body: &str, filename: &str) -> String {
     // Try frontmatter title
@@ -103,7 +436,23 @@ fn determine_title(frontmatter: &Option<Frontmatter>, body: &str, filename: &str
     filename.strip_suffix(".md").unwrap_or(filename).to_string()
 }
 
-/// Extract wiki-style links from content
+/// Longest link context shown, in characters
+const LINK_CONTEXT_LEN: usize = 120;
+
+/// A line with a link on it, trimmed and cut short, for showing the link
+/// in context
+pub fn link_context(line: &str) -> String {
+    let line = line.trim();
+    if line.chars().count() > LINK_CONTEXT_LEN {
+        let cut: String = line.chars().take(LINK_CONTEXT_LEN - 3).collect();
+        format!("{}...", cut)
+    } else {
+        line.to_string()
+    }
+}
+
+/// Extract links to notes from content: wiki links, and Markdown links to
+/// relative paths. Images, URLs and links within the note are skipped.
 pub fn extract_links(content: &str) -> Vec<ExtractedLink> {
     let mut links = Vec::new();
 
@@ -120,14 +469,181 @@ pub fn extract_links(content: &str) -> Vec<ExtractedLink> {
                     target,
                     display,
                     line_number: line_num + 1, // 1-indexed
+                    kind: LinkKind::Wiki,
                 });
             }
         }
+
+        for captures in MARKDOWN_LINK_RE.captures_iter(line) {
+            if &captures[1] == "!" {
+                continue;
+            }
+            let raw = captures.get(3).or(captures.get(4)).map_or("", |m| m.as_str());
+            let Some(target) = markdown_link_path(raw) else {
+                continue;
+            };
+            let display = captures[2].trim();
+            links.push(ExtractedLink {
+                target,
+                display: (!display.is_empty()).then(|| display.to_string()),
+                line_number: line_num + 1,
+                kind: LinkKind::Markdown,
+            });
+        }
     }
 
     links
 }
 
+/// The file a Markdown link target points at, decoded and without any
+/// `#heading`. `None` for URLs (anything with a scheme) and same-note
+/// anchors.
+pub(crate) fn markdown_link_path(target: &str) -> Option<String> {
+    let path = target.split('#').next().unwrap_or_default();
+    let scheme = path.split('/').next().unwrap_or_default();
+    if path.is_empty() || scheme.contains(':') {
+        return None;
+    }
+    Some(percent_decode(path))
+}
+
+/// Decode `%20`-style escapes, leaving malformed ones as they are
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = s
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Vault-relative path a Markdown link in the note at `source_path` points
+/// at: relative to the note's folder, or to the vault root if it starts
+/// with `/`. `None` if it leads out of the vault.
+pub fn resolve_markdown_link(source_path: &str, target: &str) -> Option<String> {
+    let (mut parts, relative) = match target.strip_prefix('/') {
+        Some(relative) => (Vec::new(), relative),
+        None => {
+            let mut folder: Vec<&str> = source_path.split('/').collect();
+            folder.pop();
+            (folder, target)
+        }
+    };
+    for part in relative.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// Extract web links: Markdown links (`[text](https://...)`) and bare or
+/// `<...>` URLs, skipping images and fenced code. Each URL is reported once.
+pub fn extract_urls(content: &str) -> Vec<ExtractedUrl> {
+    let mut urls: Vec<ExtractedUrl> = Vec::new();
+    let mut in_fence = false;
+
+    for (line_num, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let mut found = Vec::new();
+        let mut covered = Vec::new();
+        for captures in MARKDOWN_URL_RE.captures_iter(line) {
+            covered.push(captures.get(0).map(|m| m.range()).unwrap_or_default());
+            if &captures[1] == "!" {
+                continue;
+            }
+            let title = captures[2].trim();
+            found.push((captures[3].to_string(), (!title.is_empty()).then(|| title.to_string())));
+        }
+        for m in BARE_URL_RE.find_iter(line) {
+            if covered.iter().any(|range| range.contains(&m.start())) {
+                continue;
+            }
+            let url = m.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            found.push((url.to_string(), None));
+        }
+
+        for (url, title) in found {
+            if !urls.iter().any(|u| u.url == url) {
+                urls.push(ExtractedUrl {
+                    url,
+                    title,
+                    line_number: line_num + 1,
+                });
+            }
+        }
+    }
+
+    urls
+}
+
+/// Extract inline `#tags` from the body, skipping headings, fenced code and
+/// code spans. All-digit tags like `#123` don't count, as in Obsidian.
+pub fn extract_inline_tags(content: &str) -> Vec<ExtractedTag> {
+    let (_, body_start) = parse_frontmatter(content);
+    let first_line = content[..body_start].matches('\n').count();
+
+    let mut tags = Vec::new();
+    let mut in_fence = false;
+
+    for (i, line) in content[body_start..].lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence || is_heading(trimmed) {
+            continue;
+        }
+
+        let line = CODE_SPAN_RE.replace_all(line, "");
+        for captures in INLINE_TAG_RE.captures_iter(&line) {
+            let name = captures[1].trim_end_matches('/');
+            if name.chars().all(|c| c.is_ascii_digit() || c == '/' || c == '-') {
+                continue;
+            }
+            tags.push(ExtractedTag {
+                name: name.to_string(),
+                line_number: first_line + i + 1,
+            });
+        }
+    }
+
+    tags
+}
+
+/// Whether a line is an ATX heading (`# Title` up to `###### Title`)
+fn is_heading(line: &str) -> bool {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    (1..=6).contains(&level) && line[level..].chars().next().is_none_or(char::is_whitespace)
+}
+
 /// Count words in text (simple whitespace split)
 fn count_words(text: &str) -> usize {
     text.split_whitespace().count()
@@ -160,6 +676,12 @@ pub fn update_note_tags(content: &str, new_tags: &[String]) -> String {
             fm_lines.push(format!("  - {}", tag));
         }
     }
+    if !fm.aliases.is_empty() {
+        fm_lines.push("aliases:".to_string());
+        for alias in &fm.aliases {
+            fm_lines.push(format!("  - {}", alias));
+        }
+    }
     
     fm_lines.push("---".to_string());
     fm_lines.push(String::new()); // Empty line after frontmatter
@@ -171,6 +693,93 @@ pub fn update_note_tags(content: &str, new_tags: &[String]) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_frontmatter_properties() {
+        let content = "---\nstatus: open\npriority: 2\naliases: [Plan, Roadmap]\nmeta:\n  a: 1\n---\n# Body";
+        assert_eq!(
+            frontmatter_properties(content),
+            vec![
+                ("status".to_string(), "open".to_string()),
+                ("priority".to_string(), "2".to_string()),
+                ("aliases".to_string(), "Plan".to_string()),
+                ("aliases".to_string(), "Roadmap".to_string()),
+            ]
+        );
+        assert!(frontmatter_properties("# No frontmatter").is_empty());
+    }
+
+    #[test]
+    fn test_frontmatter_error() {
+        assert_eq!(frontmatter_error("---\ntags: [a, b]\n---\n# Ok"), None);
+        assert_eq!(frontmatter_error("# No frontmatter"), None);
+        assert_eq!(frontmatter_error("---\n---\nEmpty"), None);
+        assert!(frontmatter_error("---\ntags: [a, b\n---\n").is_some());
+        assert!(frontmatter_error("---\njust text\n---\n").is_some());
+        assert!(frontmatter_error("---\ntitle: Open\n# Body").is_some());
+    }
+
+    #[test]
+    fn test_extract_inline_tags() {
+        let content = "---\ntags: [fm]\n---\n# Heading #nope\n\n\
+                       #project/alpha and #idea, not#this\n\
+                       `#code` #123 [[note#section]] (#anchor)\n\
+                       ```\n#fenced\n```\n## Sub\n- #todo/\n";
+        let tags: Vec<(String, usize)> = extract_inline_tags(content)
+            .into_iter()
+            .map(|t| (t.name, t.line_number))
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                ("project/alpha".to_string(), 6),
+                ("idea".to_string(), 6),
+                ("todo".to_string(), 12),
+            ]
+        );
+        assert_eq!(extract_inline_tags("#hashtag-only line")[0].name, "hashtag-only");
+    }
+
+    #[test]
+    fn test_rename_tag_in_note() {
+        let content = "---\ntitle: Plan\ntags: [Project, project/alpha, other]\n---\n\
+                       # Plan #project\n\n#project and #project/beta, not #projects.\n\
+                       `#project` stays\n```\n#project\n```\n";
+        assert_eq!(
+            rename_tag_in_note(content, "project", "work"),
+            "---\ntitle: Plan\ntags: [work, work/alpha, other]\n---\n\
+             # Plan #project\n\n#work and #work/beta, not #projects.\n\
+             `#project` stays\n```\n#project\n```\n"
+        );
+
+        // Renaming into an existing tag doesn't list it twice
+        let merged = rename_tag_in_note("---\ntags: [a, b]\n---\nText #a\n", "a", "b");
+        assert_eq!(merged, "---\ntags: [b]\n---\nText #b\n");
+        assert_eq!(rename_tag_in_note("No tags here\n", "a", "b"), "No tags here\n");
+    }
+
+    #[test]
+    fn test_extract_urls() {
+        let content = "Read [Rust Blog](https://blog.rust-lang.org/2024/).\n\
+            Also https://example.com/a?b=1, and <https://docs.rs>.\n\
+            ![diagram](https://example.com/d.png)\n\
+            ```\nhttps://skipped.example\n```\n\
+            Again [Rust Blog](https://blog.rust-lang.org/2024/)";
+        let urls = extract_urls(content);
+
+        let found: Vec<(&str, Option<&str>, usize)> = urls
+            .iter()
+            .map(|u| (u.url.as_str(), u.title.as_deref(), u.line_number))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("https://blog.rust-lang.org/2024/", Some("Rust Blog"), 1),
+                ("https://example.com/a?b=1", None, 2),
+                ("https://docs.rs", None, 2),
+            ]
+        );
+    }
+
     #[test]
     fn test_parse_note_simple() {
         let content = "# Hello World\n\nThis is a test note.";
@@ -232,6 +841,47 @@ Content here."#;
         assert_eq!(links[2].line_number, 2);
     }
 
+    #[test]
+    fn test_extract_markdown_links() {
+        let content = "See [the plan](../projects/my%20plan.md#goals) and [[wiki]].\n\
+            ![chart](chart.png) [site](https://example.com) [top](#top) \
+            [mail](mailto:a@b.c) [<spaced>](<notes/a b.md> \"Title\")";
+        let links = extract_links(content);
+        let markdown: Vec<(&str, Option<&str>, usize)> = links
+            .iter()
+            .filter(|l| l.kind == LinkKind::Markdown)
+            .map(|l| (l.target.as_str(), l.display.as_deref(), l.line_number))
+            .collect();
+        assert_eq!(
+            markdown,
+            vec![
+                ("../projects/my plan.md", Some("the plan"), 1),
+                ("notes/a b.md", Some("<spaced>"), 2),
+            ]
+        );
+        assert_eq!(links.iter().filter(|l| l.kind == LinkKind::Wiki).count(), 1);
+    }
+
+    #[test]
+    fn test_resolve_markdown_link() {
+        assert_eq!(
+            resolve_markdown_link("daily/2024-01-01.md", "../projects/plan.md").as_deref(),
+            Some("projects/plan.md")
+        );
+        assert_eq!(resolve_markdown_link("a/b.md", "./c.md").as_deref(), Some("a/c.md"));
+        assert_eq!(resolve_markdown_link("a/b.md", "/top.md").as_deref(), Some("top.md"));
+        assert_eq!(resolve_markdown_link("b.md", "../outside.md"), None);
+    }
+
+    #[test]
+    fn test_link_context() {
+        assert_eq!(link_context("  See [[a]].  "), "See [[a]].");
+        let long = format!("[[a]] {}", "é".repeat(200));
+        let context = link_context(&long);
+        assert_eq!(context.chars().count(), 120);
+        assert!(context.ends_with("é..."));
+    }
+
     #[test]
     fn test_extract_links_empty() {
         let content = "No links here.";
@@ -258,4 +908,74 @@ Content here."#;
         assert_eq!(fm.modified, Some("2024-01-02".to_string()));
         assert!(body_start > 0);
     }
+
+    #[test]
+    fn test_get_frontmatter_property() {
+        let content = "---\nstatus: In Progress\npriority: 2\ntags:\n  - a\n---\nBody";
+        assert_eq!(get_frontmatter_property(content, "status").as_deref(), Some("In Progress"));
+        assert_eq!(get_frontmatter_property(content, "priority").as_deref(), Some("2"));
+        assert_eq!(get_frontmatter_property(content, "tags"), None);
+        assert_eq!(get_frontmatter_property(content, "missing"), None);
+        assert_eq!(get_frontmatter_property("No frontmatter", "status"), None);
+    }
+
+    #[test]
+    fn test_set_frontmatter_property() {
+        let content = "---\ntitle: Plan\nstatus:\n  - old\n  - values\nowner: sam # lead\n---\nBody";
+        let updated = set_frontmatter_property(content, "status", "done");
+        assert_eq!(updated, "---\ntitle: Plan\nstatus: done\nowner: sam # lead\n---\nBody");
+
+        let added = set_frontmatter_property(&updated, "done", "true");
+        assert!(added.ends_with("owner: sam # lead\ndone: 'true'\n---\nBody"));
+
+        let created = set_frontmatter_property("# Note\n", "status", "todo");
+        assert_eq!(created, "---\nstatus: todo\n---\n# Note\n");
+        assert_eq!(parse_note(&created, "note.md").title, "Note");
+    }
+
+    #[test]
+    fn test_add_frontmatter_tag() {
+        let content = "---\nsource: https://example.com\ntags:\n  - inbox\n---\n# Links\n";
+        let tagged = add_frontmatter_tag(content, "toread");
+        assert_eq!(
+            tagged,
+            "---\nsource: https://example.com\ntags: [inbox, toread]\n---\n# Links\n"
+        );
+        assert_eq!(add_frontmatter_tag(&tagged, "toread"), tagged);
+
+        let created = add_frontmatter_tag("# Links\n", "toread");
+        assert_eq!(created, "---\ntags: [toread]\n---\n# Links\n");
+
+        let invalid = "---\ntags: [unclosed\n---\n# Links\n";
+        assert_eq!(add_frontmatter_tag(invalid, "toread"), invalid);
+    }
+
+    #[test]
+    fn test_remove_frontmatter_entries() {
+        let content = "---\ntitle: Plan\ntags: [draft, work]\nstatus:\n  - old\nowner: sam\n---\nBody";
+        let untagged = remove_frontmatter_tag(content, "#Draft");
+        assert_eq!(untagged, "---\ntitle: Plan\ntags: [work]\nstatus:\n  - old\nowner: sam\n---\nBody");
+        let untagged = remove_frontmatter_tag(&untagged, "work");
+        assert_eq!(untagged, "---\ntitle: Plan\nstatus:\n  - old\nowner: sam\n---\nBody");
+
+        let removed = remove_frontmatter_property(&untagged, "status");
+        assert_eq!(removed, "---\ntitle: Plan\nowner: sam\n---\nBody");
+        assert_eq!(remove_frontmatter_property(&removed, "missing"), removed);
+
+        let emptied = remove_frontmatter_property("---\nstatus: done\n---\n# Note\n", "status");
+        assert_eq!(emptied, "# Note\n");
+        assert_eq!(remove_frontmatter_property("# Note\n", "status"), "# Note\n");
+    }
+
+    #[test]
+    fn test_update_note_tags_keeps_aliases() {
+        let content = "---\ntitle: Rust\naliases:\n  - rustlang\n---\nBody";
+        let updated = update_note_tags(content, &["lang".to_string()]);
+
+        let (fm, _) = parse_frontmatter(&updated);
+        let fm = fm.unwrap();
+        assert_eq!(fm.tags, vec!["lang"]);
+        assert_eq!(fm.aliases, vec!["rustlang"]);
+        assert!(updated.ends_with("Body"));
+    }
 }
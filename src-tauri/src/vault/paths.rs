@@ -0,0 +1,84 @@
+//! Resolving user-supplied note paths inside the vault
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::ChronicleError;
+
+/// Resolve a vault-relative path, rejecting anything that would land
+/// outside the vault: `..` components, absolute paths, or symlinks that
+/// point elsewhere. The path itself doesn't need to exist yet.
+pub fn resolve_vault_path(vault_path: &Path, relative: &str) -> Result<PathBuf, ChronicleError> {
+    let rel = Path::new(relative);
+    let lexically_safe = rel
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if relative.trim().is_empty() || !lexically_safe {
+        return Err(ChronicleError::InvalidPath(relative.to_string()));
+    }
+
+    let full_path = vault_path.join(rel);
+
+    // A symlink inside the vault can still lead out of it, so check where
+    // the deepest existing ancestor really lives
+    let root = vault_path.canonicalize()?;
+    let mut existing = full_path.as_path();
+    while !existing.exists() {
+        match existing.parent() {
+            Some(parent) => existing = parent,
+            None => break,
+        }
+    }
+    if !existing.canonicalize()?.starts_with(&root) {
+        return Err(ChronicleError::InvalidPath(relative.to_string()));
+    }
+
+    Ok(full_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolves_paths_inside_vault() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("note.md"), "# Note").unwrap();
+
+        assert_eq!(
+            resolve_vault_path(temp.path(), "note.md").unwrap(),
+            temp.path().join("note.md")
+        );
+        // Not-yet-created files and folders are fine
+        assert_eq!(
+            resolve_vault_path(temp.path(), "new/folder/note.md").unwrap(),
+            temp.path().join("new/folder/note.md")
+        );
+    }
+
+    #[test]
+    fn test_rejects_escaping_paths() {
+        let temp = TempDir::new().unwrap();
+
+        for path in ["../outside.md", "notes/../../outside.md", "/etc/passwd", ""] {
+            let result = resolve_vault_path(temp.path(), path);
+            assert!(
+                matches!(result, Err(ChronicleError::InvalidPath(_))),
+                "{} should be rejected",
+                path
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rejects_symlink_out_of_vault() {
+        let vault = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::os::unix::fs::symlink(outside.path(), vault.path().join("link")).unwrap();
+
+        let result = resolve_vault_path(vault.path(), "link/secret.md");
+        assert!(matches!(result, Err(ChronicleError::InvalidPath(_))));
+    }
+}
@@ -0,0 +1,140 @@
+//! Text extraction from PDF attachments
+//!
+//! Reading PDFs is built in with the `pdf-text` feature. Extracted text is
+//! kept apart from notes and searched with the text recognized in images.
+
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::db::{
+    pdf_text::{delete_pdf_text, pdf_text_times, set_pdf_text},
+    schema::Database,
+};
+use crate::error::ChronicleError;
+use crate::vault::indexer::walkdir;
+
+/// Whether this build can read PDFs
+pub const PDF_TEXT_AVAILABLE: bool = cfg!(feature = "pdf-text");
+
+/// Outcome of a text extraction pass over the vault
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PdfTextReport {
+    /// PDFs read in this pass
+    pub extracted: usize,
+    /// PDFs unchanged since they were last read
+    pub unchanged: usize,
+    /// PDFs gone from the vault, whose text was dropped
+    pub removed: usize,
+    /// PDFs that couldn't be read; they are retried on the next pass
+    pub failed: Vec<String>,
+}
+
+/// Whether the file at `path` is a PDF
+pub fn is_pdf(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("pdf"))
+}
+
+/// Extract the text of a PDF. `None` means this file couldn't be read.
+#[cfg(feature = "pdf-text")]
+pub fn extract_pdf_text(pdf: &Path) -> Result<Option<String>, ChronicleError> {
+    let bytes = fs::read(pdf)?;
+    // The parser panics on some malformed files rather than failing
+    match std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(&bytes)) {
+        Ok(Ok(text)) => Ok(Some(text.trim().to_string())),
+        Ok(Err(e)) => {
+            tracing::warn!(path = ?pdf, error = %e, "PDF text extraction failed");
+            Ok(None)
+        }
+        Err(_) => {
+            tracing::warn!(path = ?pdf, "PDF text extraction panicked");
+            Ok(None)
+        }
+    }
+}
+
+/// Extract the text of a PDF. This build can't read PDFs.
+#[cfg(not(feature = "pdf-text"))]
+pub fn extract_pdf_text(_pdf: &Path) -> Result<Option<String>, ChronicleError> {
+    Err(ChronicleError::PdfTextUnavailable)
+}
+
+/// Bring the extracted text in line with the vault's PDFs: read new and
+/// modified ones with `extract` and drop text of deleted ones. The database
+/// is only locked between files.
+pub fn index_pdf_text(
+    vault_path: &Path,
+    db: &Database,
+    mut extract: impl FnMut(&Path) -> Result<Option<String>, ChronicleError>,
+) -> Result<PdfTextReport, ChronicleError> {
+    let mut known = pdf_text_times(&db.conn())?;
+    let mut report = PdfTextReport::default();
+
+    for path in walkdir(vault_path)? {
+        if !is_pdf(&path) {
+            continue;
+        }
+        let relative = path
+            .strip_prefix(vault_path)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let modified = fs::metadata(&path)?
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+
+        if known.remove(&relative) == Some(modified) {
+            report.unchanged += 1;
+            continue;
+        }
+        match extract(&path)? {
+            Some(text) => {
+                set_pdf_text(&db.conn(), &relative, modified, &text)?;
+                report.extracted += 1;
+            }
+            None => report.failed.push(relative),
+        }
+    }
+
+    // Whatever is left wasn't found on disk
+    let conn = db.conn();
+    for path in known.keys() {
+        delete_pdf_text(&conn, path)?;
+        report.removed += 1;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::pdf_text::search_pdf_text;
+
+    #[test]
+    fn test_index_pdf_text() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let db = Database::open_memory().unwrap();
+        fs::create_dir_all(temp.path().join("attachments")).unwrap();
+        fs::write(temp.path().join("attachments/report.PDF"), b"%PDF").unwrap();
+        fs::write(temp.path().join("attachments/broken.pdf"), b"junk").unwrap();
+        fs::write(temp.path().join("attachments/photo.png"), b"png").unwrap();
+
+        let extract = |path: &Path| {
+            Ok((!path.ends_with("broken.pdf")).then(|| "Quarterly revenue".to_string()))
+        };
+        let report = index_pdf_text(temp.path(), &db, extract).unwrap();
+        assert_eq!(report.extracted, 1);
+        assert_eq!(report.failed, vec!["attachments/broken.pdf"]);
+        let results = search_pdf_text(&db.conn(), "revenue", 10).unwrap();
+        assert_eq!(results[0].path, "attachments/report.PDF");
+
+        fs::remove_file(temp.path().join("attachments/report.PDF")).unwrap();
+        let report = index_pdf_text(temp.path(), &db, extract).unwrap();
+        assert_eq!(report.removed, 1);
+        assert!(search_pdf_text(&db.conn(), "revenue", 10).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,78 @@
+//! Frontmatter edits applied to many notes at once
+
+use serde::{Deserialize, Serialize};
+
+use super::parser::{
+    add_frontmatter_tag, remove_frontmatter_property, remove_frontmatter_tag,
+    set_frontmatter_property,
+};
+
+/// One frontmatter change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PropertyChange {
+    AddTag { tag: String },
+    RemoveTag { tag: String },
+    /// Set a property to a string value, e.g. `status: done`
+    Set { key: String, value: String },
+    /// Remove a property and its value
+    Remove { key: String },
+}
+
+impl PropertyChange {
+    pub fn validate(&self) -> Result<(), String> {
+        let (label, value) = match self {
+            PropertyChange::AddTag { tag } | PropertyChange::RemoveTag { tag } => ("Tag", tag),
+            PropertyChange::Set { key, .. } | PropertyChange::Remove { key } => ("Property key", key),
+        };
+        if value.trim_start_matches('#').trim().is_empty() {
+            return Err(format!("{} must not be empty", label));
+        }
+        // `tags` holds a list; changing it as a scalar would break it
+        if let PropertyChange::Set { key, .. } = self {
+            if key == "tags" {
+                return Err("Use add_tag and remove_tag to change tags".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Apply changes in order, leaving lines they don't touch as they are
+pub fn apply_property_changes(content: &str, changes: &[PropertyChange]) -> String {
+    changes.iter().fold(content.to_string(), |content, change| match change {
+        PropertyChange::AddTag { tag } => add_frontmatter_tag(&content, tag.trim_start_matches('#')),
+        PropertyChange::RemoveTag { tag } => remove_frontmatter_tag(&content, tag),
+        PropertyChange::Set { key, value } => set_frontmatter_property(&content, key, value),
+        PropertyChange::Remove { key } => remove_frontmatter_property(&content, key),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::frontmatter_text;
+
+    #[test]
+    fn test_apply_property_changes() {
+        let content = "---\ntags: [draft]\nreviewer: sam\n---\n# Plan\n";
+        let changes = vec![
+            PropertyChange::AddTag { tag: "#project".to_string() },
+            PropertyChange::RemoveTag { tag: "draft".to_string() },
+            PropertyChange::Set { key: "status".to_string(), value: "open".to_string() },
+            PropertyChange::Remove { key: "reviewer".to_string() },
+        ];
+        let updated = apply_property_changes(content, &changes);
+        assert_eq!(updated, "---\ntags: [project]\nstatus: open\n---\n# Plan\n");
+        assert_eq!(frontmatter_text(&updated), "tags: [project]\nstatus: open");
+        assert_eq!(apply_property_changes(&updated, &changes), updated);
+    }
+
+    #[test]
+    fn test_validate_change() {
+        assert!(PropertyChange::AddTag { tag: "#".to_string() }.validate().is_err());
+        let set_tags = PropertyChange::Set { key: "tags".to_string(), value: "x".to_string() };
+        assert!(set_tags.validate().is_err());
+        assert!(PropertyChange::Remove { key: "status".to_string() }.validate().is_ok());
+    }
+}
@@ -0,0 +1,218 @@
+//! Inline queries: fenced `chronicle-query` blocks holding a smart folder
+//! filter as YAML, rendered as a live list of the notes it matches
+//!
+//! ````markdown
+//! ```chronicle-query
+//! all_tags: [project]
+//! properties:
+//!   - { key: status, op: equals, value: active }
+//! limit: 10
+//! ```
+//! ````
+//!
+//! A block can also total up numeric or checkbox properties across the
+//! notes it matches, e.g. `rollups: [{ key: hours, op: sum }]`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use super::smart_folders::SmartFilter;
+
+/// Info string that marks a fenced block as a query
+pub const QUERY_LANGUAGE: &str = "chronicle-query";
+
+/// A query block's position and contents
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryBlock {
+    /// 1-indexed line of the opening fence
+    pub start_line: usize,
+    /// 1-indexed line of the closing fence, or the last line if the block
+    /// is never closed
+    pub end_line: usize,
+    pub source: String,
+}
+
+/// What a query block asks for: a smart folder filter, a result limit and
+/// rollups of properties over the notes it matches
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct QuerySpec {
+    #[serde(flatten)]
+    pub filter: SmartFilter,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub rollups: Vec<Rollup>,
+}
+
+/// A property totted up over every note a query matches, before its limit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rollup {
+    pub key: String,
+    pub op: RollupOp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RollupOp {
+    /// Total of the numeric values; checkboxes count 1 when ticked, so this
+    /// is how many are ticked
+    Sum,
+    /// Mean of the numeric values; for checkboxes, the share ticked
+    Avg,
+    /// Number of notes with the property, whatever its value
+    Count,
+}
+
+/// A rollup's outcome; `value` is `None` for the average of no numbers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollupValue {
+    pub key: String,
+    pub op: RollupOp,
+    pub value: Option<f64>,
+}
+
+impl RollupOp {
+    /// Roll up a property's values, given as `(note ID, number)` with the
+    /// number `None` for values that aren't numbers or booleans
+    pub fn apply(self, values: &[(i64, Option<f64>)]) -> Option<f64> {
+        let numbers = values.iter().filter_map(|(_, number)| *number);
+        match self {
+            RollupOp::Sum => Some(numbers.sum()),
+            RollupOp::Avg => {
+                let (total, count) = numbers.fold((0.0, 0), |(t, c), n| (t + n, c + 1));
+                (count > 0).then(|| total / count as f64)
+            }
+            RollupOp::Count => {
+                let notes: HashSet<i64> = values.iter().map(|(id, _)| *id).collect();
+                Some(notes.len() as f64)
+            }
+        }
+    }
+}
+
+/// Every query block in a note, skipping ones nested in other fences
+pub fn find_query_blocks(content: &str) -> Vec<QueryBlock> {
+    let mut blocks = Vec::new();
+    // The open fence's marker and, for query blocks, where it started
+    let mut open: Option<(String, Option<QueryBlock>)> = None;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let fence_char = trimmed.chars().next().filter(|&c| c == '`' || c == '~');
+        let fence_len = fence_char.map_or(0, |f| trimmed.chars().take_while(|&c| c == f).count());
+        let marker = &trimmed[..fence_len];
+        let is_fence = fence_len >= 3;
+
+        match open.as_mut() {
+            None if is_fence => {
+                let info = trimmed[fence_len..].trim();
+                let query = (info == QUERY_LANGUAGE).then(|| QueryBlock {
+                    start_line: index + 1,
+                    end_line: index + 1,
+                    source: String::new(),
+                });
+                open = Some((marker.to_string(), query));
+            }
+            None => {}
+            Some((open_marker, query)) => {
+                let closes = is_fence
+                    && marker.starts_with(open_marker.as_str())
+                    && trimmed[fence_len..].trim().is_empty();
+                if closes {
+                    if let Some(mut block) = query.take() {
+                        block.end_line = index + 1;
+                        blocks.push(block);
+                    }
+                    open = None;
+                } else if let Some(block) = query {
+                    block.source.push_str(line);
+                    block.source.push('\n');
+                    block.end_line = index + 1;
+                }
+            }
+        }
+    }
+
+    if let Some((_, Some(block))) = open {
+        blocks.push(block);
+    }
+    blocks
+}
+
+/// Parse a query block's YAML. An empty block matches every note.
+pub fn parse_query(source: &str) -> Result<QuerySpec, String> {
+    if source.trim().is_empty() {
+        return Ok(QuerySpec::default());
+    }
+    let spec: QuerySpec = serde_yaml::from_str(source).map_err(|e| e.to_string())?;
+    spec.filter.validate()?;
+    if spec.rollups.iter().any(|rollup| rollup.key.trim().is_empty()) {
+        return Err("Rollups need a property key".to_string());
+    }
+    Ok(spec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_query_blocks() {
+        let content = "# Projects\n\
+                       ```chronicle-query\n\
+                       all_tags: [project]\n\
+                       ```\n\
+                       ````markdown\n\
+                       ```chronicle-query\n\
+                       ```\n\
+                       ````\n\
+                       ~~~ chronicle-query\n\
+                       limit: 2\n";
+        let blocks = find_query_blocks(content);
+        assert_eq!(
+            blocks,
+            vec![
+                QueryBlock {
+                    start_line: 2,
+                    end_line: 4,
+                    source: "all_tags: [project]\n".to_string(),
+                },
+                // Unclosed blocks run to the end of the note
+                QueryBlock {
+                    start_line: 9,
+                    end_line: 10,
+                    source: "limit: 2\n".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let spec = parse_query("any_tags: [book]\nlimit: 5\n").unwrap();
+        assert_eq!(spec.filter.any_tags, vec!["book"]);
+        assert_eq!(spec.limit, Some(5));
+        assert_eq!(parse_query("  \n").unwrap(), QuerySpec::default());
+        assert!(parse_query("created_after: yesterday").is_err());
+        assert!(parse_query("all_tags: [unclosed").is_err());
+
+        let spec = parse_query("all_tags: [client]\nrollups: [{ key: hours, op: sum }]").unwrap();
+        let hours = Rollup {
+            key: "hours".to_string(),
+            op: RollupOp::Sum,
+        };
+        assert_eq!(spec.rollups, vec![hours]);
+        assert!(parse_query("rollups: [{ key: hours, op: median }]").is_err());
+        assert!(parse_query("rollups: [{ key: '', op: count }]").is_err());
+    }
+
+    #[test]
+    fn test_rollups() {
+        let values = [(1, Some(2.5)), (1, Some(1.0)), (2, None), (3, Some(0.0))];
+        assert_eq!(RollupOp::Sum.apply(&values), Some(3.5));
+        assert_eq!(RollupOp::Avg.apply(&values), Some(3.5 / 3.0));
+        assert_eq!(RollupOp::Count.apply(&values), Some(3.0));
+        assert_eq!(RollupOp::Sum.apply(&[]), Some(0.0));
+        assert_eq!(RollupOp::Avg.apply(&[(1, None)]), None);
+    }
+}
@@ -0,0 +1,219 @@
+//! Fuzzy note matching for the quick switcher
+//!
+//! Titles are matched loosely: by prefix or substring, by the query's
+//! characters appearing in order (`mtgnts` finds "Meeting notes"), and by
+//! shared trigrams, which tolerates typos (`meetnig` still finds it).
+//! Paths are matched too, for jumping by folder.
+
+use serde::Serialize;
+
+/// Below this share of the query's trigrams a title isn't a typo match
+const MIN_TRIGRAM_COVERAGE: f64 = 0.5;
+
+/// Query trigrams past this many are ignored, so matches fit in a bitmask
+const MAX_QUERY_TRIGRAMS: usize = 64;
+
+/// A note the quick switcher matched
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuickSwitchMatch {
+    pub path: String,
+    pub title: String,
+    /// Higher is better, at most 1
+    pub score: f64,
+}
+
+/// Rank notes, given as `(path, title)`, by how well they match `query`.
+/// Returns the best `limit` matches, best first.
+pub fn quick_switch_matches(
+    query: &str,
+    notes: &[(String, String)],
+    limit: usize,
+) -> Vec<QuickSwitchMatch> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut query_trigrams = QueryTrigrams::default();
+    for_each_trigram(&query, |trigram| query_trigrams.insert(trigram));
+
+    let mut scored: Vec<(f64, &String, &String)> = notes
+        .iter()
+        .filter_map(|(path, title)| {
+            Some((match_score(&query, &query_trigrams, path, title)?, path, title))
+        })
+        .collect();
+    // Among equal scores, shorter titles are closer to what was typed
+    let order = |a: &(f64, &String, &String), b: &(f64, &String, &String)| {
+        b.0.total_cmp(&a.0).then(a.2.len().cmp(&b.2.len())).then_with(|| a.1.cmp(b.1))
+    };
+    if scored.len() > limit && limit > 0 {
+        scored.select_nth_unstable_by(limit - 1, order);
+    }
+    scored.truncate(limit);
+    scored.sort_by(order);
+
+    scored
+        .into_iter()
+        .map(|(score, path, title)| QuickSwitchMatch {
+            path: path.clone(),
+            title: title.clone(),
+            score,
+        })
+        .collect()
+}
+
+/// How well a note matches a lowercased query, or `None` if it doesn't
+fn match_score(
+    query: &str,
+    query_trigrams: &QueryTrigrams,
+    path: &str,
+    title: &str,
+) -> Option<f64> {
+    // Direct matches outscore the looser kinds, which needn't be tried
+    let title = title.to_lowercase();
+    if title == query {
+        return Some(1.0);
+    } else if title.starts_with(query) {
+        return Some(0.9);
+    } else if title.split_whitespace().any(|word| word.starts_with(query)) {
+        return Some(0.85);
+    } else if title.contains(query) {
+        return Some(0.8);
+    }
+    let path = path.trim_end_matches(".md").to_lowercase();
+    if path.contains(query) {
+        return Some(0.7);
+    }
+
+    let in_order = subsequence_score(query, &title)
+        .map(|s| 0.4 + 0.3 * s)
+        .or_else(|| subsequence_score(query, &path).map(|s| 0.3 + 0.2 * s))
+        .unwrap_or(0.0);
+
+    // Runs for every note, so the title's trigrams aren't collected
+    let (mut found, mut title_trigrams) = (0u64, 0);
+    for_each_trigram(&title, |trigram| {
+        title_trigrams += 1;
+        if let Some(i) = query_trigrams.position(trigram) {
+            found |= 1 << i;
+        }
+    });
+    let query_count = query_trigrams.trigrams.len();
+    let shared = found.count_ones() as f64;
+    let coverage = shared / query_count.max(1) as f64;
+    let typo = if coverage >= MIN_TRIGRAM_COVERAGE {
+        let dice = 2.0 * shared / (query_count + title_trigrams) as f64;
+        0.6 * (0.7 * coverage + 0.3 * dice)
+    } else {
+        0.0
+    };
+
+    let score = in_order.max(typo);
+    (score > 0.0).then_some(score)
+}
+
+/// If every character of `query` appears in `text` in order, how tightly:
+/// 1 when they are contiguous, approaching 0 as they spread out
+fn subsequence_score(query: &str, text: &str) -> Option<f64> {
+    let mut chars = text.char_indices();
+    let mut first = None;
+    let mut last = 0;
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        let (i, _) = chars.find(|(_, c)| *c == q)?;
+        first.get_or_insert(i);
+        last = i;
+    }
+    let span = (last - first?) as f64 + 1.0;
+    Some(query.chars().filter(|c| !c.is_whitespace()).count() as f64 / span)
+}
+
+/// The query's distinct trigrams, with a bloom mask so most of a title's
+/// trigrams are ruled out without a scan
+#[derive(Default)]
+struct QueryTrigrams {
+    trigrams: Vec<[char; 3]>,
+    mask: u64,
+}
+
+impl QueryTrigrams {
+    fn insert(&mut self, trigram: [char; 3]) {
+        if self.trigrams.len() < MAX_QUERY_TRIGRAMS && !self.trigrams.contains(&trigram) {
+            self.trigrams.push(trigram);
+            self.mask |= Self::bit(trigram);
+        }
+    }
+
+    fn position(&self, trigram: [char; 3]) -> Option<usize> {
+        if self.mask & Self::bit(trigram) == 0 {
+            return None;
+        }
+        self.trigrams.iter().position(|t| *t == trigram)
+    }
+
+    fn bit(trigram: [char; 3]) -> u64 {
+        let [a, b, c] = trigram.map(|c| c as u64);
+        // Fibonacci hashing; the top six bits pick the bit
+        1 << (((a << 42) | (b << 21) | c).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 58)
+    }
+}
+
+/// Trigrams of each word, padded so short words and word starts count
+fn for_each_trigram(text: &str, mut f: impl FnMut([char; 3])) {
+    let mut window = [' '; 3];
+    for c in text.chars().chain([' ']) {
+        if c.is_alphanumeric() {
+            window = [window[1], window[2], c];
+            f(window);
+        } else if window[2] != ' ' {
+            // The end of a word
+            f([window[1], window[2], ' ']);
+            window = [' '; 3];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notes(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter().map(|(p, t)| (p.to_string(), t.to_string())).collect()
+    }
+
+    fn titles(query: &str, notes: &[(String, String)]) -> Vec<String> {
+        quick_switch_matches(query, notes, 10).into_iter().map(|m| m.title).collect()
+    }
+
+    #[test]
+    fn test_quick_switch_matches() {
+        let notes = notes(&[
+            ("meetings/Meeting notes.md", "Meeting notes"),
+            ("Meeting.md", "Meeting"),
+            ("projects/alpha/Plan.md", "Plan"),
+            ("Rust ownership.md", "Rust ownership"),
+            ("Groceries.md", "Groceries"),
+        ]);
+
+        // Exact beats prefix, and only fitting notes come back
+        assert_eq!(titles("meeting", &notes), vec!["Meeting", "Meeting notes"]);
+        // A word later in the title
+        assert_eq!(titles("owner", &notes)[0], "Rust ownership");
+        // Characters in order
+        assert_eq!(titles("mtgnts", &notes), vec!["Meeting notes"]);
+        // Typos
+        assert_eq!(titles("meetnig notes", &notes)[0], "Meeting notes");
+        assert_eq!(titles("rust ownrship", &notes)[0], "Rust ownership");
+        // Folders in the path
+        assert_eq!(titles("alpha", &notes), vec!["Plan"]);
+
+        assert!(titles("", &notes).is_empty());
+        assert!(titles("zzzz", &notes).is_empty());
+    }
+
+    #[test]
+    fn test_subsequence_score() {
+        assert_eq!(subsequence_score("abc", "abc"), Some(1.0));
+        assert_eq!(subsequence_score("ac", "abc"), Some(2.0 / 3.0));
+        assert_eq!(subsequence_score("ca", "abc"), None);
+    }
+}
@@ -0,0 +1,138 @@
+//! Reminder extraction
+//!
+//! A note can carry a reminder for itself in frontmatter
+//! (`remind: 2025-02-01 09:00`), and any line can set one with
+//! `@remind(2025-02-01 09:00)`. A bare date reminds at 09:00.
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+use super::parser::{get_frontmatter_property, parse_frontmatter};
+
+/// Stored form of reminder times; sorts chronologically as text
+pub const REMIND_AT_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+static INLINE_REMIND_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"@remind\(([^)]*)\)").expect("Invalid reminder regex"));
+
+/// List marker and checkbox dropped from the start of a reminder's text
+static LINE_PREFIX_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(?:[-*+]\s+(?:\[[ xX]\]\s+)?|\d+[.)]\s+)?").expect("Invalid line prefix regex")
+});
+
+/// Reminder found in a note
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedReminder {
+    /// 1-indexed line of an inline reminder; `None` for frontmatter
+    pub line_number: Option<usize>,
+    /// Local time to remind at, in `REMIND_AT_FORMAT`
+    pub remind_at: String,
+    /// What to be reminded of: the rest of the line, empty for frontmatter
+    pub text: String,
+}
+
+/// Extract the frontmatter reminder and inline reminders, skipping fenced
+/// code. Reminders with unparseable times are ignored.
+pub fn extract_reminders(content: &str) -> Vec<ExtractedReminder> {
+    let mut reminders: Vec<ExtractedReminder> = get_frontmatter_property(content, "remind")
+        .and_then(|value| parse_remind_at(&value))
+        .map(|at| ExtractedReminder {
+            line_number: None,
+            remind_at: at.format(REMIND_AT_FORMAT).to_string(),
+            text: String::new(),
+        })
+        .into_iter()
+        .collect();
+
+    let (_, body_start) = parse_frontmatter(content);
+    let first_line = content[..body_start].matches('\n').count();
+    let mut in_fence = false;
+
+    for (i, line) in content[body_start..].lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let text = INLINE_REMIND_RE.replace_all(line, "");
+        let text = LINE_PREFIX_RE.replace(&text, "");
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        for captures in INLINE_REMIND_RE.captures_iter(line) {
+            if let Some(at) = parse_remind_at(&captures[1]) {
+                reminders.push(ExtractedReminder {
+                    line_number: Some(first_line + i + 1),
+                    remind_at: at.format(REMIND_AT_FORMAT).to_string(),
+                    text: text.clone(),
+                });
+            }
+        }
+    }
+
+    reminders
+}
+
+/// `YYYY-MM-DD HH:MM` (a `T` separator and seconds are accepted too), or
+/// a bare date for 09:00 that day
+pub fn parse_remind_at(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+            Some(date.and_time(NaiveTime::from_hms_opt(9, 0, 0)?))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_reminders() {
+        let content = "---\nremind: 2025-02-01 09:30\n---\n# Thesis\n\n\
+            - [ ] Email supervisor @remind(2025-02-03T14:00)\n\
+            Renew passport @remind(2025-03-01)\n\
+            Bad date @remind(next tuesday)\n\
+            ```\n@remind(2025-01-01 00:00)\n```\n";
+        let reminders = extract_reminders(content);
+
+        assert_eq!(
+            reminders,
+            vec![
+                ExtractedReminder {
+                    line_number: None,
+                    remind_at: "2025-02-01 09:30".to_string(),
+                    text: String::new(),
+                },
+                ExtractedReminder {
+                    line_number: Some(6),
+                    remind_at: "2025-02-03 14:00".to_string(),
+                    text: "Email supervisor".to_string(),
+                },
+                ExtractedReminder {
+                    line_number: Some(7),
+                    remind_at: "2025-03-01 09:00".to_string(),
+                    text: "Renew passport".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_remind_at() {
+        let at = |s| parse_remind_at(s).map(|t| t.format(REMIND_AT_FORMAT).to_string());
+        assert_eq!(at("2025-02-01 09:00").as_deref(), Some("2025-02-01 09:00"));
+        assert_eq!(at(" 2025-02-01T18:45:10 ").as_deref(), Some("2025-02-01 18:45"));
+        assert_eq!(at("2025-02-01").as_deref(), Some("2025-02-01 09:00"));
+        assert_eq!(at("2025-02-30"), None);
+        assert_eq!(at("tomorrow"), None);
+    }
+}
@@ -0,0 +1,286 @@
+//! Roam Research and Logseq JSON exports turned into Markdown notes
+//!
+//! Pages become notes with their blocks as nested lists. Daily pages go to
+//! the daily notes folder, and `((block refs))` become `[[note#^id]]` links
+//! to the referenced block, which gets a `^id` marker.
+
+use chrono::NaiveDate;
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Export {
+    /// Roam: a list of pages
+    Roam(Vec<Page>),
+    /// Logseq: pages under `blocks`
+    Logseq { blocks: Vec<Page> },
+}
+
+#[derive(Debug, Deserialize)]
+struct Page {
+    #[serde(alias = "page-name")]
+    title: String,
+    #[serde(default)]
+    children: Vec<Block>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Block {
+    #[serde(default, alias = "content")]
+    string: String,
+    #[serde(alias = "id")]
+    uid: Option<String>,
+    #[serde(default)]
+    children: Vec<Block>,
+}
+
+/// A page converted to a note
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvertedPage {
+    /// Vault-relative path
+    pub path: String,
+    pub content: String,
+}
+
+static BLOCK_EMBED_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\{\s*(?:\[\[)?embed(?:\]\])?\s*:\s*\(\(([\w-]+)\)\)\s*\}\}")
+        .expect("Invalid block embed regex")
+});
+
+static BLOCK_REF_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\(\(([\w-]+)\)\)").expect("Invalid block ref regex"));
+
+static TAG_LINK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"#\[\[([^\[\]]+)\]\]").expect("Invalid tag link regex"));
+
+static PAGE_LINK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[\[([^\[\]|#]+)\]\]").expect("Invalid page link regex"));
+
+static TODO_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:\{\{\[\[(TODO|DONE)\]\]\}\}|(TODO|DONE|LATER|NOW) )\s*")
+        .expect("Invalid todo regex")
+});
+
+static ITALIC_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"__([^_]+)__").expect("Invalid italic regex"));
+
+static HIGHLIGHT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\^\^([^^]+)\^\^").expect("Invalid highlight regex"));
+
+static ORDINAL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d+)(?:st|nd|rd|th)\b").expect("Invalid ordinal regex"));
+
+/// Convert a Roam or Logseq JSON export. Pages go to `folder`, except daily
+/// pages, which go where `daily_path` puts notes for their date.
+pub fn convert_roam_export(
+    json: &str,
+    folder: &str,
+    daily_path: impl Fn(NaiveDate) -> String,
+) -> serde_json::Result<Vec<ConvertedPage>> {
+    let pages = match serde_json::from_str(json)? {
+        Export::Roam(pages) | Export::Logseq { blocks: pages } => pages,
+    };
+    let folder = folder.trim_matches('/');
+
+    // Link targets of pages, by lowercased title
+    let mut targets = HashMap::new();
+    for page in &pages {
+        let path = match daily_page_date(&page.title) {
+            Some(date) => daily_path(date),
+            None if folder.is_empty() => format!("{}.md", page_file_name(&page.title)),
+            None => format!("{}/{}.md", folder, page_file_name(&page.title)),
+        };
+        targets.insert(page.title.to_lowercase(), path);
+    }
+
+    // The page each block is on, and which blocks are referenced
+    let mut block_pages = HashMap::new();
+    let mut referenced = HashSet::new();
+    for page in &pages {
+        let target = &targets[&page.title.to_lowercase()];
+        let mut stack: Vec<&Block> = page.children.iter().collect();
+        while let Some(block) = stack.pop() {
+            if let Some(uid) = &block.uid {
+                block_pages.insert(uid.clone(), target.trim_end_matches(".md").to_string());
+            }
+            for captures in BLOCK_REF_RE.captures_iter(&block.string) {
+                referenced.insert(captures[1].to_string());
+            }
+            stack.extend(&block.children);
+        }
+    }
+
+    let converter = Converter {
+        targets: &targets,
+        block_pages: &block_pages,
+        referenced: &referenced,
+    };
+    Ok(pages
+        .iter()
+        .map(|page| {
+            let mut content = format!("# {}\n\n", page.title);
+            for block in &page.children {
+                converter.render_block(block, 0, &mut content);
+            }
+            ConvertedPage {
+                path: targets[&page.title.to_lowercase()].clone(),
+                content,
+            }
+        })
+        .collect())
+}
+
+struct Converter<'a> {
+    targets: &'a HashMap<String, String>,
+    block_pages: &'a HashMap<String, String>,
+    referenced: &'a HashSet<String>,
+}
+
+impl Converter<'_> {
+    fn render_block(&self, block: &Block, depth: usize, out: &mut String) {
+        let indent = "  ".repeat(depth);
+        let text = self.convert_text(&block.string);
+        let mut lines = text.lines().filter(|l| !l.trim_start().starts_with("id:: "));
+
+        out.push_str(&format!("{}- {}", indent, lines.next().unwrap_or_default()));
+        if let Some(uid) = block.uid.as_ref().filter(|uid| self.referenced.contains(*uid)) {
+            out.push_str(&format!(" ^{}", uid));
+        }
+        out.push('\n');
+        for line in lines {
+            out.push_str(&format!("{}  {}\n", indent, line));
+        }
+
+        for child in &block.children {
+            self.render_block(child, depth + 1, out);
+        }
+    }
+
+    fn convert_text(&self, text: &str) -> String {
+        let text = TODO_RE.replace(text, |c: &Captures| {
+            match c.get(1).or(c.get(2)).map_or("", |m| m.as_str()) {
+                "DONE" => "[x] ",
+                _ => "[ ] ",
+            }
+        });
+        let text = TAG_LINK_RE.replace_all(&text, "[[$1]]");
+        let text = PAGE_LINK_RE.replace_all(&text, |c: &Captures| {
+            let title = &c[1];
+            match self.targets.get(&title.to_lowercase()) {
+                Some(path) if path.trim_end_matches(".md") != title => {
+                    format!("[[{}|{}]]", path.trim_end_matches(".md"), title)
+                }
+                _ => c[0].to_string(),
+            }
+        });
+        let block_link = |c: &Captures, prefix: &str| match self.block_pages.get(&c[1]) {
+            Some(page) => format!("{}[[{}#^{}]]", prefix, page, &c[1]),
+            None => c[0].to_string(),
+        };
+        let text = BLOCK_EMBED_RE.replace_all(&text, |c: &Captures| block_link(c, "!"));
+        let text = BLOCK_REF_RE.replace_all(&text, |c: &Captures| block_link(c, ""));
+        let text = ITALIC_RE.replace_all(&text, "*$1*");
+        HIGHLIGHT_RE.replace_all(&text, "==$1==").to_string()
+    }
+}
+
+/// Date of a daily page, from titles like `March 9th, 2026` (Roam),
+/// `Mar 9th, 2026` (Logseq) or `2026-03-09`
+fn daily_page_date(title: &str) -> Option<NaiveDate> {
+    let title = ORDINAL_RE.replace(title.trim(), "$1");
+    ["%B %d, %Y", "%Y-%m-%d"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(&title, format).ok())
+}
+
+/// A page title as a file name. `/` in titles (Roam namespaces) makes
+/// folders; characters file systems reject become `-`.
+pub(crate) fn page_file_name(title: &str) -> String {
+    let parts: Vec<String> = title
+        .split('/')
+        .map(|part| {
+            part.chars()
+                .map(|c| if r#"\:*?"<>|"#.contains(c) { '-' } else { c })
+                .collect::<String>()
+                .trim_matches(['.', ' '])
+                .to_string()
+        })
+        .filter(|part| !part.is_empty())
+        .collect();
+    if parts.is_empty() {
+        "Untitled".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daily(date: NaiveDate) -> String {
+        format!("daily/{}.md", date.format("%Y-%m-%d"))
+    }
+
+    #[test]
+    fn test_convert_roam_export() {
+        let json = r#"[
+            {"title": "Project Alpha", "children": [
+                {"string": "Goals", "uid": "goals1", "children": [
+                    {"string": "Ship __v1__ by ^^May^^", "uid": "ship01"}
+                ]},
+                {"string": "{{[[TODO]]}} Write docs for #[[Big Launch]]", "uid": "todo01"}
+            ]},
+            {"title": "March 9th, 2026", "children": [
+                {"string": "Reviewed [[Project Alpha]]: ((ship01))\nsecond line", "uid": "d1"},
+                {"string": "{{embed: ((goals1))}} and ((missing))", "uid": "d2"}
+            ]}
+        ]"#;
+        let pages = convert_roam_export(json, "roam/", daily).unwrap();
+
+        assert_eq!(pages[0].path, "roam/Project Alpha.md");
+        assert_eq!(
+            pages[0].content,
+            "# Project Alpha\n\n- Goals ^goals1\n  - Ship *v1* by ==May== ^ship01\n\
+             - [ ] Write docs for [[Big Launch]]\n"
+        );
+        assert_eq!(pages[1].path, "daily/2026-03-09.md");
+        assert_eq!(
+            pages[1].content,
+            "# March 9th, 2026\n\n\
+             - Reviewed [[roam/Project Alpha|Project Alpha]]: [[roam/Project Alpha#^ship01]]\n  \
+             second line\n\
+             - ![[roam/Project Alpha#^goals1]] and ((missing))\n"
+        );
+    }
+
+    #[test]
+    fn test_convert_logseq_export() {
+        let json = r#"{"version": 1, "blocks": [
+            {"page-name": "Mar 10th, 2026", "children": [
+                {"id": "6400-ab", "content": "DONE Call [[team/Sync]]\nid:: 6400-ab"},
+                {"id": "6400-cd", "content": "See ((6400-ab))"}
+            ]},
+            {"page-name": "team/Sync", "children": []}
+        ]}"#;
+        let pages = convert_roam_export(json, "", daily).unwrap();
+
+        assert_eq!(pages[0].path, "daily/2026-03-10.md");
+        assert_eq!(
+            pages[0].content,
+            "# Mar 10th, 2026\n\n- [x] Call [[team/Sync]] ^6400-ab\n\
+             - See [[daily/2026-03-10#^6400-ab]]\n"
+        );
+        assert_eq!(pages[1].path, "team/Sync.md");
+    }
+
+    #[test]
+    fn test_page_file_name() {
+        assert_eq!(page_file_name("Q&A: what?"), "Q&A- what-");
+        assert_eq!(page_file_name("/books//Dune./"), "books/Dune");
+        assert_eq!(page_file_name(".."), "Untitled");
+    }
+}
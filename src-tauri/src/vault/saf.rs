@@ -0,0 +1,182 @@
+//! Vaults in folders picked through Android's Storage Access Framework
+//!
+//! Files are reached through content URIs by the `SafPlugin` Kotlin
+//! plugin (`gen/android/.../SafPlugin.kt`), which also keeps the
+//! permission for a picked folder across restarts.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io;
+use std::path::PathBuf;
+use tauri::plugin::{Builder, PluginHandle, TauriPlugin};
+use tauri::{AppHandle, Manager, Runtime};
+
+use super::storage::VaultFs;
+
+const PLUGIN_PACKAGE: &str = "com.halvo.chronicle";
+
+/// Handle to the Kotlin side, managed as app state
+pub struct Saf<R: Runtime>(PluginHandle<R>);
+
+/// Tauri plugin registering `SafPlugin` with the Android activity
+pub fn init<R: Runtime>() -> TauriPlugin<R> {
+    Builder::new("saf")
+        .setup(|app, api| {
+            let handle = api.register_android_plugin(PLUGIN_PACKAGE, "SafPlugin")?;
+            app.manage(Saf(handle));
+            Ok(())
+        })
+        .build()
+}
+
+#[derive(Deserialize)]
+struct PickedFolder {
+    uri: String,
+}
+
+/// Let the user pick a folder with the system picker, returning its tree
+/// URI. Blocks until the picker closes.
+pub fn pick_folder<R: Runtime>(app: &AppHandle<R>) -> io::Result<String> {
+    let picked: PickedFolder = app
+        .state::<Saf<R>>()
+        .0
+        .run_mobile_plugin("pickFolder", ())
+        .map_err(io::Error::other)?;
+    Ok(picked.uri)
+}
+
+/// Folder in app storage holding the working copy of the vault at
+/// `tree_uri`
+pub fn mirror_dir<R: Runtime>(app: &AppHandle<R>, tree_uri: &str) -> io::Result<PathBuf> {
+    let id: String = Sha256::digest(tree_uri.as_bytes())
+        .iter()
+        .take(8)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let data_dir = app.path().app_data_dir().map_err(io::Error::other)?;
+    Ok(data_dir.join("vaults").join(id))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TreeArgs<'a> {
+    tree_uri: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FileArgs<'a> {
+    tree_uri: &'a str,
+    path: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WriteArgs<'a> {
+    tree_uri: &'a str,
+    path: &'a str,
+    /// Base64, since the bridge only carries JSON
+    contents: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RenameArgs<'a> {
+    tree_uri: &'a str,
+    from: &'a str,
+    to: &'a str,
+}
+
+#[derive(Deserialize)]
+struct FileList {
+    files: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct FileContents {
+    contents: String,
+}
+
+/// A vault in a folder granted through the Storage Access Framework
+pub struct SafFs<R: Runtime> {
+    handle: PluginHandle<R>,
+    tree_uri: String,
+}
+
+impl<R: Runtime> SafFs<R> {
+    pub fn new(app: &AppHandle<R>, tree_uri: String) -> Self {
+        Self {
+            handle: app.state::<Saf<R>>().0.clone(),
+            tree_uri,
+        }
+    }
+
+    fn call<T: serde::de::DeserializeOwned>(
+        &self,
+        command: &str,
+        args: impl Serialize,
+    ) -> io::Result<T> {
+        self.handle
+            .run_mobile_plugin(command, args)
+            .map_err(io::Error::other)
+    }
+
+    /// Call a command that returns nothing of interest
+    fn run(&self, command: &str, args: impl Serialize) -> io::Result<()> {
+        self.call::<serde_json::Value>(command, args).map(|_| ())
+    }
+}
+
+impl<R: Runtime> VaultFs for SafFs<R> {
+    fn list_files(&self) -> io::Result<Vec<String>> {
+        let list: FileList = self.call("listFiles", TreeArgs { tree_uri: &self.tree_uri })?;
+        Ok(list.files)
+    }
+
+    fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let file: FileContents = self.call(
+            "readFile",
+            FileArgs {
+                tree_uri: &self.tree_uri,
+                path,
+            },
+        )?;
+        BASE64
+            .decode(file.contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn write(&self, path: &str, contents: &[u8]) -> io::Result<()> {
+        self.run(
+            "writeFile",
+            WriteArgs {
+                tree_uri: &self.tree_uri,
+                path,
+                contents: BASE64.encode(contents),
+            },
+        )
+    }
+
+    fn remove(&self, path: &str) -> io::Result<()> {
+        self.run(
+            "deleteFile",
+            FileArgs {
+                tree_uri: &self.tree_uri,
+                path,
+            },
+        )
+    }
+
+    fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+        self.run(
+            "renameFile",
+            RenameArgs {
+                tree_uri: &self.tree_uri,
+                from,
+                to,
+            },
+        )
+    }
+}
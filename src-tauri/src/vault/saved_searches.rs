@@ -0,0 +1,58 @@
+//! Saved searches: named full-text queries narrowed by a smart folder
+//! filter, mirrored to `.chronicle/saved_searches.json`. Pinned ones are
+//! shown in the sidebar as smart lists.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::smart_folders::SmartFilter;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    /// Unique, ignoring case
+    pub name: String,
+    /// Full-text query; empty to list every note the filter matches
+    pub query: String,
+    #[serde(default)]
+    pub filter: SmartFilter,
+    #[serde(default)]
+    pub pinned: bool,
+    /// RFC 3339 timestamp of the last save
+    pub updated_at: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedSearchesFile {
+    saved_searches: Vec<SavedSearch>,
+}
+
+fn saved_searches_file(vault_path: &Path) -> PathBuf {
+    vault_path.join(".chronicle").join("saved_searches.json")
+}
+
+/// Searches saved in the vault, or `None` if it has no saved searches file
+pub fn read_saved_searches(vault_path: &Path) -> io::Result<Option<Vec<SavedSearch>>> {
+    let file = saved_searches_file(vault_path);
+    if !file.exists() {
+        return Ok(None);
+    }
+    let parsed: SavedSearchesFile = serde_json::from_slice(&fs::read(file)?)?;
+    Ok(Some(parsed.saved_searches))
+}
+
+/// Replace the vault's saved searches file
+pub fn write_saved_searches(vault_path: &Path, searches: &[SavedSearch]) -> io::Result<()> {
+    let file = saved_searches_file(vault_path);
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let content = serde_json::to_vec_pretty(&SavedSearchesFile {
+        saved_searches: searches.to_vec(),
+    })?;
+    let tmp = file.with_extension("tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, &file)
+}
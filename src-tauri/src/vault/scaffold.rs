@@ -0,0 +1,160 @@
+//! Starter layouts for new vaults
+//!
+//! A new vault gets the folders Chronicle files things into (inbox, daily
+//! notes, templates and attachments, as named in the config), a note
+//! template, a welcome note and a `.gitignore` that keeps the index out of
+//! version control. Templates add to that layout.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::models::{AppConfig, AttachmentLocation, NewNoteLocation};
+
+pub const WELCOME_NOTE: &str = "Welcome.md";
+
+/// Files Chronicle rebuilds or only uses locally
+const GITIGNORE: &str = "\
+# Chronicle's index and caches are rebuilt from the notes
+.chronicle/chronicle.db*
+.chronicle/cache/
+.chronicle/instance.lock
+";
+
+/// Starting layout for a new vault
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VaultTemplate {
+    /// Inbox, daily notes, templates and attachments
+    #[default]
+    Basic,
+    /// The basic folders plus Projects, Areas, Resources and Archive
+    Para,
+    /// The basic folders plus a folder of permanent notes named by ID
+    Zettelkasten,
+}
+
+impl VaultTemplate {
+    fn extra_folders(self) -> &'static [&'static str] {
+        match self {
+            VaultTemplate::Basic => &[],
+            VaultTemplate::Para => &["Projects", "Areas", "Resources", "Archive"],
+            VaultTemplate::Zettelkasten => &["zettel"],
+        }
+    }
+
+    /// Adjust the config to the layout: new notes go to the inbox, and with
+    /// the Zettelkasten template their filenames start with an ID
+    pub fn seed_config(self, config: &mut AppConfig) {
+        config.new_notes.location = NewNoteLocation::Folder;
+        config.new_notes.folder = config.inbox.folder.clone();
+        if self == VaultTemplate::Zettelkasten {
+            config.zettel.prefix_filenames = true;
+        }
+    }
+}
+
+/// Lay out a new vault in `root`, which must be missing or empty. Folders
+/// are named as in `config`. Returns the vault-relative paths of the notes
+/// written.
+pub fn scaffold_vault(
+    root: &Path,
+    template: VaultTemplate,
+    config: &AppConfig,
+) -> io::Result<Vec<String>> {
+    if root.exists() && fs::read_dir(root)?.next().is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} is not empty", root.display()),
+        ));
+    }
+
+    let mut folders = vec![
+        config.inbox.folder.as_str(),
+        config.daily_notes.folder.as_str(),
+        config.new_notes.templates_folder.as_str(),
+    ];
+    if config.attachments.location == AttachmentLocation::VaultFolder {
+        folders.push(config.attachments.folder.as_str());
+    }
+    folders.extend(template.extra_folders());
+    for folder in &folders {
+        fs::create_dir_all(root.join(folder))?;
+    }
+
+    let note_template = format!("{}/Note.md", config.new_notes.templates_folder);
+    let notes = [
+        (WELCOME_NOTE.to_string(), welcome_note(template, config)),
+        (note_template, "# {{title}}\n\nCreated {{date}}\n\n".to_string()),
+    ];
+    for (path, content) in &notes {
+        fs::write(root.join(path), content)?;
+    }
+    fs::write(root.join(".gitignore"), GITIGNORE)?;
+
+    Ok(notes.into_iter().map(|(path, _)| path).collect())
+}
+
+fn welcome_note(template: VaultTemplate, config: &AppConfig) -> String {
+    let mut note = format!(
+        "# Welcome\n\n\
+         This vault is a folder of Markdown notes. Link notes with `[[Note title]]` \
+         and tag them with `#tags`.\n\n\
+         - `{}/` collects quick captures and new notes until you file them\n\
+         - `{}/` holds a note for each day\n\
+         - `{}/` holds note templates\n",
+        config.inbox.folder, config.daily_notes.folder, config.new_notes.templates_folder
+    );
+    if config.attachments.location == AttachmentLocation::VaultFolder {
+        note.push_str(&format!("- `{}/` holds images and files\n", config.attachments.folder));
+    }
+    match template {
+        VaultTemplate::Basic => {}
+        VaultTemplate::Para => note.push_str(
+            "- `Projects/` for work with a deadline, `Areas/` for ongoing \
+             responsibilities, `Resources/` for reference and `Archive/` for \
+             anything inactive\n",
+        ),
+        VaultTemplate::Zettelkasten => note.push_str(
+            "- `zettel/` holds permanent notes, one idea each; new notes start \
+             with an ID you can link to\n",
+        ),
+    }
+    note
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_scaffold_vault() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("notes");
+        let config = AppConfig::default();
+
+        let notes = scaffold_vault(&root, VaultTemplate::Para, &config).unwrap();
+        assert_eq!(notes, vec!["Welcome.md", "templates/Note.md"]);
+        for folder in ["inbox", "daily", "templates", "attachments", "Projects", "Archive"] {
+            assert!(root.join(folder).is_dir(), "{} missing", folder);
+        }
+        let welcome = fs::read_to_string(root.join("Welcome.md")).unwrap();
+        assert!(welcome.contains("`Projects/`"));
+        assert!(fs::read_to_string(root.join(".gitignore")).unwrap().contains("chronicle.db"));
+
+        // Never over an existing vault
+        let err = scaffold_vault(&root, VaultTemplate::Basic, &config).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+    }
+
+    #[test]
+    fn test_seed_config() {
+        let mut config = AppConfig::default();
+        VaultTemplate::Zettelkasten.seed_config(&mut config);
+        assert_eq!(config.new_notes.location, NewNoteLocation::Folder);
+        assert_eq!(config.new_notes.folder, config.inbox.folder);
+        assert!(config.zettel.prefix_filenames);
+    }
+}
@@ -0,0 +1,129 @@
+//! Sidecar metadata for files that aren't notes
+//!
+//! A PDF, image or other attachment can be described by a YAML file next to
+//! it named after it, e.g. `report.pdf.meta.yaml`:
+//!
+//! ```yaml
+//! title: Quarterly report
+//! tags: [finance]
+//! note: |
+//!   Figures for [[Q3 planning]].
+//! ```
+//!
+//! Its keys are indexed like a note's frontmatter and `note` like its body,
+//! so the file shows up in search, tags and the graph under its own path.
+
+use std::path::{Path, PathBuf};
+
+use crate::vault::indexer::is_indexed_file;
+
+/// Appended to a file's name to name its sidecar
+pub const SIDECAR_SUFFIX: &str = ".meta.yaml";
+
+/// Sidecar key holding free-form notes about the file
+const NOTE_KEY: &str = "note";
+
+/// Where a file's sidecar would be
+pub fn sidecar_path(file: &Path) -> PathBuf {
+    let mut name = file.as_os_str().to_owned();
+    name.push(SIDECAR_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Whether a path names a sidecar
+pub fn is_sidecar(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix(SIDECAR_SUFFIX))
+        .is_some_and(|described| !described.is_empty())
+}
+
+/// The file a sidecar is named after, unless that is a note, canvas or
+/// drawing, which have metadata of their own. It may not exist.
+pub fn sidecar_target(sidecar: &Path) -> Option<PathBuf> {
+    if !is_sidecar(sidecar) {
+        return None;
+    }
+    let name = sidecar.as_os_str().to_str()?;
+    let file = PathBuf::from(name.strip_suffix(SIDECAR_SUFFIX)?);
+    (!is_indexed_file(&file)).then_some(file)
+}
+
+/// Whether a file is indexed through its sidecar: a file that isn't a
+/// note, canvas or drawing and has a sidecar next to it
+pub fn has_sidecar(file: &Path) -> bool {
+    !is_indexed_file(file) && !is_sidecar(file) && sidecar_path(file).is_file()
+}
+
+/// The file a sidecar describes, if it exists and is indexed through it
+pub fn described_file(sidecar: &Path) -> Option<PathBuf> {
+    sidecar_target(sidecar).filter(|file| file.is_file() && sidecar.is_file())
+}
+
+/// The sidecar as a Markdown note: its keys as frontmatter and its `note`
+/// as the body
+pub fn sidecar_markdown(yaml: &str) -> Result<String, String> {
+    let mut mapping: serde_yaml::Mapping = if yaml.trim().is_empty() {
+        serde_yaml::Mapping::new()
+    } else {
+        serde_yaml::from_str(yaml).map_err(|e| e.to_string())?
+    };
+    let body = match mapping.remove(NOTE_KEY) {
+        Some(serde_yaml::Value::String(note)) => note,
+        Some(serde_yaml::Value::Null) | None => String::new(),
+        Some(_) => return Err(format!("`{}` must be text", NOTE_KEY)),
+    };
+
+    if mapping.is_empty() {
+        return Ok(body);
+    }
+    let frontmatter = serde_yaml::to_string(&mapping).map_err(|e| e.to_string())?;
+    Ok(format!("---\n{}---\n\n{}", frontmatter, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::parser::parse_note;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sidecar_paths() {
+        let temp = TempDir::new().unwrap();
+        let pdf = temp.path().join("report.pdf");
+        let note = temp.path().join("note.md");
+        fs::write(&pdf, b"%PDF").unwrap();
+        fs::write(&note, "# Note").unwrap();
+        assert!(!has_sidecar(&pdf));
+
+        fs::write(sidecar_path(&pdf), "title: Report").unwrap();
+        fs::write(sidecar_path(&note), "title: Note").unwrap();
+        assert!(has_sidecar(&pdf));
+        assert_eq!(described_file(&sidecar_path(&pdf)), Some(pdf.clone()));
+        // Notes have frontmatter of their own
+        assert_eq!(described_file(&sidecar_path(&note)), None);
+        assert_eq!(sidecar_target(&sidecar_path(&note)), None);
+        assert_eq!(
+            sidecar_target(&temp.path().join("gone.png.meta.yaml")),
+            Some(temp.path().join("gone.png"))
+        );
+        assert_eq!(described_file(&temp.path().join("gone.png.meta.yaml")), None);
+        assert!(!is_sidecar(&temp.path().join(".meta.yaml")));
+    }
+
+    #[test]
+    fn test_sidecar_markdown() {
+        let yaml = "title: Quarterly report\ntags: [finance]\nnote: |\n  See [[Q3]].\n";
+        let markdown = sidecar_markdown(yaml).unwrap();
+        let parsed = parse_note(&markdown, "report.pdf");
+        assert_eq!(parsed.title, "Quarterly report");
+        assert_eq!(parsed.frontmatter.unwrap().tags, vec!["finance"]);
+        assert_eq!(parsed.links[0].target, "Q3");
+
+        assert_eq!(sidecar_markdown("note: Just text").unwrap(), "Just text");
+        assert_eq!(sidecar_markdown("").unwrap(), "");
+        assert!(sidecar_markdown("note: [1, 2]").is_err());
+        assert!(sidecar_markdown("tags: [unclosed").is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Smart folders: saved note filters, mirrored to
+//! `.chronicle/smart_folders.json` so they travel with the vault like
+//! workspaces do
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A named, stored filter whose notes are worked out each time it's opened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SmartFolder {
+    pub id: i64,
+    pub name: String,
+    pub filter: SmartFilter,
+    /// RFC 3339 timestamp of the last save
+    pub updated_at: String,
+}
+
+/// Criteria a note must meet to be in a smart folder; unset fields don't
+/// filter. Dates are `YYYY-MM-DD` and inclusive.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmartFilter {
+    /// Full-text search the note must match
+    pub query: Option<String>,
+    /// Tags the note must all have
+    pub all_tags: Vec<String>,
+    /// Tags of which the note must have at least one
+    pub any_tags: Vec<String>,
+    /// Tags the note must not have
+    pub without_tags: Vec<String>,
+    /// Only notes without any tags
+    pub untagged: bool,
+    /// Vault-relative path globs, of which the note must match one. `*`
+    /// also matches `/`, so `projects/*` covers subfolders.
+    pub paths: Vec<String>,
+    /// Frontmatter predicates the note must all satisfy
+    pub properties: Vec<PropertyPredicate>,
+    pub created_after: Option<String>,
+    pub created_before: Option<String>,
+    pub modified_after: Option<String>,
+    pub modified_before: Option<String>,
+}
+
+/// A test on one frontmatter property
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PropertyPredicate {
+    pub key: String,
+    pub op: PropertyOp,
+    /// Compared value; ignored by `exists` and `missing`
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PropertyOp {
+    /// The value (or a list item) equals `value`, ignoring case
+    Equals,
+    /// No value or list item equals `value`, ignoring case
+    NotEquals,
+    /// The value (or a list item) contains `value`, ignoring case
+    Contains,
+    Exists,
+    Missing,
+}
+
+impl SmartFilter {
+    /// Check the dates and that comparisons have a value
+    pub fn validate(&self) -> Result<(), String> {
+        let dates = [
+            &self.created_after,
+            &self.created_before,
+            &self.modified_after,
+            &self.modified_before,
+        ];
+        for date in dates.into_iter().flatten() {
+            if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+                return Err(format!("Invalid date: {}", date));
+            }
+        }
+        for predicate in &self.properties {
+            if predicate.key.trim().is_empty() {
+                return Err("Property predicates need a key".to_string());
+            }
+            let compares = !matches!(predicate.op, PropertyOp::Exists | PropertyOp::Missing);
+            if compares && predicate.value.is_none() {
+                return Err(format!("Property predicate on {} needs a value", predicate.key));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SmartFoldersFile {
+    smart_folders: Vec<SmartFolder>,
+}
+
+fn smart_folders_file(vault_path: &Path) -> PathBuf {
+    vault_path.join(".chronicle").join("smart_folders.json")
+}
+
+/// Smart folders saved in the vault, or `None` if it has no smart folders file
+pub fn read_smart_folders(vault_path: &Path) -> io::Result<Option<Vec<SmartFolder>>> {
+    let file = smart_folders_file(vault_path);
+    if !file.exists() {
+        return Ok(None);
+    }
+    let parsed: SmartFoldersFile = serde_json::from_slice(&fs::read(file)?)?;
+    Ok(Some(parsed.smart_folders))
+}
+
+/// Replace the vault's smart folders file
+pub fn write_smart_folders(vault_path: &Path, folders: &[SmartFolder]) -> io::Result<()> {
+    let file = smart_folders_file(vault_path);
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let content = serde_json::to_vec_pretty(&SmartFoldersFile {
+        smart_folders: folders.to_vec(),
+    })?;
+    let tmp = file.with_extension("tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, &file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_filter() {
+        let mut filter = SmartFilter {
+            modified_after: Some("2025-01-01".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.validate().is_ok());
+
+        filter.created_before = Some("January".to_string());
+        assert!(filter.validate().is_err());
+
+        filter.created_before = None;
+        filter.properties.push(PropertyPredicate {
+            key: "status".to_string(),
+            op: PropertyOp::Equals,
+            value: None,
+        });
+        assert!(filter.validate().is_err());
+        filter.properties[0].op = PropertyOp::Exists;
+        assert!(filter.validate().is_ok());
+    }
+}
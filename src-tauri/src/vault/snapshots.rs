@@ -0,0 +1,277 @@
+//! Local version history of notes, for vaults that aren't synced with git
+//!
+//! Versions are stored once per distinct content under
+//! `.chronicle/snapshots/objects/`, named by their SHA-256 and compressed
+//! with zlib. Each note has a log under `.chronicle/snapshots/notes/`
+//! listing its versions.
+
+use chrono::{DateTime, Duration, Local};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+const LOG_EXTENSION: &str = "json";
+
+/// A stored version of a note
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoteSnapshot {
+    /// SHA-256 of the content, which `restore_snapshot` takes
+    pub id: String,
+    /// RFC 3339 timestamp of when the version was replaced by a save
+    pub taken_at: String,
+    /// Content size in bytes
+    pub size: u64,
+}
+
+/// Snapshot store under `.chronicle/snapshots/` in the vault
+pub struct SnapshotStore {
+    dir: PathBuf,
+}
+
+impl SnapshotStore {
+    pub fn new(vault_path: &Path) -> Self {
+        Self {
+            dir: vault_path.join(".chronicle").join("snapshots"),
+        }
+    }
+
+    fn notes_dir(&self) -> PathBuf {
+        self.dir.join("notes")
+    }
+
+    /// Log of a note's versions. The note path is escaped into a single
+    /// flat filename.
+    fn log_file(&self, note_path: &str) -> PathBuf {
+        let escaped = note_path
+            .replace('%', "%25")
+            .replace('/', "%2F")
+            .replace('\\', "%5C");
+        self.notes_dir().join(format!("{}.{}", escaped, LOG_EXTENSION))
+    }
+
+    fn object_file(&self, id: &str) -> PathBuf {
+        let (fanout, rest) = id.split_at(2.min(id.len()));
+        self.dir.join("objects").join(fanout).join(rest)
+    }
+
+    /// Versions of a note, newest first
+    pub fn list(&self, note_path: &str) -> io::Result<Vec<NoteSnapshot>> {
+        let mut snapshots = self.read_log(&self.log_file(note_path))?;
+        snapshots.reverse();
+        Ok(snapshots)
+    }
+
+    /// Versions of a note, oldest first as stored
+    fn read_log(&self, file: &Path) -> io::Result<Vec<NoteSnapshot>> {
+        if !file.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_slice(&fs::read(file)?)?)
+    }
+
+    fn write_log(&self, note_path: &str, snapshots: &[NoteSnapshot]) -> io::Result<()> {
+        let file = self.log_file(note_path);
+        fs::create_dir_all(self.notes_dir())?;
+        let tmp = file.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(snapshots)?)?;
+        fs::rename(&tmp, &file)
+    }
+
+    /// Keep `content` as a version of the note unless the newest version
+    /// is younger than `debounce` or has the same content. Returns the new
+    /// snapshot, if one was taken.
+    ///
+    /// Called with the content a save is about to replace, so each burst
+    /// of saves leaves behind the version from before it started.
+    pub fn record(
+        &self,
+        note_path: &str,
+        content: &str,
+        debounce: Duration,
+        keep: usize,
+    ) -> io::Result<Option<NoteSnapshot>> {
+        let mut snapshots = self.read_log(&self.log_file(note_path))?;
+        let id = content_id(content);
+
+        if let Some(last) = snapshots.last() {
+            let recent = DateTime::parse_from_rfc3339(&last.taken_at)
+                .is_ok_and(|taken| Local::now().signed_duration_since(taken) < debounce);
+            if recent || last.id == id {
+                return Ok(None);
+            }
+        }
+
+        self.write_object(&id, content)?;
+        let snapshot = NoteSnapshot {
+            id,
+            taken_at: Local::now().to_rfc3339(),
+            size: content.len() as u64,
+        };
+        snapshots.push(snapshot.clone());
+
+        let pruned = keep > 0 && snapshots.len() > keep;
+        if pruned {
+            snapshots.drain(..snapshots.len() - keep);
+        }
+        self.write_log(note_path, &snapshots)?;
+        if pruned {
+            self.collect_garbage()?;
+        }
+
+        Ok(Some(snapshot))
+    }
+
+    /// Content of one of a note's versions
+    pub fn read(&self, note_path: &str, id: &str) -> io::Result<Option<String>> {
+        if !self.list(note_path)?.iter().any(|s| s.id == id) {
+            return Ok(None);
+        }
+        let mut content = String::new();
+        ZlibDecoder::new(fs::File::open(self.object_file(id))?).read_to_string(&mut content)?;
+        Ok(Some(content))
+    }
+
+    /// Carry a note's versions over to its new path
+    pub fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+        let old = self.log_file(from);
+        if !old.exists() {
+            return Ok(());
+        }
+        let mut snapshots = self.read_log(&self.log_file(to))?;
+        snapshots.extend(self.read_log(&old)?);
+        snapshots.sort_by(|a, b| a.taken_at.cmp(&b.taken_at));
+        self.write_log(to, &snapshots)?;
+        fs::remove_file(old)
+    }
+
+    fn write_object(&self, id: &str, content: &str) -> io::Result<()> {
+        let file = self.object_file(id);
+        if file.exists() {
+            return Ok(());
+        }
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content.as_bytes())?;
+        let tmp = file.with_extension("tmp");
+        fs::write(&tmp, encoder.finish()?)?;
+        fs::rename(&tmp, &file)
+    }
+
+    /// Delete stored contents no note's log refers to any more
+    fn collect_garbage(&self) -> io::Result<()> {
+        let mut referenced = HashSet::new();
+        for entry in fs::read_dir(self.notes_dir())? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == LOG_EXTENSION) {
+                referenced.extend(self.read_log(&path)?.into_iter().map(|s| s.id));
+            }
+        }
+
+        let objects = self.dir.join("objects");
+        for fanout in fs::read_dir(&objects)? {
+            let fanout = fanout?.path();
+            let Some(prefix) = fanout.file_name().map(|n| n.to_string_lossy().to_string()) else {
+                continue;
+            };
+            for object in fs::read_dir(&fanout)? {
+                let object = object?.path();
+                let Some(rest) = object.file_name().map(|n| n.to_string_lossy().to_string())
+                else {
+                    continue;
+                };
+                if !referenced.contains(&format!("{}{}", prefix, rest)) {
+                    fs::remove_file(&object)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Hex SHA-256 of a note's content
+fn content_id(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_and_read() {
+        let temp = TempDir::new().unwrap();
+        let store = SnapshotStore::new(temp.path());
+        assert!(store.list("notes/a.md").unwrap().is_empty());
+
+        let first = store
+            .record("notes/a.md", "# One", Duration::zero(), 0)
+            .unwrap()
+            .unwrap();
+        // Unchanged content is not stored again
+        assert!(store.record("notes/a.md", "# One", Duration::zero(), 0).unwrap().is_none());
+        let second = store
+            .record("notes/a.md", "# Two", Duration::zero(), 0)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(store.list("notes/a.md").unwrap(), vec![second.clone(), first.clone()]);
+        assert_eq!(store.read("notes/a.md", &first.id).unwrap().as_deref(), Some("# One"));
+        assert_eq!(store.read("notes/a.md", &second.id).unwrap().as_deref(), Some("# Two"));
+        // Versions are only readable through the note they belong to
+        assert_eq!(store.read("notes/b.md", &first.id).unwrap(), None);
+    }
+
+    #[test]
+    fn test_debounce_window() {
+        let temp = TempDir::new().unwrap();
+        let store = SnapshotStore::new(temp.path());
+        let window = Duration::minutes(10);
+
+        assert!(store.record("a.md", "v1", window, 0).unwrap().is_some());
+        assert!(store.record("a.md", "v2", window, 0).unwrap().is_none());
+        assert_eq!(store.list("a.md").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_pruning_removes_unreferenced_contents() {
+        let temp = TempDir::new().unwrap();
+        let store = SnapshotStore::new(temp.path());
+
+        let shared = store.record("a.md", "shared", Duration::zero(), 2).unwrap().unwrap();
+        store.record("b.md", "shared", Duration::zero(), 2).unwrap();
+        store.record("a.md", "v2", Duration::zero(), 2).unwrap();
+        store.record("a.md", "v3", Duration::zero(), 2).unwrap();
+
+        assert_eq!(store.list("a.md").unwrap().len(), 2);
+        assert_eq!(store.read("a.md", &shared.id).unwrap(), None);
+        // Still in use by the other note
+        assert_eq!(store.read("b.md", &shared.id).unwrap().as_deref(), Some("shared"));
+
+        store.record("b.md", "b2", Duration::zero(), 1).unwrap();
+        assert!(!store.object_file(&shared.id).exists());
+    }
+
+    #[test]
+    fn test_rename_keeps_history() {
+        let temp = TempDir::new().unwrap();
+        let store = SnapshotStore::new(temp.path());
+        let snapshot = store.record("a.md", "old", Duration::zero(), 0).unwrap().unwrap();
+
+        store.rename("a.md", "folder/a.md").unwrap();
+        assert!(store.list("a.md").unwrap().is_empty());
+        assert_eq!(store.list("folder/a.md").unwrap(), vec![snapshot]);
+    }
+}
@@ -0,0 +1,211 @@
+//! Storage behind a vault
+//!
+//! On desktop a vault is a plain folder and everything works on its path.
+//! On Android, folders picked with the system file picker are only
+//! reachable through the Storage Access Framework (see `super::saf`), so
+//! the vault is mirrored into app storage, where the index, watcher and
+//! every command work as usual, and changes are written back through
+//! `VaultFs`.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::indexer::walkdir;
+use super::watcher::VaultEvent;
+
+/// File access to where a vault really lives. Paths are vault-relative
+/// and use `/`.
+pub trait VaultFs: Send + Sync {
+    /// Every file in the vault, skipping hidden files and folders
+    fn list_files(&self) -> io::Result<Vec<String>>;
+    fn read(&self, path: &str) -> io::Result<Vec<u8>>;
+    /// Create or replace a file, creating its folders as needed
+    fn write(&self, path: &str, contents: &[u8]) -> io::Result<()>;
+    /// Delete a file. Missing files are not an error.
+    fn remove(&self, path: &str) -> io::Result<()>;
+    fn rename(&self, from: &str, to: &str) -> io::Result<()>;
+}
+
+/// A vault in a folder on the local file system
+pub struct LocalFs {
+    root: PathBuf,
+}
+
+impl LocalFs {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path(&self, path: &str) -> io::Result<PathBuf> {
+        relative_path(&self.root, path)
+    }
+}
+
+impl VaultFs for LocalFs {
+    fn list_files(&self) -> io::Result<Vec<String>> {
+        Ok(walkdir(&self.root)?
+            .iter()
+            .filter_map(|file| to_relative(&self.root, file))
+            .collect())
+    }
+
+    fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.path(path)?)
+    }
+
+    fn write(&self, path: &str, contents: &[u8]) -> io::Result<()> {
+        let file = self.path(path)?;
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(file, contents)
+    }
+
+    fn remove(&self, path: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(path)?) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+        let to = self.path(to)?;
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(self.path(from)?, to)
+    }
+}
+
+/// Join a vault-relative path onto `root`, refusing paths that would leave it
+fn relative_path(root: &Path, path: &str) -> io::Result<PathBuf> {
+    let rel = Path::new(path);
+    let inside = rel
+        .components()
+        .all(|c| matches!(c, std::path::Component::Normal(_)));
+    if !inside || path.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Path is outside the vault: {}", path),
+        ));
+    }
+    Ok(root.join(rel))
+}
+
+fn to_relative(root: &Path, file: &Path) -> Option<String> {
+    let rel = file.strip_prefix(root).ok()?;
+    Some(
+        rel.components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+/// Make `local_root` a copy of the vault in `source`, returning the number
+/// of files copied. Files the vault no longer has are removed from the
+/// copy; hidden folders such as `.chronicle` are left alone.
+pub fn mirror_vault(source: &dyn VaultFs, local_root: &Path) -> io::Result<usize> {
+    fs::create_dir_all(local_root)?;
+    let files = source.list_files()?;
+
+    for path in &files {
+        let local = relative_path(local_root, path)?;
+        let contents = source.read(path)?;
+        // Unchanged files are skipped so their modification times, which
+        // the indexer compares, stay put
+        if fs::read(&local).is_ok_and(|existing| existing == contents) {
+            continue;
+        }
+        if let Some(parent) = local.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(local, contents)?;
+    }
+
+    for local in walkdir(local_root)? {
+        let gone = to_relative(local_root, &local).is_some_and(|rel| !files.contains(&rel));
+        if gone {
+            fs::remove_file(local)?;
+        }
+    }
+
+    Ok(files.len())
+}
+
+/// Write a change seen in the mirror at `local_root` back to the vault
+pub fn push_change(target: &dyn VaultFs, local_root: &Path, event: &VaultEvent) -> io::Result<()> {
+    let rel = |path: &Path| {
+        to_relative(local_root, path).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("Not in the vault: {:?}", path))
+        })
+    };
+
+    match event {
+        VaultEvent::Created(path) | VaultEvent::Modified(path) => {
+            // Already gone again, which a later event will report
+            match fs::read(path) {
+                Ok(contents) => target.write(&rel(path)?, &contents),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(e),
+            }
+        }
+        VaultEvent::Deleted(path) => target.remove(&rel(path)?),
+        VaultEvent::Renamed { from, to } => target.rename(&rel(from)?, &rel(to)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_local_fs() {
+        let temp = TempDir::new().unwrap();
+        let vault = LocalFs::new(temp.path().to_path_buf());
+
+        vault.write("notes/a.md", b"# A").unwrap();
+        vault.write(".chronicle/x.json", b"{}").unwrap();
+        assert_eq!(vault.list_files().unwrap(), vec!["notes/a.md"]);
+
+        vault.rename("notes/a.md", "archive/a.md").unwrap();
+        assert_eq!(vault.read("archive/a.md").unwrap(), b"# A");
+        vault.remove("archive/a.md").unwrap();
+        vault.remove("archive/a.md").unwrap();
+        assert!(vault.list_files().unwrap().is_empty());
+
+        assert!(vault.read("../outside.md").is_err());
+    }
+
+    #[test]
+    fn test_mirror_and_push() {
+        let temp = TempDir::new().unwrap();
+        let source = LocalFs::new(temp.path().join("source"));
+        let mirror = temp.path().join("mirror");
+        source.write("a.md", b"# A").unwrap();
+        source.write("img/photo.png", &[1, 2, 3]).unwrap();
+        fs::create_dir_all(mirror.join(".chronicle")).unwrap();
+        fs::write(mirror.join(".chronicle/chronicle.db"), "index").unwrap();
+        fs::write(mirror.join("stale.md"), "gone from the vault").unwrap();
+
+        assert_eq!(mirror_vault(&source, &mirror).unwrap(), 2);
+        assert_eq!(fs::read(mirror.join("img/photo.png")).unwrap(), [1, 2, 3]);
+        assert!(!mirror.join("stale.md").exists());
+        assert!(mirror.join(".chronicle/chronicle.db").exists());
+
+        fs::write(mirror.join("b.md"), "# B").unwrap();
+        push_change(&source, &mirror, &VaultEvent::Created(mirror.join("b.md"))).unwrap();
+        let renamed = VaultEvent::Renamed {
+            from: mirror.join("a.md"),
+            to: mirror.join("done/a.md"),
+        };
+        push_change(&source, &mirror, &renamed).unwrap();
+        push_change(&source, &mirror, &VaultEvent::Deleted(mirror.join("img/photo.png"))).unwrap();
+
+        let mut files = source.list_files().unwrap();
+        files.sort();
+        assert_eq!(files, vec!["b.md", "done/a.md"]);
+    }
+}
@@ -0,0 +1,112 @@
+//! Tag colors, icons and descriptions, mirrored to `.chronicle/tags.json`
+//! so the tag pane and graph look the same on every device
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// How a tag is shown; unset fields use the app's defaults
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TagMeta {
+    /// Hex color, `#rgb` or `#rrggbb`
+    pub color: Option<String>,
+    /// An emoji or icon name
+    pub icon: Option<String>,
+    pub description: Option<String>,
+}
+
+/// A tag's name with how it's shown
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StyledTag {
+    pub name: String,
+    #[serde(flatten)]
+    pub meta: TagMeta,
+}
+
+impl TagMeta {
+    /// Trim the fields, dropping empty ones, and check the color
+    pub fn normalized(self) -> Result<Self, String> {
+        let clean = |field: Option<String>| {
+            field.map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+        };
+        let meta = Self {
+            color: clean(self.color),
+            icon: clean(self.icon),
+            description: clean(self.description),
+        };
+        if let Some(color) = &meta.color {
+            let hex = color.strip_prefix('#').unwrap_or_default();
+            let valid = matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit());
+            if !valid {
+                return Err(format!("Invalid tag color: {}", color));
+            }
+        }
+        Ok(meta)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.color.is_none() && self.icon.is_none() && self.description.is_none()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TagsFile {
+    tags: Vec<StyledTag>,
+}
+
+fn tags_file(vault_path: &Path) -> PathBuf {
+    vault_path.join(".chronicle").join("tags.json")
+}
+
+/// Tag styles saved in the vault, or `None` if it has no tags file
+pub fn read_tag_meta(vault_path: &Path) -> io::Result<Option<Vec<StyledTag>>> {
+    let file = tags_file(vault_path);
+    if !file.exists() {
+        return Ok(None);
+    }
+    let parsed: TagsFile = serde_json::from_slice(&fs::read(file)?)?;
+    Ok(Some(parsed.tags))
+}
+
+/// Replace the vault's tags file
+pub fn write_tag_meta(vault_path: &Path, tags: &[StyledTag]) -> io::Result<()> {
+    let file = tags_file(vault_path);
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let content = serde_json::to_vec_pretty(&TagsFile {
+        tags: tags.to_vec(),
+    })?;
+    let tmp = file.with_extension("tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, &file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tag_meta() {
+        let meta = TagMeta {
+            color: Some(" #E94560 ".to_string()),
+            icon: Some("".to_string()),
+            description: Some("Things to do".to_string()),
+        };
+        let meta = meta.normalized().unwrap();
+        assert_eq!(meta.color.as_deref(), Some("#E94560"));
+        assert_eq!(meta.icon, None);
+        assert!(!meta.is_empty());
+
+        for color in ["red", "#12345", "#ggg"] {
+            let meta = TagMeta {
+                color: Some(color.to_string()),
+                ..Default::default()
+            };
+            assert!(meta.normalized().is_err());
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Markdown task extraction
+//!
+//! Tasks are checklist items (`- [ ] ...` / `- [x] ...`). A due date can be
+//! given as `due:YYYY-MM-DD` or `📅 YYYY-MM-DD`, and inline `#tags` are
+//! picked up for filtering.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+use super::parser::parse_frontmatter;
+
+static TASK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*[-*+]\s+\[([ xX])\]\s+(.*)$").expect("Invalid task regex")
+});
+
+static DUE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:\bdue:|📅\s*)(\d{4}-\d{2}-\d{2})").expect("Invalid due date regex")
+});
+
+static INLINE_TAG_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|\s)#([\w/-]+)").expect("Invalid inline tag regex")
+});
+
+/// Checklist item found in a note
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedTask {
+    /// 1-indexed line of the task
+    pub line_number: usize,
+    /// Task text after the checkbox
+    pub text: String,
+    pub completed: bool,
+    /// Due date (YYYY-MM-DD)
+    pub due: Option<String>,
+    /// Inline tags, lowercased
+    pub tags: Vec<String>,
+}
+
+/// Extract checklist items, skipping frontmatter and fenced code
+pub fn extract_tasks(content: &str) -> Vec<ExtractedTask> {
+    let (_, body_start) = parse_frontmatter(content);
+    let first_line = content[..body_start].matches('\n').count();
+
+    let mut tasks = Vec::new();
+    let mut in_fence = false;
+
+    for (i, line) in content[body_start..].lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let Some(captures) = TASK_RE.captures(line) else {
+            continue;
+        };
+        let text = captures[2].trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+
+        tasks.push(ExtractedTask {
+            line_number: first_line + i + 1,
+            completed: &captures[1] != " ",
+            due: DUE_RE.captures(&text).map(|c| c[1].to_string()),
+            tags: INLINE_TAG_RE
+                .captures_iter(&text)
+                .map(|c| c[1].to_lowercase())
+                .collect(),
+            text,
+        });
+    }
+
+    tasks
+}
+
+/// Flip the checkbox of the task on `line_number` (1-indexed). Returns
+/// `None` if that line is not a task.
+pub fn toggle_task_line(content: &str, line_number: usize) -> Option<String> {
+    let (_, body_start) = parse_frontmatter(content);
+    let task = extract_tasks(content)
+        .into_iter()
+        .find(|t| t.line_number == line_number)?;
+
+    let mut offset = 0;
+    for (i, line) in content.split_inclusive('\n').enumerate() {
+        if i + 1 == line_number && offset >= body_start {
+            // The checkbox is the first "[" on a task line
+            let bracket = offset + line.find('[')?;
+            let mark = if task.completed { " " } else { "x" };
+
+            let mut updated = content.to_string();
+            updated.replace_range(bracket + 1..bracket + 2, mark);
+            return Some(updated);
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_tasks() {
+        let content = "---\ntags: [work]\n---\n# Todo\n\n- [ ] Write report due:2026-03-12 #work\n* [x] Call Sam 📅 2026-03-01\n- [] not a task\n```\n- [ ] in code\n```\n  - [X] Nested #Home/Chores";
+        let tasks = extract_tasks(content);
+
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(tasks[0].line_number, 6);
+        assert_eq!(tasks[0].text, "Write report due:2026-03-12 #work");
+        assert!(!tasks[0].completed);
+        assert_eq!(tasks[0].due.as_deref(), Some("2026-03-12"));
+        assert_eq!(tasks[0].tags, vec!["work"]);
+
+        assert!(tasks[1].completed);
+        assert_eq!(tasks[1].due.as_deref(), Some("2026-03-01"));
+
+        assert_eq!(tasks[2].line_number, 12);
+        assert!(tasks[2].completed);
+        assert_eq!(tasks[2].tags, vec!["home/chores"]);
+    }
+
+    #[test]
+    fn test_toggle_task_line() {
+        let content = "# Todo\r\n- [ ] One\r\n- [x] Two [a]\r\n";
+
+        let toggled = toggle_task_line(content, 2).unwrap();
+        assert_eq!(toggled, "# Todo\r\n- [x] One\r\n- [x] Two [a]\r\n");
+
+        let toggled = toggle_task_line(&toggled, 3).unwrap();
+        assert_eq!(toggled, "# Todo\r\n- [x] One\r\n- [ ] Two [a]\r\n");
+
+        assert_eq!(toggle_task_line(content, 1), None);
+        assert_eq!(toggle_task_line(content, 9), None);
+    }
+}
@@ -0,0 +1,259 @@
+//! Template placeholders shared by note and daily-note templates
+//!
+//! Placeholders are `{{name[:arg][|filter...]}}`:
+//!
+//! - `{{date}}`, `{{date:+7d}}`, `{{date:-1w:YYYY-MM-DD}}`, `{{date:%A}}`:
+//!   the template's date, shifted by days/weeks/months/years, in the default
+//!   or given format (strftime, or moment-style tokens such as `YYYY-MM-DD`)
+//! - `{{time}}`, `{{time:HH:mm}}`: current time
+//! - `{{title}}`: title of the note being created
+//! - `{{prompt:Project name}}`: answer the user gave when creating the note
+//!
+//! Filters: `slug`, `lower`, `upper`, `trim`. Unknown placeholders are left
+//! as they are.
+
+use chrono::{Duration, Months, NaiveDate, NaiveDateTime};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use crate::models::{validate_strftime, LocaleConfig};
+
+static PLACEHOLDER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\{([^{}]+)\}\}").expect("Invalid placeholder regex")
+});
+
+static OFFSET_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([+-])(\d+)([dwmy])$").expect("Invalid date offset regex")
+});
+
+/// Moment-style tokens, longest first so `MMMM` wins over `MM`
+const DATE_TOKENS: &[(&str, &str)] = &[
+    ("YYYY", "%Y"),
+    ("YY", "%y"),
+    ("MMMM", "%B"),
+    ("MMM", "%b"),
+    ("MM", "%m"),
+    ("M", "%-m"),
+    ("dddd", "%A"),
+    ("ddd", "%a"),
+    ("DD", "%d"),
+    ("D", "%-d"),
+    ("HH", "%H"),
+    ("hh", "%I"),
+    ("mm", "%M"),
+    ("ss", "%S"),
+    ("A", "%p"),
+];
+
+/// Values placeholders are filled from
+pub struct TemplateContext<'a> {
+    /// Date `{{date}}` refers to (today, or the daily note's day)
+    pub date: NaiveDate,
+    pub now: NaiveDateTime,
+    /// strftime format for a bare `{{date}}`
+    pub date_format: &'a str,
+    pub locale: &'a LocaleConfig,
+    pub title: Option<&'a str>,
+    /// Prompt answers, keyed by prompt label
+    pub prompts: &'a HashMap<String, String>,
+}
+
+/// Fill in every placeholder the context knows about
+pub fn render_template(template: &str, ctx: &TemplateContext) -> String {
+    PLACEHOLDER_RE
+        .replace_all(template, |caps: &regex::Captures| {
+            resolve(caps[1].trim(), ctx).unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+/// Labels of the `{{prompt:...}}` placeholders, in order and without repeats
+pub fn template_prompts(template: &str) -> Vec<String> {
+    let mut prompts: Vec<String> = Vec::new();
+    for caps in PLACEHOLDER_RE.captures_iter(template) {
+        let head = caps[1].split('|').next().unwrap_or_default().trim();
+        if let Some(label) = head.strip_prefix("prompt:").map(str::trim) {
+            if !label.is_empty() && !prompts.iter().any(|p| p == label) {
+                prompts.push(label.to_string());
+            }
+        }
+    }
+    prompts
+}
+
+fn resolve(placeholder: &str, ctx: &TemplateContext) -> Option<String> {
+    let mut parts = placeholder.split('|');
+    let head = parts.next()?.trim();
+    let (name, arg) = match head.split_once(':') {
+        Some((name, arg)) => (name.trim(), Some(arg.trim())),
+        None => (head, None),
+    };
+
+    let value = match name {
+        "date" => format_date(ctx, arg)?,
+        "time" => {
+            let format = to_strftime(arg.unwrap_or("%H:%M"));
+            validate_strftime(&format).ok()?;
+            ctx.now.format(&format).to_string()
+        }
+        "title" => ctx.title.unwrap_or_default().to_string(),
+        // Unanswered prompts render empty rather than leaving the marker
+        "prompt" => ctx.prompts.get(arg?).cloned().unwrap_or_default(),
+        _ => return None,
+    };
+
+    Some(parts.fold(value, |value, filter| apply_filter(&value, filter.trim())))
+}
+
+/// `{{date:[offset][:format]}}`; the offset is optional
+fn format_date(ctx: &TemplateContext, arg: Option<&str>) -> Option<String> {
+    let (offset, format) = match arg {
+        None => (None, None),
+        Some(arg) => match arg.split_once(':') {
+            Some((offset, format)) if OFFSET_RE.is_match(offset) => (Some(offset), Some(format)),
+            _ if OFFSET_RE.is_match(arg) => (Some(arg), None),
+            _ => (None, Some(arg)),
+        },
+    };
+
+    let date = match offset {
+        Some(offset) => shift_date(ctx.date, offset)?,
+        None => ctx.date,
+    };
+    let format = format.map(to_strftime).unwrap_or_else(|| ctx.date_format.to_string());
+    validate_strftime(&format).ok()?;
+
+    Some(ctx.locale.format_date(&date, &format))
+}
+
+fn shift_date(date: NaiveDate, offset: &str) -> Option<NaiveDate> {
+    let caps = OFFSET_RE.captures(offset)?;
+    let amount: u32 = caps[2].parse().ok()?;
+    let forward = &caps[1] == "+";
+
+    let days = |n: i64| {
+        let delta = Duration::days(n);
+        if forward {
+            date.checked_add_signed(delta)
+        } else {
+            date.checked_sub_signed(delta)
+        }
+    };
+    let months = |n: u32| {
+        if forward {
+            date.checked_add_months(Months::new(n))
+        } else {
+            date.checked_sub_months(Months::new(n))
+        }
+    };
+
+    match &caps[3] {
+        "d" => days(amount as i64),
+        "w" => days(amount as i64 * 7),
+        "m" => months(amount),
+        "y" => months(amount.checked_mul(12)?),
+        _ => None,
+    }
+}
+
+/// Formats containing `%` are strftime; anything else is read as
+/// moment-style tokens
+fn to_strftime(format: &str) -> String {
+    if format.contains('%') {
+        return format.to_string();
+    }
+
+    let mut out = String::new();
+    let mut rest = format;
+    'outer: while !rest.is_empty() {
+        for (token, spec) in DATE_TOKENS {
+            if let Some(after) = rest.strip_prefix(token) {
+                out.push_str(spec);
+                rest = after;
+                continue 'outer;
+            }
+        }
+        let c = rest.chars().next().unwrap_or_default();
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+fn apply_filter(value: &str, filter: &str) -> String {
+    match filter {
+        "slug" => slugify(value),
+        "lower" => value.to_lowercase(),
+        "upper" => value.to_uppercase(),
+        "trim" => value.trim().to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// Lowercase, with runs of anything but letters and digits turned into `-`
+fn slugify(value: &str) -> String {
+    let mut slug = String::new();
+    for c in value.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, prompts: &HashMap<String, String>) -> String {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 30).unwrap();
+        let ctx = TemplateContext {
+            date,
+            now: date.and_hms_opt(9, 5, 0).unwrap(),
+            date_format: "%Y-%m-%d",
+            locale: &LocaleConfig::default(),
+            title: Some("Q1 Planning: Draft"),
+            prompts,
+        };
+        render_template(template, &ctx)
+    }
+
+    #[test]
+    fn test_date_placeholders() {
+        let none = HashMap::new();
+        assert_eq!(render("{{date}}", &none), "2026-01-30");
+        assert_eq!(render("{{date:+7d:YYYY-MM-DD}}", &none), "2026-02-06");
+        assert_eq!(render("{{date:-1w}}", &none), "2026-01-23");
+        // Month arithmetic clamps to the end of shorter months
+        assert_eq!(render("{{date:+1m}}", &none), "2026-02-28");
+        assert_eq!(render("{{date:+1y:D MMMM YYYY}}", &none), "30 January 2027");
+        assert_eq!(render("{{date:%A}}", &none), "Friday");
+        assert_eq!(render("{{time}} {{time:HH:mm:ss}}", &none), "09:05 09:05:00");
+    }
+
+    #[test]
+    fn test_title_filters_and_prompts() {
+        let mut prompts = HashMap::new();
+        prompts.insert("Project name".to_string(), "Apollo".to_string());
+
+        assert_eq!(render("{{title|slug}}", &prompts), "q1-planning-draft");
+        assert_eq!(render("{{ title | upper }}", &prompts), "Q1 PLANNING: DRAFT");
+        assert_eq!(render("# {{prompt:Project name}} ({{prompt:Owner}})", &prompts), "# Apollo ()");
+        assert_eq!(render("{{prompt:Project name|lower}}", &prompts), "apollo");
+    }
+
+    #[test]
+    fn test_unknown_placeholders_are_kept() {
+        let none = HashMap::new();
+        assert_eq!(render("{{weekday}} {{date:%Q}}", &none), "{{weekday}} {{date:%Q}}");
+    }
+
+    #[test]
+    fn test_template_prompts() {
+        let template = "{{prompt:Project}} {{date}} {{prompt: Owner }} {{prompt:Project|slug}}";
+        assert_eq!(template_prompts(template), vec!["Project", "Owner"]);
+    }
+}
@@ -0,0 +1,335 @@
+//! TiddlyWiki tiddlers turned into Markdown notes
+//!
+//! Reads a JSON export of tiddlers or a saved wiki (`.html`), converting
+//! the common wikitext markup, links and transclusions to Markdown. System
+//! tiddlers (`$:/...`), drafts and non-text tiddlers are left out.
+
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use crate::vault::roam::{page_file_name, ConvertedPage};
+use crate::vault::{set_frontmatter_property, set_frontmatter_value};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct Tiddler {
+    title: String,
+    #[serde(default)]
+    text: String,
+    /// Space-separated, with `[[...]]` around tags containing spaces
+    #[serde(default)]
+    tags: String,
+    /// `YYYYMMDDhhmmssSSS`, UTC
+    created: Option<String>,
+    modified: Option<String>,
+    #[serde(default, rename = "type")]
+    kind: String,
+}
+
+/// Tiddler types holding text; empty means wikitext
+const TEXT_TYPES: [&str; 5] = [
+    "",
+    "text/vnd.tiddlywiki",
+    "text/x-tiddlywiki",
+    "text/x-markdown",
+    "text/markdown",
+];
+
+/// The JSON tiddler store of TiddlyWiki 5.2 and later
+static STORE_SCRIPT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?s)<script class="tiddlywiki-tiddler-store" type="application/json">(.*?)</script>"#,
+    )
+    .expect("Invalid tiddler store regex")
+});
+
+/// A tiddler in the `storeArea` of older and classic wikis
+static STORE_DIV_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<div\s+([^>]*\btitle=[^>]*)>\s*<pre>(.*?)</pre>\s*</div>")
+        .expect("Invalid tiddler div regex")
+});
+
+static ATTRIBUTE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"([\w-]+)="([^"]*)""#).expect("Invalid attribute regex")
+});
+
+static TAG_LIST_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[\[([^\]]+)\]\]|(\S+)").expect("Invalid tag list regex"));
+
+static HEADING_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(!{1,6})\s*(.*)$").expect("Invalid heading regex"));
+
+static LIST_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([*#]+)\s*(.*)$").expect("Invalid list regex"));
+
+static LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[\[([^\]|]+)(?:\|([^\]]+))?\]\]").expect("Invalid link regex")
+});
+
+static TRANSCLUSION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{([^{}|]+)\}\}").expect("Invalid transclusion regex"));
+
+/// A `CamelCase` word, or `~CamelCase` to keep it from linking. Links and
+/// URLs are matched too, to be skipped.
+static WIKI_WORD_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"\[\[[^\]]*\]\]|\]\([^)]*\)|<[^>]*>|\S+://\S+|",
+        r"(~?)\b([A-Z][a-z0-9]+[A-Z][A-Za-z0-9]*)\b"
+    ))
+    .expect("Invalid wiki word regex")
+});
+
+static BOLD_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"''(.+?)''").expect("Invalid bold regex"));
+
+static ITALIC_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(^|[^:])//(.+?)//").expect("Invalid italic regex"));
+
+static ENTITY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"&(#x[0-9a-fA-F]+|#[0-9]+|[a-z]+);").expect("Invalid entity regex")
+});
+
+/// Convert the tiddlers of a JSON export or saved wiki. Notes go to
+/// `folder`; `None` means the data holds no tiddlers.
+pub fn convert_tiddlywiki(data: &str, folder: &str) -> Option<Vec<ConvertedPage>> {
+    let tiddlers: Vec<Tiddler> = read_tiddlers(data)?
+        .into_iter()
+        .filter(|t| !t.title.starts_with("$:/") && !t.title.starts_with("Draft of '"))
+        .filter(|t| TEXT_TYPES.contains(&t.kind.as_str()))
+        .collect();
+    let folder = folder.trim_matches('/');
+
+    // Link targets, by title
+    let targets: HashMap<String, String> = tiddlers
+        .iter()
+        .map(|t| {
+            let name = page_file_name(&t.title);
+            let path = if folder.is_empty() {
+                name
+            } else {
+                format!("{}/{}", folder, name)
+            };
+            (t.title.clone(), path)
+        })
+        .collect();
+
+    Some(
+        tiddlers
+            .iter()
+            .map(|tiddler| ConvertedPage {
+                path: format!("{}.md", targets[&tiddler.title]),
+                content: tiddler_note(tiddler, &targets),
+            })
+            .collect(),
+    )
+}
+
+fn read_tiddlers(data: &str) -> Option<Vec<Tiddler>> {
+    if data.trim_start().starts_with('[') {
+        return serde_json::from_str(data).ok();
+    }
+
+    let mut tiddlers = Vec::new();
+    for captures in STORE_SCRIPT_RE.captures_iter(data) {
+        tiddlers.extend(serde_json::from_str::<Vec<Tiddler>>(&captures[1]).ok()?);
+    }
+    for captures in STORE_DIV_RE.captures_iter(data) {
+        let attributes: HashMap<&str, String> = ATTRIBUTE_RE
+            .captures_iter(&captures[1])
+            .map(|a| (a.get(1).map_or("", |m| m.as_str()), decode_entities(&a[2])))
+            .collect();
+        let attribute = |name: &str| attributes.get(name).cloned();
+        tiddlers.push(Tiddler {
+            title: attribute("title").unwrap_or_default(),
+            text: decode_entities(&captures[2]),
+            tags: attribute("tags").unwrap_or_default(),
+            created: attribute("created"),
+            modified: attribute("modified"),
+            kind: attribute("type").unwrap_or_default(),
+        });
+    }
+    (!tiddlers.is_empty()).then_some(tiddlers)
+}
+
+fn tiddler_note(tiddler: &Tiddler, targets: &HashMap<String, String>) -> String {
+    let body = if tiddler.kind.contains("markdown") {
+        tiddler.text.trim().to_string()
+    } else {
+        convert_wikitext(&tiddler.text, targets)
+    };
+    let mut content = format!("# {}\n", tiddler.title);
+    if !body.is_empty() {
+        content.push('\n');
+        content.push_str(&body);
+        content.push('\n');
+    }
+
+    let tags: Vec<String> = TAG_LIST_RE
+        .captures_iter(&tiddler.tags)
+        .filter_map(|c| c.get(1).or(c.get(2)))
+        .map(|m| m.as_str().trim().replace(' ', "-"))
+        .filter(|t| !t.is_empty())
+        .collect();
+    if !tags.is_empty() {
+        content = set_frontmatter_value(&content, "tags", &serde_json::Value::from(tags));
+    }
+    for (key, value) in [("created", &tiddler.created), ("modified", &tiddler.modified)] {
+        if let Some(date) = value.as_deref().and_then(tiddlywiki_date) {
+            content = set_frontmatter_property(&content, key, &date);
+        }
+    }
+    content
+}
+
+/// Wikitext to Markdown: headings, lists, bold and italics, links and
+/// transclusions. Code blocks are copied as they are.
+fn convert_wikitext(text: &str, targets: &HashMap<String, String>) -> String {
+    let link = |title: &str, label: Option<&str>| {
+        let title = title.trim();
+        match targets.get(title) {
+            Some(path) if path != title => format!("[[{}|{}]]", path, label.unwrap_or(title)),
+            _ => match label {
+                Some(label) => format!("[[{}|{}]]", title, label),
+                None => format!("[[{}]]", title),
+            },
+        }
+    };
+
+    let mut lines = Vec::new();
+    let mut in_code = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            lines.push(line.to_string());
+            continue;
+        }
+        if in_code {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let line = if let Some(c) = HEADING_RE.captures(line) {
+            format!("{} {}", "#".repeat(c[1].len()), &c[2])
+        } else if let Some(c) = LIST_RE.captures(line) {
+            let markers = &c[1];
+            let bullet = if markers.ends_with('#') { "1." } else { "-" };
+            format!("{}{} {}", "  ".repeat(markers.len() - 1), bullet, &c[2])
+        } else {
+            line.to_string()
+        };
+
+        // `[[label|Title]]`: TiddlyWiki puts the label first
+        let line = LINK_RE.replace_all(&line, |c: &Captures| match c.get(2) {
+            Some(target) if target.as_str().contains("://") => {
+                format!("[{}]({})", &c[1], target.as_str())
+            }
+            Some(target) => link(target.as_str(), Some(&c[1])),
+            None if c[1].contains("://") => format!("<{}>", &c[1]),
+            None => link(&c[1], None),
+        });
+        let line = TRANSCLUSION_RE.replace_all(&line, |c: &Captures| {
+            let title = c[1].trim();
+            format!("![[{}]]", targets.get(title).map_or(title, String::as_str))
+        });
+        // Wiki words that are tiddler titles become links; the rest stay
+        // text, as do escaped ones
+        let line = WIKI_WORD_RE.replace_all(&line, |c: &Captures| {
+            let Some(word) = c.get(2).map(|m| m.as_str()) else {
+                return c[0].to_string();
+            };
+            if c[1].is_empty() && targets.contains_key(word) {
+                link(word, None)
+            } else {
+                word.to_string()
+            }
+        });
+        let line = BOLD_RE.replace_all(&line, "**$1**");
+        lines.push(ITALIC_RE.replace_all(&line, "$1*$2*").to_string());
+    }
+    lines.join("\n").trim().to_string()
+}
+
+/// `20260309143005123` as `2026-03-09T14:30:05Z`
+fn tiddlywiki_date(value: &str) -> Option<String> {
+    // Classic wikis leave out the seconds, or the time altogether
+    let digits = format!("{:0<14}", value.get(..14).unwrap_or(value));
+    let date = chrono::NaiveDateTime::parse_from_str(&digits, "%Y%m%d%H%M%S").ok()?;
+    Some(date.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+}
+
+/// Decode the entities a saved wiki escapes its tiddlers with
+fn decode_entities(text: &str) -> String {
+    ENTITY_RE
+        .replace_all(text, |c: &Captures| {
+            let entity = &c[1];
+            let decoded = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(str::parse))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32),
+            };
+            decoded.map_or_else(|| c[0].to_string(), String::from)
+        })
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::get_frontmatter_property;
+
+    #[test]
+    fn test_convert_tiddlywiki_json() {
+        let text = "!! Setup\n* Read HelloThere\n\
+                    ** then ''bold'' and //italic// at https://x.io\n# first\n\
+                    [[see this|Hello There]] and [[Docs|https://tiddlywiki.com]]\n\
+                    {{Hello There}} ~NotALink MissingPage [[HelloThere]]";
+        let json = serde_json::json!([
+            {"title": "GettingStarted", "text": text,
+             "tags": "intro [[to do]]", "created": "20260309143005123"},
+            {"title": "Hello There", "text": "Hi"},
+            {"title": "HelloThere", "text": "Alias"},
+            {"title": "$:/StoryList", "text": ""},
+            {"title": "logo.png", "text": "iVBOR", "type": "image/png"}
+        ]);
+        let pages = convert_tiddlywiki(&json.to_string(), "tw").unwrap();
+        let paths: Vec<&str> = pages.iter().map(|p| p.path.as_str()).collect();
+        assert_eq!(paths, vec!["tw/GettingStarted.md", "tw/Hello There.md", "tw/HelloThere.md"]);
+
+        let note = &pages[0].content;
+        let created = get_frontmatter_property(note, "created");
+        assert_eq!(created.as_deref(), Some("2026-03-09T14:30:05Z"));
+        assert!(note.contains("tags: [\"intro\",\"to-do\"]"));
+        assert!(note.ends_with(
+            "# GettingStarted\n\n## Setup\n- Read [[tw/HelloThere|HelloThere]]\n  \
+             - then **bold** and *italic* at https://x.io\n1. first\n\
+             [[tw/Hello There|see this]] and [Docs](https://tiddlywiki.com)\n\
+             ![[tw/Hello There]] NotALink MissingPage [[tw/HelloThere|HelloThere]]\n"
+        ));
+    }
+
+    #[test]
+    fn test_convert_tiddlywiki_html() {
+        let html = r#"<html><div id="storeArea">
+            <div created="20260309" title="Q&amp;A" tags="faq">
+            <pre>Is &lt;b&gt; bold? Yes &amp; no</pre>
+            </div>
+            <div title="$:/config" ><pre>x</pre></div>
+        </div></html>"#;
+        let pages = convert_tiddlywiki(html, "").unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].path, "Q&A.md");
+        assert!(pages[0].content.ends_with("# Q&A\n\nIs <b> bold? Yes & no\n"));
+
+        assert!(convert_tiddlywiki("<html>no wiki</html>", "").is_none());
+    }
+}
@@ -0,0 +1,148 @@
+//! Saved list views: a smart folder filter plus how to show the notes it
+//! matches (columns, sort, grouping), mirrored to `.chronicle/views.json`
+//! so panes like "Recent" or "By project" are the same on every device
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::smart_folders::SmartFilter;
+
+/// Columns backed by note metadata; any other column is a frontmatter
+/// property
+pub const VIEW_NOTE_COLUMNS: &[&str] = &["title", "path", "created", "modified", "word_count"];
+
+/// A named list of notes, shown the same way wherever it's opened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListView {
+    pub id: i64,
+    pub name: String,
+    pub spec: ViewSpec,
+    /// RFC 3339 timestamp of the last save
+    pub updated_at: String,
+}
+
+/// Which notes a view lists and how
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewSpec {
+    pub filter: SmartFilter,
+    /// Columns in display order: `title`, `path`, `created`, `modified`,
+    /// `word_count`, or a property key
+    pub columns: Vec<String>,
+    pub sort: ViewSort,
+    pub group_by: Option<ViewGrouping>,
+    /// Most notes to list, taken after sorting
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewSort {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    #[default]
+    Title,
+    Path,
+    Created,
+    Modified,
+    WordCount,
+}
+
+/// What a view's notes are grouped by. A note with several tags or property
+/// values is listed under each.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "by", rename_all = "snake_case")]
+pub enum ViewGrouping {
+    Folder,
+    Tag,
+    Property { key: String },
+}
+
+impl ViewSpec {
+    /// Check the filter, and that property columns and groupings name a
+    /// property
+    pub fn validate(&self) -> Result<(), String> {
+        self.filter.validate()?;
+        if self.columns.iter().any(|column| column.trim().is_empty()) {
+            return Err("View columns must not be empty".to_string());
+        }
+        if let Some(ViewGrouping::Property { key }) = &self.group_by {
+            if key.trim().is_empty() {
+                return Err("Grouping by property needs a key".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Columns showing frontmatter properties
+    pub fn property_columns(&self) -> impl Iterator<Item = &str> {
+        self.columns
+            .iter()
+            .map(String::as_str)
+            .filter(|column| !VIEW_NOTE_COLUMNS.contains(column))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ViewsFile {
+    views: Vec<ListView>,
+}
+
+fn views_file(vault_path: &Path) -> PathBuf {
+    vault_path.join(".chronicle").join("views.json")
+}
+
+/// Views saved in the vault, or `None` if it has no views file
+pub fn read_views(vault_path: &Path) -> io::Result<Option<Vec<ListView>>> {
+    let file = views_file(vault_path);
+    if !file.exists() {
+        return Ok(None);
+    }
+    let parsed: ViewsFile = serde_json::from_slice(&fs::read(file)?)?;
+    Ok(Some(parsed.views))
+}
+
+/// Replace the vault's views file
+pub fn write_views(vault_path: &Path, views: &[ListView]) -> io::Result<()> {
+    let file = views_file(vault_path);
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let content = serde_json::to_vec_pretty(&ViewsFile {
+        views: views.to_vec(),
+    })?;
+    let tmp = file.with_extension("tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, &file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view_spec_from_json() {
+        let spec: ViewSpec = serde_json::from_str(
+            r#"{"columns": ["title", "status"], "sort": {"field": "modified", "descending": true},
+                "group_by": {"by": "property", "key": "project"}, "limit": 20}"#,
+        )
+        .unwrap();
+        assert_eq!(spec.sort.field, SortField::Modified);
+        assert_eq!(spec.property_columns().collect::<Vec<_>>(), vec!["status"]);
+        assert!(spec.validate().is_ok());
+
+        let spec = ViewSpec {
+            group_by: Some(ViewGrouping::Property { key: " ".to_string() }),
+            ..Default::default()
+        };
+        assert!(spec.validate().is_err());
+    }
+}
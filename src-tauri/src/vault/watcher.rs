@@ -9,6 +9,9 @@ use std::sync::mpsc::{channel, Receiver, Sender};
 use std::time::Duration;
 use thiserror::Error;
 
+use super::indexer::is_indexed_file;
+use super::sidecar::{has_sidecar, is_sidecar};
+
 #[derive(Error, Debug)]
 pub enum WatchError {
     #[error("Notify error: {0}")]
@@ -64,7 +67,9 @@ impl VaultWatcher {
         let paths: Vec<_> = event
             .paths
             .iter()
-            .filter(|p| Self::is_markdown_file(p))
+            .filter(|p| {
+                Self::is_markdown_file(p) || is_indexed_file(p) || is_sidecar(p) || has_sidecar(p)
+            })
             .filter(|p| !Self::is_hidden(p, vault_path))
             .cloned()
             .collect();
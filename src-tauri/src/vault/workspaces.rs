@@ -0,0 +1,57 @@
+//! Saved workspaces, mirrored to `.chronicle/workspaces.json` so they
+//! travel with the vault when it is synced. The index database can be
+//! rebuilt at any time; this file is what survives.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A named working context: the notes that were open and how the window
+/// was laid out
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Workspace {
+    pub name: String,
+    /// Vault-relative paths of the open notes, in tab order
+    pub open_notes: Vec<String>,
+    /// Panel sizes, splits and the like; owned by the frontend
+    #[serde(default)]
+    pub layout: serde_json::Value,
+    /// RFC 3339 timestamp of the last save
+    pub updated_at: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WorkspacesFile {
+    workspaces: Vec<Workspace>,
+}
+
+fn workspaces_file(vault_path: &Path) -> PathBuf {
+    vault_path.join(".chronicle").join("workspaces.json")
+}
+
+/// Workspaces saved in the vault, or `None` if it has no workspaces file
+pub fn read_workspaces(vault_path: &Path) -> io::Result<Option<Vec<Workspace>>> {
+    let file = workspaces_file(vault_path);
+    if !file.exists() {
+        return Ok(None);
+    }
+    let parsed: WorkspacesFile = serde_json::from_slice(&fs::read(file)?)?;
+    Ok(Some(parsed.workspaces))
+}
+
+/// Replace the vault's workspaces file
+pub fn write_workspaces(vault_path: &Path, workspaces: &[Workspace]) -> io::Result<()> {
+    let file = workspaces_file(vault_path);
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Pretty-printed so merges of concurrent edits stay readable
+    let content = serde_json::to_vec_pretty(&WorkspacesFile {
+        workspaces: workspaces.to_vec(),
+    })?;
+    let tmp = file.with_extension("tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, &file)
+}
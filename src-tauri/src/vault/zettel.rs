@@ -0,0 +1,133 @@
+//! Zettelkasten note IDs
+//!
+//! A note's ID is its `id` frontmatter property or, failing that, an ID at
+//! the start of its filename (`202501271430 Title.md`,
+//! `01JJN6Z0Q8X4T2V7B9C3D5E6F7-title.md`). Links may use the bare ID.
+
+use chrono::{Duration, NaiveDateTime};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use crate::models::ZettelIdScheme;
+use crate::vault::get_frontmatter_property;
+
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const ULID_LEN: usize = 26;
+const TIMESTAMP_FORMAT: &str = "%Y%m%d%H%M";
+
+/// Generate an ID for a note created at `now`
+///
+/// `taken` says whether an ID is already used; timestamp IDs move forward a
+/// minute at a time until they are free.
+pub fn generate_zettel_id<E>(
+    scheme: ZettelIdScheme,
+    now: NaiveDateTime,
+    taken: impl Fn(&str) -> Result<bool, E>,
+) -> Result<String, E> {
+    let mut time = now;
+    loop {
+        let id = match scheme {
+            ZettelIdScheme::Timestamp => time.format(TIMESTAMP_FORMAT).to_string(),
+            ZettelIdScheme::Ulid => ulid(now.and_utc().timestamp_millis().max(0) as u64, random_bits()),
+        };
+        if !taken(&id)? {
+            return Ok(id);
+        }
+        time += Duration::minutes(1);
+    }
+}
+
+/// ID a note is known by, from its frontmatter or its filename
+pub fn note_zettel_id(content: &str, filename: &str) -> Option<String> {
+    if let Some(id) = get_frontmatter_property(content, "id").filter(|id| !id.trim().is_empty()) {
+        return Some(id.trim().to_string());
+    }
+    let stem = filename.strip_suffix(".md").unwrap_or(filename);
+    leading_zettel_id(stem).map(str::to_string)
+}
+
+/// Timestamp (12 to 14 digits) or ULID at the start of a filename stem,
+/// followed by the end of the stem, a space, `-` or `_`
+pub fn leading_zettel_id(stem: &str) -> Option<&str> {
+    let end = stem.find([' ', '-', '_']).unwrap_or(stem.len());
+    let candidate = &stem[..end];
+
+    let is_timestamp = (12..=14).contains(&candidate.len()) && candidate.bytes().all(|b| b.is_ascii_digit());
+    let is_ulid = candidate.len() == ULID_LEN
+        && candidate.bytes().all(|b| CROCKFORD.contains(&b.to_ascii_uppercase()))
+        && !candidate.bytes().all(|b| b.is_ascii_digit());
+
+    (is_timestamp || is_ulid).then_some(candidate)
+}
+
+/// 48-bit millisecond timestamp then 80 random bits, in Crockford base32
+fn ulid(millis: u64, random: u128) -> String {
+    let value = ((millis as u128 & 0xFFFF_FFFF_FFFF) << 80) | (random & ((1u128 << 80) - 1));
+    (0..ULID_LEN)
+        .rev()
+        .map(|i| CROCKFORD[((value >> (i * 5)) & 0x1F) as usize] as char)
+        .collect()
+}
+
+/// Random bits from the std hasher's per-process random keys, which is
+/// plenty for IDs that only need to avoid collisions
+pub(crate) fn random_bits() -> u128 {
+    let half = || {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or_default(),
+        );
+        hasher.finish() as u128
+    };
+    (half() << 64) | half()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, 27).unwrap().and_hms_opt(14, 30, 12).unwrap()
+    }
+
+    fn generate(scheme: ZettelIdScheme, taken: &[&str]) -> String {
+        generate_zettel_id(scheme, now(), |id| Ok::<_, ()>(taken.contains(&id))).unwrap()
+    }
+
+    #[test]
+    fn test_timestamp_ids_skip_taken_minutes() {
+        assert_eq!(generate(ZettelIdScheme::Timestamp, &[]), "202501271430");
+        assert_eq!(generate(ZettelIdScheme::Timestamp, &["202501271430", "202501271431"]), "202501271432");
+    }
+
+    #[test]
+    fn test_ulid_ids() {
+        let a = generate(ZettelIdScheme::Ulid, &[]);
+        let b = generate(ZettelIdScheme::Ulid, &[]);
+        assert_eq!(a.len(), ULID_LEN);
+        assert_ne!(a, b);
+        // Same millisecond, same timestamp prefix
+        assert_eq!(a[..10], b[..10]);
+        assert_eq!(leading_zettel_id(&a), Some(a.as_str()));
+        assert_eq!(ulid(0, 0), "0".repeat(ULID_LEN));
+    }
+
+    #[test]
+    fn test_note_zettel_id() {
+        assert_eq!(leading_zettel_id("202501271430 Atomic notes"), Some("202501271430"));
+        assert_eq!(leading_zettel_id("202501271430-atomic-notes"), Some("202501271430"));
+        assert_eq!(leading_zettel_id("20250127"), None);
+        assert_eq!(leading_zettel_id("meeting-notes"), None);
+
+        assert_eq!(note_zettel_id("# Hi", "202501271430.md").as_deref(), Some("202501271430"));
+        assert_eq!(
+            note_zettel_id("---\nid: 202401010900\n---\n# Hi", "202501271430 hi.md").as_deref(),
+            Some("202401010900")
+        );
+        assert_eq!(note_zettel_id("# Hi", "hi.md"), None);
+    }
+}
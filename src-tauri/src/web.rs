@@ -0,0 +1,182 @@
+//! Fetching web pages for capture: the page title and, optionally, the
+//! readable part of the page as Markdown
+
+use regex::Regex;
+use std::io::Read;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use crate::error::ChronicleError;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+/// Pages are cut off here; titles and articles come well before
+const MAX_PAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+static TITLE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<title[^>]*>(.*?)</title>").expect("Invalid title regex"));
+
+static META_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<meta\s[^>]*>").expect("Invalid meta regex"));
+
+static CONTENT_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)\bcontent\s*=\s*("([^"]*)"|'([^']*)')"#).expect("Invalid content attribute regex")
+});
+
+/// Containers tried in order for the readable part of a page
+static CONTAINER_RES: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    ["article", "main", "body"]
+        .iter()
+        .map(|tag| {
+            Regex::new(&format!(r"(?is)<{0}\b[^>]*>(.*)</{0}>", tag)).expect("Invalid container regex")
+        })
+        .collect()
+});
+
+/// Page furniture dropped before conversion
+static NOISE_RES: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    ["script", "style", "noscript", "nav", "header", "footer", "aside", "form", "iframe", "svg"]
+        .iter()
+        .map(|tag| Regex::new(&format!(r"(?is)<{0}\b.*?</{0}>", tag)).expect("Invalid noise regex"))
+        .collect()
+});
+
+static BLANK_LINES_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\n{3,}").expect("Invalid blank lines regex"));
+
+/// Fetch an http(s) URL and return its HTML, which is empty for responses
+/// that aren't HTML
+pub fn fetch_page(url: &str) -> Result<String, ChronicleError> {
+    let scheme = url.split_once("://").map(|(s, _)| s.to_ascii_lowercase());
+    if !matches!(scheme.as_deref(), Some("http" | "https")) {
+        return Err(ChronicleError::FetchFailed(format!("Not an http(s) URL: {}", url)));
+    }
+
+    let agent = ureq::AgentBuilder::new()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("Chronicle/", env!("CARGO_PKG_VERSION")))
+        .build();
+    let response = agent.get(url).call().map_err(|e| match e {
+        ureq::Error::Status(code, _) => {
+            ChronicleError::FetchFailed(format!("{}: HTTP {}", url, code))
+        }
+        ureq::Error::Transport(t) => ChronicleError::NetworkUnreachable(t.to_string()),
+    })?;
+
+    if !response.content_type().contains("html") {
+        return Ok(String::new());
+    }
+    let mut bytes = Vec::new();
+    response.into_reader().take(MAX_PAGE_BYTES).read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// The page's `og:title`, else its `<title>`
+pub fn page_title(html: &str) -> Option<String> {
+    let og_title = META_RE
+        .find_iter(html)
+        .map(|m| m.as_str())
+        .find(|tag| {
+            let lower = tag.to_ascii_lowercase();
+            lower.contains("property=\"og:title\"") || lower.contains("property='og:title'")
+        })
+        .and_then(|tag| CONTENT_ATTR_RE.captures(tag))
+        .and_then(|caps| caps.get(2).or_else(|| caps.get(3)).map(|m| m.as_str().to_string()));
+
+    let title = og_title.or_else(|| TITLE_RE.captures(html).map(|caps| caps[1].to_string()))?;
+    let title = decode_entities(&title.split_whitespace().collect::<Vec<_>>().join(" "));
+    (!title.is_empty()).then_some(title)
+}
+
+/// The main content of a page as Markdown: the `<article>`, else `<main>`,
+/// else `<body>`, without scripts, navigation and other furniture
+pub fn readable_markdown(html: &str) -> String {
+    let container = CONTAINER_RES
+        .iter()
+        .find_map(|re| re.captures(html).and_then(|caps| caps.get(1)))
+        .map_or(html, |m| m.as_str());
+
+    let cleaned = NOISE_RES
+        .iter()
+        .fold(container.to_string(), |text, re| re.replace_all(&text, "").into_owned());
+
+    let markdown = html2md::parse_html(&cleaned);
+    BLANK_LINES_RE.replace_all(markdown.trim(), "\n\n").into_owned()
+}
+
+/// The character references that turn up in titles
+fn decode_entities(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start..];
+        let decoded = after.find(';').filter(|end| *end <= 10).and_then(|end| {
+            let entity = &after[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => match entity.strip_prefix('#') {
+                    Some(hex) if hex.starts_with(['x', 'X']) => {
+                        u32::from_str_radix(&hex[1..], 16).ok().and_then(char::from_u32)
+                    }
+                    Some(dec) => dec.parse().ok().and_then(char::from_u32),
+                    None => None,
+                },
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &after[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_title() {
+        let html = "<html><head><title>\n  Rust &amp; You | Blog\n</title></head></html>";
+        assert_eq!(page_title(html).as_deref(), Some("Rust & You | Blog"));
+
+        let og = r#"<head><meta property="og:title" content="Rust &#39;24"><title>Blog</title></head>"#;
+        assert_eq!(page_title(og).as_deref(), Some("Rust '24"));
+
+        assert_eq!(page_title("<p>No title</p>"), None);
+    }
+
+    #[test]
+    fn test_readable_markdown() {
+        let html = "<html><body><nav><a href=\"/\">Home</a></nav>\
+            <article><h1>Ownership</h1><script>track()</script>\
+            <p>Values have <em>one</em> owner.</p></article>\
+            <footer>Copyright</footer></body></html>";
+        let markdown = readable_markdown(html);
+
+        assert!(markdown.contains("Ownership"));
+        assert!(markdown.contains("Values have *one* owner."));
+        assert!(!markdown.contains("Home"));
+        assert!(!markdown.contains("track()"));
+        assert!(!markdown.contains("Copyright"));
+    }
+
+    #[test]
+    fn test_fetch_rejects_other_schemes() {
+        assert!(fetch_page("file:///etc/passwd").is_err());
+        assert!(fetch_page("javascript:alert(1)").is_err());
+    }
+}
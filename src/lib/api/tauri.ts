@@ -56,26 +56,32 @@ async function listen<T>(event: string, handler: (event: { payload: T }) => void
 function getMockResponse(cmd: string, _args?: Record<string, unknown>): unknown {
   switch (cmd) {
     case 'get_vault_info':
-      return { path: '', note_count: 0, is_open: false };
+      return { path: '', note_count: 0, is_open: false, read_only: false };
     case 'list_notes':
       return [];
     case 'list_tags':
       return [];
     case 'get_graph_data':
+    case 'get_local_graph':
+    case 'get_filtered_graph':
       return { nodes: [], edges: [] };
     case 'get_config':
       return {
-        vault: { path: null },
+        vault: { path: null, follow_obsidian_settings: true },
         editor: { font_family: 'JetBrains Mono', font_size: 14, line_height: 1.6, word_wrap: true, vim_mode: false },
         graph: { physics_enabled: true, link_distance: 100, charge_strength: -300, node_size: 8 },
         ui: { sidebar_width: 250, panel_width: 288, show_backlinks: true, show_tags: true },
       };
     case 'search_notes':
       return [];
+    case 'search_notes_page':
+      return { results: [], total: 0, next_offset: null };
     case 'get_backlinks_cmd':
       return [];
     case 'poll_vault_events':
       return;
+    case 'take_deep_links':
+      return [];
     case 'save_config':
       return;
     case 'sync_status':
@@ -129,10 +135,28 @@ function getMockResponse(cmd: string, _args?: Record<string, unknown>): unknown
 
 // Types matching Rust structs
 
+/** Error payload returned by every failing command */
+export interface ChronicleError {
+  code: string;
+  message: string;
+  context: string | null;
+}
+
+export function isChronicleError(e: unknown): e is ChronicleError {
+  return typeof e === 'object' && e !== null && 'code' in e && 'message' in e;
+}
+
+/** Human-readable message for any thrown command error */
+export function errorMessage(e: unknown): string {
+  if (isChronicleError(e)) return e.message;
+  return e instanceof Error ? e.message : String(e);
+}
+
 export interface VaultInfo {
   path: string;
   note_count: number;
   is_open: boolean;
+  read_only: boolean;
 }
 
 export interface NoteMeta {
@@ -147,6 +171,530 @@ export interface NoteMeta {
 export interface Note extends NoteMeta {
   content: string;
   tags: string[];
+  queries?: QueryResult[];
+}
+
+export interface QueryResult {
+  start_line: number;
+  end_line: number;
+  notes: NoteMeta[];
+  rollups: RollupValue[];
+  error: string | null;
+}
+
+export type RollupOp = 'sum' | 'avg' | 'count';
+
+/** A property rolled up over every note a query block matches */
+export interface RollupValue {
+  key: string;
+  op: RollupOp;
+  /** Null for the average of no numbers */
+  value: number | null;
+}
+
+export type UndoOperation =
+  | { kind: 'delete'; path: string }
+  | { kind: 'rename'; from: string; to: string }
+  | { kind: 'merge'; path: string; into: string };
+
+export interface UndoEntry {
+  id: string;
+  operation: UndoOperation;
+  recorded_at: string;
+}
+
+export interface ImportedAttachment {
+  path: string;
+  embed: string;
+}
+
+export interface OcrReport {
+  recognized: number;
+  unchanged: number;
+  removed: number;
+  /** Images Tesseract couldn't read; retried on the next pass */
+  failed: string[];
+}
+
+export interface PdfTextReport {
+  extracted: number;
+  unchanged: number;
+  removed: number;
+  /** PDFs that couldn't be read; retried on the next pass */
+  failed: string[];
+}
+
+export interface EmailImportReport {
+  /** One note per email */
+  imported: NoteMeta[];
+  /** Files that couldn't be read as emails */
+  failed: string[];
+}
+
+export interface ImportReport {
+  imported: string[];
+  /** Pages left out because a note already exists at their path */
+  skipped: string[];
+}
+
+export interface AutoLinkSuggestion {
+  /** Byte offsets into the note on disk; pass back unchanged to apply */
+  start: number;
+  end: number;
+  line_number: number;
+  text: string;
+  target_path: string;
+  link: string;
+}
+
+export interface Flashcard {
+  id: number;
+  note_path: string;
+  note_title: string;
+  kind: 'basic' | 'cloze';
+  front: string;
+  back: string;
+  line_number: number | null;
+  ease: number;
+  interval_days: number;
+  repetitions: number;
+  /** YYYY-MM-DD; null until first reviewed */
+  due: string | null;
+  last_reviewed: string | null;
+}
+
+export interface Task {
+  id: number;
+  note_path: string;
+  note_title: string;
+  line_number: number;
+  text: string;
+  completed: boolean;
+  due: string | null;
+  tags: string[];
+}
+
+/** JSON Canvas (`.canvas`); unknown fields are kept when saving */
+export interface CanvasNode {
+  id: string;
+  type: 'text' | 'file' | 'link' | 'group';
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  text?: string;
+  /** Vault-relative path of a file card */
+  file?: string;
+  subpath?: string;
+  url?: string;
+  label?: string;
+  [key: string]: unknown;
+}
+
+export interface CanvasEdge {
+  id: string;
+  fromNode: string;
+  toNode: string;
+  label?: string;
+  [key: string]: unknown;
+}
+
+export interface Canvas {
+  nodes: CanvasNode[];
+  edges: CanvasEdge[];
+  [key: string]: unknown;
+}
+
+export type HealthSeverity = 'info' | 'warning' | 'error';
+
+export type HealthIssueKind =
+  | 'empty_note'
+  | 'invalid_frontmatter'
+  | 'duplicate_title'
+  | 'broken_link'
+  | 'oversized_file'
+  | 'non_utf8_file'
+  | 'type_schema';
+
+export interface HealthIssue {
+  kind: HealthIssueKind;
+  severity: HealthSeverity;
+  path: string;
+  message: string;
+  suggested_fix: string;
+}
+
+export interface HealthReport {
+  files_checked: number;
+  /** Most severe first */
+  issues: HealthIssue[];
+}
+
+export interface NoteSnapshot {
+  /** Content hash, passed to `restoreSnapshot` */
+  id: string;
+  taken_at: string;
+  /** Content size in bytes */
+  size: number;
+}
+
+export interface BackupInfo {
+  /** File name, passed to `restoreBackup` */
+  name: string;
+  path: string;
+  /** Archive size in bytes */
+  size: number;
+  created_at: string;
+}
+
+export interface RestoreResult {
+  restored: number;
+  /** Backup of the vault taken just before the restore */
+  safety_backup: BackupInfo;
+  note_count: number;
+}
+
+export interface Workspace {
+  name: string;
+  /** Vault-relative paths, in tab order */
+  open_notes: string[];
+  /** Frontend-defined layout state, stored as given */
+  layout: unknown;
+  updated_at: string;
+}
+
+export interface LinkOccurrence {
+  source_path: string;
+  line_number: number | null;
+  display_text: string | null;
+}
+
+export interface BrokenLink {
+  target: string;
+  occurrences: LinkOccurrence[];
+  /** Similarly named notes, closest first */
+  suggestions: string[];
+}
+
+export interface UnresolvedTarget {
+  target: string;
+  /** Notes linking to it */
+  source_count: number;
+  link_count: number;
+}
+
+export type BrokenLinkFix =
+  | { action: 'create' }
+  | { action: 'retarget'; path: string }
+  | { action: 'remove' };
+
+export interface BrokenLinkFixResult {
+  changed_notes: string[];
+  links_changed: number;
+  created: NoteMeta | null;
+}
+
+export type PropertyChange =
+  | { op: 'add_tag'; tag: string }
+  | { op: 'remove_tag'; tag: string }
+  | { op: 'set'; key: string; value: string }
+  | { op: 'remove'; key: string };
+
+export interface PropertyEdit {
+  path: string;
+  title: string;
+  /** Frontmatter YAML */
+  before: string;
+  after: string;
+}
+
+export interface BulkEditResult {
+  edits: PropertyEdit[];
+  /** False for a preview */
+  applied: boolean;
+}
+
+export type EmbedStatus =
+  | 'resolved'
+  | 'attachment'
+  | 'missing'
+  | 'section_missing'
+  | 'cycle'
+  | 'too_deep';
+
+export interface ResolvedEmbed {
+  /** Link as written, e.g. `Note#Section` */
+  target: string;
+  display: string | null;
+  /** 1-indexed, within the content the embed appears in */
+  line_number: number;
+  status: EmbedStatus;
+  path: string | null;
+  content: string | null;
+  embeds: ResolvedEmbed[];
+}
+
+export type PropertyOp = 'equals' | 'not_equals' | 'contains' | 'exists' | 'missing';
+
+export interface PropertyPredicate {
+  key: string;
+  op: PropertyOp;
+  value?: string | null;
+}
+
+/** Unset fields don't filter; dates are YYYY-MM-DD and inclusive */
+export interface SmartFilter {
+  /** Full-text search the note must match */
+  query?: string | null;
+  all_tags?: string[];
+  any_tags?: string[];
+  without_tags?: string[];
+  /** Only notes without any tags */
+  untagged?: boolean;
+  /** Path globs; `*` also matches `/` */
+  paths?: string[];
+  properties?: PropertyPredicate[];
+  created_after?: string | null;
+  created_before?: string | null;
+  modified_after?: string | null;
+  modified_before?: string | null;
+}
+
+export interface SmartFolder {
+  id: number;
+  name: string;
+  filter: SmartFilter;
+  updated_at: string;
+}
+
+export interface SmartFolderContents {
+  folder: SmartFolder;
+  notes: NoteMeta[];
+}
+
+export type ViewSortField = 'title' | 'path' | 'created' | 'modified' | 'word_count';
+
+export type ViewGrouping = { by: 'folder' } | { by: 'tag' } | { by: 'property'; key: string };
+
+export interface ViewSpec {
+  filter?: SmartFilter;
+  /** `title`, `path`, `created`, `modified`, `word_count`, or a property key */
+  columns?: string[];
+  sort?: { field: ViewSortField; descending: boolean };
+  group_by?: ViewGrouping | null;
+  /** Most notes to list, taken after sorting */
+  limit?: number | null;
+}
+
+export interface ListView {
+  id: number;
+  name: string;
+  spec: ViewSpec;
+  updated_at: string;
+}
+
+export interface ViewRow {
+  note: NoteMeta;
+  /** Values of property columns; list values joined with `, ` */
+  properties: Record<string, string>;
+}
+
+export interface ViewGroup {
+  /** Folder, tag or property value; null for ungrouped notes */
+  key: string | null;
+  rows: ViewRow[];
+}
+
+export interface ViewResults {
+  view: ListView;
+  groups: ViewGroup[];
+}
+
+/** Also emitted as a `reminder` event when it comes due */
+export interface Reminder {
+  id: number;
+  note_path: string;
+  note_title: string;
+  /** Local time, YYYY-MM-DD HH:MM */
+  remind_at: string;
+  /** Empty for a note's frontmatter reminder */
+  text: string;
+  line_number: number | null;
+}
+
+export type ReviewStrategy = 'weighted' | 'oldest' | 'connected';
+
+export interface ReviewItem {
+  path: string;
+  title: string;
+  /** Latest of the note's modification, last open and last review */
+  last_seen: string;
+  last_reviewed_at: string | null;
+  link_count: number;
+}
+
+export type ReadingStatus = 'unread' | 'read';
+
+export interface ReadingItem {
+  id: number;
+  url: string;
+  title: string | null;
+  domain: string;
+  status: ReadingStatus;
+  added_from_note: string;
+  added_at: string;
+  read_at: string | null;
+}
+
+export interface DomainCount {
+  domain: string;
+  unread: number;
+  total: number;
+}
+
+export interface TaskFilter {
+  tag?: string;
+  folder?: string;
+  /** YYYY-MM-DD, inclusive */
+  due_before?: string;
+  due_after?: string;
+  completed?: boolean;
+}
+
+export interface CalendarNote {
+  path: string;
+  title: string;
+}
+
+export interface CalendarDay {
+  date: string;
+  daily_note: string | null;
+  created: CalendarNote[];
+  modified: CalendarNote[];
+  tasks_due: Task[];
+}
+
+export interface WritingProgress {
+  today: number;
+  daily_goal: number;
+  this_week: number;
+  /** 0 when no weekly goal is set */
+  weekly_goal: number;
+  current_streak: number;
+  best_streak: number;
+}
+
+export interface BoardConfig {
+  /** Frontmatter property holding the column; defaults to `status` */
+  property?: string;
+  columns?: string[];
+  folder?: string;
+  tag?: string;
+}
+
+export interface BoardCard {
+  path: string;
+  title: string;
+}
+
+export interface Board {
+  property: string;
+  columns: { name: string; cards: BoardCard[] }[];
+  unassigned: BoardCard[];
+}
+
+export interface Capture {
+  url?: string;
+  title?: string;
+  markdown?: string;
+  tags?: string[];
+}
+
+export type ScriptHook = 'note_save' | 'command' | 'import';
+
+export type ScriptCapability = 'note:read' | 'note:write' | 'note:create';
+
+export interface ScriptPlugin {
+  name: string;
+  hook: ScriptHook;
+  command: string;
+  args: string[];
+  capabilities: ScriptCapability[];
+  /** File extensions an importer handles, without the dot */
+  extensions: string[];
+  timeout_secs: number;
+}
+
+export interface ScriptResult {
+  message: string | null;
+  /** The open note, if the script rewrote it */
+  updated: NoteMeta | null;
+  created: NoteMeta[];
+}
+
+export interface NoteTemplate {
+  path: string;
+  name: string;
+  /** `{{prompt:...}}` labels to ask for before creating the note */
+  prompts: string[];
+}
+
+export interface Citation {
+  key: string;
+  entry_type: string;
+  title: string | null;
+  author: string | null;
+  year: string | null;
+  /** Literature note for this entry */
+  note_path: string;
+  note_exists: boolean;
+}
+
+export interface InsertedCitation {
+  /** `[[@key]]`, ready to insert at the cursor */
+  text: string;
+  citation: Citation;
+  created: NoteMeta | null;
+}
+
+export interface SiteExportConfig {
+  output_dir: string;
+  site_title: string;
+  site_description: string;
+  include_search: boolean;
+  include_graph: boolean;
+  custom_css: string | null;
+  exclude_patterns: string[];
+}
+
+export interface PublishReport {
+  updated: number;
+  removed: number;
+  unchanged: number;
+}
+
+export type PublishState = 'published' | 'stale' | 'new' | 'withdrawn';
+
+export interface NotePublishStatus {
+  path: string;
+  title: string;
+  state: PublishState;
+  /** Published file, relative to the publish target */
+  output: string;
+}
+
+export interface PublishStatusReport {
+  published_at: string | null;
+  notes: NotePublishStatus[];
+}
+
+/** 0 = forgotten ... 5 = perfect recall */
+export type ReviewGrade = 0 | 1 | 2 | 3 | 4 | 5;
+
+export interface Draft {
+  path: string;
+  content: string;
+  saved_at: string;
 }
 
 export interface SearchResult {
@@ -158,23 +706,42 @@ export interface SearchResult {
   match_count: number;
 }
 
+export interface AttachmentSearchResult {
+  path: string;
+  snippet: string;
+  rank: number;
+  /** Text recognized in an image, or extracted from a PDF */
+  kind: 'image' | 'pdf';
+}
+
 export interface Backlink {
   source_path: string;
   source_title: string;
   line_number: number | null;
   display_text: string | null;
   context: string | null;
+  /** `[[wiki]]` link or `[text](path.md)` Markdown link */
+  kind: 'wiki' | 'markdown';
 }
 
 export interface GraphNode {
+  /** The note's path, or `tag:` and the lowercased tag name */
   id: string;
+  kind: 'note' | 'tag';
+  uid?: string;
   title: string;
   word_count: number;
+  /** Links to and from the node */
+  degree: number;
+  /** Share of PageRank, 0 to 1 */
+  pagerank: number;
 }
 
 export interface GraphEdge {
   source: string;
   target: string;
+  /** Recency weight from 0 to 1, when `halfLifeDays` is given */
+  weight?: number;
 }
 
 export interface GraphData {
@@ -182,11 +749,53 @@ export interface GraphData {
   edges: GraphEdge[];
 }
 
-export interface TagInfo {
+export interface GraphCluster {
+  /** The shown note the cluster hangs off */
+  anchor: string;
+  size: number;
+  /** The local graph around the anchor at this depth holds the whole cluster */
+  depth: number;
+  /** Titles of the cluster's best-connected notes */
+  titles: string[];
+}
+
+export interface GraphOverview extends GraphData {
+  /** The notes left out, grouped by the nearest shown note, largest first */
+  clusters: GraphCluster[];
+  /** Notes in the largest connected group */
+  component_size: number;
+  total_nodes: number;
+}
+
+export interface TagMeta {
+  /** `#rgb` or `#rrggbb` */
+  color?: string | null;
+  /** An emoji or icon name */
+  icon?: string | null;
+  description?: string | null;
+}
+
+export interface TagInfo extends TagMeta {
   name: string;
   count: number;
 }
 
+export interface TagTreeNode {
+  /** Last part of the tag (`alpha` for `project/alpha`) */
+  name: string;
+  /** The full tag */
+  path: string;
+  /** Notes with this tag or one nested under it */
+  count: number;
+  children: TagTreeNode[];
+}
+
+export interface TagOccurrence {
+  path: string;
+  title: string;
+  line_number: number;
+}
+
 export interface AppConfig {
   vault: VaultConfig;
   editor: EditorConfig;
@@ -196,6 +805,8 @@ export interface AppConfig {
 
 export interface VaultConfig {
   path: string | null;
+  /** Take attachment, daily note and template settings from `.obsidian/` */
+  follow_obsidian_settings: boolean;
 }
 
 export interface EditorConfig {
@@ -251,15 +862,30 @@ export type ConflictResolution = 'keep_local' | 'keep_remote' | 'keep_both';
 
 export type VaultEvent =
   | { type: 'note_created'; path: string }
+  | { type: 'notes_created'; paths: string[] }
   | { type: 'note_modified'; path: string }
   | { type: 'note_deleted'; path: string }
   | { type: 'note_renamed'; old_path: string; new_path: string }
-  | { type: 'index_complete'; note_count: number };
+  | { type: 'index_complete'; note_count: number }
+  | { type: 'vault_unavailable'; path: string }
+  | { type: 'vault_restored'; note_count: number }
+  | { type: 'vault_writable'; note_count: number };
 
 // Vault commands
 
-export async function openVault(path: string): Promise<VaultInfo> {
-  return invoke('open_vault', { path });
+export type VaultTemplate = 'basic' | 'para' | 'zettelkasten';
+
+/** Lay out a new vault in an empty folder; returns the notes written. Open it with openVault. */
+export async function createVault(
+  path: string,
+  template?: VaultTemplate,
+  gitInit?: boolean
+): Promise<string[]> {
+  return invoke('create_vault', { path, template, gitInit });
+}
+
+export async function openVault(path: string, dbKey?: string): Promise<VaultInfo> {
+  return invoke('open_vault', { path, dbKey });
 }
 
 export async function getVaultInfo(): Promise<VaultInfo> {
@@ -274,29 +900,63 @@ export async function pollVaultEvents(): Promise<void> {
   return invoke('poll_vault_events');
 }
 
+/** Android only: pick a vault folder, returning a content URI for openVault */
+export async function pickVaultFolder(): Promise<string> {
+  return invoke('pick_vault_folder');
+}
+
 // Note commands
 
 export async function listNotes(): Promise<NoteMeta[]> {
   return invoke('list_notes');
 }
 
-export async function getNote(path: string): Promise<Note> {
-  return invoke('get_note', { path });
+export async function getNote(path: string, resolveQueries?: boolean): Promise<Note> {
+  return invoke('get_note', { path, resolveQueries });
 }
 
 export async function createNote(title: string, content?: string): Promise<NoteMeta> {
   return invoke('create_note', { title, content });
 }
 
+export interface NewNoteSpec {
+  title: string;
+  /** Vault folder, instead of where new notes usually go */
+  folder?: string;
+  content?: string;
+  tags?: string[];
+}
+
+/** Create notes together: if any can't be created, none are */
+export async function createNotes(notes: NewNoteSpec[]): Promise<NoteMeta[]> {
+  return invoke('create_notes', { notes });
+}
+
 export async function saveNote(path: string, content: string): Promise<NoteMeta> {
   return invoke('save_note', { path, content });
 }
 
+export interface DeleteImpact {
+  broken_links: Backlink[];
+  broken_embeds: Backlink[];
+  linked_tasks: Task[];
+  open_tasks: Task[];
+}
+
+export async function getDeleteImpact(path: string): Promise<DeleteImpact> {
+  return invoke('get_delete_impact', { path });
+}
+
 export async function deleteNote(path: string): Promise<void> {
   return invoke('delete_note', { path });
 }
 
-export async function renameNote(oldPath: string, newPath: string): Promise<NoteMeta> {
+export interface RenamedNote extends NoteMeta {
+  /** Notes whose links were rewritten to the new path */
+  updated_files: string[];
+}
+
+export async function renameNote(oldPath: string, newPath: string): Promise<RenamedNote> {
   return invoke('rename_note', { oldPath, newPath });
 }
 
@@ -304,20 +964,582 @@ export async function updateNoteTags(path: string, tags: string[]): Promise<Note
   return invoke('update_note_tags', { path, tags });
 }
 
+export async function undoLastOperation(): Promise<UndoEntry | null> {
+  return invoke('undo_last_operation');
+}
+
+export async function importAttachment(
+  notePath: string,
+  source: { bytes: number[]; fileName?: string } | { sourcePath: string; fileName?: string }
+): Promise<ImportedAttachment> {
+  return invoke('import_attachment', { notePath, ...source });
+}
+
+export async function recognizeAttachments(): Promise<OcrReport> {
+  return invoke('recognize_attachments');
+}
+
+export async function extractPdfAttachments(): Promise<PdfTextReport> {
+  return invoke('extract_pdf_attachments');
+}
+
+export async function importEml(files: string[], folder: string): Promise<EmailImportReport> {
+  return invoke('import_eml', { files, folder });
+}
+
+export async function importRoam(jsonExport: string, folder: string): Promise<ImportReport> {
+  return invoke('import_roam', { jsonExport, folder });
+}
+
+export async function importTiddlywiki(htmlOrJson: string, folder: string): Promise<ImportReport> {
+  return invoke('import_tiddlywiki', { htmlOrJson, folder });
+}
+
+export async function suggestAutoLinks(path: string): Promise<AutoLinkSuggestion[]> {
+  return invoke('suggest_auto_links', { path });
+}
+
+export async function applyAutoLinks(path: string, suggestions: AutoLinkSuggestion[]): Promise<number> {
+  return invoke('apply_auto_links', { path, suggestions });
+}
+
+// Draft commands
+
+export async function saveDraft(path: string, content: string): Promise<void> {
+  return invoke('save_draft', { path, content });
+}
+
+export async function getDraft(path: string): Promise<Draft | null> {
+  return invoke('get_draft', { path });
+}
+
+export async function discardDraft(path: string): Promise<void> {
+  return invoke('discard_draft', { path });
+}
+
+export async function listDrafts(): Promise<Draft[]> {
+  return invoke('list_drafts');
+}
+
 // Search commands
 
 export async function searchNotes(query: string, limit?: number): Promise<SearchResult[]> {
   return invoke('search_notes', { query, limit });
 }
 
+/** `relevance` falls back to newest first for queries with only scopes */
+export type SearchSort = 'relevance' | 'modified' | 'title';
+
+export interface SearchPage {
+  results: SearchResult[];
+  /** Matching notes on every page together */
+  total: number;
+  /** Offset of the next page, null on the last */
+  next_offset: number | null;
+}
+
+/** One page of results, with the total, for infinite scroll */
+export async function searchNotesPage(
+  query: string,
+  sort?: SearchSort,
+  offset?: number,
+  limit?: number
+): Promise<SearchPage> {
+  return invoke('search_notes_page', { query, sort, offset, limit });
+}
+
+export interface SearchSuggestion {
+  /** The word's stem, as indexed; searching for it finds every form */
+  term: string;
+  note_count: number;
+}
+
+/** Indexed words completing a prefix, or close to it if it looks misspelt */
+export async function suggestSearchTerms(
+  prefix: string,
+  limit?: number
+): Promise<SearchSuggestion[]> {
+  return invoke('suggest_search_terms', { prefix, limit });
+}
+
+/** A corrected query to offer when a search finds nothing, if there is one */
+export async function didYouMean(query: string): Promise<string | null> {
+  return invoke('did_you_mean', { query });
+}
+
+export interface QuickSwitchMatch {
+  path: string;
+  title: string;
+  /** Higher is better, at most 1 */
+  score: number;
+}
+
+/** Notes whose title or path loosely matches, typos and all, best first */
+export async function quickSwitch(query: string, limit?: number): Promise<QuickSwitchMatch[]> {
+  return invoke('quick_switch', { query, limit });
+}
+
+export interface SavedSearch {
+  name: string;
+  /** Empty to list every note the filter matches */
+  query: string;
+  filter: SmartFilter;
+  /** Shown in the sidebar */
+  pinned: boolean;
+  updated_at: string;
+}
+
+/** Save a named search, replacing any with the same name */
+export async function saveSearch(
+  name: string,
+  query: string,
+  filter?: SmartFilter,
+  pinned?: boolean
+): Promise<SavedSearch> {
+  return invoke('save_search', { name, query, filter, pinned });
+}
+
+/** Pinned searches first, then by name */
+export async function listSavedSearches(): Promise<SavedSearch[]> {
+  return invoke('list_saved_searches');
+}
+
+export async function deleteSavedSearch(name: string): Promise<void> {
+  return invoke('delete_saved_search', { name });
+}
+
+export async function runSavedSearch(name: string, limit?: number): Promise<SearchResult[]> {
+  return invoke('run_saved_search', { name, limit });
+}
+
+export async function searchAttachments(
+  query: string,
+  limit?: number
+): Promise<AttachmentSearchResult[]> {
+  return invoke('search_attachments', { query, limit });
+}
+
 export async function getBacklinks(path: string): Promise<Backlink[]> {
   return invoke('get_backlinks_cmd', { path });
 }
 
+// Flashcard commands
+
+export async function getDueCards(limit?: number): Promise<Flashcard[]> {
+  return invoke('get_due_cards', { limit });
+}
+
+export async function reviewCard(id: number, grade: ReviewGrade): Promise<Flashcard> {
+  return invoke('review_card', { id, grade });
+}
+
+// Task commands
+
+export async function queryTasks(filter?: TaskFilter): Promise<Task[]> {
+  return invoke('query_tasks', { filter });
+}
+
+export async function toggleTask(path: string, line: number): Promise<Task> {
+  return invoke('toggle_task', { path, line });
+}
+
+// Workspace commands
+
+export async function saveWorkspace(
+  name: string,
+  openNotes: string[],
+  layout?: unknown
+): Promise<Workspace> {
+  return invoke('save_workspace', { name, openNotes, layout });
+}
+
+export async function loadWorkspace(name: string): Promise<Workspace> {
+  return invoke('load_workspace', { name });
+}
+
+export async function listWorkspaces(): Promise<Workspace[]> {
+  return invoke('list_workspaces');
+}
+
+export async function deleteWorkspace(name: string): Promise<void> {
+  return invoke('delete_workspace', { name });
+}
+
+// Snapshot commands
+
+export async function listNoteSnapshots(path: string): Promise<NoteSnapshot[]> {
+  return invoke('list_note_snapshots', { path });
+}
+
+export async function restoreSnapshot(path: string, id: string): Promise<NoteMeta> {
+  return invoke('restore_snapshot', { path, id });
+}
+
+// Backup commands
+
+export async function createBackup(includeAttachments?: boolean): Promise<BackupInfo> {
+  return invoke('create_backup', { includeAttachments });
+}
+
+export async function listBackups(): Promise<BackupInfo[]> {
+  return invoke('list_backups');
+}
+
+export async function restoreBackup(name: string): Promise<RestoreResult> {
+  return invoke('restore_backup', { name });
+}
+
+// Broken link commands
+
+export async function getBrokenLinkReport(): Promise<BrokenLink[]> {
+  return invoke('get_broken_link_report');
+}
+
+/** Missing notes that links point at, most linked first */
+export async function listUnresolvedLinks(): Promise<UnresolvedTarget[]> {
+  return invoke('list_unresolved_links');
+}
+
+export async function fixBrokenLink(
+  target: string,
+  fix: BrokenLinkFix
+): Promise<BrokenLinkFixResult> {
+  return invoke('fix_broken_link', { target, fix });
+}
+
+// Health check commands
+
+export async function checkVaultHealth(): Promise<HealthReport> {
+  return invoke('check_vault_health');
+}
+
+// Bulk edit commands
+
+export async function bulkEditProperties(
+  filter: SmartFilter,
+  changes: PropertyChange[],
+  preview?: boolean
+): Promise<BulkEditResult> {
+  return invoke('bulk_edit_properties', { filter, changes, preview });
+}
+
+// Embed commands
+
+export async function resolveEmbeds(path: string, depth?: number): Promise<ResolvedEmbed[]> {
+  return invoke('resolve_embeds', { path, depth });
+}
+
+/** HTML for the preview: the saved note at `path`, or `content` as that note */
+export async function renderNoteHtml(path?: string, content?: string): Promise<string> {
+  return invoke('render_note_html', { path, content });
+}
+
+// Formatting commands
+
+/** Formats `content` if given, otherwise the note at `path` in place */
+export async function formatNote(target: { path?: string; content?: string }): Promise<string> {
+  return invoke('format_note', target);
+}
+
+// Smart folder commands
+
+export async function saveSmartFolder(
+  name: string,
+  filter: SmartFilter,
+  id?: number
+): Promise<SmartFolder> {
+  return invoke('save_smart_folder', { id, name, filter });
+}
+
+export async function getSmartFolder(id: number): Promise<SmartFolderContents> {
+  return invoke('get_smart_folder', { id });
+}
+
+export async function listSmartFolders(): Promise<SmartFolder[]> {
+  return invoke('list_smart_folders');
+}
+
+export async function deleteSmartFolder(id: number): Promise<void> {
+  return invoke('delete_smart_folder', { id });
+}
+
+// Saved view commands
+
+export async function saveView(name: string, spec: ViewSpec, id?: number): Promise<ListView> {
+  return invoke('save_view', { id, name, spec });
+}
+
+export async function listViews(): Promise<ListView[]> {
+  return invoke('list_views');
+}
+
+export async function getViewResults(id: number): Promise<ViewResults> {
+  return invoke('get_view_results', { id });
+}
+
+export async function deleteView(id: number): Promise<void> {
+  return invoke('delete_view', { id });
+}
+
+// Reminder commands
+
+export async function listUpcomingReminders(limit?: number): Promise<Reminder[]> {
+  return invoke('list_upcoming_reminders', { limit });
+}
+
+// Review queue commands
+
+export async function getReviewQueue(
+  strategy?: ReviewStrategy,
+  days?: number,
+  limit?: number
+): Promise<ReviewItem[]> {
+  return invoke('get_review_queue', { strategy, days, limit });
+}
+
+export async function markReviewed(path: string): Promise<void> {
+  return invoke('mark_reviewed', { path });
+}
+
+// Reading list commands
+
+export async function listReadingList(
+  status?: ReadingStatus,
+  domain?: string
+): Promise<ReadingItem[]> {
+  return invoke('list_reading_list', { status, domain });
+}
+
+export async function readingListDomains(): Promise<DomainCount[]> {
+  return invoke('reading_list_domains');
+}
+
+export async function setReadingStatus(id: number, status: ReadingStatus): Promise<ReadingItem> {
+  return invoke('set_reading_status', { id, status });
+}
+
+// Canvas commands
+
+export async function getCanvas(path: string): Promise<Canvas> {
+  return invoke('get_canvas', { path });
+}
+
+export async function saveCanvas(path: string, canvas: Canvas): Promise<void> {
+  return invoke('save_canvas', { path, canvas });
+}
+
+// Calendar commands
+
+/** Every day of a month; `month` is YYYY-MM */
+export async function getCalendarData(month: string): Promise<CalendarDay[]> {
+  return invoke('get_calendar_data', { month });
+}
+
+export async function getWritingProgress(): Promise<WritingProgress> {
+  return invoke('get_writing_progress');
+}
+
+// Board commands
+
+export async function getBoard(boardConfig: BoardConfig): Promise<Board> {
+  return invoke('get_board', { boardConfig });
+}
+
+export async function moveCard(path: string, newStatus: string, property?: string): Promise<NoteMeta> {
+  return invoke('move_card', { path, newStatus, property });
+}
+
+// Capture commands
+
+export async function quickCapture(capture: Capture): Promise<NoteMeta> {
+  return invoke('quick_capture', { capture });
+}
+
+export async function captureUrl(
+  url: string,
+  note?: string,
+  includeBody?: boolean
+): Promise<NoteMeta> {
+  return invoke('capture_url', { url, note, includeBody });
+}
+
+// Script plugin commands
+
+export async function listScriptPlugins(): Promise<ScriptPlugin[]> {
+  return invoke('list_script_plugins');
+}
+
+export async function runScriptCommand(name: string, path?: string): Promise<ScriptResult> {
+  return invoke('run_script_command', { name, path });
+}
+
+export async function importWithScript(name: string, file: string): Promise<ScriptResult> {
+  return invoke('import_with_script', { name, file });
+}
+
+// Template commands
+
+export async function listTemplates(): Promise<NoteTemplate[]> {
+  return invoke('list_templates');
+}
+
+/** `prompts` maps each prompt label to the user's answer */
+export async function createNoteFromTemplate(
+  title: string,
+  template: string,
+  prompts: Record<string, string>,
+  currentPath?: string
+): Promise<NoteMeta> {
+  return invoke('create_note_from_template', { title, template, prompts, currentPath });
+}
+
+// Citation commands
+
+export async function searchCitations(query: string, limit?: number): Promise<Citation[]> {
+  return invoke('search_citations', { query, limit });
+}
+
+export async function insertCitation(key: string, createNote?: boolean): Promise<InsertedCitation> {
+  return invoke('insert_citation', { key, createNote });
+}
+
+// Publishing commands
+
+/** Export the whole vault as a static website */
+export async function publishVault(config: SiteExportConfig): Promise<string> {
+  return invoke('publish_vault', { config });
+}
+
+/** Publish notes marked `publish: true` to the configured target */
+export async function publishNotes(): Promise<PublishReport> {
+  return invoke('publish_notes');
+}
+
+export async function publishStatus(): Promise<PublishStatusReport> {
+  return invoke('publish_status');
+}
+
+export interface ShareExport {
+  /** The note file, or a zip of it and its attachments */
+  path: string;
+  attachments: number;
+  missing_attachments: string[];
+}
+
+/** Export a cleaned copy of one note into destDir, for sending to others */
+export async function exportNoteShareable(
+  path: string,
+  destDir: string,
+  format?: 'markdown' | 'html',
+  keepProperties?: string[]
+): Promise<ShareExport> {
+  return invoke('export_note_shareable', { path, destDir, format, keepProperties });
+}
+
+export interface CollectionExport {
+  path: string;
+  /** Included notes, in document order */
+  notes: string[];
+}
+
+/**
+ * Combine a note or folder and the notes it links to (two links deep by
+ * default) into one printable document in destDir
+ */
+export async function exportCollection(
+  root: string,
+  destDir: string,
+  format?: 'html' | 'pdf',
+  maxDepth?: number
+): Promise<CollectionExport> {
+  return invoke('export_collection', { root, destDir, format, maxDepth });
+}
+
+// Export commands
+
+/** OPML outline of the folder tree or the nested tag hierarchy */
+export async function exportOpml(mode: 'folders' | 'tags'): Promise<string> {
+  return invoke('export_opml', { mode });
+}
+
+// Zettelkasten commands
+
+/** A fresh ID in the configured scheme (timestamp or ULID), for `[[id]]` links */
+export async function generateZettelId(): Promise<string> {
+  return invoke('generate_zettel_id');
+}
+
 // Graph commands
 
-export async function getGraphData(): Promise<GraphData> {
-  return invoke('get_graph_data');
+/** With includeTags, tags become nodes linked to the notes carrying them */
+export async function getGraphData(
+  includeTags?: boolean,
+  halfLifeDays?: number
+): Promise<GraphData> {
+  return invoke('get_graph_data', { includeTags, halfLifeDays });
+}
+
+/**
+ * The best-connected notes (500 by default) of the largest connected group,
+ * with the rest summarized in clusters to expand with `getLocalGraph`
+ */
+export async function getGraphOverview(maxNodes?: number): Promise<GraphOverview> {
+  return invoke('get_graph_overview', { maxNodes });
+}
+
+/** Notes matching a filter (tags, path globs, dates, ...) and the links among them */
+export async function getFilteredGraph(
+  filter: SmartFilter,
+  includeTags?: boolean,
+  halfLifeDays?: number
+): Promise<GraphData> {
+  return invoke('get_filtered_graph', { filter, includeTags, halfLifeDays });
+}
+
+/** Notes within `depth` links of a note (1 by default) and the links among them */
+export async function getLocalGraph(
+  path: string,
+  depth?: number,
+  includeTags?: boolean,
+  halfLifeDays?: number
+): Promise<GraphData> {
+  return invoke('get_local_graph', { path, depth, includeTags, halfLifeDays });
+}
+
+/**
+ * The shortest chain of links between two notes, both included, or null if
+ * nothing connects them. Links are followed either way unless `directed`.
+ */
+export async function findPath(
+  from: string,
+  to: string,
+  directed?: boolean
+): Promise<NoteMeta[] | null> {
+  return invoke('find_path', { from, to, directed });
+}
+
+export interface GraphRevision {
+  commit: string;
+  time: string;
+}
+
+export interface GraphComparison {
+  from: GraphRevision;
+  to: GraphRevision;
+  added_nodes: GraphNode[];
+  removed_nodes: GraphNode[];
+  added_edges: GraphEdge[];
+  removed_edges: GraphEdge[];
+  unchanged_nodes: number;
+}
+
+/** Graph changes between two git revisions (commits, branches or YYYY-MM-DD dates) */
+export async function compareGraph(
+  revA: string,
+  revB: string,
+  folder?: string
+): Promise<GraphComparison> {
+  return invoke('compare_graph', { revA, revB, folder });
 }
 
 // Tag commands
@@ -330,6 +1552,83 @@ export async function getNotesByTag(tag: string): Promise<NoteMeta[]> {
   return invoke('get_notes_by_tag', { tag });
 }
 
+export async function listTagTree(): Promise<TagTreeNode[]> {
+  return invoke('list_tag_tree');
+}
+
+/** Returns the notes that were rewritten */
+export async function renameTag(old: string, newName: string): Promise<string[]> {
+  return invoke('rename_tag', { old, new: newName });
+}
+
+/** Set a tag's color, icon and description; empty fields are cleared */
+export async function updateTagMeta(tag: string, meta: TagMeta): Promise<void> {
+  return invoke('update_tag_meta', { tag, meta });
+}
+
+export async function getTagOccurrences(tag: string): Promise<TagOccurrence[]> {
+  return invoke('get_tag_occurrences', { tag });
+}
+
+export async function listNotesByType(noteType: string): Promise<NoteMeta[]> {
+  return invoke('list_notes_by_type', { noteType });
+}
+
+// Breadcrumb commands
+
+export interface FolderCrumb {
+  name: string;
+  path: string;
+}
+
+export interface NoteCrumb {
+  path: string;
+  title: string;
+}
+
+export interface Breadcrumbs {
+  folders: FolderCrumb[];
+  parents: NoteCrumb[];
+  direct_parents: NoteCrumb[];
+}
+
+export async function getBreadcrumbs(path: string): Promise<Breadcrumbs> {
+  return invoke('get_breadcrumbs', { path });
+}
+
+// Map of content commands
+
+export type MocSource = { tag: string } | { folder: string };
+
+export async function generateMoc(source: MocSource, target: string): Promise<NoteMeta> {
+  return invoke('generate_moc', { source, target });
+}
+
+// Inbox commands
+
+export type InboxAction =
+  | { action: 'file'; folder: string }
+  | { action: 'tag'; tags: string[] }
+  | { action: 'merge'; into: string }
+  | { action: 'delete' };
+
+export interface InboxResult {
+  note: NoteMeta | null;
+  remaining: number;
+}
+
+export async function getInboxCount(): Promise<number> {
+  return invoke('get_inbox_count');
+}
+
+export async function getNextInboxItem(after?: string): Promise<NoteMeta | null> {
+  return invoke('get_next_inbox_item', { after });
+}
+
+export async function processInboxItem(path: string, action: InboxAction): Promise<InboxResult> {
+  return invoke('process_inbox_item', { path, action });
+}
+
 // Config commands
 
 export async function getConfig(): Promise<AppConfig> {
@@ -348,6 +1647,23 @@ export function onVaultEvent(callback: (event: VaultEvent) => void): Promise<Unl
   });
 }
 
+// Deep link commands
+
+/** A chronicle:// link; vault_path is set when its vault isn't the open one */
+export type DeepLinkRequest = { vault_path: string | null } & (
+  | { action: 'open'; vault: string | null; note: string | null }
+  | { action: 'new'; vault: string | null; title: string; content: string | null }
+);
+
+export async function takeDeepLinks(): Promise<DeepLinkRequest[]> {
+  return invoke('take_deep_links');
+}
+
+/** Fires when links are waiting to be taken with takeDeepLinks */
+export function onDeepLink(callback: () => void): Promise<UnlistenFn> {
+  return listen<null>('deep-link', () => callback());
+}
+
 // Sync commands
 
 export async function syncStatus(): Promise<SyncStatus> {
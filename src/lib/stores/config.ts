@@ -29,7 +29,7 @@ const defaultUiConfig: UiConfig = {
 };
 
 const defaultConfig: AppConfig = {
-  vault: { path: null },
+  vault: { path: null, follow_obsidian_settings: true },
   editor: defaultEditorConfig,
   graph: defaultGraphConfig,
   ui: defaultUiConfig,
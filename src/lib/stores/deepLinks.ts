@@ -0,0 +1,46 @@
+/**
+ * Deep links - follows chronicle:// links from other apps
+ */
+import * as api from '$lib/api/tauri';
+import type { DeepLinkRequest } from '$lib/api/tauri';
+import { openVault, refreshNotes } from './vault';
+import { openNote } from './editor';
+
+async function follow(link: DeepLinkRequest): Promise<void> {
+  if (link.vault_path) {
+    await openVault(link.vault_path);
+  }
+  if (link.action === 'open' && link.note) {
+    await openNote(link.note);
+  } else if (link.action === 'new') {
+    const meta = await api.createNote(link.title, link.content ?? undefined);
+    await refreshNotes();
+    await openNote(meta.path);
+  }
+}
+
+async function followPending(): Promise<void> {
+  for (const link of await api.takeDeepLinks()) {
+    try {
+      await follow(link);
+    } catch (e) {
+      console.error('Failed to follow deep link:', e);
+    }
+  }
+}
+
+let unlistenFn: (() => void) | null = null;
+
+// Follow the link the app was launched with, then any that arrive later
+export async function initDeepLinks(): Promise<void> {
+  if (unlistenFn) return;
+  unlistenFn = await api.onDeepLink(() => {
+    followPending();
+  });
+  await followPending();
+}
+
+export function cleanupDeepLinks(): void {
+  unlistenFn?.();
+  unlistenFn = null;
+}
@@ -13,6 +13,38 @@ export const currentNote = writable<Note | null>(null);
 export const backlinks = writable<Backlink[]>([]);
 export const isDirty = writable(false);
 export const isSaving = writable(false);
+// Set when the open note was restored from an unsaved draft
+export const restoredDraft = writable(false);
+
+// Unsaved edits are flushed to a draft this often, so a crash loses at
+// most a few seconds of typing
+const DRAFT_FLUSH_MS = 2000;
+let draftTimer: ReturnType<typeof setTimeout> | null = null;
+
+async function flushDraft(): Promise<void> {
+  const current = get(currentNote);
+  if (!current || !get(isDirty)) return;
+  try {
+    await api.saveDraft(current.path, current.content);
+  } catch (e) {
+    console.error('Failed to save draft:', e);
+  }
+}
+
+function scheduleDraftFlush(): void {
+  if (draftTimer) return;
+  draftTimer = setTimeout(() => {
+    draftTimer = null;
+    flushDraft();
+  }, DRAFT_FLUSH_MS);
+}
+
+function cancelDraftFlush(): void {
+  if (draftTimer) {
+    clearTimeout(draftTimer);
+    draftTimer = null;
+  }
+}
 
 // Derived
 export const currentPath = derived(currentNote, ($note) => $note?.path ?? null);
@@ -31,9 +63,23 @@ function toPluginNote(note: Note): PluginNote {
 // Actions
 
 export async function openNote(path: string): Promise<void> {
+  // Don't lose edits to the note being switched away from
+  cancelDraftFlush();
+  await flushDraft();
+  
   const note = await api.getNote(path);
-  currentNote.set(note);
-  isDirty.set(false);
+  
+  // Pick up edits that never made it into the note
+  const draft = await api.getDraft(path).catch(() => null);
+  if (draft && draft.content !== note.content) {
+    currentNote.set({ ...note, content: draft.content });
+    isDirty.set(true);
+    restoredDraft.set(true);
+  } else {
+    currentNote.set(note);
+    isDirty.set(false);
+    restoredDraft.set(false);
+  }
   
   // Load backlinks
   const links = await api.getBacklinks(path);
@@ -54,10 +100,12 @@ export async function saveCurrentNote(): Promise<void> {
   const current = get(currentNote);
   if (!current) return;
   
+  cancelDraftFlush();
   isSaving.set(true);
   try {
     await api.saveNote(current.path, current.content);
     isDirty.set(false);
+    restoredDraft.set(false);
     await refreshNotes();
     
     // Notify plugins
@@ -73,6 +121,7 @@ export function updateContent(content: string): void {
     isDirty.set(true);
     return { ...note, content };
   });
+  scheduleDraftFlush();
   
   // Notify plugins
   pluginEvents.emitNoteChange(content);
@@ -82,6 +131,7 @@ export async function deleteCurrentNote(): Promise<void> {
   const current = get(currentNote);
   if (!current) return;
   
+  cancelDraftFlush();
   await api.deleteNote(current.path);
   currentNote.set(null);
   backlinks.set([]);
@@ -90,6 +140,10 @@ export async function deleteCurrentNote(): Promise<void> {
 }
 
 export function closeNote(): void {
+  // Flush right away so closing never drops edits since the last flush
+  cancelDraftFlush();
+  flushDraft();
+  restoredDraft.set(false);
   currentNote.set(null);
   backlinks.set([]);
   isDirty.set(false);
@@ -98,6 +152,17 @@ export function closeNote(): void {
   pluginEvents.emitNoteClose();
 }
 
+export async function discardCurrentDraft(): Promise<void> {
+  const current = get(currentNote);
+  if (!current) return;
+  
+  cancelDraftFlush();
+  await api.discardDraft(current.path);
+  restoredDraft.set(false);
+  await reloadCurrentNote();
+  isDirty.set(false);
+}
+
 export async function reloadCurrentNote(): Promise<void> {
   const current = get(currentNote);
   if (!current) return;
@@ -7,3 +7,4 @@ export * from './search';
 export * from './graph';
 export * from './config';
 export * from './sync';
+export * from './deepLinks';
@@ -42,7 +42,7 @@ export async function refreshStatus(): Promise<void> {
     syncStatus.set(status);
     syncError.set(null);
   } catch (e) {
-    syncError.set(e instanceof Error ? e.message : String(e));
+    syncError.set(api.errorMessage(e));
   }
 }
 
@@ -55,7 +55,7 @@ export async function initSync(remoteUrl?: string): Promise<boolean> {
     syncStatus.set(status);
     return true;
   } catch (e) {
-    syncError.set(e instanceof Error ? e.message : String(e));
+    syncError.set(api.errorMessage(e));
     return false;
   } finally {
     isSyncing.set(false);
@@ -71,7 +71,7 @@ export async function push(): Promise<SyncResult | null> {
     await refreshStatus();
     return result;
   } catch (e) {
-    syncError.set(e instanceof Error ? e.message : String(e));
+    syncError.set(api.errorMessage(e));
     return null;
   } finally {
     isSyncing.set(false);
@@ -93,7 +93,7 @@ export async function pull(): Promise<SyncResult | null> {
     
     return result;
   } catch (e) {
-    syncError.set(e instanceof Error ? e.message : String(e));
+    syncError.set(api.errorMessage(e));
     return null;
   } finally {
     isSyncing.set(false);
@@ -121,7 +121,7 @@ export async function loadConflict(path: string): Promise<void> {
     const conflict = await api.syncGetConflict(path);
     currentConflict.set(conflict);
   } catch (e) {
-    syncError.set(e instanceof Error ? e.message : String(e));
+    syncError.set(api.errorMessage(e));
   }
 }
 
@@ -138,7 +138,7 @@ export async function resolveConflict(
     await refreshStatus();
     return result.success;
   } catch (e) {
-    syncError.set(e instanceof Error ? e.message : String(e));
+    syncError.set(api.errorMessage(e));
     return false;
   } finally {
     isSyncing.set(false);
@@ -10,6 +10,8 @@ export const vaultInfo = writable<VaultInfo | null>(null);
 export const notes = writable<NoteMeta[]>([]);
 export const isLoading = writable(false);
 export const error = writable<string | null>(null);
+// True while the open vault's folder is missing (e.g. unmounted drive)
+export const vaultUnavailable = writable(false);
 
 // Tag filter for file browser
 export const tagFilter = writable<string | null>(null);
@@ -59,19 +61,19 @@ export async function checkVaultStatus(): Promise<void> {
 
 // Actions
 
-export async function openVault(path: string): Promise<void> {
+export async function openVault(path: string, dbKey?: string): Promise<void> {
   isLoading.set(true);
   error.set(null);
   
   try {
-    const info = await api.openVault(path);
+    const info = await api.openVault(path, dbKey);
     vaultInfo.set(info);
     
     // Load notes
     const noteList = await api.listNotes();
     notes.set(noteList);
   } catch (e) {
-    error.set(e instanceof Error ? e.message : String(e));
+    error.set(api.errorMessage(e));
     throw e;
   } finally {
     isLoading.set(false);
@@ -81,6 +83,7 @@ export async function openVault(path: string): Promise<void> {
 export async function closeVault(): Promise<void> {
   await api.closeVault();
   vaultInfo.set(null);
+  vaultUnavailable.set(false);
   notes.set([]);
 }
 
@@ -110,6 +113,13 @@ export async function initVaultEvents(): Promise<void> {
       case 'note_renamed':
         refreshNotes();
         break;
+      case 'vault_unavailable':
+        vaultUnavailable.set(true);
+        break;
+      case 'vault_restored':
+        vaultUnavailable.set(false);
+        refreshNotes();
+        break;
     }
   });
   